@@ -3,11 +3,14 @@ mod util;
 mod viewer;
 
 use bevy::{prelude::*, winit::WinitSettings};
-use ui::ui_plugin;
+use ui::{cmd_args::CmdArgs, ui_plugin};
 use viewer::viewer_plugin;
 
 fn main() {
+    // read these first so that '--help' doesn't open a window
+    let cmd_args = CmdArgs::from_env();
     App::new()
+        .insert_resource(cmd_args)
         .insert_resource(Msaa::Sample4)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
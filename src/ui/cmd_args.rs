@@ -0,0 +1,252 @@
+use super::{
+    file_associations::launch_path,
+    settings::{AppSettings, SetupAppSettingsSet},
+    ui_state::KmpFilePath,
+};
+use crate::viewer::{
+    camera::{CameraMode, CameraModeChanged},
+    edit::select::Selected,
+    kmp::{
+        components::{
+            AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
+            KmpSelectablePoint, Object, RespawnPoint, RoutePoint, StartPoint,
+        },
+        convert::convert,
+        ordering::OrderId,
+        schema::field_docs_markdown,
+        sections::KmpEditMode,
+    },
+};
+use bevy::prelude::*;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+pub fn cmd_args_plugin(app: &mut App) {
+    app.add_systems(Startup, apply_startup_cmd_args.after(SetupAppSettingsSet))
+        .add_systems(
+            Update,
+            select_from_cmd_args.run_if(resource_exists::<KmpFilePath>.and_then(resource_exists::<PendingSelect>)),
+        );
+}
+
+const USAGE: &str = "\
+Usage: kmpeek [FILE] [OPTIONS]
+       kmpeek convert <INPUT> --to <OUTPUT> [--base <KMP>]
+
+Arguments:
+  [FILE]                 A KMP or KCL file to open
+
+Options:
+  --section <SECTION>    The section to edit, e.g. 'checkpoints' or 'enemy-paths'
+  --select <INDEX>       Select the point with this index in the section
+  --camera <CAMERA>      The camera to use: 'fly', 'orbit' or 'topdown'
+  --safe-mode            Ignore the stored tab layout and settings
+  --field-docs           Print documentation of every KMP field as markdown
+  --help                 Print this message
+
+Converting:
+  <INPUT> --to <OUTPUT>  Convert a KMP to a folder with a CSV file for each section that can be stored as CSV, or
+                         a folder of CSV files back to a KMP, without opening the editor
+  --base <KMP>           The KMP to take the other sections from when converting CSV files to a KMP";
+
+/// Arguments passed on the command line, e.g. `kmpeek course.kmp --section checkpoints --select 12 --camera topdown`
+#[derive(Resource, Default, Clone, Debug)]
+pub struct CmdArgs {
+    pub path: Option<PathBuf>,
+    pub section: Option<KmpEditMode>,
+    pub select: Option<u32>,
+    pub camera: Option<CameraMode>,
+    pub safe_mode: bool,
+    /// Print the KMP field docs and exit instead of opening the editor
+    pub field_docs: bool,
+    /// Convert between KMP and CSV and exit instead of opening the editor
+    pub convert: Option<ConvertArgs>,
+}
+
+/// Arguments for `kmpeek convert in.kmp --to out_dir`, or `kmpeek convert in_dir --to out.kmp --base in.kmp`
+#[derive(Clone, Debug)]
+pub struct ConvertArgs {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub base: Option<PathBuf>,
+}
+
+impl CmdArgs {
+    /// Read the command line arguments, printing the usage and exiting if `--help` is passed or they are invalid
+    pub fn from_env() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Some(args)) if args.field_docs => {
+                println!("{}", field_docs_markdown());
+                std::process::exit(0);
+            }
+            Ok(Some(CmdArgs {
+                convert: Some(convert_args),
+                ..
+            })) => match convert(&convert_args.input, &convert_args.output, convert_args.base.as_deref()) {
+                Ok(()) => std::process::exit(0),
+                Err(err) => {
+                    eprintln!("error: {err:#}");
+                    std::process::exit(1);
+                }
+            },
+            Ok(Some(args)) => args,
+            Ok(None) => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Err(err) => {
+                eprintln!("error: {err}\n\n{USAGE}");
+                std::process::exit(1);
+            }
+        }
+    }
+    /// Returns `None` if the usage should be printed
+    fn parse(args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = args.peekable();
+        if args.next_if_eq("convert").is_some() {
+            return Self::parse_convert(args);
+        }
+        let mut cmd_args = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("'{arg}' needs a value"));
+            match arg.as_str() {
+                "--help" | "-h" => return Ok(None),
+                "--safe-mode" => cmd_args.safe_mode = true,
+                "--field-docs" => cmd_args.field_docs = true,
+                "--section" => {
+                    let value = value()?;
+                    cmd_args.section = Some(
+                        KmpEditMode::iter()
+                            .find(|mode| normalize(&mode.to_string()) == normalize(&value))
+                            .ok_or_else(|| format!("'{value}' is not a section"))?,
+                    );
+                }
+                "--select" => {
+                    let value = value()?;
+                    cmd_args.select = Some(value.parse().map_err(|_| format!("'{value}' is not a valid index"))?);
+                }
+                "--camera" => {
+                    let value = value()?;
+                    cmd_args.camera = Some(match normalize(&value).as_str() {
+                        "fly" => CameraMode::Fly,
+                        "orbit" => CameraMode::Orbit,
+                        "topdown" => CameraMode::TopDown,
+                        _ => return Err(format!("'{value}' is not a camera")),
+                    });
+                }
+                // older versions of macOS pass the process serial number when the app is opened from Finder
+                _ if arg.starts_with("-psn_") => {}
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{arg}'")),
+                _ => cmd_args.path = Some(launch_path(&arg)),
+            }
+        }
+        if cmd_args.select.is_some() && cmd_args.section.is_none() {
+            return Err("'--select' needs '--section' to know which section to select from".into());
+        }
+        Ok(Some(cmd_args))
+    }
+    fn parse_convert(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let (mut input, mut output, mut base) = (None, None, None);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("'{arg}' needs a value"));
+            match arg.as_str() {
+                "--help" | "-h" => return Ok(None),
+                "--to" => output = Some(PathBuf::from(value()?)),
+                "--base" => base = Some(PathBuf::from(value()?)),
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{arg}'")),
+                _ if input.is_none() => input = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument '{arg}'")),
+            }
+        }
+        Ok(Some(Self {
+            convert: Some(ConvertArgs {
+                input: input.ok_or("'convert' needs a file or folder to convert")?,
+                output: output.ok_or("'convert' needs '--to' to know where to write to")?,
+                base,
+            }),
+            ..default()
+        }))
+    }
+}
+
+// so that e.g. 'Enemy Paths', 'enemy-paths' and 'enemypaths' all match
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// The point to select once the KMP has loaded
+#[derive(Resource)]
+struct PendingSelect {
+    index: u32,
+    frames_waited: u8,
+}
+
+fn apply_startup_cmd_args(
+    mut commands: Commands,
+    cmd_args: Res<CmdArgs>,
+    mut mode: ResMut<KmpEditMode>,
+    mut settings: ResMut<AppSettings>,
+    mut ev_camera_mode_changed: EventWriter<CameraModeChanged>,
+) {
+    if let Some(section) = cmd_args.section {
+        *mode = section;
+    }
+    if let Some(index) = cmd_args.select {
+        commands.insert_resource(PendingSelect {
+            index,
+            frames_waited: 0,
+        });
+    }
+    if let Some(camera) = cmd_args.camera {
+        settings.camera.mode = camera;
+        ev_camera_mode_changed.send(CameraModeChanged(camera));
+    }
+}
+
+fn select_from_cmd_args(world: &mut World) {
+    // the points are hidden when they are spawned, so refresh the visibilities to show the current section,
+    // then wait a couple of frames so that changing the mode doesn't deselect the point straight after
+    let mut pending = world.resource_mut::<PendingSelect>();
+    pending.frames_waited += 1;
+    let (index, frames_waited) = (pending.index, pending.frames_waited);
+    if frames_waited == 1 {
+        world.resource_mut::<KmpEditMode>().set_changed();
+    }
+    if frames_waited < 3 {
+        return;
+    }
+
+    let mode = *world.resource::<KmpEditMode>();
+    let found = match mode {
+        KmpEditMode::StartPoints => select_point::<StartPoint>(world, index),
+        KmpEditMode::EnemyPaths => select_point::<EnemyPathPoint>(world, index),
+        KmpEditMode::ItemPaths => select_point::<ItemPathPoint>(world, index),
+        KmpEditMode::Checkpoints => select_point::<Checkpoint>(world, index),
+        KmpEditMode::RespawnPoints => select_point::<RespawnPoint>(world, index),
+        KmpEditMode::Objects => select_point::<Object>(world, index),
+        KmpEditMode::Routes => select_point::<RoutePoint>(world, index),
+        KmpEditMode::Areas => select_point::<AreaPoint>(world, index),
+        KmpEditMode::Cameras => select_point::<KmpCamera>(world, index),
+        KmpEditMode::CannonPoints => select_point::<CannonPoint>(world, index),
+        KmpEditMode::BattleFinishPoints => select_point::<BattleFinishPoint>(world, index),
+        KmpEditMode::TrackInfo => None,
+    };
+    // keep trying until the point's section is shown, as anything hidden gets deselected
+    if found != Some(false) || frames_waited > 60 {
+        world.remove_resource::<PendingSelect>();
+    }
+}
+
+/// Returns `None` if there is no point with that index, or whether it could be selected yet
+fn select_point<T: Component>(world: &mut World, index: u32) -> Option<bool> {
+    let mut q = world.query_filtered::<(Entity, &OrderId, &Visibility), (With<T>, With<KmpSelectablePoint>)>();
+    let (e, _, visibility) = q.iter(world).find(|x| x.1 .0 == index)?;
+    if visibility != Visibility::Visible {
+        return Some(false);
+    }
+    world.entity_mut(e).insert(Selected);
+    Some(true)
+}
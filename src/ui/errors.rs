@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+pub fn errors_plugin(app: &mut App) {
+    app.init_resource::<ErrorMessages>();
+}
+
+/// Errors from things the user asked for that couldn't be done (e.g. a file that couldn't be read or written), kept
+/// until the user dismisses them from the window in the menu bar
+#[derive(Resource, Default)]
+pub struct ErrorMessages(pub Vec<String>);
+
+impl ErrorMessages {
+    pub fn push(&mut self, err: anyhow::Error) {
+        error!("{err:?}");
+        // '#' includes the context of the error, e.g. "couldn't open course.kmp: No such file or directory"
+        self.0.push(format!("{err:#}"));
+    }
+    /// For when there's no system to pipe a result into [`show_errors`] from
+    pub fn report(world: &mut World, result: anyhow::Result<()>) {
+        if let Err(err) = result {
+            world.resource_mut::<ErrorMessages>().push(err);
+        }
+    }
+}
+
+/// Pipe systems that can fail into this so that the user can see what went wrong
+pub fn show_errors(In(result): In<anyhow::Result<()>>, mut errors: ResMut<ErrorMessages>) {
+    if let Err(err) = result {
+        errors.push(err);
+    }
+}
@@ -0,0 +1,196 @@
+use super::{
+    settings::AppSettings,
+    update_ui::{KclFileSelected, KmpFileSelected},
+};
+use crate::viewer::kmp::project::companion_kcl;
+use bevy::prelude::*;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+pub fn file_associations_plugin(app: &mut App) {
+    app.init_resource::<PendingOpenFiles>()
+        .add_systems(Update, (queue_dropped_files, open_pending_files).chain());
+}
+
+/// Files waiting to be opened, from the command line or dropped onto the window. They are opened one at a time,
+/// as a KMP is only read from the first open event there is.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PendingOpenFiles(pub VecDeque<PathBuf>);
+
+fn queue_dropped_files(mut ev_drag_and_drop: EventReader<FileDragAndDrop>, mut pending: ResMut<PendingOpenFiles>) {
+    for ev in ev_drag_and_drop.read() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = ev {
+            pending.push_back(path_buf.clone());
+        }
+    }
+}
+
+fn open_pending_files(
+    mut pending: ResMut<PendingOpenFiles>,
+    mut ev_kmp_file_selected: ResMut<Events<KmpFileSelected>>,
+    mut ev_kcl_file_selected: ResMut<Events<KclFileSelected>>,
+    settings: Res<AppSettings>,
+) {
+    // wait until the last KMP has been opened and its event has gone
+    if !ev_kmp_file_selected.is_empty() {
+        return;
+    }
+    while let Some(path) = pending.pop_front() {
+        if open_file(&path, &settings, &mut ev_kmp_file_selected, &mut ev_kcl_file_selected) {
+            return;
+        }
+    }
+}
+
+// send the events to open a KMP, KCL or course archive, returning whether a KMP is being opened
+fn open_file(
+    path: &Path,
+    settings: &AppSettings,
+    ev_kmp_file_selected: &mut Events<KmpFileSelected>,
+    ev_kcl_file_selected: &mut Events<KclFileSelected>,
+) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+    match path.extension().and_then(|x| x.to_str()) {
+        Some("kmp") => {
+            ev_kmp_file_selected.send(KmpFileSelected(path.into()));
+            // if there is a course.kcl in the same directory (or the KCL of the project) and the setting to open it is
+            // set, open the kcl as well
+            let course_kcl_path = companion_kcl(path);
+            if settings.open_course_kcl_in_dir && course_kcl_path.exists() {
+                ev_kcl_file_selected.send(KclFileSelected(course_kcl_path));
+            }
+            true
+        }
+        Some("kcl") => {
+            ev_kcl_file_selected.send(KclFileSelected(path.into()));
+            false
+        }
+        // the KMP and KCL are both read out of the archive
+        Some("szs") => {
+            ev_kmp_file_selected.send(KmpFileSelected(path.into()));
+            ev_kcl_file_selected.send(KclFileSelected(path.into()));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Turn a file argument into an absolute path. File managers may pass `file://` URLs instead of paths, and the
+/// working directory might not be the file's directory, so relative paths are made absolute straight away.
+pub fn launch_path(arg: &str) -> PathBuf {
+    let path = match arg.strip_prefix("file://") {
+        Some(url) => PathBuf::from(percent_decode(url.strip_prefix("localhost").unwrap_or(url))),
+        None => PathBuf::from(arg),
+    };
+    std::path::absolute(&path).unwrap_or(path)
+}
+
+// decode the escaped characters (like '%20' for a space) in a file URL
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|x| std::str::from_utf8(x).ok());
+        match hex.and_then(|x| u8::from_str_radix(x, 16).ok()) {
+            Some(byte) if bytes[i] == b'%' => {
+                out.push(byte);
+                i += 3;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Make KMPeek the program that .kmp files open with, for the current user
+#[cfg(target_os = "windows")]
+pub fn register_kmp_handler() -> anyhow::Result<()> {
+    use anyhow::{bail, Context};
+    use std::process::Command;
+    let exe = std::env::current_exe().context("could not find the KMPeek executable")?;
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    let keys = [
+        (r"HKCU\Software\Classes\.kmp", "kmpeek.kmp"),
+        (r"HKCU\Software\Classes\kmpeek.kmp", "Mario Kart Wii KMP File"),
+        (r"HKCU\Software\Classes\kmpeek.kmp\shell\open\command", command.as_str()),
+    ];
+    for (key, value) in keys {
+        let status = Command::new("reg")
+            .args(["add", key, "/ve", "/d", value, "/f"])
+            .status()
+            .context("could not run reg")?;
+        if !status.success() {
+            bail!("could not write registry key '{key}'");
+        }
+    }
+    Ok(())
+}
+
+/// Make KMPeek the program that .kmp files open with, for the current user
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn register_kmp_handler() -> anyhow::Result<()> {
+    use anyhow::{bail, Context};
+    use std::{fs, process::Command};
+    const MIME_TYPE: &str = "application/x-mkw-kmp";
+
+    let exe = std::env::current_exe().context("could not find the KMPeek executable")?;
+    let data_dir = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            PathBuf::from(std::env::var_os("HOME").context("could not find the home directory")?).join(".local/share")
+        }
+    };
+
+    // tell the desktop that .kmp files are their own type, and that KMPeek can open that type
+    let mime_dir = data_dir.join("mime");
+    fs::create_dir_all(mime_dir.join("packages"))?;
+    fs::write(
+        mime_dir.join("packages/kmpeek.xml"),
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <mime-info xmlns=\"http://www.freedesktop.org/standards/shared-mime-info\">\n  \
+            <mime-type type=\"{MIME_TYPE}\">\n    <comment>Mario Kart Wii KMP File</comment>\n    \
+            <glob pattern=\"*.kmp\"/>\n  </mime-type>\n</mime-info>\n"
+        ),
+    )
+    .context("could not write the mime type")?;
+    let apps_dir = data_dir.join("applications");
+    fs::create_dir_all(&apps_dir)?;
+    fs::write(
+        apps_dir.join("kmpeek.desktop"),
+        format!(
+            "[Desktop Entry]\nType=Application\nName=KMPeek\nExec=\"{}\" %f\nMimeType={MIME_TYPE};\nTerminal=false\n",
+            exe.display()
+        ),
+    )
+    .context("could not write the desktop entry")?;
+
+    // these only refresh caches, so the association still works (after logging in again) if they aren't installed
+    let _ = Command::new("update-mime-database").arg(&mime_dir).status();
+    let _ = Command::new("update-desktop-database").arg(&apps_dir).status();
+    let status = Command::new("xdg-mime")
+        .args(["default", "kmpeek.desktop", MIME_TYPE])
+        .status()
+        .context("could not run xdg-mime")?;
+    if !status.success() {
+        bail!("xdg-mime could not set KMPeek as the default for .kmp files");
+    }
+    Ok(())
+}
+
+/// On macOS the file types an app opens are declared in its bundle's Info.plist, which can't be changed while it's
+/// running, so this only explains how to do it
+#[cfg(target_os = "macos")]
+pub fn register_kmp_handler() -> anyhow::Result<()> {
+    anyhow::bail!(
+        "on macOS, choose 'Get Info' on a .kmp file in Finder, pick KMPeek under 'Open with' and click 'Change All'"
+    )
+}
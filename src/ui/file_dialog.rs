@@ -1,4 +1,5 @@
 use super::util::get_egui_ctx;
+use crate::viewer::kmp::{project::ProjectRoot, sections::KmpEditMode};
 use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_egui::egui::Align2;
 use egui_file::FileDialog;
@@ -16,8 +17,22 @@ pub enum DialogType {
     OpenKmpKcl,
     ExportSettings,
     ImportSettings,
-    // ExportCsv,
-    // ImportCsv,
+    ImportDonorKmp,
+    ImportSectionsKmp,
+    OpenReferenceKmp,
+    ExportReachableArea,
+    ExportSnapshot,
+    ExportCsv(KmpEditMode),
+    ImportCsv(KmpEditMode),
+    ExportWorkbook,
+    ImportWorkbook,
+    OpenCustomRules,
+    ExportCameras,
+    /// Whether to line the cameras up with the start line
+    ImportCameras(bool),
+    ChooseProjectRoot,
+    ChooseProjectKcl,
+    ChooseProjectExports,
 }
 
 #[derive(Event)]
@@ -71,6 +86,7 @@ pub fn show_file_dialog(world: &mut World) {
 #[derive(SystemParam)]
 pub struct FileDialogManager<'w> {
     file_dialog: ResMut<'w, FileDialogRes>,
+    project: Res<'w, ProjectRoot>,
 }
 
 impl FileDialogManager<'_> {
@@ -81,13 +97,13 @@ impl FileDialogManager<'_> {
         self.file_dialog.0 = None;
     }
     pub fn open_kmp_kcl(&mut self) {
-        let mut dialog = FileDialog::open_file(None)
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
             .default_size(FILE_DIALOG_SIZE)
             .anchor(Align2::CENTER_CENTER, [0., 0.])
             .show_files_filter(Box::new(move |path| {
                 if let Some(os_str) = path.extension() {
                     if let Some(str) = os_str.to_str() {
-                        return ["kcl", "kmp"].contains(&str);
+                        return ["kcl", "kmp", "szs"].contains(&str);
                     }
                 }
                 false
@@ -95,6 +111,69 @@ impl FileDialogManager<'_> {
         dialog.open();
         self.file_dialog.0 = Some((dialog, DialogType::OpenKmpKcl));
     }
+    pub fn import_donor_kmp(&mut self) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "kmp";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ImportDonorKmp));
+    }
+    pub fn import_sections_kmp(&mut self) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "kmp";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ImportSectionsKmp));
+    }
+    pub fn open_reference_kmp(&mut self) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "kmp";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::OpenReferenceKmp));
+    }
+    pub fn export_reachable_area(&mut self) {
+        let mut dialog = FileDialog::save_file(self.project.export_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .default_filename("reachable_area.obj");
+        dialog.open();
+
+        self.file_dialog.0 = Some((dialog, DialogType::ExportReachableArea));
+    }
+    pub fn export_snapshot(&mut self) {
+        let mut dialog = FileDialog::save_file(self.project.export_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .default_filename("minimap.png");
+        dialog.open();
+
+        self.file_dialog.0 = Some((dialog, DialogType::ExportSnapshot));
+    }
     pub fn import_settings(&mut self) {
         let mut dialog = FileDialog::open_file(None)
             .default_size(FILE_DIALOG_SIZE)
@@ -119,28 +198,120 @@ impl FileDialogManager<'_> {
 
         self.file_dialog.0 = Some((dialog, DialogType::ExportSettings));
     }
-    // pub fn export_csv(&mut self, name: impl Into<String>) {
-    //     let mut dialog = FileDialog::save_file(None)
-    //         .default_size(FILE_DIALOG_SIZE)
-    //         .anchor(Align2::CENTER_CENTER, [0., 0.])
-    //         .default_filename(name.into());
-    //     dialog.open();
-
-    //     self.file_dialog.0 = Some((dialog, DialogType::ExportCsv));
-    // }
-    // pub fn import_csv(&mut self) {
-    //     let mut dialog = FileDialog::open_file(None)
-    //         .default_size(FILE_DIALOG_SIZE)
-    //         .anchor(Align2::CENTER_CENTER, [0., 0.])
-    //         .show_files_filter(Box::new(|path| {
-    //             if let Some(os_str) = path.extension() {
-    //                 if let Some(str) = os_str.to_str() {
-    //                     return str == "csv";
-    //                 }
-    //             }
-    //             false
-    //         }));
-    //     dialog.open();
-    //     self.file_dialog.0 = Some((dialog, DialogType::ImportCsv));
-    // }
+    pub fn export_csv(&mut self, mode: KmpEditMode, name: impl Into<String>) {
+        let mut dialog = FileDialog::save_file(self.project.export_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .default_filename(name.into());
+        dialog.open();
+
+        self.file_dialog.0 = Some((dialog, DialogType::ExportCsv(mode)));
+    }
+    pub fn import_csv(&mut self, mode: KmpEditMode) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "csv";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ImportCsv(mode)));
+    }
+    pub fn export_workbook(&mut self, name: impl Into<String>) {
+        let mut dialog = FileDialog::save_file(self.project.export_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .default_filename(name.into());
+        dialog.open();
+
+        self.file_dialog.0 = Some((dialog, DialogType::ExportWorkbook));
+    }
+    pub fn import_workbook(&mut self) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return ["xlsx", "ods"].contains(&str);
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ImportWorkbook));
+    }
+    pub fn export_cameras(&mut self, name: impl Into<String>) {
+        let mut dialog = FileDialog::save_file(self.project.export_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .default_filename(name.into());
+        dialog.open();
+
+        self.file_dialog.0 = Some((dialog, DialogType::ExportCameras));
+    }
+    pub fn import_cameras(&mut self, relative_to_start: bool) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "kmp";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ImportCameras(relative_to_start)));
+    }
+    pub fn open_custom_rules(&mut self) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "toml";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::OpenCustomRules));
+    }
+    pub fn choose_project_root(&mut self) {
+        let mut dialog = FileDialog::select_folder(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.]);
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ChooseProjectRoot));
+    }
+    pub fn choose_project_kcl(&mut self) {
+        let mut dialog = FileDialog::open_file(self.project.dialog_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.])
+            .show_files_filter(Box::new(|path| {
+                if let Some(os_str) = path.extension() {
+                    if let Some(str) = os_str.to_str() {
+                        return str == "kcl";
+                    }
+                }
+                false
+            }));
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ChooseProjectKcl));
+    }
+    pub fn choose_project_exports(&mut self) {
+        let mut dialog = FileDialog::select_folder(self.project.export_dir())
+            .default_size(FILE_DIALOG_SIZE)
+            .anchor(Align2::CENTER_CENTER, [0., 0.]);
+        dialog.open();
+        self.file_dialog.0 = Some((dialog, DialogType::ChooseProjectExports));
+    }
 }
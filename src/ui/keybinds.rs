@@ -1,23 +1,423 @@
-use crate::viewer::edit::EditMode;
-
-use super::file_dialog::FileDialogManager;
+use super::{
+    file_dialog::FileDialogManager,
+    settings::{AppSettings, SetupAppSettingsSet},
+    ui_state::{KmpFilePath, PkvWriteQueue, SafeMode, SwitchWorkspace},
+};
+use crate::viewer::{
+    camera::{CameraMode, CameraModeChanged, BOOKMARK_SLOTS},
+    edit::{
+        create_delete::DuplicateSelected,
+        select::PickMode,
+        undo::{Redo, Undo},
+        EditMode,
+    },
+    kmp::SaveFile,
+};
 use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
 
 pub fn keybinds_plugin(app: &mut App) {
-    app.add_systems(Update, keybinds);
+    app.add_systems(Startup, setup_keybinds.in_set(SetupAppSettingsSet))
+        .add_systems(Update, keybinds.run_if(resource_exists::<Keybinds>))
+        // recorded at the end of the frame, so the keys pressed don't also trigger whatever they were bound to before
+        .add_systems(Last, record_keybind.run_if(resource_exists::<Keybinds>));
+}
+
+/// Something that can be done with a keyboard shortcut
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, EnumIter, Display)]
+pub enum KeyAction {
+    #[strum(serialize = "Open File")]
+    Open,
+    Save,
+    Undo,
+    Redo,
+    Duplicate,
+    #[strum(serialize = "Delete Selected")]
+    Delete,
+    #[strum(serialize = "Select All")]
+    SelectAll,
+    #[strum(serialize = "Unlink Points")]
+    UnlinkPoints,
+    #[strum(serialize = "Cycle Edit Mode")]
+    CycleEditMode,
+    #[strum(serialize = "Cycle Pick Mode")]
+    CyclePickMode,
+    #[strum(serialize = "Cycle Camera Mode")]
+    CycleCameraMode,
+    #[strum(serialize = "Go Home")]
+    GoHome,
+    #[strum(serialize = "Set Home")]
+    SetHome,
+    #[strum(serialize = "Measure Tool")]
+    Measure,
+    #[strum(serialize = "Fly Forward")]
+    FlyForward,
+    #[strum(serialize = "Fly Backward")]
+    FlyBackward,
+    #[strum(serialize = "Fly Left")]
+    FlyLeft,
+    #[strum(serialize = "Fly Right")]
+    FlyRight,
+    #[strum(serialize = "Fly Up")]
+    FlyUp,
+    #[strum(serialize = "Fly Down")]
+    FlyDown,
+    #[strum(serialize = "Nudge Forward")]
+    NudgeForward,
+    #[strum(serialize = "Nudge Backward")]
+    NudgeBackward,
+    #[strum(serialize = "Nudge Left")]
+    NudgeLeft,
+    #[strum(serialize = "Nudge Right")]
+    NudgeRight,
+    #[strum(serialize = "Nudge Up")]
+    NudgeUp,
+    #[strum(serialize = "Nudge Down")]
+    NudgeDown,
+    #[strum(serialize = "Go To Bookmark 1")]
+    GoToBookmark1,
+    #[strum(serialize = "Go To Bookmark 2")]
+    GoToBookmark2,
+    #[strum(serialize = "Go To Bookmark 3")]
+    GoToBookmark3,
+    #[strum(serialize = "Go To Bookmark 4")]
+    GoToBookmark4,
+    #[strum(serialize = "Go To Bookmark 5")]
+    GoToBookmark5,
+    #[strum(serialize = "Go To Bookmark 6")]
+    GoToBookmark6,
+    #[strum(serialize = "Go To Bookmark 7")]
+    GoToBookmark7,
+    #[strum(serialize = "Go To Bookmark 8")]
+    GoToBookmark8,
+    #[strum(serialize = "Go To Bookmark 9")]
+    GoToBookmark9,
+    #[strum(serialize = "Save Bookmark 1")]
+    SetBookmark1,
+    #[strum(serialize = "Save Bookmark 2")]
+    SetBookmark2,
+    #[strum(serialize = "Save Bookmark 3")]
+    SetBookmark3,
+    #[strum(serialize = "Save Bookmark 4")]
+    SetBookmark4,
+    #[strum(serialize = "Save Bookmark 5")]
+    SetBookmark5,
+    #[strum(serialize = "Save Bookmark 6")]
+    SetBookmark6,
+    #[strum(serialize = "Save Bookmark 7")]
+    SetBookmark7,
+    #[strum(serialize = "Save Bookmark 8")]
+    SetBookmark8,
+    #[strum(serialize = "Save Bookmark 9")]
+    SetBookmark9,
+}
+impl KeyAction {
+    /// The action which goes to each camera bookmark slot
+    pub const GO_TO_BOOKMARK: [Self; BOOKMARK_SLOTS] = [
+        Self::GoToBookmark1,
+        Self::GoToBookmark2,
+        Self::GoToBookmark3,
+        Self::GoToBookmark4,
+        Self::GoToBookmark5,
+        Self::GoToBookmark6,
+        Self::GoToBookmark7,
+        Self::GoToBookmark8,
+        Self::GoToBookmark9,
+    ];
+    /// The action which saves the current view into each camera bookmark slot
+    pub const SET_BOOKMARK: [Self; BOOKMARK_SLOTS] = [
+        Self::SetBookmark1,
+        Self::SetBookmark2,
+        Self::SetBookmark3,
+        Self::SetBookmark4,
+        Self::SetBookmark5,
+        Self::SetBookmark6,
+        Self::SetBookmark7,
+        Self::SetBookmark8,
+        Self::SetBookmark9,
+    ];
+    fn default_keybinds(self) -> Vec<Keybind> {
+        use KeyCode::*;
+        match self {
+            Self::Open => vec![Keybind::ctrl(KeyO)],
+            Self::Save => vec![Keybind::ctrl(KeyS)],
+            Self::Undo => vec![Keybind::ctrl(KeyZ)],
+            Self::Redo => vec![Keybind::ctrl(KeyZ).with_shift(), Keybind::ctrl(KeyY)],
+            Self::Duplicate => vec![Keybind::ctrl(KeyD)],
+            Self::Delete => vec![Keybind::key(Delete), Keybind::key(Backspace)],
+            Self::SelectAll => vec![Keybind::ctrl(KeyA)],
+            Self::UnlinkPoints => vec![Keybind::key(KeyU)],
+            Self::CycleEditMode => vec![Keybind::key(KeyG)],
+            Self::CyclePickMode => vec![Keybind::key(KeyP)],
+            Self::CycleCameraMode => vec![Keybind::key(KeyC)],
+            Self::GoHome => vec![Keybind::key(Home)],
+            Self::SetHome => vec![Keybind::ctrl(Home)],
+            Self::Measure => vec![Keybind::key(KeyM)],
+            // the arrow keys and PgUp/PgDn fly the camera, so need Ctrl held to nudge the selection instead
+            Self::FlyForward => vec![Keybind::key(KeyW), Keybind::key(ArrowUp)],
+            Self::FlyBackward => vec![Keybind::key(KeyS), Keybind::key(ArrowDown)],
+            Self::FlyLeft => vec![Keybind::key(KeyA), Keybind::key(ArrowLeft)],
+            Self::FlyRight => vec![Keybind::key(KeyD), Keybind::key(ArrowRight)],
+            Self::FlyUp => vec![Keybind::key(KeyE), Keybind::key(PageUp)],
+            Self::FlyDown => vec![Keybind::key(KeyQ), Keybind::key(PageDown)],
+            Self::NudgeForward => vec![Keybind::ctrl(ArrowUp)],
+            Self::NudgeBackward => vec![Keybind::ctrl(ArrowDown)],
+            Self::NudgeLeft => vec![Keybind::ctrl(ArrowLeft)],
+            Self::NudgeRight => vec![Keybind::ctrl(ArrowRight)],
+            Self::NudgeUp => vec![Keybind::ctrl(PageUp)],
+            Self::NudgeDown => vec![Keybind::ctrl(PageDown)],
+            // the same number keys switch workspaces with Alt held
+            Self::GoToBookmark1 => vec![Keybind::key(Digit1)],
+            Self::GoToBookmark2 => vec![Keybind::key(Digit2)],
+            Self::GoToBookmark3 => vec![Keybind::key(Digit3)],
+            Self::GoToBookmark4 => vec![Keybind::key(Digit4)],
+            Self::GoToBookmark5 => vec![Keybind::key(Digit5)],
+            Self::GoToBookmark6 => vec![Keybind::key(Digit6)],
+            Self::GoToBookmark7 => vec![Keybind::key(Digit7)],
+            Self::GoToBookmark8 => vec![Keybind::key(Digit8)],
+            Self::GoToBookmark9 => vec![Keybind::key(Digit9)],
+            Self::SetBookmark1 => vec![Keybind::ctrl(Digit1)],
+            Self::SetBookmark2 => vec![Keybind::ctrl(Digit2)],
+            Self::SetBookmark3 => vec![Keybind::ctrl(Digit3)],
+            Self::SetBookmark4 => vec![Keybind::ctrl(Digit4)],
+            Self::SetBookmark5 => vec![Keybind::ctrl(Digit5)],
+            Self::SetBookmark6 => vec![Keybind::ctrl(Digit6)],
+            Self::SetBookmark7 => vec![Keybind::ctrl(Digit7)],
+            Self::SetBookmark8 => vec![Keybind::ctrl(Digit8)],
+            Self::SetBookmark9 => vec![Keybind::ctrl(Digit9)],
+        }
+    }
+}
+
+/// A key along with the modifiers that have to be held with it. Ctrl also means Cmd on macOS.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Keybind {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: KeyCode,
+}
+impl Keybind {
+    pub fn key(key: KeyCode) -> Self {
+        Self {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            key,
+        }
+    }
+    pub fn ctrl(key: KeyCode) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::key(key)
+        }
+    }
+    pub fn with_shift(self) -> Self {
+        Self { shift: true, ..self }
+    }
+    /// Whether the key was just pressed with exactly these modifiers held, so that for example Ctrl+Z doesn't also
+    /// trigger when Ctrl+Shift+Z is pressed
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.key)
+            && self.ctrl == keys.control_or_super_pressed()
+            && self.shift == keys.shift_pressed()
+            && self.alt == keys.alt_pressed()
+    }
+    /// Like [`Keybind::just_pressed`], but shift can be held as well even if it isn't part of the keybind, for actions
+    /// which shift makes go further
+    pub fn just_pressed_or_shifted(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.key) && self.modifiers_pressed_or_shifted(keys)
+    }
+    /// Whether the key is being held down, with shift allowed in the same way as [`Keybind::just_pressed_or_shifted`]
+    pub fn held(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.pressed(self.key) && self.modifiers_pressed_or_shifted(keys)
+    }
+    fn modifiers_pressed_or_shifted(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        self.ctrl == keys.control_or_super_pressed()
+            && (!self.shift || keys.shift_pressed())
+            && self.alt == keys.alt_pressed()
+    }
+    /// The keybind currently being pressed, if a key other than a modifier was just pressed
+    pub fn from_pressed(keys: &ButtonInput<KeyCode>) -> Option<Self> {
+        let key = keys.get_just_pressed().find(|key| !is_modifier(**key))?;
+        Some(Self {
+            ctrl: keys.control_or_super_pressed(),
+            shift: keys.shift_pressed(),
+            alt: keys.alt_pressed(),
+            key: *key,
+        })
+    }
+}
+impl std::fmt::Display for Keybind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            let ctrl = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+            write!(f, "{ctrl}+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        // 'KeyZ' and 'Digit1' read better as 'Z' and '1'
+        let key = format!("{:?}", self.key);
+        let key = key.strip_prefix("Key").or(key.strip_prefix("Digit")).unwrap_or(&key);
+        write!(f, "{key}")
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    use KeyCode::*;
+    matches!(
+        key,
+        ControlLeft | ControlRight | ShiftLeft | ShiftRight | AltLeft | AltRight | SuperLeft | SuperRight
+    )
+}
+
+/// The keyboard shortcuts for each action, which can be changed in the settings tab. Stored separately from the
+/// settings, under the 'keybinds' key.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct Keybinds {
+    binds: BTreeMap<KeyAction, Vec<Keybind>>,
+    /// The action and index of the keybind that the next key pressed will be recorded into. Shortcuts don't do
+    /// anything while a keybind is being recorded.
+    #[serde(skip)]
+    pub recording: Option<(KeyAction, usize)>,
+    /// Shortcuts don't do anything while this is set, e.g. while picking a point to link to
+    #[serde(skip)]
+    pub suspended: bool,
+}
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            binds: KeyAction::iter().map(|x| (x, x.default_keybinds())).collect(),
+            recording: None,
+            suspended: false,
+        }
+    }
+}
+impl Keybinds {
+    pub fn get(&self, action: KeyAction) -> &[Keybind] {
+        self.binds.get(&action).map_or(&[], |x| x.as_slice())
+    }
+    pub fn get_mut(&mut self, action: KeyAction) -> &mut Vec<Keybind> {
+        self.binds.entry(action).or_default()
+    }
+    pub fn just_pressed(&self, action: KeyAction, keys: &ButtonInput<KeyCode>) -> bool {
+        self.recording.is_none() && !self.suspended && self.get(action).iter().any(|x| x.just_pressed(keys))
+    }
+    /// See [`Keybind::just_pressed_or_shifted`]
+    pub fn just_pressed_or_shifted(&self, action: KeyAction, keys: &ButtonInput<KeyCode>) -> bool {
+        self.recording.is_none() && !self.suspended && self.get(action).iter().any(|x| x.just_pressed_or_shifted(keys))
+    }
+    /// Whether an action which happens for as long as its key is held, like flying the camera, is being done. Unlike
+    /// other shortcuts these still work while shortcuts are suspended, so the camera can be moved while picking a
+    /// point.
+    pub fn held(&self, action: KeyAction, keys: &ButtonInput<KeyCode>) -> bool {
+        self.recording.is_none() && self.get(action).iter().any(|x| x.held(keys))
+    }
+    /// The first keybind for this action, for showing next to buttons that do the same thing
+    pub fn text(&self, action: KeyAction) -> String {
+        self.get(action).first().map(|x| x.to_string()).unwrap_or_default()
+    }
+    /// The other things that are done when this keybind is pressed, including switching workspaces
+    pub fn conflicts(&self, action: KeyAction, keybind: Keybind) -> Vec<String> {
+        let mut conflicts: Vec<_> = self
+            .binds
+            .iter()
+            .filter(|(other, binds)| **other != action && binds.contains(&keybind))
+            .map(|(other, _)| other.to_string())
+            .collect();
+        if keybind.alt && !keybind.ctrl && !keybind.shift && WORKSPACE_KEYS.contains(&keybind.key) {
+            conflicts.push("Switch Workspace".into());
+        }
+        conflicts
+    }
+    // actions added since the keybinds were stored get their default keybinds
+    fn with_missing_defaults(mut self) -> Self {
+        for action in KeyAction::iter() {
+            self.binds.entry(action).or_insert_with(|| action.default_keybinds());
+        }
+        self
+    }
+}
+
+fn setup_keybinds(mut commands: Commands, pkv: Res<PkvStore>, safe_mode: Res<SafeMode>) {
+    let keybinds = if **safe_mode {
+        Keybinds::default()
+    } else {
+        pkv.get::<Keybinds>("keybinds")
+            .map(Keybinds::with_missing_defaults)
+            .unwrap_or_default()
+    };
+    commands.insert_resource(keybinds);
+}
+
+// the next keys pressed while a keybind is being recorded from the settings tab become the keybind
+fn record_keybind(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut keybinds: ResMut<Keybinds>,
+    mut pkv_writes: ResMut<PkvWriteQueue>,
+    safe_mode: Res<SafeMode>,
+) {
+    let Some((action, index)) = keybinds.recording else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        keybinds.recording = None;
+        return;
+    }
+    let Some(keybind) = Keybind::from_pressed(&keys) else {
+        return;
+    };
+    let binds = keybinds.get_mut(action);
+    if index < binds.len() {
+        binds[index] = keybind;
+    } else {
+        binds.push(keybind);
+    }
+    keybinds.recording = None;
+    if !**safe_mode {
+        pkv_writes.save_keybinds();
+    }
 }
 
-fn keybinds(keys: Res<ButtonInput<KeyCode>>, mut file_dialog: FileDialogManager, mut edit_mode: ResMut<EditMode>) {
-    if keys.keybind_pressed([Modifier::Ctrl], [KeyCode::KeyZ]) {
-        // undo
+fn keybinds(
+    keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    mut file_dialog: FileDialogManager,
+    mut edit_mode: ResMut<EditMode>,
+    mut pick_mode: ResMut<PickMode>,
+    mut settings: ResMut<AppSettings>,
+    kmp_file_path: Option<Res<KmpFilePath>>,
+    mut ev_undo: EventWriter<Undo>,
+    mut ev_redo: EventWriter<Redo>,
+    mut ev_duplicate: EventWriter<DuplicateSelected>,
+    mut ev_switch_workspace: EventWriter<SwitchWorkspace>,
+    mut ev_save: EventWriter<SaveFile>,
+    mut ev_camera_mode_changed: EventWriter<CameraModeChanged>,
+) {
+    if keybinds.recording.is_some() || keybinds.suspended {
+        return;
     }
-    if keys.keybind_pressed([Modifier::Ctrl, Modifier::Shift], [KeyCode::KeyZ])
-        || keys.keybind_pressed([Modifier::Ctrl], [KeyCode::KeyY])
-    {
-        // redo
+    let pressed = |action| keybinds.just_pressed(action, &keys);
+
+    if pressed(KeyAction::Redo) {
+        ev_redo.send_default();
+    } else if pressed(KeyAction::Undo) {
+        ev_undo.send_default();
+    }
+
+    if pressed(KeyAction::Duplicate) {
+        ev_duplicate.send_default();
     }
 
-    if keys.keybind_pressed([Modifier::Ctrl], [KeyCode::KeyO]) {
+    if pressed(KeyAction::Open) {
         // open or close file dialog
         if file_dialog.is_open() {
             file_dialog.close();
@@ -26,11 +426,17 @@ fn keybinds(keys: Res<ButtonInput<KeyCode>>, mut file_dialog: FileDialogManager,
         }
     }
 
-    if keys.keybind_pressed([Modifier::Ctrl], [KeyCode::KeyS]) {
-        // save
+    if pressed(KeyAction::Save) && kmp_file_path.is_some() {
+        ev_save.send(SaveFile);
     }
 
-    if keys.keybind_pressed([], [KeyCode::KeyG]) {
+    for (i, key) in WORKSPACE_KEYS.into_iter().enumerate() {
+        if keys.keybind_pressed([Modifier::Alt], [key]) {
+            ev_switch_workspace.send(SwitchWorkspace(i));
+        }
+    }
+
+    if pressed(KeyAction::CycleEditMode) {
         *edit_mode = match *edit_mode {
             EditMode::Tweak => EditMode::SelectBox,
             EditMode::SelectBox => EditMode::Translate,
@@ -38,8 +444,35 @@ fn keybinds(keys: Res<ButtonInput<KeyCode>>, mut file_dialog: FileDialogManager,
             EditMode::Rotate => EditMode::Tweak,
         }
     }
+
+    if pressed(KeyAction::CyclePickMode) {
+        *pick_mode = pick_mode.next();
+    }
+
+    if pressed(KeyAction::CycleCameraMode) {
+        let mode = match settings.camera.mode {
+            CameraMode::Fly => CameraMode::Orbit,
+            CameraMode::Orbit => CameraMode::TopDown,
+            CameraMode::TopDown => CameraMode::Fly,
+        };
+        settings.camera.mode = mode;
+        ev_camera_mode_changed.send(CameraModeChanged(mode));
+    }
 }
 
+/// Alt and one of these switches to the workspace with the same number
+pub const WORKSPACE_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
 #[derive(PartialEq)]
 pub enum Modifier {
     Ctrl,
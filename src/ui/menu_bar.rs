@@ -1,14 +1,76 @@
-use crate::viewer::kmp::SaveFile;
+use crate::{
+    util::kcl_file::Kcl,
+    viewer::camera::{FrameCameras, GoToCameraBookmark, GoToCoordinates, SetCameraBookmark},
+    viewer::edit::{
+        blender_coords::{CopyBlenderCoords, PasteBlenderCoords},
+        create_at_coordinates::{is_path_section, ApplyCreateAtCoordinates, CreateAtCoordinates},
+        create_delete::{DuplicateSelected, RouteDuplication, RouteDuplicationPrompt},
+        kcl_snap::DropSelectionToKcl,
+        link_select_mode::{LinkSelectMode, LinkSelectState},
+        mirror_selection::{MirrorPick, MirrorSelected, MirrorSelection},
+        select::Selected,
+        undo::{Redo, Undo, UndoHistory},
+        volume_select::{ApplyVolumeSelect, KclSideFilter, VolumeSelect, VolumeShape},
+    },
+    viewer::kcl_reachability::{ComputeReachableArea, ReachableArea},
+    viewer::kmp::{
+        branches::KmpBranches,
+        build_kmp,
+        camera_aim::{AimCameras, CameraAim},
+        camera_chain::{ProposedCameraChain, RepairCameraChain},
+        camera_file::CAMERA_FILE_NAME,
+        checkpoint_skips::{CheckpointSkips, FindCheckpointSkips},
+        checkpoints::CheckpointLeft,
+        components::{
+            AreaKind, AreaPoint, KmpCamera, KmpCameraIntroStart, KmpSelectablePoint, RespawnPoint, RoutePoint,
+        },
+        concurrent_edit::{ConcurrentEditPrompt, ConcurrentEdits},
+        csv::{section_csv_file_name, section_supports_csv},
+        documents::{CloseDocument, CloseDocumentPrompt, OpenDocuments, SwitchDocument},
+        donor_import::DonorImportReport,
+        external_edit::{EditSectionExternally, ExternalEdit},
+        header_values::{header_value_meaning, OpenedHeaderValues},
+        history::{format_save_time, FileProperties},
+        intro_preview::{IntroPreview, PlayIntro, StopIntro},
+        item_boxes::{CreateItemBoxes, ItemBoxGenerator, ItemBoxPick, ItemBoxShape, ItemBoxSpacing},
+        kcl_check::CheckPathsAgainstKcl,
+        lap_count::PlaceLapCountCheckpoint,
+        moving_road::{CheckMovingRoad, MovingRoadCheck},
+        object_density::{AuditObjectDensity, ObjectDensityAudit, DENSITY_RADIUS, HOTSPOT_OBJECT_LIMIT},
+        ordering::{IndexChangeWarning, OrderId},
+        path::EntityPathGroups,
+        project::{set_project_paths, ProjectRoot},
+        reference::ReferenceKmp,
+        reverse_path::ReversePaths,
+        routes::RouteLink,
+        section_import::{ApplySectionImport, SectionImport},
+        sections::KmpEditMode,
+        sidecar::KmpSidecar,
+        sound_triggers::{
+            respawn_sound_triggers, AssignSoundTriggers, SoundTriggerConflict, SoundTriggerEditor, MAX_SOUND_TRIGGER,
+            NO_SOUND_TRIGGER,
+        },
+        track_length::{MeasureTrackLength, TrackStats, NORMAL_LAP_TIME},
+        workbook::WORKBOOK_FILE_NAME,
+        KmpConversionPrompt, KmpLoadReport, SaveFile,
+    },
+    viewer::snapshot::{TopDownSnapshot, MAX_SNAPSHOT_SIZE},
+};
 
 use super::{
+    errors::ErrorMessages,
     file_dialog::FileDialogManager,
-    tabs::{DockTree, Tab},
-    ui_state::{KmpFilePath, ResetDockTree, SaveDockTree},
-    util::get_egui_ctx,
+    keybinds::{KeyAction, Keybinds, WORKSPACE_KEYS},
+    settings::AppSettings,
+    tabs::{DockTree, Tab, Workspaces},
+    ui_state::{KmpFilePath, PkvWriteQueue, ResetDockTree, SafeMode, SaveDockTree, SwitchWorkspace},
+    update_ui::KmpFileSelected,
+    util::{combobox_enum, drag_vec3, get_egui_ctx, DragSpeed},
 };
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 use bevy_egui::egui::{self, Align, Button, Layout};
+use std::path::PathBuf;
 use strum::IntoEnumIterator;
 
 pub fn show_menu_bar(world: &mut World) {
@@ -16,13 +78,18 @@ pub fn show_menu_bar(world: &mut World) {
 
     egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
         egui::menu::bar(ui, |ui| {
+            if world.resource::<LinkSelectState>().is_active() {
+                ui.disable();
+            }
             let mut sc_btn = "Ctrl";
             if cfg!(target_os = "macos") {
                 sc_btn = "Cmd";
             }
+            // cloned so the menus can still change the world
+            let keybinds = world.resource::<Keybinds>().clone();
             ui.menu_button("File", |ui| {
                 if ui
-                    .add(Button::new("Open KMP/KCL").shortcut_text(format!("{sc_btn}+O")))
+                    .add(Button::new("Open KMP/KCL").shortcut_text(keybinds.text(KeyAction::Open)))
                     .clicked()
                 {
                     let mut ss = SystemState::<FileDialogManager>::new(world);
@@ -32,12 +99,39 @@ pub fn show_menu_bar(world: &mut World) {
 
                     ui.close_menu();
                 }
+                if ui
+                    .button("Open Reference KMP...")
+                    .on_hover_text_at_pointer(
+                        "Show another KMP (like the original track) faded underneath this one to line things up against. It can't be selected or edited",
+                    )
+                    .clicked()
+                {
+                    let mut ss = SystemState::<FileDialogManager>::new(world);
+                    ss.get_mut(world).open_reference_kmp();
+                    ui.close_menu();
+                }
+                if let Some(mut reference) = world.get_resource_mut::<ReferenceKmp>() {
+                    let file_name = reference.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    ui.checkbox(&mut reference.show, format!("Show Reference ({file_name})"))
+                        .on_hover_text_at_pointer(reference.path.display().to_string());
+                    ui.checkbox(&mut reference.all_sections, "Show All Reference Sections");
+                    if ui.button("Close Reference").clicked() {
+                        world.remove_resource::<ReferenceKmp>();
+                        ui.close_menu();
+                    }
+                }
+                ui.separator();
                 if !world.contains_resource::<KmpFilePath>() {
                     ui.disable();
                 }
 
+                let read_only = world.get_resource::<KmpSidecar>().is_some_and(|x| x.read_only);
                 if ui
-                    .add(Button::new("Save").shortcut_text(format!("{sc_btn}+S")))
+                    .add_enabled(
+                        !read_only,
+                        Button::new("Save").shortcut_text(keybinds.text(KeyAction::Save)),
+                    )
+                    .on_disabled_hover_text("The KMP was opened read-only")
                     .clicked()
                 {
                     world.send_event(SaveFile);
@@ -50,25 +144,647 @@ pub fn show_menu_bar(world: &mut World) {
                 {
                     ui.close_menu();
                 }
+                if ui
+                    .button("File Properties...")
+                    .on_hover_text_at_pointer("See when the KMP was saved, by who, and what changed each time")
+                    .clicked()
+                {
+                    world.init_resource::<FileProperties>();
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui
+                    .button("Set Project Root...")
+                    .on_hover_text_at_pointer(
+                        "Choose the folder of the track project the KMP is in. The project's KCL and exports folder are stored relative to it, so the folder can be moved or renamed without breaking them",
+                    )
+                    .clicked()
+                {
+                    let mut ss = SystemState::<FileDialogManager>::new(world);
+                    ss.get_mut(world).choose_project_root();
+                    ui.close_menu();
+                }
+                if let Some(project) = world.resource::<ProjectRoot>().as_ref() {
+                    let name = project.root.file_name().unwrap_or_default().to_string_lossy();
+                    ui.label(format!("Project: {name}"))
+                        .on_hover_text_at_pointer(project.root.display().to_string());
+                    let kcl = project.file.kcl.clone();
+                    let exports = project.file.exports.clone();
+                    let describe = |path: &Option<PathBuf>, default: &str| match path {
+                        Some(path) => path.display().to_string(),
+                        None => default.into(),
+                    };
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Set Project KCL...")
+                            .on_hover_text_at_pointer(format!(
+                                "The KCL loaded with every KMP in the project: {}",
+                                describe(&kcl, "the 'course.kcl' next to each KMP")
+                            ))
+                            .clicked()
+                        {
+                            let mut ss = SystemState::<FileDialogManager>::new(world);
+                            ss.get_mut(world).choose_project_kcl();
+                            ui.close_menu();
+                        }
+                        if kcl.is_some() && ui.small_button("Reset").clicked() {
+                            set_project_paths(world, None, exports.clone());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Set Exports Folder...")
+                            .on_hover_text_at_pointer(format!(
+                                "The folder files are exported to by default: {}",
+                                describe(&exports, "the project root")
+                            ))
+                            .clicked()
+                        {
+                            let mut ss = SystemState::<FileDialogManager>::new(world);
+                            ss.get_mut(world).choose_project_exports();
+                            ui.close_menu();
+                        }
+                        if exports.is_some() && ui.small_button("Reset").clicked() {
+                            set_project_paths(world, kcl.clone(), None);
+                        }
+                    });
+                }
             });
             ui.menu_button("Edit", |ui| {
-                // haven't implemented undo/redo yet
-                ui.disable();
+                let history = world.resource::<UndoHistory>();
+                let (can_undo, can_redo) = (history.can_undo(), history.can_redo());
                 if ui
-                    .add(Button::new("Undo").shortcut_text(format!("{sc_btn}+Z")))
+                    .add_enabled(can_undo, Button::new("Undo").shortcut_text(keybinds.text(KeyAction::Undo)))
                     .clicked()
                 {
-                    // undo!();
+                    world.send_event_default::<Undo>();
+                    ui.close_menu();
                 }
                 if ui
-                    .add(Button::new("Redo").shortcut_text(format!("{sc_btn}+Shift+Z")))
+                    .add_enabled(can_redo, Button::new("Redo").shortcut_text(keybinds.text(KeyAction::Redo)))
                     .clicked()
                 {
-                    // redo!();
+                    world.send_event_default::<Redo>();
+                    ui.close_menu();
+                }
+                if ui
+                    .add(Button::new("Duplicate").shortcut_text(keybinds.text(KeyAction::Duplicate)))
+                    .on_hover_text_at_pointer(
+                        "Copy the selected points, linked to the same previous points. Alt drag a selected point to drag the copies away",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<DuplicateSelected>();
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Mirror Selection...")
+                    .on_hover_text_at_pointer(
+                        "Copy the selected points mirrored across a plane, for symmetric battle arenas. Mirrored paths are joined up to any points on the plane",
+                    )
+                    .clicked()
+                {
+                    world.init_resource::<MirrorSelection>();
+                    ui.close_menu();
+                }
+                ui.add_enabled_ui(world.contains_resource::<Kcl>(), |ui| {
+                    if ui
+                        .button("Drop Selection onto KCL")
+                        .on_hover_text_at_pointer("Move the selected points straight down onto the ground below them")
+                        .clicked()
+                    {
+                        world.send_event(DropSelectionToKcl { align_to_surface: false });
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Drop Selection onto KCL, Aligned to Surface")
+                        .on_hover_text_at_pointer(
+                            "Move the selected points straight down onto the ground below them, and tilt them to match the slope of the ground, for respawn and start points on banked roads",
+                        )
+                        .clicked()
+                    {
+                        world.send_event(DropSelectionToKcl { align_to_surface: true });
+                        ui.close_menu();
+                    }
+                });
+                ui.separator();
+                let has_selection = world
+                    .query_filtered::<(), With<Selected>>()
+                    .iter(world)
+                    .next()
+                    .is_some();
+                if ui
+                    .add_enabled(has_selection, Button::new("Copy as Blender Coordinates"))
+                    .on_hover_text_at_pointer(
+                        "Copy the positions of the selected points in Blender's Z-up coordinates, one per line, using the scale set in the settings",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<CopyBlenderCoords>();
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(has_selection, Button::new("Paste from Blender"))
+                    .on_hover_text_at_pointer(
+                        "Move the selected points to positions copied from Blender. A single position moves the centre of the selection there, otherwise there must be one position for each selected point",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<PasteBlenderCoords>();
+                    ui.close_menu();
+                }
+                ui.separator();
+                let mode = *world.resource::<KmpEditMode>();
+                if ui
+                    .add_enabled(
+                        world.contains_resource::<KmpFilePath>() && mode != KmpEditMode::TrackInfo,
+                        Button::new(format!("New {mode} Point at Coordinates...")),
+                    )
+                    .on_hover_text_at_pointer(
+                        "Create points at exactly typed positions, or paste a list of positions to create many at once",
+                    )
+                    .clicked()
+                {
+                    world.insert_resource(CreateAtCoordinates::new(mode));
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Go to Coordinates...")
+                    .on_hover_text_at_pointer("Move the camera to look at an exactly typed position")
+                    .clicked()
+                {
+                    world.init_resource::<GoToCoordinates>();
+                    ui.close_menu();
+                }
+            });
+
+            ui.menu_button("Tools", |ui| {
+                ui.add_enabled_ui(world.contains_resource::<Kcl>(), |ui| {
+                    if ui
+                        .button("Check Paths Against KCL")
+                        .on_hover_text_at_pointer(
+                            "Flag enemy and item path points which are inside walls, floating, or over out of bounds areas",
+                        )
+                        .clicked()
+                    {
+                        world.send_event_default::<CheckPathsAgainstKcl>();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Find Reachable Area")
+                        .on_hover_text_at_pointer(
+                            "Highlight the drivable area that can be reached from the first start point in green, and drivable islands that can't be reached in pink, which may be mistakes in the KCL",
+                        )
+                        .clicked()
+                    {
+                        world.send_event_default::<ComputeReachableArea>();
+                        ui.close_menu();
+                    }
+                    if let Some(area) = world.get_resource::<ReachableArea>() {
+                        ui.label(format!("{} unreachable islands found", area.islands.len()));
+                        ui.horizontal(|ui| {
+                            if ui.button("Export...").clicked() {
+                                let mut ss = SystemState::<FileDialogManager>::new(world);
+                                ss.get_mut(world).export_reachable_area();
+                                ui.close_menu();
+                            }
+                            if ui.button("Hide").clicked() {
+                                ReachableArea::clear(world);
+                                ui.close_menu();
+                            }
+                        });
+                    }
+                    if ui
+                        .button("Find Checkpoint Skips")
+                        .on_hover_text_at_pointer(
+                            "Look for checkpoints that can be driven around the end of within the drivable KCL. This is a rough check from above, so roads which pass over each other can be flagged by mistake",
+                        )
+                        .clicked()
+                    {
+                        world.send_event_default::<FindCheckpointSkips>();
+                        ui.close_menu();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Max Height Above Ground");
+                        let mut settings = world.resource_mut::<AppSettings>();
+                        ui.add(
+                            egui::DragValue::new(&mut settings.kcl_check.max_height_above_ground)
+                                .speed(10.)
+                                .range(0.0..=f32::MAX),
+                        );
+                    });
+                });
+                if ui
+                    .button("Export Top-Down Snapshot...")
+                    .on_hover_text_at_pointer(
+                        "Save what the top down camera sees to a PNG at a chosen resolution, for minimap mock-ups and documentation",
+                    )
+                    .clicked()
+                {
+                    world.init_resource::<TopDownSnapshot>();
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui
+                    .button("Place Lap Count Checkpoint")
+                    .on_hover_text_at_pointer(
+                        "Make sure there is exactly one lap count checkpoint, placed across the finish line at the first start point",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<PlaceLapCountCheckpoint>();
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Reverse Track Direction")
+                    .on_hover_text_at_pointer(
+                        "Turn round every enemy, item and checkpoint path and every start point, for making a reversed version of the track. Routes are left as they are",
+                    )
+                    .clicked()
+                {
+                    world.send_event(ReversePaths { whole_track: true });
+                    ui.close_menu();
+                }
+                ui.add_enabled_ui(world.contains_resource::<KmpFilePath>(), |ui| {
+                    if ui
+                        .button("Import Start Points & Respawns from KMP...")
+                        .on_hover_text_at_pointer(
+                            "Replace the start points, respawn points and track info with the ones from another KMP. Checkpoints are linked to whichever new respawn point is closest to their old one",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).import_donor_kmp();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Import Sections From File...")
+                        .on_hover_text_at_pointer(
+                            "Add the points of chosen sections of another KMP to this one, such as its cameras or objects",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).import_sections_kmp();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Export Cameras...")
+                        .on_hover_text_at_pointer(
+                            "Save all the cameras, with the routes they follow and the areas that switch to them, so they can be imported into another version of the track",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).export_cameras(CAMERA_FILE_NAME);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Import Cameras...")
+                        .on_hover_text_at_pointer(
+                            "Add the cameras, routes and camera areas from an exported camera file, in the same positions they were exported from",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).import_cameras(false);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Import Cameras Relative to Start Line...")
+                        .on_hover_text_at_pointer(
+                            "Add the cameras, routes and camera areas from an exported camera file, moved so that they are in the same place relative to the first start point as they were in the track they came from",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).import_cameras(true);
+                        ui.close_menu();
+                    }
+                });
+                if ui
+                    .button("Select/Delete in Volume")
+                    .on_hover_text_at_pointer(
+                        "Select or delete all the points of chosen sections inside a box or sphere, for clearing out part of the track",
+                    )
+                    .clicked()
+                {
+                    let positions: Vec<_> = world
+                        .query_filtered::<&Transform, With<Selected>>()
+                        .iter(world)
+                        .map(|x| x.translation)
+                        .collect();
+                    let center = if positions.is_empty() {
+                        Vec3::ZERO
+                    } else {
+                        positions.iter().sum::<Vec3>() / positions.len() as f32
+                    };
+                    let mode = *world.resource::<KmpEditMode>();
+                    world.insert_resource(VolumeSelect::new(center, mode));
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Generate Item Boxes")
+                    .on_hover_text_at_pointer(
+                        "Place a row or arc of evenly spaced item boxes, between two points picked on the KCL or around a centre",
+                    )
+                    .clicked()
+                {
+                    let positions: Vec<_> = world
+                        .query_filtered::<&Transform, With<Selected>>()
+                        .iter(world)
+                        .map(|x| x.translation)
+                        .collect();
+                    let center = if positions.is_empty() {
+                        Vec3::ZERO
+                    } else {
+                        positions.iter().sum::<Vec3>() / positions.len() as f32
+                    };
+                    world.insert_resource(ItemBoxGenerator::new(center));
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Audit Object Density")
+                    .on_hover_text_at_pointer(
+                        "Find the areas of the track with the most objects within draw distance of each other, which may cause slowdown in game",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<AuditObjectDensity>();
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Track Stats")
+                    .on_hover_text_at_pointer(
+                        "Measure a lap along the middle of the checkpoints, and estimate how long it takes to drive",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<MeasureTrackLength>();
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Respawn Sound Triggers")
+                    .on_hover_text_at_pointer(
+                        "List the sound trigger of every respawn point, find any that clash, and give them free IDs",
+                    )
+                    .clicked()
+                {
+                    world.init_resource::<SoundTriggerEditor>();
+                    ui.close_menu();
+                }
+                if ui
+                    .button("Repair Camera Chain")
+                    .on_hover_text_at_pointer(
+                        "Rebuild the chain of opening cameras from the intro start, so each one's next index points to the following camera",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<RepairCameraChain>();
+                    ui.close_menu();
+                }
+                let has_intro_start = world
+                    .query_filtered::<(), With<KmpCameraIntroStart>>()
+                    .iter(world)
+                    .next()
+                    .is_some();
+                if ui
+                    .add_enabled(has_intro_start, Button::new("Play Intro"))
+                    .on_hover_text_at_pointer(
+                        "Move the viewport camera through the opening cameras from the intro start, following their routes, zoom, view and time",
+                    )
+                    .clicked()
+                {
+                    world.send_event_default::<PlayIntro>();
+                    ui.close_menu();
+                }
+                let selected_cameras: Vec<_> = world
+                    .query_filtered::<(Entity, &KmpCamera), With<Selected>>()
+                    .iter(world)
+                    .map(|(e, camera)| (e, camera.view_start))
+                    .collect();
+                if ui
+                    .add_enabled(!selected_cameras.is_empty(), Button::new("Aim Cameras at Target"))
+                    .on_hover_text_at_pointer(
+                        "Point all the selected cameras at a position or point, optionally setting their view and zoom to match",
+                    )
+                    .clicked()
+                {
+                    // start from the average of where the cameras already look
+                    let target = selected_cameras.iter().map(|x| x.1).sum::<Vec3>() / selected_cameras.len() as f32;
+                    let cameras = selected_cameras.into_iter().map(|x| x.0).collect();
+                    world.insert_resource(CameraAim::new(cameras, target));
+                    ui.close_menu();
+                }
+                let selected_moving_road = {
+                    let mut q = world.query_filtered::<(Entity, &AreaPoint), With<Selected>>();
+                    let selected: Vec<_> = q.iter(world).collect();
+                    match selected[..] {
+                        [(e, area)] if matches!(area.kind, AreaKind::MovingRoad) => Some(e),
+                        _ => None,
+                    }
+                };
+                if ui
+                    .add_enabled(selected_moving_road.is_some(), Button::new("Check Moving Road"))
+                    .on_hover_text_at_pointer(
+                        "With a single moving road area selected, check its route and the Moving Road KCL inside it, and preview how the road moves",
+                    )
+                    .clicked()
+                {
+                    world.send_event(CheckMovingRoad(selected_moving_road.unwrap()));
+                    ui.close_menu();
+                }
+                ui.separator();
+                let mode = *world.resource::<KmpEditMode>();
+                let can_edit_externally = world.contains_resource::<KmpFilePath>() && section_supports_csv(mode);
+                ui.add_enabled_ui(can_edit_externally, |ui| {
+                    if ui
+                        .button(format!("Export {mode} as CSV..."))
+                        .on_hover_text_at_pointer(
+                            "Save every point in the section to a CSV file in KMP order, with its position, rotation, settings and links",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).export_csv(mode, section_csv_file_name(mode));
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button(format!("Import {mode} from CSV..."))
+                        .on_hover_text_at_pointer(
+                            "Replace every point in the section with the points in a CSV file, in the same format as exported",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).import_csv(mode);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button(format!("Edit {mode} in External Editor"))
+                        .on_hover_text_at_pointer(
+                            "Open the section as a CSV file in your default spreadsheet program. Whenever the file is saved, the section is reloaded from it",
+                        )
+                        .clicked()
+                    {
+                        world.send_event_default::<EditSectionExternally>();
+                        ui.close_menu();
+                    }
+                });
+                if let Some(edit) = world.get_resource::<ExternalEdit>() {
+                    if ui
+                        .button(format!("Stop Watching {}", edit.mode))
+                        .on_hover_text_at_pointer(edit.path.display().to_string())
+                        .clicked()
+                    {
+                        world.remove_resource::<ExternalEdit>();
+                        ui.close_menu();
+                    }
+                }
+                ui.separator();
+                ui.add_enabled_ui(world.contains_resource::<KmpFilePath>(), |ui| {
+                    if ui
+                        .button("Export All Sections as Workbook...")
+                        .on_hover_text_at_pointer(
+                            "Save every section that can be exported as CSV to one .xlsx workbook, with a sheet for each section",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).export_workbook(WORKBOOK_FILE_NAME);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Import Workbook...")
+                        .on_hover_text_at_pointer(
+                            "Replace every section that has a sheet in an .xlsx or .ods workbook with the rows of that sheet, in the same format as exported",
+                        )
+                        .clicked()
+                    {
+                        let mut ss = SystemState::<FileDialogManager>::new(world);
+                        ss.get_mut(world).import_workbook();
+                        ui.close_menu();
+                    }
+                });
+            });
+
+            ui.menu_button("Branches", |ui| {
+                if !world.contains_resource::<KmpFilePath>() {
+                    ui.disable();
+                }
+                if ui
+                    .button("New Branch")
+                    .on_hover_text_at_pointer(
+                        "Snapshot the KMP so you can try out changes on a new branch, and switch back to the current state at any time",
+                    )
+                    .clicked()
+                {
+                    KmpBranches::create(world);
+                }
+                let mut switch_to = None;
+                let mut delete = None;
+                let mut branches = world.resource_mut::<KmpBranches>();
+                let current = branches.current();
+                if !branches.branches().is_empty() {
+                    ui.separator();
+                }
+                for (i, branch) in branches.branches().iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.radio(current == Some(i), &branch.name).clicked() {
+                            switch_to = Some(i);
+                        }
+                        if current != Some(i) && ui.small_button("🗑").on_hover_text_at_pointer("Delete Branch").clicked() {
+                            delete = Some(i);
+                        }
+                    });
+                }
+                if let Some(branch) = branches.current_mut() {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut branch.name);
+                    });
+                }
+                if let Some(i) = delete {
+                    branches.delete(i);
+                }
+                if let Some(i) = switch_to {
+                    KmpBranches::switch_to(world, i);
+                }
+            });
+
+            ui.menu_button("Bookmarks", |ui| {
+                let kmp_file_path = world.get_resource::<KmpFilePath>().map(|x| x.0.clone());
+                let (mut go_to, mut set, mut changed) = (None, None, false);
+                let (Some(kmp_file_path), Some(mut sidecar)) = (kmp_file_path, world.get_resource_mut::<KmpSidecar>())
+                else {
+                    ui.disable();
+                    ui.label("Open a KMP to bookmark camera positions");
+                    return;
+                };
+                for (i, slot) in sidecar.camera_bookmarks.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let Some(bookmark) = slot.as_mut() else {
+                            let button = Button::new(format!("Save View to Bookmark {}", i + 1))
+                                .shortcut_text(keybinds.text(KeyAction::SET_BOOKMARK[i]));
+                            if ui.add(button).clicked() {
+                                set = Some(i);
+                                ui.close_menu();
+                            }
+                            return;
+                        };
+                        let go_btn = Button::new("Go").shortcut_text(keybinds.text(KeyAction::GO_TO_BOOKMARK[i]));
+                        if ui.add(go_btn).clicked() {
+                            go_to = Some(i);
+                            ui.close_menu();
+                        }
+                        changed |= ui
+                            .add(egui::TextEdit::singleline(&mut bookmark.name).desired_width(120.))
+                            .lost_focus();
+                        if ui
+                            .small_button("💾")
+                            .on_hover_text_at_pointer(format!(
+                                "Save the current view over this bookmark ({})",
+                                keybinds.text(KeyAction::SET_BOOKMARK[i])
+                            ))
+                            .clicked()
+                        {
+                            set = Some(i);
+                        }
+                        if ui.small_button("🗑").on_hover_text_at_pointer("Delete Bookmark").clicked() {
+                            *slot = None;
+                            changed = true;
+                        }
+                    });
+                }
+                if changed {
+                    let result = sidecar.save(&kmp_file_path);
+                    ErrorMessages::report(world, result);
+                }
+                if let Some(i) = go_to {
+                    world.send_event(GoToCameraBookmark(i));
+                }
+                if let Some(i) = set {
+                    world.send_event(SetCameraBookmark(i));
                 }
             });
 
             ui.menu_button("Window", |ui| {
+                // each workspace keeps its own tab layout
+                let workspaces = world.resource::<Workspaces>();
+                let active = workspaces.active;
+                let names: Vec<_> = workspaces.layouts.iter().map(|x| x.name.clone()).collect();
+                for (i, name) in names.into_iter().enumerate() {
+                    let mut button = Button::new(name).selected(i == active);
+                    if i < WORKSPACE_KEYS.len() {
+                        button = button.shortcut_text(format!("Alt+{}", i + 1));
+                    }
+                    if ui.add(button).clicked() {
+                        world.send_event(SwitchWorkspace(i));
+                        ui.close_menu();
+                    }
+                }
+                ui.separator();
                 if ui.button("Save Tab Layout").clicked() {
                     world.send_event_default::<SaveDockTree>();
                     ui.close_menu();
@@ -98,10 +814,1506 @@ pub fn show_menu_bar(world: &mut World) {
                 }
             });
 
+            show_open_documents(ui, world);
+
             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                 ui.hyperlink_to("Thomas Alban", "https://github.com/ThomasAlban");
                 ui.label("Made by");
+                if **world.resource::<SafeMode>() {
+                    ui.separator();
+                    ui.colored_label(egui::Color32::YELLOW, "Safe Mode");
+                }
+                let mut pkv_writes = world.resource_mut::<PkvWriteQueue>();
+                if let Some(error) = pkv_writes.error.clone() {
+                    ui.separator();
+                    let res = ui
+                        .add(
+                            egui::Label::new(egui::RichText::new("Couldn't Save Settings").color(egui::Color32::RED))
+                                .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text_at_pointer(format!("{error}\n\nClick to dismiss"));
+                    if res.clicked() {
+                        pkv_writes.error = None;
+                    }
+                }
+            });
+        });
+    });
+
+    show_donor_import_report(ctx, world);
+    show_section_import(ctx, world);
+    show_proposed_camera_chain(ctx, world);
+    show_object_density_audit(ctx, world);
+    show_checkpoint_skips(ctx, world);
+    show_index_change_warning(ctx, world);
+    show_volume_select(ctx, world);
+    show_moving_road_check(ctx, world);
+    show_create_at_coordinates(ctx, world);
+    show_go_to_coordinates(ctx, world);
+    show_mirror_selection(ctx, world);
+    show_item_box_generator(ctx, world);
+    show_intro_preview(ctx, world);
+    show_camera_aim(ctx, world);
+    show_close_document_prompt(ctx, world);
+    show_route_duplication_prompt(ctx, world);
+    show_kmp_conversion_prompt(ctx, world);
+    show_kmp_load_report(ctx, world);
+    show_error_messages(ctx, world);
+    show_file_properties(ctx, world);
+    show_concurrent_edit_prompt(ctx, world);
+    show_concurrent_edits(ctx, world);
+    show_track_stats(ctx, world);
+    show_sound_trigger_editor(ctx, world);
+    show_top_down_snapshot(ctx, world);
+}
+
+// minutes and seconds, like the game's timer
+fn format_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.).floor();
+    format!("{minutes}:{:06.3}", seconds - minutes * 60.)
+}
+
+fn show_top_down_snapshot(ctx: &egui::Context, world: &mut World) {
+    let Some(mut snapshot) = world.get_resource_mut::<TopDownSnapshot>() else {
+        return;
+    };
+    let mut export = false;
+    let mut open = true;
+    egui::Window::new("Top-Down Snapshot")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("The snapshot is centred on the top down camera, and shows the KCL and the KMP sections currently shown.");
+            egui::Grid::new("top_down_snapshot").show(ui, |ui| {
+                ui.label("Width");
+                ui.add(egui::DragValue::new(&mut snapshot.width).range(1..=MAX_SNAPSHOT_SIZE).suffix(" px"));
+                ui.end_row();
+                ui.label("Height");
+                ui.add(egui::DragValue::new(&mut snapshot.height).range(1..=MAX_SNAPSHOT_SIZE).suffix(" px"));
+                ui.end_row();
+            });
+            export = ui.button("Export...").clicked();
+        });
+    if export {
+        let mut ss = SystemState::<FileDialogManager>::new(world);
+        ss.get_mut(world).export_snapshot();
+    }
+    if !open {
+        world.remove_resource::<TopDownSnapshot>();
+    }
+}
+
+fn show_track_stats(ctx: &egui::Context, world: &mut World) {
+    let Some(mut stats) = world.get_resource_mut::<TrackStats>() else {
+        return;
+    };
+    let mut remeasure = false;
+    let mut open = true;
+    egui::Window::new("Track Stats")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if stats.midline.len() < 2 {
+                ui.label("There aren't enough checkpoints to measure the track");
+                return;
+            }
+            egui::Grid::new("track_stats").show(ui, |ui| {
+                ui.label("Lap Length");
+                ui.label(format!("{:.0} units", stats.lap_length));
+                ui.end_row();
+                ui.label("Kart Speed")
+                    .on_hover_text_at_pointer("The average speed assumed around the lap, in units per second");
+                ui.add(
+                    egui::DragValue::new(&mut stats.kart_speed)
+                        .speed(10.)
+                        .range(100.0..=20000.0),
+                );
+                ui.end_row();
+                ui.label("Lap Time");
+                let lap_time = format_time(stats.lap_time());
+                if stats.is_lap_time_normal() {
+                    ui.label(lap_time);
+                } else {
+                    ui.colored_label(egui::Color32::from_rgb(255, 69, 0), lap_time);
+                }
+                ui.end_row();
+                ui.label(format!("Race Time ({} laps)", stats.lap_count));
+                ui.label(format_time(stats.race_time()));
+                ui.end_row();
+            });
+            if !stats.is_lap_time_normal() {
+                let (min, max) = NORMAL_LAP_TIME;
+                let too = if stats.lap_time() < min { "short" } else { "long" };
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 69, 0),
+                    format!("Most tracks have laps of {min}-{max} seconds, so this lap may be too {too}"),
+                );
+            }
+            if !stats.closed {
+                ui.label("The checkpoints don't loop back to the start, so the lap is only measured to the last one");
+            }
+            if stats.branches {
+                ui.label("The checkpoints split, so only the first way at each split is measured");
+            }
+            ui.label("This is only a rough estimate, so test it in game to be sure");
+            remeasure = ui.button("Measure Again").clicked();
+        });
+    if remeasure {
+        world.send_event_default::<MeasureTrackLength>();
+    }
+    if !open {
+        world.remove_resource::<TrackStats>();
+    }
+}
+
+fn show_error_messages(ctx: &egui::Context, world: &mut World) {
+    let mut errors = world.resource_mut::<ErrorMessages>();
+    if errors.0.is_empty() {
+        return;
+    }
+    let mut open = true;
+    egui::Window::new("Something Went Wrong")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                for error in errors.0.iter() {
+                    ui.colored_label(egui::Color32::from_rgb(255, 69, 0), error);
+                }
+            });
+            ui.separator();
+            if ui.button("Dismiss").clicked() {
+                errors.0.clear();
+            }
+        });
+    if !open {
+        errors.0.clear();
+    }
+}
+
+fn show_kmp_load_report(ctx: &egui::Context, world: &mut World) {
+    let Some(report) = world.get_resource::<KmpLoadReport>() else {
+        return;
+    };
+    let mut open = true;
+    egui::Window::new("KMP Opened With Problems")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let name = report.path.file_name().unwrap_or_default().to_string_lossy();
+            ui.label(format!(
+                "{name} doesn't look like a normal course, so it may not all have been loaded. It can still be looked through, but check it carefully before saving over it."
+            ));
+            if !report.empty_sections.is_empty() {
+                ui.separator();
+                ui.label(format!("Empty sections: {}", report.empty_sections.join(", ")));
+            }
+            if !report.errors.is_empty() {
+                ui.separator();
+                ui.label("Skipped or replaced:");
+                egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                    for error in report.errors.iter() {
+                        ui.colored_label(egui::Color32::from_rgb(255, 69, 0), error);
+                    }
+                });
+            }
+        });
+    if !open {
+        world.remove_resource::<KmpLoadReport>();
+    }
+}
+
+fn show_file_properties(ctx: &egui::Context, world: &mut World) {
+    let Some(mut show_headers) = world.get_resource::<FileProperties>().map(|x| x.section_headers) else {
+        return;
+    };
+    let path = world.get_resource::<KmpFilePath>().map(|x| x.0.clone());
+    let history = world
+        .get_resource::<KmpSidecar>()
+        .map(|x| x.history.clone())
+        .unwrap_or_default();
+    // each section's name, number of entries, and additional value in the file and as it will be saved
+    let headers: Vec<_> = if show_headers && path.is_some() {
+        let (kmp, _) = build_kmp(world);
+        let opened = world.get_resource::<OpenedHeaderValues>().cloned().unwrap_or_default();
+        kmp.section_headers()
+            .into_iter()
+            .enumerate()
+            .map(|(i, header)| {
+                let name = String::from_utf8_lossy(&header.section_name).into_owned();
+                (i, name, header.num_entries, opened.0[i], header.additional_value)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut open = true;
+    egui::Window::new("File Properties")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let Some(path) = path else {
+                ui.label("No KMP is open");
+                return;
+            };
+            ui.label(path.display().to_string());
+            ui.separator();
+            let res = egui::CollapsingHeader::new("Section Headers")
+                .open(Some(show_headers))
+                .show(ui, |ui| {
+                    ui.label("Values other tools keep in the section headers are saved as they were");
+                    egui::Grid::new("section_headers").striped(true).show(ui, |ui| {
+                        ui.label("Section");
+                        ui.label("Entries");
+                        ui.label("In File");
+                        ui.label("Will Save As");
+                        ui.end_row();
+                        for (i, name, entries, opened, built) in headers.iter() {
+                            ui.label(name).on_hover_text_at_pointer(header_value_meaning(*i));
+                            ui.label(entries.to_string());
+                            ui.label(format!("{opened:#06x}"));
+                            let text = format!("{built:#06x}");
+                            if opened == built {
+                                ui.label(text);
+                            } else {
+                                ui.colored_label(egui::Color32::from_rgb(255, 69, 0), text)
+                                    .on_hover_text_at_pointer("This will be different to the value in the file");
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            if res.header_response.clicked() {
+                show_headers = !show_headers;
+            }
+            ui.separator();
+            let Some(last) = history.last() else {
+                ui.label("This KMP hasn't been saved by KMPeek yet");
+                return;
+            };
+            ui.label(format!("Last saved {} by {}", format_save_time(last.time), last.by));
+            ui.separator();
+            ui.label("History:");
+            egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                for (i, record) in history.iter().enumerate().rev() {
+                    let title = format!("{} - {}", format_save_time(record.time), record.by);
+                    egui::CollapsingHeader::new(title).id_source(i).show(ui, |ui| {
+                        if record.changes.is_empty() {
+                            ui.label("No changes recorded");
+                        }
+                        for change in record.changes.iter() {
+                            ui.label(change);
+                        }
+                        let counts: Vec<_> = record
+                            .counts
+                            .iter()
+                            .map(|(name, count)| format!("{name}: {count}"))
+                            .collect();
+                        ui.small(counts.join(", "));
+                    });
+                }
+            });
+        });
+    if !open {
+        world.remove_resource::<FileProperties>();
+    } else {
+        world.resource_mut::<FileProperties>().section_headers = show_headers;
+    }
+}
+
+fn show_sound_trigger_editor(ctx: &egui::Context, world: &mut World) {
+    if !world.contains_resource::<SoundTriggerEditor>() {
+        return;
+    }
+    let mut rows = respawn_sound_triggers(world);
+    let has_kcl = world.contains_resource::<Kcl>();
+    let mut changed = Vec::new();
+    let mut assign = Vec::new();
+    let mut select = None;
+    let mut open = true;
+    egui::Window::new("Respawn Sound Triggers")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if rows.is_empty() {
+                ui.label("There are no respawn points");
+                return;
+            }
+            ui.label("When a player respawns, the sound is set as if they had driven through the Sound Trigger KCL with this ID as its variant.");
+            if !has_kcl {
+                ui.label("Load a KCL to check the IDs against its sound triggers");
+            }
+            egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                egui::Grid::new("respawn_sound_triggers").striped(true).show(ui, |ui| {
+                    ui.strong("Respawn");
+                    ui.strong("Sound Trigger");
+                    ui.end_row();
+                    for row in rows.iter_mut() {
+                        ui.label(row.index.to_string());
+                        let mut id = row.id.unwrap_or(NO_SOUND_TRIGGER);
+                        let drag = egui::DragValue::new(&mut id)
+                            .range(NO_SOUND_TRIGGER..=MAX_SOUND_TRIGGER)
+                            .speed(0.1)
+                            .custom_formatter(|x, _| if x < 0. { "None".into() } else { x.to_string() });
+                        if ui.add(drag).changed() {
+                            changed.push((row.e, id));
+                        }
+                        match row.conflict {
+                            Some(SoundTriggerConflict::Duplicate(first)) => {
+                                ui.colored_label(egui::Color32::from_rgb(255, 69, 0), format!("Same as respawn {first}"));
+                            }
+                            Some(SoundTriggerConflict::NotInKcl) => {
+                                ui.colored_label(egui::Color32::from_rgb(255, 69, 0), "Not in the KCL")
+                                    .on_hover_text_at_pointer("The KCL has no Sound Trigger collision with this variant");
+                            }
+                            None => {
+                                ui.label("");
+                            }
+                        }
+                        if ui
+                            .button("Free ID")
+                            .on_hover_text_at_pointer("Give this respawn point an ID no other respawn point uses")
+                            .clicked()
+                        {
+                            assign.push(row.e);
+                        }
+                        if ui.button("Select").clicked() {
+                            select = Some(row.e);
+                        }
+                        ui.end_row();
+                    }
+                });
             });
+            let conflicting: Vec<_> = rows.iter().filter(|x| x.conflict.is_some()).map(|x| x.e).collect();
+            if ui
+                .add_enabled(!conflicting.is_empty(), Button::new("Fix All"))
+                .on_hover_text_at_pointer("Give every respawn point that clashes a free ID, preferring the ones the KCL has")
+                .clicked()
+            {
+                assign = conflicting;
+            }
         });
+    for (e, id) in changed {
+        if let Some(mut respawn) = world.get_mut::<RespawnPoint>(e) {
+            respawn.sound_trigger = id;
+        }
+    }
+    if !assign.is_empty() {
+        world.send_event(AssignSoundTriggers(assign));
+    }
+    if let Some(e) = select {
+        let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+        for e in selected {
+            world.entity_mut(e).remove::<Selected>();
+        }
+        world.entity_mut(e).insert(Selected);
+        let frame = world.get::<Transform>(e).map(|x| x.translation).into_iter().collect();
+        world.send_event(FrameCameras(frame));
+        *world.resource_mut::<KmpEditMode>() = KmpEditMode::RespawnPoints;
+    }
+    if !open {
+        world.remove_resource::<SoundTriggerEditor>();
+    }
+}
+
+// the open KMPs, to switch between them or close them
+fn show_open_documents(ui: &mut egui::Ui, world: &mut World) {
+    let docs = world.resource::<OpenDocuments>();
+    let Some(active) = docs.active else {
+        return;
+    };
+    let history = world.resource::<UndoHistory>();
+    // unsaved documents are marked with a dot, like most editors
+    let names: Vec<_> = (0..docs.docs.len())
+        .map(|i| {
+            let name = docs.docs[i].name();
+            if docs.has_unsaved_changes(i, history) {
+                format!("{name} •")
+            } else {
+                name
+            }
+        })
+        .collect();
+    let can_close = names.len() > 1;
+    let unsaved = docs.has_unsaved_changes(active, history);
+
+    ui.separator();
+    let mut selected = active;
+    egui::ComboBox::from_id_source("open_documents")
+        .selected_text(&names[active])
+        .show_ui(ui, |ui| {
+            for (i, name) in names.iter().enumerate() {
+                ui.selectable_value(&mut selected, i, name);
+            }
+        })
+        .response
+        .on_hover_text_at_pointer("The open KMPs. Each one keeps its own undo history while another is shown");
+    if selected != active {
+        world.send_event(SwitchDocument(selected));
+    }
+    let close = ui
+        .add_enabled(can_close, Button::new("✖").frame(false))
+        .on_hover_text_at_pointer("Close this KMP")
+        .on_disabled_hover_text("The only open KMP can't be closed")
+        .clicked();
+    if close {
+        if unsaved {
+            world.insert_resource(CloseDocumentPrompt(active));
+        } else {
+            world.send_event(CloseDocument {
+                index: active,
+                save: false,
+            });
+        }
+    }
+
+    let path = world.resource::<OpenDocuments>().docs[active].path.clone();
+    if world.get_resource::<KmpSidecar>().is_some_and(|x| x.read_only) {
+        let edit = ui
+            .add(
+                egui::Label::new(egui::RichText::new("Read-Only").color(egui::Color32::YELLOW))
+                    .sense(egui::Sense::click()),
+            )
+            .on_hover_text_at_pointer(
+                "This KMP can't be saved, so that nobody else's changes are overwritten\n\nClick to edit it anyway",
+            )
+            .clicked();
+        if edit {
+            world.resource_mut::<KmpSidecar>().read_only = false;
+        }
+    } else if let Some(other) = world.resource::<ConcurrentEdits>().others.get(&path) {
+        ui.colored_label(egui::Color32::RED, "Also Being Edited")
+            .on_hover_text_at_pointer(format!("This KMP is also open in kmpeek for {}", other.who()));
+    }
+}
+
+fn show_close_document_prompt(ctx: &egui::Context, world: &mut World) {
+    let Some(CloseDocumentPrompt(index)) = world.get_resource::<CloseDocumentPrompt>() else {
+        return;
+    };
+    let index = *index;
+    let Some(name) = world.resource::<OpenDocuments>().docs.get(index).map(|x| x.name()) else {
+        world.remove_resource::<CloseDocumentPrompt>();
+        return;
+    };
+    let mut close = None;
+    let mut cancel = false;
+    egui::Window::new("Unsaved Changes")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("{name} has unsaved changes. Save them before closing it?"));
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    close = Some(true);
+                }
+                if ui.button("Don't Save").clicked() {
+                    close = Some(false);
+                }
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+    if let Some(save) = close {
+        world.send_event(CloseDocument { index, save });
+    } else if cancel {
+        world.remove_resource::<CloseDocumentPrompt>();
+    }
+}
+
+fn show_route_duplication_prompt(ctx: &egui::Context, world: &mut World) {
+    let Some(RouteDuplicationPrompt { routed }) = world.get_resource::<RouteDuplicationPrompt>() else {
+        return;
+    };
+    let routed = *routed;
+    let mut routes = None;
+    let mut cancel = false;
+    egui::Window::new("Duplicate Routed Points")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let points = if routed == 1 {
+                "point has a route"
+            } else {
+                "points have routes"
+            };
+            ui.label(format!(
+                "{routed} of the selected {points}. Should the copies follow the same routes?"
+            ));
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Share Routes")
+                    .on_hover_text_at_pointer("The copies follow the same routes as the originals")
+                    .clicked()
+                {
+                    routes = Some(RouteDuplication::Share);
+                }
+                if ui
+                    .button("Copy Routes")
+                    .on_hover_text_at_pointer("Each route is copied, and the copies follow the new routes")
+                    .clicked()
+                {
+                    routes = Some(RouteDuplication::Copy);
+                }
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+    if routes.is_some() {
+        world.send_event(DuplicateSelected {
+            dragged: None,
+            routes,
+            mirror: None,
+        });
+    } else if cancel {
+        world.remove_resource::<RouteDuplicationPrompt>();
+    }
+}
+
+fn show_kmp_conversion_prompt(ctx: &egui::Context, world: &mut World) {
+    let Some(prompt) = world.get_resource::<KmpConversionPrompt>() else {
+        return;
+    };
+    if prompt.accepted {
+        return;
+    }
+    let name = prompt
+        .path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let mut convert = false;
+    let mut cancel = false;
+    egui::Window::new("Little-Endian KMP")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{name} is stored little-endian, which Mario Kart Wii can't read. Convert it to the normal big-endian format? It will be saved big-endian."
+            ));
+            ui.horizontal(|ui| {
+                convert = ui.button("Convert").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+    if convert {
+        let mut prompt = world.resource_mut::<KmpConversionPrompt>();
+        prompt.accepted = true;
+        let path = prompt.path.clone();
+        world.send_event(KmpFileSelected(path));
+    } else if cancel {
+        world.remove_resource::<KmpConversionPrompt>();
+    }
+}
+
+fn show_concurrent_edit_prompt(ctx: &egui::Context, world: &mut World) {
+    let Some(prompt) = world.get_resource::<ConcurrentEditPrompt>() else {
+        return;
+    };
+    if prompt.read_only.is_some() {
+        return;
+    }
+    let name = prompt
+        .path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let mut read_only = None;
+    let mut cancel = false;
+    egui::Window::new("Already Being Edited")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{name} is already open in kmpeek for {}. If you both edit it, whichever of you saves last will \
+                overwrite the other's changes.",
+                prompt.editor.who()
+            ));
+            ui.horizontal(|ui| {
+                if ui.button("Open Read-Only").clicked() {
+                    read_only = Some(true);
+                }
+                if ui.button("Edit Anyway").clicked() {
+                    read_only = Some(false);
+                }
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+    if let Some(read_only) = read_only {
+        let mut prompt = world.resource_mut::<ConcurrentEditPrompt>();
+        prompt.read_only = Some(read_only);
+        let path = prompt.path.clone();
+        world.send_event(KmpFileSelected(path));
+    } else if cancel {
+        world.remove_resource::<ConcurrentEditPrompt>();
+    }
+}
+
+// warn about anyone who starts editing a KMP after it was opened here
+fn show_concurrent_edits(ctx: &egui::Context, world: &mut World) {
+    let edits = world.resource::<ConcurrentEdits>();
+    let others: Vec<_> = edits
+        .undismissed()
+        .map(|(path, editor)| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            (path.clone(), name, editor.who())
+        })
+        .collect();
+    if others.is_empty() {
+        return;
+    }
+    let active_path = world.get_resource::<KmpFilePath>().map(|x| x.0.clone());
+    let mut make_read_only = false;
+    let mut dismiss = false;
+    egui::Window::new("Also Being Edited")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            for (_, name, who) in others.iter() {
+                ui.label(format!("{name} has since been opened for editing in kmpeek for {who}."));
+            }
+            ui.label("Whichever of you saves last will overwrite the other's changes.");
+            ui.horizontal(|ui| {
+                if others.iter().any(|x| Some(&x.0) == active_path.as_ref()) {
+                    make_read_only = ui
+                        .button("Make Read-Only")
+                        .on_hover_text_at_pointer("Stop editing the open KMP here, leaving it to them")
+                        .clicked();
+                }
+                dismiss = ui.button("Keep Editing").clicked();
+            });
+        });
+    if make_read_only {
+        if let Some(mut sidecar) = world.get_resource_mut::<KmpSidecar>() {
+            sidecar.read_only = true;
+        }
+    }
+    if make_read_only || dismiss {
+        world.resource_mut::<ConcurrentEdits>().dismiss_all();
+    }
+}
+
+fn show_intro_preview(ctx: &egui::Context, world: &mut World) {
+    let Some(mut preview) = world.get_resource_mut::<IntroPreview>() else {
+        return;
+    };
+    let mut stop = false;
+    egui::Window::new("Intro Preview")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -10.])
+        .show(ctx, |ui| {
+            let (current, elapsed, num_shots) = (preview.current, preview.elapsed, preview.shots.len());
+            if let Some(shot) = preview.shots.get(current) {
+                ui.label(format!("Camera {} ({}/{num_shots})", shot.index, current + 1));
+                ui.add(
+                    egui::ProgressBar::new(elapsed / shot.duration)
+                        .text(format!("{:.2}s / {:.2}s", elapsed, shot.duration)),
+                );
+            }
+            ui.horizontal(|ui| {
+                let pause_text = if preview.paused { "Resume" } else { "Pause" };
+                if ui.button(pause_text).clicked() {
+                    preview.paused = !preview.paused;
+                }
+                if ui
+                    .add_enabled(current > 0, Button::new("⏮"))
+                    .on_hover_text_at_pointer("Previous Camera")
+                    .clicked()
+                {
+                    preview.current -= 1;
+                    preview.elapsed = 0.;
+                }
+                if ui
+                    .add_enabled(current + 1 < num_shots, Button::new("⏭"))
+                    .on_hover_text_at_pointer("Next Camera")
+                    .clicked()
+                {
+                    preview.current += 1;
+                    preview.elapsed = 0.;
+                }
+                stop = ui.button("Stop").clicked();
+            });
+        });
+    if stop {
+        world.send_event_default::<StopIntro>();
+    }
+}
+
+fn show_camera_aim(ctx: &egui::Context, world: &mut World) {
+    if !world.contains_resource::<CameraAim>() {
+        return;
+    }
+    let has_kcl = world.contains_resource::<Kcl>();
+    let mut apply = false;
+    let mut open = true;
+    world.resource_scope(|world, mut aim: Mut<CameraAim>| {
+        let mut q_camera = world.query::<(&Transform, &OrderId)>();
+        let mut cameras: Vec<_> = q_camera
+            .iter_many(world, &aim.cameras)
+            .map(|(transform, order_id)| (order_id.0, transform.translation))
+            .collect();
+        cameras.sort_by_key(|x| x.0);
+
+        egui::Window::new("Aim Cameras at Target")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("camera_aim").show(ui, |ui| {
+                    ui.label("Target");
+                    drag_vec3(ui, &mut aim.target, DragSpeed::Fast);
+                    let picking = aim.picking;
+                    if ui
+                        .selectable_label(picking, "🖊")
+                        .on_hover_text_at_pointer(if has_kcl {
+                            "Pick a point or a position on the KCL"
+                        } else {
+                            "Pick a point"
+                        })
+                        .clicked()
+                    {
+                        aim.picking = !picking;
+                    }
+                    ui.end_row();
+                    ui.label("Subject Size");
+                    ui.add(
+                        egui::DragValue::new(&mut aim.subject_size)
+                            .speed(DragSpeed::Fast)
+                            .range(0. ..=f32::MAX),
+                    )
+                    .on_hover_text_at_pointer("How wide the thing being looked at is, for the suggested zoom");
+                    ui.end_row();
+                });
+                ui.checkbox(&mut aim.set_view, "Set View Start & End to Target");
+                ui.checkbox(&mut aim.set_zoom, "Set Zoom to Suggested Zoom");
+                if aim.picking {
+                    ui.label("Click on a point or the KCL to pick the target");
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                    egui::Grid::new("camera_aim_cameras").striped(true).show(ui, |ui| {
+                        ui.label("Camera");
+                        ui.label("Distance");
+                        ui.label("Suggested Zoom");
+                        ui.end_row();
+                        for (index, pos) in cameras.iter() {
+                            ui.label(index.to_string());
+                            ui.label(format!("{:.0}", pos.distance(aim.target)));
+                            ui.label(format!("{:.1}°", aim.suggested_zoom(*pos)));
+                            ui.end_row();
+                        }
+                    });
+                });
+                if ui.button(format!("Aim {} Cameras", cameras.len())).clicked() {
+                    apply = true;
+                }
+            });
     });
+    if apply {
+        world.send_event_default::<AimCameras>();
+    }
+    if !open {
+        world.remove_resource::<CameraAim>();
+    }
+}
+
+fn show_item_box_generator(ctx: &egui::Context, world: &mut World) {
+    // a position which can be typed in, or picked by clicking on the KCL
+    fn position_row(
+        ui: &mut egui::Ui,
+        generator: &mut ItemBoxGenerator,
+        label: &str,
+        pick: ItemBoxPick,
+        has_kcl: bool,
+    ) {
+        ui.label(label);
+        let pos = match pick {
+            ItemBoxPick::Start => &mut generator.start,
+            ItemBoxPick::End => &mut generator.end,
+            ItemBoxPick::Center => &mut generator.center,
+        };
+        drag_vec3(ui, pos, DragSpeed::Fast);
+        let picking = generator.picking == Some(pick);
+        if ui
+            .add_enabled(has_kcl, egui::SelectableLabel::new(picking, "🖊"))
+            .on_hover_text_at_pointer("Pick on the KCL")
+            .clicked()
+        {
+            generator.picking = if picking { None } else { Some(pick) };
+        }
+        ui.end_row();
+    }
+    if !world.contains_resource::<ItemBoxGenerator>() {
+        return;
+    }
+    let has_kcl = world.contains_resource::<Kcl>();
+    let mut create = false;
+    let mut open = true;
+    world.resource_scope(|_, mut generator: Mut<ItemBoxGenerator>| {
+        egui::Window::new("Generate Item Boxes")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for shape in ItemBoxShape::iter() {
+                        ui.selectable_value(&mut generator.shape, shape, shape.to_string());
+                    }
+                });
+                egui::Grid::new("item_box_generator").show(ui, |ui| {
+                    match generator.shape {
+                        ItemBoxShape::Row => {
+                            position_row(ui, &mut generator, "Start", ItemBoxPick::Start, has_kcl);
+                            position_row(ui, &mut generator, "End", ItemBoxPick::End, has_kcl);
+                        }
+                        ItemBoxShape::Arc => {
+                            position_row(ui, &mut generator, "Centre", ItemBoxPick::Center, has_kcl);
+                            ui.label("Radius");
+                            ui.add(
+                                egui::DragValue::new(&mut generator.radius)
+                                    .speed(DragSpeed::Fast)
+                                    .range(0. ..=f32::MAX),
+                            );
+                            ui.end_row();
+                            ui.label("Arc Angle");
+                            ui.add(egui::Slider::new(&mut generator.arc_angle, 0. ..=360.).suffix("°"));
+                            ui.end_row();
+                            ui.label("Direction");
+                            ui.add(egui::Slider::new(&mut generator.direction, -180. ..=180.).suffix("°"));
+                            ui.end_row();
+                        }
+                    }
+                    ui.label("Count");
+                    ui.add(egui::DragValue::new(&mut generator.count).range(1..=100));
+                    ui.end_row();
+                    ui.label("Spacing");
+                    combobox_enum(ui, &mut generator.spacing, None);
+                    ui.end_row();
+                    if generator.spacing == ItemBoxSpacing::Fixed {
+                        ui.label("Distance");
+                        ui.add(
+                            egui::DragValue::new(&mut generator.distance)
+                                .speed(DragSpeed::Fast)
+                                .range(0. ..=f32::MAX),
+                        );
+                        ui.end_row();
+                    }
+                    ui.label("Height");
+                    ui.add(egui::DragValue::new(&mut generator.height).speed(DragSpeed::Slow))
+                        .on_hover_text_at_pointer("How far above the ground the boxes are placed");
+                    ui.end_row();
+                });
+                if generator.picking.is_some() {
+                    ui.label("Click on the KCL to pick the position");
+                }
+                if ui.button(format!("Create {} Item Boxes", generator.count)).clicked() {
+                    create = true;
+                }
+            });
+    });
+    if create {
+        world.send_event_default::<CreateItemBoxes>();
+    }
+    if !open {
+        world.remove_resource::<ItemBoxGenerator>();
+    }
+}
+
+fn show_mirror_selection(ctx: &egui::Context, world: &mut World) {
+    if !world.contains_resource::<MirrorSelection>() {
+        return;
+    }
+    let has_kcl = world.contains_resource::<Kcl>();
+    let num_selected = world
+        .query_filtered::<(), (With<Selected>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .count();
+    let mut mirror = false;
+    let mut open = true;
+    world.resource_scope(|_, mut tool: Mut<MirrorSelection>| {
+        egui::Window::new("Mirror Selection")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("The selection is mirrored across the vertical plane through these points:");
+                egui::Grid::new("mirror_selection").show(ui, |ui| {
+                    for (label, pick) in [("Point A", MirrorPick::A), ("Point B", MirrorPick::B)] {
+                        ui.label(label);
+                        let pos = match pick {
+                            MirrorPick::A => &mut tool.a,
+                            MirrorPick::B => &mut tool.b,
+                        };
+                        drag_vec3(ui, pos, DragSpeed::Fast);
+                        let picking = tool.picking == Some(pick);
+                        if ui
+                            .add_enabled(has_kcl, egui::SelectableLabel::new(picking, "🖊"))
+                            .on_hover_text_at_pointer("Pick on the KCL")
+                            .clicked()
+                        {
+                            tool.picking = if picking { None } else { Some(pick) };
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Through Point A:");
+                    if ui
+                        .button("Along X")
+                        .on_hover_text_at_pointer("Make the plane run along the X axis through Point A")
+                        .clicked()
+                    {
+                        tool.b = tool.a + Vec3::X * 1000.;
+                    }
+                    if ui
+                        .button("Along Z")
+                        .on_hover_text_at_pointer("Make the plane run along the Z axis through Point A")
+                        .clicked()
+                    {
+                        tool.b = tool.a + Vec3::Z * 1000.;
+                    }
+                });
+                if tool.picking.is_some() {
+                    ui.label("Click on the KCL to pick the position");
+                }
+                let valid = tool.plane().is_some();
+                if !valid {
+                    ui.label("The points can't be directly above each other");
+                }
+                if ui
+                    .add_enabled(
+                        valid && num_selected > 0,
+                        Button::new(format!("Mirror {num_selected} Selected Points")),
+                    )
+                    .clicked()
+                {
+                    mirror = true;
+                }
+            });
+    });
+    if mirror {
+        world.send_event_default::<MirrorSelected>();
+    }
+    if !open {
+        world.remove_resource::<MirrorSelection>();
+    }
+}
+
+fn show_create_at_coordinates(ctx: &egui::Context, world: &mut World) {
+    if !world.contains_resource::<CreateAtCoordinates>() {
+        return;
+    }
+    let mut create = false;
+    let mut open = true;
+    world.resource_scope(|_, mut dialog: Mut<CreateAtCoordinates>| {
+        egui::Window::new(format!("New {} at Coordinates", dialog.mode))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut dialog.multiple, "Paste Multiple Points");
+                if dialog.multiple {
+                    ui.label("One point per line, as X Y Z, or X Y Z followed by the X Y Z rotation. Numbers can be separated by spaces, tabs or commas.");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut dialog.text)
+                            .code_editor()
+                            .desired_rows(8)
+                            .hint_text("0 0 0\n1000 0 0"),
+                    );
+                } else {
+                    egui::Grid::new("create_at_coordinates").show(ui, |ui| {
+                        ui.label("Position");
+                        drag_vec3(ui, &mut dialog.pos, DragSpeed::Fast);
+                        ui.end_row();
+                        ui.label("Rotation");
+                        drag_vec3(ui, &mut dialog.rot, DragSpeed::Slow);
+                        ui.end_row();
+                    });
+                }
+                if is_path_section(dialog.mode) {
+                    ui.checkbox(&mut dialog.link, "Link On From Selected Point")
+                        .on_hover_text_at_pointer("Link each new point on from the selected point, or the point created before it");
+                }
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if ui.button("Create").clicked() {
+                    create = true;
+                }
+            });
+    });
+    if create {
+        world.send_event_default::<ApplyCreateAtCoordinates>();
+    }
+    if !open {
+        world.remove_resource::<CreateAtCoordinates>();
+    }
+}
+
+fn show_go_to_coordinates(ctx: &egui::Context, world: &mut World) {
+    if !world.contains_resource::<GoToCoordinates>() {
+        return;
+    }
+    let selected: Vec<_> = world
+        .query_filtered::<&Transform, (With<Selected>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .map(|x| x.translation)
+        .collect();
+    let mut go = false;
+    let mut open = true;
+    world.resource_scope(|_, mut dialog: Mut<GoToCoordinates>| {
+        egui::Window::new("Go to Coordinates")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("go_to_coordinates").show(ui, |ui| {
+                    ui.label("Position");
+                    drag_vec3(ui, &mut dialog.pos, DragSpeed::Fast);
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!selected.is_empty(), Button::new("Use Selection"))
+                        .on_hover_text_at_pointer("Fill in the centre of the selected points")
+                        .clicked()
+                    {
+                        dialog.pos = selected.iter().sum::<Vec3>() / selected.len() as f32;
+                    }
+                    go = ui.button("Go").clicked();
+                });
+            });
+    });
+    if go {
+        let pos = world.resource::<GoToCoordinates>().pos;
+        world.send_event(FrameCameras(vec![pos]));
+    }
+    if !open {
+        world.remove_resource::<GoToCoordinates>();
+    }
+}
+
+fn show_moving_road_check(ctx: &egui::Context, world: &mut World) {
+    let Some(check) = world.get_resource::<MovingRoadCheck>() else {
+        return;
+    };
+    let area_e = check.area;
+    if world.get_entity(area_e).is_none() {
+        world.remove_resource::<MovingRoadCheck>();
+        return;
+    }
+    let area_name = match world.get::<OrderId>(area_e) {
+        Some(id) => format!("Area {}", id.0),
+        None => "Area".into(),
+    };
+    let mut routes: Vec<Entity> = world
+        .get_resource::<EntityPathGroups<RoutePoint>>()
+        .map(|x| x.iter().filter_map(|x| x.path.first().copied()).collect())
+        .unwrap_or_default();
+    // don't offer routes whose points have been deleted since the paths were last worked out
+    routes.retain(|e| world.get_entity(*e).is_some());
+
+    let mut check = world.resource_mut::<MovingRoadCheck>();
+    let mut open = true;
+    let mut link_to = None;
+    let mut recheck = false;
+    let mut pick_route = false;
+    egui::Window::new("Moving Road")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{area_name} follows"));
+                let selected_text = match check.route {
+                    Some(i) => format!("Route {i}"),
+                    None => "No Route".into(),
+                };
+                egui::ComboBox::from_id_source("moving_road_route")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        for (i, route_e) in routes.iter().enumerate() {
+                            if ui
+                                .selectable_label(check.route == Some(i), format!("Route {i}"))
+                                .clicked()
+                            {
+                                link_to = Some(*route_e);
+                            }
+                        }
+                    });
+                if ui
+                    .button("🖊")
+                    .on_hover_text_at_pointer("Pick the route in the viewport")
+                    .clicked()
+                {
+                    pick_route = true;
+                }
+            });
+            ui.separator();
+            if check.variants.is_empty() {
+                ui.label("No Moving Road KCL found inside the area");
+            } else {
+                egui::Grid::new("moving_road_variants").striped(true).show(ui, |ui| {
+                    for (variant, count) in check.variants.iter() {
+                        ui.label(format!("Variant {variant}"));
+                        ui.label(format!("{count} triangles"));
+                        ui.end_row();
+                    }
+                });
+            }
+            ui.checkbox(&mut check.preview, "Preview Motion");
+            ui.separator();
+            if check.warnings.is_empty() {
+                ui.colored_label(egui::Color32::GREEN, "The area, route and KCL agree with each other");
+            }
+            for warning in check.warnings.iter() {
+                ui.colored_label(egui::Color32::from_rgb(255, 69, 0), warning);
+            }
+            if ui.button("Check Again").clicked() {
+                recheck = true;
+            }
+        });
+    if let Some(route_e) = link_to {
+        world.entity_mut(area_e).remove::<RouteLink>();
+        world.entity_mut(area_e).insert(RouteLink(route_e));
+        recheck = true;
+    }
+    if pick_route {
+        world.insert_resource(LinkSelectMode::<RoutePoint>::new([area_e]));
+    }
+    if recheck {
+        world.send_event(CheckMovingRoad(area_e));
+    }
+    if !open {
+        world.remove_resource::<MovingRoadCheck>();
+    }
+}
+
+fn show_volume_select(ctx: &egui::Context, world: &mut World) {
+    if !world.contains_resource::<VolumeSelect>() {
+        return;
+    }
+    let mut apply = None;
+    let mut open = true;
+    world.resource_scope(|_, mut volume: Mut<VolumeSelect>| {
+        egui::Window::new("Select/Delete in Volume")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for shape in VolumeShape::iter() {
+                        ui.selectable_value(&mut volume.shape, shape, shape.to_string());
+                    }
+                });
+                egui::Grid::new("volume_select").show(ui, |ui| {
+                    ui.label("Centre");
+                    drag_vec3(ui, &mut volume.center, DragSpeed::Fast);
+                    ui.end_row();
+                    match volume.shape {
+                        VolumeShape::Box => {
+                            ui.label("Half Size");
+                            drag_vec3(ui, &mut volume.half_extents, DragSpeed::Fast);
+                        }
+                        VolumeShape::Sphere => {
+                            ui.label("Radius");
+                            ui.add(egui::DragValue::new(&mut volume.radius).speed(DragSpeed::Fast).range(0. ..=f32::MAX));
+                        }
+                    }
+                    ui.end_row();
+                    ui.label("Only");
+                    combobox_enum(ui, &mut volume.kcl_filter, None).on_hover_text_at_pointer(
+                        "Only affect points with KCL directly below them (Above KCL), or directly above them (Below KCL)",
+                    );
+                    ui.end_row();
+                });
+                ui.label("Sections:");
+                for mode in KmpEditMode::iter().filter(|x| *x != KmpEditMode::TrackInfo) {
+                    let mut included = volume.sections.contains(&mode);
+                    if ui.checkbox(&mut included, mode.to_string()).changed() {
+                        if included {
+                            volume.sections.insert(mode);
+                        } else {
+                            volume.sections.remove(&mode);
+                        }
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Select").clicked() {
+                        apply = Some(false);
+                    }
+                    if ui.button("Delete").clicked() {
+                        apply = Some(true);
+                    }
+                });
+                if volume.kcl_filter != KclSideFilter::All {
+                    ui.label("Points are checked against the KCL, so a KCL needs to be open");
+                }
+            });
+    });
+    if let Some(delete) = apply {
+        world.send_event(ApplyVolumeSelect { delete });
+    }
+    if !open {
+        world.remove_resource::<VolumeSelect>();
+    }
+}
+
+fn show_index_change_warning(ctx: &egui::Context, world: &mut World) {
+    let Some(warning) = world.get_resource::<IndexChangeWarning>() else {
+        return;
+    };
+    let mut save = false;
+    let mut cancel = false;
+    egui::Window::new("Indexes Will Change")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Saving will change the index of {} point(s) compared to the file as it was opened or last saved. \
+                Anything that refers to these points by index (such as external patches) may need updating.",
+                warning.0.len()
+            ));
+            egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                egui::Grid::new("index_changes").striped(true).show(ui, |ui| {
+                    for change in warning.0.iter() {
+                        ui.label(change.section.to_string());
+                        ui.label(format!("{} → {}", change.old, change.new));
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                save = ui.button("Save Anyway").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+    if save {
+        IndexChangeWarning::save_anyway(world);
+    } else if cancel {
+        world.remove_resource::<IndexChangeWarning>();
+    }
+}
+
+fn show_object_density_audit(ctx: &egui::Context, world: &mut World) {
+    let Some(audit) = world.get_resource::<ObjectDensityAudit>() else {
+        return;
+    };
+    let mut frame = None;
+    let mut open = true;
+    egui::Window::new("Object Density")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("{} objects in total", audit.total));
+            egui::CollapsingHeader::new("Objects by ID").show(ui, |ui| {
+                egui::Grid::new("object_counts").striped(true).show(ui, |ui| {
+                    for (id, count) in audit.counts_by_id.iter() {
+                        ui.label(format!("ID {id}"));
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.separator();
+            ui.label(format!("Densest areas (objects within {DENSITY_RADIUS} units):"));
+            egui::Grid::new("object_hotspots").striped(true).show(ui, |ui| {
+                for (i, hotspot) in audit.hotspots.iter().enumerate() {
+                    let text = format!("{} objects", hotspot.objects.len());
+                    let most_common: Vec<_> = hotspot
+                        .counts_by_id
+                        .iter()
+                        .take(5)
+                        .map(|(id, count)| format!("ID {id}: {count}"))
+                        .collect();
+                    let label = if hotspot.is_over_limit() {
+                        ui.colored_label(egui::Color32::from_rgb(255, 69, 0), text)
+                    } else {
+                        ui.label(text)
+                    };
+                    label.on_hover_text_at_pointer(most_common.join("\n"));
+                    if ui.button("Frame").clicked() {
+                        frame = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if audit.hotspots.iter().any(|x| x.is_over_limit()) {
+                ui.label(format!(
+                    "Areas with more than {HOTSPOT_OBJECT_LIMIT} objects are likely to cause slowdown"
+                ));
+            }
+        });
+    if let Some(i) = frame {
+        let positions: Vec<_> = audit.hotspots[i]
+            .objects
+            .iter()
+            .filter_map(|e| world.get::<Transform>(*e))
+            .map(|x| x.translation)
+            .collect();
+        world.send_event(FrameCameras(positions));
+        *world.resource_mut::<KmpEditMode>() = KmpEditMode::Objects;
+    }
+    if !open {
+        world.remove_resource::<ObjectDensityAudit>();
+    }
+}
+
+fn show_checkpoint_skips(ctx: &egui::Context, world: &mut World) {
+    let Some(skips) = world.get_resource::<CheckpointSkips>() else {
+        return;
+    };
+    let mut rows: Vec<_> = skips
+        .iter()
+        .map(|skip| {
+            let id = world.get::<OrderId>(skip.checkpoint).map(|x| x.0);
+            let mut frame = vec![skip.gap];
+            let right = world.get::<CheckpointLeft>(skip.checkpoint).map(|x| x.right);
+            for e in [Some(skip.checkpoint), right].into_iter().flatten() {
+                frame.extend(world.get::<Transform>(e).map(|x| x.translation));
+            }
+            (id, skip.checkpoint, skip.side, frame)
+        })
+        .collect();
+    rows.sort_by_key(|x| x.0);
+    let mut select = None;
+    let mut open = true;
+    egui::Window::new("Checkpoint Skips")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if rows.is_empty() {
+                ui.label("No checkpoints could be driven around");
+                return;
+            }
+            ui.label(format!("{} checkpoints may be skippable:", rows.len()));
+            egui::ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                egui::Grid::new("checkpoint_skips").striped(true).show(ui, |ui| {
+                    for (i, (id, _, side, _)) in rows.iter().enumerate() {
+                        let id = id.map(|x| x.to_string()).unwrap_or_default();
+                        ui.label(format!("Checkpoint {id} can be driven around its {side} end"));
+                        if ui.button("Select").clicked() {
+                            select = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    if let Some(i) = select {
+        let (_, e, _, frame) = rows.swap_remove(i);
+        let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+        for e in selected {
+            world.entity_mut(e).remove::<Selected>();
+        }
+        world.entity_mut(e).insert(Selected);
+        world.send_event(FrameCameras(frame));
+        *world.resource_mut::<KmpEditMode>() = KmpEditMode::Checkpoints;
+    }
+    if !open {
+        world.remove_resource::<CheckpointSkips>();
+    }
+}
+
+fn show_proposed_camera_chain(ctx: &egui::Context, world: &mut World) {
+    let Some(proposed) = world.get_resource::<ProposedCameraChain>() else {
+        return;
+    };
+    let indexes = |entities: &[Entity]| -> Vec<_> {
+        entities
+            .iter()
+            .filter_map(|e| world.get::<OrderId>(*e))
+            .map(|x| x.0.to_string())
+            .collect()
+    };
+    let (chain, unrepairable) = (indexes(&proposed.chain), indexes(&proposed.unrepairable));
+    let (mut apply, mut cancel) = (false, false);
+    egui::Window::new("Repair Camera Chain")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.label("The opening cameras will be linked in this order, shown by the green arrows:");
+            ui.label(chain.join(" → "));
+            if !unrepairable.is_empty() {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 69, 0),
+                    format!(
+                        "These cameras can't be linked to, as a next index only goes up to 254: {}",
+                        unrepairable.join(", ")
+                    ),
+                );
+            }
+            ui.horizontal(|ui| {
+                apply = ui.button("Apply").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+    if apply {
+        world.resource_scope(|world, proposed: Mut<ProposedCameraChain>| proposed.apply(world));
+    }
+    if apply || cancel {
+        world.remove_resource::<ProposedCameraChain>();
+    }
+}
+
+fn show_section_import(ctx: &egui::Context, world: &mut World) {
+    let Some(mut import) = world.get_resource_mut::<SectionImport>() else {
+        return;
+    };
+    let mut apply = false;
+    let mut open = true;
+    egui::Window::new("Import Sections")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("From {}", import.path.display()));
+            ui.separator();
+            for section in KmpEditMode::iter().filter(|x| *x != KmpEditMode::TrackInfo) {
+                let count = import.count(section);
+                let mut chosen = import.is_chosen(section);
+                let res = ui.add_enabled(count > 0, egui::Checkbox::new(&mut chosen, format!("{section} ({count})")));
+                if res.changed() {
+                    import.set_chosen(section, chosen);
+                }
+            }
+            ui.separator();
+            ui.label("Imported points which use a route or respawn point are linked to the imported ones if they are ticked, or to the current ones with the same index if not");
+            ui.with_layout(Layout::top_down(Align::Center), |ui| {
+                apply = ui
+                    .add_enabled(!import.chosen.is_empty(), Button::new("Import"))
+                    .clicked();
+            });
+        });
+    if apply {
+        world.send_event_default::<ApplySectionImport>();
+    } else if !open {
+        world.remove_resource::<SectionImport>();
+    }
+}
+
+fn show_donor_import_report(ctx: &egui::Context, world: &mut World) {
+    let Some(report) = world.get_resource::<DonorImportReport>() else {
+        return;
+    };
+    let mut close = false;
+    egui::Window::new("Import Complete")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.label(format!("Imported from {}", report.donor.display()));
+            ui.label(format!("{} checkpoint respawn links were matched", report.matched));
+            if !report.unmatched.is_empty() {
+                let indexes: Vec<_> = report.unmatched.iter().map(|x| x.to_string()).collect();
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 69, 0),
+                    format!(
+                        "No nearby respawn point was found for checkpoints {}, so they have been unlinked",
+                        indexes.join(", ")
+                    ),
+                );
+            }
+            ui.with_layout(Layout::top_down(Align::Center), |ui| {
+                close = ui.button("OK").clicked();
+            });
+        });
+    if close {
+        world.remove_resource::<DonorImportReport>();
+    }
 }
@@ -1,15 +1,21 @@
 use self::{
-    keybinds::keybinds_plugin, settings::app_settings_plugin, tabs::docktree_plugin, ui_state::ui_state_plugin,
-    update_ui::update_ui_plugin, viewport::viewport_plugin,
+    cmd_args::cmd_args_plugin, keybinds::keybinds_plugin, settings::app_settings_plugin, status_bar::status_bar_plugin,
+    tabs::docktree_plugin, ui_state::ui_state_plugin, update_ui::update_ui_plugin, viewport::viewport_plugin,
 };
 use bevy::app::App;
 use bevy_egui::EguiPlugin;
+use errors::errors_plugin;
+use file_associations::file_associations_plugin;
 use file_dialog::file_dialog_plugin;
 
+pub mod cmd_args;
+pub mod errors;
+pub mod file_associations;
 pub mod file_dialog;
 pub mod keybinds;
 mod menu_bar;
 pub mod settings;
+mod status_bar;
 pub mod tabs;
 pub mod ui_state;
 pub mod update_ui;
@@ -26,5 +32,9 @@ pub fn ui_plugin(app: &mut App) {
         keybinds_plugin,
         app_settings_plugin,
         file_dialog_plugin,
+        cmd_args_plugin,
+        status_bar_plugin,
+        file_associations_plugin,
+        errors_plugin,
     ));
 }
@@ -3,12 +3,23 @@ use std::{
     io::Write,
 };
 
-use crate::viewer::{camera::CameraSettings, kcl_model::KclModelSettings, kmp::settings::KmpModelSettings};
+use crate::viewer::{
+    camera::CameraSettings,
+    edit::{
+        blender_coords::BlenderCoordSettings, create_delete::PathHeightSnapSettings, nudge::NudgeSettings,
+        select::PickSettings,
+    },
+    kcl_model::KclModelSettings,
+    kmp::{custom_rules::CustomRuleSettings, kcl_check::KclCheckSettings, settings::KmpModelSettings},
+};
 use bevy::prelude::*;
 use bevy_pkv::PkvStore;
 use serde::{Deserialize, Serialize};
 
-use super::file_dialog::{DialogType, FileDialogResult};
+use super::{
+    file_dialog::{DialogType, FileDialogResult},
+    ui_state::SafeMode,
+};
 
 pub fn app_settings_plugin(app: &mut App) {
     app.add_systems(Startup, setup_app_settings.in_set(SetupAppSettingsSet))
@@ -20,6 +31,12 @@ pub struct AppSettings {
     pub camera: CameraSettings,
     pub kcl_model: KclModelSettings,
     pub kmp_model: KmpModelSettings,
+    pub nudge: NudgeSettings,
+    pub pick: PickSettings,
+    pub path_height_snap: PathHeightSnapSettings,
+    pub kcl_check: KclCheckSettings,
+    pub custom_rules: CustomRuleSettings,
+    pub blender: BlenderCoordSettings,
     pub open_course_kcl_in_dir: bool,
     pub increment: u32,
 }
@@ -29,6 +46,12 @@ impl Default for AppSettings {
             camera: CameraSettings::default(),
             kcl_model: KclModelSettings::default(),
             kmp_model: KmpModelSettings::default(),
+            nudge: NudgeSettings::default(),
+            pick: PickSettings::default(),
+            path_height_snap: PathHeightSnapSettings::default(),
+            kcl_check: KclCheckSettings::default(),
+            custom_rules: CustomRuleSettings::default(),
+            blender: BlenderCoordSettings::default(),
             open_course_kcl_in_dir: true,
             increment: 1,
         }
@@ -38,7 +61,11 @@ impl Default for AppSettings {
 #[derive(SystemSet, Hash, PartialEq, Eq, Clone, Debug)]
 pub struct SetupAppSettingsSet;
 
-pub fn setup_app_settings(mut commands: Commands, mut pkv: ResMut<PkvStore>) {
+pub fn setup_app_settings(mut commands: Commands, mut pkv: ResMut<PkvStore>, safe_mode: Res<SafeMode>) {
+    if **safe_mode {
+        commands.insert_resource(AppSettings::default());
+        return;
+    }
     // get the app settings if it exists, if not, set it to default
     // THIS LINE SHOULD BE REMOVED TO MAKE THIS ACTUALLY WORK
     pkv.set("settings", &AppSettings::default()).unwrap();
@@ -0,0 +1,132 @@
+use super::{ui_state::KmpFilePath, util::get_egui_ctx};
+use crate::viewer::{
+    edit::select::Selected,
+    kmp::{meshes_materials::section_memory, path::KmpPathNode, sections::KmpEditMode},
+};
+use bevy::{ecs::entity::EntityHashMap, prelude::*};
+use bevy_egui::egui;
+
+pub fn status_bar_plugin(app: &mut App) {
+    app.init_resource::<SelectionStats>()
+        .add_systems(Update, update_selection_stats);
+}
+
+/// Statistics about the current selection, shown in the status bar
+#[derive(Resource, Default)]
+pub struct SelectionStats {
+    pub count: usize,
+    pub centroid: Vec3,
+    /// Length of the path going through all the selected points, if they form a single unbroken run of a path
+    pub path_length: Option<f32>,
+}
+
+fn update_selection_stats(
+    mut stats: ResMut<SelectionStats>,
+    q_selected: Query<(Entity, &Transform, Option<&KmpPathNode>), With<Selected>>,
+) {
+    let count = q_selected.iter().len();
+    let centroid = if count > 0 {
+        q_selected.iter().map(|x| x.1.translation).sum::<Vec3>() / count as f32
+    } else {
+        Vec3::ZERO
+    };
+    let path_nodes: EntityHashMap<_> = q_selected
+        .iter()
+        .filter_map(|(e, transform, node)| node.map(|node| (e, (transform.translation, node))))
+        .collect();
+    let path_length = if path_nodes.len() == count {
+        contiguous_path_length(&path_nodes)
+    } else {
+        None
+    };
+
+    stats.count = count;
+    stats.centroid = centroid;
+    stats.path_length = path_length;
+}
+
+/// Returns the length of the path if the nodes can be walked from start to end without branching or gaps
+fn contiguous_path_length(nodes: &EntityHashMap<(Vec3, &KmpPathNode)>) -> Option<f32> {
+    if nodes.len() < 2 {
+        return None;
+    }
+    let is_start = |node: &KmpPathNode| !node.prev_nodes.iter().any(|e| nodes.contains_key(e));
+    let mut starts = nodes.iter().filter(|(_, (_, node))| is_start(node));
+    let (start_e, _) = starts.next()?;
+    if starts.next().is_some() {
+        return None;
+    }
+
+    let (mut cur_pos, mut cur_node) = nodes[start_e];
+    let mut length = 0.;
+    let mut visited = 1;
+    loop {
+        let mut next = cur_node.next_nodes.iter().filter(|e| nodes.contains_key(*e));
+        let Some(next_e) = next.next() else {
+            break;
+        };
+        if next.next().is_some() || visited == nodes.len() {
+            return None;
+        }
+        let (next_pos, next_node) = nodes[next_e];
+        length += cur_pos.distance(next_pos);
+        visited += 1;
+        (cur_pos, cur_node) = (next_pos, next_node);
+    }
+    (visited == nodes.len()).then_some(length)
+}
+
+pub fn show_status_bar(world: &mut World) {
+    let ctx = &get_egui_ctx(world);
+
+    egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if !world.contains_resource::<KmpFilePath>() {
+                ui.label("No KMP open");
+                return;
+            }
+            ui.label(world.resource::<KmpEditMode>().to_string());
+            ui.separator();
+            ui.label("Memory").on_hover_ui(|ui| show_section_memory(ui, world));
+
+            let stats = world.resource::<SelectionStats>();
+            if stats.count == 0 {
+                return;
+            }
+            ui.separator();
+            ui.label(format!("{} selected", stats.count));
+            ui.separator();
+            let Vec3 { x, y, z } = stats.centroid;
+            ui.label(format!("Centre: {x:.1}, {y:.1}, {z:.1}"));
+            if let Some(path_length) = stats.path_length {
+                ui.separator();
+                ui.label(format!("Path Length: {path_length:.1}"));
+            }
+        });
+    });
+}
+
+// roughly how much memory the points and materials of each section are taking up
+fn show_section_memory(ui: &mut egui::Ui, world: &mut World) {
+    let memory = section_memory(world);
+    egui::Grid::new("section_memory").striped(true).show(ui, |ui| {
+        ui.strong("Section");
+        ui.strong("Entities");
+        ui.strong("Materials");
+        ui.strong("Size");
+        ui.end_row();
+        for section in memory.iter() {
+            ui.label(section.section.to_string());
+            ui.label(section.entities.to_string());
+            ui.label(section.materials.to_string());
+            ui.label(format!("{:.1} KB", section.bytes as f32 / 1024.));
+            ui.end_row();
+        }
+        let total: usize = memory.iter().map(|x| x.bytes).sum();
+        ui.strong("Total");
+        ui.label("");
+        ui.label("");
+        ui.strong(format!("{:.1} KB", total as f32 / 1024.));
+        ui.end_row();
+    });
+}
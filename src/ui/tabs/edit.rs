@@ -4,20 +4,32 @@ use crate::{
         multi_edit::{checkbox_multi_edit, combobox_enum_multi_edit, drag_value_multi_edit, map, rotation_multi_edit},
         DragSpeed, Icons, LinkSelectBtnType,
     },
-    util::{give_me_a_mut, iter_mut_from_entities},
+    util::{give_me_a_mut, iter_mut_from_entities, kmp_file::ExtendedPresence},
     viewer::{
-        edit::{link_select_mode::LinkSelectMode, select::Selected},
+        edit::{
+            link_select_mode::LinkSelectMode,
+            pin::{pin, unpin, Pinned, MAX_PINNED},
+            select::Selected,
+            transform_history::TransformHistory,
+        },
         kmp::{
-            checkpoints::{CheckpointRespawnLink, GetSelectedCheckpoints},
+            checkpoints::{abrupt_width_change, checkpoint_stats, CheckpointRespawnLink, GetSelectedCheckpoints},
             components::{
-                AreaKind, AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, CheckpointKind, EnemyPathPoint,
-                ItemPathPoint, KmpCamera, KmpCameraIntroStart, Object, PathOverallStart, RespawnPoint, RoutePoint,
-                RouteSettings, StartPoint, TrackInfo, TransformEditOptions,
+                AreaKind, AreaPoint, BattleDispatchPoint, BattleFinishPoint, CannonPoint, Checkpoint, CheckpointKind,
+                EnemyPathPoint, ItemPathPoint, KmpCamera, KmpCameraIntroStart, Object, PathOverallStart, RespawnPoint,
+                RoutePoint, RouteSettings, StartPoint, TrackInfo, TrackType, TransformEditOptions,
             },
+            kcl_check::PathKclWarning,
+            leniency::{apply_leniency_suggestion, measure_track_widths, LeniencySuggestion},
             ordering::OrderId,
-            path::{EntityPathGroups, PathType, RecalcPaths, ToPathType},
+            path::{EntityPathGroups, KmpPathNode, PathType, RecalcPaths, ToPathType},
+            path_tools::{selected_chain, PathResampleSpacing, ResamplePath, MIN_SPACING},
+            respawn_links::{LinkNearestRespawns, RespawnLinkMode},
+            reverse_path::ReversePaths,
             routes::{GetRouteStart, RouteLink, RouteLinkedEntities},
+            schema::{FieldSchema, KmpSchema},
             sections::KmpEditMode,
+            start_points::{player_index_name, player_index_options, start_point_warnings},
         },
     },
 };
@@ -35,10 +47,13 @@ use std::{
     fmt::Display,
     ops::{AddAssign, Sub, SubAssign},
 };
+use strum_macros::EnumIter;
 
 pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
     edit_track_info(ui, world);
 
+    show_kcl_warnings(ui, world);
+
     edit_component::<(Option<&TransformEditOptions>, &mut Transform), ()>(ui, world, "Transform", |ui, items, _| {
         let all_hide_rot = items.iter().all(|x| x.0.is_some_and(|x| x.hide_rotation));
         let all_hide_y_tr = items.iter().all(|x| x.0.is_some_and(|x| x.hide_y_translation));
@@ -51,29 +66,92 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
 
         if !all_hide_rot {
             edit_spacing(ui);
-            rotation_multi_edit(ui, items.iter_mut().map(|(_, x)| &mut **x), |ui, rots| {
+            // the transforms are only marked as changed if the rotation is actually edited
+            let transforms = items.iter_mut().map(|(_, x)| x.bypass_change_detection());
+            let changed = rotation_multi_edit(ui, transforms, |ui, rots| {
                 give_me_a_mut(rots, |rots| {
                     let [x, y, z] = vec3_drag_value_edit_row(ui, "Rotation", DragSpeed::Slow, rots);
                     (x, y, z)
                 })
             });
+            if changed {
+                items.iter_mut().for_each(|(_, x)| x.set_changed());
+            }
         }
     });
 
+    show_transform_history(ui, world);
+
+    show_pin_buttons(ui, world);
+
+    show_start_point_warnings(ui, world);
+
     edit_component::<&mut StartPoint, ()>(ui, world, "Start Point", |ui, items, _| {
-        drag_value_edit_row(ui, "Player Index", DragSpeed::Slow, map!(items => player_index));
+        set_field_docs::<StartPoint>(ui);
+        edit_row(ui, "Player", true, |ui| {
+            let first = items[0].player_index;
+            let selected_text = if items.iter().all(|x| x.player_index == first) {
+                player_index_name(first)
+            } else {
+                String::new()
+            };
+            egui::ComboBox::from_id_source("start_point_player")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for index in player_index_options() {
+                        if ui.selectable_label(index == first, player_index_name(index)).clicked() {
+                            for item in items.iter_mut() {
+                                item.player_index = index;
+                            }
+                        }
+                    }
+                })
+                .response
+                .on_hover_text_at_pointer(
+                    "Race tracks use one start point for all players, and battle arenas use one start point for each player",
+                );
+        });
     });
 
-    edit_component::<(&mut EnemyPathPoint, Entity), PathStartBtn<EnemyPathPoint>>(
+    edit_component::<
+        (&mut EnemyPathPoint, Entity),
+        (
+            PathStartBtn<EnemyPathPoint>,
+            Option<Res<TrackInfo>>,
+            Query<(), With<BattleDispatchPoint>>,
+            Commands,
+        ),
+    >(
         ui,
         world,
         "Enemy Point",
-        |ui, items, mut path_start_btn| {
+        |ui, items, (mut path_start_btn, track_info, q_dispatch, mut commands)| {
+            set_field_docs::<EnemyPathPoint>(ui);
             drag_value_edit_row(ui, "Leniency", DragSpeed::Slow, map!(items => 0 leniency));
             combobox_edit_row(ui, "Setting 1", map!(items => 0 setting_1));
             combobox_edit_row(ui, "Setting 2", map!(items => 0 setting_2));
             drag_value_edit_row(ui, "Setting 3", DragSpeed::Slow, map!(items => 0 setting_3));
             edit_spacing(ui);
+            if track_info.is_some_and(|x| x.track_type == TrackType::Battle) {
+                edit_row(ui, "Dispatch Point", false, |ui| {
+                    let num_dispatch = items.iter().filter(|x| q_dispatch.contains(x.1)).count();
+                    let mut is_dispatch = num_dispatch > 0;
+                    let intermediate = is_dispatch && num_dispatch < items.len();
+                    let res = ui
+                        .add(Checkbox::without_text(&mut is_dispatch).indeterminate(intermediate))
+                        .on_hover_text_at_pointer("Whether CPUs choose which way to go next at this point");
+                    if res.changed() {
+                        for (_, e) in items.iter() {
+                            if is_dispatch {
+                                commands.entity(*e).insert(BattleDispatchPoint);
+                            } else {
+                                commands.entity(*e).remove::<BattleDispatchPoint>();
+                            }
+                        }
+                    }
+                });
+                edit_spacing(ui);
+            }
             path_start_btn.show(ui, items.iter().map(|x| x.1));
         },
     );
@@ -83,6 +161,7 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
         world,
         "Item Point",
         |ui, items, mut path_start_btn| {
+            set_field_docs::<ItemPathPoint>(ui);
             drag_value_edit_row(ui, "Bullet Control", DragSpeed::Slow, map!(items => 0 bullet_control));
             edit_spacing(ui);
             combobox_edit_row(ui, "Bullet Height", map!(items => 0 bullet_height));
@@ -93,6 +172,10 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
         },
     );
 
+    show_path_resample(ui, world);
+    show_leniency_suggestion(ui, world);
+    show_reverse_paths(ui, world);
+
     edit_component_entities::<
         GetSelectedCheckpoints,
         (
@@ -101,6 +184,7 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
             Query<&CheckpointRespawnLink>,
             Query<&mut Visibility>,
             Query<&OrderId>,
+            ResMut<RespawnLinkMode>,
             Commands,
         ),
     >(
@@ -110,7 +194,16 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
         "Checkpoint",
         |ui,
          entities,
-         (mut q_cp, mut path_start_btn, q_cp_respawn_link, mut q_visibility, q_order_id, mut commands)| {
+         (
+            mut q_cp,
+            mut path_start_btn,
+            q_cp_respawn_link,
+            mut q_visibility,
+            q_order_id,
+            mut respawn_link_mode,
+            mut commands,
+        )| {
+            set_field_docs::<Checkpoint>(ui);
             let mut items = iter_mut_from_entities(&entities, &mut q_cp);
             combobox_edit_row(ui, "Type", map!(items => kind));
 
@@ -195,16 +288,35 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
                     commands.insert_resource(LinkSelectMode::<RespawnPoint>::new(entities.clone()));
                 }
             });
+            edit_row(ui, "Auto Respawn", false, |ui| {
+                combobox_enum(ui, &mut *respawn_link_mode, Some(130.)).on_hover_text_at_pointer(
+                    "Behind Along Path picks the last respawn point before each checkpoint, Nearest picks the closest",
+                );
+                if ui
+                    .button("Link")
+                    .on_hover_text_at_pointer("Link each selected checkpoint to a respawn point")
+                    .clicked()
+                {
+                    let checkpoints = entities.iter().copied().collect();
+                    commands.add(move |world: &mut World| {
+                        world.send_event(LinkNearestRespawns(checkpoints));
+                    });
+                }
+            });
 
             path_start_btn.show(ui, entities);
         },
     );
 
+    show_checkpoint_stats(ui, world);
+
     edit_component::<&mut RespawnPoint, ()>(ui, world, "Respawn Point", |ui, items, _| {
+        set_field_docs::<RespawnPoint>(ui);
         drag_value_edit_row(ui, "Sound Trigger", DragSpeed::Slow, map!(items => sound_trigger));
     });
 
     edit_component::<(&mut Object, Entity), RouteEditRowParam>(ui, world, "Object", |ui, items, mut route_edit_row| {
+        set_field_docs::<Object>(ui);
         vec3_drag_value_edit_row(ui, "Scale", DragSpeed::Fast, map!(items => 0 scale));
         edit_spacing(ui);
         drag_value_edit_row(ui, "ID", DragSpeed::Slow, map!(items => 0 object_id));
@@ -218,6 +330,9 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
             );
         }
         edit_spacing(ui);
+        drag_value_edit_row(ui, "Presence", DragSpeed::Slow, map!(items => 0 presence));
+        extended_presence_edit_rows(ui, map!(items => 0 extended_presence).collect());
+        edit_spacing(ui);
         route_edit_row.show(ui, items.iter().map(|x| x.1));
     });
 
@@ -233,6 +348,7 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
         |r| r.get_selected(),
         "Route Settings",
         |ui, entities, (mut q, mut q_visibility)| {
+            set_field_docs::<RouteSettings>(ui);
             let mut items = iter_mut_from_entities(&entities, &mut q);
 
             checkbox_edit_row(ui, "Smooth Motion", map!(items => 0 smooth_motion));
@@ -282,6 +398,7 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
     );
 
     edit_component::<&mut RoutePoint, ()>(ui, world, "Route Point", |ui, items, _| {
+        set_field_docs::<RoutePoint>(ui);
         drag_value_edit_row(ui, "Settings", DragSpeed::Slow, map!(items => settings));
         drag_value_edit_row(
             ui,
@@ -291,71 +408,78 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
         );
     });
 
-    edit_component::<&mut AreaPoint, ()>(ui, world, "Area", |ui, items, _| {
-        vec3_drag_value_edit_row(ui, "Scale", DragSpeed::Slow, map!(items => scale));
-        edit_spacing(ui);
-        combobox_edit_row(ui, "Shape", map!(items => shape));
-        drag_value_edit_row(ui, "Priority", DragSpeed::Slow, map!(items => priority));
-        combobox_edit_row(ui, "Type", map!(items => kind));
-
-        // for now, area type UI settings will only work when 1 point is selected
-        if let Some(item) = items.iter_mut().next() {
-            match &mut item.kind {
-                AreaKind::Camera { cam_index } => {
-                    edit_row(ui, "Camera Index", true, |ui| {
-                        ui.add(DragValue::new(cam_index).speed(DragSpeed::Slow));
-                    });
-                }
-                AreaKind::EnvEffect(env_effect_obj) => {
-                    edit_row(ui, "Env Effect Object", true, |ui| {
-                        combobox_enum(ui, env_effect_obj, None);
-                    });
-                }
-                AreaKind::FogEffect { bfg_entry, setting_2 } => {
-                    edit_row(ui, "BFG Entry", true, |ui| {
-                        ui.add(DragValue::new(bfg_entry).speed(DragSpeed::Slow));
-                    });
-                    edit_row(ui, "Setting 2", true, |ui| {
-                        ui.add(DragValue::new(setting_2).speed(DragSpeed::Slow));
-                    });
-                }
-                AreaKind::MovingRoad => {
-                    // TODO - add route link here
-                }
-                AreaKind::MinimapControl { setting_1, setting_2 } => {
-                    edit_row(ui, "Setting 1", true, |ui| {
-                        ui.add(DragValue::new(setting_1).speed(DragSpeed::Slow));
-                    });
-                    edit_row(ui, "Setting 2", true, |ui| {
-                        ui.add(DragValue::new(setting_2).speed(DragSpeed::Slow));
-                    });
-                }
-                AreaKind::BloomEffect { bblm_file, fade_time } => {
-                    edit_row(ui, "BBLM File", true, |ui| {
-                        ui.add(DragValue::new(bblm_file).speed(DragSpeed::Slow));
-                    });
-                    edit_row(ui, "Fade Time", true, |ui| {
-                        ui.add(DragValue::new(fade_time).speed(DragSpeed::Slow));
-                    });
-                }
-                AreaKind::ObjectGroup { group_id } | AreaKind::ObjectUnload { group_id } => {
-                    edit_row(ui, "Group ID", true, |ui| {
-                        ui.add(DragValue::new(group_id).speed(DragSpeed::Slow));
-                    });
+    edit_component::<(&mut AreaPoint, Entity), RouteEditRowParam>(
+        ui,
+        world,
+        "Area",
+        |ui, items, mut route_edit_row| {
+            set_field_docs::<AreaPoint>(ui);
+            vec3_drag_value_edit_row(ui, "Scale", DragSpeed::Slow, map!(items => 0 scale));
+            edit_spacing(ui);
+            combobox_edit_row(ui, "Shape", map!(items => 0 shape));
+            drag_value_edit_row(ui, "Priority", DragSpeed::Slow, map!(items => 0 priority));
+            combobox_edit_row(ui, "Type", map!(items => 0 kind));
+
+            // for now, area type UI settings will only work when 1 point is selected
+            if let Some((item, e)) = items.iter_mut().next() {
+                match &mut item.kind {
+                    AreaKind::Camera { cam_index } => {
+                        edit_row(ui, "Camera Index", true, |ui| {
+                            ui.add(DragValue::new(cam_index).speed(DragSpeed::Slow));
+                        });
+                    }
+                    AreaKind::EnvEffect(env_effect_obj) => {
+                        edit_row(ui, "Env Effect Object", true, |ui| {
+                            combobox_enum(ui, env_effect_obj, None);
+                        });
+                    }
+                    AreaKind::FogEffect { bfg_entry, setting_2 } => {
+                        edit_row(ui, "BFG Entry", true, |ui| {
+                            ui.add(DragValue::new(bfg_entry).speed(DragSpeed::Slow));
+                        });
+                        edit_row(ui, "Setting 2", true, |ui| {
+                            ui.add(DragValue::new(setting_2).speed(DragSpeed::Slow));
+                        });
+                    }
+                    AreaKind::MovingRoad => {
+                        route_edit_row.show(ui, [*e]);
+                    }
+                    AreaKind::MinimapControl { setting_1, setting_2 } => {
+                        edit_row(ui, "Setting 1", true, |ui| {
+                            ui.add(DragValue::new(setting_1).speed(DragSpeed::Slow));
+                        });
+                        edit_row(ui, "Setting 2", true, |ui| {
+                            ui.add(DragValue::new(setting_2).speed(DragSpeed::Slow));
+                        });
+                    }
+                    AreaKind::BloomEffect { bblm_file, fade_time } => {
+                        edit_row(ui, "BBLM File", true, |ui| {
+                            ui.add(DragValue::new(bblm_file).speed(DragSpeed::Slow));
+                        });
+                        edit_row(ui, "Fade Time", true, |ui| {
+                            ui.add(DragValue::new(fade_time).speed(DragSpeed::Slow));
+                        });
+                    }
+                    AreaKind::ObjectGroup { group_id } | AreaKind::ObjectUnload { group_id } => {
+                        edit_row(ui, "Group ID", true, |ui| {
+                            ui.add(DragValue::new(group_id).speed(DragSpeed::Slow));
+                        });
+                    }
+                    // other types of area don't have any settings
+                    _ => {}
                 }
-                // other types of area don't have any settings
-                _ => {}
             }
-        }
-        edit_spacing(ui);
-        checkbox_edit_row(ui, "Always Show Area", map!(items => show_area));
-    });
+            edit_spacing(ui);
+            checkbox_edit_row(ui, "Always Show Area", map!(items => 0 show_area));
+        },
+    );
 
     edit_component::<(&mut KmpCamera, Entity), (RouteEditRowParam, Query<Entity, With<KmpCameraIntroStart>>, Commands)>(
         ui,
         world,
         "Camera",
         |ui, items, (mut route_edit_row, q_cam_start, mut commands)| {
+            set_field_docs::<KmpCamera>(ui);
             edit_row(ui, "Intro Start", false, |ui| {
                 let mut intro_start_in_items = items.iter().any(|x| q_cam_start.contains(x.1));
                 let intermediate = intro_start_in_items && items.len() > 1;
@@ -400,6 +524,7 @@ pub fn show_edit_tab(ui: &mut Ui, world: &mut World) {
     );
 
     edit_component::<&mut CannonPoint, ()>(ui, world, "Cannon Point", |ui, items, _| {
+        set_field_docs::<CannonPoint>(ui);
         combobox_edit_row(ui, "Shoot Effect", map!(items => shoot_effect));
     });
 
@@ -414,32 +539,42 @@ fn edit_track_info(ui: &mut Ui, world: &mut World) {
     let Some(mut track_info) = world.get_resource_mut::<TrackInfo>() else {
         return;
     };
+    // edit a copy, so the track info is only marked as changed when something is actually edited
+    let mut track_info_cp = track_info.clone();
 
     framed_collapsing_header("Track Info", ui, |ui| {
+        set_field_docs::<TrackInfo>(ui);
         edit_row(ui, "Track Type", false, |ui| {
-            combobox_enum(ui, &mut track_info.track_type, None);
+            combobox_enum(ui, &mut track_info_cp.track_type, None);
         });
+        let battle = track_info_cp.track_type == TrackType::Battle;
         edit_row(ui, "Lap Count", true, |ui| {
-            ui.add(DragValue::new(&mut track_info.lap_count).speed(DragSpeed::Slow))
+            ui.add_enabled(
+                !battle,
+                DragValue::new(&mut track_info_cp.lap_count).speed(DragSpeed::Slow),
+            )
+            .on_disabled_hover_text("Battle arenas don't have laps")
         });
         edit_row(ui, "Speed Mod", true, |ui| {
-            ui.add(DragValue::new(&mut track_info.speed_mod).speed(DragSpeed::Slow))
+            ui.add(DragValue::new(&mut track_info_cp.speed_mod).speed(DragSpeed::Slow))
         });
         edit_spacing(ui);
         edit_row(ui, "Lens Flare Colour", false, |ui| {
-            ui.color_edit_button_srgba_unmultiplied(&mut track_info.lens_flare_color);
+            ui.color_edit_button_srgba_unmultiplied(&mut track_info_cp.lens_flare_color);
         });
         edit_row(ui, "Lens Flare Flashing", false, |ui| {
-            ui.add(Checkbox::without_text(&mut track_info.lens_flare_flashing));
+            ui.add(Checkbox::without_text(&mut track_info_cp.lens_flare_flashing));
         });
         edit_spacing(ui);
         edit_row(ui, "First Player Pos", false, |ui| {
-            combobox_enum(ui, &mut track_info.first_player_pos, None);
+            combobox_enum(ui, &mut track_info_cp.first_player_pos, None);
         });
         edit_row(ui, "Narrow Player Spacing", false, |ui| {
-            ui.add(Checkbox::without_text(&mut track_info.narrow_player_spacing));
+            ui.add(Checkbox::without_text(&mut track_info_cp.narrow_player_spacing));
         });
     });
+    track_info.set_if_neq(track_info_cp);
+    clear_field_docs(ui);
     edit_spacing(ui);
 }
 
@@ -460,6 +595,7 @@ fn edit_component<D: QueryData + 'static, P: SystemParam + 'static>(
         let title = edit_component_title(title, items.len());
 
         framed_collapsing_header(title, ui, |ui| add_body(ui, &mut items, p));
+        clear_field_docs(ui);
         edit_spacing(ui);
     }
     system_state.apply(world);
@@ -485,6 +621,7 @@ fn edit_component_entities<PEntities: SystemParam + 'static, P: SystemParam + 's
     let title = edit_component_title(title, entities.len());
 
     framed_collapsing_header(title, ui, |ui| add_body(ui, entities, paramset.p1()));
+    clear_field_docs(ui);
     edit_spacing(ui);
 
     ss.apply(world);
@@ -521,6 +658,357 @@ impl<T: Component + ToPathType> PathStartBtn<'_, '_, T> {
     }
 }
 
+// step back and forth through the recent transforms of the selected point, to compare candidate positions
+fn show_transform_history(ui: &mut Ui, world: &mut World) {
+    let mut q = world.query_filtered::<(&mut TransformHistory, &mut Transform), With<Selected>>();
+    let mut items: Vec<_> = q.iter_mut(world).collect();
+    let [(history, transform)] = items.as_mut_slice() else {
+        return;
+    };
+    if history.len() < 2 {
+        return;
+    }
+    framed_collapsing_header("Transform History", ui, |ui| {
+        let last = history.len() - 1;
+        let mut index = history.cursor();
+        edit_row(ui, "Step", true, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(index > 0, egui::Button::new("◀")).clicked() {
+                    index -= 1;
+                }
+                ui.add(egui::Slider::new(&mut index, 0..=last).show_value(false));
+                if ui.add_enabled(index < last, egui::Button::new("▶")).clicked() {
+                    index += 1;
+                }
+                ui.label(format!("{}/{}", index + 1, last + 1));
+            });
+        });
+        if index != history.cursor() {
+            if let Some(new_transform) = history.step_to(index) {
+                **transform = new_transform;
+            }
+        }
+    });
+    edit_spacing(ui);
+}
+
+// pin the selected points so their values are always shown in the viewport
+fn show_pin_buttons(ui: &mut Ui, world: &mut World) {
+    let selected: Vec<_> = world
+        .query_filtered::<(Entity, Has<Pinned>), (With<Selected>, With<Transform>)>()
+        .iter(world)
+        .collect();
+    if selected.is_empty() {
+        return;
+    }
+    let pinned_count = world.query::<&Pinned>().iter(world).count();
+    let all_pinned = selected.iter().all(|x| x.1);
+    ui.horizontal(|ui| {
+        if all_pinned {
+            if ui.button("Unpin").clicked() {
+                unpin(world, selected.iter().map(|x| x.0));
+            }
+        } else if ui
+            .add_enabled(pinned_count < MAX_PINNED, egui::Button::new("Pin"))
+            .on_hover_text_at_pointer("Always show the values of the selected points in the viewport")
+            .on_disabled_hover_text(format!("Only {MAX_PINNED} points can be pinned at once"))
+            .clicked()
+        {
+            pin(world, selected.iter().map(|x| x.0));
+        }
+    });
+    edit_spacing(ui);
+}
+
+// respace the selected chain of enemy or item points evenly along a smooth curve
+fn show_path_resample(ui: &mut Ui, world: &mut World) {
+    let chain = selected_chain::<EnemyPathPoint>(world).or_else(|| selected_chain::<ItemPathPoint>(world));
+    let Some(chain) = chain else {
+        return;
+    };
+    let mut resample = false;
+    framed_collapsing_header("Resample Path", ui, |ui| {
+        let mut spacing = world.resource_mut::<PathResampleSpacing>();
+        edit_row(ui, "Spacing", true, |ui| {
+            ui.add(
+                DragValue::new(&mut **spacing)
+                    .speed(DragSpeed::Fast)
+                    .range(MIN_SPACING..=f32::MAX),
+            )
+            .on_hover_text_at_pointer("The distance to aim for between each point along the curve")
+        });
+        ui.with_layout(Layout::top_down(Align::Center), |ui| {
+            resample = ui
+                .button(format!("Resample {} Points", chain.len()))
+                .on_hover_text_at_pointer(
+                    "Replace the points between the first and last selected with points evenly spaced along a smooth curve, blending their settings",
+                )
+                .clicked();
+        });
+    });
+    edit_spacing(ui);
+    if resample {
+        world.send_event_default::<ResamplePath>();
+    }
+}
+
+// suggest leniencies for the selected enemy points from how far apart the walls are around them
+fn show_leniency_suggestion(ui: &mut Ui, world: &mut World) {
+    let selected: Vec<_> = world
+        .query_filtered::<Entity, (With<EnemyPathPoint>, With<Selected>)>()
+        .iter(world)
+        .collect();
+    if selected.is_empty() {
+        return;
+    }
+    let (mut measure, mut apply) = (false, false);
+    framed_collapsing_header("Suggest Leniency", ui, |ui| {
+        let mut suggestion = world.resource_mut::<LeniencySuggestion>();
+        edit_row(ui, "Scale", true, |ui| {
+            ui.add(
+                DragValue::new(&mut suggestion.scale)
+                    .speed(0.0001)
+                    .range(0.0..=f32::MAX),
+            )
+            .on_hover_text_at_pointer(
+                "The leniency given for each unit of distance between a point and the nearest wall beside it",
+            )
+        });
+        ui.with_layout(Layout::top_down(Align::Center), |ui| {
+            measure = ui
+                .button(format!("Measure Track Width at {} Points", selected.len()))
+                .on_hover_text_at_pointer("Find the nearest wall either side of the path at each selected point")
+                .clicked();
+            if !suggestion.is_for(&selected) {
+                return;
+            }
+            let leniencies: Vec<_> = suggestion.leniencies().map(|x| x.1).collect();
+            let (Some(min), Some(max)) = (
+                leniencies.iter().copied().reduce(f32::min),
+                leniencies.iter().copied().reduce(f32::max),
+            ) else {
+                ui.label("No walls were found beside the selected points");
+                return;
+            };
+            ui.label(format!("Suggested leniency: {min:.2} to {max:.2}"));
+            let missing = selected.len() - leniencies.len();
+            if missing > 0 {
+                ui.label(format!(
+                    "No walls were found beside {missing} of the points, so they won't be changed"
+                ));
+            }
+            apply = ui
+                .button(format!("Set Leniency of {} Points", leniencies.len()))
+                .clicked();
+        });
+    });
+    edit_spacing(ui);
+    if measure {
+        let widths = measure_track_widths(world, &selected);
+        world.resource_mut::<LeniencySuggestion>().widths = widths;
+    }
+    if apply {
+        apply_leniency_suggestion(world);
+    }
+}
+
+// turn round the paths the selected points are on, and the selected start points
+fn show_reverse_paths(ui: &mut Ui, world: &mut World) {
+    let selected = world
+        .query_filtered::<(), (Or<(With<KmpPathNode>, With<StartPoint>)>, With<Selected>)>()
+        .iter(world)
+        .count();
+    if selected == 0 {
+        return;
+    }
+    let mut reverse = false;
+    framed_collapsing_header("Reverse Direction", ui, |ui| {
+        ui.with_layout(Layout::top_down(Align::Center), |ui| {
+            reverse = ui
+                .button("Reverse Direction")
+                .on_hover_text_at_pointer(
+                    "Turn round every path the selected points are on, swapping the sides of checkpoints, and turn the selected start points to face the other way",
+                )
+                .clicked();
+        });
+    });
+    edit_spacing(ui);
+    if reverse {
+        world.send_event_default::<ReversePaths>();
+    }
+}
+
+// the width of each selected checkpoint and the area of the quad to the next, flagging big changes in width
+fn show_checkpoint_stats(ui: &mut Ui, world: &mut World) {
+    let mut ss = SystemState::<GetSelectedCheckpoints>::new(world);
+    let mut selected: Vec<_> = ss.get_mut(world).get_entities().into_iter().collect();
+    if selected.is_empty() {
+        return;
+    }
+    let index = |e: Entity| world.get::<OrderId>(e).map(|x| x.0).unwrap_or_default();
+    selected.sort_by_key(|e| index(*e));
+    let stats: Vec<_> = selected
+        .iter()
+        .filter_map(|e| checkpoint_stats(world, *e).map(|x| (index(*e), x)))
+        .collect();
+    let warnings: Vec<_> = selected
+        .iter()
+        .flat_map(|e| {
+            let next_nodes = world
+                .get::<KmpPathNode>(*e)
+                .map(|x| x.next_nodes.clone())
+                .unwrap_or_default();
+            next_nodes.into_iter().filter_map(|next| {
+                abrupt_width_change(world, *e, next).map(|ratio| {
+                    format!(
+                        "Checkpoints {} and {} differ in width by {ratio:.1} times",
+                        index(*e),
+                        index(next)
+                    )
+                })
+            })
+        })
+        .collect();
+    let widths: Vec<_> = stats.iter().map(|x| x.1.width).collect();
+
+    framed_collapsing_header(edit_component_title("Checkpoint Sizes", stats.len()), ui, |ui| {
+        if widths.len() > 1 {
+            let min = widths.iter().copied().fold(f32::MAX, f32::min);
+            let max = widths.iter().copied().fold(f32::MIN, f32::max);
+            let average = widths.iter().sum::<f32>() / widths.len() as f32;
+            ui.label(format!("Width: {min:.0} to {max:.0}, {average:.0} on average"));
+        }
+        egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+            egui::Grid::new("checkpoint_sizes").striped(true).show(ui, |ui| {
+                ui.label("Checkpoint");
+                ui.label("Width");
+                ui.label("Quad Area")
+                    .on_hover_text_at_pointer("The area between each checkpoint and the next, seen from above");
+                ui.end_row();
+                for (index, stats) in stats.iter() {
+                    ui.label(index.to_string());
+                    ui.label(format!("{:.0}", stats.width));
+                    let areas: Vec<_> = stats.quads.iter().map(|x| format!("{:.0}", x.1)).collect();
+                    ui.label(if areas.is_empty() { "-".into() } else { areas.join(", ") });
+                    ui.end_row();
+                }
+            });
+        });
+        for warning in warnings {
+            ui.colored_label(egui::Color32::from_rgb(255, 69, 0), warning);
+        }
+    });
+    edit_spacing(ui);
+}
+
+fn show_kcl_warnings(ui: &mut Ui, world: &mut World) {
+    let warnings: Vec<_> = world
+        .query_filtered::<&PathKclWarning, With<Selected>>()
+        .iter(world)
+        .copied()
+        .collect();
+    if warnings.is_empty() {
+        return;
+    }
+    framed_collapsing_header(edit_component_title("KCL Warnings", warnings.len()), ui, |ui| {
+        for warning in warnings {
+            ui.colored_label(egui::Color32::from_rgb(255, 69, 0), warning.to_string());
+        }
+    });
+    edit_spacing(ui);
+}
+
+fn show_start_point_warnings(ui: &mut Ui, world: &mut World) {
+    if *world.resource::<KmpEditMode>() != KmpEditMode::StartPoints {
+        return;
+    }
+    let warnings = start_point_warnings(world);
+    if warnings.is_empty() {
+        return;
+    }
+    framed_collapsing_header(edit_component_title("Start Point Warnings", warnings.len()), ui, |ui| {
+        for warning in warnings {
+            ui.colored_label(egui::Color32::from_rgb(255, 69, 0), warning);
+        }
+    });
+    edit_spacing(ui);
+}
+
+// which of the ways of using LE-CODE extended presence is picked in the combobox
+#[derive(strum_macros::Display, EnumIter, PartialEq, Clone, Copy)]
+enum ExtendedPresenceKind {
+    None,
+    Flags,
+    #[strum(serialize = "Definition Object")]
+    DefinitionObject,
+}
+impl From<ExtendedPresence> for ExtendedPresenceKind {
+    fn from(value: ExtendedPresence) -> Self {
+        match value {
+            ExtendedPresence::None => Self::None,
+            ExtendedPresence::Flags(_) => Self::Flags,
+            ExtendedPresence::DefinitionObject(_) => Self::DefinitionObject,
+        }
+    }
+}
+
+// edit extended presence as named flag checkboxes or the index of a definition object, depending on its kind
+fn extended_presence_edit_rows(ui: &mut Ui, mut items: Vec<Mut<ExtendedPresence>>) {
+    let mut kinds: Vec<_> = items.iter().map(|x| ExtendedPresenceKind::from(**x)).collect();
+    let kind_changed = give_me_a_mut(&mut kinds, |kinds| {
+        combobox_edit_row(ui, "Extended Presence", kinds).changed()
+    });
+    if kind_changed {
+        for (item, kind) in items.iter_mut().zip(kinds.iter()) {
+            if ExtendedPresenceKind::from(**item) == *kind {
+                continue;
+            }
+            **item = match kind {
+                ExtendedPresenceKind::None => ExtendedPresence::None,
+                ExtendedPresenceKind::Flags => ExtendedPresence::Flags(0),
+                ExtendedPresenceKind::DefinitionObject => ExtendedPresence::DefinitionObject(0),
+            };
+        }
+    }
+
+    // the flags or definition object can only be edited together if every item is the same kind
+    if kinds.iter().any(|x| *x != kinds[0]) {
+        return;
+    }
+    match kinds[0] {
+        ExtendedPresenceKind::None => {}
+        ExtendedPresenceKind::Flags => {
+            for (bit, name) in ExtendedPresence::FLAGS {
+                let mut set: Vec<_> = items.iter().map(|x| x.to_raw() & bit != 0).collect();
+                if !give_me_a_mut(&mut set, |set| checkbox_edit_row(ui, name, set).changed()) {
+                    continue;
+                }
+                for (item, set) in items.iter_mut().zip(set) {
+                    let raw = item.to_raw();
+                    **item = ExtendedPresence::Flags(if set { raw | bit } else { raw & !bit });
+                }
+            }
+        }
+        ExtendedPresenceKind::DefinitionObject => {
+            let mut indexes: Vec<_> = items
+                .iter()
+                .map(|x| match **x {
+                    ExtendedPresence::DefinitionObject(index) => index,
+                    _ => 0,
+                })
+                .collect();
+            let changed = give_me_a_mut(&mut indexes, |indexes| {
+                drag_value_edit_row(ui, "Definition Object", DragSpeed::Slow, indexes).changed()
+            });
+            if changed {
+                for (item, index) in items.iter_mut().zip(indexes) {
+                    **item = ExtendedPresence::DefinitionObject(index.min(ExtendedPresence::MAX_DEFINITION_OBJECT));
+                }
+            }
+        }
+    }
+}
+
 fn edit_component_title(name: impl Into<String>, num: usize) -> String {
     let name = name.into();
     if num > 1 {
@@ -530,6 +1018,17 @@ fn edit_component_title(name: impl Into<String>, num: usize) -> String {
     }
 }
 
+// the docs of the component being edited, which edit rows look their label up in to show as a tooltip
+fn field_docs_id() -> egui::Id {
+    egui::Id::new("edit_field_docs")
+}
+fn set_field_docs<T: KmpSchema>(ui: &mut Ui) {
+    ui.data_mut(|d| d.insert_temp(field_docs_id(), T::FIELDS));
+}
+fn clear_field_docs(ui: &mut Ui) {
+    ui.data_mut(|d| d.remove::<&'static [FieldSchema]>(field_docs_id()));
+}
+
 pub fn edit_spacing(ui: &mut Ui) {
     ui.vertical(|ui| ui.add_space(3.));
 }
@@ -582,10 +1081,17 @@ pub fn edit_row<R>(
     justified: bool,
     add_contents: impl FnOnce(&mut Ui) -> R,
 ) -> R {
+    let label: WidgetText = label.into();
+    let field = ui
+        .data(|d| d.get_temp::<&'static [FieldSchema]>(field_docs_id()))
+        .and_then(|fields| fields.iter().find(|x| x.matches(label.text())));
     ui.horizontal(|ui| {
         ui.columns(2, |ui| {
             ui[0].with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.add(egui::Label::new(label).truncate());
+                let res = ui.add(egui::Label::new(label).truncate());
+                if let Some(field) = field {
+                    res.on_hover_text_at_pointer(field.tooltip());
+                }
             });
             if justified {
                 ui[1].centered_and_justified(add_contents)
@@ -0,0 +1,122 @@
+use crate::{
+    ui::ui_state::KmpFilePath,
+    viewer::{
+        camera::FrameCameras,
+        edit::select::Selected,
+        kmp::{
+            find_value::{FindValue, FoundValues},
+            sections::KmpEditMode,
+        },
+    },
+};
+use bevy::prelude::*;
+use bevy_egui::egui::{self, Ui};
+use strum::IntoEnumIterator;
+
+// a single value like '7', or an inclusive range like '0..10' or '0 to 10'
+fn parse_search(s: &str) -> Option<FindValue> {
+    let s = s.trim();
+    let (min, max) = s.split_once("..").or_else(|| s.split_once(" to ")).unwrap_or((s, s));
+    let (min, max): (f64, f64) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+    Some(FindValue {
+        min: min.min(max),
+        max: min.max(max),
+    })
+}
+
+pub fn show_find_tab(ui: &mut Ui, world: &mut World) {
+    let query_id = ui.id().with("find_value_query");
+    let mut query = ui.data(|d| d.get_temp::<String>(query_id)).unwrap_or_default();
+    let search = parse_search(&query);
+    ui.horizontal(|ui| {
+        let res = ui.add(
+            egui::TextEdit::singleline(&mut query)
+                .hint_text("e.g. 7 or 0..10")
+                .desired_width(120.),
+        );
+        let submitted = res.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        res.on_hover_text_at_pointer(
+            "A value or range to look for in every numeric field of every section, including links to routes and respawn points",
+        );
+        ui.add_enabled_ui(world.contains_resource::<KmpFilePath>() && search.is_some(), |ui| {
+            if ui.button("Find").clicked() || submitted {
+                if let Some(search) = search {
+                    world.send_event(search);
+                }
+            }
+        });
+    });
+    ui.data_mut(|d| d.insert_temp(query_id, query));
+
+    let found = world.resource::<FoundValues>();
+    let Some(searched) = found.searched else {
+        return;
+    };
+    let range = if searched.min == searched.max {
+        format!("equal to {}", searched.min)
+    } else {
+        format!("from {} to {}", searched.min, searched.max)
+    };
+    ui.label(format!("{} values found {range}", found.matches.len()));
+    ui.separator();
+
+    let mut to_select: Option<(KmpEditMode, Vec<Entity>)> = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for mode in KmpEditMode::iter() {
+            let matches: Vec<_> = found.matches.iter().filter(|x| x.mode == mode).collect();
+            if matches.is_empty() {
+                continue;
+            }
+            egui::CollapsingHeader::new(format!("{mode} ({})", matches.len()))
+                .id_source(mode)
+                .default_open(true)
+                .show(ui, |ui| {
+                    let entities: Vec<_> = matches.iter().filter_map(|x| x.entity).collect();
+                    if !entities.is_empty() && ui.button("Select All").clicked() {
+                        to_select = Some((mode, entities));
+                    }
+                    for found in matches {
+                        let text = match found.entity {
+                            Some(_) => format!("{} {}: {} = {}", mode, found.index, found.field, found.value),
+                            None => format!("{}: {} = {}", mode, found.field, found.value),
+                        };
+                        let res = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                        if res.clicked() {
+                            to_select = Some((mode, found.entity.into_iter().collect()));
+                        }
+                        res.on_hover_text_at_pointer(if found.entity.is_some() {
+                            "Click to select and go to the point"
+                        } else {
+                            "Click to go to the section"
+                        });
+                    }
+                });
+        }
+    });
+
+    // switch to the section and select the points with the value
+    let Some((mode, entities)) = to_select else {
+        return;
+    };
+    *world.resource_mut::<KmpEditMode>() = mode;
+    let entities: Vec<_> = entities
+        .into_iter()
+        .filter(|e| world.get_entity(*e).is_some())
+        .collect();
+    if entities.is_empty() {
+        return;
+    }
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for e in selected {
+        world.entity_mut(e).remove::<Selected>();
+    }
+    let mut positions = Vec::new();
+    for e in entities {
+        let mut entity = world.entity_mut(e);
+        entity.insert(Selected);
+        if let Some(transform) = entity.get::<Transform>() {
+            positions.push(transform.translation);
+        }
+    }
+    world.send_event(FrameCameras(positions));
+}
@@ -0,0 +1,110 @@
+use crate::{
+    ui::{file_dialog::FileDialogManager, settings::AppSettings, ui_state::KmpFilePath},
+    viewer::{
+        camera::FrameCameras,
+        edit::select::Selected,
+        kmp::{
+            custom_rules::EXAMPLE_RULES,
+            sections::KmpEditMode,
+            validate::{IssueSeverity, KmpIssues, ValidateKmp},
+        },
+    },
+};
+use bevy::{ecs::system::SystemState, prelude::*};
+use bevy_egui::egui::{self, Color32, Ui};
+
+pub fn show_issues_tab(ui: &mut Ui, world: &mut World) {
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(world.contains_resource::<KmpFilePath>(), |ui| {
+            if ui.button("Check for Issues").clicked() {
+                world.send_event_default::<ValidateKmp>();
+            }
+        });
+        let issues = world.resource::<KmpIssues>();
+        if issues.checked {
+            let errors = issues
+                .issues
+                .iter()
+                .filter(|x| x.severity == IssueSeverity::Error)
+                .count();
+            ui.label(format!("{errors} errors, {} warnings", issues.issues.len() - errors));
+        }
+    });
+    ui.label("The KMP is also checked whenever it is saved");
+    ui.horizontal(|ui| {
+        let mut settings = world.resource_mut::<AppSettings>();
+        let file_name = settings
+            .custom_rules
+            .file
+            .as_ref()
+            .and_then(|x| x.file_name())
+            .map_or("None".into(), |x| x.to_string_lossy().to_string());
+        ui.label(format!("Custom Rules: {file_name}"))
+            .on_hover_text_at_pointer(format!(
+                "A TOML file of extra rules to check the KMP against, like:\n\n{EXAMPLE_RULES}"
+            ));
+        if settings.custom_rules.file.is_some() && ui.button("Clear").clicked() {
+            settings.custom_rules.file = None;
+        }
+        if ui.button("Choose...").clicked() {
+            let mut ss = SystemState::<FileDialogManager>::new(world);
+            ss.get_mut(world).open_custom_rules();
+        }
+    });
+    ui.separator();
+
+    let issues = world.resource::<KmpIssues>();
+    if issues.checked && issues.issues.is_empty() {
+        ui.colored_label(Color32::GREEN, "No issues found");
+    }
+    let mut go_to = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (i, issue) in issues.issues.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let color = match issue.severity {
+                    IssueSeverity::Error => Color32::RED,
+                    IssueSeverity::Warning => Color32::from_rgb(255, 165, 0),
+                };
+                ui.colored_label(color, issue.severity.to_string());
+                let text = format!("{}: {}", issue.mode, issue.message);
+                let res = ui.add(egui::Label::new(text).wrap().sense(egui::Sense::click()));
+                if res.clicked() {
+                    go_to = Some(i);
+                }
+                res.on_hover_text_at_pointer(if issue.entities.is_empty() {
+                    "Click to go to the section"
+                } else {
+                    "Click to select the points causing this"
+                });
+            });
+        }
+    });
+
+    // switch to the section and select the points with the problem
+    let Some(i) = go_to else {
+        return;
+    };
+    let issue = &world.resource::<KmpIssues>().issues[i];
+    let (mode, entities) = (issue.mode, issue.entities.clone());
+    *world.resource_mut::<KmpEditMode>() = mode;
+    let entities: Vec<_> = entities
+        .into_iter()
+        .filter(|e| world.get_entity(*e).is_some())
+        .collect();
+    if entities.is_empty() {
+        return;
+    }
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for e in selected {
+        world.entity_mut(e).remove::<Selected>();
+    }
+    let mut positions = Vec::new();
+    for e in entities {
+        let mut entity = world.entity_mut(e);
+        entity.insert(Selected);
+        if let Some(transform) = entity.get::<Transform>() {
+            positions.push(transform.translation);
+        }
+    }
+    world.send_event(FrameCameras(positions));
+}
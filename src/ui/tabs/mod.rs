@@ -1,18 +1,25 @@
 mod edit;
+mod find;
+mod issues;
 mod outliner;
 mod settings;
+mod stats;
 mod table;
 mod viewport;
 
-use super::util::get_egui_ctx;
+use super::{ui_state::SafeMode, util::get_egui_ctx};
+use crate::viewer::edit::link_select_mode::LinkSelectState;
 use bevy::prelude::*;
 use bevy_egui::egui;
 use bevy_pkv::PkvStore;
 use edit::show_edit_tab;
 use egui_dock::{DockArea, DockState, NodeIndex, Style};
+use find::show_find_tab;
+use issues::show_issues_tab;
 use outliner::show_outliner_tab;
 use serde::{Deserialize, Serialize};
 use settings::show_settings_tab;
+use stats::show_stats_tab;
 use strum_macros::{Display, EnumIter};
 use table::show_table_tab;
 use viewport::show_viewport_tab;
@@ -21,7 +28,13 @@ pub fn docktree_plugin(app: &mut App) {
     app.add_systems(Startup, setup_docktree);
 }
 
-fn setup_docktree(mut commands: Commands, mut pkv: ResMut<PkvStore>) {
+fn setup_docktree(mut commands: Commands, mut pkv: ResMut<PkvStore>, safe_mode: Res<SafeMode>) {
+    if **safe_mode {
+        commands.insert_resource(DockTree::default());
+        commands.insert_resource(Workspaces::default());
+        return;
+    }
+    commands.insert_resource(pkv.get::<Workspaces>("workspaces").unwrap_or_default());
     // get the docktree if it exists, if not, set it to default
     let tree = match pkv.get::<DockTree>("tree") {
         Ok(tree) => tree,
@@ -48,6 +61,77 @@ impl Default for DockTree {
     }
 }
 
+/// Named tab layouts for different tasks, switched between from the Window menu. The layout of the active workspace
+/// is the [`DockTree`] itself, and is only copied back in here when switching away from it or saving it.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct Workspaces {
+    pub layouts: Vec<Workspace>,
+    pub active: usize,
+}
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Workspace {
+    pub name: String,
+    pub tree: DockTree,
+}
+impl Workspace {
+    fn new(name: &str, tree: DockState<Tab>) -> Self {
+        Self {
+            name: name.into(),
+            tree: DockTree(tree),
+        }
+    }
+}
+impl Default for Workspaces {
+    fn default() -> Self {
+        let mut pathing = DockState::new(vec![Tab::Viewport]);
+        let surface = pathing.main_surface_mut();
+        let [_, right] = surface.split_right(NodeIndex::root(), 0.75, vec![Tab::Outliner]);
+        surface.split_below(right, 0.5, vec![Tab::Edit, Tab::Issues]);
+
+        let mut cameras = DockState::new(vec![Tab::Viewport]);
+        let surface = cameras.main_surface_mut();
+        let [_, right] = surface.split_right(NodeIndex::root(), 0.7, vec![Tab::Edit]);
+        surface.split_below(right, 0.6, vec![Tab::Outliner]);
+
+        let mut tables = DockState::new(vec![Tab::Table]);
+        let surface = tables.main_surface_mut();
+        let [_, below] = surface.split_below(NodeIndex::root(), 0.6, vec![Tab::Viewport]);
+        surface.split_right(below, 0.6, vec![Tab::Edit, Tab::Find]);
+
+        Self {
+            layouts: vec![
+                Workspace {
+                    name: "Default".into(),
+                    tree: DockTree::default(),
+                },
+                Workspace::new("Pathing", pathing),
+                Workspace::new("Cameras", cameras),
+                Workspace::new("Tables", tables),
+            ],
+            active: 0,
+        }
+    }
+}
+impl Workspaces {
+    /// The workspaces with the current tab layout stored as the active one's
+    pub fn with_current(&self, tree: &DockTree) -> Self {
+        let mut workspaces = self.clone();
+        if let Some(workspace) = workspaces.layouts.get_mut(workspaces.active) {
+            workspace.tree = tree.clone();
+        }
+        workspaces
+    }
+    /// The layout a workspace starts with, used when resetting it
+    pub fn default_tree(name: &str) -> DockTree {
+        Self::default()
+            .layouts
+            .into_iter()
+            .find(|x| x.name == name)
+            .map(|x| x.tree)
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Display, PartialEq, EnumIter, Serialize, Deserialize, Clone, Copy)]
 pub enum Tab {
     Viewport,
@@ -55,6 +139,9 @@ pub enum Tab {
     Edit,
     Table,
     Settings,
+    Issues,
+    Find,
+    Stats,
 }
 
 // this tells egui how to render each tab
@@ -64,6 +151,10 @@ impl egui_dock::TabViewer for TabViewer<'_> {
     // each tab will be distinguished by an enum which can be converted to a string using strum
     type Tab = Tab;
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
+        // everything apart from the viewport is dimmed while picking a point to link to, so it's clear where to click
+        if !matches!(tab, Tab::Viewport) && self.0.resource::<LinkSelectState>().is_active() {
+            ui.disable();
+        }
         // we can do different things inside the tab depending on its name
         match tab {
             Tab::Viewport => show_viewport_tab(ui, self.0),
@@ -71,6 +162,9 @@ impl egui_dock::TabViewer for TabViewer<'_> {
             Tab::Edit => show_edit_tab(ui, self.0),
             Tab::Table => show_table_tab(ui, self.0),
             Tab::Settings => show_settings_tab(ui, self.0),
+            Tab::Issues => show_issues_tab(ui, self.0),
+            Tab::Find => show_find_tab(ui, self.0),
+            Tab::Stats => show_stats_tab(ui, self.0),
         };
     }
     // show the title of the tab - the 'Tab' type already stores its title anyway
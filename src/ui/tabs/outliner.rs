@@ -6,11 +6,14 @@ use crate::{
     viewer::{
         edit::select::Selected,
         kmp::{
+            area_volumes::AreaOverlaps,
             components::{
-                AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
-                Object, RespawnPoint, RoutePoint, StartPoint,
+                AreaKind, AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, DisplayName, EnemyPathPoint,
+                ItemPathPoint, KmpCamera, LabelColor, Object, PointNote, RespawnPoint, RoutePoint, StartPoint,
+                TrackInfo,
             },
-            path::{EntityPathGroup, EntityPathGroups},
+            ordering::{move_in_order, OrderId},
+            path::{is_battle_track, EntityPathGroup, EntityPathGroups},
             sections::KmpEditMode,
             SetSectionVisibility,
         },
@@ -18,6 +21,7 @@ use crate::{
 };
 use bevy::prelude::*;
 use bevy_egui::egui::{self, collapsing_header::CollapsingState, Align, Color32, Layout, Ui};
+use strum::IntoEnumIterator;
 
 pub fn show_outliner_tab(ui: &mut Ui, world: &mut World) {
     // show the buttons at the top
@@ -30,18 +34,25 @@ pub fn show_outliner_tab(ui: &mut Ui, world: &mut World) {
     });
     ui.add_space(2.);
 
+    let battle = is_battle_track(world);
     show_track_info_outliner(ui, world);
     show_point_outliner::<StartPoint>(ui, world);
+    // battle arenas rely on their battle finish points, so they are moved up next to the start points
+    if battle {
+        show_point_outliner::<BattleFinishPoint>(ui, world);
+    }
     show_path_outliner::<EnemyPathPoint>(ui, world);
     show_path_outliner::<ItemPathPoint>(ui, world);
     show_path_outliner::<Checkpoint>(ui, world);
     show_point_outliner::<RespawnPoint>(ui, world);
     show_point_outliner::<Object>(ui, world);
     show_path_outliner::<RoutePoint>(ui, world);
-    show_point_outliner::<AreaPoint>(ui, world);
+    show_area_outliner(ui, world);
     show_point_outliner::<KmpCamera>(ui, world);
     show_point_outliner::<CannonPoint>(ui, world);
-    show_point_outliner::<BattleFinishPoint>(ui, world);
+    if !battle {
+        show_point_outliner::<BattleFinishPoint>(ui, world);
+    }
 }
 
 const ICON_SIZE: f32 = 14.;
@@ -63,13 +74,23 @@ fn show_track_info_outliner(ui: &mut Ui, world: &mut World) {
 }
 
 fn show_point_outliner<T: Component>(ui: &mut Ui, world: &mut World) {
-    show_header::<T>(ui, world, false);
+    CollapsingState::load_with_default_open(ui.ctx(), ui.next_auto_id(), false)
+        .show_header(ui, |ui| {
+            show_header::<T>(ui, world, false, true);
+        })
+        .body(|ui| {
+            let mut q = world.query_filtered::<(Entity, &OrderId), With<T>>();
+            let mut points: Vec<_> = q.iter(world).map(|(e, order_id)| (e, order_id.0)).collect();
+            points.sort_by_key(|x| x.1);
+            let points = points.into_iter().map(|x| x.0).collect();
+            show_point_list::<T>(ui, world, points, true);
+        });
 }
 
 fn show_path_outliner<T: Component>(ui: &mut Ui, world: &mut World) {
     CollapsingState::load_with_default_open(ui.ctx(), ui.next_auto_id(), false)
         .show_header(ui, |ui| {
-            show_header::<T>(ui, world, true);
+            show_header::<T>(ui, world, true, true);
         })
         .body(|ui| {
             let mut paths_to_show = Vec::new();
@@ -79,7 +100,7 @@ fn show_path_outliner<T: Component>(ui: &mut Ui, world: &mut World) {
                 }
             }
             for (i, pathgroup) in paths_to_show {
-                show_path(
+                show_path::<T>(
                     ui,
                     world,
                     i,
@@ -90,7 +111,94 @@ fn show_path_outliner<T: Component>(ui: &mut Ui, world: &mut World) {
         });
 }
 
-fn show_path(ui: &mut Ui, world: &mut World, i: usize, pathgroup: EntityPathGroup, color: Color32) {
+fn show_area_outliner(ui: &mut Ui, world: &mut World) {
+    CollapsingState::load_with_default_open(ui.ctx(), ui.next_auto_id(), false)
+        .show_header(ui, |ui| {
+            show_header::<AreaPoint>(ui, world, false, true);
+        })
+        .body(|ui| {
+            // show a toggle for each kind of area, which toggles 'Always Show Area' for all areas of that kind
+            for kind in AreaKind::iter() {
+                let kind_name: &'static str = kind.into();
+                let mut q = world.query::<&mut AreaPoint>();
+                let areas: Vec<_> = q
+                    .iter(world)
+                    .filter(|area| <&'static str>::from(area.kind) == kind_name)
+                    .map(|area| area.show_area)
+                    .collect();
+                if areas.is_empty() {
+                    continue;
+                }
+                let mut all_shown = areas.iter().all(|x| *x);
+                ui.horizontal(|ui| {
+                    ui.add_space(10.);
+                    ui.add_sized(
+                        [ICON_SIZE, ICON_SIZE],
+                        Icons::cube_group(ui.ctx(), ICON_SIZE).tint(Icons::SECTION_COLORS[KmpEditMode::Areas as usize]),
+                    );
+                    ui.label(format!("{} ({})", kind, areas.len()));
+                    let view_btn_response = ui
+                        .with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            view_icon_btn(ui, &mut all_shown)
+                        })
+                        .inner
+                        .on_hover_text_at_pointer(format!("Always show all {kind} areas"));
+                    if view_btn_response.changed() {
+                        for mut area in q.iter_mut(world) {
+                            if <&'static str>::from(area.kind) == kind_name {
+                                area.show_area = all_shown;
+                            }
+                        }
+                    }
+                });
+            }
+            show_area_overlaps(ui, world);
+        });
+}
+
+// list the areas which overlap others of the same kind, saying which one wins, so they can be selected and fixed
+fn show_area_overlaps(ui: &mut Ui, world: &mut World) {
+    let overlaps: Vec<_> = world
+        .resource::<AreaOverlaps>()
+        .0
+        .iter()
+        .map(|x| (x.winner, x.loser))
+        .collect();
+    if overlaps.is_empty() {
+        return;
+    }
+    ui.separator();
+    ui.label(format!("Overlaps ({})", overlaps.len()));
+    let mut q_area = world.query::<(&AreaPoint, &OrderId)>();
+    let mut to_select = None;
+    for (winner, loser) in overlaps {
+        let (Ok((w_area, w_id)), Ok((l_area, l_id))) = (q_area.get(world, winner), q_area.get(world, loser)) else {
+            continue;
+        };
+        let text = format!(
+            "{} {} (priority {}) overrides {} (priority {})",
+            w_area.kind, w_id.0, w_area.priority, l_id.0, l_area.priority
+        );
+        ui.horizontal(|ui| {
+            ui.add_space(10.);
+            let label = ui.add(egui::Label::new(text).selectable(false).sense(egui::Sense::click()));
+            if label.on_hover_text_at_pointer("Select both areas").clicked() {
+                to_select = Some([winner, loser]);
+            }
+        });
+    }
+    if let Some(to_select) = to_select {
+        let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+        for e in selected {
+            world.entity_mut(e).remove::<Selected>();
+        }
+        for e in to_select {
+            world.entity_mut(e).insert(Selected);
+        }
+    }
+}
+
+fn show_path<T: Component>(ui: &mut Ui, world: &mut World, i: usize, pathgroup: EntityPathGroup, color: Color32) {
     let mut all_visible = if !pathgroup.path.is_empty() {
         pathgroup
             .path
@@ -99,54 +207,264 @@ fn show_path(ui: &mut Ui, world: &mut World, i: usize, pathgroup: EntityPathGrou
     } else {
         false
     };
-    ui.horizontal(|ui| {
-        ui.add_space(10.);
-        ui.add_sized([ICON_SIZE, ICON_SIZE], Icons::path(ui.ctx(), ICON_SIZE).tint(color));
-        let label = ui.add(
-            egui::Label::new(format!("Path {i}"))
-                .selectable(false)
-                .sense(egui::Sense::click()),
-        );
-        if label.clicked() {
-            let keys = world.resource::<ButtonInput<KeyCode>>();
-            if !keys.shift_pressed() {
-                // deselect everything
-                let entities: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
-                for e in entities {
-                    world.entity_mut(e).remove::<Selected>();
+    // a path whose points all have the same colour is shown in that colour
+    let colors: Vec<_> = pathgroup
+        .path
+        .iter()
+        .map(|e| world.get::<LabelColor>(*e).map(|x| x.0))
+        .collect();
+    let group_color = colors
+        .first()
+        .copied()
+        .flatten()
+        .filter(|x| colors.iter().all(|y| *y == Some(*x)));
+    let color = group_color.map_or(color, |[r, g, b]| Color32::from_rgb(r, g, b));
+    let mut new_color = None;
+    CollapsingState::load_with_default_open(ui.ctx(), ui.next_auto_id(), false)
+        .show_header(ui, |ui| {
+            ui.add_sized([ICON_SIZE, ICON_SIZE], Icons::path(ui.ctx(), ICON_SIZE).tint(color));
+            let label = ui.add(
+                egui::Label::new(format!("Path {i}"))
+                    .selectable(false)
+                    .sense(egui::Sense::click()),
+            );
+            label.context_menu(|ui| {
+                new_color = label_color_menu(ui, group_color);
+            });
+            if label.clicked() {
+                let keys = world.resource::<ButtonInput<KeyCode>>();
+                if !keys.shift_pressed() {
+                    // deselect everything
+                    let entities: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+                    for e in entities {
+                        world.entity_mut(e).remove::<Selected>();
+                    }
+                }
+                for e in pathgroup.path.iter() {
+                    world.entity_mut(*e).insert(Selected);
                 }
             }
-            for e in pathgroup.path.iter() {
-                world.entity_mut(*e).insert(Selected);
+            let view_btn_response = ui
+                .with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    view_icon_btn(ui, &mut all_visible)
+                })
+                .inner;
+
+            if view_btn_response.changed() {
+                for e in pathgroup.path.iter() {
+                    let Ok(mut visibility) = world.query::<&mut Visibility>().get_mut(world, *e) else {
+                        continue;
+                    };
+                    *visibility = if all_visible {
+                        Visibility::Visible
+                    } else {
+                        Visibility::Hidden
+                    };
+                }
+            }
+        })
+        .body(|ui| {
+            // the order of path points comes from how they are linked, so they can't be dragged into a different order
+            show_point_list::<T>(ui, world, pathgroup.path.clone(), false);
+        });
+    if let Some(change) = new_color {
+        set_label_color(world, &pathgroup.path, change);
+    }
+}
+
+// colours points can be given to group them together in the outliner
+const LABEL_COLORS: [[u8; 3]; 6] = [
+    [230, 80, 80],
+    [240, 150, 60],
+    [230, 210, 70],
+    [90, 200, 100],
+    [80, 150, 240],
+    [180, 110, 230],
+];
+
+// a row of colours to pick from, returning the new colour if one was picked or cleared
+fn label_color_menu(ui: &mut Ui, current: Option<[u8; 3]>) -> Option<Option<[u8; 3]>> {
+    let mut change = None;
+    ui.label("Colour");
+    ui.horizontal(|ui| {
+        for color in LABEL_COLORS {
+            let [r, g, b] = color;
+            let mut button = egui::Button::new("")
+                .fill(Color32::from_rgb(r, g, b))
+                .min_size(egui::vec2(16., 16.));
+            if current == Some(color) {
+                button = button.stroke(ui.visuals().selection.stroke);
+            }
+            if ui.add(button).clicked() {
+                change = Some(Some(color));
+                ui.close_menu();
             }
         }
-        let view_btn_response = ui
-            .with_layout(Layout::right_to_left(Align::Center), |ui| {
-                view_icon_btn(ui, &mut all_visible)
-            })
-            .inner;
-
-        if view_btn_response.changed() {
-            for e in pathgroup.path.iter() {
-                let Ok(mut visibility) = world.query::<&mut Visibility>().get_mut(world, *e) else {
-                    continue;
-                };
-                *visibility = if all_visible {
-                    Visibility::Visible
+        if ui.add_enabled(current.is_some(), egui::Button::new("Clear")).clicked() {
+            change = Some(None);
+            ui.close_menu();
+        }
+    });
+    change
+}
+
+fn set_label_color(world: &mut World, entities: &[Entity], color: Option<[u8; 3]>) {
+    for e in entities {
+        let Some(mut entity) = world.get_entity_mut(*e) else {
+            continue;
+        };
+        match color {
+            Some(color) => entity.insert(LabelColor(color)),
+            None => entity.remove::<LabelColor>(),
+        };
+    }
+}
+
+// the point being renamed in the outliner, along with the name being typed
+fn rename_id() -> egui::Id {
+    egui::Id::new("outliner_rename")
+}
+
+// list every point in a section or path with its ID and name, so points can be selected, renamed, and dragged into
+// a different order if `reorderable`
+fn show_point_list<T: Component>(ui: &mut Ui, world: &mut World, points: Vec<Entity>, reorderable: bool) {
+    let mut renaming = ui.data(|d| d.get_temp::<(Entity, String)>(rename_id()));
+    let mut clicked = None;
+    let mut start_rename = None;
+    let mut finish_rename = None;
+    let mut dropped = None;
+    let mut new_note = None;
+    let mut new_color = None;
+
+    for e in points {
+        let Some(order_id) = world.get::<OrderId>(e).map(|x| x.0) else {
+            continue;
+        };
+        let selected = world.get::<Selected>(e).is_some();
+        let name = world.get::<DisplayName>(e).map(|x| x.0.clone());
+        let note = world.get::<PointNote>(e).map(|x| x.0.clone());
+        let color = world.get::<LabelColor>(e).map(|x| x.0);
+        ui.horizontal(|ui| {
+            ui.add_space(28.);
+            if let Some((_, text)) = renaming.as_mut().filter(|x| x.0 == e) {
+                let res = ui.text_edit_singleline(text);
+                if res.lost_focus() {
+                    finish_rename = Some((e, text.clone()));
                 } else {
-                    Visibility::Hidden
-                };
+                    res.request_focus();
+                }
+                return;
+            }
+            let text = match &name {
+                Some(name) => format!("{order_id}: {name}"),
+                None => format!("{order_id}"),
+            };
+            let mut text = egui::RichText::new(text);
+            if let Some([r, g, b]) = color {
+                text = text.color(Color32::from_rgb(r, g, b));
+            }
+            let mut res = ui
+                .selectable_label(selected, text)
+                .interact(egui::Sense::click_and_drag());
+            if let Some(note) = note.as_ref().filter(|x| !x.is_empty()) {
+                res = res.on_hover_text(note);
+            }
+            if res.clicked() {
+                clicked = Some(e);
+            }
+            if res.double_clicked() {
+                start_rename = Some((e, name.clone().unwrap_or_default()));
+            }
+            res.context_menu(|ui| {
+                if ui.button("Rename").clicked() {
+                    start_rename = Some((e, name.clone().unwrap_or_default()));
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(name.is_some(), egui::Button::new("Clear Name"))
+                    .clicked()
+                {
+                    finish_rename = Some((e, String::new()));
+                    ui.close_menu();
+                }
+                ui.separator();
+                ui.label("Note");
+                let mut text = note.clone().unwrap_or_default();
+                if ui.text_edit_multiline(&mut text).changed() {
+                    new_note = Some((e, text));
+                }
+                if let Some(change) = label_color_menu(ui, color) {
+                    new_color = Some((vec![e], change));
+                }
+            });
+            if !reorderable {
+                return;
+            }
+            res.dnd_set_drag_payload(e);
+            // show where the dragged point will go
+            if res.dnd_hover_payload::<Entity>().is_some_and(|x| *x != e) {
+                ui.painter().rect_stroke(res.rect, 2., ui.visuals().selection.stroke);
+            }
+            if let Some(moved) = res.dnd_release_payload::<Entity>() {
+                dropped = Some((*moved, e));
+            }
+        });
+    }
+
+    if let Some((e, note)) = new_note {
+        if let Some(mut entity) = world.get_entity_mut(e) {
+            if note.is_empty() {
+                entity.remove::<PointNote>();
+            } else {
+                entity.insert(PointNote(note));
+            }
+        }
+    }
+    if let Some((entities, color)) = new_color {
+        set_label_color(world, &entities, color);
+    }
+    if let Some(rename) = start_rename {
+        renaming = Some(rename);
+    }
+    if let Some((e, name)) = finish_rename {
+        renaming = None;
+        let name = name.trim();
+        if let Some(mut entity) = world.get_entity_mut(e) {
+            if name.is_empty() {
+                entity.remove::<DisplayName>();
+            } else {
+                entity.insert(DisplayName(name.to_string()));
             }
         }
+    }
+    ui.data_mut(|d| match renaming {
+        Some(renaming) => d.insert_temp(rename_id(), renaming),
+        None => d.remove::<(Entity, String)>(rename_id()),
     });
+
+    if let Some(e) = clicked {
+        let keys = world.resource::<ButtonInput<KeyCode>>();
+        if !keys.shift_pressed() {
+            let entities: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+            for e in entities {
+                world.entity_mut(e).remove::<Selected>();
+            }
+        }
+        world.entity_mut(e).insert(Selected);
+    }
+    if let Some((moved, target)) = dropped {
+        if moved != target {
+            move_in_order::<T>(world, moved, target);
+        }
+    }
 }
 
-fn show_header<T: Component>(ui: &mut Ui, world: &mut World, path: bool) {
+fn show_header<T: Component>(ui: &mut Ui, world: &mut World, path: bool, collapsible: bool) {
     let entities: Vec<_> = world.query_filtered::<Entity, With<T>>().iter(world).collect();
     let cur_mode = world.resource::<KmpEditMode>().in_mode::<T>();
 
     ui.horizontal(|ui| {
-        if !path {
+        if !collapsible {
             ui.add_space(18.);
         }
         ui.add_sized(
@@ -158,10 +476,20 @@ fn show_header<T: Component>(ui: &mut Ui, world: &mut World, path: bool) {
             }
             .tint(Icons::SECTION_COLORS[KmpEditMode::from_type::<T>() as usize]),
         );
-        if ui
-            .selectable_label(cur_mode, KmpEditMode::from_type::<T>().to_string())
-            .clicked()
-        {
+        let mode = KmpEditMode::from_type::<T>();
+        let unused_note = world
+            .get_resource::<TrackInfo>()
+            .and_then(|x| mode.unused_note(&x.track_type));
+        // sections the game ignores for this type of track are greyed out
+        let mut text = egui::RichText::new(mode.to_string());
+        if unused_note.is_some() {
+            text = text.weak();
+        }
+        let mut res = ui.selectable_label(cur_mode, text);
+        if let Some(note) = unused_note {
+            res = res.on_hover_text_at_pointer(note);
+        }
+        if res.clicked() {
             world.resource_mut::<KmpEditMode>().set_mode::<T>();
         }
 
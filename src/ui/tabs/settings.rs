@@ -1,34 +1,51 @@
 use crate::{
-    ui::{file_dialog::FileDialogManager, settings::AppSettings},
-    util::kcl_file::KclFlag,
+    ui::{
+        file_associations::register_kmp_handler,
+        file_dialog::FileDialogManager,
+        keybinds::{KeyAction, Keybinds},
+        settings::AppSettings,
+        ui_state::{PkvWriteQueue, SafeMode},
+        util::combobox_enum,
+    },
+    util::kcl_file::{Kcl, KclFlag, KCL_VARIANTS},
     viewer::{
-        camera::{CameraSettings, FlyCam, FlySettings, OrbitCam, OrbitSettings, TopDownCam, TopDownSettings},
+        camera::{
+            CameraSettings, FlyCam, FlySettings, OrbitCam, OrbitSettings, TopDownCam, TopDownSettings, ViewDistance,
+            ViewDistancePreset,
+        },
+        edit::create_delete::PathHeightSnap,
         kcl_model::KclModelUpdated,
+        kmp::{sections::KmpEditMode, value_colors::ColorByField},
     },
 };
 use bevy::{ecs::system::SystemState, prelude::*};
 use bevy_egui::egui::{self, Ui};
-use bevy_pkv::PkvStore;
 use strum::IntoEnumIterator;
 
 pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
     let mut ss = SystemState::<(
         ResMut<AppSettings>,
-        ResMut<PkvStore>,
+        ResMut<PkvWriteQueue>,
+        Res<SafeMode>,
         FileDialogManager,
         Query<&mut Transform, (With<FlyCam>, Without<OrbitCam>, Without<TopDownCam>)>,
         Query<&mut Transform, (Without<FlyCam>, With<OrbitCam>, Without<TopDownCam>)>,
         Query<(&mut Transform, &'static mut Projection), (Without<FlyCam>, Without<OrbitCam>, With<TopDownCam>)>,
         EventWriter<KclModelUpdated>,
+        ResMut<Keybinds>,
+        Option<Res<Kcl>>,
     )>::new(world);
     let (
         mut settings,
-        mut pkv,
+        mut pkv_writes,
+        safe_mode,
         mut file_dialog,
         mut q_fly_cam,
         mut q_orbit_cam,
         mut q_topdown_cam,
         mut ev_kcl_model_updated,
+        mut keybinds,
+        kcl,
     ) = ss.get_mut(world);
 
     let mut fly_cam = q_fly_cam.single_mut();
@@ -46,6 +63,73 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
                 &mut settings.open_course_kcl_in_dir,
                 "Auto open course.kcl",
             ).on_hover_text_at_pointer("If enabled, when opening a KMP file, if there is a 'course.kcl' file in the same directory, it will also be opened");
+            ui.checkbox(
+                &mut settings.kmp_model.show_saved_ghosts,
+                "Show Saved Ghosts",
+            ).on_hover_text_at_pointer("If enabled, faded ghosts are shown where points in the current section were when the KMP was last saved");
+
+            ui.checkbox(
+                &mut settings.kmp_model.smooth_routes,
+                "Show Smoothed Routes",
+            ).on_hover_text_at_pointer("If enabled, routes with 'Smooth Motion' enabled are shown as the curve that objects and cameras follow in game, rather than straight lines between the points");
+            ui.checkbox(
+                &mut settings.kmp_model.color_routes,
+                "Colour Routes by Index",
+            ).on_hover_text_at_pointer("If enabled, each route is shown in its own colour and labelled with its index in the viewport, so that routes which overlap can be told apart");
+            ui.checkbox(
+                &mut settings.kmp_model.show_checkpoint_quads,
+                "Show Checkpoint Quads",
+            ).on_hover_text_at_pointer("If enabled, the area between each checkpoint and the next is filled in while editing checkpoints, in the colour of the checkpoint's type. Quads which aren't convex are shown in red, as the game can't reliably tell when players pass through them");
+            ui.checkbox(
+                &mut settings.kmp_model.show_bullet_corridor,
+                "Show Bullet Bill Corridor",
+            ).on_hover_text_at_pointer("If enabled, the space a Bullet Bill travels through is shown as a ribbon along the item paths while editing them, as wide as each point's Bullet Control. It follows the height of points set to 'Follow Point Height', and drops onto the KCL everywhere else");
+            ui.checkbox(
+                &mut settings.kmp_model.show_camera_labels,
+                "Show Camera Labels",
+            ).on_hover_text_at_pointer("If enabled, each camera is labelled with its type, time (in seconds) and index in the viewport, fading out with distance");
+            ui.checkbox(
+                &mut settings.kmp_model.show_object_models,
+                "Show Object Models",
+            ).on_hover_text_at_pointer("If enabled, objects with a recognised ID (such as item boxes) are shown with a simple model at their scale, so that different kinds of object can be told apart in the viewport");
+            ui.checkbox(
+                &mut settings.kmp_model.show_area_volumes,
+                "Show Area Volumes",
+            ).on_hover_text_at_pointer("If enabled, every area is shown as a translucent box or cylinder of the space it covers, coloured by the kind of area");
+            ui.checkbox(
+                &mut settings.kmp_model.show_area_overlaps,
+                "Show Area Overlaps",
+            ).on_hover_text_at_pointer("If enabled, places where two areas of the same kind but different priorities overlap are outlined, and listed under Areas in the outliner");
+            ui.checkbox(
+                &mut settings.kmp_model.gpu_path_links,
+                "Draw Path Links as Lines",
+            ).on_hover_text_at_pointer("If enabled, the links between path points are drawn as lines on the GPU instead of as 3D models, which is much faster on tracks with dense paths. Links to selected points are still drawn as models so that they stand out");
+            ui.add_enabled(
+                settings.kmp_model.gpu_path_links,
+                egui::Slider::new(&mut settings.kmp_model.path_link_width, 1.0..=10.)
+                    .text("Path Link Width"),
+            );
+
+            let color_by = &mut settings.kmp_model.color_by_value;
+            egui::ComboBox::from_label("Colour Points By")
+                .selected_text(color_by.field.to_string())
+                .show_ui(ui, |ui| {
+                    for field in ColorByField::iter() {
+                        ui.selectable_value(&mut color_by.field, field, field.to_string());
+                    }
+                });
+            if color_by.field != ColorByField::Off && !color_by.field.is_categorical() {
+                ui.checkbox(&mut color_by.auto_range, "Automatic Range")
+                    .on_hover_text_at_pointer("If enabled, the lowest and highest values are used as the ends of the colour scale");
+                if !color_by.auto_range {
+                    ui.horizontal(|ui| {
+                        ui.label("Min");
+                        ui.add(egui::DragValue::new(&mut color_by.min).speed(0.1));
+                        ui.label("Max");
+                        ui.add(egui::DragValue::new(&mut color_by.max).speed(0.1));
+                    });
+                }
+            }
 
         });
 
@@ -55,6 +139,10 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
             let kcl_model_settings_before = settings.kcl_model.clone();
             ui.checkbox(&mut settings.kcl_model.backface_culling, "Backface Culling")
                 .on_hover_text_at_pointer("Whether or not the back faces of the collision model are shown");
+            ui.checkbox(&mut settings.kcl_model.show_heatmap, "Show Drivable Area Heatmap")
+                .on_hover_text_at_pointer(
+                    "Colour the drivable surfaces by how fast karts can drive on them: green for road, yellow to red for offroad, and cyan for boosts",
+                );
 
             let visible = &mut settings.kcl_model.visible;
 
@@ -99,9 +187,14 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
             }
 
             ui.collapsing("Customise Colours", |ui| {
+                ui.checkbox(&mut settings.kcl_model.color_by_variant, "Colour by Variant")
+                    .on_hover_text_at_pointer(
+                        "Turn the colour of each variant of a flag a different way round, so that e.g. boost panels of different strengths can be told apart",
+                    );
                 ui.horizontal(|ui| {
                     if ui.button("Check All").clicked() {
                         settings.kcl_model.visible = [true; 32];
+                        settings.kcl_model.variant_visible = [[true; KCL_VARIANTS]; 32];
                     }
                     if ui.button("Uncheck All").clicked() {
                         settings.kcl_model.visible = [false; 32];
@@ -117,6 +210,24 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
                         ui.color_edit_button_rgba_unmultiplied(&mut color);
                         settings.kcl_model.color[i] = Srgba::from_f32_array(color).into();
                         ui.checkbox(&mut settings.kcl_model.visible[i], kcl_flag.to_string());
+                        // only the variants the loaded KCL actually has, or all of them if there isn't one
+                        let counts = kcl.as_ref().map(|x| x.vertex_groups[i].variant_counts());
+                        if counts.is_some_and(|x| x.iter().filter(|x| **x > 0).count() < 2) {
+                            return;
+                        }
+                        ui.menu_button("Variants", |ui| {
+                            for variant in 0..KCL_VARIANTS {
+                                let count = counts.map(|x| x[variant]);
+                                if count == Some(0) {
+                                    continue;
+                                }
+                                let label = match count {
+                                    Some(count) => format!("Variant {variant} ({count} triangles)"),
+                                    None => format!("Variant {variant}"),
+                                };
+                                ui.checkbox(&mut settings.kcl_model.variant_visible[i][variant], label);
+                            }
+                        });
                     });
                 }
             });
@@ -125,6 +236,86 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
             }
         });
 
+    egui::CollapsingHeader::new("Selecting").default_open(false).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut settings.pick.radius)
+                    .speed(0.1)
+                    .range(0. ..=50.)
+                    .suffix(" px"),
+            );
+            ui.label("Pick Radius");
+        })
+        .response
+        .on_hover_text_at_pointer(
+            "How close the mouse has to be to a point to click on it. If several points are in range, the nearest one in the section being edited is picked",
+        );
+    });
+
+    egui::CollapsingHeader::new("Nudging").default_open(false).show(ui, |ui| {
+        ui.checkbox(&mut settings.nudge.screen_relative, "Relative to Camera")
+            .on_hover_text_at_pointer(
+                "If enabled, the nudge keybinds move points relative to the camera direction, rather than along the X and Z axes",
+            );
+        ui.label("Step Sizes:");
+        for mode in KmpEditMode::iter() {
+            let Some(step) = settings.nudge.step.get_mut(mode) else {
+                continue;
+            };
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(step).speed(1.).range(0.1..=f32::MAX));
+                ui.label(mode.to_string());
+            });
+        }
+    });
+
+    egui::CollapsingHeader::new("Blender Coordinates")
+        .default_open(false)
+        .show(ui, |ui| {
+            let blender = &mut settings.blender;
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut blender.scale)
+                        .speed(0.001)
+                        .range(0.0001..=100.)
+                        .max_decimals(4),
+                );
+                ui.label("Scale");
+            })
+            .response
+            .on_hover_text_at_pointer(
+                "How many Blender units one unit in game is, when copying positions to Blender or pasting them back. This should match the scale your Blender add-on imports tracks at",
+            );
+            ui.checkbox(&mut blender.flip_forward, "Flip Forward Axis")
+                .on_hover_text_at_pointer(
+                    "If enabled, the game's Z axis points along Blender's -Y axis, which keeps the track the same way round as most add-ons import it. If disabled, it points along +Y",
+                );
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut blender.precision).range(0..=8));
+                ui.label("Decimal Places");
+            });
+        });
+
+    egui::CollapsingHeader::new("Creating Path Points")
+        .default_open(false)
+        .show(ui, |ui| {
+            let snap = &mut settings.path_height_snap;
+            ui.horizontal(|ui| {
+                combobox_enum(ui, &mut snap.mode, None);
+                ui.label("Height");
+            })
+            .response
+            .on_hover_text_at_pointer(
+                "How the height of a new path point linked on from previous points is chosen. 'Ground' puts it where you click, 'Previous Point' keeps the height of the previous point, and 'Smoothed' moves it part of the way from the previous point's height towards the ground",
+            );
+            if snap.mode == PathHeightSnap::Smoothed {
+                ui.add(egui::Slider::new(&mut snap.smoothing, 0.0..=1.0).text("Smoothing"))
+                    .on_hover_text_at_pointer(
+                        "How much of the way towards the ground new points go, where 0 is the previous point's height",
+                    );
+            }
+        });
+
     egui::CollapsingHeader::new("Camera").default_open(true).show(ui, |ui| {
         ui.horizontal(|ui| {
             if ui.button("Reset Positions").clicked() {
@@ -135,8 +326,8 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
                 *orbit_cam = Transform::from_translation(orbit_default.start_pos).looking_at(Vec3::ZERO, Vec3::Y);
                 *topdown_cam.0 = Transform::from_translation(topdown_default.start_pos).looking_at(Vec3::ZERO, Vec3::Z);
                 *topdown_cam.1 = Projection::Orthographic(OrthographicProjection {
-                    near: topdown_default.near,
-                    far: topdown_default.far,
+                    near: settings.camera.top_down.view_distance.near,
+                    far: settings.camera.top_down.view_distance.far,
                     scale: topdown_default.scale,
                     ..default()
                 });
@@ -188,6 +379,7 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
                         );
                     });
             });
+            view_distance_settings(ui, "fly_view_distance", &mut settings.camera.fly.view_distance);
         });
         ui.collapsing("Orbit Camera", |ui| {
             ui.horizontal(|ui| {
@@ -229,6 +421,7 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
                         );
                     });
             });
+            view_distance_settings(ui, "orbit_view_distance", &mut settings.camera.orbit.view_distance);
         });
         ui.collapsing("Top Down Camera", |ui| {
             ui.horizontal(|ui| {
@@ -265,9 +458,22 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
                         );
                     });
             });
+            view_distance_settings(
+                ui,
+                "top_down_view_distance",
+                &mut settings.camera.top_down.view_distance,
+            );
         });
     });
 
+    egui::CollapsingHeader::new("Keyboard Shortcuts")
+        .default_open(false)
+        .show(ui, |ui| {
+            if keybind_settings(ui, &mut keybinds) && !**safe_mode {
+                pkv_writes.save_keybinds();
+            }
+        });
+
     ui.horizontal(|ui| {
         if ui.button("Export Settings").clicked() {
             file_dialog.export_settings();
@@ -278,14 +484,169 @@ pub fn show_settings_tab(ui: &mut Ui, world: &mut World) {
         }
     });
     ui.horizontal(|ui| {
-        if ui.button("Save Settings").clicked() {
-            pkv.set("settings", settings.as_ref()).unwrap();
+        if ui
+            .add_enabled(!**safe_mode, egui::Button::new("Save Settings"))
+            .on_disabled_hover_text("Settings can't be saved in safe mode")
+            .clicked()
+        {
+            pkv_writes.save_settings();
         }
         if ui.button("Reset Settings").clicked() {
             *settings = AppSettings::default();
-            pkv.set("settings", settings.as_ref()).unwrap();
+            if !**safe_mode {
+                pkv_writes.save_settings();
+            }
+        }
+    });
+
+    egui::CollapsingHeader::new("Stored State")
+        .default_open(**safe_mode)
+        .show(ui, |ui| {
+            if **safe_mode {
+                ui.label("Running in safe mode, so the stored tab layout and settings were not loaded.");
+            }
+            ui.label(
+                "Clearing these resets what is loaded the next time KMPeek starts, without changing anything now.",
+            );
+            ui.horizontal(|ui| {
+                if ui.button("Clear Tab Layout").clicked() {
+                    pkv_writes.clear_tree();
+                }
+                if ui.button("Clear Settings").clicked() {
+                    pkv_writes.clear_settings();
+                }
+                if ui.button("Clear All").clicked() {
+                    pkv_writes.clear_all();
+                }
+            });
+        });
+
+    egui::CollapsingHeader::new("System").show(ui, |ui| {
+        // the result of the last attempt, so it stays shown
+        let result_id = ui.id().with("register_kmp_handler_result");
+        if ui
+            .button("Open .kmp Files with KMPeek")
+            .on_hover_text_at_pointer(
+                "Make KMPeek the program that opens .kmp files when they are double clicked, for the current user",
+            )
+            .clicked()
+        {
+            let result = match register_kmp_handler() {
+                Ok(()) => "KMPeek will now open .kmp files".to_string(),
+                Err(err) => format!("{err:#}"),
+            };
+            ui.data_mut(|d| d.insert_temp(result_id, result));
+        }
+        if let Some(result) = ui.data(|d| d.get_temp::<String>(result_id)) {
+            ui.label(result);
         }
+        ui.label("Files can also be opened by dropping them onto the window.");
     });
 
     ss.apply(world);
 }
+
+// returns whether any keybinds were changed
+fn keybind_settings(ui: &mut Ui, keybinds: &mut Keybinds) -> bool {
+    let mut changed = false;
+    ui.label("Click a shortcut and press the new keys for it, or Escape to cancel.");
+    egui::Grid::new("keybinds").striped(true).show(ui, |ui| {
+        for action in KeyAction::iter() {
+            ui.label(action.to_string());
+            ui.horizontal(|ui| {
+                let mut remove = None;
+                for (i, keybind) in keybinds.get(action).to_vec().into_iter().enumerate() {
+                    let recording = keybinds.recording == Some((action, i));
+                    let conflicts = keybinds.conflicts(action, keybind);
+                    let mut text = egui::RichText::new(if recording {
+                        "Press keys...".to_string()
+                    } else {
+                        keybind.to_string()
+                    });
+                    if !conflicts.is_empty() {
+                        text = text.color(egui::Color32::from_rgb(255, 69, 0));
+                    }
+                    let mut response = ui.selectable_label(recording, text);
+                    if !conflicts.is_empty() {
+                        response = response.on_hover_text_at_pointer(format!("Also used for {}", conflicts.join(", ")));
+                    }
+                    if response.clicked() {
+                        keybinds.recording = if recording { None } else { Some((action, i)) };
+                    }
+                    if response.secondary_clicked() {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove {
+                    keybinds.get_mut(action).remove(i);
+                    keybinds.recording = None;
+                    changed = true;
+                }
+                let new_index = keybinds.get(action).len();
+                let recording_new = keybinds.recording == Some((action, new_index));
+                if ui
+                    .selectable_label(recording_new, if recording_new { "Press keys..." } else { "+" })
+                    .on_hover_text_at_pointer("Add another shortcut. Right click a shortcut to remove it")
+                    .clicked()
+                {
+                    keybinds.recording = if recording_new { None } else { Some((action, new_index)) };
+                }
+            });
+            ui.end_row();
+        }
+    });
+    if ui.button("Reset Shortcuts").clicked() {
+        *keybinds = Keybinds::default();
+        changed = true;
+    }
+    changed
+}
+
+fn view_distance_settings(ui: &mut Ui, id: &str, view_distance: &mut ViewDistance) {
+    ui.horizontal(|ui| {
+        ui.label("View Distance")
+            .on_hover_text_at_pointer("Presets for the clip planes and fog to suit the size of the track");
+        let selected = view_distance.preset().map_or("Custom".to_string(), |x| x.to_string());
+        egui::ComboBox::from_id_source(id)
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for preset in ViewDistancePreset::iter() {
+                    if ui
+                        .selectable_label(view_distance.preset() == Some(preset), preset.to_string())
+                        .clicked()
+                    {
+                        // keep whether fog is enabled, as the presets are about the size of the track
+                        let fog = view_distance.fog;
+                        *view_distance = preset.view_distance();
+                        view_distance.fog = fog;
+                    }
+                }
+            });
+    });
+    ui.horizontal(|ui| {
+        ui.label("Near Clip").on_hover_text_at_pointer(
+            "Anything closer to the camera than this isn't drawn. Increase this if surfaces flicker through each other",
+        );
+        ui.add(
+            egui::DragValue::new(&mut view_distance.near)
+                .speed(0.1)
+                .range(0.001..=f32::MAX),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Far Clip").on_hover_text_at_pointer(
+            "Anything further from the camera than this isn't drawn. Increase this if the edges of the track are cut off",
+        );
+        ui.add(egui::DragValue::new(&mut view_distance.far).speed(1000.).range(view_distance.near..=f32::MAX));
+    });
+    ui.checkbox(&mut view_distance.fog, "Fog")
+        .on_hover_text_at_pointer("Fade things out into the background with distance, to make depth easier to judge");
+    if view_distance.fog {
+        ui.horizontal(|ui| {
+            ui.label("Fog Start");
+            ui.add(egui::DragValue::new(&mut view_distance.fog_start).speed(1000.));
+            ui.label("End");
+            ui.add(egui::DragValue::new(&mut view_distance.fog_end).speed(1000.));
+        });
+    }
+}
@@ -0,0 +1,156 @@
+use crate::viewer::{
+    edit::select::Selected,
+    kmp::{
+        components::{
+            AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
+            KmpSelectablePoint, Object, RespawnPoint, RoutePoint, StartPoint,
+        },
+        path::EntityPathGroups,
+        sections::KmpEditMode,
+        validate::MAX_POINT_INDEX,
+    },
+};
+use bevy::prelude::*;
+use bevy_egui::egui::{self, Color32, Ui};
+
+// the sizes of each part of the KMP in bytes, for estimating the size of the file
+const HEADER_SIZE: usize = 0x4C;
+const SECTION_HEADER_SIZE: usize = 0x8;
+const PATH_GROUP_SIZE: usize = 0x10;
+const ROUTE_HEADER_SIZE: usize = 0x4;
+const STGI_SIZE: usize = 0xC;
+// how close to a limit a count has to be before it is warned about
+const LIMIT_WARNING_FRACTION: f32 = 0.9;
+
+const WARNING_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+
+struct SectionStats {
+    mode: KmpEditMode,
+    points: usize,
+    selected: usize,
+    /// The number of groups written for paths, or routes for route points
+    groups: Option<usize>,
+    /// The most points the section can have before they can't all be referred to
+    limit: Option<usize>,
+    size: usize,
+}
+impl SectionStats {
+    fn new<T: Component>(world: &mut World, entry_size: usize, limit: Option<usize>) -> Self {
+        let mut q = world.query_filtered::<Has<Selected>, (With<T>, With<KmpSelectablePoint>)>();
+        let (points, selected) = q
+            .iter(world)
+            .fold((0, 0), |(points, selected), x| (points + 1, selected + x as usize));
+        Self {
+            mode: KmpEditMode::from_type::<T>(),
+            points,
+            selected,
+            groups: None,
+            limit,
+            size: SECTION_HEADER_SIZE + points * entry_size,
+        }
+    }
+    // the groups are written as their own section, apart from routes which have a small header before each route
+    fn with_groups<T: Component>(mut self, world: &World, group_size: usize, own_section: bool) -> Self {
+        let groups = world.get_resource::<EntityPathGroups<T>>().map_or(0, |x| x.len());
+        self.groups = Some(groups);
+        self.size += groups * group_size;
+        if own_section {
+            self.size += SECTION_HEADER_SIZE;
+        }
+        self
+    }
+    fn near_limit(&self) -> Option<usize> {
+        self.limit
+            .filter(|limit| self.points as f32 >= *limit as f32 * LIMIT_WARNING_FRACTION)
+    }
+}
+
+pub fn show_stats_tab(ui: &mut Ui, world: &mut World) {
+    let path_limit = Some(MAX_POINT_INDEX + 1);
+    // respawns, cameras and routes are referred to by a single byte elsewhere in the KMP
+    let index_limit = Some(MAX_POINT_INDEX);
+    let stats = [
+        SectionStats::new::<StartPoint>(world, 0x1C, None),
+        SectionStats::new::<EnemyPathPoint>(world, 0x14, path_limit).with_groups::<EnemyPathPoint>(
+            world,
+            PATH_GROUP_SIZE,
+            true,
+        ),
+        SectionStats::new::<ItemPathPoint>(world, 0x14, path_limit).with_groups::<ItemPathPoint>(
+            world,
+            PATH_GROUP_SIZE,
+            true,
+        ),
+        SectionStats::new::<Checkpoint>(world, 0x14, path_limit).with_groups::<Checkpoint>(
+            world,
+            PATH_GROUP_SIZE,
+            true,
+        ),
+        SectionStats::new::<RespawnPoint>(world, 0x1C, index_limit),
+        SectionStats::new::<Object>(world, 0x3C, None),
+        SectionStats::new::<RoutePoint>(world, 0x10, None).with_groups::<RoutePoint>(world, ROUTE_HEADER_SIZE, false),
+        SectionStats::new::<AreaPoint>(world, 0x30, None),
+        SectionStats::new::<KmpCamera>(world, 0x48, index_limit),
+        SectionStats::new::<CannonPoint>(world, 0x1C, None),
+        SectionStats::new::<BattleFinishPoint>(world, 0x1C, None),
+    ];
+    let size = HEADER_SIZE + SECTION_HEADER_SIZE + STGI_SIZE + stats.iter().map(|x| x.size).sum::<usize>();
+    // too many routes is the same problem as too many points, just counted by group
+    let routes = stats
+        .iter()
+        .find(|x| x.mode == KmpEditMode::Routes)
+        .and_then(|x| x.groups)
+        .unwrap_or_default();
+
+    ui.label(format!(
+        "Estimated file size: {:.1} KB ({size} bytes)",
+        size as f32 / 1024.
+    ));
+    ui.separator();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("kmp_stats").striped(true).show(ui, |ui| {
+            ui.strong("Section");
+            ui.strong("Points");
+            ui.strong("Selected");
+            ui.strong("Groups");
+            ui.end_row();
+            for section in stats.iter() {
+                ui.label(section.mode.to_string());
+                let points = match section.limit {
+                    Some(limit) => format!("{} / {limit}", section.points),
+                    None => section.points.to_string(),
+                };
+                match section.near_limit() {
+                    Some(limit) if section.points > limit => ui.colored_label(Color32::RED, points),
+                    Some(_) => ui.colored_label(WARNING_COLOR, points),
+                    None => ui.label(points),
+                };
+                ui.label(section.selected.to_string());
+                ui.label(section.groups.map(|x| x.to_string()).unwrap_or_default());
+                ui.end_row();
+            }
+        });
+
+        let mut warnings: Vec<_> = stats
+            .iter()
+            .filter_map(|x| Some((x, x.near_limit()?)))
+            .map(|(section, limit)| {
+                format!(
+                    "{} has {} of at most {limit} points. Going over the limit freezes the game",
+                    section.mode, section.points
+                )
+            })
+            .collect();
+        if routes as f32 >= MAX_POINT_INDEX as f32 * LIMIT_WARNING_FRACTION {
+            warnings.push(format!(
+                "There are {routes} routes, but only {MAX_POINT_INDEX} can be referred to by areas and cameras"
+            ));
+        }
+        if !warnings.is_empty() {
+            ui.separator();
+        }
+        for warning in warnings {
+            ui.colored_label(WARNING_COLOR, warning);
+        }
+    });
+}
@@ -1,22 +1,34 @@
 use crate::{
     ui::{
+        errors::ErrorMessages,
         keybinds::ModifiersPressed,
         util::{combobox_enum, drag_vec3, euler_to_quat_ui, get_euler_rot, DragSpeed},
         viewport::ViewportInfo,
     },
+    util::kmp_file::ExtendedPresence,
     viewer::{
         edit::{create_delete::CreatePoint, select::Selected},
         kmp::{
+            checkpoints::CheckpointRespawnLink,
             components::{
                 AreaKind, AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint,
-                KmpCamera, Object, RespawnPoint, StartPoint,
+                KmpCamera, Object, RespawnPoint, StartPoint, TrackInfo,
             },
-            ordering::OrderId,
+            csv::section_supports_csv,
+            ordering::{OrderId, OriginalIndex},
+            path::{EntityPathGroups, KmpPathNode},
+            row_clipboard::{copy_section_rows, paste_section_rows, RowClipboard},
+            schema::KmpSchema,
             sections::KmpEditMode,
+            start_points::{player_index_name, player_index_options},
         },
     },
 };
-use bevy::{ecs::system::SystemState, prelude::*};
+use bevy::{
+    ecs::{entity::EntityHashMap, system::SystemState},
+    prelude::*,
+    utils::HashSet,
+};
 use bevy_egui::egui::{self, emath::Numeric, Checkbox, Direction, DragValue, Layout, Response, Sense, Ui};
 use egui_extras::{Column, TableBuilder, TableRow};
 
@@ -30,7 +42,15 @@ pub fn show_table_tab(ui: &mut Ui, world: &mut World) {
             if ui.button("+").clicked() {
                 world.send_event_default::<CreatePoint>();
             }
+            show_row_clipboard_buttons(ui, world);
         });
+        let mode = *world.resource::<KmpEditMode>();
+        if let Some(note) = world
+            .get_resource::<TrackInfo>()
+            .and_then(|x| mode.unused_note(&x.track_type))
+        {
+            ui.weak(note);
+        }
     }
 
     show_kmp_table::<StartPoint>(ui, world);
@@ -45,19 +65,102 @@ pub fn show_table_tab(ui: &mut Ui, world: &mut World) {
     show_kmp_table::<BattleFinishPoint>(ui, world);
 }
 
+// copy and paste selected rows with the buttons, or with the usual shortcuts while the pointer is over the table
+fn show_row_clipboard_buttons(ui: &mut Ui, world: &mut World) {
+    let mode = *world.resource::<KmpEditMode>();
+    if !section_supports_csv(mode) {
+        return;
+    }
+    let mut copy = ui
+        .button("Copy Rows")
+        .on_hover_text("Copy the selected rows, which can also be pasted into a spreadsheet")
+        .clicked();
+    let mut paste = ui
+        .add_enabled(
+            world.resource::<RowClipboard>().0.is_some(),
+            egui::Button::new("Paste Rows"),
+        )
+        .on_hover_text(
+            "Paste copied rows onto the selected rows in order, or one row onto all of them. Rows copied from a \
+            spreadsheet can be pasted with Ctrl+V while the pointer is over the table.",
+        )
+        .clicked()
+        .then(|| world.resource::<RowClipboard>().0.clone())
+        .flatten();
+
+    // leave the shortcuts alone while a value is being typed, so they still work on the text
+    if world.resource::<ViewportInfo>().mouse_in_table && !ui.ctx().wants_keyboard_input() {
+        ui.input(|i| {
+            for event in i.events.iter() {
+                match event {
+                    egui::Event::Copy => copy = true,
+                    egui::Event::Paste(text) => paste = Some(text.clone()),
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    if copy {
+        match copy_section_rows(world, mode) {
+            Ok(text) => {
+                ui.ctx().output_mut(|o| o.copied_text = text.clone());
+                world.resource_mut::<RowClipboard>().0 = Some(text);
+            }
+            Err(err) => world.resource_mut::<ErrorMessages>().push(err),
+        }
+    }
+    if let Some(text) = paste {
+        if let Err(err) = paste_section_rows(world, mode, &text) {
+            world.resource_mut::<ErrorMessages>().push(err);
+        }
+    }
+}
+
 trait ShowKmpTableTrait {
     const ROTATION: bool = true;
     const Y_TRANSLATION: bool = true;
     const COLUMNS: &'static [&'static str];
     fn show_row(row: &mut TableRow, item: &mut Self);
+    /// Read-only columns shown after the editable ones, which are worked out from the rest of the KMP
+    const INFO_COLUMNS: &'static [&'static str] = &[];
+    fn row_info(_world: &mut World) -> EntityHashMap<Vec<InfoCell>> {
+        EntityHashMap::default()
+    }
+}
+
+struct InfoCell {
+    text: String,
+    /// Entity which is selected when the cell is clicked
+    select: Option<Entity>,
+}
+impl InfoCell {
+    fn from_ids(ids: impl IntoIterator<Item = u32>) -> Self {
+        let mut ids: Vec<_> = ids.into_iter().collect();
+        ids.sort();
+        let text = if ids.is_empty() {
+            "-".into()
+        } else {
+            ids.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        Self { text, select: None }
+    }
 }
 
 use DragSpeed::*;
 
 impl ShowKmpTableTrait for StartPoint {
-    const COLUMNS: &'static [&'static str] = &["Player Index"];
+    const COLUMNS: &'static [&'static str] = &["Player"];
     fn show_row(row: &mut TableRow, item: &mut Self) {
-        drag_value_column(row, Slow, &mut item.player_index);
+        row.col(|ui| {
+            egui::ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(player_index_name(item.player_index))
+                .show_ui(ui, |ui| {
+                    for index in player_index_options() {
+                        ui.selectable_value(&mut item.player_index, index, player_index_name(index));
+                    }
+                });
+        });
     }
 }
 
@@ -92,6 +195,41 @@ impl ShowKmpTableTrait for Checkpoint {
     const ROTATION: bool = false;
     const Y_TRANSLATION: bool = false;
     const COLUMNS: &'static [&'static str] = &["Type"];
+    const INFO_COLUMNS: &'static [&'static str] = &["Respawn", "Group", "Previous", "Next"];
+    fn row_info(world: &mut World) -> EntityHashMap<Vec<InfoCell>> {
+        let order_ids: EntityHashMap<u32> = world
+            .query::<(Entity, &OrderId)>()
+            .iter(world)
+            .map(|(e, id)| (e, id.0))
+            .collect();
+        let groups = world.get_resource::<EntityPathGroups<Checkpoint>>().cloned();
+        let mut q =
+            world.query_filtered::<(Entity, Option<&CheckpointRespawnLink>, Option<&KmpPathNode>), With<Checkpoint>>();
+        q.iter(world)
+            .map(|(e, respawn, node)| {
+                let respawn = match respawn.and_then(|x| order_ids.get(&x.0).map(|id| (x.0, id))) {
+                    Some((respawn_e, id)) => InfoCell {
+                        text: id.to_string(),
+                        select: Some(respawn_e),
+                    },
+                    None => InfoCell::from_ids([]),
+                };
+                let group = groups
+                    .as_ref()
+                    .and_then(|x| x.iter().position(|group| group.path.contains(&e)));
+                let linked_ids = |nodes: Option<&HashSet<Entity>>| {
+                    InfoCell::from_ids(nodes.into_iter().flatten().filter_map(|e| order_ids.get(e).copied()))
+                };
+                let cells = vec![
+                    respawn,
+                    InfoCell::from_ids(group.map(|x| x as u32)),
+                    linked_ids(node.map(|x| &x.prev_nodes)),
+                    linked_ids(node.map(|x| &x.next_nodes)),
+                ];
+                (e, cells)
+            })
+            .collect()
+    }
     fn show_row(row: &mut TableRow, item: &mut Self) {
         combobox_column(row, &mut item.kind);
     }
@@ -117,6 +255,7 @@ impl ShowKmpTableTrait for Object {
         "Setting 7",
         "Setting 8",
         "Presence",
+        "Extended Presence",
     ];
     fn show_row(row: &mut TableRow, item: &mut Self) {
         drag_vec3_column(row, Slow, &mut item.scale);
@@ -125,6 +264,12 @@ impl ShowKmpTableTrait for Object {
             drag_value_column(row, Slow, setting);
         }
         drag_value_column(row, Slow, &mut item.presence);
+        // the raw value, as LE-CODE tools show it
+        let mut extended_presence = item.extended_presence.to_raw();
+        drag_value_column(row, Slow, &mut extended_presence);
+        if extended_presence != item.extended_presence.to_raw() {
+            item.extended_presence = ExtendedPresence::from_raw(extended_presence);
+        }
     }
 }
 
@@ -209,13 +354,23 @@ impl ShowKmpTableTrait for BattleFinishPoint {
     fn show_row(_: &mut TableRow, _: &mut Self) {}
 }
 
-fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut Ui, world: &mut World) {
+fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait + KmpSchema>(ui: &mut Ui, world: &mut World) {
     if !world.resource::<KmpEditMode>().in_mode::<T>() {
         return;
     }
 
+    let row_info = T::row_info(world);
+    let mut select_linked = None;
+
     let mut ss = SystemState::<(
-        Query<(&mut T, &mut Transform, Entity, Has<Selected>, &OrderId)>,
+        Query<(
+            &mut T,
+            &mut Transform,
+            Entity,
+            Has<Selected>,
+            &OrderId,
+            Option<&OriginalIndex>,
+        )>,
         Query<Entity, With<T>>,
         Commands,
         Res<ButtonInput<KeyCode>>,
@@ -228,13 +383,14 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
         .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
         .sense(Sense::click())
         .column(Column::exact(25.)) // id
+        .column(Column::exact(40.)) // original index
         .column(Column::exact(50.)) // selected
         .column(Column::auto().resizable(true)); // translation
 
     if T::ROTATION {
         table_builder = table_builder.column(Column::auto().resizable(true));
     }
-    for _ in T::COLUMNS.iter() {
+    for _ in T::COLUMNS.iter().chain(T::INFO_COLUMNS) {
         table_builder = table_builder.column(Column::auto().resizable(true));
     }
 
@@ -245,6 +401,10 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
         header.col(|ui| {
             ui.label("ID");
         });
+        header.col(|ui| {
+            ui.label("File ID")
+                .on_hover_text_at_pointer("The ID this point had in the file when it was opened or last saved");
+        });
         header.col(|ui| {
             ui.label("Selected");
         });
@@ -256,9 +416,12 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
                 ui.label("Rotation");
             });
         }
-        for name in T::COLUMNS.iter() {
+        for name in T::COLUMNS.iter().chain(T::INFO_COLUMNS) {
             header.col(|ui| {
-                ui.label(*name);
+                let res = ui.label(*name);
+                if let Some(field) = T::field(name) {
+                    res.on_hover_text_at_pointer(field.tooltip());
+                }
             });
         }
         // empty header (which fills remaining space on the right)
@@ -266,7 +429,7 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
     });
 
     table.body(|mut body| {
-        for (mut t, mut transform, e, is_selected, order_id) in q.iter_mut().sort::<&OrderId>() {
+        for (mut t, mut transform, e, is_selected, order_id, original_index) in q.iter_mut().sort::<&OrderId>() {
             body.row(20., |mut row| {
                 row.set_selected(is_selected);
 
@@ -276,6 +439,17 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
                 row.col(|ui| {
                     ui.add(egui::Label::new(order_id.to_string()).selectable(false));
                 });
+                row.col(|ui| {
+                    // highlight the points which will be saved with a different ID
+                    let text = match original_index {
+                        Some(x) if x.0 != order_id.0 => {
+                            egui::RichText::new(x.to_string()).color(egui::Color32::from_rgb(255, 165, 0))
+                        }
+                        Some(x) => egui::RichText::new(x.to_string()),
+                        None => egui::RichText::new("-").weak(),
+                    };
+                    ui.add(egui::Label::new(text).selectable(false));
+                });
                 row.col(|ui| {
                     select_checkbox_changed = ui.add(Checkbox::without_text(&mut select_checkbox)).changed();
                 });
@@ -325,6 +499,27 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
 
                 T::show_row(&mut row, &mut t_cp);
 
+                let cells = row_info.get(&e).map(|x| x.as_slice()).unwrap_or_default();
+                for i in 0..T::INFO_COLUMNS.len() {
+                    let Some(cell) = cells.get(i) else {
+                        row.col(|_| {});
+                        continue;
+                    };
+                    row.col(|ui| {
+                        let Some(linked_e) = cell.select else {
+                            ui.add(egui::Label::new(&cell.text).selectable(false));
+                            return;
+                        };
+                        if ui
+                            .link(&cell.text)
+                            .on_hover_text_at_pointer("Click to select")
+                            .clicked()
+                        {
+                            select_linked = Some(linked_e);
+                        }
+                    });
+                }
+
                 t.set_if_neq(t_cp);
                 transform.set_if_neq(transform_cp);
 
@@ -351,6 +546,18 @@ fn show_kmp_table<T: Component + PartialEq + Clone + ShowKmpTableTrait>(ui: &mut
     });
 
     ss.apply(world);
+
+    // select the point that was clicked on in one of the info columns, and switch to its section so it can be seen
+    if let Some(linked_e) = select_linked {
+        let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+        for e in selected {
+            world.entity_mut(e).remove::<Selected>();
+        }
+        world.entity_mut(linked_e).insert(Selected);
+        if world.get::<RespawnPoint>(linked_e).is_some() {
+            world.resource_mut::<KmpEditMode>().set_mode::<RespawnPoint>();
+        }
+    }
 }
 
 // #[derive(SystemParam)]
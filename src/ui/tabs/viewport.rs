@@ -1,18 +1,48 @@
 use crate::{
     ui::{
+        keybinds::{KeyAction, Keybinds},
         settings::AppSettings,
+        ui_state::KmpFilePath,
         util::{button_triggered_popup, image_selectable_value, Icons},
         viewport::{ViewportImage, ViewportInfo},
     },
-    util::ToEguiRect,
+    util::{kcl_file::KclFlag, world_to_ui_viewport, ToEguiRect},
     viewer::{
-        camera::{CameraMode, CameraModeChanged},
-        edit::{link_select_mode::LinkSelectMode, select::SelectBox, EditMode},
-        kmp::components::{RespawnPoint, RoutePoint},
+        camera::{
+            CameraMode, CameraModeChanged, CursorKclPos, Gizmo2dCam, GoToCameraBookmark, GoToHomeCamera, SetHomeCamera,
+        },
+        edit::{
+            context_menu::{create_point_at_menu, select_only, ViewportContextMenu},
+            create_delete::DuplicateSelected,
+            guides::{Guide, GuideAxis, Rulers, TopDownMapping},
+            kcl_snap::DropSelectionToKcl,
+            link_select_mode::LinkSelectState,
+            link_unlink_path::link_selected_to,
+            measure::Measurement,
+            pin::{pinned_readouts, unpin},
+            select::{PickMode, SelectBox, SelectRegion, SelectShape, Selected},
+            EditMode,
+        },
+        kmp::{
+            checkpoints::CheckpointRight,
+            components::{
+                AreaPoint, CheckpointMarker, EnemyPathPoint, ItemPathPoint, KmpCamera, KmpCameraKind,
+                KmpSelectablePoint, PathOverallStart, RouteSettings, StartPoint, TrackInfo,
+            },
+            ordering::{OrderId, RefreshOrdering},
+            path::KmpPathNode,
+            path_start::set_path_start,
+            path_tools::{insert_point_between, insert_point_on_link},
+            sections::KmpEditMode,
+            sidecar::KmpSidecar,
+            start_points::start_slots,
+            value_colors::{value_color, ColorByField, ValueColorLegend},
+        },
     },
 };
 use bevy::{ecs::system::SystemState, math::vec2, prelude::*, render::render_resource::Extent3d};
-use bevy_egui::egui::{self, show_tooltip_at_pointer, Color32, Margin, Response, Rounding, Sense, Stroke, Ui};
+use bevy_egui::egui::{self, Color32, Margin, Response, Rounding, Sense, Stroke, Ui};
+use strum::IntoEnumIterator;
 use transform_gizmo_bevy::{config::TransformPivotPoint, GizmoOptions, GizmoOrientation};
 
 pub fn show_viewport_tab(ui: &mut Ui, world: &mut World) {
@@ -61,21 +91,45 @@ pub fn show_viewport_tab(ui: &mut Ui, world: &mut World) {
     viewport_info.viewport_rect = viewport_rect;
 
     show_select_box(ui, world);
+    show_value_color_legend(ui, world);
+    show_camera_labels(ui, world);
+    show_start_slot_labels(ui, world);
+    show_route_labels(ui, world);
+    show_position_readout(ui, world);
 
-    let responses = show_overlayed_ui(ui, world);
+    let mut responses = show_rulers_and_guides(ui, world);
+    responses.extend(show_overlayed_ui(ui, world));
+    responses.extend(show_pinned_readouts(ui, world));
+    responses.extend(show_measurement(ui, world));
+    responses.extend(show_context_menu(ui, world));
 
     world.resource_mut::<ViewportInfo>().mouse_on_overlayed_ui = responses.iter().any(|x| x.contains_pointer());
 
-    // show the route hover label if needed
-    if world.contains_resource::<LinkSelectMode<RoutePoint>>() {
-        show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), ui.next_auto_id(), |ui| {
-            ui.label("Select a Route (ESC to cancel)");
-        });
-    } else if world.contains_resource::<LinkSelectMode<RespawnPoint>>() {
-        show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), ui.next_auto_id(), |ui| {
-            ui.label("Select a Respawn (ESC to cancel)");
-        });
-    }
+    show_link_select_banner(ui, world);
+}
+
+// while picking a point to link to, say what to pick across the top of the viewport, so it's hard to miss
+fn show_link_select_banner(ui: &mut Ui, world: &mut World) {
+    let Some(prompt) = world.resource::<LinkSelectState>().prompt() else {
+        return;
+    };
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect.to_egui_rect();
+    let margin = ruler_margin(world);
+    let painter = ui.painter_at(vp_rect);
+    let visuals = ui.visuals();
+    let galley = painter.layout_no_wrap(
+        prompt.into(),
+        egui::FontId::proportional(16.),
+        visuals.strong_text_color(),
+    );
+    let rect = egui::Align2::CENTER_TOP.anchor_size(vp_rect.center_top() + egui::vec2(0., 10. + margin), galley.size());
+    painter.rect(
+        rect.expand(6.),
+        4.,
+        visuals.selection.bg_fill.gamma_multiply(0.8),
+        visuals.selection.stroke,
+    );
+    painter.galley(rect.min, galley, visuals.strong_text_color());
 }
 
 fn show_select_box(ui: &mut Ui, world: &mut World) {
@@ -83,21 +137,785 @@ fn show_select_box(ui: &mut Ui, world: &mut World) {
     ui.allocate_ui_at_rect(vp_rect, |ui| {
         ui.set_clip_rect(vp_rect);
         let painter = ui.painter();
-        if let Some(select_box) = world.resource::<SelectBox>().0 {
-            let select_box = select_box.to_egui_rect();
-            painter.rect(
-                select_box,
-                Rounding::from(2.),
-                Color32::from_rgba_unmultiplied(200, 200, 200, 15),
-                Stroke {
-                    width: 1.,
-                    color: Color32::GRAY,
-                },
-            );
+        let stroke = Stroke {
+            width: 1.,
+            color: Color32::GRAY,
+        };
+        match &world.resource::<SelectBox>().region {
+            Some(SelectRegion::Box(select_box)) => {
+                painter.rect(
+                    select_box.to_egui_rect(),
+                    Rounding::from(2.),
+                    Color32::from_rgba_unmultiplied(200, 200, 200, 15),
+                    stroke,
+                );
+            }
+            // the lasso isn't filled, as egui can only fill convex shapes
+            Some(SelectRegion::Lasso(points)) => {
+                let points = points.iter().map(|x| egui::pos2(x.x, x.y)).collect();
+                painter.add(egui::Shape::closed_line(points, stroke));
+            }
+            None => {}
         }
     });
 }
 
+fn format_pos(pos: Vec3) -> String {
+    format!("X {:.1}  Y {:.1}  Z {:.1}", pos.x, pos.y, pos.z)
+}
+
+// where on the KCL the mouse is, and the centre of the selection, along the bottom of the viewport
+fn show_position_readout(ui: &mut Ui, world: &mut World) {
+    let positions: Vec<_> = world
+        .query_filtered::<&Transform, (With<Selected>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .map(|x| x.translation)
+        .collect();
+    let mut lines = Vec::new();
+    if let Some(pos) = **world.resource::<CursorKclPos>() {
+        lines.push(format!("Cursor: {}", format_pos(pos)));
+    }
+    if !positions.is_empty() {
+        let centre = positions.iter().sum::<Vec3>() / positions.len() as f32;
+        lines.push(format!("Selection: {}", format_pos(centre)));
+    }
+    if lines.is_empty() {
+        return;
+    }
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect.to_egui_rect();
+    let margin = ruler_margin(world);
+    let painter = ui.painter_at(vp_rect);
+    let text_color = ui.visuals().strong_text_color();
+    let galley = painter.layout_no_wrap(lines.join("    "), egui::FontId::monospace(12.), text_color);
+    let rect =
+        egui::Align2::CENTER_BOTTOM.anchor_size(vp_rect.center_bottom() + egui::vec2(0., -5. - margin), galley.size());
+    painter.rect_filled(rect.expand(3.), 2., ui.visuals().extreme_bg_color.gamma_multiply(0.7));
+    painter.galley(rect.min, galley, text_color);
+}
+
+// the values of the pinned points in the top right corner, which stay shown whatever is selected
+fn show_pinned_readouts(ui: &mut Ui, world: &mut World) -> Option<Response> {
+    let readouts = pinned_readouts(world);
+    if readouts.is_empty() {
+        return None;
+    }
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect.to_egui_rect();
+    let margin = ruler_margin(world);
+    let mut to_unpin = None;
+    let res = egui::Area::new(ui.id().with("pinned_readouts"))
+        .fixed_pos(vp_rect.right_top() + egui::vec2(-5. - margin, 40.))
+        .pivot(egui::Align2::RIGHT_TOP)
+        .constrain_to(vp_rect)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (i, readout) in readouts.iter().enumerate() {
+                    if i > 0 {
+                        ui.separator();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.strong(&readout.title);
+                        if ui.small_button("✖").on_hover_text_at_pointer("Unpin").clicked() {
+                            to_unpin = Some(readout.entity);
+                        }
+                    });
+                    egui::Grid::new(("pinned_readout", readout.entity)).show(ui, |ui| {
+                        for (label, value) in readout.rows.iter() {
+                            ui.label(*label);
+                            ui.monospace(value);
+                            ui.end_row();
+                        }
+                    });
+                }
+            });
+        })
+        .response;
+    if let Some(e) = to_unpin {
+        unpin(world, [e]);
+    }
+    Some(res)
+}
+
+/// Something that can be chosen from the viewport context menu
+enum ContextAction {
+    AddPoint,
+    Duplicate,
+    Delete,
+    DropToKcl,
+    SetPathStart,
+    LinkSelected,
+    InsertBetween(Entity),
+    InsertOnLink(Entity),
+    ToggleShowArea,
+    HideKcl(usize),
+    GoHome,
+    GoToBookmark(usize),
+}
+
+// the menu opened by right clicking in the viewport, with whatever can be done to the point or KCL that was clicked
+fn show_context_menu(ui: &mut Ui, world: &mut World) -> Option<Response> {
+    let menu = world.remove_resource::<ViewportContextMenu>()?;
+    // the point could have been deleted some other way while the menu was open
+    if menu.point.is_some_and(|e| world.get_entity(e).is_none()) {
+        return None;
+    }
+    let mode = *world.resource::<KmpEditMode>();
+    let targets = menu.targets(world);
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    let point = menu.point.map(|e| world.entity(e));
+    let is_path_pt = point.is_some_and(|x| {
+        x.contains::<EnemyPathPoint>() || x.contains::<ItemPathPoint>() || x.contains::<CheckpointMarker>()
+    });
+    let is_path_start = point.is_some_and(|x| {
+        x.contains::<PathOverallStart>()
+            || x.get::<CheckpointRight>()
+                .is_some_and(|x| world.get::<PathOverallStart>(x.left).is_some())
+    });
+    let node = point.and_then(|x| x.get::<KmpPathNode>()).cloned();
+    let area_shown = point.and_then(|x| x.get::<AreaPoint>()).map(|x| x.show_area);
+    let point_selected = point.is_some_and(|x| x.contains::<Selected>());
+    // linking needs points selected other than the one clicked on, which must be a path point
+    let can_link = node.is_some() && !point_selected && !selected.is_empty();
+    // a point can be put in between the clicked point and a single other selected point it links straight to
+    let insert_with = match (&node, &selected[..]) {
+        (Some(_), [other]) if !point_selected => Some(*other),
+        (Some(_), [a, b]) if point_selected => Some(if Some(*a) == menu.point { *b } else { *a }),
+        _ => None,
+    }
+    .filter(|other| {
+        node.as_ref()
+            .is_some_and(|x| x.next_nodes.contains(other) || x.prev_nodes.contains(other))
+    });
+    let can_create = mode != KmpEditMode::TrackInfo && menu.create_pos(world).is_some();
+    let bookmarks: Vec<_> = world
+        .get_resource::<KmpSidecar>()
+        .map(|x| {
+            x.camera_bookmarks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, x)| x.as_ref().map(|x| (i, x.name.clone())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let keybinds = world.resource::<Keybinds>();
+
+    let points_text = |n: usize| {
+        if n == 1 {
+            "Point".to_string()
+        } else {
+            format!("{n} Points")
+        }
+    };
+    let mut action = None;
+    let res = egui::Area::new(ui.id().with("viewport_context_menu"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(menu.pos.to_array())
+        .constrain(true)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::menu(ui.style()).show(ui, |ui| {
+                ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui| {
+                    if can_create
+                        && ui
+                            .button("Add Point Here")
+                            .on_hover_text_at_pointer("Same as Alt clicking here")
+                            .clicked()
+                    {
+                        action = Some(ContextAction::AddPoint);
+                    }
+                    if !targets.is_empty() {
+                        if can_create {
+                            ui.separator();
+                        }
+                        if ui.button(format!("Duplicate {}", points_text(targets.len()))).clicked() {
+                            action = Some(ContextAction::Duplicate);
+                        }
+                        if ui.button(format!("Delete {}", points_text(targets.len()))).clicked() {
+                            action = Some(ContextAction::Delete);
+                        }
+                        if ui
+                            .button("Drop to KCL")
+                            .on_hover_text_at_pointer("Move the points straight down onto the KCL below them")
+                            .clicked()
+                        {
+                            action = Some(ContextAction::DropToKcl);
+                        }
+                    }
+                    if is_path_pt
+                        && ui
+                            .add_enabled(!is_path_start, egui::Button::new("Set as Path Start"))
+                            .on_hover_text_at_pointer("Make this the point the section's paths start from")
+                            .clicked()
+                    {
+                        action = Some(ContextAction::SetPathStart);
+                    }
+                    if can_link
+                        && ui
+                            .button("Link Selected to This")
+                            .on_hover_text_at_pointer("Same as Alt clicking this point")
+                            .clicked()
+                    {
+                        action = Some(ContextAction::LinkSelected);
+                    }
+                    if let Some(other) = insert_with {
+                        if ui
+                            .button("Insert Point Between")
+                            .on_hover_text_at_pointer(
+                                "Add a point halfway between this point and the selected point it links to",
+                            )
+                            .clicked()
+                        {
+                            action = Some(ContextAction::InsertBetween(other));
+                        }
+                    }
+                    if let Some(link) = menu.link {
+                        if ui
+                            .button("Insert Point on Link")
+                            .on_hover_text_at_pointer("Same as Alt clicking the link")
+                            .clicked()
+                        {
+                            action = Some(ContextAction::InsertOnLink(link));
+                        }
+                    }
+                    if let Some(mut shown) = area_shown {
+                        if ui.checkbox(&mut shown, "Always Show Area").clicked() {
+                            action = Some(ContextAction::ToggleShowArea);
+                        }
+                    }
+                    if let Some((_, flag)) = menu.kcl {
+                        let name = KclFlag::iter().nth(flag).map(|x| x.to_string()).unwrap_or_default();
+                        ui.separator();
+                        if ui
+                            .button(format!("Hide {name} KCL"))
+                            .on_hover_text_at_pointer("Can be shown again in the settings tab")
+                            .clicked()
+                        {
+                            action = Some(ContextAction::HideKcl(flag));
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("Go Home").clicked() {
+                        action = Some(ContextAction::GoHome);
+                    }
+                    if !bookmarks.is_empty() {
+                        ui.menu_button("Go to Bookmark", |ui| {
+                            for (i, name) in bookmarks.iter() {
+                                let button =
+                                    egui::Button::new(name).shortcut_text(keybinds.text(KeyAction::GO_TO_BOOKMARK[*i]));
+                                if ui.add(button).clicked() {
+                                    action = Some(ContextAction::GoToBookmark(*i));
+                                }
+                            }
+                        });
+                    }
+                });
+            });
+        })
+        .response;
+
+    let close = action.is_some() || res.clicked_elsewhere() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+    match action {
+        Some(ContextAction::AddPoint) => create_point_at_menu(world, &menu),
+        Some(ContextAction::Duplicate) => {
+            select_only(world, &targets);
+            world.send_event_default::<DuplicateSelected>();
+        }
+        Some(ContextAction::Delete) => {
+            for e in targets {
+                if let Some(e) = world.get_entity_mut(e) {
+                    e.despawn_recursive();
+                }
+            }
+            world.send_event(RefreshOrdering);
+        }
+        Some(ContextAction::DropToKcl) => {
+            select_only(world, &targets);
+            world.send_event_default::<DropSelectionToKcl>();
+        }
+        Some(ContextAction::SetPathStart) => {
+            if let Some(e) = menu.point {
+                set_path_start(world, e);
+            }
+        }
+        Some(ContextAction::LinkSelected) => {
+            if let Some(e) = menu.point {
+                link_selected_to(world, e);
+            }
+        }
+        Some(ContextAction::InsertBetween(other)) => {
+            if let Some(new) = menu.point.and_then(|e| insert_point_between(world, e, other)) {
+                select_only(world, &[new]);
+            }
+        }
+        Some(ContextAction::InsertOnLink(link)) => {
+            if let Some(new) = insert_point_on_link(world, link) {
+                select_only(world, &[new]);
+            }
+        }
+        Some(ContextAction::ToggleShowArea) => {
+            if let Some(mut area) = menu.point.and_then(|e| world.get_mut::<AreaPoint>(e)) {
+                area.show_area = !area.show_area;
+            }
+        }
+        Some(ContextAction::HideKcl(flag)) => {
+            if let Some(visible) = world.resource_mut::<AppSettings>().kcl_model.visible.get_mut(flag) {
+                *visible = false;
+            }
+        }
+        Some(ContextAction::GoHome) => world.send_event_default::<GoToHomeCamera>(),
+        Some(ContextAction::GoToBookmark(i)) => world.send_event(GoToCameraBookmark(i)),
+        None => {}
+    }
+    if !close {
+        world.insert_resource(menu);
+    }
+    Some(res)
+}
+
+// the results of the measure tool in the bottom right corner
+fn show_measurement(ui: &mut Ui, world: &mut World) -> Option<Response> {
+    let measurement = world.get_resource::<Measurement>()?;
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect.to_egui_rect();
+    let margin = ruler_margin(world);
+    let length = |x: f32| format!("{x:.1}");
+    let angle = |x: f32| format!("{x:.1}°");
+    let mut rows = Vec::new();
+    if measurement.points.len() > 1 {
+        rows.push(("Total Distance", length(measurement.total_distance())));
+    }
+    if measurement.points.len() > 2 {
+        rows.extend(
+            measurement
+                .straight_distance()
+                .map(|x| ("Straight Distance", length(x))),
+        );
+    }
+    if let Some((horizontal, height)) = measurement
+        .horizontal_and_height()
+        .filter(|_| measurement.points.len() > 1)
+    {
+        rows.push(("Horizontal", length(horizontal)));
+        rows.push(("Height", length(height)));
+    }
+    rows.extend(measurement.heading().map(|x| ("Heading", angle(x))));
+    rows.extend(measurement.slope().map(|x| ("Slope", angle(x))));
+    rows.extend(measurement.corner_angle().map(|x| ("Corner Angle", angle(x))));
+    let num_points = measurement.points.len();
+
+    let (mut undo, mut clear, mut close) = (false, false, false);
+    let res = egui::Area::new(ui.id().with("measurement"))
+        .fixed_pos(vp_rect.right_bottom() + egui::vec2(-5. - margin, -5.))
+        .pivot(egui::Align2::RIGHT_BOTTOM)
+        .constrain_to(vp_rect)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("Measure");
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text_at_pointer("Stop measuring (Esc)")
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+                if rows.is_empty() {
+                    ui.label("Click on the KCL or on points to measure between them");
+                }
+                egui::Grid::new("measurement").show(ui, |ui| {
+                    for (label, value) in rows.iter() {
+                        ui.label(*label);
+                        ui.monospace(value);
+                        ui.end_row();
+                    }
+                });
+                ui.add_enabled_ui(num_points > 0, |ui| {
+                    ui.horizontal(|ui| {
+                        undo = ui.button("Undo Point").clicked();
+                        clear = ui.button("Clear").clicked();
+                    });
+                });
+            });
+        })
+        .response;
+    if close {
+        world.remove_resource::<Measurement>();
+    } else if let Some(mut measurement) = world.get_resource_mut::<Measurement>() {
+        if undo {
+            measurement.points.pop();
+        }
+        if clear {
+            measurement.points.clear();
+        }
+    }
+    Some(res)
+}
+
+// a colour bar in the bottom left corner showing which values the colours of the points map to
+fn show_value_color_legend(ui: &mut Ui, world: &mut World) {
+    let legend = world.resource::<ValueColorLegend>();
+    if legend.field == ColorByField::Off {
+        return;
+    }
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect.to_egui_rect();
+    let painter = ui.painter_at(vp_rect);
+    let text_color = ui.visuals().strong_text_color();
+    let font = egui::FontId::proportional(12.);
+
+    let margin = ruler_margin(world);
+    let bar = egui::Rect::from_min_size(
+        vp_rect.left_bottom() + egui::vec2(10., -30. - margin),
+        egui::vec2(200., 12.),
+    );
+    painter.text(
+        bar.left_top() - egui::vec2(0., 4.),
+        egui::Align2::LEFT_BOTTOM,
+        legend.field.to_string(),
+        font.clone(),
+        text_color,
+    );
+    if legend.field.is_categorical() {
+        return;
+    }
+    const SLICES: usize = 32;
+    let slice_width = bar.width() / SLICES as f32;
+    for i in 0..SLICES {
+        let t = i as f32 / (SLICES - 1) as f32;
+        let color = value_color(t, 0., 1.).to_srgba().to_u8_array();
+        let slice = egui::Rect::from_min_size(
+            bar.left_top() + egui::vec2(i as f32 * slice_width, 0.),
+            egui::vec2(slice_width + 0.5, bar.height()),
+        );
+        painter.rect_filled(slice, 0., Color32::from_rgb(color[0], color[1], color[2]));
+    }
+    painter.text(
+        bar.left_bottom() + egui::vec2(0., 2.),
+        egui::Align2::LEFT_TOP,
+        format!("{:.2}", legend.min),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        bar.right_bottom() + egui::vec2(0., 2.),
+        egui::Align2::RIGHT_TOP,
+        format!("{:.2}", legend.max),
+        font,
+        text_color,
+    );
+}
+
+// labels are fully opaque up to this distance from the camera, then fade out until they disappear at the max distance
+const CAMERA_LABEL_FADE_START: f32 = 20000.;
+const CAMERA_LABEL_FADE_END: f32 = 60000.;
+
+fn camera_kind_abbreviation(kind: KmpCameraKind) -> &'static str {
+    match kind {
+        KmpCameraKind::Goal => "Goal",
+        KmpCameraKind::FixSearch => "FixSrch",
+        KmpCameraKind::PathSearch => "PathSrch",
+        KmpCameraKind::KartFollow => "KartFol",
+        KmpCameraKind::KartPathFollow => "KartPathFol",
+        KmpCameraKind::OP_FixMoveAt => "OP Fix",
+        KmpCameraKind::OP_PathMoveAt => "OP Path",
+        KmpCameraKind::MiniGame => "MiniGame",
+        KmpCameraKind::MissionSuccess => "Mission",
+        KmpCameraKind::Unknown => "?",
+    }
+}
+
+// a small label next to each visible camera with its type, time and index, so camera chains can be read in the viewport
+fn show_camera_labels(ui: &mut Ui, world: &mut World) {
+    if !world.resource::<AppSettings>().kmp_model.show_camera_labels {
+        return;
+    }
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect;
+    let mut q_cam = world.query_filtered::<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>();
+    let Some((cam, cam_gt)) = q_cam.iter(world).find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let (cam, cam_gt) = (cam.clone(), *cam_gt);
+
+    let painter = ui.painter_at(vp_rect.to_egui_rect());
+    let mut q_cameras = world.query::<(&KmpCamera, &GlobalTransform, &OrderId, &ViewVisibility)>();
+    for (camera, gt, order_id, visibility) in q_cameras.iter(world) {
+        if !visibility.get() {
+            continue;
+        }
+        let distance = gt.translation().distance(cam_gt.translation());
+        if distance > CAMERA_LABEL_FADE_END {
+            continue;
+        }
+        let Some(pos) = world_to_ui_viewport((&cam, &cam_gt), vp_rect, gt.translation()) else {
+            continue;
+        };
+        let opacity = 1.
+            - ((distance - CAMERA_LABEL_FADE_START) / (CAMERA_LABEL_FADE_END - CAMERA_LABEL_FADE_START)).clamp(0., 1.);
+
+        // the time is stored in frames, and the game runs at 60fps
+        let text = format!(
+            "{} {:.1}s #{}",
+            camera_kind_abbreviation(camera.kind),
+            camera.time / 60.,
+            order_id.0
+        );
+        draw_viewport_label(ui, &painter, pos, text, opacity);
+    }
+}
+
+// a small piece of text with a background, just above and to the right of a point in the viewport
+fn draw_viewport_label(ui: &Ui, painter: &egui::Painter, pos: Vec2, text: String, opacity: f32) {
+    let font = egui::FontId::proportional(11.);
+    let text_color = ui.visuals().strong_text_color();
+    let bg_color = ui.visuals().extreme_bg_color;
+    let galley = painter.layout_no_wrap(text, font, text_color.gamma_multiply(opacity));
+    let rect = egui::Align2::LEFT_BOTTOM.anchor_size(egui::pos2(pos.x + 10., pos.y - 10.), galley.size());
+    painter.rect_filled(rect.expand(2.), 2., bg_color.gamma_multiply(0.7 * opacity));
+    painter.galley(rect.min, galley, text_color);
+}
+
+// label each starting grid slot with the player who starts there
+fn show_start_slot_labels(ui: &mut Ui, world: &mut World) {
+    if *world.resource::<KmpEditMode>() != KmpEditMode::StartPoints {
+        return;
+    }
+    let Some(track_info) = world.get_resource::<TrackInfo>().cloned() else {
+        return;
+    };
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect;
+    let mut q_cam = world.query_filtered::<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>();
+    let Some((cam, cam_gt)) = q_cam.iter(world).find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let (cam, cam_gt) = (cam.clone(), *cam_gt);
+    let painter = ui.painter_at(vp_rect.to_egui_rect());
+
+    let mut q_start_points = world.query::<(&StartPoint, &Transform)>();
+    for (start_point, transform) in q_start_points.iter(world) {
+        for (player, pos) in start_slots(start_point, transform, &track_info) {
+            let Some(pos) = world_to_ui_viewport((&cam, &cam_gt), vp_rect, pos) else {
+                continue;
+            };
+            draw_viewport_label(ui, &painter, pos, format!("P{}", player + 1), 1.);
+        }
+    }
+}
+
+// label the first point of each route with the route's index
+fn show_route_labels(ui: &mut Ui, world: &mut World) {
+    if !world.resource::<AppSettings>().kmp_model.color_routes {
+        return;
+    }
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect;
+    let mut q_cam = world.query_filtered::<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>();
+    let Some((cam, cam_gt)) = q_cam.iter(world).find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let (cam, cam_gt) = (cam.clone(), *cam_gt);
+    let painter = ui.painter_at(vp_rect.to_egui_rect());
+
+    let mut q_route_start = world.query_filtered::<(&Transform, &Visibility, &OrderId), With<RouteSettings>>();
+    for (i, (transform, visibility, _)) in q_route_start.iter(world).sort::<&OrderId>().enumerate() {
+        if visibility == Visibility::Hidden {
+            continue;
+        }
+        let Some(pos) = world_to_ui_viewport((&cam, &cam_gt), vp_rect, transform.translation) else {
+            continue;
+        };
+        draw_viewport_label(ui, &painter, pos, format!("Route {i}"), 1.);
+    }
+}
+
+// how thick the rulers along the bottom and right of the viewport are
+const RULER_SIZE: f32 = 18.;
+// roughly how far apart the numbered ticks on the rulers are
+const RULER_TICK_SPACING: f32 = 80.;
+
+// how far things anchored to the bottom and right of the viewport have to move in to stay clear of the rulers
+fn ruler_margin(world: &World) -> f32 {
+    match world.resource::<Rulers>().active(world.resource::<AppSettings>()) {
+        true => RULER_SIZE,
+        false => 0.,
+    }
+}
+
+// the distance between ticks on the rulers, rounded to 1, 2 or 5 times a power of 10
+fn ruler_step(units_per_px: f32) -> f32 {
+    let raw = RULER_TICK_SPACING * units_per_px;
+    let pow = 10_f32.powf(raw.log10().floor());
+    [1., 2., 5., 10.]
+        .into_iter()
+        .map(|x| x * pow)
+        .find(|x| *x >= raw)
+        .unwrap_or(raw)
+}
+
+fn ruler_label(value: f32, step: f32) -> String {
+    if step >= 1. {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+// rulers in world units along the bottom and right of the top down viewport, which guides can be dragged out of
+fn show_rulers_and_guides(ui: &mut Ui, world: &mut World) -> Vec<Response> {
+    let mut responses = Vec::new();
+    if ruler_margin(world) == 0. {
+        return responses;
+    }
+    let vp_rect = world.resource::<ViewportInfo>().viewport_rect;
+    let mut q_cam = world.query_filtered::<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>();
+    let Some((cam, cam_gt)) = q_cam.iter(world).find(|cam| cam.0.is_active) else {
+        return responses;
+    };
+    let Some(mapping) = TopDownMapping::new((cam, cam_gt), vp_rect) else {
+        return responses;
+    };
+    let vp_rect = vp_rect.to_egui_rect();
+    let bottom_ruler = egui::Rect::from_min_max(
+        egui::pos2(vp_rect.left(), vp_rect.bottom() - RULER_SIZE),
+        egui::pos2(vp_rect.right() - RULER_SIZE, vp_rect.bottom()),
+    );
+    let right_ruler = egui::Rect::from_min_max(
+        egui::pos2(vp_rect.right() - RULER_SIZE, vp_rect.top()),
+        egui::pos2(vp_rect.right(), vp_rect.bottom() - RULER_SIZE),
+    );
+    let on_ruler = |pos: egui::Pos2| bottom_ruler.contains(pos) || right_ruler.contains(pos) || !vp_rect.contains(pos);
+
+    let painter = ui.painter_at(vp_rect);
+    let text_color = ui.visuals().text_color();
+    let guide_color = Color32::from_rgb(0, 200, 255);
+    let font = egui::FontId::proportional(10.);
+
+    // guides are stored with the track, so can only be placed while a KMP is open
+    let mut guides = world.get_resource::<KmpSidecar>().map(|x| x.guides.clone());
+
+    // move guides around by dragging them, and get rid of them by dropping them back onto a ruler
+    let mut removed = None;
+    for (i, guide) in guides.iter_mut().flatten().enumerate() {
+        let (line, cursor) = match guide.axis {
+            GuideAxis::X => {
+                let x = mapping.ui_x(guide.value);
+                (
+                    [egui::pos2(x, vp_rect.top()), egui::pos2(x, vp_rect.bottom())],
+                    egui::CursorIcon::ResizeHorizontal,
+                )
+            }
+            GuideAxis::Z => {
+                let y = mapping.ui_y(guide.value);
+                (
+                    [egui::pos2(vp_rect.left(), y), egui::pos2(vp_rect.right(), y)],
+                    egui::CursorIcon::ResizeVertical,
+                )
+            }
+        };
+        painter.line_segment(line, Stroke::new(1., guide_color));
+        let grab = egui::Rect::from_two_pos(line[0], line[1]).expand(3.).intersect(vp_rect);
+        if !grab.is_positive() {
+            continue;
+        }
+        let res = ui
+            .interact(grab, ui.id().with(("guide", i)), Sense::drag())
+            .on_hover_cursor(cursor);
+        if let Some(pos) = res.interact_pointer_pos().filter(|_| res.dragged()) {
+            guide.value = match guide.axis {
+                GuideAxis::X => mapping.world_x(pos.x),
+                GuideAxis::Z => mapping.world_z(pos.y),
+            };
+        }
+        if res.drag_stopped() && res.interact_pointer_pos().is_some_and(on_ruler) {
+            removed = Some(i);
+        }
+        responses.push(res);
+    }
+
+    // the rulers themselves, with a tick at each step of world units
+    let bg_color = ui.visuals().extreme_bg_color.gamma_multiply(0.8);
+    let step = ruler_step(mapping.units_per_px());
+    for ruler in [bottom_ruler, right_ruler] {
+        painter.rect_filled(ruler, 0., bg_color);
+    }
+    let (x_a, x_b) = (
+        mapping.world_x(bottom_ruler.left()),
+        mapping.world_x(bottom_ruler.right()),
+    );
+    for k in (x_a.min(x_b) / step).ceil() as i64..=(x_a.max(x_b) / step).floor() as i64 {
+        let value = k as f32 * step;
+        let x = mapping.ui_x(value);
+        painter.line_segment(
+            [egui::pos2(x, bottom_ruler.bottom()), egui::pos2(x, bottom_ruler.top())],
+            Stroke::new(1., text_color),
+        );
+        painter.text(
+            egui::pos2(x + 2., bottom_ruler.top() + 1.),
+            egui::Align2::LEFT_TOP,
+            ruler_label(value, step),
+            font.clone(),
+            text_color,
+        );
+    }
+    let (z_a, z_b) = (
+        mapping.world_z(right_ruler.top()),
+        mapping.world_z(right_ruler.bottom()),
+    );
+    for k in (z_a.min(z_b) / step).ceil() as i64..=(z_a.max(z_b) / step).floor() as i64 {
+        let value = k as f32 * step;
+        let y = mapping.ui_y(value);
+        painter.line_segment(
+            [egui::pos2(right_ruler.right(), y), egui::pos2(right_ruler.left(), y)],
+            Stroke::new(1., text_color),
+        );
+        // the labels run down the ruler, so they fit in its width
+        let galley = painter.layout_no_wrap(ruler_label(value, step), font.clone(), text_color);
+        let label = egui::epaint::TextShape::new(egui::pos2(right_ruler.right() - 1., y + 2.), galley, text_color)
+            .with_angle(std::f32::consts::FRAC_PI_2);
+        painter.add(label);
+    }
+
+    // drag a new guide out of a ruler, which lies along that ruler
+    let mut created = None;
+    if guides.is_some() {
+        for (ruler, axis) in [(bottom_ruler, GuideAxis::Z), (right_ruler, GuideAxis::X)] {
+            let cursor = match axis {
+                GuideAxis::X => egui::CursorIcon::ResizeHorizontal,
+                GuideAxis::Z => egui::CursorIcon::ResizeVertical,
+            };
+            let res = ui
+                .interact(ruler, ui.id().with(("ruler", axis == GuideAxis::X)), Sense::drag())
+                .on_hover_cursor(cursor)
+                .on_hover_text_at_pointer("Drag out a guide");
+            if let Some(pos) = res.interact_pointer_pos() {
+                let value = match axis {
+                    GuideAxis::X => mapping.world_x(pos.x),
+                    GuideAxis::Z => mapping.world_z(pos.y),
+                };
+                if res.dragged() && !on_ruler(pos) {
+                    let line = match axis {
+                        GuideAxis::X => [egui::pos2(pos.x, vp_rect.top()), egui::pos2(pos.x, vp_rect.bottom())],
+                        GuideAxis::Z => [egui::pos2(vp_rect.left(), pos.y), egui::pos2(vp_rect.right(), pos.y)],
+                    };
+                    painter.line_segment(line, Stroke::new(1., guide_color.gamma_multiply(0.6)));
+                }
+                if res.drag_stopped() && !on_ruler(pos) {
+                    created = Some(Guide { axis, value });
+                }
+            }
+            responses.push(res);
+        }
+    }
+
+    if let Some(mut guides) = guides {
+        if let Some(i) = removed {
+            guides.remove(i);
+        }
+        guides.extend(created);
+        let mut sidecar = world.resource_mut::<KmpSidecar>();
+        // only mark the sidecar as changed if a guide was actually moved
+        if sidecar.guides != guides {
+            sidecar.guides = guides;
+        }
+    }
+    responses
+}
+
 fn show_overlayed_ui(ui: &mut Ui, world: &mut World) -> Vec<Response> {
     let vp_rect = world.resource::<ViewportInfo>().viewport_rect.to_egui_rect();
     // let ss = SystemState::<(
@@ -174,8 +992,25 @@ fn show_overlayed_ui(ui: &mut Ui, world: &mut World) -> Vec<Response> {
                     responses.push(r);
                 }
 
+                let has_kmp_open = world.contains_resource::<KmpFilePath>();
+                let keybinds = world.resource::<Keybinds>().clone();
+                let rulers = world.resource::<Rulers>();
+                let (mut show_rulers, mut snap_to_guides) = (rulers.shown, rulers.snap);
+                let mut clear_guides = false;
+                let bookmarks: Vec<_> = world
+                    .get_resource::<KmpSidecar>()
+                    .map(|x| {
+                        x.camera_bookmarks
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, x)| x.as_ref().map(|x| (i, x.name.clone())))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let mut go_to_bookmark = None;
                 let camera_mode = &mut world.resource_mut::<AppSettings>().camera.mode;
                 let mut ev_camera_mode_change = None;
+                let (mut go_home, mut set_home) = (false, false);
                 let camera_btn = ui.button(format!("Camera: {}", camera_mode));
                 responses.push(camera_btn.clone());
                 let r = button_triggered_popup(ui, "camera_button_popup", camera_btn, |ui| {
@@ -194,13 +1029,123 @@ fn show_overlayed_ui(ui: &mut Ui, world: &mut World) -> Vec<Response> {
                             ev_camera_mode_change = Some(CameraModeChanged(CameraMode::TopDown));
                         }
                     });
+                    ui.horizontal(|ui| {
+                        go_home = ui
+                            .button("Go Home")
+                            .on_hover_text_at_pointer(format!(
+                                "Move the cameras to the home position ({})",
+                                keybinds.text(KeyAction::GoHome)
+                            ))
+                            .clicked();
+                        set_home = ui
+                            .add_enabled(has_kmp_open, egui::Button::new("Set Home"))
+                            .on_hover_text_at_pointer(format!(
+                                "Save the current camera positions as the home position for this KMP ({})",
+                                keybinds.text(KeyAction::SetHome)
+                            ))
+                            .clicked();
+                    });
+                    if !bookmarks.is_empty() {
+                        ui.label("Bookmarks:");
+                        ui.horizontal_wrapped(|ui| {
+                            for (i, name) in bookmarks.iter() {
+                                if ui
+                                    .button(name)
+                                    .on_hover_text_at_pointer(format!(
+                                        "Move the cameras to this bookmark ({})",
+                                        keybinds.text(KeyAction::GO_TO_BOOKMARK[*i])
+                                    ))
+                                    .clicked()
+                                {
+                                    go_to_bookmark = Some(*i);
+                                }
+                            }
+                        });
+                    }
+                    if *camera_mode == CameraMode::TopDown {
+                        ui.separator();
+                        ui.checkbox(&mut show_rulers, "Rulers")
+                            .on_hover_text_at_pointer("Show rulers along the edges of the viewport, which guides can be dragged out of");
+                        ui.add_enabled_ui(show_rulers, |ui| {
+                            ui.checkbox(&mut snap_to_guides, "Snap to Guides")
+                                .on_hover_text_at_pointer("Snap dragged points onto guides they get close to");
+                            clear_guides = ui
+                                .add_enabled(has_kmp_open, egui::Button::new("Clear Guides"))
+                                .on_hover_text_at_pointer("Remove all the guides from this KMP")
+                                .clicked();
+                        });
+                    }
                 });
+                let mut rulers = world.resource_mut::<Rulers>();
+                rulers.shown = show_rulers;
+                rulers.snap = snap_to_guides;
+                if clear_guides {
+                    world.resource_mut::<KmpSidecar>().guides.clear();
+                }
+                if go_home {
+                    world.send_event_default::<GoToHomeCamera>();
+                }
+                if set_home {
+                    world.send_event_default::<SetHomeCamera>();
+                }
+                if let Some(i) = go_to_bookmark {
+                    world.send_event(GoToCameraBookmark(i));
+                }
                 if let Some(ev_camera_mode_change) = ev_camera_mode_change {
                     world.send_event(ev_camera_mode_change);
                 }
                 if let Some(r) = r {
                     responses.push(r);
                 }
+
+                let mut pick_mode = world.resource_mut::<PickMode>();
+                let pick_btn = ui
+                    .button(format!("Pick: {}", *pick_mode))
+                    .on_hover_text_at_pointer(format!(
+                        "What clicking in the viewport can hit ({})",
+                        keybinds.text(KeyAction::CyclePickMode)
+                    ));
+                responses.push(pick_btn.clone());
+                let r = button_triggered_popup(ui, "pick_mode_popup", pick_btn, |ui| {
+                    for mode in PickMode::iter() {
+                        ui.selectable_value(&mut *pick_mode, mode, mode.to_string());
+                    }
+                });
+                if let Some(r) = r {
+                    responses.push(r);
+                }
+
+                let measuring = world.contains_resource::<Measurement>();
+                let measure_btn = ui
+                    .selectable_label(measuring, "Measure")
+                    .on_hover_text_at_pointer(format!(
+                        "Click points or the KCL to measure distances and angles between them ({})",
+                        keybinds.text(KeyAction::Measure)
+                    ));
+                responses.push(measure_btn.clone());
+                if measure_btn.clicked() {
+                    if measuring {
+                        world.remove_resource::<Measurement>();
+                    } else {
+                        world.init_resource::<Measurement>();
+                    }
+                }
+
+                if *world.resource::<EditMode>() == EditMode::SelectBox {
+                    let mut select_box = world.resource_mut::<SelectBox>();
+                    let shape_btn = ui.button(format!("Select: {}", select_box.shape)).on_hover_text_at_pointer(
+                        "The shape dragged out to select points. Hold Shift to add to the selection, or Ctrl to take away from it",
+                    );
+                    responses.push(shape_btn.clone());
+                    let r = button_triggered_popup(ui, "select_shape_popup", shape_btn, |ui| {
+                        for shape in SelectShape::iter() {
+                            ui.selectable_value(&mut select_box.shape, shape, shape.to_string());
+                        }
+                    });
+                    if let Some(r) = r {
+                        responses.push(r);
+                    }
+                }
             });
             // cursor/gizmo mode
             let vertical_res = ui
@@ -1,17 +1,19 @@
 use super::{
+    cmd_args::CmdArgs,
+    keybinds::Keybinds,
     settings::{AppSettings, SetupAppSettingsSet},
-    tabs::DockTree,
+    tabs::{DockTree, Workspaces},
 };
-use crate::ui::update_ui::{KclFileSelected, KmpFileSelected};
-use bevy::prelude::*;
+use crate::ui::file_associations::PendingOpenFiles;
+use bevy::{app::AppExit, prelude::*};
 use bevy_pkv::PkvStore;
-use std::{
-    env,
-    path::{Path, PathBuf},
-};
+use std::{path::PathBuf, time::Duration};
 
 pub fn ui_state_plugin(app: &mut App) {
+    let safe_mode = app.world().get_resource::<CmdArgs>().is_some_and(|x| x.safe_mode);
     app.insert_resource(PkvStore::new("ThomasAlban", "kmpeek"))
+        .insert_resource(SafeMode(safe_mode))
+        .init_resource::<PkvWriteQueue>()
         // .init_resource::<CustomiseKclOpen>()
         // .init_resource::<CameraSettingsOpen>()
         // .init_resource::<ShowModesCollapsed>()
@@ -20,52 +22,214 @@ pub fn ui_state_plugin(app: &mut App) {
         .add_systems(Update, save_docktree.run_if(on_event::<SaveDockTree>()))
         .add_systems(Update, reset_docktree.run_if(on_event::<ResetDockTree>()))
         .add_event::<ResetDockTree>()
-        .add_systems(Startup, check_cmd_args.after(SetupAppSettingsSet));
+        .add_event::<SwitchWorkspace>()
+        .add_systems(Update, switch_workspace.run_if(on_event::<SwitchWorkspace>()))
+        .add_systems(Startup, check_cmd_args.after(SetupAppSettingsSet))
+        .add_systems(Last, flush_pkv_writes)
+        .add_systems(Update, enter_safe_mode_on_key_hold);
+}
+
+/// In safe mode, the tab layout and settings stored from previous sessions are ignored and the defaults are used
+/// instead, without overwriting what is stored. This is so that a broken layout or settings can't make the app unusable.
+/// Enabled with the '--safe-mode' command line argument, or by holding shift from when the app starts up until it has
+/// loaded.
+#[derive(Resource, Deref, Clone, Copy)]
+pub struct SafeMode(pub bool);
+
+// how long shift has to be held for from startup to enter safe mode
+const SAFE_MODE_KEY_HOLD_TIME: f32 = 2.;
+
+fn enter_safe_mode_on_key_hold(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut holding: Local<Option<bool>>,
+    mut safe_mode: ResMut<SafeMode>,
+    mut tree: ResMut<DockTree>,
+    mut workspaces: ResMut<Workspaces>,
+    mut settings: ResMut<AppSettings>,
+    mut keybinds: ResMut<Keybinds>,
+) {
+    // shift has to be held from the very first frame without letting go, so pressing it soon after starting up (e.g.
+    // to select several points) doesn't count
+    let pressed = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let holding = holding.get_or_insert(pressed);
+    *holding &= pressed;
+    if !*holding || **safe_mode || time.elapsed_seconds() < SAFE_MODE_KEY_HOLD_TIME {
+        return;
+    }
+    *holding = false;
+    safe_mode.0 = true;
+    *tree = DockTree::default();
+    *workspaces = Workspaces::default();
+    *settings = AppSettings::default();
+    *keybinds = Keybinds::default();
 }
 
 #[derive(Event, Default)]
 pub struct SaveDockTree;
-pub fn save_docktree(mut pkv: ResMut<PkvStore>, tree: Res<DockTree>) {
-    pkv.set("tree", tree.as_ref()).unwrap();
+pub fn save_docktree(mut pkv_writes: ResMut<PkvWriteQueue>, safe_mode: Res<SafeMode>) {
+    if **safe_mode {
+        return;
+    }
+    pkv_writes.save_tree();
 }
 #[derive(Event, Default)]
 pub struct ResetDockTree;
-pub fn reset_docktree(mut pkv: ResMut<PkvStore>, mut tree: ResMut<DockTree>) {
-    *tree = DockTree::default();
-    pkv.set("tree", tree.as_ref()).unwrap();
+/// Resets the tab layout of the active workspace to the one it started with
+pub fn reset_docktree(
+    mut pkv_writes: ResMut<PkvWriteQueue>,
+    mut tree: ResMut<DockTree>,
+    workspaces: Res<Workspaces>,
+    safe_mode: Res<SafeMode>,
+) {
+    *tree = match workspaces.layouts.get(workspaces.active) {
+        Some(workspace) => Workspaces::default_tree(&workspace.name),
+        None => DockTree::default(),
+    };
+    if **safe_mode {
+        return;
+    }
+    pkv_writes.save_tree();
 }
 
-#[derive(Resource, Default, Deref, DerefMut, Clone)]
-pub struct KmpFilePath(pub PathBuf);
+/// Send this event to switch to the workspace at this index, keeping the tab layout of the current one
+#[derive(Event)]
+pub struct SwitchWorkspace(pub usize);
+fn switch_workspace(
+    mut ev_switch: EventReader<SwitchWorkspace>,
+    mut pkv_writes: ResMut<PkvWriteQueue>,
+    mut tree: ResMut<DockTree>,
+    mut workspaces: ResMut<Workspaces>,
+    safe_mode: Res<SafeMode>,
+) {
+    let Some(SwitchWorkspace(index)) = ev_switch.read().last() else {
+        return;
+    };
+    if *index == workspaces.active || *index >= workspaces.layouts.len() {
+        return;
+    }
+    *workspaces = workspaces.with_current(&tree);
+    workspaces.active = *index;
+    *tree = workspaces.layouts[*index].tree.clone();
+    if **safe_mode {
+        return;
+    }
+    pkv_writes.save_tree();
+}
+
+// the pkv store is written at most this often, so that saving repeatedly doesn't cause stutters on slow drives
+const PKV_WRITE_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Copy, PartialEq)]
+enum StoredValue {
+    Current,
+    Default,
+}
+
+/// Writes to the PkvStore are queued up here and flushed at most every few seconds, as well as when the app exits.
+/// Only the latest write to each key is kept, and the value is read at the time it is actually written.
+#[derive(Resource, Default)]
+pub struct PkvWriteQueue {
+    tree: Option<StoredValue>,
+    settings: Option<StoredValue>,
+    keybinds: Option<StoredValue>,
+    clear: bool,
+    last_flush: Option<Duration>,
+    /// The error from the last time the queue was written, if there was one
+    pub error: Option<String>,
+}
+impl PkvWriteQueue {
+    pub fn save_tree(&mut self) {
+        self.tree = Some(StoredValue::Current);
+    }
+    pub fn save_settings(&mut self) {
+        self.settings = Some(StoredValue::Current);
+    }
+    pub fn save_keybinds(&mut self) {
+        self.keybinds = Some(StoredValue::Current);
+    }
+    /// Store the default tab layouts, without changing the current ones
+    pub fn clear_tree(&mut self) {
+        self.tree = Some(StoredValue::Default);
+    }
+    /// Store the default settings and keybinds, without changing the current ones
+    pub fn clear_settings(&mut self) {
+        self.settings = Some(StoredValue::Default);
+        self.keybinds = Some(StoredValue::Default);
+    }
+    pub fn clear_all(&mut self) {
+        self.tree = None;
+        self.settings = None;
+        self.keybinds = None;
+        self.clear = true;
+    }
+    fn is_empty(&self) -> bool {
+        self.tree.is_none() && self.settings.is_none() && self.keybinds.is_none() && !self.clear
+    }
+}
 
-pub fn check_cmd_args(
-    mut ev_kmp_file_selected: EventWriter<KmpFileSelected>,
-    mut ev_kcl_file_selected: EventWriter<KclFileSelected>,
+fn flush_pkv_writes(
+    mut queue: ResMut<PkvWriteQueue>,
+    mut pkv: ResMut<PkvStore>,
+    tree: Res<DockTree>,
+    workspaces: Res<Workspaces>,
     settings: Res<AppSettings>,
+    keybinds: Res<Keybinds>,
+    time: Res<Time<Real>>,
+    ev_app_exit: EventReader<AppExit>,
 ) {
-    // if there is a command line arg of a path to a kmp or kcl, open it
-    let args: Vec<String> = env::args().collect();
-    if let Some(arg) = args.get(1) {
-        let path = Path::new(arg);
-        if path.is_file() {
-            if let Some(file_ext) = path.extension() {
-                // if the file is a kmp file
-                if file_ext == "kmp" {
-                    // open it
-                    ev_kmp_file_selected.send(KmpFileSelected(path.into()));
-                    // if there is a course.kcl in the same directory and the setting to open it is set, open the kcl as well
-                    if settings.open_course_kcl_in_dir {
-                        let mut course_kcl_path = path.to_owned();
-                        course_kcl_path.set_file_name("course.kcl");
-                        if course_kcl_path.exists() {
-                            ev_kcl_file_selected.send(KclFileSelected(course_kcl_path));
-                        }
-                    }
-                // else if the file is a kcl file, open it
-                } else if file_ext == "kcl" {
-                    ev_kcl_file_selected.send(KclFileSelected(path.into()));
-                }
+    if queue.is_empty() {
+        return;
+    }
+    let now = time.elapsed();
+    let exiting = !ev_app_exit.is_empty();
+    if !exiting && queue.last_flush.is_some_and(|last| now - last < PKV_WRITE_INTERVAL) {
+        return;
+    }
+
+    let mut write = || -> anyhow::Result<()> {
+        if queue.clear {
+            pkv.clear()?;
+        }
+        match queue.tree {
+            // each workspace's layout is stored along with the current one
+            Some(StoredValue::Current) => {
+                pkv.set("tree", tree.as_ref())?;
+                pkv.set("workspaces", &workspaces.with_current(&tree))?;
+            }
+            Some(StoredValue::Default) => {
+                pkv.set("tree", &DockTree::default())?;
+                pkv.set("workspaces", &Workspaces::default())?;
             }
+            None => {}
         }
+        match queue.settings {
+            Some(StoredValue::Current) => pkv.set("settings", settings.as_ref())?,
+            Some(StoredValue::Default) => pkv.set("settings", &AppSettings::default())?,
+            None => {}
+        }
+        match queue.keybinds {
+            Some(StoredValue::Current) => pkv.set("keybinds", keybinds.as_ref())?,
+            Some(StoredValue::Default) => pkv.set("keybinds", &Keybinds::default())?,
+            None => {}
+        }
+        Ok(())
+    };
+    let error = write().err().map(|err| err.to_string());
+
+    *queue = PkvWriteQueue {
+        last_flush: Some(now),
+        error,
+        ..default()
+    };
+}
+
+#[derive(Resource, Default, Deref, DerefMut, Clone)]
+pub struct KmpFilePath(pub PathBuf);
+
+// if there is a command line arg of a path to a kmp, kcl or course archive, open it
+pub fn check_cmd_args(mut pending: ResMut<PendingOpenFiles>, cmd_args: Res<CmdArgs>) {
+    if let Some(path) = &cmd_args.path {
+        pending.push_back(path.clone());
     }
 }
@@ -1,6 +1,8 @@
 use crate::util::egui_has_primary_context;
 
-use super::{file_dialog::show_file_dialog, menu_bar::show_menu_bar, tabs::show_dock_area};
+use super::{
+    file_dialog::show_file_dialog, menu_bar::show_menu_bar, status_bar::show_status_bar, tabs::show_dock_area,
+};
 use bevy::prelude::*;
 use bevy_egui::EguiContexts;
 use std::path::PathBuf;
@@ -27,6 +29,7 @@ fn setup_ui_images(mut contexts: EguiContexts) {
 
 fn update_ui(world: &mut World) {
     show_menu_bar(world);
+    show_status_bar(world);
     show_dock_area(world);
     show_file_dialog(world);
     world.flush();
@@ -36,7 +36,11 @@ impl From<DragSpeed> for f64 {
 pub mod multi_edit {
     use super::{euler_to_quat_ui, get_euler_rot, DragSpeed};
     use bevy::{math::Vec3, prelude::Mut, transform::components::Transform};
-    use bevy_egui::egui::{self, emath::Numeric, Checkbox, DragValue, Response, Ui, WidgetText};
+    use bevy_egui::egui::{
+        self,
+        emath::{self, Numeric},
+        Checkbox, DragValue, Response, Ui, WidgetText,
+    };
     use std::{
         fmt::Display,
         ops::{AddAssign, Sub, SubAssign},
@@ -92,6 +96,8 @@ pub mod multi_edit {
     //     changed
     // }
 
+    /// Edit a number shared by several items. Dragging changes every item by the same amount, and while dragging the
+    /// value from before the drag is shown alongside the new one, and Escape puts the items back to how they were.
     pub fn drag_value_multi_edit<
         'a,
         T: 'a + Clone + PartialEq + Numeric + Sub<Output = T> + AddAssign<T> + SubAssign<T>,
@@ -103,20 +109,49 @@ pub mod multi_edit {
         let mut items: Vec<_> = items.into_iter().collect();
         let mut edit = *items[0];
         let before = edit;
-
-        // if they are all the same
-        let res = if items.iter().all(|x| **x == edit) {
-            // show normal drag value
-            ui.add(DragValue::new(&mut edit).speed(speed))
-        } else {
+        let all_same = items.iter().all(|x| **x == edit);
+
+        // the values of the items when the current drag started, kept in egui's memory under the drag value's id
+        let id = ui.next_auto_id();
+        let drag_start: Option<Vec<f64>> = ui.data(|d| d.get_temp(id));
+        let start_value = drag_start.as_ref().and_then(|x| x.first().copied());
+
+        let drag_value = DragValue::new(&mut edit).speed(speed);
+        let mut res = match (start_value, all_same) {
+            (Some(start), true) => ui.add(drag_value.custom_formatter(move |x, decimals| {
+                let format = |x| emath::format_with_decimals_in_range(x, decimals.clone());
+                format!("{} → {}", format(start), format(x))
+            })),
+            // the items started with different values, so only how much they have all moved by can be shown
+            (Some(start), false) => ui.add(drag_value.custom_formatter(move |x, decimals| {
+                let delta = emath::format_with_decimals_in_range((x - start).abs(), decimals);
+                if x < start {
+                    format!("-{delta}")
+                } else {
+                    format!("+{delta}")
+                }
+            })),
+            (None, true) => ui.add(drag_value),
             // show blank drag value
-            ui.add(
-                DragValue::new(&mut edit)
-                    .speed(speed)
-                    .custom_formatter(|_, _| "".into()),
-            )
+            (None, false) => ui.add(drag_value.custom_formatter(|_, _| "".into())),
         };
 
+        if res.drag_started() {
+            let values: Vec<_> = items.iter().map(|x| x.to_f64()).collect();
+            ui.data_mut(|d| d.insert_temp(id, values));
+        } else if !res.dragged() {
+            ui.data_mut(|d| d.remove::<Vec<f64>>(id));
+        } else if let Some(drag_start) = drag_start.filter(|_| ui.input(|i| i.key_pressed(egui::Key::Escape))) {
+            // cancel the drag, leaving the items as they were before it
+            for (item, value) in items.iter_mut().zip(drag_start) {
+                **item = T::from_f64(value);
+            }
+            ui.data_mut(|d| d.remove::<Vec<f64>>(id));
+            ui.ctx().stop_dragging();
+            res.mark_changed();
+            return res;
+        }
+
         if res.changed() && !res.dragged() {
             // if we have set the value by typing it in
             items.iter_mut().for_each(|x| **x = edit);
@@ -1,10 +1,10 @@
 use crate::util::read_write_arrays::ReadArrays;
-use bevy::{math::vec3, prelude::*};
+use bevy::{math::vec3, prelude::*, utils::HashSet};
 use byteorder::{ReadBytesExt, BE};
 use std::io::{self, Read, Seek, SeekFrom};
 use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
-#[derive(Display, EnumString, IntoStaticStr, EnumIter)]
+#[derive(Display, EnumString, IntoStaticStr, EnumIter, Clone, Copy, PartialEq)]
 pub enum KclFlag {
     #[strum(serialize = "Road 1")]
     Road1,
@@ -72,23 +72,197 @@ pub enum KclFlag {
     InvisibleWall2,
 }
 
+/// The number of variants each KCL flag can have
+pub const KCL_VARIANTS: usize = 8;
+
+/// The variant of a KCL flag, which changes how the base type behaves (e.g. which way a moving road moves)
+pub fn kcl_variant(flag: u16) -> u16 {
+    (flag >> 5) & 0x7
+}
+
 #[derive(Resource)]
 pub struct Kcl {
     pub vertex_groups: Vec<VertexGroup>,
+    /// The spatial index the game uses to find which triangles are near a position. If it couldn't be read, every
+    /// triangle in the file is shown instead of only the ones it references.
+    pub octree: Option<KclOctree>,
 }
 impl Default for Kcl {
     fn default() -> Self {
         let mut vertex_groups: Vec<VertexGroup> = Vec::with_capacity(32);
         for _ in 0..32 {
-            vertex_groups.push(VertexGroup { vertices: Vec::new() })
+            vertex_groups.push(VertexGroup::default())
+        }
+        Self {
+            vertex_groups,
+            octree: None,
+        }
+    }
+}
+
+/// The octree which splits the KCL's area into cubes, each with a list of the triangles inside it
+pub struct KclOctree {
+    /// The corner of the area covered by the octree
+    pub min_pos: Vec3,
+    /// Bits of a position (relative to `min_pos`) which mean it's outside the area covered, on each axis
+    pub mask: UVec3,
+    /// log2 of the width of the cubes at the root of the octree
+    pub block_width_shift: u32,
+    /// log2 of the number of root cubes along the X axis
+    pub x_blocks_shift: u32,
+    /// log2 of the number of root cubes in each XY layer
+    pub xy_blocks_shift: u32,
+    /// The root cubes, in order of X, then Y, then Z
+    pub root: Vec<OctreeNode>,
+}
+
+pub enum OctreeNode {
+    /// A cube split into 8 smaller cubes, indexed by `x | y << 1 | z << 2` where each axis is 0 for the lower half
+    Branch(Box<[OctreeNode; 8]>),
+    /// Indexes of the triangles in the cube, in the order they're stored in the file
+    Leaf(Vec<u16>),
+}
+
+// a malformed file could have branches pointing back to themselves, and real files don't get anywhere near this deep
+const MAX_OCTREE_DEPTH: u32 = 32;
+// branches can also share children, which would make the octree grow exponentially with its depth, so the total number
+// of nodes is limited too; real files have far fewer than this
+const MAX_OCTREE_NODES: usize = 0x100000;
+
+impl KclOctree {
+    fn read(mut r: impl Read + Seek, block_data_offset: u32) -> io::Result<Self> {
+        r.seek(SeekFrom::Start(0x14))?;
+        let min_pos = r.read_vec3()?;
+        let mask = UVec3::new(r.read_u32::<BE>()?, r.read_u32::<BE>()?, r.read_u32::<BE>()?);
+        let block_width_shift = r.read_u32::<BE>()?;
+        let x_blocks_shift = r.read_u32::<BE>()?;
+        let xy_blocks_shift = r.read_u32::<BE>()?;
+
+        let invalid_size = || io::Error::new(io::ErrorKind::InvalidData, "invalid KCL octree size");
+        if block_width_shift >= 32 {
+            return Err(invalid_size());
+        }
+        let blocks = (!mask >> block_width_shift).to_array();
+        let mut num_root = 1u64;
+        for axis in blocks {
+            // the mask can be 0 on an axis, in which case there are 2^32 blocks along it
+            num_root *= axis as u64 + 1;
+            if num_root > 0x10000 {
+                return Err(invalid_size());
+            }
+        }
+        let mut nodes_left = MAX_OCTREE_NODES;
+        let root = Self::read_nodes(&mut r, block_data_offset as u64, num_root as usize, 0, &mut nodes_left)?;
+        Ok(Self {
+            min_pos,
+            mask,
+            block_width_shift,
+            x_blocks_shift,
+            xy_blocks_shift,
+            root,
+        })
+    }
+
+    // read a group of nodes, whose offsets are relative to the start of the group
+    fn read_nodes(
+        r: &mut (impl Read + Seek),
+        start: u64,
+        count: usize,
+        depth: u32,
+        nodes_left: &mut usize,
+    ) -> io::Result<Vec<OctreeNode>> {
+        if depth > MAX_OCTREE_DEPTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "KCL octree is too deep"));
+        }
+        *nodes_left = nodes_left
+            .checked_sub(count)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "KCL octree has too many nodes"))?;
+        r.seek(SeekFrom::Start(start))?;
+        let mut values = vec![0u32; count];
+        for value in values.iter_mut() {
+            *value = r.read_u32::<BE>()?;
+        }
+        let mut nodes = Vec::with_capacity(count);
+        for value in values {
+            let offset = start + (value & 0x7fff_ffff) as u64;
+            if value & 0x8000_0000 != 0 {
+                // the offset points to the 2 bytes before the list, which is ended by a 0
+                r.seek(SeekFrom::Start(offset + 2))?;
+                let mut triangles = Vec::new();
+                loop {
+                    let index = r.read_u16::<BE>()?;
+                    if index == 0 {
+                        break;
+                    }
+                    // triangle indexes start from 1
+                    triangles.push(index - 1);
+                }
+                nodes.push(OctreeNode::Leaf(triangles));
+            } else {
+                let children = Self::read_nodes(r, offset, 8, depth + 1, nodes_left)?;
+                let children: [OctreeNode; 8] = children.try_into().unwrap_or_else(|_| unreachable!());
+                nodes.push(OctreeNode::Branch(Box::new(children)));
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Indexes of the triangles the game checks for collision at a position, or `None` if the position is outside
+    /// the area covered by the KCL
+    pub fn triangles_at(&self, pos: Vec3) -> Option<&[u16]> {
+        let rel = pos - self.min_pos;
+        if rel.cmplt(Vec3::ZERO).any() {
+            return None;
+        }
+        let rel = rel.as_uvec3();
+        if (rel & self.mask) != UVec3::ZERO {
+            return None;
+        }
+        let mut shift = self.block_width_shift;
+        let root_index =
+            ((rel.z >> shift) << self.xy_blocks_shift) | ((rel.y >> shift) << self.x_blocks_shift) | (rel.x >> shift);
+        let mut node = self.root.get(root_index as usize)?;
+        loop {
+            match node {
+                OctreeNode::Leaf(triangles) => return Some(triangles),
+                OctreeNode::Branch(children) => {
+                    shift = shift.checked_sub(1)?;
+                    let index = ((rel.x >> shift) & 1) | (((rel.y >> shift) & 1) << 1) | (((rel.z >> shift) & 1) << 2);
+                    node = &children[index as usize];
+                }
+            }
         }
-        Self { vertex_groups }
+    }
+
+    /// Every triangle referenced anywhere in the octree
+    pub fn referenced_triangles(&self) -> HashSet<u16> {
+        fn visit(node: &OctreeNode, referenced: &mut HashSet<u16>) {
+            match node {
+                OctreeNode::Leaf(triangles) => referenced.extend(triangles),
+                OctreeNode::Branch(children) => children.iter().for_each(|x| visit(x, referenced)),
+            }
+        }
+        let mut referenced = HashSet::default();
+        self.root.iter().for_each(|x| visit(x, &mut referenced));
+        referenced
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct VertexGroup {
     pub vertices: Vec<Vec3>,
+    /// The full flag of each triangle (one for every 3 vertices), including its variant, BLIGHT index and wheel depth
+    pub flags: Vec<u16>,
+}
+impl VertexGroup {
+    /// How many triangles there are of each variant
+    pub fn variant_counts(&self) -> [usize; KCL_VARIANTS] {
+        let mut counts = [0; KCL_VARIANTS];
+        for flag in self.flags.iter() {
+            counts[kcl_variant(*flag) as usize] += 1;
+        }
+        counts
+    }
 }
 
 impl Kcl {
@@ -123,12 +297,21 @@ impl Kcl {
             normals.push(vec3(x, y, z));
         }
 
+        // only show triangles the game can actually collide with, which are the ones referenced by the octree
+        let mut kcl = Kcl {
+            octree: KclOctree::read(&mut r, offsets[3]).ok(),
+            ..default()
+        };
+        let referenced = kcl.octree.as_ref().map(|x| x.referenced_triangles());
+
         // go to the start of the triangular prisms section
         r.seek(SeekFrom::Start(offsets[2] as u64 + 0x10))?;
 
-        let mut kcl = Kcl::default();
-
+        let mut index: u16 = 0;
         while r.stream_position()? < offsets[3] as u64 {
+            let is_referenced = referenced.as_ref().is_none_or(|x| x.contains(&index));
+            index = index.wrapping_add(1);
+
             let length = r.read_f32::<BE>()?;
             let pos_index = r.read_u16::<BE>()? as usize;
             let face_nrm_index = r.read_u16::<BE>()? as usize;
@@ -146,6 +329,7 @@ impl Kcl {
                 || nrm_a_index >= normals.len()
                 || nrm_b_index >= normals.len()
                 || nrm_c_index >= normals.len()
+                || !is_referenced
             {
                 continue;
             }
@@ -165,6 +349,7 @@ impl Kcl {
             let v3 = *vertex + (cross_a * (length / cross_a.dot(*nrm_c)));
 
             kcl.vertex_groups[kcl_type].vertices.extend([v1, v2, v3]);
+            kcl.vertex_groups[kcl_type].flags.push(kcl_flag);
         }
         Ok(kcl)
     }
@@ -1,5 +1,6 @@
+use anyhow::bail;
 use bevy::prelude::*;
-use binrw::{binrw, BinRead, BinWrite};
+use binrw::{binrw, BinRead, BinWrite, Endian};
 use derive_new::new;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -34,7 +35,7 @@ pub struct KmpFile {
 /// The header, which contains general information about the KMP
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[binrw]
-#[brw(magic = b"RKMD", big)]
+#[brw(magic = b"RKMD")]
 #[br(assert(num_sections == 15, "number of sections in header was not 15"))]
 pub struct Header {
     // currently the file_len field will be incorrect when writing back to the header
@@ -48,18 +49,18 @@ pub struct Header {
 /// Each section has a header containing its info (like the name and number of entries)
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct SectionHeader {
     pub section_name: [u8; 4],
     pub num_entries: u16,
-    /// The POTI section stores the total number of points of all routes here. The CAME section stores different values. For all other sections, the value is 0 (padding).
+    /// The POTI section stores the total number of points of all routes here, and the first byte of the CAME section's
+    /// value is the camera the intro starts from. For all other sections, the value is normally 0 (padding), but
+    /// some tools store their own data here, so it is kept as it was when saving.
     pub additional_value: u16,
 }
 
 /// A generic type for a section of a KMP - each section contains a header, and a number of entries.
 #[derive(Debug, Serialize, Deserialize, Clone, Default, Deref, DerefMut)]
 #[binrw]
-#[brw(big)]
 pub struct Section<T>
 where
     for<'a> T: BinRead<Args<'a> = ()> + 'a,
@@ -74,7 +75,6 @@ where
 /// The KTPT (kart point) section describes kart points; the starting position for racers.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Ktpt {
     pub position: [f32; 3],
     pub rotation: [f32; 3],
@@ -85,7 +85,6 @@ pub struct Ktpt {
 /// The ENPT (enemy point) section describes enemy points; the routes of CPU racers. The CPU racers attempt to follow the path described by each group of points (as determined by ENPH). More than 0xFF (255) entries will force a console freeze while loading the track.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Enpt {
     pub position: [f32; 3],
     pub leniency: f32,
@@ -100,7 +99,6 @@ pub struct Enpt {
 /// * The CKPH (checkpoint path) section describes checkpoint grouping; how the routes of checkpoints link together.
 #[derive(Debug, Serialize, Deserialize, Clone, Deref, DerefMut, Default, new)]
 #[binrw]
-#[brw(big)]
 pub struct PathGroup<T: 'static + Default> {
     pub start: u8,
     pub group_length: u8,
@@ -115,7 +113,6 @@ pub struct PathGroup<T: 'static + Default> {
 /// The ITPT (item point) section describes item points; the Red Shell and Bullet Bill routes. The items attempt to follow the path described by each group of points (as determined by ITPH). More than 0xFF (255) entries will force a console freeze while loading the track.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Itpt {
     pub position: [f32; 3],
     pub bullet_control: f32,
@@ -126,7 +123,6 @@ pub struct Itpt {
 /// The CKPT (checkpoint) section describes checkpoints; the routes players must follow to count laps. The racers must follow the path described by each group of points (as determined by CKPH). More than 0xFF (255) entries are possible if the last group begins at index ≤254. This is not recommended because Lakitu will always appear on-screen.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Ckpt {
     pub cp_left: [f32; 2],
     pub cp_right: [f32; 2],
@@ -139,11 +135,12 @@ pub struct Ckpt {
 /// The GOBJ (geo object) section describes objects; things such as item boxes, pipes and also controlled objects such as sound triggers.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Gobj {
     pub object_id: u16,
-    /// * this is part of the extended presence flags, but the value must be 0 if the object does not use this extension
-    pub padding: u16,
+    /// * padding in the original game, which LE-CODE distributions use for extended presence flags and definition objects
+    /// * the value must be 0 if the object does not use this extension
+    /// * read it with [`ExtendedPresence::from_raw`]
+    pub extended_presence: u16,
     pub position: [f32; 3],
     pub rotation: [f32; 3],
     pub scale: [f32; 3],
@@ -152,10 +149,55 @@ pub struct Gobj {
     pub presence_flags: u16,
 }
 
+/// What LE-CODE reads from [`Gobj::extended_presence`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtendedPresence {
+    /// the object doesn't use the extension, and only the normal presence flags apply
+    #[default]
+    None,
+    /// the object only appears in the modes and engine classes whose bits are set (see [`ExtendedPresence::FLAGS`])
+    Flags(u16),
+    /// the object appears whenever the definition object at this GOBJ index says it should
+    DefinitionObject(u16),
+}
+impl ExtendedPresence {
+    /// set when the rest of the value is the GOBJ index of a definition object rather than flags
+    pub const DEFINITION_OBJECT_BIT: u16 = 0x1000;
+    /// the highest GOBJ index a definition object reference can hold
+    pub const MAX_DEFINITION_OBJECT: u16 = 0x0fff;
+    /// the named flag bits; any other bits are kept as they are
+    pub const FLAGS: [(u16, &'static str); 8] = [
+        (0x0001, "Offline"),
+        (0x0002, "Online"),
+        (0x0004, "Time Trial"),
+        (0x0008, "Versus"),
+        (0x0010, "Battle"),
+        (0x0020, "Mirror"),
+        (0x0040, "150cc"),
+        (0x0080, "200cc"),
+    ];
+
+    pub fn from_raw(raw: u16) -> Self {
+        if raw == 0 {
+            Self::None
+        } else if raw & Self::DEFINITION_OBJECT_BIT != 0 {
+            Self::DefinitionObject(raw & !Self::DEFINITION_OBJECT_BIT)
+        } else {
+            Self::Flags(raw)
+        }
+    }
+    pub fn to_raw(self) -> u16 {
+        match self {
+            Self::None => 0,
+            Self::Flags(bits) => bits & !Self::DEFINITION_OBJECT_BIT,
+            Self::DefinitionObject(index) => index | Self::DEFINITION_OBJECT_BIT,
+        }
+    }
+}
+
 /// Each POTI entry can contain a number of POTI entries/points.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct PotiPoint {
     pub position: [f32; 3],
     pub setting_1: u16,
@@ -165,7 +207,6 @@ pub struct PotiPoint {
 /// The POTI (point information) section describes routes; these are routes for many things including cameras and objects.
 #[derive(Debug, Serialize, Deserialize, Clone, Deref, DerefMut, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Poti {
     pub num_points: u16,
     pub setting_1: u8,
@@ -178,7 +219,6 @@ pub struct Poti {
 /// The AREA (area) section describes areas; used to determine which camera to use, for example. The size is 5000 for both the positive and negative sides of the X and Z-axes, and 10000 for only the positive side of the Y-axis.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Area {
     pub shape: u8,
     pub kind: u8,
@@ -197,7 +237,6 @@ pub struct Area {
 /// The CAME (camera) section describes cameras; used to determine cameras for starting routes, Time Trial pans, etc.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Came {
     pub kind: u8,
     pub next_index: u8,
@@ -220,7 +259,6 @@ pub struct Came {
 /// The JGPT (jugem point) section describes "Jugem" points; the respawn positions. The index is relevant for the link of the CKPT section.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Jgpt {
     pub position: [f32; 3],
     pub rotation: [f32; 3],
@@ -231,7 +269,6 @@ pub struct Jgpt {
 /// The CNPT (cannon point) section describes cannon points; the cannon target positions.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Cnpt {
     pub position: [f32; 3],
     pub rotation: [f32; 3],
@@ -242,7 +279,6 @@ pub struct Cnpt {
 /// The MSPT (mission success point) section describes end positions. After battles and tournaments have ended, the players are placed on this point(s).
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Mspt {
     pub position: [f32; 3],
     pub rotation: [f32; 3],
@@ -253,7 +289,6 @@ pub struct Mspt {
 /// The STGI (stage info) section describes stage information; information about a track.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[binrw]
-#[brw(big)]
 pub struct Stgi {
     pub lap_count: u8,
     pub pole_pos: u8,
@@ -268,62 +303,178 @@ pub struct Stgi {
     // pub speed_mod: f32,
 }
 
+/// What kind of file some KMP data seems to be, judged from its header
+#[derive(Debug, Clone, PartialEq)]
+pub enum KmpFormat {
+    /// A normal big-endian Mario Kart Wii KMP
+    Wii,
+    /// A Mario Kart Wii KMP with every value stored little-endian, which can be converted to big-endian
+    LittleEndian,
+    /// Something that can't be read as a Mario Kart Wii KMP, with the reason why
+    Unsupported(String),
+}
+impl KmpFormat {
+    pub fn detect(data: &[u8]) -> Self {
+        if data.len() < KmpFile::HEADER_LEN as usize {
+            return Self::Unsupported("the file is too short to be a KMP".into());
+        }
+        let magic = &data[..4];
+        let u16_at = |i: usize, endian: Endian| {
+            let bytes = [data[i], data[i + 1]];
+            match endian {
+                Endian::Big => u16::from_be_bytes(bytes),
+                Endian::Little => u16::from_le_bytes(bytes),
+            }
+        };
+        if magic != b"RKMD" && magic != b"DMKR" {
+            return Self::Unsupported(format!(
+                "the file starts with '{}' rather than 'RKMD', so it isn't a KMP",
+                String::from_utf8_lossy(magic)
+            ));
+        }
+        // the section count and header length are fixed in Mario Kart Wii, so whichever byte order gives the
+        // expected values is the one the file is in
+        let matches = |endian| u16_at(8, endian) == 15 && u16_at(10, endian) == KmpFile::HEADER_LEN;
+        if matches(Endian::Big) {
+            return Self::Wii;
+        }
+        if matches(Endian::Little) {
+            return Self::LittleEndian;
+        }
+        let endian = if magic == b"DMKR" { Endian::Little } else { Endian::Big };
+        let (num_sections, header_len) = (u16_at(8, endian), u16_at(10, endian));
+        Self::Unsupported(format!(
+            "the header has {num_sections} sections and is {header_len:#x} bytes long, where Mario Kart Wii KMPs have 15 sections and a {:#x} byte header, so it is probably from another Mario Kart game",
+            KmpFile::HEADER_LEN
+        ))
+    }
+}
+
 impl KmpFile {
     const HEADER_LEN: u16 = 0x4c;
 
+    /// The header of each section, in the order they are in the file
+    pub fn section_headers(&self) -> [&SectionHeader; 15] {
+        [
+            &self.ktpt.section_header,
+            &self.enpt.section_header,
+            &self.enph.section_header,
+            &self.itpt.section_header,
+            &self.itph.section_header,
+            &self.ckpt.section_header,
+            &self.ckph.section_header,
+            &self.gobj.section_header,
+            &self.poti.section_header,
+            &self.area.section_header,
+            &self.came.section_header,
+            &self.jgpt.section_header,
+            &self.cnpt.section_header,
+            &self.mspt.section_header,
+            &self.stgi.section_header,
+        ]
+    }
+
+    pub fn section_headers_mut(&mut self) -> [&mut SectionHeader; 15] {
+        [
+            &mut self.ktpt.section_header,
+            &mut self.enpt.section_header,
+            &mut self.enph.section_header,
+            &mut self.itpt.section_header,
+            &mut self.itph.section_header,
+            &mut self.ckpt.section_header,
+            &mut self.ckph.section_header,
+            &mut self.gobj.section_header,
+            &mut self.poti.section_header,
+            &mut self.area.section_header,
+            &mut self.came.section_header,
+            &mut self.jgpt.section_header,
+            &mut self.cnpt.section_header,
+            &mut self.mspt.section_header,
+            &mut self.stgi.section_header,
+        ]
+    }
+
     pub fn read<R: Read + Seek>(r: &mut R) -> anyhow::Result<Self> {
+        Self::read_with_endian(r, Endian::Big)
+    }
+
+    /// Reads a KMP from its bytes, converting it if it is in a format that can be converted, or giving an error
+    /// explaining why it can't be read otherwise
+    pub fn read_any(data: &[u8]) -> anyhow::Result<Self> {
+        Self::read_format(data, &KmpFormat::detect(data))
+    }
+
+    /// Reads a KMP from its bytes in a format that has already been detected
+    pub fn read_format(data: &[u8], format: &KmpFormat) -> anyhow::Result<Self> {
+        match format {
+            KmpFormat::Wii => Self::read(&mut Cursor::new(data)),
+            KmpFormat::LittleEndian => {
+                // some tools byte swap the magic along with everything else
+                let mut data = data.to_vec();
+                data[..4].copy_from_slice(b"RKMD");
+                Self::read_with_endian(&mut Cursor::new(data), Endian::Little)
+            }
+            KmpFormat::Unsupported(reason) => bail!("unsupported kmp format: {reason}"),
+        }
+    }
+
+    fn read_with_endian<R: Read + Seek>(r: &mut R, endian: Endian) -> anyhow::Result<Self> {
         let mut kmp = KmpFile {
-            header: Header::read(r)?,
+            header: Header::read_options(r, endian, ())?,
             ..default()
         };
 
-        kmp.read_kmp_section::<Ktpt, _>(r, 0)?;
-        kmp.read_kmp_section::<Enpt, _>(r, 1)?;
-        kmp.read_kmp_section::<PathGroup<Enpt>, _>(r, 2)?;
-        kmp.read_kmp_section::<Itpt, _>(r, 3)?;
-        kmp.read_kmp_section::<PathGroup<Itpt>, _>(r, 4)?;
-        kmp.read_kmp_section::<Ckpt, _>(r, 5)?;
-        kmp.read_kmp_section::<PathGroup<Ckpt>, _>(r, 6)?;
-        kmp.read_kmp_section::<Gobj, _>(r, 7)?;
-        kmp.read_kmp_section::<Poti, _>(r, 8)?;
-        kmp.read_kmp_section::<Area, _>(r, 9)?;
-        kmp.read_kmp_section::<Came, _>(r, 10)?;
-        kmp.read_kmp_section::<Jgpt, _>(r, 11)?;
-        kmp.read_kmp_section::<Cnpt, _>(r, 12)?;
-        kmp.read_kmp_section::<Mspt, _>(r, 13)?;
-        kmp.read_kmp_section::<Stgi, _>(r, 14)?;
+        kmp.read_kmp_section::<Ktpt, _>(r, 0, endian)?;
+        kmp.read_kmp_section::<Enpt, _>(r, 1, endian)?;
+        kmp.read_kmp_section::<PathGroup<Enpt>, _>(r, 2, endian)?;
+        kmp.read_kmp_section::<Itpt, _>(r, 3, endian)?;
+        kmp.read_kmp_section::<PathGroup<Itpt>, _>(r, 4, endian)?;
+        kmp.read_kmp_section::<Ckpt, _>(r, 5, endian)?;
+        kmp.read_kmp_section::<PathGroup<Ckpt>, _>(r, 6, endian)?;
+        kmp.read_kmp_section::<Gobj, _>(r, 7, endian)?;
+        kmp.read_kmp_section::<Poti, _>(r, 8, endian)?;
+        kmp.read_kmp_section::<Area, _>(r, 9, endian)?;
+        kmp.read_kmp_section::<Came, _>(r, 10, endian)?;
+        kmp.read_kmp_section::<Jgpt, _>(r, 11, endian)?;
+        kmp.read_kmp_section::<Cnpt, _>(r, 12, endian)?;
+        kmp.read_kmp_section::<Mspt, _>(r, 13, endian)?;
+        kmp.read_kmp_section::<Stgi, _>(r, 14, endian)?;
 
         Ok(kmp)
     }
 
-    pub fn write<W: Write + Seek>(mut self, w: &mut W) -> anyhow::Result<()> {
+    pub fn write<W: Write + Seek>(self, w: &mut W) -> anyhow::Result<()> {
+        self.write_with_endian(w, Endian::Big)
+    }
+
+    fn write_with_endian<W: Write + Seek>(mut self, w: &mut W, endian: Endian) -> anyhow::Result<()> {
         // skip the header for now
         w.seek(SeekFrom::Start(Self::HEADER_LEN as u64))?;
 
-        self.write_kmp_section::<Ktpt, _>(w, 0)?;
-        self.write_kmp_section::<Enpt, _>(w, 1)?;
-        self.write_kmp_section::<PathGroup<Enpt>, _>(w, 2)?;
-        self.write_kmp_section::<Itpt, _>(w, 3)?;
-        self.write_kmp_section::<PathGroup<Itpt>, _>(w, 4)?;
-        self.write_kmp_section::<Ckpt, _>(w, 5)?;
-        self.write_kmp_section::<PathGroup<Ckpt>, _>(w, 6)?;
-        self.write_kmp_section::<Gobj, _>(w, 7)?;
-        self.write_kmp_section::<Poti, _>(w, 8)?;
-        self.write_kmp_section::<Area, _>(w, 9)?;
-        self.write_kmp_section::<Came, _>(w, 10)?;
-        self.write_kmp_section::<Jgpt, _>(w, 11)?;
-        self.write_kmp_section::<Cnpt, _>(w, 12)?;
-        self.write_kmp_section::<Mspt, _>(w, 13)?;
-        self.write_kmp_section::<Stgi, _>(w, 14)?;
+        self.write_kmp_section::<Ktpt, _>(w, 0, endian)?;
+        self.write_kmp_section::<Enpt, _>(w, 1, endian)?;
+        self.write_kmp_section::<PathGroup<Enpt>, _>(w, 2, endian)?;
+        self.write_kmp_section::<Itpt, _>(w, 3, endian)?;
+        self.write_kmp_section::<PathGroup<Itpt>, _>(w, 4, endian)?;
+        self.write_kmp_section::<Ckpt, _>(w, 5, endian)?;
+        self.write_kmp_section::<PathGroup<Ckpt>, _>(w, 6, endian)?;
+        self.write_kmp_section::<Gobj, _>(w, 7, endian)?;
+        self.write_kmp_section::<Poti, _>(w, 8, endian)?;
+        self.write_kmp_section::<Area, _>(w, 9, endian)?;
+        self.write_kmp_section::<Came, _>(w, 10, endian)?;
+        self.write_kmp_section::<Jgpt, _>(w, 11, endian)?;
+        self.write_kmp_section::<Cnpt, _>(w, 12, endian)?;
+        self.write_kmp_section::<Mspt, _>(w, 13, endian)?;
+        self.write_kmp_section::<Stgi, _>(w, 14, endian)?;
 
         // todo: go back to the start and write the header
         w.seek(SeekFrom::Start(0))?;
-        self.header.write(w)?;
+        self.header.write_options(w, endian, ())?;
 
         Ok(())
     }
 
-    fn read_kmp_section<T, R: Read + Seek>(&mut self, r: &mut R, i: usize) -> anyhow::Result<()>
+    fn read_kmp_section<T, R: Read + Seek>(&mut self, r: &mut R, i: usize, endian: Endian) -> anyhow::Result<()>
     where
         for<'a> T: BinRead<Args<'a> = ()> + 'a,
         T: KmpGetSection,
@@ -331,16 +482,16 @@ impl KmpFile {
         r.seek(SeekFrom::Start(
             self.header.section_offsets[i] as u64 + Self::HEADER_LEN as u64,
         ))?;
-        *T::get_section_mut(self) = Section::<T>::read(r)?;
+        *T::get_section_mut(self) = Section::<T>::read_options(r, endian, ())?;
         Ok(())
     }
-    fn write_kmp_section<T, W: Write + Seek>(&mut self, w: &mut W, i: usize) -> anyhow::Result<()>
+    fn write_kmp_section<T, W: Write + Seek>(&mut self, w: &mut W, i: usize, endian: Endian) -> anyhow::Result<()>
     where
         for<'a> T: BinWrite<Args<'a> = ()> + 'a,
         T: KmpGetSection,
     {
         self.header.section_offsets[i] = w.stream_position()? as u32 - Self::HEADER_LEN as u32;
-        T::get_section(self).write(w)?;
+        T::get_section(self).write_options(w, endian, ())?;
         Ok(())
     }
 }
@@ -578,3 +729,80 @@ fn read_write_kmp_test(path: &str) {
         );
     }
 }
+
+#[test]
+fn test_detect_format() {
+    let be = std::fs::read("test_files/desert_course/course.kmp").unwrap();
+    assert_eq!(KmpFormat::detect(&be), KmpFormat::Wii);
+
+    let le = write_kmp_test(KmpFile::read(&mut Cursor::new(&be)).unwrap(), Endian::Little);
+    assert_eq!(KmpFormat::detect(&le), KmpFormat::LittleEndian);
+    // some tools byte swap the magic along with everything else
+    let mut le_swapped_magic = le.clone();
+    le_swapped_magic[..4].copy_from_slice(b"DMKR");
+    assert_eq!(KmpFormat::detect(&le_swapped_magic), KmpFormat::LittleEndian);
+
+    // other Mario Kart games have a different number of sections
+    let mut other_game = be.clone();
+    other_game[8..10].copy_from_slice(&12u16.to_be_bytes());
+    assert!(matches!(KmpFormat::detect(&other_game), KmpFormat::Unsupported(_)));
+
+    let mut not_kmp = be.clone();
+    not_kmp[..4].copy_from_slice(b"Yaz0");
+    assert!(matches!(KmpFormat::detect(&not_kmp), KmpFormat::Unsupported(_)));
+    assert!(matches!(KmpFormat::detect(&be[..0x10]), KmpFormat::Unsupported(_)));
+}
+
+#[test]
+fn test_little_endian_conversion() {
+    for path in [
+        "test_files/desert_course/course.kmp",
+        "test_files/boardcross_course/course.kmp",
+        "test_files/shopping_course/course.kmp",
+    ] {
+        let be = std::fs::read(path).unwrap();
+        let le = write_kmp_test(KmpFile::read(&mut Cursor::new(&be)).unwrap(), Endian::Little);
+        let kmp = KmpFile::read_any(&le).unwrap();
+        assert!(
+            write_kmp_test(kmp, Endian::Big) == be,
+            "{path} changed when converted from little-endian"
+        );
+    }
+}
+
+#[allow(dead_code)]
+fn write_kmp_test(kmp: KmpFile, endian: Endian) -> Vec<u8> {
+    let mut out_cursor = Cursor::new(Vec::new());
+    kmp.write_with_endian(&mut out_cursor, endian).unwrap();
+    out_cursor.into_inner()
+}
+
+#[test]
+fn test_extended_presence_round_trip() {
+    for raw in 0..=u16::MAX {
+        assert_eq!(ExtendedPresence::from_raw(raw).to_raw(), raw);
+    }
+    assert_eq!(ExtendedPresence::from_raw(0), ExtendedPresence::None);
+    assert_eq!(ExtendedPresence::from_raw(0x0005), ExtendedPresence::Flags(0x0005));
+    assert_eq!(
+        ExtendedPresence::from_raw(0x1003),
+        ExtendedPresence::DefinitionObject(3)
+    );
+
+    let be = std::fs::read("test_files/desert_course/course.kmp").unwrap();
+    let mut kmp = KmpFile::read(&mut Cursor::new(&be)).unwrap();
+    kmp.gobj.entries[0].extended_presence = ExtendedPresence::Flags(0x0024).to_raw();
+    kmp.gobj.entries[1].extended_presence = ExtendedPresence::DefinitionObject(0).to_raw();
+    let written = write_kmp_test(kmp, Endian::Big);
+
+    let kmp = KmpFile::read(&mut Cursor::new(&written)).unwrap();
+    assert_eq!(
+        ExtendedPresence::from_raw(kmp.gobj.entries[0].extended_presence),
+        ExtendedPresence::Flags(0x0024)
+    );
+    assert_eq!(
+        ExtendedPresence::from_raw(kmp.gobj.entries[1].extended_presence),
+        ExtendedPresence::DefinitionObject(0)
+    );
+    assert!(write_kmp_test(kmp, Endian::Big) == written);
+}
@@ -4,6 +4,7 @@ pub mod kcl_file;
 pub mod kmp_file;
 pub mod read_write_arrays;
 pub mod shapes;
+pub mod szs;
 
 use bevy::{
     ecs::{
@@ -33,6 +34,19 @@ pub fn world_to_ui_viewport(cam: (&Camera, &GlobalTransform), viewport_rect: Rec
     };
     Some(ndc_to_ui_viewport(ndc.xy(), viewport_rect))
 }
+/// Where a position in the world is drawn by the 2D gizmo camera, so that shapes can be painted over it at the same
+/// size however far away it is. `None` if the position is behind the camera.
+pub fn world_to_gizmo_cam(
+    cam: (&Camera, &GlobalTransform),
+    gizmo_cam: (&Camera, &GlobalTransform),
+    world_pos: Vec3,
+) -> Option<Vec3> {
+    let ndc = cam.0.world_to_ndc(cam.1, world_pos)?;
+    if ndc.z < 0. || ndc.z > 1. {
+        return None;
+    };
+    gizmo_cam.0.ndc_to_world(gizmo_cam.1, ndc)
+}
 pub fn ui_viewport_to_world(cam: (&Camera, &GlobalTransform), viewport_rect: Rect, viewport_pos: Vec2) -> Option<Vec3> {
     let ndc = ui_viewport_to_ndc(viewport_pos, viewport_rect);
     cam.0.ndc_to_world(cam.1, ndc.extend(0.))
@@ -217,3 +231,20 @@ pub fn try_despawn(commands: &mut Commands, entity: Entity) {
         }
     });
 }
+
+/// Open a file with whatever program the OS has set as the default for it
+pub fn open_with_default_program(path: &std::path::Path) -> std::io::Result<()> {
+    use std::process::Command;
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        // the empty string is the window title, otherwise 'start' treats a quoted path as the title
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    cmd.arg(path).spawn()?;
+    Ok(())
+}
@@ -0,0 +1,333 @@
+use anyhow::{bail, Context};
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+use std::{
+    fs,
+    io::{Cursor, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// The name of the KMP inside a course archive
+pub const COURSE_KMP: &str = "course.kmp";
+/// The name of the KCL inside a course archive
+pub const COURSE_KCL: &str = "course.kcl";
+
+const YAZ0_MAGIC: &[u8; 4] = b"Yaz0";
+const YAZ0_HEADER_SIZE: usize = 16;
+// how far back, and how many bytes, a single back reference can copy
+const YAZ0_MAX_DISTANCE: usize = 0x1000;
+const YAZ0_MAX_LENGTH: usize = 0xFF + 0x12;
+// how many earlier positions with the same 3 bytes are tried when looking for a back reference
+const YAZ0_MAX_CHAIN: usize = 128;
+
+const U8_MAGIC: u32 = 0x55AA382D;
+const U8_HEADER_SIZE: u32 = 0x20;
+const U8_NODE_SIZE: u32 = 12;
+const U8_ALIGNMENT: u32 = 0x20;
+
+/// Read a course archive (a .szs file), which is a Yaz0 compressed U8 archive
+pub fn read_szs(path: &Path) -> anyhow::Result<U8Archive> {
+    let data = fs::read(path).context("could not open szs file")?;
+    let data = if data.starts_with(YAZ0_MAGIC) {
+        yaz0_decompress(&data)?
+    } else {
+        data
+    };
+    U8Archive::read(&data)
+}
+
+/// Compress and write a U8 archive to a .szs file
+pub fn write_szs(path: &Path, archive: &U8Archive) -> anyhow::Result<()> {
+    fs::write(path, yaz0_compress(&archive.write()?)).context("could not write szs file")
+}
+
+pub fn yaz0_decompress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if data.len() < YAZ0_HEADER_SIZE || !data.starts_with(YAZ0_MAGIC) {
+        bail!("not a Yaz0 compressed file");
+    }
+    let size = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+    // the size is only trusted as far as the data could plausibly decompress to, so a bad header can't allocate gigabytes
+    let mut out = Vec::with_capacity(size.min(data.len().saturating_mul(8)));
+    let mut pos = YAZ0_HEADER_SIZE;
+    let mut next_byte = || {
+        let byte = data.get(pos).copied().context("Yaz0 data ended early");
+        pos += 1;
+        byte
+    };
+
+    while out.len() < size {
+        // each bit of the code byte says whether the next chunk is a single byte or a back reference
+        let code = next_byte()?;
+        for bit in 0..8 {
+            if out.len() >= size {
+                break;
+            }
+            if code & (0x80 >> bit) != 0 {
+                out.push(next_byte()?);
+                continue;
+            }
+            let (b1, b2) = (next_byte()? as usize, next_byte()? as usize);
+            let distance = ((b1 & 0xF) << 8 | b2) + 1;
+            let length = match b1 >> 4 {
+                0 => next_byte()? as usize + 0x12,
+                n => n + 2,
+            };
+            if distance > out.len() {
+                bail!("Yaz0 back reference goes before the start of the data");
+            }
+            // copied a byte at a time, as the bytes being copied can overlap with the ones being written
+            let start = out.len() - distance;
+            for i in 0..length {
+                out.push(out[start + i]);
+            }
+        }
+    }
+    out.truncate(size);
+    Ok(out)
+}
+
+pub fn yaz0_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() / 2 + YAZ0_HEADER_SIZE);
+    out.extend_from_slice(YAZ0_MAGIC);
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(&[0; 8]);
+
+    // hash chains of the positions each 3 byte sequence appears at, to find back references quickly
+    const NONE: usize = usize::MAX;
+    const HASH_SIZE: usize = 1 << 16;
+    let hash = |pos: usize| {
+        let key = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+        (key.wrapping_mul(2654435761) >> 16) as usize % HASH_SIZE
+    };
+    let mut head = vec![NONE; HASH_SIZE];
+    let mut prev = vec![NONE; data.len()];
+    let insert = |pos: usize, head: &mut [usize], prev: &mut [usize]| {
+        if pos + 3 <= data.len() {
+            let h = hash(pos);
+            prev[pos] = head[h];
+            head[h] = pos;
+        }
+    };
+    let find_match = |pos: usize, head: &[usize], prev: &[usize]| {
+        if pos + 3 > data.len() {
+            return None;
+        }
+        let max_length = YAZ0_MAX_LENGTH.min(data.len() - pos);
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = head[hash(pos)];
+        for _ in 0..YAZ0_MAX_CHAIN {
+            if candidate == NONE || pos - candidate > YAZ0_MAX_DISTANCE {
+                break;
+            }
+            let length = (0..max_length)
+                .take_while(|i| data[candidate + i] == data[pos + i])
+                .count();
+            if length >= 3 && best.is_none_or(|(_, best_length)| length > best_length) {
+                best = Some((pos - candidate, length));
+                if length == max_length {
+                    break;
+                }
+            }
+            candidate = prev[candidate];
+        }
+        best
+    };
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let code_pos = out.len();
+        out.push(0);
+        for bit in 0..8 {
+            if pos >= data.len() {
+                break;
+            }
+            let Some((distance, length)) = find_match(pos, &head, &prev) else {
+                out[code_pos] |= 0x80 >> bit;
+                out.push(data[pos]);
+                insert(pos, &mut head, &mut prev);
+                pos += 1;
+                continue;
+            };
+            let distance = distance - 1;
+            if length >= 0x12 {
+                out.extend_from_slice(&[(distance >> 8) as u8, distance as u8, (length - 0x12) as u8]);
+            } else {
+                out.extend_from_slice(&[((length - 2) << 4 | distance >> 8) as u8, distance as u8]);
+            }
+            for i in pos..pos + length {
+                insert(i, &mut head, &mut prev);
+            }
+            pos += length;
+        }
+    }
+    out
+}
+
+/// A U8 archive, stored as its flat list of nodes. Directories are followed by everything inside them.
+#[derive(Clone, Default)]
+pub struct U8Archive {
+    pub nodes: Vec<U8Node>,
+}
+
+#[derive(Clone)]
+pub struct U8Node {
+    pub name: String,
+    pub kind: U8NodeKind,
+}
+
+#[derive(Clone)]
+pub enum U8NodeKind {
+    /// `parent` is the index of the parent directory, and `end` is the index of the first node after the directory
+    Directory {
+        parent: u32,
+        end: u32,
+    },
+    File(Vec<u8>),
+}
+
+impl U8Archive {
+    pub fn read(data: &[u8]) -> anyhow::Result<Self> {
+        let mut r = Cursor::new(data);
+        if r.read_u32::<BE>()? != U8_MAGIC {
+            bail!("not a U8 archive");
+        }
+        let root_offset = r.read_u32::<BE>()? as u64;
+
+        // the root node's size is the number of nodes in the archive
+        r.seek(SeekFrom::Start(root_offset + 8))?;
+        let num_nodes = r.read_u32::<BE>()?;
+        let strings_offset = root_offset as usize + num_nodes as usize * U8_NODE_SIZE as usize;
+
+        r.seek(SeekFrom::Start(root_offset))?;
+        // a bad node count can't be more nodes than fit in the archive
+        let mut nodes = Vec::with_capacity((num_nodes as usize).min(data.len() / U8_NODE_SIZE as usize));
+        for _ in 0..num_nodes {
+            let is_dir = r.read_u8()? == 1;
+            let name_offset = r.read_u24::<BE>()? as usize;
+            let offset = r.read_u32::<BE>()?;
+            let size = r.read_u32::<BE>()?;
+
+            let name = data
+                .get(strings_offset + name_offset..)
+                .and_then(|x| x.split(|b| *b == 0).next())
+                .context("U8 node name is outside the archive")?;
+            let name = String::from_utf8_lossy(name).into_owned();
+
+            let kind = if is_dir {
+                U8NodeKind::Directory {
+                    parent: offset,
+                    end: size,
+                }
+            } else {
+                let file = offset
+                    .checked_add(size)
+                    .and_then(|end| data.get(offset as usize..end as usize))
+                    .with_context(|| format!("U8 file '{name}' is outside the archive"))?;
+                U8NodeKind::File(file.to_vec())
+            };
+            nodes.push(U8Node { name, kind });
+        }
+        Ok(Self { nodes })
+    }
+
+    pub fn write(&self) -> anyhow::Result<Vec<u8>> {
+        let mut strings = Vec::new();
+        let mut name_offsets = Vec::new();
+        for node in self.nodes.iter() {
+            name_offsets.push(strings.len() as u32);
+            strings.extend_from_slice(node.name.as_bytes());
+            strings.push(0);
+        }
+        let nodes_size = self.nodes.len() as u32 * U8_NODE_SIZE + strings.len() as u32;
+        let data_offset = align(U8_HEADER_SIZE + nodes_size, U8_ALIGNMENT);
+
+        let mut w = Cursor::new(Vec::new());
+        w.write_u32::<BE>(U8_MAGIC)?;
+        w.write_u32::<BE>(U8_HEADER_SIZE)?;
+        w.write_u32::<BE>(nodes_size)?;
+        w.write_u32::<BE>(data_offset)?;
+        w.write_all(&[0; 16])?;
+
+        let mut file_offset = data_offset;
+        for (node, name_offset) in self.nodes.iter().zip(name_offsets) {
+            let (is_dir, offset, size) = match &node.kind {
+                U8NodeKind::Directory { parent, end } => (true, *parent, *end),
+                U8NodeKind::File(file) => {
+                    let offset = file_offset;
+                    file_offset = align(file_offset + file.len() as u32, U8_ALIGNMENT);
+                    (false, offset, file.len() as u32)
+                }
+            };
+            w.write_u8(is_dir as u8)?;
+            w.write_u24::<BE>(name_offset)?;
+            w.write_u32::<BE>(offset)?;
+            w.write_u32::<BE>(size)?;
+        }
+        w.write_all(&strings)?;
+
+        for node in self.nodes.iter() {
+            if let U8NodeKind::File(file) = &node.kind {
+                let padding = align(w.position() as u32, U8_ALIGNMENT) - w.position() as u32;
+                w.write_all(&vec![0; padding as usize])?;
+                w.write_all(file)?;
+            }
+        }
+        Ok(w.into_inner())
+    }
+
+    /// The contents of the first file with this name, in any directory
+    pub fn file(&self, name: &str) -> Option<&Vec<u8>> {
+        self.nodes.iter().find_map(|node| match &node.kind {
+            U8NodeKind::File(file) if node.name == name => Some(file),
+            _ => None,
+        })
+    }
+
+    pub fn file_mut(&mut self, name: &str) -> Option<&mut Vec<u8>> {
+        self.nodes.iter_mut().find_map(|node| match &mut node.kind {
+            U8NodeKind::File(file) if node.name == name => Some(file),
+            _ => None,
+        })
+    }
+
+    /// A reader over the first file with this name, for reading the file out of the archive
+    pub fn file_reader(&self, name: &str) -> Option<Cursor<&[u8]>> {
+        self.file(name).map(|file| Cursor::new(file.as_slice()))
+    }
+}
+
+fn align(x: u32, alignment: u32) -> u32 {
+    x.div_ceil(alignment) * alignment
+}
+
+#[test]
+fn test_szs_round_trip() {
+    let kmp: Vec<u8> = (0..5000u32).map(|x| (x % 7 + x / 300) as u8).collect();
+    let archive = U8Archive {
+        nodes: vec![
+            U8Node {
+                name: String::new(),
+                kind: U8NodeKind::Directory { parent: 0, end: 4 },
+            },
+            U8Node {
+                name: ".".into(),
+                kind: U8NodeKind::Directory { parent: 0, end: 4 },
+            },
+            U8Node {
+                name: COURSE_KMP.into(),
+                kind: U8NodeKind::File(kmp.clone()),
+            },
+            U8Node {
+                name: COURSE_KCL.into(),
+                kind: U8NodeKind::File(b"not really a kcl".to_vec()),
+            },
+        ],
+    };
+    let data = archive.write().unwrap();
+    let compressed = yaz0_compress(&data);
+    assert!(compressed.len() < data.len());
+    assert_eq!(yaz0_decompress(&compressed).unwrap(), data);
+
+    let read_archive = U8Archive::read(&data).unwrap();
+    assert_eq!(read_archive.file(COURSE_KMP), Some(&kmp));
+    assert_eq!(read_archive.write().unwrap(), data);
+}
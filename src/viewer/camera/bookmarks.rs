@@ -0,0 +1,104 @@
+use super::{home::AllCameras, HomeCamera, UpdateCameraSet};
+use crate::{
+    ui::{
+        errors::ErrorMessages,
+        keybinds::{KeyAction, Keybinds},
+        ui_state::KmpFilePath,
+        viewport::ViewportInfo,
+    },
+    viewer::kmp::sidecar::KmpSidecar,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub fn bookmarks_plugin(app: &mut App) {
+    app.add_event::<GoToCameraBookmark>()
+        .add_event::<SetCameraBookmark>()
+        .add_systems(
+            Update,
+            (
+                bookmark_keybinds,
+                set_camera_bookmark.run_if(on_event::<SetCameraBookmark>()),
+                go_to_camera_bookmark.run_if(on_event::<GoToCameraBookmark>()),
+            )
+                .chain()
+                .in_set(UpdateCameraSet),
+        );
+}
+
+/// How many camera bookmarks each KMP can have, one for each number key by default
+pub const BOOKMARK_SLOTS: usize = 9;
+
+/// A named position for every camera, such as the start line or a tricky corner, saved in the sidecar
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub cameras: HomeCamera,
+}
+
+/// Move all the cameras to the bookmark in this slot, if there is one
+#[derive(Event)]
+pub struct GoToCameraBookmark(pub usize);
+
+/// Save the current position of all the cameras into this bookmark slot for the currently open KMP, keeping the name
+/// of any bookmark already there
+#[derive(Event)]
+pub struct SetCameraBookmark(pub usize);
+
+fn bookmark_keybinds(
+    keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    viewport_info: Res<ViewportInfo>,
+    mut ev_go_to: EventWriter<GoToCameraBookmark>,
+    mut ev_set: EventWriter<SetCameraBookmark>,
+) {
+    if !viewport_info.mouse_in_viewport {
+        return;
+    }
+    for i in 0..BOOKMARK_SLOTS {
+        if keybinds.just_pressed(KeyAction::SET_BOOKMARK[i], &keys) {
+            ev_set.send(SetCameraBookmark(i));
+        } else if keybinds.just_pressed(KeyAction::GO_TO_BOOKMARK[i], &keys) {
+            ev_go_to.send(GoToCameraBookmark(i));
+        }
+    }
+}
+
+fn set_camera_bookmark(
+    mut ev_set: EventReader<SetCameraBookmark>,
+    cameras: AllCameras,
+    kmp_file_path: Option<Res<KmpFilePath>>,
+    sidecar: Option<ResMut<KmpSidecar>>,
+    mut errors: ResMut<ErrorMessages>,
+) {
+    // bookmarks are stored per file, so we can't set them if there is no file open
+    let (Some(kmp_file_path), Some(mut sidecar)) = (kmp_file_path, sidecar) else {
+        return;
+    };
+    for SetCameraBookmark(i) in ev_set.read() {
+        let Some(slot) = sidecar.camera_bookmarks.get_mut(*i) else {
+            continue;
+        };
+        let name = slot.take().map_or_else(|| format!("Bookmark {}", i + 1), |x| x.name);
+        *slot = Some(CameraBookmark {
+            name,
+            cameras: cameras.get(),
+        });
+    }
+    if let Err(err) = sidecar.save(&kmp_file_path.0) {
+        errors.push(err);
+    }
+}
+
+fn go_to_camera_bookmark(
+    mut ev_go_to: EventReader<GoToCameraBookmark>,
+    mut cameras: AllCameras,
+    sidecar: Option<Res<KmpSidecar>>,
+) {
+    let Some(GoToCameraBookmark(i)) = ev_go_to.read().last() else {
+        return;
+    };
+    if let Some(bookmark) = sidecar.and_then(|x| x.camera_bookmarks.get(*i).cloned().flatten()) {
+        cameras.set(bookmark.cameras);
+    }
+}
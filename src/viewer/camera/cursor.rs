@@ -0,0 +1,57 @@
+use super::{Gizmo2dCam, UpdateCameraSet};
+use crate::{
+    ui::viewport::ViewportInfo,
+    util::{ui_viewport_to_ndc, RaycastFromCam},
+    viewer::kcl_model::KCLModelSection,
+};
+use bevy::prelude::*;
+use bevy_mod_raycast::prelude::*;
+
+pub fn cursor_plugin(app: &mut App) {
+    app.init_resource::<CursorKclPos>()
+        .add_systems(Update, update_cursor_kcl_pos.after(UpdateCameraSet));
+}
+
+/// Where on the KCL the mouse is over in the viewport, if it is over the KCL at all
+#[derive(Resource, Default, Deref, PartialEq)]
+pub struct CursorKclPos(pub Option<Vec3>);
+
+/// A dialog for moving the cameras to look at an exactly typed position. The dialog is open while this resource
+/// exists.
+#[derive(Resource, Default)]
+pub struct GoToCoordinates {
+    pub pos: Vec3,
+}
+
+// casting against the whole KCL is slow, so it's only done again when the mouse or camera has moved
+fn update_cursor_kcl_pos(
+    mut cursor_pos: ResMut<CursorKclPos>,
+    mut last_mouse_pos: Local<Option<Vec2>>,
+    viewport_info: Res<ViewportInfo>,
+    q_window: Query<&Window>,
+    q_camera: Query<(&Camera, Ref<GlobalTransform>), Without<Gizmo2dCam>>,
+    mut raycast: Raycast,
+    q_kcl: Query<(), With<KCLModelSection>>,
+) {
+    let mouse_pos = q_window.get_single().ok().and_then(|x| x.cursor_position());
+    let Some((mouse_pos, (cam, cam_transform))) = mouse_pos
+        .filter(|_| viewport_info.mouse_in_viewport)
+        .zip(q_camera.iter().find(|cam| cam.0.is_active))
+    else {
+        cursor_pos.set_if_neq(CursorKclPos(None));
+        *last_mouse_pos = None;
+        return;
+    };
+    if *last_mouse_pos == Some(mouse_pos) && !cam_transform.is_changed() {
+        return;
+    }
+    *last_mouse_pos = Some(mouse_pos);
+
+    let mouse_pos_ndc = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+    let pos = RaycastFromCam::new((cam, &*cam_transform), mouse_pos_ndc, &mut raycast)
+        .filter(&|e| q_kcl.contains(e))
+        .cast()
+        .first()
+        .map(|x| x.1.position());
+    cursor_pos.set_if_neq(CursorKclPos(pos));
+}
@@ -1,5 +1,5 @@
 use crate::ui::{
-    keybinds::ModifiersPressed,
+    keybinds::{KeyAction, Keybinds},
     settings::AppSettings,
     viewport::{SetupViewportSet, ViewportImage, ViewportInfo},
 };
@@ -13,7 +13,7 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 use transform_gizmo_bevy::GizmoCamera;
 
-use super::{CameraMode, UpdateCameraSet};
+use super::{CameraMode, UpdateCameraSet, ViewDistance};
 
 pub fn fly_cam_plugin(app: &mut App) {
     app.add_systems(Startup, camera_setup.after(SetupViewportSet))
@@ -30,6 +30,7 @@ pub struct FlySettings {
     pub hold_mouse_to_move: bool,
     pub speed: f32,
     pub speed_boost: f32,
+    pub view_distance: ViewDistance,
     pub key_bindings: FlyKeyBindings,
 }
 impl Default for FlySettings {
@@ -40,19 +41,15 @@ impl Default for FlySettings {
             hold_mouse_to_move: false,
             speed: 1.,
             speed_boost: 3.,
+            view_distance: ViewDistance::default(),
             key_bindings: FlyKeyBindings::default(),
         }
     }
 }
 
+/// The keys which move the fly camera are set along with the other keybinds, see [`KeyAction::FlyForward`] and so on
 #[derive(Serialize, Deserialize)]
 pub struct FlyKeyBindings {
-    pub move_forward: Vec<KeyCode>,
-    pub move_backward: Vec<KeyCode>,
-    pub move_left: Vec<KeyCode>,
-    pub move_right: Vec<KeyCode>,
-    pub move_ascend: Vec<KeyCode>,
-    pub move_descend: Vec<KeyCode>,
     pub speed_boost: Vec<KeyCode>,
     pub mouse_button: MouseButton,
 }
@@ -60,12 +57,6 @@ pub struct FlyKeyBindings {
 impl Default for FlyKeyBindings {
     fn default() -> Self {
         Self {
-            move_forward: vec![KeyCode::KeyW, KeyCode::ArrowUp],
-            move_backward: vec![KeyCode::KeyS, KeyCode::ArrowDown],
-            move_left: vec![KeyCode::KeyA, KeyCode::ArrowLeft],
-            move_right: vec![KeyCode::KeyD, KeyCode::ArrowRight],
-            move_ascend: vec![KeyCode::KeyE, KeyCode::PageUp],
-            move_descend: vec![KeyCode::KeyQ, KeyCode::PageDown],
             speed_boost: vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
             mouse_button: MouseButton::Right,
         }
@@ -96,14 +87,12 @@ fn fly_cam_move(
     mut q_fly_cam: Query<&mut Transform, With<FlyCam>>,
     mut ev_request_redraw: EventWriter<RequestRedraw>,
     settings: Res<AppSettings>,
+    keybinds: Res<Keybinds>,
     viewport_info: Res<ViewportInfo>,
 ) {
     if !viewport_info.mouse_in_viewport || settings.camera.mode != CameraMode::Fly {
         return;
     }
-    if keys.control_or_super_pressed() {
-        return;
-    }
 
     let window = q_window.get_single().unwrap();
     // if we need to be holding the mouse to move but we aren't, return
@@ -118,33 +107,33 @@ fn fly_cam_move(
     let forward = -Vec3::new(local_z.x, 0., local_z.z).normalize();
     let right = Vec3::new(local_z.z, 0., -local_z.x).normalize();
 
-    let mut speed_boost = false;
-
     if keys.get_pressed().count() > 0 {
         // redraw the window when we're holding a button down (e.g. flying around but not moving the mouse)
         // as otherwise the window doesn't redraw
         ev_request_redraw.send(RequestRedraw);
     }
 
-    for key in keys.get_pressed() {
-        let key_bindings = &settings.camera.fly.key_bindings;
-        if key_bindings.move_forward.contains(key) {
-            velocity += forward;
-        } else if key_bindings.move_backward.contains(key) {
-            velocity -= forward;
-        } else if key_bindings.move_left.contains(key) {
-            velocity -= right;
-        } else if key_bindings.move_right.contains(key) {
-            velocity += right;
-        } else if key_bindings.move_ascend.contains(key) {
-            velocity += Vec3::Y;
-        } else if key_bindings.move_descend.contains(key) {
-            velocity -= Vec3::Y;
-        } else if key_bindings.speed_boost.contains(key) {
-            speed_boost = true;
-        }
+    let held = |action| keybinds.held(action, &keys);
+    if held(KeyAction::FlyForward) {
+        velocity += forward;
+    }
+    if held(KeyAction::FlyBackward) {
+        velocity -= forward;
+    }
+    if held(KeyAction::FlyLeft) {
+        velocity -= right;
+    }
+    if held(KeyAction::FlyRight) {
+        velocity += right;
+    }
+    if held(KeyAction::FlyUp) {
+        velocity += Vec3::Y;
+    }
+    if held(KeyAction::FlyDown) {
+        velocity -= Vec3::Y;
     }
-    if speed_boost {
+    let speed_boost = settings.camera.fly.key_bindings.speed_boost.iter();
+    if keys.any_pressed(speed_boost.copied()) {
         velocity *= settings.camera.fly.speed_boost;
     }
 
@@ -0,0 +1,214 @@
+use super::{FlyCam, OrbitCam, TopDownCam, UpdateCameraSet};
+use crate::{
+    ui::{
+        errors::ErrorMessages,
+        keybinds::{KeyAction, Keybinds},
+        ui_state::KmpFilePath,
+        viewport::ViewportInfo,
+    },
+    util::kcl_file::Kcl,
+    viewer::kmp::{components::KmpSelectablePoint, sidecar::KmpSidecar},
+};
+use bevy::{ecs::system::SystemParam, math::vec3, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub fn home_cam_plugin(app: &mut App) {
+    app.add_event::<GoToHomeCamera>()
+        .add_event::<SetHomeCamera>()
+        .add_event::<FrameCameras>()
+        .add_systems(
+            Update,
+            (
+                home_keybinds,
+                send_go_home_on_open.run_if(resource_added::<Kcl>.or_else(resource_exists_and_changed::<KmpFilePath>)),
+                set_home_camera.run_if(on_event::<SetHomeCamera>()),
+                go_to_home_camera.run_if(on_event::<GoToHomeCamera>()),
+                frame_cameras.run_if(on_event::<FrameCameras>()),
+            )
+                .chain()
+                .in_set(UpdateCameraSet),
+        );
+}
+
+/// Move all the cameras to the home position saved in the sidecar, or if there isn't one, frame the whole track
+#[derive(Event, Default)]
+pub struct GoToHomeCamera;
+
+/// Save the current position of all the cameras as the home position for the currently open KMP
+#[derive(Event, Default)]
+pub struct SetHomeCamera;
+
+/// Move all the cameras so that the given positions are all in view
+#[derive(Event)]
+pub struct FrameCameras(pub Vec<Vec3>);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HomeCamera {
+    pub fly: Transform,
+    pub orbit: Transform,
+    pub orbit_focus: Vec3,
+    pub orbit_radius: f32,
+    pub topdown: Transform,
+    pub topdown_scale: f32,
+}
+
+fn home_keybinds(
+    keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    viewport_info: Res<ViewportInfo>,
+    mut ev_go_home: EventWriter<GoToHomeCamera>,
+    mut ev_set_home: EventWriter<SetHomeCamera>,
+) {
+    if !viewport_info.mouse_in_viewport {
+        return;
+    }
+    if keybinds.just_pressed(KeyAction::SetHome, &keys) {
+        ev_set_home.send_default();
+    } else if keybinds.just_pressed(KeyAction::GoHome, &keys) {
+        ev_go_home.send_default();
+    }
+}
+
+fn send_go_home_on_open(mut ev_go_home: EventWriter<GoToHomeCamera>) {
+    ev_go_home.send_default();
+}
+
+fn set_home_camera(
+    cameras: AllCameras,
+    kmp_file_path: Option<Res<KmpFilePath>>,
+    sidecar: Option<ResMut<KmpSidecar>>,
+    mut errors: ResMut<ErrorMessages>,
+) {
+    // the home camera is stored per file, so we can't set it if there is no file open
+    let (Some(kmp_file_path), Some(mut sidecar)) = (kmp_file_path, sidecar) else {
+        return;
+    };
+    sidecar.home_camera = Some(cameras.get());
+    if let Err(err) = sidecar.save(&kmp_file_path.0) {
+        errors.push(err);
+    }
+}
+
+/// Every camera, so they can all be moved at once
+#[derive(SystemParam)]
+pub(super) struct AllCameras<'w, 's> {
+    q_fly: Query<'w, 's, &'static mut Transform, (With<FlyCam>, Without<OrbitCam>, Without<TopDownCam>)>,
+    q_orbit: Query<'w, 's, (&'static mut Transform, &'static mut OrbitCam), (Without<FlyCam>, Without<TopDownCam>)>,
+    q_topdown: Query<
+        'w,
+        's,
+        (&'static mut Transform, &'static mut Projection),
+        (With<TopDownCam>, Without<FlyCam>, Without<OrbitCam>),
+    >,
+}
+impl AllCameras<'_, '_> {
+    /// The current position of every camera
+    pub(super) fn get(&self) -> HomeCamera {
+        let (orbit, orbit_cam) = self.q_orbit.single();
+        let (topdown, topdown_proj) = self.q_topdown.single();
+        let topdown_scale = match topdown_proj {
+            Projection::Orthographic(proj) => proj.scale,
+            _ => 1.,
+        };
+        HomeCamera {
+            fly: *self.q_fly.single(),
+            orbit: *orbit,
+            orbit_focus: orbit_cam.focus,
+            orbit_radius: orbit_cam.radius,
+            topdown: *topdown,
+            topdown_scale,
+        }
+    }
+    /// Move every camera to the given positions
+    pub(super) fn set(&mut self, home: HomeCamera) {
+        *self.q_fly.single_mut() = home.fly;
+        let (mut orbit, mut orbit_cam) = self.q_orbit.single_mut();
+        *orbit = home.orbit;
+        orbit_cam.focus = home.orbit_focus;
+        orbit_cam.radius = home.orbit_radius;
+        let (mut topdown, mut topdown_proj) = self.q_topdown.single_mut();
+        *topdown = home.topdown;
+        if let Projection::Orthographic(proj) = &mut *topdown_proj {
+            proj.scale = home.topdown_scale;
+        }
+    }
+}
+
+fn go_to_home_camera(
+    mut cameras: AllCameras,
+    q_points: Query<
+        &Transform,
+        (
+            With<KmpSelectablePoint>,
+            Without<FlyCam>,
+            Without<OrbitCam>,
+            Without<TopDownCam>,
+        ),
+    >,
+    sidecar: Option<Res<KmpSidecar>>,
+    kcl: Option<Res<Kcl>>,
+    viewport_info: Res<ViewportInfo>,
+) {
+    let home = match sidecar.and_then(|x| x.home_camera.clone()) {
+        Some(home) => home,
+        None => {
+            // frame the KCL if there is one, otherwise frame the KMP points
+            let positions: Vec<Vec3> = match kcl {
+                Some(kcl) => kcl
+                    .vertex_groups
+                    .iter()
+                    .flat_map(|x| x.vertices.iter().copied())
+                    .collect(),
+                None => q_points.iter().map(|x| x.translation).collect(),
+            };
+            let Some(home) = frame_positions(&positions, viewport_info.viewport_rect.height()) else {
+                return;
+            };
+            home
+        }
+    };
+
+    cameras.set(home);
+}
+
+fn frame_cameras(mut ev_frame: EventReader<FrameCameras>, mut cameras: AllCameras, viewport_info: Res<ViewportInfo>) {
+    let Some(FrameCameras(positions)) = ev_frame.read().last() else {
+        return;
+    };
+    if let Some(home) = frame_positions(positions, viewport_info.viewport_rect.height()) {
+        cameras.set(home);
+    }
+}
+
+/// Work out camera positions which fit all the given positions in view
+fn frame_positions(positions: &[Vec3], viewport_height: f32) -> Option<HomeCamera> {
+    if positions.is_empty() {
+        return None;
+    }
+    let min = positions.iter().copied().reduce(Vec3::min)?;
+    let max = positions.iter().copied().reduce(Vec3::max)?;
+    let center = (min + max) / 2.;
+    // the radius of a sphere which contains everything
+    let radius = ((max - min).length() / 2.).max(1000.);
+
+    let offset = vec3(1., 0.8, 0.).normalize() * radius * 1.2;
+    let fly = Transform::from_translation(center + offset).looking_at(center, Vec3::Y);
+    let orbit = fly;
+
+    let topdown = Transform::from_translation(vec3(center.x, max.y + 10000., center.z))
+        .looking_at(vec3(center.x, min.y, center.z), Vec3::Z);
+    let topdown_scale = if viewport_height > 0. {
+        ((max - min).xz().max_element() / viewport_height * 1.1).clamp(1., 500.)
+    } else {
+        100.
+    };
+
+    Some(HomeCamera {
+        fly,
+        orbit,
+        orbit_focus: center,
+        orbit_radius: offset.length(),
+        topdown,
+        topdown_scale,
+    })
+}
@@ -1,19 +1,30 @@
-use self::{fly::fly_cam_plugin, gizmo_2d::gizmo_2d_cam_plugin, orbit::orbit_cam_plugin, topdown::topdown_cam_plugin};
+use self::{
+    bookmarks::bookmarks_plugin, cursor::cursor_plugin, fly::fly_cam_plugin, gizmo_2d::gizmo_2d_cam_plugin,
+    home::home_cam_plugin, orbit::orbit_cam_plugin, topdown::topdown_cam_plugin, view_distance::view_distance_plugin,
+};
 pub use self::{
+    bookmarks::{CameraBookmark, GoToCameraBookmark, SetCameraBookmark, BOOKMARK_SLOTS},
+    cursor::{CursorKclPos, GoToCoordinates},
     fly::{FlyCam, FlySettings},
     gizmo_2d::Gizmo2dCam,
+    home::{FrameCameras, GoToHomeCamera, HomeCamera, SetHomeCamera},
     orbit::{OrbitCam, OrbitSettings},
     topdown::{TopDownCam, TopDownSettings},
+    view_distance::{ViewDistance, ViewDistancePreset},
 };
 use crate::ui::{settings::AppSettings, update_ui::UpdateUiSet, viewport::ViewportInfo};
 use bevy::{prelude::*, window::CursorGrabMode};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString, IntoStaticStr};
 
+mod bookmarks;
+mod cursor;
 mod fly;
 mod gizmo_2d;
+mod home;
 mod orbit;
 mod topdown;
+mod view_distance;
 
 pub fn camera_plugin(app: &mut App) {
     app.add_plugins((
@@ -21,6 +32,10 @@ pub fn camera_plugin(app: &mut App) {
         orbit_cam_plugin,
         topdown_cam_plugin,
         gizmo_2d_cam_plugin,
+        home_cam_plugin,
+        view_distance_plugin,
+        cursor_plugin,
+        bookmarks_plugin,
     ))
     .configure_sets(Update, UpdateCameraSet.before(UpdateUiSet))
     .add_event::<CameraModeChanged>()
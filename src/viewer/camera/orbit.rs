@@ -1,4 +1,4 @@
-use super::{CameraMode, UpdateCameraSet};
+use super::{CameraMode, UpdateCameraSet, ViewDistance};
 use crate::ui::{
     settings::AppSettings,
     viewport::{SetupViewportSet, ViewportImage, ViewportInfo},
@@ -39,6 +39,7 @@ pub struct OrbitSettings {
     pub rotate_sensitivity: f32,
     pub pan_sensitivity: f32,
     pub scroll_sensitivity: f32,
+    pub view_distance: ViewDistance,
     pub key_bindings: OrbitKeyBindings,
 }
 impl Default for OrbitSettings {
@@ -48,6 +49,7 @@ impl Default for OrbitSettings {
             rotate_sensitivity: 1.,
             pan_sensitivity: 1.,
             scroll_sensitivity: 1.,
+            view_distance: ViewDistance::default(),
             key_bindings: OrbitKeyBindings::default(),
         }
     }
@@ -1,4 +1,4 @@
-use super::{CameraMode, UpdateCameraSet};
+use super::{CameraMode, UpdateCameraSet, ViewDistance};
 use crate::ui::{
     settings::AppSettings,
     viewport::{SetupViewportSet, ViewportImage, ViewportInfo},
@@ -23,22 +23,20 @@ pub struct TopDownCam;
 #[derive(Serialize, Deserialize)]
 pub struct TopDownSettings {
     pub start_pos: Vec3,
-    pub near: f32,
-    pub far: f32,
     pub scale: f32,
     pub move_sensitivity: f32,
     pub scroll_sensitivity: f32,
+    pub view_distance: ViewDistance,
     pub key_bindings: TopDownKeyBindings,
 }
 impl Default for TopDownSettings {
     fn default() -> Self {
         Self {
             start_pos: vec3(0., 100000., 0.),
-            near: 0.000001,
-            far: 1000000.,
             scale: 100.,
             move_sensitivity: 1.,
             scroll_sensitivity: 1.,
+            view_distance: ViewDistance::default(),
             key_bindings: TopDownKeyBindings::default(),
         }
     }
@@ -67,8 +65,8 @@ fn camera_setup(mut commands: Commands, viewport: Res<ViewportImage>) {
                 ..default()
             },
             projection: Projection::Orthographic(OrthographicProjection {
-                near: topdown_default.near,
-                far: topdown_default.far,
+                near: topdown_default.view_distance.near,
+                far: topdown_default.view_distance.far,
                 scale: topdown_default.scale,
                 ..default()
             }),
@@ -0,0 +1,101 @@
+use super::{FlyCam, OrbitCam, TopDownCam};
+use crate::ui::settings::AppSettings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+pub fn view_distance_plugin(app: &mut App) {
+    app.add_systems(Update, apply_view_distance.run_if(resource_changed::<AppSettings>));
+}
+
+/// The clip planes and fog of a camera. A larger near plane reduces z-fighting, and a larger far plane stops the
+/// edges of big tracks being cut off.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ViewDistance {
+    pub near: f32,
+    pub far: f32,
+    /// Fade things out into the background colour with distance, to make depth easier to judge
+    pub fog: bool,
+    pub fog_start: f32,
+    pub fog_end: f32,
+}
+impl Default for ViewDistance {
+    fn default() -> Self {
+        ViewDistancePreset::Normal.view_distance()
+    }
+}
+impl ViewDistance {
+    /// The preset these settings are the same as, if any
+    pub fn preset(&self) -> Option<ViewDistancePreset> {
+        ViewDistancePreset::iter().find(|x| x.view_distance() == *self)
+    }
+}
+
+#[derive(Display, EnumIter, Clone, Copy, PartialEq)]
+pub enum ViewDistancePreset {
+    #[strum(serialize = "Small Track")]
+    Small,
+    #[strum(serialize = "Normal Track")]
+    Normal,
+    #[strum(serialize = "Huge Track")]
+    Huge,
+}
+impl ViewDistancePreset {
+    pub fn view_distance(self) -> ViewDistance {
+        let (near, far, fog_start) = match self {
+            Self::Small => (10., 200_000., 50_000.),
+            Self::Normal => (1., 1_000_000., 300_000.),
+            Self::Huge => (5., 5_000_000., 1_500_000.),
+        };
+        ViewDistance {
+            near,
+            far,
+            fog: false,
+            fog_start,
+            fog_end: far,
+        }
+    }
+}
+
+fn apply_view_distance(
+    mut commands: Commands,
+    settings: Res<AppSettings>,
+    clear_color: Res<ClearColor>,
+    mut q_cams: Query<
+        (Entity, &mut Projection, Has<FlyCam>, Has<OrbitCam>),
+        Or<(With<FlyCam>, With<OrbitCam>, With<TopDownCam>)>,
+    >,
+) {
+    for (e, mut projection, is_fly, is_orbit) in q_cams.iter_mut() {
+        let view_distance = if is_fly {
+            settings.camera.fly.view_distance
+        } else if is_orbit {
+            settings.camera.orbit.view_distance
+        } else {
+            settings.camera.top_down.view_distance
+        };
+        match &mut *projection {
+            Projection::Perspective(projection) => {
+                projection.near = view_distance.near;
+                projection.far = view_distance.far;
+            }
+            Projection::Orthographic(projection) => {
+                projection.near = view_distance.near;
+                projection.far = view_distance.far;
+            }
+        }
+        if view_distance.fog {
+            commands.entity(e).insert(FogSettings {
+                color: clear_color.0,
+                falloff: FogFalloff::Linear {
+                    start: view_distance.fog_start,
+                    end: view_distance.fog_end,
+                },
+                ..default()
+            });
+        } else {
+            commands.entity(e).remove::<FogSettings>();
+        }
+    }
+}
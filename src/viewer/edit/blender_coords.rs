@@ -0,0 +1,145 @@
+use super::{create_at_coordinates::parse_coordinates, select::Selected};
+use crate::{
+    ui::{errors::show_errors, settings::AppSettings},
+    viewer::kmp::{components::KmpSelectablePoint, ordering::OrderId},
+};
+use anyhow::{anyhow, bail};
+use bevy::{math::vec3, prelude::*};
+use bevy_egui::EguiClipboard;
+use serde::{Deserialize, Serialize};
+
+pub fn blender_coords_plugin(app: &mut App) {
+    app.add_event::<CopyBlenderCoords>()
+        .add_event::<PasteBlenderCoords>()
+        .add_systems(
+            Update,
+            (
+                copy_blender_coords.run_if(on_event::<CopyBlenderCoords>()),
+                paste_blender_coords
+                    .pipe(show_errors)
+                    .run_if(on_event::<PasteBlenderCoords>()),
+            ),
+        );
+}
+
+/// How positions are converted to and from Blender, which is Z-up where the game is Y-up, and usually works at a
+/// much smaller scale
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct BlenderCoordSettings {
+    /// How many Blender units one game unit is
+    pub scale: f32,
+    /// If enabled, the game's Z axis points along Blender's -Y axis, which keeps models the same way round. If
+    /// disabled, it points along +Y, which mirrors them.
+    pub flip_forward: bool,
+    /// How many decimal places are copied
+    pub precision: usize,
+}
+impl Default for BlenderCoordSettings {
+    fn default() -> Self {
+        Self {
+            scale: 0.01,
+            flip_forward: true,
+            precision: 4,
+        }
+    }
+}
+impl BlenderCoordSettings {
+    pub fn to_blender(&self, pos: Vec3) -> Vec3 {
+        let forward = if self.flip_forward { -pos.z } else { pos.z };
+        vec3(pos.x, forward, pos.y) * self.scale
+    }
+    pub fn from_blender(&self, pos: Vec3) -> Vec3 {
+        let pos = pos / self.scale;
+        let z = if self.flip_forward { -pos.y } else { pos.y };
+        vec3(pos.x, pos.z, z)
+    }
+}
+
+/// Send this event to copy the positions of the selected points to the clipboard in Blender's coordinates, one
+/// point per line
+#[derive(Event, Default)]
+pub struct CopyBlenderCoords;
+
+/// Send this event to move the selected points to positions on the clipboard copied from Blender. A single position
+/// moves the centre of the selection there, otherwise there must be one position for each selected point.
+#[derive(Event, Default)]
+pub struct PasteBlenderCoords;
+
+// the selected points, in the order they are in the KMP
+fn selected_in_order(world: &mut World) -> Vec<Entity> {
+    let mut q_selected =
+        world.query_filtered::<(Entity, Option<&OrderId>), (With<Selected>, With<KmpSelectablePoint>)>();
+    let mut selected: Vec<_> = q_selected
+        .iter(world)
+        .map(|(e, order_id)| (e, order_id.map(|x| x.0)))
+        .collect();
+    selected.sort_by_key(|x| x.1);
+    selected.into_iter().map(|x| x.0).collect()
+}
+
+fn copy_blender_coords(world: &mut World) {
+    world.resource_mut::<Events<CopyBlenderCoords>>().clear();
+    let blender = world.resource::<AppSettings>().blender.clone();
+    let selected = selected_in_order(world);
+    if selected.is_empty() {
+        return;
+    }
+    let p = blender.precision;
+    let text = selected
+        .iter()
+        .filter_map(|e| world.get::<Transform>(*e))
+        .map(|transform| {
+            let Vec3 { x, y, z } = blender.to_blender(transform.translation);
+            format!("{x:.p$}, {y:.p$}, {z:.p$}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    world.resource_mut::<EguiClipboard>().set_contents(&text);
+}
+
+fn paste_blender_coords(world: &mut World) -> anyhow::Result<()> {
+    world.resource_mut::<Events<PasteBlenderCoords>>().clear();
+    let blender = world.resource::<AppSettings>().blender.clone();
+    let text = world
+        .resource_mut::<EguiClipboard>()
+        .get_contents()
+        .ok_or(anyhow!("nothing to paste from the clipboard"))?;
+    let positions: Vec<_> = parse_coordinates(&text)
+        .map_err(|e| anyhow!(e))?
+        .into_iter()
+        .map(|(pos, _)| blender.from_blender(pos))
+        .collect();
+    let selected = selected_in_order(world);
+    if selected.is_empty() {
+        bail!("no points are selected to paste onto");
+    }
+
+    if let [pos] = positions[..] {
+        // keep the shape of the selection, just moving its centre to the pasted position
+        let centre = selected
+            .iter()
+            .filter_map(|e| world.get::<Transform>(*e))
+            .map(|x| x.translation)
+            .sum::<Vec3>()
+            / selected.len() as f32;
+        for e in selected {
+            if let Some(mut transform) = world.get_mut::<Transform>(e) {
+                transform.translation += pos - centre;
+            }
+        }
+        return Ok(());
+    }
+    if positions.len() != selected.len() {
+        bail!(
+            "pasted {} positions, but {} points are selected",
+            positions.len(),
+            selected.len()
+        );
+    }
+    for (e, pos) in selected.into_iter().zip(positions) {
+        if let Some(mut transform) = world.get_mut::<Transform>(e) {
+            transform.translation = pos;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use super::{
+    create_delete::CreatePoint,
+    link_select_mode::not_link_selecting,
+    select::{pick_nearest_point, LinkPicker, PickMode, Selected},
+};
+use crate::{
+    ui::{settings::AppSettings, viewport::ViewportInfo},
+    util::{get_ray_from_cam, ui_viewport_to_ndc, RaycastFromCam},
+    viewer::{
+        camera::Gizmo2dCam,
+        kcl_model::KCLModelSection,
+        kmp::{
+            checkpoints::CheckpointHeight, components::KmpSelectablePoint, ordering::OrderId, sections::KmpEditMode,
+        },
+    },
+};
+use bevy::prelude::*;
+use bevy_mod_raycast::prelude::*;
+
+pub fn context_menu_plugin(app: &mut App) {
+    app.add_systems(Update, open_context_menu.run_if(not_link_selecting));
+}
+
+// how far the mouse can move between pressing and releasing the right mouse button for it to count as a click,
+// rather than a drag to move the camera
+const CLICK_DISTANCE: f32 = 4.;
+
+/// The menu opened by right clicking in the viewport, offering what can be done with whatever was clicked on. The
+/// menu is open while this resource exists.
+#[derive(Resource)]
+pub struct ViewportContextMenu {
+    /// Where the menu was opened, in UI coordinates
+    pub pos: Vec2,
+    /// The point that was clicked on, if any. This is picked the same way as left clicking picks a point to select.
+    pub point: Option<Entity>,
+    /// The path link that was clicked on, if no point was
+    pub link: Option<Entity>,
+    /// Where the KCL was clicked on and the flag of the KCL there, if it was
+    pub kcl: Option<(Vec3, usize)>,
+    /// The ray from the camera through where was clicked, as checkpoints are placed on a flat plane rather than the KCL
+    pub ray: Option<Ray3d>,
+}
+impl ViewportContextMenu {
+    /// The points the menu acts on: the whole selection if the point clicked on is part of it, otherwise just that
+    /// point
+    pub fn targets(&self, world: &mut World) -> Vec<Entity> {
+        let Some(point) = self.point else {
+            return Vec::new();
+        };
+        if world.get::<Selected>(point).is_none() {
+            return vec![point];
+        }
+        let mut q = world.query_filtered::<(Entity, Option<&OrderId>), With<Selected>>();
+        let mut selected: Vec<_> = q.iter(world).map(|(e, id)| (e, id.map(|x| x.0))).collect();
+        selected.sort_by_key(|x| x.1);
+        selected.into_iter().map(|x| x.0).collect()
+    }
+    /// Where a new point of the section being edited would go, as if Alt clicking where the menu was opened
+    pub fn create_pos(&self, world: &World) -> Option<Vec3> {
+        if *world.resource::<KmpEditMode>() == KmpEditMode::Checkpoints {
+            let ray = self.ray?;
+            let height = world.resource::<CheckpointHeight>().0;
+            let dist = ray.intersect_plane(Vec3::Y * height, InfinitePlane3d::default())?;
+            return Some(ray.get_point(dist));
+        }
+        self.kcl.map(|x| x.0)
+    }
+}
+
+/// Select only these points
+pub fn select_only(world: &mut World, points: &[Entity]) {
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for e in selected {
+        world.entity_mut(e).remove::<Selected>();
+    }
+    for e in points {
+        if let Some(mut entity) = world.get_entity_mut(*e) {
+            entity.insert(Selected);
+        }
+    }
+}
+
+/// Create a point of the section being edited where the menu was opened, linked on from the selected points as if
+/// Alt clicking there
+pub fn create_point_at_menu(world: &mut World, menu: &ViewportContextMenu) {
+    if let Some(position) = menu.create_pos(world) {
+        world.send_event(CreatePoint { position });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn open_context_menu(
+    mut commands: Commands,
+    viewport_info: Res<ViewportInfo>,
+    q_window: Query<&Window>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    mut raycast: Raycast,
+    q_kmp_section: Query<(EntityRef, &Transform, &Visibility), With<KmpSelectablePoint>>,
+    q_kcl: Query<&KCLModelSection>,
+    (pick_mode, settings, mode): (Res<PickMode>, Res<AppSettings>, Res<KmpEditMode>),
+    link_picker: LinkPicker,
+    mut pressed_at: Local<Option<Vec2>>,
+) {
+    let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        *pressed_at = (viewport_info.mouse_in_viewport && !viewport_info.mouse_on_overlayed_ui).then_some(mouse_pos);
+    }
+    if !mouse_buttons.just_released(MouseButton::Right) {
+        return;
+    }
+    let Some(pressed_at) = pressed_at.take() else {
+        return;
+    };
+    // otherwise the right mouse button was dragged to move the camera
+    if pressed_at.distance(mouse_pos) > CLICK_DISTANCE {
+        return;
+    }
+    let Some(cam) = q_camera.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let mouse_pos_ndc = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+    let ray = get_ray_from_cam(cam, mouse_pos_ndc);
+
+    let filter = |e: Entity| {
+        (pick_mode.hits_points() && q_kmp_section.contains(e)) || (pick_mode.hits_kcl() && q_kcl.contains(e))
+    };
+    let intersections = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+        .filter(&filter)
+        .cast();
+
+    // the point is picked the same way as left clicking would, so that right clicking a point always opens the menu
+    // for the point which clicking would select
+    let point = if pick_mode.hits_points() {
+        let in_section = |e: Entity| q_kmp_section.get(e).is_ok_and(|x| mode.contains_entity(&x.0));
+        let hit = intersections
+            .iter()
+            .find(|x| q_kmp_section.contains(x.0))
+            .map(|x| (x.0, in_section(x.0)));
+        let points = q_kmp_section
+            .iter()
+            .filter(|x| *x.2 == Visibility::Visible)
+            .map(|(entity, transform, _)| (entity.id(), transform.translation, mode.contains_entity(&entity)));
+        pick_nearest_point(
+            cam,
+            viewport_info.viewport_rect,
+            mouse_pos,
+            settings.pick.radius,
+            hit,
+            points,
+        )
+    } else {
+        None
+    };
+    let link = if pick_mode.hits_points() && point.is_none() {
+        link_picker.pick(cam, viewport_info.viewport_rect, mouse_pos)
+    } else {
+        None
+    };
+    let kcl = intersections
+        .iter()
+        .find_map(|(e, hit)| q_kcl.get(*e).ok().map(|x| (hit.position(), x.0)));
+
+    commands.insert_resource(ViewportContextMenu {
+        pos: mouse_pos,
+        point,
+        link,
+        kcl,
+        ray,
+    });
+}
@@ -0,0 +1,201 @@
+use super::select::Selected;
+use crate::viewer::kmp::{
+    checkpoints::{get_both_cp_nodes, GetSelectedCheckpoints},
+    components::{
+        AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
+        MaxConnectedPath, Object, RespawnPoint, RoutePoint, Spawn, Spawner, StartPoint,
+    },
+    ordering::RefreshOrdering,
+    path::{is_checkpoint, KmpPathNode, RecalcPaths},
+    sections::KmpEditMode,
+};
+use bevy::{
+    ecs::{entity::EntityHashSet, system::SystemState},
+    prelude::*,
+};
+
+pub fn create_at_coordinates_plugin(app: &mut App) {
+    app.add_event::<ApplyCreateAtCoordinates>().add_systems(
+        Update,
+        apply_create_at_coordinates.run_if(on_event::<ApplyCreateAtCoordinates>()),
+    );
+}
+
+/// A dialog for creating points in the current section at exactly typed (or pasted) positions, for transcribing
+/// positions from other tools or spreadsheets. The dialog is open while this resource exists.
+#[derive(Resource)]
+pub struct CreateAtCoordinates {
+    pub mode: KmpEditMode,
+    pub pos: Vec3,
+    pub rot: Vec3,
+    /// Create a point for each line of [`CreateAtCoordinates::text`] instead of a single point
+    pub multiple: bool,
+    pub text: String,
+    /// For paths, link each new point on from the one before it
+    pub link: bool,
+    /// Why the text couldn't be read, if it couldn't
+    pub error: Option<String>,
+}
+impl CreateAtCoordinates {
+    pub fn new(mode: KmpEditMode) -> Self {
+        Self {
+            mode,
+            pos: Vec3::ZERO,
+            rot: Vec3::ZERO,
+            multiple: false,
+            text: String::new(),
+            link: true,
+            error: None,
+        }
+    }
+    /// The position and rotation of each point to create
+    pub fn points(&self) -> Result<Vec<(Vec3, Vec3)>, String> {
+        if !self.multiple {
+            return Ok(vec![(self.pos, self.rot)]);
+        }
+        parse_coordinates(&self.text)
+    }
+}
+
+/// Whether a section's points are linked together into paths
+pub fn is_path_section(mode: KmpEditMode) -> bool {
+    matches!(
+        mode,
+        KmpEditMode::EnemyPaths | KmpEditMode::ItemPaths | KmpEditMode::Checkpoints | KmpEditMode::Routes
+    )
+}
+
+/// Send this event to create the points in the [`CreateAtCoordinates`] dialog
+#[derive(Event, Default)]
+pub struct ApplyCreateAtCoordinates;
+
+/// Read a position, and optionally a rotation, from each line of the text. The numbers can be separated by commas,
+/// semicolons, tabs or spaces, and lines without any numbers in them (like column headers) are skipped.
+pub fn parse_coordinates(text: &str) -> Result<Vec<(Vec3, Vec3)>, String> {
+    let mut points = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if !line.chars().any(|x| x.is_ascii_digit()) {
+            continue;
+        }
+        let values = line
+            .split(|x: char| x == ',' || x == ';' || x.is_whitespace())
+            .filter(|x| !x.is_empty())
+            .map(|x| x.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Line {}: {e}", i + 1))?;
+        match values[..] {
+            [x, y, z] => points.push((Vec3::new(x, y, z), Vec3::ZERO)),
+            [x, y, z, rx, ry, rz] => points.push((Vec3::new(x, y, z), Vec3::new(rx, ry, rz))),
+            _ => {
+                return Err(format!(
+                    "Line {}: expected 3 numbers (X Y Z) or 6 numbers (X Y Z and rotation), found {}",
+                    i + 1,
+                    values.len()
+                ))
+            }
+        }
+    }
+    if points.is_empty() {
+        return Err("No coordinates found".into());
+    }
+    Ok(points)
+}
+
+fn create_points<T: Component + Spawn + Default + Clone>(world: &mut World, points: &[(Vec3, Vec3)]) -> Vec<Entity> {
+    points
+        .iter()
+        .map(|(pos, rot)| Spawner::<T>::builder().pos(*pos).rot(*rot).build().spawn(world))
+        .collect()
+}
+
+fn create_path<T: Component + Spawn + Default + Clone + MaxConnectedPath>(
+    world: &mut World,
+    points: &[(Vec3, Vec3)],
+    link: bool,
+) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    // the first point carries on from whatever is selected, like when alt clicking
+    let mut prev_nodes: EntityHashSet = if !link {
+        EntityHashSet::default()
+    } else if is_checkpoint::<T>() {
+        SystemState::<GetSelectedCheckpoints>::new(world)
+            .get_mut(world)
+            .get_entities()
+    } else {
+        world
+            .query_filtered::<Entity, (With<T>, With<Selected>)>()
+            .iter(world)
+            .collect()
+    };
+    // start a new path if the selected points can't be linked on from any more
+    if prev_nodes
+        .iter()
+        .any(|e| world.get::<KmpPathNode>(*e).is_some_and(|x| x.at_max_next()))
+    {
+        prev_nodes.clear();
+    }
+    for (pos, rot) in points.iter() {
+        let e = Spawner::<T>::builder()
+            .pos(*pos)
+            .rot(*rot)
+            .prev_nodes(prev_nodes.clone())
+            .max(T::MAX_CONNECTED)
+            .build()
+            .spawn(world);
+        if link {
+            // checkpoints are linked on from their left point
+            let prev_e = if is_checkpoint::<T>() {
+                get_both_cp_nodes(world, e).0
+            } else {
+                e
+            };
+            prev_nodes = EntityHashSet::from_iter([prev_e]);
+        }
+        entities.push(e);
+    }
+    world.send_event_default::<RecalcPaths>();
+    entities
+}
+
+fn apply_create_at_coordinates(world: &mut World) {
+    world.resource_mut::<Events<ApplyCreateAtCoordinates>>().clear();
+    let Some(dialog) = world.get_resource::<CreateAtCoordinates>() else {
+        return;
+    };
+    let (mode, link) = (dialog.mode, dialog.link);
+    let points = match dialog.points() {
+        Ok(points) => points,
+        Err(error) => {
+            world.resource_mut::<CreateAtCoordinates>().error = Some(error);
+            return;
+        }
+    };
+    world.resource_mut::<CreateAtCoordinates>().error = None;
+
+    let created = match mode {
+        KmpEditMode::StartPoints => create_points::<StartPoint>(world, &points),
+        KmpEditMode::EnemyPaths => create_path::<EnemyPathPoint>(world, &points, link),
+        KmpEditMode::ItemPaths => create_path::<ItemPathPoint>(world, &points, link),
+        KmpEditMode::Checkpoints => create_path::<Checkpoint>(world, &points, link),
+        KmpEditMode::RespawnPoints => create_points::<RespawnPoint>(world, &points),
+        KmpEditMode::Objects => create_points::<Object>(world, &points),
+        KmpEditMode::Routes => create_path::<RoutePoint>(world, &points, link),
+        KmpEditMode::Areas => create_points::<AreaPoint>(world, &points),
+        KmpEditMode::Cameras => create_points::<KmpCamera>(world, &points),
+        KmpEditMode::CannonPoints => create_points::<CannonPoint>(world, &points),
+        KmpEditMode::BattleFinishPoints => create_points::<BattleFinishPoint>(world, &points),
+        KmpEditMode::TrackInfo => Vec::new(),
+    };
+    world.send_event(RefreshOrdering);
+
+    // select the new points so they can be checked over, or moved together
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for e in selected {
+        world.entity_mut(e).remove::<Selected>();
+    }
+    for e in created {
+        world.entity_mut(e).insert(Selected);
+    }
+    // the new points might be in a different section to the one being viewed, which would leave them hidden
+    *world.resource_mut::<KmpEditMode>() = mode;
+}
@@ -1,24 +1,45 @@
-use super::select::{SelectSet, Selected};
+use super::{
+    link_select_mode::not_link_selecting,
+    mirror_selection::{on_mirror_plane, MirrorPlane},
+    select::{LinkPicker, PickMode, SelectSet, Selected},
+    tweak::tweak_interaction,
+};
 use crate::{
-    ui::viewport::ViewportInfo,
+    ui::{
+        keybinds::{KeyAction, Keybinds, ModifiersPressed},
+        settings::AppSettings,
+        util::get_euler_rot,
+        viewport::ViewportInfo,
+    },
     util::{get_ray_from_cam, try_despawn, ui_viewport_to_ndc, RaycastFromCam},
     viewer::{
         camera::Gizmo2dCam,
         kcl_model::KCLModelSection,
         kmp::{
-            checkpoints::{CheckpointHeight, GetSelectedCheckpoints},
+            checkpoints::{
+                checkpoint_spawner, CheckpointHeight, CheckpointLeft, CheckpointRespawnLink, CheckpointRight,
+                GetSelectedCheckpoints,
+            },
             components::{
                 AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
-                KmpSelectablePoint, MaxConnectedPath, Object, RespawnPoint, RoutePoint, Spawn, Spawner, StartPoint,
+                KmpSelectablePoint, MaxConnectedPath, Object, RespawnPoint, RoutePoint, RouteSettings, Spawn, Spawner,
+                StartPoint,
             },
             ordering::RefreshOrdering,
             path::{is_checkpoint, KmpPathNode, RecalcPaths},
+            path_tools::InsertPointOnLink,
+            routes::{RouteLink, RouteStartBundle},
             sections::KmpEditMode,
         },
     },
 };
-use bevy::{ecs::entity::EntityHashSet, prelude::*};
+use bevy::{
+    ecs::entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+};
 use bevy_mod_raycast::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
 
 #[derive(SystemSet, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct DeleteSet;
@@ -26,10 +47,11 @@ pub struct DeleteSet;
 pub fn create_delete_plugin(app: &mut App) {
     app.add_event::<CreatePoint>()
         .add_event::<JustCreatedPoint>()
+        .add_event::<DuplicateSelected>()
         .add_systems(
             Update,
             (
-                alt_click_create_point,
+                alt_click_create_point.run_if(not_link_selecting),
                 (
                     create_point::<StartPoint>,
                     create_path::<EnemyPathPoint>,
@@ -47,7 +69,14 @@ pub fn create_delete_plugin(app: &mut App) {
                 .chain()
                 .before(SelectSet),
         )
-        .add_systems(Update, delete_point.in_set(DeleteSet).after(SelectSet));
+        .add_systems(Update, delete_point.in_set(DeleteSet).after(SelectSet))
+        .add_systems(
+            Update,
+            (alt_drag_duplicate, duplicate_selected)
+                .chain()
+                .after(SelectSet)
+                .before(tweak_interaction),
+        );
 }
 
 #[derive(Event, Default)]
@@ -58,6 +87,72 @@ pub struct CreatePoint {
 #[derive(Event)]
 pub struct JustCreatedPoint(pub Entity);
 
+/// Send this event to make a copy of each selected point, linked to the same previous points, and select the
+/// copies instead
+#[derive(Event, Default)]
+pub struct DuplicateSelected {
+    /// The point being Alt dragged, so that its copy can carry on being dragged instead
+    pub dragged: Option<Entity>,
+    /// What to do with the routes of copied cameras, objects and areas. If this isn't given and any of them have a
+    /// route, the [`RouteDuplicationPrompt`] is opened to ask.
+    pub routes: Option<RouteDuplication>,
+    /// If given, the copies are mirrored across this plane. Points on the plane are their own mirror images, so
+    /// aren't copied, and the copies are linked to them.
+    pub mirror: Option<MirrorPlane>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RouteDuplication {
+    /// The copies follow the same route as the originals
+    Share,
+    /// Each route is copied, and the copies follow the new route
+    Copy,
+}
+
+/// Asks whether the copies of routed points should share their routes or get copies of them. The prompt is open
+/// while this resource exists.
+#[derive(Resource)]
+pub struct RouteDuplicationPrompt {
+    /// How many of the selected points have a route
+    pub routed: usize,
+}
+
+/// How the height of a new path point is chosen when it is linked on from previous points, so that paths over
+/// bumpy ground can have clean, gradual slopes
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PathHeightSnapSettings {
+    pub mode: PathHeightSnap,
+    /// For [`PathHeightSnap::Smoothed`], how much of the height difference to the ground a new point follows,
+    /// from 0 (same height as the previous point) to 1 (on the ground)
+    pub smoothing: f32,
+}
+impl Default for PathHeightSnapSettings {
+    fn default() -> Self {
+        Self {
+            mode: PathHeightSnap::default(),
+            smoothing: 0.25,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Display, EnumIter)]
+pub enum PathHeightSnap {
+    #[default]
+    Ground,
+    #[strum(serialize = "Previous Point")]
+    PreviousPoint,
+    Smoothed,
+}
+impl PathHeightSnapSettings {
+    fn snap_height(&self, ground: f32, prev: f32) -> f32 {
+        match self.mode {
+            PathHeightSnap::Ground => ground,
+            PathHeightSnap::PreviousPoint => prev,
+            PathHeightSnap::Smoothed => prev + (ground - prev) * self.smoothing.clamp(0., 1.),
+        }
+    }
+}
+
 // responsible for consuming 'create point' events and creating the relevant point depending on what edit mode we are in
 fn create_point<T: Component + Spawn + Default + Clone>(
     mut commands: Commands,
@@ -85,7 +180,9 @@ fn create_path<T: Component + Spawn + Default + Clone + MaxConnectedPath>(
     mode: Res<KmpEditMode>,
     q_selected_pt: Query<Entity, (With<T>, With<Selected>)>,
     q_kmp_path_node: Query<&KmpPathNode>,
+    q_transform: Query<&Transform>,
     mut q_cp: GetSelectedCheckpoints,
+    settings: Res<AppSettings>,
     mut ev_create_point: EventReader<CreatePoint>,
     mut ev_recalc_paths: EventWriter<RecalcPaths>,
     mut ev_just_created_point: EventWriter<JustCreatedPoint>,
@@ -96,13 +193,20 @@ fn create_path<T: Component + Spawn + Default + Clone + MaxConnectedPath>(
     let Some(create_pt) = ev_create_point.read().next() else {
         return;
     };
-    let pos = create_pt.position;
+    let mut pos = create_pt.position;
     let prev_nodes: EntityHashSet = if is_checkpoint::<T>() {
         q_cp.get().into_iter().map(|x| x.0).collect()
     } else {
         q_selected_pt.iter().collect()
     };
 
+    // checkpoints don't have a height, so only snap the other paths
+    if !is_checkpoint::<T>() && !prev_nodes.is_empty() {
+        let prev_heights: Vec<_> = q_transform.iter_many(&prev_nodes).map(|x| x.translation.y).collect();
+        let prev_height = prev_heights.iter().sum::<f32>() / prev_heights.len() as f32;
+        pos.y = settings.path_height_snap.snap_height(pos.y, prev_height);
+    }
+
     // if any prev points are at max linking capacity, then return
     if q_kmp_path_node.iter_many(&prev_nodes).any(|x| x.at_max_next()) {
         return;
@@ -137,6 +241,9 @@ fn alt_click_create_point(
     q_kmp_pt: Query<(), With<KmpSelectablePoint>>,
     q_kcl: Query<(), With<KCLModelSection>>,
     mut ev_create_pt: EventWriter<CreatePoint>,
+    pick_mode: Res<PickMode>,
+    link_picker: LinkPicker,
+    mut ev_insert_on_link: EventWriter<InsertPointOnLink>,
 ) {
     if *mode == KmpEditMode::TrackInfo {
         return;
@@ -145,7 +252,7 @@ fn alt_click_create_point(
         return;
     }
     // only run the function if the alt key is held and the mouse has just been clicked
-    if !keys.pressed(KeyCode::AltLeft) || !mouse_buttons.just_pressed(MouseButton::Left) {
+    if !keys.alt_pressed() || !mouse_buttons.just_pressed(MouseButton::Left) {
         return;
     }
 
@@ -157,12 +264,25 @@ fn alt_click_create_point(
     let cam = q_camera.iter().find(|cam| cam.0.is_active).unwrap();
 
     let ndc_mouse_pos = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
-    let intersections = RaycastFromCam::new(cam, ndc_mouse_pos, &mut raycast).cast();
+    // if points can't be clicked on, look straight through them to the KCL
+    let kcl_only = |e: Entity| q_kcl.contains(e);
+    let mut raycast_from_cam = RaycastFromCam::new(cam, ndc_mouse_pos, &mut raycast);
+    if !pick_mode.hits_points() {
+        raycast_from_cam = raycast_from_cam.filter(&kcl_only);
+    }
+    let intersections = raycast_from_cam.cast();
 
     // if we are clicking on a kmp point then return and don't create another point
     if intersections.iter().any(|e| q_kmp_pt.contains(e.0)) {
         return;
     };
+    // clicking on a link splits it with a new point, rather than adding one on the end of the selected points
+    if pick_mode.hits_points() {
+        if let Some(link) = link_picker.pick(cam, viewport_info.viewport_rect, mouse_pos) {
+            ev_insert_on_link.send(InsertPointOnLink(link));
+            return;
+        }
+    }
 
     let mouse_3d_pos = if *mode == KmpEditMode::Checkpoints {
         let Some(ray) = get_ray_from_cam(cam, ndc_mouse_pos) else {
@@ -173,6 +293,9 @@ fn alt_click_create_point(
         };
         ray.get_point(dist)
     } else {
+        if !pick_mode.hits_kcl() {
+            return;
+        }
         let Some(kcl_intersection) = intersections.iter().find(|e| q_kcl.contains(e.0)) else {
             return;
         };
@@ -184,6 +307,7 @@ fn alt_click_create_point(
 
 fn delete_point(
     keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
     mut q_selected: Query<Entity, With<Selected>>,
     mut commands: Commands,
     viewport_info: Res<ViewportInfo>,
@@ -192,7 +316,7 @@ fn delete_point(
     if !viewport_info.mouse_in_viewport && !viewport_info.mouse_in_table {
         return;
     }
-    if !keys.just_pressed(KeyCode::Backspace) && !keys.just_pressed(KeyCode::Delete) {
+    if !keybinds.just_pressed(KeyAction::Delete, &keys) {
         return;
     }
 
@@ -201,3 +325,324 @@ fn delete_point(
     }
     ev_refresh_ordering.send_default();
 }
+
+// alt clicking on a selected point duplicates the selection, and drags the copies away from the originals. Alt
+// clicking on a point which isn't selected links the selection to it instead (see `get_pt_to_link`)
+fn alt_drag_duplicate(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    viewport_info: Res<ViewportInfo>,
+    mut raycast: Raycast,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_window: Query<&Window>,
+    q_points: Query<(), With<KmpSelectablePoint>>,
+    q_selected: Query<(), With<Selected>>,
+    pick_mode: Res<PickMode>,
+    mut ev_duplicate: EventWriter<DuplicateSelected>,
+) {
+    if !viewport_info.mouse_in_viewport || viewport_info.mouse_on_overlayed_ui || !pick_mode.hits_points() {
+        return;
+    }
+    if !keys.alt_pressed() || !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    let Some(cam) = q_camera.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let ndc_mouse_pos = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+    let intersections = RaycastFromCam::new(cam, ndc_mouse_pos, &mut raycast)
+        .filter(&|e| q_points.contains(e))
+        .cast();
+    let Some(dragged) = intersections.first().map(|x| x.0).filter(|e| q_selected.contains(*e)) else {
+        return;
+    };
+    ev_duplicate.send(DuplicateSelected {
+        dragged: Some(dragged),
+        routes: None,
+        mirror: None,
+    });
+}
+
+// order path points so that each comes after any of its previous points which are also being copied,
+// so the copies can be linked to the copies of their previous points as soon as they are spawned
+fn path_order(world: &World, points: Vec<Entity>) -> Vec<Entity> {
+    let mut remaining = points;
+    let mut ordered = Vec::new();
+    while !remaining.is_empty() {
+        let (ready, not_ready): (Vec<_>, Vec<_>) = remaining.iter().partition(|e| {
+            world
+                .get::<KmpPathNode>(**e)
+                .map(|x| x.prev_nodes.iter().all(|prev| !remaining.contains(prev)))
+                .unwrap_or(true)
+        });
+        // if the points loop round, just start from wherever
+        if ready.is_empty() {
+            ordered.extend(not_ready);
+            break;
+        }
+        ordered.extend(ready);
+        remaining = not_ready;
+    }
+    ordered
+}
+
+// the previous points of a point, swapped for their copies if they have been copied. When mirroring, points which
+// haven't been copied are on the other side of the plane to the copies, so are only kept if they are on the plane.
+fn copied_prev_nodes(
+    world: &World,
+    e: Entity,
+    copies: &EntityHashMap<Entity>,
+    mirror: Option<&MirrorPlane>,
+) -> EntityHashSet {
+    let Some(node) = world.get::<KmpPathNode>(e) else {
+        return EntityHashSet::default();
+    };
+    node.prev_nodes
+        .iter()
+        .filter_map(|prev| match copies.get(prev) {
+            Some(copy) => Some(*copy),
+            None if mirror.is_some_and(|plane| !on_mirror_plane(world, *prev, plane)) => None,
+            None => Some(*prev),
+        })
+        // don't link on from points which already have as many next points as they can
+        .filter(|prev| world.get::<KmpPathNode>(*prev).is_some_and(|x| !x.at_max_next()))
+        .collect()
+}
+
+// copy the route starting at this point, with its settings, returning the start of the copy
+fn copy_route(world: &mut World, start: Entity) -> Entity {
+    let mut points = Vec::new();
+    let mut cur_e = Some(start);
+    while let Some(e) = cur_e {
+        let entity = world.entity(e);
+        let (Some(route_pt), Some(transform)) = (entity.get::<RoutePoint>(), entity.get::<Transform>()) else {
+            break;
+        };
+        points.push((route_pt.clone(), *transform));
+        cur_e = entity
+            .get::<KmpPathNode>()
+            .and_then(|x| x.next_nodes.iter().next().copied())
+            .filter(|e| *e != start);
+    }
+    let route_settings = world.get::<RouteSettings>(start).cloned().unwrap_or_default();
+
+    let mut copy_start = None;
+    let mut prev_e: Option<Entity> = None;
+    for (route_pt, transform) in points {
+        let e = Spawner::builder()
+            .component(route_pt)
+            .pos(transform.translation)
+            .rot(get_euler_rot(&transform))
+            .prev_nodes(prev_e.into_iter().collect::<EntityHashSet>())
+            .max(1)
+            .build()
+            .spawn(world);
+        if prev_e.is_none() {
+            world.entity_mut(e).insert(RouteStartBundle {
+                route_settings: route_settings.clone(),
+                ..default()
+            });
+            copy_start = Some(e);
+        }
+        prev_e = Some(e);
+    }
+    copy_start.unwrap_or(start)
+}
+
+fn duplicate_points<T: Component + Spawn + Default + Clone>(
+    world: &mut World,
+    selected: &[Entity],
+    copies: &mut EntityHashMap<Entity>,
+    route_copies: &EntityHashMap<Entity>,
+    mirror: Option<&MirrorPlane>,
+) {
+    let points: Vec<_> = selected
+        .iter()
+        .copied()
+        .filter(|e| world.get::<T>(*e).is_some())
+        .collect();
+    for e in path_order(world, points) {
+        if mirror.is_some_and(|plane| on_mirror_plane(world, e, plane)) {
+            copies.insert(e, e);
+            continue;
+        }
+        let entity = world.entity(e);
+        let (Some(component), Some(transform)) = (entity.get::<T>(), entity.get::<Transform>()) else {
+            continue;
+        };
+        let transform = mirror.map_or(*transform, |plane| plane.mirror_transform(transform));
+        let path_node = entity.get::<KmpPathNode>();
+        let spawner = Spawner::<T>::builder()
+            .pos(transform.translation)
+            .rot(get_euler_rot(&transform))
+            .component(component.clone())
+            .maybe_route(
+                entity
+                    .get::<RouteLink>()
+                    .map(|x| route_copies.get(&x.0).copied().unwrap_or(x.0)),
+            )
+            .maybe_max(path_node.map(|x| x.max))
+            .maybe_prev_nodes(path_node.map(|_| copied_prev_nodes(world, e, copies, mirror)))
+            .build();
+        let copy = spawner.spawn(world);
+        copies.insert(e, copy);
+    }
+}
+
+fn duplicate_checkpoints(
+    world: &mut World,
+    selected: &[Entity],
+    copies: &mut EntityHashMap<Entity>,
+    mirror: Option<&MirrorPlane>,
+) {
+    // a checkpoint is copied whether its left or right point is selected
+    let mut lefts: Vec<_> = selected
+        .iter()
+        .filter_map(|e| match world.get::<CheckpointRight>(*e) {
+            Some(right) => Some(right.left),
+            None => world.get::<Checkpoint>(*e).map(|_| *e),
+        })
+        .collect();
+    lefts.sort();
+    lefts.dedup();
+    let height = world.resource::<CheckpointHeight>().0;
+    for left in path_order(world, lefts) {
+        let right = world.get::<CheckpointLeft>(left).unwrap().right;
+        if mirror.is_some_and(|plane| on_mirror_plane(world, left, plane)) {
+            copies.insert(left, left);
+            copies.insert(right, right);
+            continue;
+        }
+        let cp = world.get::<Checkpoint>(left).unwrap().clone();
+        let (left_pos, right_pos) = (
+            world.get::<Transform>(left).unwrap().translation,
+            world.get::<Transform>(right).unwrap().translation,
+        );
+        // mirroring swaps which side of the track each end is on
+        let pos = match mirror {
+            Some(plane) => (plane.mirror_pos(right_pos).xz(), plane.mirror_pos(left_pos).xz()),
+            None => (left_pos.xz(), right_pos.xz()),
+        };
+        let respawn_link = world.get::<CheckpointRespawnLink>(left).map(|x| x.0);
+        let prev_lefts = copied_prev_nodes(world, left, copies, mirror);
+        let (copy_left, copy_right) = checkpoint_spawner().cp(cp).pos(pos).height(height).world(world).call();
+        for prev_left in prev_lefts {
+            KmpPathNode::link_nodes(prev_left, copy_left, world);
+            let prev_right = world.get::<CheckpointLeft>(prev_left).unwrap().right;
+            KmpPathNode::link_nodes(prev_right, copy_right, world);
+        }
+        if let Some(respawn_e) = respawn_link {
+            world.entity_mut(copy_left).insert(CheckpointRespawnLink(respawn_e));
+        }
+        copies.insert(left, copy_left);
+        copies.insert(right, copy_right);
+    }
+}
+
+// link mirrored copies on to the points after the originals which are on the plane, as they will have been left out
+// when the points after them were linked to their previous points
+fn link_mirrored_next_nodes(world: &mut World, copies: &EntityHashMap<Entity>, plane: &MirrorPlane) {
+    for (e, copy) in copies.iter().filter(|(e, copy)| e != copy) {
+        let Some(node) = world.get::<KmpPathNode>(*e) else {
+            continue;
+        };
+        let next_nodes: Vec<_> = node
+            .next_nodes
+            .iter()
+            .copied()
+            .filter(|next| match copies.get(next) {
+                Some(next_copy) => next_copy == next,
+                None => on_mirror_plane(world, *next, plane),
+            })
+            .collect();
+        for next in next_nodes {
+            KmpPathNode::link_nodes(*copy, next, world);
+            // the right points of checkpoints are linked alongside the left points
+            let rights = world
+                .get::<CheckpointLeft>(*copy)
+                .zip(world.get::<CheckpointLeft>(next))
+                .map(|(copy, next)| (copy.right, next.right));
+            if let Some((copy_right, next_right)) = rights {
+                KmpPathNode::link_nodes(copy_right, next_right, world);
+            }
+        }
+    }
+}
+
+fn duplicate_selected(world: &mut World) {
+    let Some((dragged, routes, mirror)) = world
+        .resource_mut::<Events<DuplicateSelected>>()
+        .drain()
+        .last()
+        .map(|x| (x.dragged, x.routes, x.mirror))
+    else {
+        return;
+    };
+    world.remove_resource::<RouteDuplicationPrompt>();
+    let selected: Vec<_> = world
+        .query_filtered::<Entity, (With<Selected>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .collect();
+    if selected.is_empty() {
+        return;
+    }
+
+    let routes_used: EntityHashSet = selected
+        .iter()
+        .filter_map(|e| world.get::<RouteLink>(*e).map(|x| x.0))
+        .collect();
+    let routes = match routes {
+        Some(routes) => routes,
+        // there's no time to ask while Alt dragging, so the copies just share the routes
+        None if routes_used.is_empty() || dragged.is_some() => RouteDuplication::Share,
+        None => {
+            let routed = selected
+                .iter()
+                .filter(|e| world.get::<RouteLink>(**e).is_some())
+                .count();
+            world.insert_resource(RouteDuplicationPrompt { routed });
+            return;
+        }
+    };
+    let mut route_copies = EntityHashMap::default();
+    if routes == RouteDuplication::Copy {
+        for route_start in routes_used {
+            route_copies.insert(route_start, copy_route(world, route_start));
+        }
+    }
+
+    let mut copies = EntityHashMap::default();
+    let route_copies = &route_copies;
+    let mirror = mirror.as_ref();
+    duplicate_points::<StartPoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<EnemyPathPoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<ItemPathPoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_checkpoints(world, &selected, &mut copies, mirror);
+    duplicate_points::<RespawnPoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<Object>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<RoutePoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<AreaPoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<KmpCamera>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<CannonPoint>(world, &selected, &mut copies, route_copies, mirror);
+    duplicate_points::<BattleFinishPoint>(world, &selected, &mut copies, route_copies, mirror);
+    if let Some(plane) = mirror {
+        link_mirrored_next_nodes(world, &copies, plane);
+    }
+    world.send_event_default::<RecalcPaths>();
+    world.send_event(RefreshOrdering);
+
+    for e in selected.iter() {
+        world.entity_mut(*e).remove::<Selected>();
+        if let Some(copy) = copies.get(e) {
+            world.entity_mut(*copy).insert(Selected);
+        }
+    }
+    // the tweak system picks up the newly created point to drag, as the copies can't be clicked on until their
+    // transforms have been propagated
+    if let Some(copy) = dragged.and_then(|e| copies.get(&e)) {
+        world.send_event(JustCreatedPoint(*copy));
+    }
+}
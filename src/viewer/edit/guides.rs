@@ -0,0 +1,110 @@
+use crate::{ui::settings::AppSettings, util::ui_viewport_to_world, viewer::camera::CameraMode};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub fn guides_plugin(app: &mut App) {
+    app.init_resource::<Rulers>();
+}
+
+/// How close, in pixels, a dragged point has to get to a guide to snap onto it
+pub const GUIDE_SNAP_DISTANCE: f32 = 8.;
+
+/// Rulers along the edges of the viewport in the top down camera, which guides can be dragged out of
+#[derive(Resource)]
+pub struct Rulers {
+    pub shown: bool,
+    /// Whether points dragged near a guide snap onto it
+    pub snap: bool,
+}
+impl Default for Rulers {
+    fn default() -> Self {
+        Self {
+            shown: false,
+            snap: true,
+        }
+    }
+}
+impl Rulers {
+    /// Rulers and guides only make sense looking straight down, so they are hidden in the other camera modes
+    pub fn active(&self, settings: &AppSettings) -> bool {
+        self.shown && settings.camera.mode == CameraMode::TopDown
+    }
+}
+
+/// A line across the track at a fixed X or Z, for lining points up against in the top down camera
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Guide {
+    pub axis: GuideAxis,
+    pub value: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum GuideAxis {
+    /// A line along Z at a fixed X, shown vertically in the viewport
+    X,
+    /// A line along X at a fixed Z, shown horizontally in the viewport
+    Z,
+}
+
+/// How the world X and Z axes line up with the viewport in the top down camera
+#[derive(Clone, Copy)]
+pub struct TopDownMapping {
+    /// The world position of the top left corner of the viewport
+    origin: Vec3,
+    /// How far across X and down Z the world moves for each pixel across and down the viewport
+    per_px: Vec2,
+    viewport_min: Vec2,
+}
+impl TopDownMapping {
+    pub fn new(cam: (&Camera, &GlobalTransform), viewport_rect: Rect) -> Option<Self> {
+        let origin = ui_viewport_to_world(cam, viewport_rect, viewport_rect.min)?;
+        let across = ui_viewport_to_world(cam, viewport_rect, viewport_rect.min + Vec2::X)?;
+        let down = ui_viewport_to_world(cam, viewport_rect, viewport_rect.min + Vec2::Y)?;
+        let per_px = Vec2::new(across.x - origin.x, down.z - origin.z);
+        if per_px.x == 0. || per_px.y == 0. {
+            return None;
+        }
+        Some(Self {
+            origin,
+            per_px,
+            viewport_min: viewport_rect.min,
+        })
+    }
+    /// The world X under a horizontal position in the viewport
+    pub fn world_x(&self, ui_x: f32) -> f32 {
+        self.origin.x + (ui_x - self.viewport_min.x) * self.per_px.x
+    }
+    /// The world Z under a vertical position in the viewport
+    pub fn world_z(&self, ui_y: f32) -> f32 {
+        self.origin.z + (ui_y - self.viewport_min.y) * self.per_px.y
+    }
+    pub fn ui_x(&self, world_x: f32) -> f32 {
+        self.viewport_min.x + (world_x - self.origin.x) / self.per_px.x
+    }
+    pub fn ui_y(&self, world_z: f32) -> f32 {
+        self.viewport_min.y + (world_z - self.origin.z) / self.per_px.y
+    }
+    /// How many world units one pixel covers
+    pub fn units_per_px(&self) -> f32 {
+        self.per_px.x.abs()
+    }
+}
+
+/// Move the position onto the nearest X guide and the nearest Z guide within `max_distance` of it
+pub fn snap_to_guides(mut pos: Vec3, guides: &[Guide], max_distance: f32) -> Vec3 {
+    let nearest = |axis: GuideAxis, value: f32| {
+        guides
+            .iter()
+            .filter(|x| x.axis == axis)
+            .map(|x| x.value)
+            .filter(|x| (x - value).abs() <= max_distance)
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+    };
+    if let Some(x) = nearest(GuideAxis::X, pos.x) {
+        pos.x = x;
+    }
+    if let Some(z) = nearest(GuideAxis::Z, pos.z) {
+        pos.z = z;
+    }
+    pos
+}
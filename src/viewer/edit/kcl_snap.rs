@@ -0,0 +1,68 @@
+use super::{
+    select::Selected,
+    tweak::{SnapTo, Tweakable},
+};
+use crate::viewer::{
+    kcl_model::KCLModelSection,
+    kmp::kcl_check::{TRIGGER_FLAGS, WALL_FLAGS},
+};
+use bevy::prelude::*;
+use bevy_mod_raycast::prelude::*;
+
+pub fn kcl_snap_plugin(app: &mut App) {
+    app.add_event::<DropSelectionToKcl>()
+        .add_systems(Update, drop_selection_to_kcl.run_if(on_event::<DropSelectionToKcl>()));
+}
+
+/// Send this event to move every selected point straight down onto the KCL below it
+#[derive(Event, Default)]
+pub struct DropSelectionToKcl {
+    /// Also tilt each point so that its up direction matches the surface it lands on, keeping the way it faces,
+    /// which is useful for respawn points and start points on banked roads
+    pub align_to_surface: bool,
+}
+
+// start the ray slightly above the point so points sitting just under the road still land on it
+const RAY_START_OFFSET: f32 = 10.;
+
+fn drop_selection_to_kcl(
+    mut ev_drop: EventReader<DropSelectionToKcl>,
+    mut q_selected: Query<(&mut Transform, &Tweakable), With<Selected>>,
+    q_kcl: Query<&KCLModelSection>,
+    mut raycast: Raycast,
+) {
+    let Some(align_to_surface) = ev_drop.read().last().map(|x| x.align_to_surface) else {
+        return;
+    };
+    let is_flag = |flags: &[_], e: Entity| q_kcl.get(e).is_ok_and(|s| flags.iter().any(|f| *f as usize == s.0));
+    // only land on the ground, not on walls or on triggers which have no collision
+    let filter = |e: Entity| q_kcl.contains(e) && !is_flag(&WALL_FLAGS, e) && !is_flag(&TRIGGER_FLAGS, e);
+    let raycast_settings = RaycastSettings {
+        // hidden parts of the KCL still have collision in game
+        visibility: RaycastVisibility::Ignore,
+        ..default()
+    }
+    .with_filter(&filter);
+
+    for (mut transform, tweakable) in q_selected.iter_mut() {
+        // checkpoints stay on their own plane
+        if tweakable.0 != SnapTo::Kcl {
+            continue;
+        }
+        let ray = Ray3d::new(transform.translation + Vec3::Y * RAY_START_OFFSET, Vec3::NEG_Y);
+        let Some((_, hit)) = raycast.cast_ray(ray, &raycast_settings).first() else {
+            continue;
+        };
+        transform.translation = hit.position();
+        if align_to_surface {
+            // triangles can be wound either way, so make sure the normal points up
+            let normal = if hit.normal().y < 0. {
+                -hit.normal()
+            } else {
+                hit.normal()
+            };
+            let up = transform.rotation * Vec3::Y;
+            transform.rotation = Quat::from_rotation_arc(up, normal.normalize()) * transform.rotation;
+        }
+    }
+}
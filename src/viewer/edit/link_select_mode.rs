@@ -1,5 +1,5 @@
 use crate::{
-    ui::viewport::ViewportInfo,
+    ui::{keybinds::Keybinds, viewport::ViewportInfo},
     util::{ui_viewport_to_ndc, RaycastFromCam},
     viewer::{
         camera::Gizmo2dCam,
@@ -17,14 +17,64 @@ use std::marker::PhantomData;
 use super::select::SelectSet;
 
 pub fn link_select_mode_plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        (
-            update_link_selection_mode::<RoutePoint>,
-            update_link_selection_mode::<RespawnPoint>,
-        )
-            .after(SelectSet),
-    );
+    app.init_resource::<LinkSelectState>()
+        .add_systems(PreUpdate, update_link_select_state)
+        .add_systems(
+            Update,
+            (
+                update_link_selection_mode::<RoutePoint>,
+                update_link_selection_mode::<RespawnPoint>,
+            )
+                .after(SelectSet),
+        );
+}
+
+/// What link select mode is waiting for the user to pick, if anything. Kept in step with the [`LinkSelectMode`]
+/// resources, so the rest of the UI can dim itself and hold off on edits without knowing about each kind of link.
+#[derive(Resource, Default, PartialEq, Clone, Copy, Debug)]
+pub enum LinkSelectState {
+    #[default]
+    Inactive,
+    PickingRoute,
+    PickingRespawn,
+}
+impl LinkSelectState {
+    pub fn is_active(self) -> bool {
+        self != Self::Inactive
+    }
+    /// What to tell the user to do while picking
+    pub fn prompt(self) -> Option<&'static str> {
+        match self {
+            Self::Inactive => None,
+            Self::PickingRoute => Some("Pick a Route (Esc to cancel)"),
+            Self::PickingRespawn => Some("Pick a Respawn Point (Esc to cancel)"),
+        }
+    }
+}
+
+/// Run condition for systems which edit the KMP, so that they don't do anything while a link is being picked
+pub fn not_link_selecting(state: Res<LinkSelectState>) -> bool {
+    !state.is_active()
+}
+
+fn update_link_select_state(
+    mut state: ResMut<LinkSelectState>,
+    keybinds: Option<ResMut<Keybinds>>,
+    route_select_mode: Option<Res<LinkSelectMode<RoutePoint>>>,
+    respawn_select_mode: Option<Res<LinkSelectMode<RespawnPoint>>>,
+) {
+    let new_state = if route_select_mode.is_some() {
+        LinkSelectState::PickingRoute
+    } else if respawn_select_mode.is_some() {
+        LinkSelectState::PickingRespawn
+    } else {
+        LinkSelectState::Inactive
+    };
+    state.set_if_neq(new_state);
+    // shortcuts like delete and undo would change things out from under the link being made
+    if let Some(mut keybinds) = keybinds.filter(|x| x.suspended != new_state.is_active()) {
+        keybinds.suspended = new_state.is_active();
+    }
 }
 
 /// If this resource exists in the world, it means we are in link select mode and are currently
@@ -105,14 +155,13 @@ fn update_link_selection_mode<T: Component + CreateLink>(
         return;
     }
 
-    if !mouse_buttons.just_pressed(MouseButton::Left) {
+    // clicks outside the viewport or that miss every point are ignored, so a misclick doesn't lose the link being
+    // made. Only picking a point or pressing escape leaves link select mode.
+    if !viewport_info.mouse_in_viewport || !mouse_buttons.just_pressed(MouseButton::Left) {
         return;
     }
 
-    commands.remove_resource::<LinkSelectMode<T>>();
-
     let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
-        reset_visibilities();
         return;
     };
 
@@ -125,9 +174,9 @@ fn update_link_selection_mode<T: Component + CreateLink>(
         .filter(&|e| q_route_pt.contains(e))
         .cast();
     let Some(intersection_e) = intersections.first().map(|x| x.0) else {
-        reset_visibilities();
         return;
     };
+    commands.remove_resource::<LinkSelectMode<T>>();
     let entities = res.0.clone();
     let e_v_map = e_v_map.clone();
 
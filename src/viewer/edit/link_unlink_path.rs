@@ -1,6 +1,9 @@
 use super::select::{SelectSet, Selected};
 use crate::{
-    ui::viewport::ViewportInfo,
+    ui::{
+        keybinds::{KeyAction, Keybinds, ModifiersPressed},
+        viewport::ViewportInfo,
+    },
     util::{ui_viewport_to_ndc, RaycastFromCam},
     viewer::{
         camera::Gizmo2dCam,
@@ -12,7 +15,10 @@ use crate::{
         },
     },
 };
-use bevy::{ecs::world::Command, prelude::*};
+use bevy::{
+    ecs::{system::SystemState, world::Command},
+    prelude::*,
+};
 use bevy_mod_raycast::prelude::*;
 
 pub fn link_unlink_plugin(app: &mut App) {
@@ -42,7 +48,7 @@ pub fn get_pt_to_link(
     if !mouse_buttons.just_pressed(MouseButton::Left) {
         return None;
     }
-    if !keys.pressed(KeyCode::AltLeft) && !keys.pressed(KeyCode::AltRight) {
+    if !keys.alt_pressed() {
         return None;
     }
     if q_selected.is_empty() {
@@ -72,6 +78,10 @@ fn link_points<T: Component + LinkKmpPoint + Default>(
     let Some(alt_clicked_pt) = *alt_clicked_pt else {
         return;
     };
+    // alt clicking on a selected point duplicates the selection instead of linking to it
+    if q_selected.contains(alt_clicked_pt) {
+        return;
+    }
 
     if q_pts.contains(alt_clicked_pt) {
         for selected in q_selected.iter().filter(|e| q_pts.contains(*e)) {
@@ -90,6 +100,50 @@ fn link_points<T: Component + LinkKmpPoint + Default>(
     }
 }
 
+/// Link every selected point of the same kind as `target` to it, as Alt clicking on it does. Returns how many points
+/// were linked.
+pub fn link_selected_to(world: &mut World, target: Entity) -> usize {
+    let Some(entity) = world.get_entity(target) else {
+        return 0;
+    };
+    let count = if entity.contains::<EnemyPathPoint>() {
+        link_selected_to_point::<EnemyPathPoint>(world, target)
+    } else if entity.contains::<ItemPathPoint>() {
+        link_selected_to_point::<ItemPathPoint>(world, target)
+    } else if entity.contains::<CheckpointMarker>() {
+        link_selected_to_point::<CheckpointMarker>(world, target)
+    } else if entity.contains::<RoutePoint>() {
+        link_selected_to_point::<RoutePoint>(world, target)
+    } else {
+        0
+    };
+    if count > 0 {
+        world.send_event(RecalcPaths::all());
+    }
+    count
+}
+
+fn link_selected_to_point<T: Component + LinkKmpPoint>(world: &mut World, target: Entity) -> usize {
+    let selected: Vec<_> = world
+        .query_filtered::<Entity, (With<T>, With<Selected>)>()
+        .iter(world)
+        .filter(|e| *e != target)
+        .collect();
+    let mut count = 0;
+    for selected in selected {
+        // we need to check we are not linking to our own route start to create a circle
+        if is_route_pt::<T>() {
+            let mut ss = SystemState::<GetRouteStart>::new(world);
+            if ss.get(world).get_entity(selected) == target {
+                continue;
+            }
+        }
+        T::link(world, selected, target);
+        count += 1;
+    }
+    count
+}
+
 trait LinkKmpPoint {
     fn link(world: &mut World, prev_e: Entity, next_e: Entity) {
         KmpPathNode::link_nodes(prev_e, next_e, world);
@@ -111,12 +165,12 @@ impl LinkKmpPoint for CheckpointMarker {
 pub fn unlink_points(
     mut commands: Commands,
     keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
     q_kmp_path_node: Query<&KmpPathNode>,
     q_selected: Query<Entity, With<Selected>>,
     mut ev_recalc_paths: EventWriter<RecalcPaths>,
 ) {
-    // unlink points with the U key
-    if !keys.just_pressed(KeyCode::KeyU) {
+    if !keybinds.just_pressed(KeyAction::UnlinkPoints, &keys) {
         return;
     }
 
@@ -147,3 +201,28 @@ pub fn unlink_points(
     }
     ev_recalc_paths.send(RecalcPaths::all());
 }
+
+#[test]
+fn test_alt_click_selected_point_keeps_links() {
+    use bevy::ecs::system::RunSystemOnce;
+
+    let mut world = World::new();
+    world.init_resource::<Events<RecalcPaths>>();
+    let first = world
+        .spawn((EnemyPathPoint::default(), KmpPathNode::default(), Selected))
+        .id();
+    let second = world
+        .spawn((EnemyPathPoint::default(), KmpPathNode::default(), Selected))
+        .id();
+    KmpPathNode::link_nodes(first, second, &mut world);
+
+    // the original of an alt drag is the selected point that was clicked on
+    world.run_system_once_with(Some(second), link_points::<EnemyPathPoint>);
+
+    let first_node = world.get::<KmpPathNode>(first).unwrap();
+    assert!(first_node.get_previous().is_empty());
+    assert!(first_node.get_next().len() == 1 && first_node.get_next().contains(&second));
+    let second_node = world.get::<KmpPathNode>(second).unwrap();
+    assert!(second_node.get_previous().len() == 1 && second_node.get_previous().contains(&first));
+    assert!(second_node.get_next().is_empty());
+}
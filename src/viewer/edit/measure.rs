@@ -0,0 +1,171 @@
+use super::select::{pick_nearest_point, PickMode, SelectSet};
+use crate::{
+    ui::{
+        keybinds::{KeyAction, Keybinds},
+        settings::AppSettings,
+        viewport::ViewportInfo,
+    },
+    util::{ui_viewport_to_ndc, RaycastFromCam},
+    viewer::{camera::Gizmo2dCam, kcl_model::KCLModelSection, kmp::components::KmpSelectablePoint},
+};
+use bevy::{color::palettes::css, prelude::*};
+use bevy_mod_raycast::prelude::*;
+
+pub fn measure_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            toggle_measure,
+            pick_measure_point.run_if(resource_exists::<Measurement>),
+            draw_measurement.run_if(resource_exists::<Measurement>),
+        )
+            .chain()
+            .before(SelectSet),
+    );
+}
+
+// size of the markers drawn at each measured point
+const MARKER_SIZE: f32 = 40.;
+
+/// Measures distances and angles between points clicked on in the viewport, which snap to KMP points or the KCL.
+/// The tool is on while this resource exists.
+#[derive(Resource, Default)]
+pub struct Measurement {
+    pub points: Vec<Vec3>,
+}
+impl Measurement {
+    /// The length of the whole line through every point
+    pub fn total_distance(&self) -> f32 {
+        self.points.windows(2).map(|x| x[0].distance(x[1])).sum()
+    }
+    /// The distance from the first point to the last, as the crow flies
+    pub fn straight_distance(&self) -> Option<f32> {
+        Some(self.points.first()?.distance(*self.points.last()?))
+    }
+    /// The horizontal distance and the height difference from the first point to the last
+    pub fn horizontal_and_height(&self) -> Option<(f32, f32)> {
+        let (first, last) = (self.points.first()?, self.points.last()?);
+        Some((first.xz().distance(last.xz()), last.y - first.y))
+    }
+    /// The direction of the last segment around the Y axis in degrees, in the same way rotations are in the KMP
+    pub fn heading(&self) -> Option<f32> {
+        let [.., a, b] = self.points[..] else {
+            return None;
+        };
+        let dir = b - a;
+        (dir.xz() != Vec2::ZERO).then(|| dir.x.atan2(dir.z).to_degrees())
+    }
+    /// How steeply the last segment goes up or down, in degrees
+    pub fn slope(&self) -> Option<f32> {
+        let [.., a, b] = self.points[..] else {
+            return None;
+        };
+        let dir = b - a;
+        (dir != Vec3::ZERO).then(|| dir.y.atan2(dir.xz().length()).to_degrees())
+    }
+    /// The angle at the second to last point, between the last two segments
+    pub fn corner_angle(&self) -> Option<f32> {
+        let [.., a, b, c] = self.points[..] else {
+            return None;
+        };
+        let (u, v) = (a - b, c - b);
+        (u != Vec3::ZERO && v != Vec3::ZERO).then(|| u.angle_between(v).to_degrees())
+    }
+}
+
+fn toggle_measure(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    measurement: Option<Res<Measurement>>,
+) {
+    if measurement.is_some() && keys.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<Measurement>();
+    } else if keybinds.recording.is_none() && keybinds.just_pressed(KeyAction::Measure, &keys) {
+        if measurement.is_some() {
+            commands.remove_resource::<Measurement>();
+        } else {
+            commands.init_resource::<Measurement>();
+        }
+    }
+}
+
+// add wherever is clicked to the measurement, snapping to the nearest point if there is one under the mouse,
+// otherwise the KCL
+fn pick_measure_point(
+    mut measurement: ResMut<Measurement>,
+    viewport_info: Res<ViewportInfo>,
+    q_window: Query<&Window>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    mut raycast: Raycast,
+    q_kcl: Query<(), With<KCLModelSection>>,
+    q_points: Query<(Entity, &GlobalTransform, &ViewVisibility), With<KmpSelectablePoint>>,
+    pick_mode: Res<PickMode>,
+    settings: Res<AppSettings>,
+) {
+    if !viewport_info.mouse_in_viewport
+        || viewport_info.mouse_on_overlayed_ui
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    let Some(cam) = q_camera.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let mouse_pos_ndc = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+
+    let point = if pick_mode.hits_points() {
+        let hit = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+            .filter(&|e| q_points.get(e).is_ok_and(|x| x.2.get()))
+            .cast()
+            .first()
+            .map(|x| (x.0, false));
+        pick_nearest_point(
+            cam,
+            viewport_info.viewport_rect,
+            mouse_pos,
+            settings.pick.radius,
+            hit,
+            q_points
+                .iter()
+                .filter(|x| x.2.get())
+                .map(|(e, gt, _)| (e, gt.translation(), false)),
+        )
+        .and_then(|e| q_points.get(e).ok())
+        .map(|x| x.1.translation())
+    } else {
+        None
+    };
+    let pos = point.or_else(|| {
+        if !pick_mode.hits_kcl() {
+            return None;
+        }
+        RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+            .filter(&|e| q_kcl.contains(e))
+            .cast()
+            .first()
+            .map(|x| x.1.position())
+    });
+    if let Some(pos) = pos {
+        measurement.points.push(pos);
+    }
+}
+
+fn draw_measurement(mut gizmos: Gizmos, measurement: Res<Measurement>) {
+    for pair in measurement.points.windows(2) {
+        gizmos.line(pair[0], pair[1], css::YELLOW);
+    }
+    // a faint line straight from the start to the end, for the straight line distance
+    if let [first, .., last] = measurement.points[..] {
+        if measurement.points.len() > 2 {
+            gizmos.line(first, last, css::YELLOW.with_alpha(0.3));
+        }
+    }
+    for pos in measurement.points.iter() {
+        gizmos.sphere(*pos, Quat::IDENTITY, MARKER_SIZE, css::YELLOW);
+    }
+}
@@ -0,0 +1,186 @@
+use super::create_delete::{DuplicateSelected, RouteDuplication};
+use crate::{
+    ui::viewport::ViewportInfo,
+    util::{ui_viewport_to_ndc, RaycastFromCam},
+    viewer::{camera::Gizmo2dCam, kcl_model::KCLModelSection, kmp::checkpoints::CheckpointLeft},
+};
+use bevy::{color::palettes::css, prelude::*};
+use bevy_mod_raycast::prelude::*;
+
+pub fn mirror_selection_plugin(app: &mut App) {
+    app.add_event::<MirrorSelected>().add_systems(
+        Update,
+        (
+            pick_mirror_point.run_if(resource_exists::<MirrorSelection>),
+            draw_mirror_plane.run_if(resource_exists::<MirrorSelection>),
+            mirror_selected.run_if(on_event::<MirrorSelected>()),
+        ),
+    );
+}
+
+// how far from the plane a point can be and still count as being on it, so that paths which meet on the axis of
+// symmetry are joined up to their mirror images
+const ON_PLANE_DISTANCE: f32 = 50.;
+// how far the plane is drawn out past the two points it is set from
+const PLANE_DRAW_EXTENT: f32 = 20000.;
+const PLANE_DRAW_HEIGHT: f32 = 3000.;
+
+/// A vertical plane which points are mirrored across
+#[derive(Clone, Copy, PartialEq)]
+pub struct MirrorPlane {
+    pub origin: Vec3,
+    pub normal: Vec3,
+}
+impl MirrorPlane {
+    /// The vertical plane which both points lie on, if they aren't directly above each other
+    pub fn through(a: Vec3, b: Vec3) -> Option<Self> {
+        let dir = (b - a).xz().try_normalize()?;
+        Some(Self {
+            origin: a,
+            normal: Vec3::new(dir.y, 0., -dir.x),
+        })
+    }
+    pub fn distance(&self, pos: Vec3) -> f32 {
+        (pos - self.origin).dot(self.normal)
+    }
+    pub fn contains(&self, pos: Vec3) -> bool {
+        self.distance(pos).abs() <= ON_PLANE_DISTANCE
+    }
+    pub fn mirror_pos(&self, pos: Vec3) -> Vec3 {
+        pos - 2. * self.distance(pos) * self.normal
+    }
+    pub fn mirror_dir(&self, dir: Vec3) -> Vec3 {
+        dir - 2. * dir.dot(self.normal) * self.normal
+    }
+    /// Mirroring the axes of a rotation would flip it inside out, so the forward and up directions are mirrored and
+    /// the sideways direction is worked out from them
+    pub fn mirror_rot(&self, rot: Quat) -> Quat {
+        let [x, y, z] = [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| self.mirror_dir(rot * axis));
+        Quat::from_mat3(&Mat3::from_cols(-x, y, z)).normalize()
+    }
+    pub fn mirror_transform(&self, transform: &Transform) -> Transform {
+        Transform {
+            translation: self.mirror_pos(transform.translation),
+            rotation: self.mirror_rot(transform.rotation),
+            scale: transform.scale,
+        }
+    }
+}
+
+/// Whether a point is its own mirror image, so doesn't need copying. For a checkpoint, this means it spans the plane
+/// with each end being the mirror image of the other.
+pub fn on_mirror_plane(world: &World, e: Entity, plane: &MirrorPlane) -> bool {
+    let Some(pos) = world.get::<Transform>(e).map(|x| x.translation) else {
+        return false;
+    };
+    match world
+        .get::<CheckpointLeft>(e)
+        .and_then(|x| world.get::<Transform>(x.right))
+    {
+        Some(right) => plane.mirror_pos(pos).xz().distance(right.translation.xz()) <= ON_PLANE_DISTANCE * 2.,
+        None => plane.contains(pos),
+    }
+}
+
+/// Copies the selected points mirrored across a plane, for symmetric battle arenas. The tool is open while this
+/// resource exists.
+#[derive(Resource)]
+pub struct MirrorSelection {
+    /// The plane is the vertical plane through both of these points
+    pub a: Vec3,
+    pub b: Vec3,
+    /// Which point the next click on the KCL sets, if any
+    pub picking: Option<MirrorPick>,
+}
+impl Default for MirrorSelection {
+    fn default() -> Self {
+        Self {
+            a: Vec3::ZERO,
+            b: Vec3::Z * 1000.,
+            picking: None,
+        }
+    }
+}
+impl MirrorSelection {
+    pub fn plane(&self) -> Option<MirrorPlane> {
+        MirrorPlane::through(self.a, self.b)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MirrorPick {
+    A,
+    B,
+}
+
+/// Send this event to copy the selected points mirrored across the plane in the [`MirrorSelection`]
+#[derive(Event, Default)]
+pub struct MirrorSelected;
+
+fn mirror_selected(world: &mut World) {
+    world.resource_mut::<Events<MirrorSelected>>().clear();
+    let Some(plane) = world.get_resource::<MirrorSelection>().and_then(|x| x.plane()) else {
+        return;
+    };
+    // copying routes would leave them unmirrored, so the copies follow the same routes, unless the route points
+    // are also selected and mirrored themselves
+    world.send_event(DuplicateSelected {
+        dragged: None,
+        routes: Some(RouteDuplication::Share),
+        mirror: Some(plane),
+    });
+}
+
+// set the point being picked to wherever on the KCL is clicked
+fn pick_mirror_point(
+    mut mirror: ResMut<MirrorSelection>,
+    viewport_info: Res<ViewportInfo>,
+    q_window: Query<&Window>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    mut raycast: Raycast,
+    q_kcl: Query<(), With<KCLModelSection>>,
+) {
+    let Some(picking) = mirror.picking else {
+        return;
+    };
+    if !viewport_info.mouse_in_viewport
+        || viewport_info.mouse_on_overlayed_ui
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    let Some(cam) = q_camera.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let mouse_pos_ndc = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+    let intersections = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+        .filter(&|e| q_kcl.contains(e))
+        .cast();
+    let Some(pos) = intersections.first().map(|x| x.1.position()) else {
+        return;
+    };
+    match picking {
+        MirrorPick::A => mirror.a = pos,
+        MirrorPick::B => mirror.b = pos,
+    }
+    mirror.picking = None;
+}
+
+fn draw_mirror_plane(mut gizmos: Gizmos, mirror: Res<MirrorSelection>) {
+    gizmos.sphere(mirror.a, Quat::IDENTITY, 50., css::AQUA);
+    gizmos.sphere(mirror.b, Quat::IDENTITY, 50., css::AQUA);
+    let Some(plane) = mirror.plane() else {
+        return;
+    };
+    let along = plane.normal.cross(Vec3::Y) * PLANE_DRAW_EXTENT;
+    let up = Vec3::Y * PLANE_DRAW_HEIGHT;
+    let centre = mirror.a.lerp(mirror.b, 0.5);
+    let color = css::AQUA.with_alpha(0.5);
+    gizmos.line(centre - along, centre + along, css::AQUA);
+    gizmos.line(centre - along + up, centre + along + up, color);
+    gizmos.line(centre - along - up, centre + along - up, color);
+}
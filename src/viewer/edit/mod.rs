@@ -1,18 +1,36 @@
 pub mod area_gizmo;
+pub mod blender_coords;
+pub mod context_menu;
+pub mod create_at_coordinates;
 pub mod create_delete;
+pub mod guides;
+pub mod kcl_snap;
 pub mod link_select_mode;
 pub mod link_unlink_path;
+pub mod measure;
+pub mod mirror_selection;
+pub mod nudge;
+pub mod pin;
 pub mod select;
 pub mod transform_gizmo;
+pub mod transform_history;
 pub mod tweak;
+pub mod undo;
+pub mod volume_select;
 
 use self::{
-    area_gizmo::area_gizmo_plugin, create_delete::create_delete_plugin, link_unlink_path::link_unlink_plugin,
-    select::select_plugin, transform_gizmo::transform_gizmo_plugin, tweak::tweak_plugin,
+    area_gizmo::area_gizmo_plugin, blender_coords::blender_coords_plugin, context_menu::context_menu_plugin,
+    create_at_coordinates::create_at_coordinates_plugin, create_delete::create_delete_plugin, guides::guides_plugin,
+    kcl_snap::kcl_snap_plugin, link_unlink_path::link_unlink_plugin, select::select_plugin,
+    transform_gizmo::transform_gizmo_plugin, transform_history::transform_history_plugin, tweak::tweak_plugin,
+    undo::undo_plugin, volume_select::volume_select_plugin,
 };
 use bevy::prelude::*;
 use bevy_mod_outline::OutlinePlugin;
 use link_select_mode::link_select_mode_plugin;
+use measure::measure_plugin;
+use mirror_selection::mirror_selection_plugin;
+use nudge::nudge_plugin;
 use strum_macros::EnumIter;
 
 pub fn edit_plugin(app: &mut App) {
@@ -25,6 +43,19 @@ pub fn edit_plugin(app: &mut App) {
         link_unlink_plugin,
         tweak_plugin,
         link_select_mode_plugin,
+        nudge_plugin,
+        transform_history_plugin,
+        volume_select_plugin,
+        undo_plugin,
+        create_at_coordinates_plugin,
+        kcl_snap_plugin,
+        guides_plugin,
+    ))
+    .add_plugins((
+        blender_coords_plugin,
+        measure_plugin,
+        mirror_selection_plugin,
+        context_menu_plugin,
     ))
     .init_resource::<EditMode>();
 }
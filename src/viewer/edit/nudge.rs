@@ -0,0 +1,168 @@
+use super::select::Selected;
+use crate::{
+    ui::{
+        keybinds::{KeyAction, Keybinds, ModifiersPressed},
+        settings::AppSettings,
+        viewport::ViewportInfo,
+    },
+    util::egui_has_primary_context,
+    viewer::{
+        camera::Gizmo2dCam,
+        kmp::{components::TransformEditOptions, sections::KmpEditMode},
+    },
+};
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use bevy_egui::EguiContexts;
+use serde::{Deserialize, Serialize};
+
+pub fn nudge_plugin(app: &mut App) {
+    app.add_systems(Update, nudge_selection.run_if(egui_has_primary_context));
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct NudgeSettings {
+    /// If enabled, the nudge keys move points relative to the direction the camera is facing,
+    /// rather than along the world X and Z axes
+    pub screen_relative: bool,
+    pub step: NudgeSteps,
+}
+impl Default for NudgeSettings {
+    fn default() -> Self {
+        Self {
+            screen_relative: true,
+            step: NudgeSteps::default(),
+        }
+    }
+}
+
+/// How far a single key press moves a point, for each section
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct NudgeSteps {
+    pub start_points: f32,
+    pub enemy_paths: f32,
+    pub item_paths: f32,
+    pub checkpoints: f32,
+    pub respawn_points: f32,
+    pub objects: f32,
+    pub routes: f32,
+    pub areas: f32,
+    pub cameras: f32,
+    pub cannon_points: f32,
+    pub battle_finish_points: f32,
+}
+impl Default for NudgeSteps {
+    fn default() -> Self {
+        Self {
+            start_points: 50.,
+            enemy_paths: 100.,
+            item_paths: 100.,
+            checkpoints: 500.,
+            respawn_points: 100.,
+            objects: 50.,
+            routes: 100.,
+            areas: 250.,
+            cameras: 10.,
+            cannon_points: 100.,
+            battle_finish_points: 100.,
+        }
+    }
+}
+impl NudgeSteps {
+    pub fn get(mut self, mode: KmpEditMode) -> Option<f32> {
+        self.get_mut(mode).copied()
+    }
+    /// Returns `None` for track info, as it has no points to nudge
+    pub fn get_mut(&mut self, mode: KmpEditMode) -> Option<&mut f32> {
+        Some(match mode {
+            KmpEditMode::StartPoints => &mut self.start_points,
+            KmpEditMode::EnemyPaths => &mut self.enemy_paths,
+            KmpEditMode::ItemPaths => &mut self.item_paths,
+            KmpEditMode::Checkpoints => &mut self.checkpoints,
+            KmpEditMode::RespawnPoints => &mut self.respawn_points,
+            KmpEditMode::Objects => &mut self.objects,
+            KmpEditMode::Routes => &mut self.routes,
+            KmpEditMode::Areas => &mut self.areas,
+            KmpEditMode::Cameras => &mut self.cameras,
+            KmpEditMode::CannonPoints => &mut self.cannon_points,
+            KmpEditMode::BattleFinishPoints => &mut self.battle_finish_points,
+            KmpEditMode::TrackInfo => return None,
+        })
+    }
+}
+
+// the nudge keybinds (Ctrl + arrow keys and Ctrl + PgUp/PgDn by default) move the selection along the ground plane and
+// up and down. Holding shift as well multiplies the step by 10.
+fn nudge_selection(
+    keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
+    viewport_info: Res<ViewportInfo>,
+    settings: Res<AppSettings>,
+    mode: Res<KmpEditMode>,
+    q_cam: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    mut q_selected: Query<(&mut Transform, Option<&TransformEditOptions>), With<Selected>>,
+    mut contexts: EguiContexts,
+) {
+    // only nudge with the mouse over the viewport, and not while typing into a text box
+    if !viewport_info.mouse_in_viewport || contexts.ctx_mut().wants_keyboard_input() || q_selected.is_empty() {
+        return;
+    }
+    let pressed = |action| keybinds.just_pressed_or_shifted(action, &keys);
+
+    let mut dir = Vec3::ZERO;
+    if pressed(KeyAction::NudgeForward) {
+        dir.z -= 1.;
+    }
+    if pressed(KeyAction::NudgeBackward) {
+        dir.z += 1.;
+    }
+    if pressed(KeyAction::NudgeLeft) {
+        dir.x -= 1.;
+    }
+    if pressed(KeyAction::NudgeRight) {
+        dir.x += 1.;
+    }
+    if pressed(KeyAction::NudgeUp) {
+        dir.y += 1.;
+    }
+    if pressed(KeyAction::NudgeDown) {
+        dir.y -= 1.;
+    }
+    if dir == Vec3::ZERO {
+        return;
+    }
+
+    if settings.nudge.screen_relative {
+        // rotate the ground plane movement so that 'up' is the direction the camera is facing
+        if let Some((_, cam_gt)) = q_cam.iter().find(|cam| cam.0.is_active) {
+            let forward = cam_gt.forward().xz();
+            // when looking straight down (e.g. top down camera), use the camera's up direction instead
+            let forward = if forward.length_squared() > 0.0001 {
+                forward
+            } else {
+                cam_gt.up().xz()
+            }
+            .normalize_or_zero();
+            if forward != Vec2::ZERO {
+                let right = vec2(-forward.y, forward.x);
+                let ground = right * dir.x - forward * dir.z;
+                dir = vec3(ground.x, dir.y, ground.y);
+            }
+        }
+    }
+
+    let Some(step) = settings.nudge.step.get(*mode) else {
+        return;
+    };
+    let step = if keys.shift_pressed() { step * 10. } else { step };
+
+    for (mut transform, edit_options) in q_selected.iter_mut() {
+        let mut delta = dir * step;
+        if edit_options.is_some_and(|x| x.hide_y_translation) {
+            delta.y = 0.;
+        }
+        transform.translation += delta;
+    }
+}
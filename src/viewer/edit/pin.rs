@@ -0,0 +1,125 @@
+use crate::viewer::kmp::{
+    checkpoints::{CheckpointLeft, CheckpointRight},
+    components::{AreaPoint, CannonPoint, Checkpoint, EnemyPathPoint, KmpCamera, Object, RespawnPoint, StartPoint},
+    object_models::ObjectModels,
+    ordering::OrderId,
+    sections::KmpEditMode,
+};
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+/// Pinned points have their key values shown in the viewport all the time, whatever is selected. The number is the
+/// order they were pinned in.
+#[derive(Component, Clone, Copy)]
+pub struct Pinned(pub u32);
+
+/// Only a few points can be pinned, so the readout doesn't cover the viewport
+pub const MAX_PINNED: usize = 4;
+
+/// The key values of a pinned point, read fresh each frame
+pub struct PinnedReadout {
+    pub entity: Entity,
+    pub title: String,
+    pub rows: Vec<(&'static str, String)>,
+}
+
+/// Pin these points, in addition to the ones already pinned, up to [`MAX_PINNED`]. Returns how many were pinned.
+pub fn pin(world: &mut World, entities: impl IntoIterator<Item = Entity>) -> usize {
+    let mut q_pinned = world.query::<&Pinned>();
+    let mut count = q_pinned.iter(world).count();
+    let mut next = q_pinned.iter(world).map(|x| x.0 + 1).max().unwrap_or_default();
+    let mut pinned = 0;
+    for e in entities {
+        // the left point of a checkpoint holds its values
+        let e = world.get::<CheckpointRight>(e).map(|x| x.left).unwrap_or(e);
+        if count >= MAX_PINNED || world.get::<Pinned>(e).is_some() {
+            continue;
+        }
+        let Some(mut entity) = world.get_entity_mut(e) else {
+            continue;
+        };
+        entity.insert(Pinned(next));
+        next += 1;
+        count += 1;
+        pinned += 1;
+    }
+    pinned
+}
+
+pub fn unpin(world: &mut World, entities: impl IntoIterator<Item = Entity>) {
+    for e in entities {
+        let e = world.get::<CheckpointRight>(e).map(|x| x.left).unwrap_or(e);
+        if let Some(mut entity) = world.get_entity_mut(e) {
+            entity.remove::<Pinned>();
+        }
+    }
+}
+
+fn format_pos(pos: Vec3) -> String {
+    format!("{:.0}, {:.0}, {:.0}", pos.x, pos.y, pos.z)
+}
+
+/// The readouts of every pinned point, in the order they were pinned
+pub fn pinned_readouts(world: &mut World) -> Vec<PinnedReadout> {
+    let mut q_pinned = world.query::<(Entity, &Pinned)>();
+    let mut pinned: Vec<_> = q_pinned.iter(world).map(|(e, pinned)| (e, *pinned)).collect();
+    pinned.sort_by_key(|x| x.1 .0);
+
+    let mut readouts = Vec::new();
+    for (e, _) in pinned {
+        let entity = world.entity(e);
+        let Some(mode) = KmpEditMode::iter().find(|mode| mode.contains_entity(&entity)) else {
+            continue;
+        };
+        let title = match entity.get::<OrderId>() {
+            Some(order_id) => format!("{mode} {}", order_id.0),
+            None => mode.to_string(),
+        };
+        let mut rows = Vec::new();
+        let right = entity
+            .get::<CheckpointLeft>()
+            .and_then(|x| world.get::<Transform>(x.right));
+        if let Some(transform) = entity.get::<Transform>() {
+            let label = if right.is_some() { "Left" } else { "Position" };
+            rows.push((label, format_pos(transform.translation)));
+        }
+        if let Some(right) = right {
+            rows.push(("Right", format_pos(right.translation)));
+        }
+        if let Some(cp) = entity.get::<Checkpoint>() {
+            rows.push(("Type", cp.kind.to_string()));
+        }
+        if let Some(camera) = entity.get::<KmpCamera>() {
+            rows.push(("Type", camera.kind.to_string()));
+            rows.push(("Time", format!("{} frames", camera.time)));
+        }
+        if let Some(area) = entity.get::<AreaPoint>() {
+            rows.push(("Type", area.kind.to_string()));
+            rows.push(("Shape", area.shape.to_string()));
+            rows.push(("Priority", area.priority.to_string()));
+        }
+        if let Some(object) = entity.get::<Object>() {
+            rows.push(("Object ID", format!("0x{:03X}", object.object_id)));
+            if let Some(model) = world
+                .get_resource::<ObjectModels>()
+                .and_then(|x| x.get(object.object_id))
+            {
+                rows.push(("Object", model.name.clone()));
+            }
+        }
+        if let Some(start) = entity.get::<StartPoint>() {
+            rows.push(("Player", start.player_index.to_string()));
+        }
+        if let Some(enemy) = entity.get::<EnemyPathPoint>() {
+            rows.push(("Leniency", enemy.leniency.to_string()));
+        }
+        if let Some(respawn) = entity.get::<RespawnPoint>() {
+            rows.push(("Sound Trigger", respawn.sound_trigger.to_string()));
+        }
+        if let Some(cannon) = entity.get::<CannonPoint>() {
+            rows.push(("Shoot Effect", cannon.shoot_effect.to_string()));
+        }
+        readouts.push(PinnedReadout { entity: e, title, rows });
+    }
+    readouts
+}
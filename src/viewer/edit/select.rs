@@ -1,17 +1,25 @@
 use super::area_gizmo::AreaGizmoOptions;
 use super::create_delete::JustCreatedPoint;
 use super::link_select_mode::LinkSelectMode;
+use super::measure::Measurement;
+use super::mirror_selection::MirrorSelection;
 use super::EditMode;
-use crate::ui::keybinds::{Modifier, ModifiersPressed};
+use crate::ui::keybinds::{KeyAction, Keybinds, ModifiersPressed};
+use crate::ui::settings::AppSettings;
 use crate::ui::update_ui::UpdateUiSet;
 use crate::ui::viewport::ViewportInfo;
 use crate::util::{ui_viewport_to_ndc, world_to_ui_viewport, RaycastFromCam};
 use crate::viewer::camera::Gizmo2dCam;
+use crate::viewer::kmp::camera_aim::CameraAim;
 use crate::viewer::kmp::components::{KmpSelectablePoint, RespawnPoint, RoutePoint};
+use crate::viewer::kmp::item_boxes::ItemBoxGenerator;
+use crate::viewer::kmp::path::KmpPathNodeLink;
 use crate::viewer::kmp::sections::KmpEditMode;
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_mod_outline::*;
 use bevy_mod_raycast::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
 use transform_gizmo_bevy::GizmoTarget;
 
 #[derive(SystemSet, Debug, PartialEq, Eq, Hash, Clone)]
@@ -19,6 +27,7 @@ pub struct SelectSet;
 
 pub fn select_plugin(app: &mut App) {
     app.init_resource::<SelectBox>()
+        .init_resource::<PickMode>()
         .add_systems(Update, (select, select_box, select_all).in_set(SelectSet))
         .add_systems(Update, update_outlines.after(SelectSet))
         .add_systems(
@@ -30,22 +39,152 @@ pub fn select_plugin(app: &mut App) {
 #[derive(Component, Default)]
 pub struct Selected;
 
+/// What clicking in the viewport can hit, so that the KCL can be clicked on where points densely cover it,
+/// or points can be clicked on without accidentally creating points on the KCL
+#[derive(Resource, Default, PartialEq, Eq, Clone, Copy, Display, EnumIter)]
+pub enum PickMode {
+    #[default]
+    #[strum(serialize = "Points & KCL")]
+    All,
+    #[strum(serialize = "KCL Only")]
+    KclOnly,
+    #[strum(serialize = "Points Only")]
+    PointsOnly,
+}
+/// How close to a point the mouse has to be to click on it
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PickSettings {
+    /// Points within this many pixels of the mouse can be clicked on, so small or distant points don't have to be hit
+    /// exactly
+    pub radius: f32,
+}
+impl Default for PickSettings {
+    fn default() -> Self {
+        Self { radius: 8. }
+    }
+}
+
+/// Of the points within the pick radius of the mouse, find the one nearest to it on screen, then nearest to the camera.
+/// Points which are `preferred` are picked over any others. `hit` is the point the mouse is directly over, if any,
+/// which counts as being no distance away.
+pub fn pick_nearest_point(
+    cam: (&Camera, &GlobalTransform),
+    viewport_rect: Rect,
+    mouse_pos: Vec2,
+    radius: f32,
+    hit: Option<(Entity, bool)>,
+    points: impl IntoIterator<Item = (Entity, Vec3, bool)>,
+) -> Option<Entity> {
+    let cam_pos = cam.1.translation();
+    let mut best = hit.map(|(e, preferred)| ((!preferred, 0., 0.), e));
+    for (e, pos, preferred) in points {
+        let Some(screen_pos) = world_to_ui_viewport(cam, viewport_rect, pos) else {
+            continue;
+        };
+        let distance = screen_pos.distance(mouse_pos);
+        if distance > radius {
+            continue;
+        }
+        let key = (!preferred, distance, cam_pos.distance(pos));
+        if best.is_none_or(|(best_key, _)| key < best_key) {
+            best = Some((key, e));
+        }
+    }
+    best.map(|x| x.1)
+}
+
+/// Finds the shown path link nearest to the mouse on screen, for clicking on links rather than the points they link
+#[derive(SystemParam)]
+pub struct LinkPicker<'w, 's> {
+    q_link: Query<'w, 's, (Entity, &'static KmpPathNodeLink, &'static Visibility)>,
+    q_transform: Query<'w, 's, &'static Transform, With<KmpSelectablePoint>>,
+    settings: Res<'w, AppSettings>,
+}
+impl LinkPicker<'_, '_> {
+    /// The link within the pick radius of the mouse that passes nearest to it on screen, if any
+    pub fn pick(&self, cam: (&Camera, &GlobalTransform), viewport_rect: Rect, mouse_pos: Vec2) -> Option<Entity> {
+        let mut best = None;
+        for (e, link, visibility) in self.q_link.iter() {
+            if *visibility == Visibility::Hidden {
+                continue;
+            }
+            let Ok([prev, next]) = self.q_transform.get_many([link.prev_node, link.next_node]) else {
+                continue;
+            };
+            let to_screen = |x: &Transform| world_to_ui_viewport(cam, viewport_rect, x.translation);
+            let (Some(a), Some(b)) = (to_screen(prev), to_screen(next)) else {
+                continue;
+            };
+            // the nearest point to the mouse along the line on screen
+            let t = ((mouse_pos - a).dot(b - a) / (b - a).length_squared().max(f32::EPSILON)).clamp(0., 1.);
+            let distance = a.lerp(b, t).distance(mouse_pos);
+            if distance > self.settings.pick.radius {
+                continue;
+            }
+            if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+                best = Some((distance, e));
+            }
+        }
+        best.map(|x| x.1)
+    }
+}
+
+// clicks this close to the last one, without the camera having moved, pick the next point behind the last one picked
+const CYCLE_CLICK_DISTANCE: f32 = 3.;
+
+/// The point picked by the last click, so that clicking again in the same place can pick the one behind it
+struct LastPick {
+    mouse_pos: Vec2,
+    cam: GlobalTransform,
+    entity: Entity,
+}
+
+impl PickMode {
+    pub fn hits_points(self) -> bool {
+        self != Self::KclOnly
+    }
+    pub fn hits_kcl(self) -> bool {
+        self != Self::PointsOnly
+    }
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::KclOnly,
+            Self::KclOnly => Self::PointsOnly,
+            Self::PointsOnly => Self::All,
+        }
+    }
+}
+
 fn select(
     viewport_info: Res<ViewportInfo>,
     q_window: Query<&Window>,
     keys: Res<ButtonInput<KeyCode>>,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
-    q_camera: Query<(&mut Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
     q_gizmos: Query<&GizmoTarget>,
     mut raycast: Raycast,
-    q_kmp_section: Query<&KmpSelectablePoint>,
+    q_kmp_section: Query<(EntityRef, &Transform, &Visibility), With<KmpSelectablePoint>>,
     mut commands: Commands,
     area_gizmo_opts: Res<AreaGizmoOptions>,
     q_selected: Query<Entity, With<Selected>>,
     mut ev_just_created_point: EventReader<JustCreatedPoint>,
+    (pick_mode, settings, mode, edit_mode, mut last_pick): (
+        Res<PickMode>,
+        Res<AppSettings>,
+        Res<KmpEditMode>,
+        Res<EditMode>,
+        Local<Option<LastPick>>,
+    ),
 
     route_selection_mode: Option<Res<LinkSelectMode<RoutePoint>>>,
     respawn_selection_mode: Option<Res<LinkSelectMode<RespawnPoint>>>,
+    // tools which pick positions in the viewport, which clicking shouldn't select points for
+    (item_box_generator, camera_aim, measurement, mirror_selection): (
+        Option<Res<ItemBoxGenerator>>,
+        Option<Res<CameraAim>>,
+        Option<Res<Measurement>>,
+        Option<Res<MirrorSelection>>,
+    ),
 ) {
     if !viewport_info.mouse_in_viewport
         || viewport_info.mouse_on_overlayed_ui
@@ -55,15 +194,29 @@ fn select(
         || q_gizmos.iter().any(|x| x.is_focused())
         || route_selection_mode.is_some()
         || respawn_selection_mode.is_some()
+        || item_box_generator.is_some_and(|x| x.picking.is_some())
+        || camera_aim.is_some_and(|x| x.picking)
+        || measurement.is_some()
+        || mirror_selection.is_some_and(|x| x.picking.is_some())
     {
         return;
     }
+    // newly created points should still be selected even if points can't be clicked on
+    if !pick_mode.hits_points() {
+        for created_point in ev_just_created_point.read() {
+            commands.entity(created_point.0).insert(Selected);
+        }
+        return;
+    }
 
     let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
         return;
     };
 
-    let shift_key_down = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
+    // shift keeps the selection, as does ctrl when starting a select box, which then takes away from it
+    let keep_selection = keys.pressed(KeyCode::ShiftLeft)
+        || keys.pressed(KeyCode::ShiftRight)
+        || (*edit_mode == EditMode::SelectBox && keys.control_or_super_pressed());
 
     // get the active camera
     let cam = q_camera.iter().find(|cam| cam.0.is_active).unwrap();
@@ -73,18 +226,50 @@ fn select(
     let intersections = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
         .filter(&|e| q_kmp_section.contains(e))
         .cast();
-    let intersection = intersections.first();
+    // points in the section being edited are picked over points of other sections which are shown
+    let in_section = |e: Entity| q_kmp_section.get(e).is_ok_and(|x| mode.contains_entity(&x.0));
+    let hit = intersections.first().map(|x| (x.0, in_section(x.0)));
+    let points = q_kmp_section
+        .iter()
+        .filter(|x| *x.2 == Visibility::Visible)
+        .map(|(entity, transform, _)| (entity.id(), transform.translation, mode.contains_entity(&entity)));
 
-    // deselect everything if we already have something selected but don't have the shift key down
-    if intersection.is_some() && !shift_key_down {
+    // every point stacked under the mouse, nearest first, with those in the section being edited before the rest
+    let mut stacked: Vec<_> = intersections.iter().map(|x| x.0).collect();
+    stacked.sort_by_key(|e| !in_section(*e));
+    // clicking again in the same place picks the next point behind the last one, so points stacked on top of each
+    // other can all be picked without moving the camera
+    let cycled = last_pick
+        .as_ref()
+        .filter(|x| x.mouse_pos.distance(mouse_pos) <= CYCLE_CLICK_DISTANCE && x.cam == *cam.1 && stacked.len() > 1)
+        .and_then(|x| stacked.iter().position(|e| *e == x.entity))
+        .map(|i| stacked[(i + 1) % stacked.len()]);
+    let intersection = cycled.or_else(|| {
+        pick_nearest_point(
+            cam,
+            viewport_info.viewport_rect,
+            mouse_pos,
+            settings.pick.radius,
+            hit,
+            points,
+        )
+    });
+    *last_pick = intersection.map(|entity| LastPick {
+        mouse_pos,
+        cam: *cam.1,
+        entity,
+    });
+
+    // deselect everything if we already have something selected but aren't keeping it
+    if intersection.is_some() && !keep_selection {
         for selected in q_selected.iter() {
             commands.entity(selected).remove::<Selected>();
         }
     }
     // select the entity
-    if let Some((to_select, _)) = intersection {
-        commands.entity(*to_select).insert(Selected);
-    } else if !shift_key_down {
+    if let Some(to_select) = intersection {
+        commands.entity(to_select).insert(Selected);
+    } else if !keep_selection {
         // if we just randomly clicked on nothing then deselect everything
         for selected in q_selected.iter() {
             commands.entity(selected).remove::<Selected>();
@@ -99,8 +284,9 @@ fn select_all(
     mut commands: Commands,
     q_selectable: Query<(Entity, &Visibility), With<KmpSelectablePoint>>,
     keys: Res<ButtonInput<KeyCode>>,
+    keybinds: Res<Keybinds>,
 ) {
-    if !keys.keybind_pressed([Modifier::Ctrl], [KeyCode::KeyA]) {
+    if !keybinds.just_pressed(KeyAction::SelectAll, &keys) {
         return;
     }
 
@@ -137,10 +323,47 @@ fn deselect_on_mode_change(mode: Res<KmpEditMode>, mut commands: Commands, q_sel
 }
 
 #[derive(Resource, Default)]
-pub struct SelectBox(pub Option<Rect>);
+pub struct SelectBox {
+    pub shape: SelectShape,
+    /// The box or lasso currently being dragged out, in viewport coordinates
+    pub region: Option<SelectRegion>,
+}
 impl SelectBox {
     /// How much we have to move the mouse before we actually start making a select box
     const LENIENCY_BEFORE_SELECT: f32 = 3.;
+    /// How far the mouse has to move before another point is added to a lasso
+    const LASSO_SPACING: f32 = 4.;
+}
+
+#[derive(Default, PartialEq, Eq, Clone, Copy, Display, EnumIter)]
+pub enum SelectShape {
+    #[default]
+    Box,
+    Lasso,
+}
+
+pub enum SelectRegion {
+    Box(Rect),
+    /// The points the mouse has been dragged through, which are joined back up to the first to close the shape
+    Lasso(Vec<Vec2>),
+}
+impl SelectRegion {
+    pub fn contains(&self, pos: Vec2) -> bool {
+        match self {
+            Self::Box(rect) => rect.contains(pos),
+            Self::Lasso(points) => {
+                // count how many edges a line going right from the position crosses, which is odd if it's inside
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > pos.y) != (b.y > pos.y) && pos.x < a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
+        }
+    }
 }
 
 // this handles working out the select box rectangle and actually selecting stuff (the visuals for the box are handled in the UI section)
@@ -149,11 +372,13 @@ fn select_box(
     q_window: Query<&Window>,
     edit_mode: Res<EditMode>,
     viewport_info: Res<ViewportInfo>,
-    q_selectable: Query<(&Transform, Entity, &Visibility, Has<Selected>), With<KmpSelectablePoint>>,
+    q_selectable: Query<(EntityRef, &Transform, &Visibility), With<KmpSelectablePoint>>,
     q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
     mut commands: Commands,
     mut select_box: ResMut<SelectBox>,
     mut initial_mouse_pos: Local<Vec2>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mode: Res<KmpEditMode>,
 ) {
     if *edit_mode != EditMode::SelectBox {
         return;
@@ -174,37 +399,55 @@ fn select_box(
     {
         // delete the select box if mouse isn't in viewport
         if !viewport_info.mouse_in_viewport {
-            *select_box = SelectBox::default();
+            select_box.region = None;
             return;
         }
 
-        // set the select box with the initial mouse pos and the current mouse pos as the 2 corners
-        *select_box = SelectBox(Some(Rect::from_corners(*initial_mouse_pos, mouse_pos)));
+        let initial_mouse_pos = *initial_mouse_pos;
+        match (select_box.shape, &mut select_box.region) {
+            // the select box has the initial mouse pos and the current mouse pos as the 2 corners
+            (SelectShape::Box, region) => {
+                *region = Some(SelectRegion::Box(Rect::from_corners(initial_mouse_pos, mouse_pos)));
+            }
+            (SelectShape::Lasso, Some(SelectRegion::Lasso(points))) => {
+                if points
+                    .last()
+                    .is_some_and(|x| x.distance(mouse_pos) > SelectBox::LASSO_SPACING)
+                {
+                    points.push(mouse_pos);
+                }
+            }
+            (SelectShape::Lasso, region) => {
+                *region = Some(SelectRegion::Lasso(vec![initial_mouse_pos, mouse_pos]));
+            }
+        }
     }
 
     // when we release the mouse button, we actually select stuff
     if mouse_buttons.just_released(MouseButton::Left) {
-        let Some(select_rect) = select_box.0 else {
+        let Some(region) = select_box.region.take() else {
             return;
         };
         // get the active camera
         let cam = q_camera.iter().find(|cam| cam.0.is_active).unwrap();
 
-        // select stuff
-        for selectable in q_selectable.iter() {
-            if selectable.2 != Visibility::Visible || selectable.3 {
-                continue;
-            }
-            let Some(viewport_pos) = world_to_ui_viewport(cam, viewport_info.viewport_rect, selectable.0.translation)
-            else {
-                continue;
-            };
-            if select_rect.contains(viewport_pos) {
-                commands.entity(selectable.1).insert(Selected);
+        // shift adds to the selection, ctrl takes away from it, and otherwise the selection is replaced
+        let add = keys.shift_pressed();
+        let subtract = !add && keys.control_or_super_pressed();
+
+        for (entity, transform, visibility) in q_selectable.iter() {
+            let is_selected = entity.contains::<Selected>();
+            // only points in the section being edited can be selected, even if other sections are shown
+            let inside = *visibility == Visibility::Visible
+                && mode.contains_entity(&entity)
+                && world_to_ui_viewport(cam, viewport_info.viewport_rect, transform.translation)
+                    .is_some_and(|pos| region.contains(pos));
+            if inside && !subtract && !is_selected {
+                commands.entity(entity.id()).insert(Selected);
+            } else if is_selected && (inside && subtract || !inside && !add && !subtract) {
+                commands.entity(entity.id()).remove::<Selected>();
             }
         }
-        // reset the select box after we've selected stuff
-        *select_box = SelectBox::default();
     }
 }
 
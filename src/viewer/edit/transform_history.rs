@@ -0,0 +1,79 @@
+use super::select::Selected;
+use crate::viewer::kmp::components::KmpSelectablePoint;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+pub fn transform_history_plugin(app: &mut App) {
+    app.add_systems(Update, (add_transform_history, record_transform_history).chain());
+}
+
+// how many transforms are kept for each point
+const MAX_HISTORY_LEN: usize = 20;
+// how long a point has to stay still after being moved before its new transform is recorded,
+// so that dragging a point around doesn't fill the history with every position along the way
+const SETTLE_TIME: f32 = 0.5;
+
+/// Recent transforms of a point during this session, so that candidate positions can be quickly compared.
+/// This is separate from undo/redo, and stepping through it never removes anything from it.
+#[derive(Component, Clone)]
+pub struct TransformHistory {
+    entries: VecDeque<Transform>,
+    cursor: usize,
+    last_changed: Option<f32>,
+}
+impl TransformHistory {
+    fn new(transform: Transform) -> Self {
+        Self {
+            entries: VecDeque::from([transform]),
+            cursor: 0,
+            last_changed: None,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+    /// Move to an entry in the history, returning the transform the point should be set to
+    pub fn step_to(&mut self, index: usize) -> Option<Transform> {
+        let transform = *self.entries.get(index)?;
+        self.cursor = index;
+        Some(transform)
+    }
+    fn record(&mut self, transform: Transform) {
+        // stepping through the history sets the transform, which shouldn't be recorded again
+        if self.entries.get(self.cursor) == Some(&transform) {
+            return;
+        }
+        self.entries.push_back(transform);
+        if self.entries.len() > MAX_HISTORY_LEN {
+            self.entries.pop_front();
+        }
+        self.cursor = self.entries.len() - 1;
+    }
+}
+
+// start keeping a history of a point the first time it is selected
+fn add_transform_history(
+    mut commands: Commands,
+    q_selected: Query<(Entity, &Transform), (Added<Selected>, With<KmpSelectablePoint>, Without<TransformHistory>)>,
+) {
+    for (e, transform) in q_selected.iter() {
+        commands.entity(e).insert(TransformHistory::new(*transform));
+    }
+}
+
+fn record_transform_history(mut q_history: Query<(Ref<Transform>, &mut TransformHistory)>, time: Res<Time<Real>>) {
+    let now = time.elapsed_seconds();
+    for (transform, mut history) in q_history.iter_mut() {
+        if transform.is_changed() && !transform.is_added() {
+            history.last_changed = Some(now);
+            continue;
+        }
+        if history.last_changed.is_some_and(|t| now - t >= SETTLE_TIME) {
+            history.last_changed = None;
+            history.record(*transform);
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use super::{
     create_delete::JustCreatedPoint,
-    select::{SelectSet, Selected},
+    guides::{snap_to_guides, Rulers, TopDownMapping, GUIDE_SNAP_DISTANCE},
+    link_select_mode::not_link_selecting,
+    select::{pick_nearest_point, PickMode, SelectSet, Selected},
     EditMode,
 };
 use crate::{
-    ui::viewport::ViewportInfo,
+    ui::{settings::AppSettings, viewport::ViewportInfo},
     util::{get_ray_from_cam, ui_viewport_to_ndc, RaycastFromCam},
-    viewer::{camera::Gizmo2dCam, kcl_model::KCLModelSection, kmp::checkpoints::CheckpointHeight},
+    viewer::{
+        camera::Gizmo2dCam,
+        kcl_model::KCLModelSection,
+        kmp::{checkpoints::CheckpointHeight, sidecar::KmpSidecar},
+    },
 };
 use bevy::{prelude::*, utils::HashMap};
 use bevy_mod_raycast::prelude::*;
@@ -21,7 +27,7 @@ pub enum SnapTo {
 }
 
 pub fn tweak_plugin(app: &mut App) {
-    app.add_systems(Update, tweak_interaction.after(SelectSet));
+    app.add_systems(Update, tweak_interaction.after(SelectSet).run_if(not_link_selecting));
 }
 
 #[derive(Resource, Clone, Debug)]
@@ -50,6 +56,10 @@ pub fn tweak_interaction(
     checkpoint_height: Res<CheckpointHeight>,
     q_kcl: Query<(), With<KCLModelSection>>,
     mut ev_just_created_point: EventReader<JustCreatedPoint>,
+    pick_mode: Res<PickMode>,
+    settings: Res<AppSettings>,
+    rulers: Res<Rulers>,
+    sidecar: Option<Res<KmpSidecar>>,
 ) {
     if *edit_mode != EditMode::Tweak || !viewport_info.mouse_in_viewport || q_selected.is_empty() {
         return;
@@ -73,12 +83,25 @@ pub fn tweak_interaction(
 
     if mouse_buttons.just_pressed(MouseButton::Left) {
         // get the transform of the thing the mouse has just clicked on
-        let ray = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
-            .filter(&|e| q_selected.contains(e))
-            .cast();
+        // allow for the same leeway as when the point was clicked on to select it
+        let picked = if pick_mode.hits_points() {
+            let ray = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+                .filter(&|e| q_selected.contains(e))
+                .cast();
+            pick_nearest_point(
+                cam,
+                viewport_info.viewport_rect,
+                mouse_pos,
+                settings.pick.radius,
+                ray.first().map(|x| (x.0, true)),
+                q_selected.iter().map(|x| (x.0, x.1.translation, true)),
+            )
+        } else {
+            None
+        };
 
-        let mouse_over_entity = match ray.first() {
-            Some(e) => e.0,
+        let mouse_over_entity = match picked {
+            Some(e) => e,
             // if there is no intersection, then deal with the possibility that we just created a checkpoint,
             // so want to interact with the right hand node of the newly created cp
             None => {
@@ -148,24 +171,30 @@ pub fn tweak_interaction(
         }
     };
 
+    // if not possible to snap, move the point in the camera plane based on where we started dragging the point
+    let main_pos = snap_pos.or_else(|| {
+        let camera_plane =
+            InfinitePlane3d::new((-tweak_interaction.initial_interaction_point + cam.1.translation()).normalize());
+        let camera_plane_origin = tweak_interaction.initial_interaction_point;
+        let dist = cam_ray.intersect_plane(camera_plane_origin, camera_plane)?;
+        Some(cam_ray.get_point(dist))
+    });
+    let Some(mut main_pos) = main_pos else {
+        return;
+    };
+
+    // line the point being dragged up with any guides it gets close to
+    if let Some(sidecar) = sidecar.filter(|_| rulers.snap && rulers.active(&settings)) {
+        if let Some(mapping) = TopDownMapping::new(cam, viewport_info.viewport_rect) {
+            let max_distance = GUIDE_SNAP_DISTANCE * mapping.units_per_px();
+            main_pos = snap_to_guides(main_pos, &sidecar.guides, max_distance);
+        }
+    }
+
     for mut selected in q_selected.iter_mut() {
         let Some(position_difference) = tweak_interaction.position_differences.get(&selected.0) else {
             continue;
         };
-
-        if let Some(snap_pos) = snap_pos {
-            // snap if possible
-            selected.1.translation = snap_pos + *position_difference;
-        } else {
-            // if not possible to snap, move the point in the camera plane based on where we started dragging the point
-            let camera_plane =
-                InfinitePlane3d::new((-tweak_interaction.initial_interaction_point + cam.1.translation()).normalize());
-            let camera_plane_origin = tweak_interaction.initial_interaction_point;
-
-            if let Some(dist) = cam_ray.intersect_plane(camera_plane_origin, camera_plane) {
-                let pos_on_plane = cam_ray.get_point(dist);
-                selected.1.translation = pos_on_plane + *position_difference;
-            }
-        }
+        selected.1.translation = main_pos + *position_difference;
     }
 }
@@ -0,0 +1,298 @@
+use crate::{
+    ui::ui_state::KmpFilePath,
+    viewer::kmp::{
+        components::{
+            AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, DisplayName, EnemyPathPoint, ItemPathPoint,
+            KmpCamera, KmpCameraIntroStart, KmpSelectablePoint, LabelColor, Object, PathOverallStart, PointNote,
+            RespawnPoint, RoutePoint, StartPoint, TrackInfo,
+        },
+        header_values::OpenedHeaderValues,
+        ordering::OrderId,
+        path::{EntityPathGroups, KmpPathNode},
+        respawn_kmp,
+        sections::add_for_all_components,
+        KmpState,
+    },
+};
+use bevy::prelude::*;
+use bevy_egui::EguiContext;
+use std::{io::Cursor, path::PathBuf};
+
+pub fn undo_plugin(app: &mut App) {
+    app.init_resource::<UndoHistory>()
+        .add_event::<Undo>()
+        .add_event::<Redo>()
+        .add_systems(
+            Update,
+            (
+                clear_undo_history.run_if(resource_exists_and_changed::<KmpFilePath>),
+                undo_redo,
+                record_undo_history,
+            )
+                .chain(),
+        )
+        .add_systems(Update, notice_point_changes.before(record_undo_history));
+    add_for_all_components!(@system app, notice_section_changes);
+}
+
+#[derive(Event, Default)]
+pub struct Undo;
+#[derive(Event, Default)]
+pub struct Redo;
+
+// how many states are kept to undo back to
+const MAX_UNDO_LEN: usize = 100;
+
+/// Snapshots of the whole KMP taken after each edit, so that any edit (moving points, editing components,
+/// creating, deleting, linking or unlinking) can be undone by respawning the state from before it. The selection,
+/// pins and original indices of the points are kept in each snapshot, so they come back with it.
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    undo: Vec<KmpSnapshot>,
+    redo: Vec<KmpSnapshot>,
+    /// The state the KMP was in when it was last checked
+    current: Option<KmpSnapshot>,
+    /// Set after the KMP has been opened or respawned (e.g. by undoing or redoing), as the spawned entities might not be
+    /// written out exactly the same way until their paths and ordering have been recalculated, so the next state
+    /// recorded once nothing has changed for a frame replaces the current one instead of being a change
+    rebaseline: bool,
+    /// Set when something the KMP is built from has changed, so that a snapshot is taken once the edit is finished
+    changed: bool,
+    /// The file the history belongs to
+    path: Option<PathBuf>,
+    /// The written file as it was when it was last opened or saved
+    saved: Option<Vec<u8>>,
+}
+impl UndoHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    /// Whether the KMP has changed since it was last opened or saved, as of the last time it was checked
+    pub fn has_unsaved_changes(&self) -> bool {
+        match (&self.current, &self.saved) {
+            (Some(current), Some(saved)) => current.bytes != *saved,
+            _ => false,
+        }
+    }
+    /// The written file as it was when it was last opened or saved
+    pub fn saved(&self) -> Option<&[u8]> {
+        self.saved.as_deref()
+    }
+    /// Record the written file as the saved state
+    pub fn mark_saved(&mut self, bytes: Vec<u8>) {
+        self.saved = Some(bytes);
+    }
+    /// Call after putting back a history which was taken out of the world, as the KMP it belongs to has just been
+    /// respawned
+    pub fn restored(&mut self) {
+        self.rebaseline = true;
+        self.changed = true;
+    }
+    /// Record any change made since the KMP was last checked, and return the state of the KMP as it is now, or `None`
+    /// if it is still being spawned
+    pub fn record_latest(world: &mut World) -> Option<KmpState> {
+        record_change(world);
+        let history = world.resource::<UndoHistory>();
+        Some(history.current.as_ref()?.state.clone())
+    }
+}
+
+struct KmpSnapshot {
+    state: KmpState,
+    // the written file, for quickly checking whether anything has changed
+    bytes: Vec<u8>,
+}
+impl KmpSnapshot {
+    fn take(world: &mut World) -> Option<Self> {
+        // the KMP can't be built while a file is still being spawned
+        if !world.contains_resource::<TrackInfo>()
+            || !world.contains_resource::<EntityPathGroups<EnemyPathPoint>>()
+            || !world.contains_resource::<EntityPathGroups<ItemPathPoint>>()
+            || !world.contains_resource::<EntityPathGroups<Checkpoint>>()
+        {
+            return None;
+        }
+        let state = KmpState::take(world);
+        let mut bytes = Cursor::new(Vec::new());
+        state.kmp.clone().write(&mut bytes).ok()?;
+        Some(Self {
+            state,
+            bytes: bytes.into_inner(),
+        })
+    }
+    // only changes to the KMP and the metadata of its points are undoable, not selecting or pinning points
+    fn is_same(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+            && self.state.shown_areas == other.state.shown_areas
+            && self.state.point_metadata == other.state.point_metadata
+    }
+}
+
+// record any change made since the KMP was last checked
+fn record_change(world: &mut World) {
+    let Some(snapshot) = KmpSnapshot::take(world) else {
+        return;
+    };
+    let mut history = world.resource_mut::<UndoHistory>();
+    // the first state recorded after opening the file is the saved state
+    if history.saved.is_none() {
+        history.saved = Some(snapshot.bytes.clone());
+    }
+    if history.rebaseline {
+        history.rebaseline = false;
+        history.current = Some(snapshot);
+        return;
+    }
+    match history.current.take() {
+        Some(current) if !current.is_same(&snapshot) => {
+            history.undo.push(current);
+            if history.undo.len() > MAX_UNDO_LEN {
+                history.undo.remove(0);
+            }
+            history.redo.clear();
+        }
+        _ => {}
+    }
+    history.current = Some(snapshot);
+}
+
+// the components of the points in a section changing, or points being deleted from it
+fn notice_section_changes<T: Component>(
+    q_changed: Query<(), Changed<T>>,
+    mut removed: RemovedComponents<T>,
+    mut history: ResMut<UndoHistory>,
+) {
+    let removed = removed.read().count() > 0;
+    if !q_changed.is_empty() || removed {
+        history.changed = true;
+    }
+}
+
+// everything else the KMP is built from, such as the points' transforms and links, and the track info
+fn notice_point_changes(
+    q_changed: Query<
+        (),
+        (
+            With<KmpSelectablePoint>,
+            Or<(
+                Changed<Transform>,
+                Changed<KmpPathNode>,
+                Changed<OrderId>,
+                Added<PathOverallStart>,
+                Added<KmpCameraIntroStart>,
+                Changed<DisplayName>,
+                Changed<PointNote>,
+                Changed<LabelColor>,
+            )>,
+        ),
+    >,
+    mut removed_links: RemovedComponents<KmpPathNode>,
+    mut removed_starts: RemovedComponents<PathOverallStart>,
+    mut removed_intro_starts: RemovedComponents<KmpCameraIntroStart>,
+    mut removed_names: RemovedComponents<DisplayName>,
+    mut removed_notes: RemovedComponents<PointNote>,
+    mut removed_colors: RemovedComponents<LabelColor>,
+    track_info: Option<Res<TrackInfo>>,
+    header_values: Option<Res<OpenedHeaderValues>>,
+    enemy_paths: Option<Res<EntityPathGroups<EnemyPathPoint>>>,
+    item_paths: Option<Res<EntityPathGroups<ItemPathPoint>>>,
+    checkpoint_paths: Option<Res<EntityPathGroups<Checkpoint>>>,
+    mut history: ResMut<UndoHistory>,
+) {
+    let removed = removed_links.read().count()
+        + removed_starts.read().count()
+        + removed_intro_starts.read().count()
+        + removed_names.read().count()
+        + removed_notes.read().count()
+        + removed_colors.read().count()
+        > 0;
+    if !q_changed.is_empty()
+        || removed
+        || track_info.is_some_and(|x| x.is_changed())
+        || header_values.is_some_and(|x| x.is_changed())
+        || enemy_paths.is_some_and(|x| x.is_changed())
+        || item_paths.is_some_and(|x| x.is_changed())
+        || checkpoint_paths.is_some_and(|x| x.is_changed())
+    {
+        history.changed = true;
+    }
+}
+
+// take a snapshot once an edit is finished, so that e.g. a drag, or typing a number in, is undone in one go
+fn record_undo_history(world: &mut World) {
+    let mut history = world.resource_mut::<UndoHistory>();
+    if history.rebaseline {
+        // wait for the spawned KMP to settle
+        if !std::mem::take(&mut history.changed) {
+            record_change(world);
+        }
+        return;
+    }
+    if !history.changed {
+        return;
+    }
+    // wait until the mouse and keys are let go, and nothing is being typed into
+    let mouse_pressed = world
+        .resource::<ButtonInput<MouseButton>>()
+        .get_pressed()
+        .next()
+        .is_some();
+    let keys_pressed = world.resource::<ButtonInput<KeyCode>>().get_pressed().next().is_some();
+    let typing = world
+        .query::<&mut EguiContext>()
+        .iter_mut(world)
+        .any(|mut ctx| ctx.get_mut().wants_keyboard_input());
+    if mouse_pressed || keys_pressed || typing {
+        return;
+    }
+    world.resource_mut::<UndoHistory>().changed = false;
+    record_change(world);
+}
+
+fn undo_redo(world: &mut World) {
+    let undo = world.resource_mut::<Events<Undo>>().drain().count() > 0;
+    let redo = world.resource_mut::<Events<Redo>>().drain().count() > 0;
+    if !undo && !redo {
+        return;
+    }
+    // make sure the latest change can be undone even if it hasn't been checked for yet
+    if !world.resource::<UndoHistory>().rebaseline {
+        record_change(world);
+    }
+    let mut history = world.resource_mut::<UndoHistory>();
+    let history = &mut *history;
+    let Some(current) = history.current.take() else {
+        return;
+    };
+    let (from, to) = if undo {
+        (&mut history.undo, &mut history.redo)
+    } else {
+        (&mut history.redo, &mut history.undo)
+    };
+    let Some(snapshot) = from.pop() else {
+        history.current = Some(current);
+        return;
+    };
+    to.push(current);
+    history.rebaseline = true;
+    history.changed = true;
+
+    respawn_kmp(world, &snapshot.state);
+    world.resource_mut::<UndoHistory>().current = Some(snapshot);
+}
+
+// the history belongs to the file it was made from, and is kept if that file has just been switched back to
+fn clear_undo_history(mut history: ResMut<UndoHistory>, path: Res<KmpFilePath>) {
+    if history.path.as_ref() == Some(&path.0) {
+        return;
+    }
+    *history = UndoHistory {
+        path: Some(path.0.clone()),
+        rebaseline: true,
+        changed: true,
+        ..default()
+    };
+}
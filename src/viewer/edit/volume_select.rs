@@ -0,0 +1,178 @@
+use super::select::Selected;
+use crate::viewer::{
+    kcl_model::KCLModelSection,
+    kmp::{
+        components::{
+            AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
+            KmpSelectablePoint, Object, RespawnPoint, RoutePoint, StartPoint,
+        },
+        ordering::RefreshOrdering,
+        sections::KmpEditMode,
+    },
+};
+use bevy::{color::palettes::css, ecs::system::SystemState, prelude::*, utils::HashSet};
+use bevy_mod_raycast::prelude::*;
+use strum_macros::{Display, EnumIter};
+
+pub fn volume_select_plugin(app: &mut App) {
+    app.add_event::<ApplyVolumeSelect>().add_systems(
+        Update,
+        (
+            draw_volume.run_if(resource_exists::<VolumeSelect>),
+            apply_volume_select.run_if(on_event::<ApplyVolumeSelect>()),
+        ),
+    );
+}
+
+/// A box or sphere which points can be selected or deleted inside of, for clearing out parts of a track.
+/// The tool is open while this resource exists.
+#[derive(Resource)]
+pub struct VolumeSelect {
+    pub shape: VolumeShape,
+    pub center: Vec3,
+    /// Half the size of the box along each axis
+    pub half_extents: Vec3,
+    pub radius: f32,
+    /// Which sections are affected
+    pub sections: HashSet<KmpEditMode>,
+    pub kcl_filter: KclSideFilter,
+}
+impl VolumeSelect {
+    pub fn new(center: Vec3, mode: KmpEditMode) -> Self {
+        Self {
+            shape: VolumeShape::default(),
+            center,
+            half_extents: Vec3::splat(5000.),
+            radius: 5000.,
+            sections: HashSet::from_iter([mode]),
+            kcl_filter: KclSideFilter::default(),
+        }
+    }
+    pub fn contains(&self, pos: Vec3) -> bool {
+        match self.shape {
+            VolumeShape::Box => (pos - self.center).abs().cmple(self.half_extents).all(),
+            VolumeShape::Sphere => pos.distance(self.center) <= self.radius,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum VolumeShape {
+    #[default]
+    Box,
+    Sphere,
+}
+
+/// Whether to only affect points on one side of the KCL surface
+#[derive(Default, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum KclSideFilter {
+    #[default]
+    #[strum(serialize = "All Points")]
+    All,
+    /// Points with KCL directly below them
+    #[strum(serialize = "Above KCL")]
+    Above,
+    /// Points with KCL directly above them
+    #[strum(serialize = "Below KCL")]
+    Below,
+}
+
+/// Send this event to select (or delete) every point inside the [`VolumeSelect`] volume
+#[derive(Event)]
+pub struct ApplyVolumeSelect {
+    pub delete: bool,
+}
+
+fn draw_volume(mut gizmos: Gizmos, volume: Res<VolumeSelect>) {
+    let color = css::ORANGE_RED;
+    match volume.shape {
+        VolumeShape::Box => gizmos.cuboid(
+            Transform::from_translation(volume.center).with_scale(volume.half_extents * 2.),
+            color,
+        ),
+        VolumeShape::Sphere => {
+            gizmos.sphere(volume.center, Quat::IDENTITY, volume.radius, color);
+        }
+    }
+}
+
+fn points_in_section(world: &mut World, mode: KmpEditMode) -> Vec<(Entity, Vec3)> {
+    fn points<T: Component>(world: &mut World) -> Vec<(Entity, Vec3)> {
+        world
+            .query_filtered::<(Entity, &Transform), (With<T>, With<KmpSelectablePoint>)>()
+            .iter(world)
+            .map(|(e, t)| (e, t.translation))
+            .collect()
+    }
+    match mode {
+        KmpEditMode::StartPoints => points::<StartPoint>(world),
+        KmpEditMode::EnemyPaths => points::<EnemyPathPoint>(world),
+        KmpEditMode::ItemPaths => points::<ItemPathPoint>(world),
+        KmpEditMode::Checkpoints => points::<Checkpoint>(world),
+        KmpEditMode::RespawnPoints => points::<RespawnPoint>(world),
+        KmpEditMode::Objects => points::<Object>(world),
+        KmpEditMode::Routes => points::<RoutePoint>(world),
+        KmpEditMode::Areas => points::<AreaPoint>(world),
+        KmpEditMode::Cameras => points::<KmpCamera>(world),
+        KmpEditMode::CannonPoints => points::<CannonPoint>(world),
+        KmpEditMode::BattleFinishPoints => points::<BattleFinishPoint>(world),
+        KmpEditMode::TrackInfo => Vec::new(),
+    }
+}
+
+fn apply_volume_select(world: &mut World) {
+    let Some(delete) = world
+        .resource_mut::<Events<ApplyVolumeSelect>>()
+        .drain()
+        .last()
+        .map(|x| x.delete)
+    else {
+        return;
+    };
+    let Some(volume) = world.get_resource::<VolumeSelect>() else {
+        return;
+    };
+    let (sections, kcl_filter) = (volume.sections.clone(), volume.kcl_filter);
+
+    let mut inside = Vec::new();
+    for mode in sections {
+        for (e, pos) in points_in_section(world, mode) {
+            if world.resource::<VolumeSelect>().contains(pos) {
+                inside.push((e, pos));
+            }
+        }
+    }
+
+    if kcl_filter != KclSideFilter::All {
+        let mut ss = SystemState::<(Raycast, Query<(), With<KCLModelSection>>)>::new(world);
+        let (mut raycast, q_kcl) = ss.get_mut(world);
+        let filter = |e: Entity| q_kcl.contains(e);
+        let raycast_settings = RaycastSettings {
+            visibility: RaycastVisibility::Ignore,
+            ..default()
+        }
+        .with_filter(&filter);
+        let dir = match kcl_filter {
+            KclSideFilter::Above => Vec3::NEG_Y,
+            _ => Vec3::Y,
+        };
+        inside.retain(|(_, pos)| !raycast.cast_ray(Ray3d::new(*pos, dir), &raycast_settings).is_empty());
+    }
+
+    if delete {
+        for (e, _) in inside {
+            if let Some(e) = world.get_entity_mut(e) {
+                e.despawn_recursive();
+            }
+        }
+        world.send_event(RefreshOrdering);
+        return;
+    }
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for e in selected {
+        world.entity_mut(e).remove::<Selected>();
+    }
+    for (e, _) in inside {
+        world.entity_mut(e).insert(Selected);
+    }
+}
@@ -0,0 +1,92 @@
+use super::kcl_model::{KCLModelSection, KclModelUpdated};
+use crate::{
+    ui::settings::AppSettings,
+    util::kcl_file::{Kcl, KclFlag},
+};
+use bevy::prelude::*;
+use strum::IntoEnumIterator;
+
+pub fn kcl_heatmap_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            spawn_heatmap.run_if(resource_added::<Kcl>),
+            update_heatmap_visibility.run_if(on_event::<KclModelUpdated>()),
+        ),
+    );
+}
+
+/// Overlay on top of the drivable parts of the KCL, coloured by how fast karts can drive on them
+#[derive(Component)]
+pub struct KclHeatmap;
+
+/// Roughly how fast karts go on each drivable surface compared to normal road, or `None` if it isn't drivable.
+/// The real values depend on the vehicle, but these are close enough to see where paths should go.
+pub fn speed_multiplier(flag: KclFlag) -> Option<f32> {
+    use KclFlag::*;
+    match flag {
+        Road1 | Road2 | SlipperyRoad1 | SlipperyRoad2 | JumpPad | HalfPipeRamp | MovingRoad | StickyRoad
+        | RotatingRoad => Some(1.),
+        WeakOffroad => Some(0.8),
+        Offroad => Some(0.6),
+        HeavyOffroad => Some(0.35),
+        MovingWater => Some(0.85),
+        BoostPanel | BoostRamp => Some(1.3),
+        _ => None,
+    }
+}
+
+// red for the slowest surfaces, through yellow, to green for normal road and cyan for boosts
+fn heatmap_color(multiplier: f32) -> Color {
+    let hue = multiplier.clamp(0., 1.3) / 1.3 * 180.;
+    Color::hsla(hue, 1., 0.5, 0.75)
+}
+
+fn spawn_heatmap(
+    mut commands: Commands,
+    q_kcl: Query<(Entity, &KCLModelSection, &Handle<Mesh>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<AppSettings>,
+) {
+    for (e, section, mesh) in q_kcl.iter() {
+        let Some(multiplier) = KclFlag::iter().nth(section.0).and_then(speed_multiplier) else {
+            continue;
+        };
+        let heatmap = commands
+            .spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: materials.add(StandardMaterial {
+                        base_color: heatmap_color(multiplier),
+                        unlit: true,
+                        double_sided: true,
+                        cull_mode: None,
+                        alpha_mode: AlphaMode::Blend,
+                        // draw on top of the KCL model without z-fighting
+                        depth_bias: 100.,
+                        ..default()
+                    }),
+                    visibility: heatmap_visibility(&settings),
+                    ..default()
+                },
+                KclHeatmap,
+            ))
+            .id();
+        // as a child so it gets despawned along with the KCL model, and hidden along with its section
+        commands.entity(e).add_child(heatmap);
+    }
+}
+
+fn update_heatmap_visibility(mut q_heatmap: Query<&mut Visibility, With<KclHeatmap>>, settings: Res<AppSettings>) {
+    for mut visibility in q_heatmap.iter_mut() {
+        visibility.set_if_neq(heatmap_visibility(&settings));
+    }
+}
+
+fn heatmap_visibility(settings: &AppSettings) -> Visibility {
+    if settings.kcl_model.show_heatmap {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
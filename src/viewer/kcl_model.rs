@@ -1,6 +1,11 @@
+use super::kcl_reachability::ReachableArea;
 use crate::{
     ui::{settings::AppSettings, update_ui::KclFileSelected},
-    util::{kcl_file::Kcl, try_despawn},
+    util::{
+        kcl_file::{kcl_variant, Kcl, KCL_VARIANTS},
+        szs::{read_szs, COURSE_KCL},
+        try_despawn,
+    },
 };
 use bevy::{
     prelude::*,
@@ -24,12 +29,20 @@ pub struct KclModelUpdated;
 pub struct KclModelSettings {
     pub visible: [bool; 32],
     pub color: [Color; 32],
+    /// Whether each variant of each flag is shown, on top of whether the flag itself is
+    pub variant_visible: [[bool; KCL_VARIANTS]; 32],
+    /// Shift the colour of each variant of a flag, so that e.g. boost panels of different strengths can be told apart
+    pub color_by_variant: bool,
     pub backface_culling: bool,
+    /// Overlay the drivable surfaces with a heatmap of how fast karts can drive on them
+    pub show_heatmap: bool,
 }
 impl Default for KclModelSettings {
     fn default() -> Self {
         Self {
             visible: [true; 32],
+            variant_visible: [[true; KCL_VARIANTS]; 32],
+            color_by_variant: false,
             color: [
                 Color::srgba(1.0, 1.0, 1.0, 1.0), // road
                 Color::srgba(1.0, 0.9, 0.8, 1.0), // slippery road (sand/dirt)
@@ -65,6 +78,7 @@ impl Default for KclModelSettings {
                 Color::srgba(0.8, 0.7, 0.8, 1.0), // special wall
             ],
             backface_culling: false,
+            show_heatmap: false,
         }
     }
 }
@@ -73,6 +87,45 @@ impl Default for KclModelSettings {
 #[derive(Component)]
 pub struct KCLModelSection(pub usize);
 
+/// The variant of the triangles in a part of the KCL model, which is split up by variant as well as flag
+#[derive(Component)]
+pub struct KclModelVariant(pub usize);
+
+impl KclModelSettings {
+    pub fn is_visible(&self, flag: usize, variant: usize) -> bool {
+        self.visible[flag] && self.variant_visible[flag][variant]
+    }
+    /// The colour of a flag, with its hue turned further round for each variant if colouring by variant
+    pub fn variant_color(&self, flag: usize, variant: usize) -> Color {
+        let color = self.color[flag];
+        if !self.color_by_variant || variant == 0 {
+            return color;
+        }
+        let hsla = Hsla::from(color);
+        Hsla {
+            hue: (hsla.hue + variant as f32 * 360. / KCL_VARIANTS as f32) % 360.,
+            // greys wouldn't change at all otherwise
+            saturation: hsla.saturation.max(0.6),
+            lightness: hsla.lightness.clamp(0.3, 0.7),
+            ..hsla
+        }
+        .into()
+    }
+}
+
+/// Remove the loaded KCL, its model and anything worked out from it, e.g. when switching to a track which doesn't
+/// have one
+pub fn unload_kcl(world: &mut World) {
+    let mut q = world.query_filtered::<Entity, With<KCLModelSection>>();
+    let model: Vec<_> = q.iter(world).collect();
+    for e in model {
+        // the heatmap of each section is a child of it
+        world.entity_mut(e).despawn_recursive();
+    }
+    ReachableArea::clear(world);
+    world.remove_resource::<Kcl>();
+}
+
 pub fn spawn_model(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -84,62 +137,85 @@ pub fn spawn_model(
     let Some(ev) = ev_kcl_file_selected.read().next() else {
         return;
     };
-    if ev.0.extension() != Some(OsStr::new("kcl")) {
-        return;
-    }
+    // open the KCL file and read it
+    let kcl = match ev.0.extension().and_then(OsStr::to_str) {
+        Some("kcl") => {
+            let kcl_file = File::open(ev.0.clone()).expect("could not open kcl file");
+            Kcl::read(kcl_file).expect("could not read kcl file")
+        }
+        // a course archive might not have a KCL in it, in which case keep whatever is loaded
+        Some("szs") => match read_szs(&ev.0).map(|x| x.file_reader(COURSE_KCL).map(Kcl::read)) {
+            Ok(Some(Ok(kcl))) => kcl,
+            _ => return,
+        },
+        _ => return,
+    };
     // despawn all entities with KCLModelSection (so that we have a clean slate)
     for entity in q_model.iter_mut() {
         try_despawn(&mut commands, entity);
     }
     commands.remove_resource::<Kcl>();
 
-    // open the KCL file and read it
-    let kcl_file = File::open(ev.0.clone()).expect("could not open kcl file");
-    let kcl = Kcl::read(kcl_file).expect("could not read kcl file");
-    // spawn the KCL model
-    for i in 0..32 {
-        let vertex_group = kcl.vertex_groups[i].clone();
-
-        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    // spawn the KCL model, with a part for each variant of each flag so they can be shown and coloured separately
+    for (i, vertex_group) in kcl.vertex_groups.iter().enumerate() {
+        let mut variants: [Vec<Vec3>; KCL_VARIANTS] = default();
+        for (triangle, flag) in vertex_group.vertices.chunks_exact(3).zip(vertex_group.flags.iter()) {
+            variants[kcl_variant(*flag) as usize].extend_from_slice(triangle);
+        }
+        for (variant, vertices) in variants.into_iter().enumerate() {
+            if vertices.is_empty() {
+                continue;
+            }
+            let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
 
-        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertex_group.vertices.clone());
-        mesh.compute_flat_normals();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+            mesh.compute_flat_normals();
 
-        let color = settings.kcl_model.color[i];
+            let color = settings.kcl_model.variant_color(i, variant);
 
-        commands.spawn((
-            PbrBundle {
-                mesh: meshes.add(mesh),
-                material: materials.add(StandardMaterial {
-                    base_color: color,
-                    cull_mode: if settings.kcl_model.backface_culling {
-                        Some(Face::Back)
+            commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(mesh),
+                    material: materials.add(StandardMaterial {
+                        base_color: color,
+                        cull_mode: if settings.kcl_model.backface_culling {
+                            Some(Face::Back)
+                        } else {
+                            None
+                        },
+                        double_sided: !settings.kcl_model.backface_culling,
+                        alpha_mode: if color.alpha() < 1. {
+                            AlphaMode::Blend
+                        } else {
+                            AlphaMode::Opaque
+                        },
+                        ..default()
+                    }),
+                    visibility: if settings.kcl_model.is_visible(i, variant) {
+                        Visibility::Inherited
                     } else {
-                        None
-                    },
-                    double_sided: !settings.kcl_model.backface_culling,
-                    alpha_mode: if color.alpha() < 1. {
-                        AlphaMode::Blend
-                    } else {
-                        AlphaMode::Opaque
+                        Visibility::Hidden
                     },
                     ..default()
-                }),
-                visibility: if settings.kcl_model.visible[i] {
-                    Visibility::Inherited
-                } else {
-                    Visibility::Hidden
                 },
-                ..default()
-            },
-            KCLModelSection(i),
-        ));
+                KCLModelSection(i),
+                KclModelVariant(variant),
+            ));
+        }
     }
     commands.insert_resource(kcl);
 }
 
 pub fn update_kcl_model(
-    mut q_kcl: Query<(&mut Visibility, &KCLModelSection, &mut Handle<StandardMaterial>), With<KCLModelSection>>,
+    mut q_kcl: Query<
+        (
+            &mut Visibility,
+            &KCLModelSection,
+            &KclModelVariant,
+            &mut Handle<StandardMaterial>,
+        ),
+        With<KCLModelSection>,
+    >,
     mut materials: ResMut<Assets<StandardMaterial>>,
     settings: Res<AppSettings>,
     mut ev_kcl_model_updated: EventReader<KclModelUpdated>,
@@ -151,15 +227,15 @@ pub fn update_kcl_model(
         ev_kcl_model_updated.clear();
     }
 
-    for (mut visibility, kcl_model_section, standard_material) in q_kcl.iter_mut() {
-        let i = kcl_model_section.0;
-        *visibility = if settings.kcl_model.visible[i] {
+    for (mut visibility, kcl_model_section, variant, standard_material) in q_kcl.iter_mut() {
+        let (i, variant) = (kcl_model_section.0, variant.0);
+        *visibility = if settings.kcl_model.is_visible(i, variant) {
             Visibility::Inherited
         } else {
             Visibility::Hidden
         };
         let material = materials.get_mut(standard_material.id()).unwrap();
-        material.base_color = settings.kcl_model.color[i];
+        material.base_color = settings.kcl_model.variant_color(i, variant);
         material.alpha_mode = if material.base_color.alpha() < 1. {
             AlphaMode::Blend
         } else {
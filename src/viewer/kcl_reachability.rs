@@ -0,0 +1,218 @@
+use super::{
+    kcl_heatmap::speed_multiplier,
+    kmp::{components::StartPoint, ordering::OrderId},
+};
+use crate::{
+    ui::{
+        errors::show_errors,
+        file_dialog::{DialogType, FileDialogResult},
+    },
+    util::kcl_file::{Kcl, KclFlag},
+};
+use anyhow::Context;
+use bevy::{
+    color::palettes::css,
+    ecs::event::ManualEventReader,
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+    utils::HashMap,
+};
+use std::{collections::VecDeque, fmt::Write as _, fs};
+use strum::IntoEnumIterator;
+
+pub fn kcl_reachability_plugin(app: &mut App) {
+    app.add_event::<ComputeReachableArea>().add_systems(
+        Update,
+        (
+            compute_reachable_area
+                .pipe(show_errors)
+                .run_if(on_event::<ComputeReachableArea>()),
+            export_reachable_area
+                .pipe(show_errors)
+                .run_if(on_event::<FileDialogResult>()),
+            clear_reachable_area.run_if(resource_added::<Kcl>),
+        ),
+    );
+}
+
+/// Send this event to flood fill the drivable parts of the KCL from the first start point
+#[derive(Event, Default)]
+pub struct ComputeReachableArea;
+
+/// Approximation of the drivable area karts can reach from the start line, and any drivable areas which can't be
+/// reached, which are likely to be mistakes in the KCL. Remove this resource with [`ReachableArea::clear`].
+#[derive(Resource)]
+pub struct ReachableArea {
+    /// Vertices of the reachable triangles, in groups of 3
+    pub reachable: Vec<Vec3>,
+    /// Vertices of the triangles of each unreachable island
+    pub islands: Vec<Vec<Vec3>>,
+}
+impl ReachableArea {
+    pub fn clear(world: &mut World) {
+        world.remove_resource::<ReachableArea>();
+        let overlays: Vec<_> = world
+            .query_filtered::<Entity, With<ReachableAreaOverlay>>()
+            .iter(world)
+            .collect();
+        for e in overlays {
+            world.entity_mut(e).despawn_recursive();
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ReachableAreaOverlay;
+
+// vertices closer together than this are treated as the same vertex, as the KCL stores triangles separately and
+// the positions calculated for shared corners don't always match exactly
+const VERTEX_SNAP: f32 = 10.;
+
+fn snap(v: Vec3) -> IVec3 {
+    (v / VERTEX_SNAP).round().as_ivec3()
+}
+
+fn compute_reachable_area(world: &mut World) -> anyhow::Result<()> {
+    let start_pos = world
+        .query::<(&Transform, &OrderId, &StartPoint)>()
+        .iter(world)
+        .min_by_key(|x| x.1 .0)
+        .map(|x| x.0.translation)
+        .context("there needs to be a start point to find the reachable area from")?;
+    let kcl = world
+        .get_resource::<Kcl>()
+        .context("a KCL needs to be open to find the reachable area")?;
+
+    // every drivable triangle in the KCL
+    let triangles: Vec<[Vec3; 3]> = KclFlag::iter()
+        .zip(kcl.vertex_groups.iter())
+        .filter(|(flag, _)| speed_multiplier(*flag).is_some())
+        .flat_map(|(_, group)| group.vertices.chunks_exact(3).map(|x| [x[0], x[1], x[2]]))
+        .collect();
+
+    let mut vertex_triangles: HashMap<IVec3, Vec<usize>> = HashMap::default();
+    for (i, tri) in triangles.iter().enumerate() {
+        for v in tri {
+            vertex_triangles.entry(snap(*v)).or_default().push(i);
+        }
+    }
+
+    // flood fill across triangles which share a corner, giving each connected region its own index
+    let mut region_of = vec![usize::MAX; triangles.len()];
+    let mut num_regions = 0;
+    for first in 0..triangles.len() {
+        if region_of[first] != usize::MAX {
+            continue;
+        }
+        region_of[first] = num_regions;
+        let mut queue = VecDeque::from([first]);
+        while let Some(i) = queue.pop_front() {
+            for v in triangles[i] {
+                for &j in vertex_triangles[&snap(v)].iter() {
+                    if region_of[j] == usize::MAX {
+                        region_of[j] = num_regions;
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        num_regions += 1;
+    }
+
+    let centroid = |tri: &[Vec3; 3]| (tri[0] + tri[1] + tri[2]) / 3.;
+    let start_region = triangles
+        .iter()
+        .enumerate()
+        .min_by(|a, b| {
+            let dist_a = centroid(a.1).distance_squared(start_pos);
+            let dist_b = centroid(b.1).distance_squared(start_pos);
+            dist_a.total_cmp(&dist_b)
+        })
+        .map(|x| region_of[x.0])
+        .context("the KCL has no drivable surfaces")?;
+
+    let mut reachable = Vec::new();
+    let mut islands = vec![Vec::new(); num_regions];
+    for (tri, region) in triangles.iter().zip(region_of) {
+        if region == start_region {
+            reachable.extend(tri);
+        } else {
+            islands[region].extend(tri);
+        }
+    }
+    islands.retain(|x| !x.is_empty());
+
+    ReachableArea::clear(world);
+    spawn_overlay(world, reachable.clone(), Color::from(css::LIME).with_alpha(0.4));
+    for island in islands.iter() {
+        spawn_overlay(world, island.clone(), Color::from(css::MAGENTA).with_alpha(0.8));
+    }
+    world.insert_resource(ReachableArea { reachable, islands });
+    Ok(())
+}
+
+fn spawn_overlay(world: &mut World, vertices: Vec<Vec3>, color: Color) {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.compute_flat_normals();
+    let mesh = world.resource_mut::<Assets<Mesh>>().add(mesh);
+    let material = world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+        base_color: color,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        alpha_mode: AlphaMode::Blend,
+        // draw on top of the KCL model (and heatmap) without z-fighting
+        depth_bias: 200.,
+        ..default()
+    });
+    world.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            ..default()
+        },
+        ReachableAreaOverlay,
+    ));
+}
+
+fn clear_reachable_area(world: &mut World) {
+    ReachableArea::clear(world);
+}
+
+// write the reachable area as an OBJ file, with each unreachable island as a separate object
+fn export_reachable_area(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<FileDialogResult>>,
+) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let Some(path) = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ExportReachableArea))
+        .map(|x| x.path.clone())
+        .last()
+    else {
+        return Ok(());
+    };
+    let area = world
+        .get_resource::<ReachableArea>()
+        .context("the reachable area needs to be computed before it can be exported")?;
+
+    let mut obj = String::new();
+    let mut num_vertices = 0;
+    let objects = std::iter::once(("reachable".to_string(), &area.reachable))
+        .chain(area.islands.iter().enumerate().map(|(i, x)| (format!("island_{i}"), x)));
+    for (name, vertices) in objects {
+        writeln!(obj, "o {name}")?;
+        for v in vertices.iter() {
+            writeln!(obj, "v {} {} {}", v.x, v.y, v.z)?;
+        }
+        for i in (0..vertices.len()).step_by(3) {
+            let i = num_vertices + i;
+            writeln!(obj, "f {} {} {}", i + 1, i + 2, i + 3)?;
+        }
+        num_vertices += vertices.len();
+    }
+    fs::write(&path, obj).context("could not write the reachable area file")?;
+    Ok(())
+}
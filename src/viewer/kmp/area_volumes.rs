@@ -0,0 +1,213 @@
+use super::components::{AreaKind, AreaPoint, AreaShape};
+use crate::{ui::settings::AppSettings, util::shapes::Cylinder};
+use bevy::{color::palettes::css, math::vec3, prelude::*, utils::HashMap};
+use strum::IntoEnumIterator;
+
+pub fn area_volumes_plugin(app: &mut App) {
+    app.init_resource::<AreaOverlaps>()
+        .add_systems(Startup, setup_area_volumes)
+        .add_systems(
+            Update,
+            (
+                update_area_volumes.run_if(resource_exists::<AreaVolumeAssets>),
+                find_area_overlaps,
+                draw_area_overlaps,
+            ),
+        );
+}
+
+// how many points along each side of an area are checked for being inside another area
+const OVERLAP_SAMPLES: usize = 8;
+const VOLUME_ALPHA: f32 = 0.12;
+
+/// The colour areas of this kind are shown in, so each kind stands out from the others
+pub fn area_kind_color(kind: AreaKind) -> Color {
+    let name: &'static str = kind.into();
+    let count = AreaKind::iter().count();
+    let index = AreaKind::iter()
+        .position(|x| <&'static str>::from(x) == name)
+        .unwrap_or_default();
+    Color::hsl(index as f32 * 360. / count as f32, 0.8, 0.55)
+}
+
+/// Whether two areas are of the same kind, whatever their settings
+pub fn same_area_kind(a: AreaKind, b: AreaKind) -> bool {
+    <&'static str>::from(a) == <&'static str>::from(b)
+}
+
+#[derive(Resource)]
+struct AreaVolumeAssets {
+    cube: Handle<Mesh>,
+    cylinder: Handle<Mesh>,
+    materials: HashMap<&'static str, Handle<StandardMaterial>>,
+}
+
+/// Marks the translucent volume showing the space an area covers, which is a child of the area
+#[derive(Component)]
+pub struct AreaVolume;
+
+fn setup_area_volumes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let materials = AreaKind::iter()
+        .map(|kind| {
+            let material = materials.add(StandardMaterial {
+                base_color: area_kind_color(kind).with_alpha(VOLUME_ALPHA),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            });
+            (kind.into(), material)
+        })
+        .collect();
+    commands.insert_resource(AreaVolumeAssets {
+        cube: meshes.add(Cuboid::from_size(Vec3::ONE)),
+        cylinder: meshes.add(Mesh::from(Cylinder {
+            height: 1.,
+            radius_bottom: 0.5,
+            radius_top: 0.5,
+            radial_segments: 32,
+            height_segments: 1,
+        })),
+        materials,
+    });
+}
+
+// keep a volume on each area matching its shape, size and kind, rebuilding it whenever the area changes
+fn update_area_volumes(
+    mut commands: Commands,
+    q_areas: Query<(Entity, Ref<AreaPoint>, Option<&Children>)>,
+    q_volumes: Query<(), With<AreaVolume>>,
+    assets: Res<AreaVolumeAssets>,
+    settings: Res<AppSettings>,
+) {
+    let show = settings.kmp_model.show_area_volumes;
+    for (e, area, children) in q_areas.iter() {
+        if !area.is_changed() && !settings.is_changed() {
+            continue;
+        }
+        for child in children
+            .iter()
+            .flat_map(|x| x.iter())
+            .filter(|x| q_volumes.contains(**x))
+        {
+            commands.entity(*child).despawn_recursive();
+        }
+        if !show {
+            continue;
+        }
+        let mesh = match area.shape {
+            AreaShape::Box => assets.cube.clone(),
+            AreaShape::Cylinder => assets.cylinder.clone(),
+        };
+        let kind: &'static str = area.kind.into();
+        let Some(material) = assets.materials.get(kind).cloned() else {
+            continue;
+        };
+        // the area's position is the centre of its base, and the area's rotation is inherited from it
+        let transform = Transform::from_translation(Vec3::Y * area.scale.y / 2.).with_scale(area.scale.abs());
+        commands.entity(e).with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh,
+                    material,
+                    transform,
+                    ..default()
+                },
+                AreaVolume,
+            ));
+        });
+    }
+}
+
+/// Two areas of the same kind but different priorities which overlap, so the game has to pick between them
+pub struct AreaOverlap {
+    /// The area with the higher priority, which takes effect where they overlap
+    pub winner: Entity,
+    pub loser: Entity,
+    /// A box around the space the areas share
+    pub bounds: (Vec3, Vec3),
+}
+
+/// The overlapping areas found when the overlap inspector is turned on
+#[derive(Resource, Default)]
+pub struct AreaOverlaps(pub Vec<AreaOverlap>);
+
+// points spread through the area, which can be checked against other areas to find where they overlap
+fn area_samples(area: &AreaPoint, transform: &Transform) -> Vec<Vec3> {
+    let step = |i: usize| (i as f32 + 0.5) / OVERLAP_SAMPLES as f32;
+    let mut samples = Vec::new();
+    for x in 0..OVERLAP_SAMPLES {
+        for y in 0..OVERLAP_SAMPLES {
+            for z in 0..OVERLAP_SAMPLES {
+                let local = vec3(step(x) - 0.5, step(y), step(z) - 0.5) * area.scale;
+                let pos = transform.translation + transform.rotation * local;
+                if area.contains(transform, pos) {
+                    samples.push(pos);
+                }
+            }
+        }
+    }
+    samples
+}
+
+fn find_area_overlaps(
+    mut overlaps: ResMut<AreaOverlaps>,
+    q_areas: Query<(Entity, &AreaPoint, &Transform)>,
+    q_changed: Query<(), (With<AreaPoint>, Or<(Changed<AreaPoint>, Changed<Transform>)>)>,
+    mut removed: RemovedComponents<AreaPoint>,
+    settings: Res<AppSettings>,
+) {
+    if !settings.kmp_model.show_area_overlaps {
+        overlaps.0.clear();
+        return;
+    }
+    let was_removed = removed.read().count() > 0;
+    if q_changed.is_empty() && !was_removed && !settings.is_changed() {
+        return;
+    }
+    overlaps.0.clear();
+
+    let areas: Vec<_> = q_areas.iter().collect();
+    let samples: Vec<_> = areas.iter().map(|(_, area, t)| area_samples(area, t)).collect();
+    for (i, (a_e, a, a_t)) in areas.iter().enumerate() {
+        for (j, (b_e, b, b_t)) in areas.iter().enumerate().skip(i + 1) {
+            if !same_area_kind(a.kind, b.kind) || a.priority == b.priority {
+                continue;
+            }
+            // check the points of each area against the other, so small areas inside big ones are still found
+            let shared: Vec<_> = samples[i]
+                .iter()
+                .filter(|pos| b.contains(b_t, **pos))
+                .chain(samples[j].iter().filter(|pos| a.contains(a_t, **pos)))
+                .copied()
+                .collect();
+            if shared.is_empty() {
+                continue;
+            }
+            let min = shared.iter().copied().reduce(Vec3::min).unwrap();
+            let max = shared.iter().copied().reduce(Vec3::max).unwrap();
+            let (winner, loser) = if a.priority > b.priority {
+                (*a_e, *b_e)
+            } else {
+                (*b_e, *a_e)
+            };
+            overlaps.0.push(AreaOverlap {
+                winner,
+                loser,
+                bounds: (min, max),
+            });
+        }
+    }
+}
+
+fn draw_area_overlaps(mut gizmos: Gizmos, overlaps: Res<AreaOverlaps>) {
+    for overlap in overlaps.0.iter() {
+        let (min, max) = overlap.bounds;
+        let transform = Transform::from_translation((min + max) / 2.).with_scale((max - min).max(Vec3::splat(1.)));
+        gizmos.cuboid(transform, css::MAGENTA);
+    }
+}
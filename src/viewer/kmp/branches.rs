@@ -0,0 +1,98 @@
+use super::{respawn_kmp, KmpState};
+use crate::ui::ui_state::KmpFilePath;
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+pub fn branches_plugin(app: &mut App) {
+    app.init_resource::<KmpBranches>().add_systems(
+        Update,
+        clear_branches.run_if(resource_exists_and_changed::<KmpFilePath>),
+    );
+}
+
+/// In-memory snapshots of the KMP which can be switched between, so that a risky change can be tried out
+/// while keeping the state from before it. Unlike undo, every branch stays accessible until it's deleted.
+#[derive(Resource, Default)]
+pub struct KmpBranches {
+    branches: Vec<KmpBranch>,
+    current: Option<usize>,
+    /// The file the branches belong to
+    path: Option<PathBuf>,
+}
+
+pub struct KmpBranch {
+    pub name: String,
+    state: KmpState,
+}
+
+impl KmpBranches {
+    pub fn branches(&self) -> &[KmpBranch] {
+        &self.branches
+    }
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+    pub fn current_mut(&mut self) -> Option<&mut KmpBranch> {
+        self.branches.get_mut(self.current?)
+    }
+
+    /// Start a new branch from the current state, leaving the branch we were on as it is now
+    pub fn create(world: &mut World) {
+        let state = KmpState::take(world);
+        let mut branches = world.resource_mut::<KmpBranches>();
+        if branches.current.is_none() {
+            branches.branches.push(KmpBranch {
+                name: "Main".into(),
+                state: state.clone(),
+            });
+        } else if let Some(current) = branches.current_mut() {
+            current.state = state.clone();
+        }
+        let name = format!("Branch {}", branches.branches.len());
+        branches.branches.push(KmpBranch { name, state });
+        branches.current = Some(branches.branches.len() - 1);
+    }
+
+    /// Store the current state in the branch we're on, then load another branch
+    pub fn switch_to(world: &mut World, index: usize) {
+        if world.resource::<KmpBranches>().current == Some(index) {
+            return;
+        }
+        let state = KmpState::take(world);
+        let mut branches = world.resource_mut::<KmpBranches>();
+        if let Some(current) = branches.current_mut() {
+            current.state = state;
+        }
+        let Some(branch) = branches.branches.get(index) else {
+            return;
+        };
+        let state = branch.state.clone();
+        branches.current = Some(index);
+
+        respawn_kmp(world, &state);
+    }
+
+    /// Delete a branch, which can't be the one we're currently on
+    pub fn delete(&mut self, index: usize) {
+        if self.current == Some(index) || index >= self.branches.len() {
+            return;
+        }
+        self.branches.remove(index);
+        if let Some(current) = self.current.as_mut() {
+            if *current > index {
+                *current -= 1;
+            }
+        }
+    }
+}
+
+// branches belong to the file they were made from
+fn clear_branches(mut branches: ResMut<KmpBranches>, path: Res<KmpFilePath>) {
+    if branches.path.as_ref() == Some(&path.0) {
+        return;
+    }
+    *branches = KmpBranches {
+        path: Some(path.0.clone()),
+        ..default()
+    };
+}
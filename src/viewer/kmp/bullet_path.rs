@@ -0,0 +1,153 @@
+use super::{
+    components::{ItemPathBulletHeight, ItemPathPoint},
+    kcl_check::{TRIGGER_FLAGS, WALL_FLAGS},
+    path::KmpPathNode,
+    sections::KmpEditMode,
+};
+use crate::{ui::settings::AppSettings, util::kcl_file::KclFlag, viewer::kcl_model::KCLModelSection};
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+};
+use bevy_mod_raycast::prelude::*;
+
+pub fn bullet_path_plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_bullet_corridor)
+        .add_systems(Update, update_bullet_corridor);
+}
+
+// points with a bullet control of 0 still get a thin ribbon, so that the path can be seen
+const MIN_CORRIDOR_HALF_WIDTH: f32 = 50.;
+// start the ray slightly above the point so points sitting just under the road still find it
+const RAY_START_OFFSET: f32 = 10.;
+
+/// The space a Bullet Bill travels through along the item paths, as a ribbon as wide as each point's bullet control
+/// range. The ribbon follows the height of the points which have 'Follow Point Height', and drops onto the KCL below
+/// the others, as the Bullet Bill works out its own height there.
+#[derive(Component)]
+pub struct BulletCorridor;
+
+fn spawn_bullet_corridor(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default()));
+    // the colour of each part of the ribbon comes from its vertex colours
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        BulletCorridor,
+    ));
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_bullet_corridor(
+    settings: Res<AppSettings>,
+    mode: Res<KmpEditMode>,
+    q_changed: Query<
+        (),
+        (
+            Or<(Changed<Transform>, Changed<ItemPathPoint>, Changed<KmpPathNode>)>,
+            With<ItemPathPoint>,
+        ),
+    >,
+    mut removed: RemovedComponents<ItemPathPoint>,
+    q_kcl_added: Query<(), Added<KCLModelSection>>,
+    q_item_points: Query<(&ItemPathPoint, &Transform, &KmpPathNode)>,
+    q_kcl: Query<&KCLModelSection>,
+    mut raycast: Raycast,
+    mut q_corridor: Query<(&Handle<Mesh>, &mut Visibility), With<BulletCorridor>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok((mesh, mut visibility)) = q_corridor.get_single_mut() else {
+        return;
+    };
+    let visible = settings.kmp_model.show_bullet_corridor && *mode == KmpEditMode::ItemPaths;
+    visibility.set_if_neq(if visible {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    });
+    let removed = removed.read().count() > 0;
+    if !visible
+        || (q_changed.is_empty() && !removed && q_kcl_added.is_empty() && !settings.is_changed() && !mode.is_changed())
+    {
+        return;
+    }
+
+    let is_flag = |flags: &[KclFlag], e: Entity| q_kcl.get(e).is_ok_and(|s| flags.iter().any(|f| *f as usize == s.0));
+    // the Bullet Bill rides along the road, not walls or triggers which have no collision
+    let filter = |e: Entity| q_kcl.contains(e) && !is_flag(&WALL_FLAGS, e) && !is_flag(&TRIGGER_FLAGS, e);
+    let raycast_settings = RaycastSettings {
+        // hidden parts of the KCL still have collision in game
+        visibility: RaycastVisibility::Ignore,
+        ..default()
+    }
+    .with_filter(&filter);
+    let mut corridor_pos = |point: &ItemPathPoint, pos: Vec3| {
+        if point.bullet_height == ItemPathBulletHeight::FollowPointHeight {
+            return pos;
+        }
+        let ray = Ray3d::new(pos + Vec3::Y * RAY_START_OFFSET, Vec3::NEG_Y);
+        raycast
+            .cast_ray(ray, &raycast_settings)
+            .first()
+            .map(|(_, hit)| hit.position())
+            .unwrap_or(pos)
+    };
+
+    let colors = &settings.kmp_model.color.item_paths;
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut vertex_colors: Vec<[f32; 4]> = Vec::new();
+    for (point, transform, node) in q_item_points.iter() {
+        for next_e in node.next_nodes.iter() {
+            let Ok((next_point, next_transform, _)) = q_item_points.get(*next_e) else {
+                continue;
+            };
+            let Some(side) = (next_transform.translation - transform.translation)
+                .with_y(0.)
+                .try_normalize()
+                .map(|dir| dir.cross(Vec3::Y))
+            else {
+                continue;
+            };
+            let half_width = |x: &ItemPathPoint| x.bullet_control.max(MIN_CORRIDOR_HALF_WIDTH);
+            let (start, end) = (
+                corridor_pos(point, transform.translation),
+                corridor_pos(next_point, next_transform.translation),
+            );
+            let (start_side, end_side) = (side * half_width(point), side * half_width(next_point));
+            let corners = [start - start_side, start + start_side, end + end_side, end - end_side];
+            // the parts where the Bullet Bill follows the points' height stand out from where it finds its own
+            let color = if point.bullet_height == ItemPathBulletHeight::FollowPointHeight {
+                colors.line
+            } else {
+                colors.point
+            }
+            .with_alpha(0.25);
+            positions.extend([corners[0], corners[1], corners[2], corners[0], corners[2], corners[3]]);
+            vertex_colors.extend([color.to_linear().to_f32_array(); 6]);
+        }
+    }
+
+    let Some(mesh) = meshes.get_mut(mesh) else {
+        return;
+    };
+    *mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vertex_colors);
+    mesh.compute_flat_normals();
+}
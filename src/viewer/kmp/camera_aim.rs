@@ -0,0 +1,139 @@
+use super::components::{KmpCamera, KmpSelectablePoint};
+use crate::{
+    ui::viewport::ViewportInfo,
+    util::{ui_viewport_to_ndc, RaycastFromCam},
+    viewer::{camera::Gizmo2dCam, kcl_model::KCLModelSection},
+};
+use bevy::{color::palettes::css, prelude::*};
+use bevy_mod_raycast::prelude::*;
+
+pub fn camera_aim_plugin(app: &mut App) {
+    app.add_event::<AimCameras>().add_systems(
+        Update,
+        (
+            pick_aim_target.run_if(resource_exists::<CameraAim>),
+            draw_camera_aim.run_if(resource_exists::<CameraAim>),
+            aim_cameras.run_if(on_event::<AimCameras>()),
+        ),
+    );
+}
+
+// the narrowest and widest field of view suggested, in degrees
+const MIN_ZOOM: f32 = 5.;
+const MAX_ZOOM: f32 = 120.;
+
+/// Points several cameras at the same target. The tool is open while this resource exists.
+#[derive(Resource)]
+pub struct CameraAim {
+    /// The cameras which were selected when the tool was opened, so that picking a target doesn't lose them
+    pub cameras: Vec<Entity>,
+    pub target: Vec3,
+    /// Also set the view start and view end of each camera to the target
+    pub set_view: bool,
+    /// Also set the zoom start and zoom end of each camera to the suggested zoom
+    pub set_zoom: bool,
+    /// How wide the thing being looked at is, which the suggested zoom fits in the view
+    pub subject_size: f32,
+    /// Whether the next click in the viewport sets the target
+    pub picking: bool,
+}
+impl CameraAim {
+    pub fn new(cameras: Vec<Entity>, target: Vec3) -> Self {
+        Self {
+            cameras,
+            target,
+            set_view: true,
+            set_zoom: false,
+            subject_size: 3000.,
+            picking: false,
+        }
+    }
+    /// The field of view in degrees at which the subject just fits in the view of a camera at this position
+    pub fn suggested_zoom(&self, pos: Vec3) -> f32 {
+        let distance = pos.distance(self.target);
+        if distance <= 0. {
+            return MAX_ZOOM;
+        }
+        (2. * (self.subject_size / 2. / distance).atan())
+            .to_degrees()
+            .clamp(MIN_ZOOM, MAX_ZOOM)
+    }
+}
+
+/// Send this event to point every camera in the [`CameraAim`] at its target, and close it
+#[derive(Event, Default)]
+pub struct AimCameras;
+
+// set the target to wherever on the KCL is clicked, or to the point that is clicked on
+fn pick_aim_target(
+    mut aim: ResMut<CameraAim>,
+    viewport_info: Res<ViewportInfo>,
+    q_window: Query<&Window>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    mut raycast: Raycast,
+    q_kcl: Query<(), With<KCLModelSection>>,
+    q_point: Query<&GlobalTransform, With<KmpSelectablePoint>>,
+) {
+    if !aim.picking
+        || !viewport_info.mouse_in_viewport
+        || viewport_info.mouse_on_overlayed_ui
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    let Some(cam) = q_camera.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let mouse_pos_ndc = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+    let intersections = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+        .filter(&|e| q_kcl.contains(e) || q_point.contains(e))
+        .cast();
+    let Some((e, intersection)) = intersections.first() else {
+        return;
+    };
+    aim.target = match q_point.get(*e) {
+        Ok(point) => point.translation(),
+        Err(_) => intersection.position(),
+    };
+    aim.picking = false;
+}
+
+fn draw_camera_aim(mut gizmos: Gizmos, aim: Res<CameraAim>, q_camera: Query<&Transform, With<KmpCamera>>) {
+    for transform in q_camera.iter_many(&aim.cameras) {
+        gizmos.line(transform.translation, aim.target, css::AQUA);
+    }
+    gizmos.sphere(aim.target, Quat::IDENTITY, 200., css::AQUA);
+}
+
+fn aim_cameras(world: &mut World) {
+    world.resource_mut::<Events<AimCameras>>().clear();
+    let Some(aim) = world.remove_resource::<CameraAim>() else {
+        return;
+    };
+    for e in aim.cameras.iter() {
+        let Some(mut transform) = world.get_mut::<Transform>(*e) else {
+            continue;
+        };
+        let pos = transform.translation;
+        if pos != aim.target {
+            // points face along their +Z axis, the opposite way to bevy's forward
+            transform.look_to(pos - aim.target, Vec3::Y);
+        }
+        let Some(mut camera) = world.get_mut::<KmpCamera>(*e) else {
+            continue;
+        };
+        if aim.set_view {
+            camera.view_start = aim.target;
+            camera.view_end = aim.target;
+        }
+        if aim.set_zoom {
+            let zoom = aim.suggested_zoom(pos);
+            camera.zoom_start = zoom;
+            camera.zoom_end = zoom;
+        }
+    }
+}
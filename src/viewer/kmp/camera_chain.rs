@@ -0,0 +1,134 @@
+use super::{
+    components::{KmpCamera, KmpCameraIntroStart, KmpCameraKind},
+    ordering::OrderId,
+};
+use bevy::{color::palettes::css, ecs::entity::EntityHashSet, prelude::*};
+
+pub fn camera_chain_plugin(app: &mut App) {
+    app.add_event::<RepairCameraChain>().add_systems(
+        Update,
+        (
+            propose_camera_chain.run_if(on_event::<RepairCameraChain>()),
+            draw_proposed_camera_chain.run_if(resource_exists::<ProposedCameraChain>),
+        ),
+    );
+}
+
+/// Send this event to work out a linear chain of opening cameras starting from the intro start,
+/// which is then shown for confirmation before any next indexes are changed
+#[derive(Event, Default)]
+pub struct RepairCameraChain;
+
+/// A rebuilt opening camera chain waiting to be confirmed. Remove this resource to cancel.
+#[derive(Resource)]
+pub struct ProposedCameraChain {
+    pub chain: Vec<Entity>,
+    /// Opening cameras left out of the chain, as their index is too high for a next index to point to them
+    pub unrepairable: Vec<Entity>,
+}
+
+/// Value of next_index for the last camera in a chain
+pub(super) const NO_NEXT_CAMERA: u8 = 0xff;
+
+fn is_opening_camera(camera: &KmpCamera) -> bool {
+    matches!(camera.kind, KmpCameraKind::OP_FixMoveAt | KmpCameraKind::OP_PathMoveAt)
+}
+
+// the next index which points to a camera, if it can be pointed to at all
+fn next_index_of(order_id: &OrderId) -> Option<u8> {
+    u8::try_from(order_id.0).ok().filter(|x| *x != NO_NEXT_CAMERA)
+}
+
+fn propose_camera_chain(
+    mut commands: Commands,
+    q_cameras: Query<(Entity, &KmpCamera, &Transform, &OrderId)>,
+    q_intro_start: Query<Entity, With<KmpCameraIntroStart>>,
+) {
+    let mut candidates: Vec<_> = q_cameras
+        .iter()
+        .filter(|(e, camera, ..)| is_opening_camera(camera) || q_intro_start.contains(*e))
+        .collect();
+    candidates.sort_by_key(|x| x.3 .0);
+
+    // start from the intro start, or the first opening camera if there isn't one
+    let start = q_intro_start.iter().next().or_else(|| candidates.first().map(|x| x.0));
+    let Some(mut cur_e) = start else {
+        return;
+    };
+    // the start is linked to by the intro start rather than a next index, so it can have any index
+    let (candidates, unrepairable): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|x| x.0 == cur_e || next_index_of(x.3).is_some());
+    let unrepairable = unrepairable.into_iter().map(|x| x.0).collect();
+
+    let mut chain = vec![cur_e];
+    let mut visited = EntityHashSet::from_iter([cur_e]);
+    while chain.len() < candidates.len() {
+        let Ok((_, cur_camera, cur_transform, _)) = q_cameras.get(cur_e) else {
+            break;
+        };
+        let unvisited = candidates.iter().filter(|x| !visited.contains(&x.0));
+
+        // keep the existing link if it still goes somewhere sensible,
+        // otherwise go to the nearest camera, preferring the ones that came next in the old order if it's a tie
+        let existing = candidates
+            .iter()
+            .find(|x| next_index_of(x.3) == Some(cur_camera.next_index) && !visited.contains(&x.0));
+        let next = existing.or_else(|| {
+            unvisited.min_by(|a, b| {
+                let dist_a = a.2.translation.distance(cur_transform.translation);
+                let dist_b = b.2.translation.distance(cur_transform.translation);
+                dist_a.total_cmp(&dist_b).then(a.3.cmp(b.3))
+            })
+        });
+        let Some(next) = next else {
+            break;
+        };
+        cur_e = next.0;
+        chain.push(cur_e);
+        visited.insert(cur_e);
+    }
+
+    commands.insert_resource(ProposedCameraChain { chain, unrepairable });
+}
+
+impl ProposedCameraChain {
+    /// Set the next index of every camera in the chain so that they go one after the other
+    pub fn apply(&self, world: &mut World) {
+        let order_ids: Vec<_> = self
+            .chain
+            .iter()
+            .map(|e| world.get::<OrderId>(*e).and_then(next_index_of))
+            .collect();
+        for (i, e) in self.chain.iter().enumerate() {
+            let next_index = order_ids.get(i + 1).copied().flatten().unwrap_or(NO_NEXT_CAMERA);
+            if let Some(mut camera) = world.get_mut::<KmpCamera>(*e) {
+                camera.next_index = next_index;
+            }
+        }
+        let Some(start_e) = self.chain.first().copied() else {
+            return;
+        };
+        let old_starts: Vec<_> = world
+            .query_filtered::<Entity, With<KmpCameraIntroStart>>()
+            .iter(world)
+            .collect();
+        for e in old_starts {
+            world.entity_mut(e).remove::<KmpCameraIntroStart>();
+        }
+        world.entity_mut(start_e).insert(KmpCameraIntroStart);
+    }
+}
+
+fn draw_proposed_camera_chain(
+    mut gizmos: Gizmos,
+    proposed: Res<ProposedCameraChain>,
+    q_transform: Query<&Transform, With<KmpCamera>>,
+) {
+    for pair in proposed.chain.windows(2) {
+        let Ok([prev, next]) = q_transform.get_many([pair[0], pair[1]]) else {
+            continue;
+        };
+        gizmos.arrow(prev.translation, next.translation, css::LIME);
+    }
+}
@@ -0,0 +1,227 @@
+use super::{
+    build_kmp,
+    components::{AreaPoint, KmpCamera, KmpCameraIntroStart, RoutePoint, StartPoint},
+    ordering::OrderId,
+    path::RecalcPaths,
+    point::spawn_point_section,
+    refresh_spawned_points,
+    routes::spawn_route_section,
+    section_import::import_section,
+    write_kmp_file, KmpErrors, KmpSectionIdEntityMap,
+};
+use crate::{
+    ui::{
+        errors::show_errors,
+        file_dialog::{DialogType, FileDialogResult},
+        util::{get_euler_rot, set_euler_rot},
+    },
+    util::kmp_file::{KmpFile, Section},
+};
+use anyhow::{bail, Context};
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use std::{fs, path::Path};
+
+pub fn camera_file_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        export_import_cameras
+            .pipe(show_errors)
+            .run_if(on_event::<FileDialogResult>()),
+    );
+}
+
+/// Name of the file cameras are exported to by default
+pub const CAMERA_FILE_NAME: &str = "cameras.kmp";
+
+// the AREA kind of camera areas, and the index which means an area or camera has no route or next camera
+const CAMERA_AREA_KIND: u8 = 0;
+const NO_INDEX: u8 = 0xff;
+
+/// Export all the cameras, the routes they follow and the areas that switch to them into a KMP with nothing else in
+/// but the start points, which imports can line the cameras back up with. The routes are renumbered to only include
+/// the ones the cameras use.
+pub fn export_cameras(world: &mut World, path: &Path) -> anyhow::Result<()> {
+    let (kmp, _) = build_kmp(world);
+
+    let mut routes: Vec<u8> = kmp.came.iter().map(|x| x.route).filter(|x| *x != NO_INDEX).collect();
+    routes.sort();
+    routes.dedup();
+    let new_route = |route: u8| routes.iter().position(|x| *x == route).map_or(NO_INDEX, |x| x as u8);
+
+    let mut came = kmp.came.clone();
+    for camera in came.iter_mut() {
+        camera.route = new_route(camera.route);
+    }
+    let mut poti = Section::new(routes.iter().map(|x| kmp.poti[*x as usize].clone()).collect());
+    poti.section_header.additional_value = poti.iter().flat_map(|x| x.iter()).count() as u16;
+    let area = Section::new(
+        kmp.area
+            .iter()
+            .filter(|x| x.kind == CAMERA_AREA_KIND)
+            .cloned()
+            .map(|mut x| {
+                x.route = NO_INDEX;
+                x
+            })
+            .collect(),
+    );
+
+    let out = KmpFile {
+        header: kmp.header.clone(),
+        ktpt: kmp.ktpt.clone(),
+        enpt: Section::new(Vec::new()),
+        enph: Section::new(Vec::new()),
+        itpt: Section::new(Vec::new()),
+        itph: Section::new(Vec::new()),
+        ckpt: Section::new(Vec::new()),
+        ckph: Section::new(Vec::new()),
+        gobj: Section::new(Vec::new()),
+        poti,
+        area,
+        came,
+        jgpt: Section::new(Vec::new()),
+        cnpt: Section::new(Vec::new()),
+        mspt: Section::new(Vec::new()),
+        stgi: kmp.stgi.clone(),
+    };
+    write_kmp_file(path, &out)?;
+    Ok(())
+}
+
+// moves positions and rotations from around the start line of one track to around the start line of another
+struct StartLineShift {
+    from: Vec3,
+    to: Vec3,
+    rotation: Quat,
+}
+impl StartLineShift {
+    fn new(from: &Transform, to: &Transform) -> Self {
+        Self {
+            from: from.translation,
+            to: to.translation,
+            rotation: to.rotation * from.rotation.inverse(),
+        }
+    }
+    fn pos(&self, pos: [f32; 3]) -> [f32; 3] {
+        (self.to + self.rotation * (Vec3::from(pos) - self.from)).into()
+    }
+    fn rot(&self, rot: [f32; 3]) -> [f32; 3] {
+        let mut transform = Transform::default();
+        set_euler_rot(rot.into(), &mut transform);
+        transform.rotation = self.rotation * transform.rotation;
+        get_euler_rot(&transform).into()
+    }
+}
+
+// the first start point of the KMP in the world, which is where the start line is
+fn start_line(world: &mut World) -> Option<Transform> {
+    let mut q = world.query_filtered::<(&Transform, &OrderId), With<StartPoint>>();
+    q.iter(world).min_by_key(|x| x.1 .0).map(|x| *x.0)
+}
+
+/// Add the cameras, routes and camera areas from an exported camera file after the ones already in the KMP, renumbering
+/// the next cameras and camera areas to point at the added cameras. The intro start moves to the added cameras' one.
+/// If `relative_to_start` is set, everything is moved and turned so it is in the same place relative to the start
+/// line as it was in the track it was exported from.
+pub fn import_cameras(world: &mut World, path: &Path, relative_to_start: bool) -> anyhow::Result<()> {
+    let data = fs::read(path).context("could not open camera file")?;
+    let mut kmp = KmpFile::read_any(&data).context("could not read camera file")?;
+    if kmp.came.is_empty() {
+        bail!("the file has no cameras");
+    }
+
+    if relative_to_start {
+        let Some(from) = kmp.ktpt.first() else {
+            bail!("the file has no start point to line the cameras up with");
+        };
+        let mut from_transform = Transform::from_translation(from.position.into());
+        set_euler_rot(from.rotation.into(), &mut from_transform);
+        let Some(to_transform) = start_line(world) else {
+            bail!("there is no start point to line the cameras up with");
+        };
+        let shift = StartLineShift::new(&from_transform, &to_transform);
+        for camera in kmp.came.iter_mut() {
+            camera.position = shift.pos(camera.position);
+            camera.rotation = shift.rot(camera.rotation);
+            camera.view_start = shift.pos(camera.view_start);
+            camera.view_end = shift.pos(camera.view_end);
+        }
+        for point in kmp.poti.iter_mut().flat_map(|x| x.points.iter_mut()) {
+            point.position = shift.pos(point.position);
+        }
+        for area in kmp.area.iter_mut() {
+            area.position = shift.pos(area.position);
+            area.rotation = shift.rot(area.rotation);
+        }
+    }
+
+    // only the areas which switch cameras come with them
+    kmp.area.entries.retain(|x| x.kind == CAMERA_AREA_KIND);
+    for area in kmp.area.iter_mut() {
+        area.route = NO_INDEX;
+    }
+
+    // the added cameras go after the existing ones, so anything pointing at them has to point that much further on
+    let existing = world.query_filtered::<(), With<KmpCamera>>().iter(world).count();
+    if existing + kmp.came.len() > NO_INDEX as usize {
+        bail!("there would be more cameras than a KMP can have");
+    }
+    let offset = existing as u8;
+    for camera in kmp.came.iter_mut().filter(|x| x.next_index != NO_INDEX) {
+        camera.next_index += offset;
+    }
+    for area in kmp.area.iter_mut().filter(|x| x.came_index != NO_INDEX) {
+        area.came_index += offset;
+    }
+
+    world.init_resource::<KmpErrors>();
+    let mut route_id_map = None;
+    import_section::<RoutePoint>(world, |world| route_id_map = Some(spawn_route_section(world, &kmp)));
+    world.insert_resource(route_id_map.unwrap());
+    let mut camera_id_map = None;
+    import_section::<KmpCamera>(world, |world| {
+        camera_id_map = Some(spawn_point_section::<KmpCamera>(world, &kmp))
+    });
+    import_section::<AreaPoint>(world, |world| {
+        spawn_point_section::<AreaPoint>(world, &kmp);
+    });
+    world.remove_resource::<KmpErrors>();
+    world.remove_resource::<KmpSectionIdEntityMap<RoutePoint>>();
+
+    // the intro start index is the first byte of the additional value
+    let intro_start = (kmp.came.section_header.additional_value >> 8) as u32;
+    if let Some(e) = camera_id_map.and_then(|x| x.get(&intro_start).copied()) {
+        let old: Vec<_> = world
+            .query_filtered::<Entity, With<KmpCameraIntroStart>>()
+            .iter(world)
+            .collect();
+        for old_e in old {
+            world.entity_mut(old_e).remove::<KmpCameraIntroStart>();
+        }
+        world.entity_mut(e).insert(KmpCameraIntroStart);
+    }
+
+    world.send_event(RecalcPaths::all());
+    refresh_spawned_points(world);
+    Ok(())
+}
+
+fn export_import_cameras(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<FileDialogResult>>,
+) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let results: Vec<_> = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ExportCameras | DialogType::ImportCameras(_)))
+        .map(|x| (x.dialog_type, x.path.clone()))
+        .collect();
+    for (dialog_type, path) in results {
+        match dialog_type {
+            DialogType::ExportCameras => export_cameras(world, &path)?,
+            DialogType::ImportCameras(relative_to_start) => import_cameras(world, &path, relative_to_start)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use super::{
+    components::{CannonPoint, CannonShootEffect, KmpSelectablePoint},
+    ordering::OrderId,
+    sections::KmpEditMode,
+};
+use crate::{
+    ui::settings::AppSettings,
+    util::kcl_file::{kcl_variant, Kcl, KclFlag, KCL_VARIANTS},
+};
+use bevy::prelude::*;
+
+pub fn cannons_plugin(app: &mut App) {
+    app.add_systems(Update, draw_cannon_trajectories.run_if(in_cannon_points_mode));
+}
+
+fn in_cannon_points_mode(mode: Res<KmpEditMode>) -> bool {
+    *mode == KmpEditMode::CannonPoints
+}
+
+// how high the curved shots go above the straight line between the trigger and the destination, as a fraction of the
+// distance between them. These are only estimates of the arcs seen in game, but are close enough to line up landings.
+const CURVED_ARC_HEIGHT: f32 = 0.2;
+const CURVED_SLOW_ARC_HEIGHT: f32 = 0.35;
+const TRAJECTORY_SEGMENTS: usize = 32;
+const TRIGGER_MARKER_RADIUS: f32 = 300.;
+
+/// The middle of the Cannon Trigger KCL of each variant, which is where the cannon point of the same index is shot
+/// from. The variant of the trigger is the index of the cannon point it sends players to.
+pub fn cannon_trigger_centres(kcl: &Kcl) -> [Option<Vec3>; KCL_VARIANTS] {
+    let group = &kcl.vertex_groups[KclFlag::CannonTrigger as usize];
+    let mut sums = [(Vec3::ZERO, 0); KCL_VARIANTS];
+    for (tri, flag) in group.vertices.chunks_exact(3).zip(group.flags.iter()) {
+        let sum = &mut sums[kcl_variant(*flag) as usize];
+        sum.0 += (tri[0] + tri[1] + tri[2]) / 3.;
+        sum.1 += 1;
+    }
+    sums.map(|(sum, count)| (count > 0).then(|| sum / count as f32))
+}
+
+/// Where along the path from the trigger to the destination a player is at `t` (from 0 to 1)
+pub fn cannon_trajectory_point(from: Vec3, to: Vec3, effect: CannonShootEffect, t: f32) -> Vec3 {
+    let height = match effect {
+        CannonShootEffect::Straight => 0.,
+        CannonShootEffect::Curved => CURVED_ARC_HEIGHT,
+        CannonShootEffect::CurvedSlow => CURVED_SLOW_ARC_HEIGHT,
+    } * from.distance(to);
+    from.lerp(to, t) + Vec3::Y * height * 4. * t * (1. - t)
+}
+
+// draw the path players take from each cannon trigger to its cannon point, and mark triggers without a cannon point
+fn draw_cannon_trajectories(
+    mut gizmos: Gizmos,
+    q_cannons: Query<(&CannonPoint, &Transform, &OrderId), With<KmpSelectablePoint>>,
+    kcl: Option<Res<Kcl>>,
+    settings: Res<AppSettings>,
+) {
+    let Some(kcl) = kcl else {
+        return;
+    };
+    let colors = &settings.kmp_model.color.cannon_points;
+    let triggers = cannon_trigger_centres(&kcl);
+
+    for (variant, trigger) in triggers.into_iter().enumerate() {
+        let Some(from) = trigger else {
+            continue;
+        };
+        let Some((cannon, transform, _)) = q_cannons.iter().find(|x| x.2 .0 as usize == variant) else {
+            // the trigger sends players to a cannon point that doesn't exist
+            gizmos.sphere(from, Quat::IDENTITY, TRIGGER_MARKER_RADIUS, Color::srgb(1., 0., 0.));
+            continue;
+        };
+        let to = transform.translation;
+        gizmos.sphere(from, Quat::IDENTITY, TRIGGER_MARKER_RADIUS, colors.arrow);
+        let points: Vec<_> = (0..=TRAJECTORY_SEGMENTS)
+            .map(|i| cannon_trajectory_point(from, to, cannon.shoot_effect, i as f32 / TRAJECTORY_SEGMENTS as f32))
+            .collect();
+        gizmos.linestrip(points.iter().copied(), colors.line);
+        // show which way the player is heading as they land
+        if let [.., before, last] = points[..] {
+            gizmos.arrow(before, last, colors.arrow);
+        }
+    }
+}
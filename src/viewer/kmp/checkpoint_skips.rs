@@ -0,0 +1,273 @@
+use super::{
+    checkpoints::CheckpointLeft,
+    components::{Checkpoint, KmpSelectablePoint},
+    path::KmpPathNode,
+};
+use crate::{
+    ui::errors::show_errors,
+    util::kcl_file::{Kcl, KclFlag},
+    viewer::kcl_heatmap::speed_multiplier,
+};
+use anyhow::{bail, Context};
+use bevy::{
+    color::palettes::css,
+    ecs::entity::EntityHashMap,
+    math::ivec2,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use std::{collections::VecDeque, fmt::Display};
+use strum::IntoEnumIterator;
+
+pub fn checkpoint_skips_plugin(app: &mut App) {
+    app.add_event::<FindCheckpointSkips>().add_systems(
+        Update,
+        (
+            find_checkpoint_skips
+                .pipe(show_errors)
+                .run_if(on_event::<FindCheckpointSkips>()),
+            draw_checkpoint_skips.run_if(resource_exists::<CheckpointSkips>),
+        ),
+    );
+}
+
+// the size of the grid the drivable KCL is flattened onto. Small gaps between the end of a checkpoint and a wall
+// aren't found, but those can't be driven through anyway.
+const CELL_SIZE: f32 = 150.;
+// how far around a checkpoint is searched for a way past it, relative to the size of the checkpoint and its
+// neighbours, so that driving the whole lap back round to the checkpoint isn't counted
+const SEARCH_SCALE: f32 = 1.5;
+
+/// Send this event to look for checkpoints which can be driven around, by flood filling the drivable KCL from the
+/// quad before each checkpoint without crossing the checkpoint, and seeing if the quad after it is reached. This is a
+/// coarse check which flattens the KCL from above, so roads passing over each other can give false positives.
+#[derive(Event, Default)]
+pub struct FindCheckpointSkips;
+
+/// The checkpoints found by the last skip detection. Remove this resource to dismiss it.
+#[derive(Resource, Deref)]
+pub struct CheckpointSkips(pub Vec<CheckpointSkip>);
+
+pub struct CheckpointSkip {
+    /// The left point of the checkpoint which can be driven around
+    pub checkpoint: Entity,
+    pub side: SkipSide,
+    /// Where the way around the checkpoint crosses the line the checkpoint is on
+    pub gap: Vec3,
+    /// The end of the checkpoint that the gap is past
+    pub end: Vec3,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SkipSide {
+    Left,
+    Right,
+}
+impl Display for SkipSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+        }
+    }
+}
+
+fn cell_of(pos: Vec2) -> IVec2 {
+    (pos / CELL_SIZE).floor().as_ivec2()
+}
+fn cell_center(cell: IVec2) -> Vec2 {
+    (cell.as_vec2() + 0.5) * CELL_SIZE
+}
+
+// every cell of the grid with drivable ground in it, with the height of the highest ground in the cell
+fn drivable_cells(kcl: &Kcl) -> HashMap<IVec2, f32> {
+    let mut cells: HashMap<IVec2, f32> = HashMap::default();
+    let mut add = |cell: IVec2, height: f32| {
+        let h = cells.entry(cell).or_insert(height);
+        *h = h.max(height);
+    };
+    let groups = KclFlag::iter()
+        .zip(kcl.vertex_groups.iter())
+        .filter(|(flag, _)| speed_multiplier(*flag).is_some());
+    for (_, group) in groups {
+        for tri in group.vertices.chunks_exact(3) {
+            let [a, b, c] = [tri[0].xz(), tri[1].xz(), tri[2].xz()];
+            let height = (tri[0].y + tri[1].y + tri[2].y) / 3.;
+            // the corners are always added so that thin triangles still count
+            for v in [a, b, c] {
+                add(cell_of(v), height);
+            }
+            let (min, max) = (cell_of(a.min(b).min(c)), cell_of(a.max(b).max(c)));
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    let cell = ivec2(x, y);
+                    if in_polygon(cell_center(cell), &[a, b, c]) {
+                        add(cell, height);
+                    }
+                }
+            }
+        }
+    }
+    cells
+}
+
+fn in_polygon(p: Vec2, polygon: &[Vec2]) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let t = ((p - a).dot(b - a) / (b - a).length_squared()).clamp(0., 1.);
+    p.distance(a + (b - a) * t)
+}
+
+struct CheckpointEnds {
+    left: Vec2,
+    right: Vec2,
+    prev: Vec<Entity>,
+    next: Vec<Entity>,
+}
+
+fn find_checkpoint_skips(world: &mut World) -> anyhow::Result<()> {
+    let kcl = world
+        .get_resource::<Kcl>()
+        .context("a KCL needs to be open to look for checkpoint skips")?;
+    let cells = drivable_cells(kcl);
+
+    let mut q_cp =
+        world.query_filtered::<(Entity, &CheckpointLeft, &KmpPathNode), (With<Checkpoint>, With<KmpSelectablePoint>)>();
+    let checkpoints: EntityHashMap<_> = q_cp
+        .iter(world)
+        .filter_map(|(e, cp_left, node)| {
+            let cp = CheckpointEnds {
+                left: world.get::<Transform>(e)?.translation.xz(),
+                right: world.get::<Transform>(cp_left.right)?.translation.xz(),
+                prev: node.prev_nodes.iter().copied().collect(),
+                next: node.next_nodes.iter().copied().collect(),
+            };
+            Some((e, cp))
+        })
+        .collect();
+    if checkpoints.is_empty() {
+        bail!("there are no checkpoints to check");
+    }
+
+    let mut skips = Vec::new();
+    for (e, cp) in checkpoints.iter() {
+        let pairs = cp.prev.iter().flat_map(|p| cp.next.iter().map(move |n| (p, n)));
+        for (prev, next) in pairs.filter_map(|(p, n)| Some((checkpoints.get(p)?, checkpoints.get(n)?))) {
+            if let Some(skip) = find_way_around(&cells, *e, cp, prev, next) {
+                skips.push(skip);
+                break;
+            }
+        }
+    }
+    world.insert_resource(CheckpointSkips(skips));
+    Ok(())
+}
+
+// flood fill from the quad before the checkpoint, without crossing the checkpoint, to see if the quad after it can be
+// reached. If it can, the way round is followed back to find where it passes the checkpoint.
+fn find_way_around(
+    cells: &HashMap<IVec2, f32>,
+    checkpoint: Entity,
+    cp: &CheckpointEnds,
+    prev: &CheckpointEnds,
+    next: &CheckpointEnds,
+) -> Option<CheckpointSkip> {
+    let (a, b) = (cp.left, cp.right);
+    if a.distance_squared(b) < f32::EPSILON {
+        return None;
+    }
+    let mid = a.lerp(b, 0.5);
+    let radius = [
+        a.distance(b),
+        mid.distance(prev.left.lerp(prev.right, 0.5)),
+        mid.distance(next.left.lerp(next.right, 0.5)),
+    ]
+    .into_iter()
+    .fold(0., f32::max)
+        * SEARCH_SCALE;
+
+    let before = [prev.left, prev.right, b, a];
+    let after = [a, b, next.right, next.left];
+    // thick enough that moving between neighbouring cells can't jump over it
+    let blocked = |cell: IVec2| distance_to_segment(cell_center(cell), a, b) < CELL_SIZE * 0.75;
+    let open = |cell: IVec2| cells.contains_key(&cell) && !blocked(cell) && cell_center(cell).distance(mid) < radius;
+
+    let (min, max) = (
+        cell_of(prev.left.min(prev.right).min(a).min(b)),
+        cell_of(prev.left.max(prev.right).max(a).max(b)),
+    );
+    let mut queue = VecDeque::new();
+    let mut came_from: HashMap<IVec2, Option<IVec2>> = HashMap::default();
+    for x in min.x..=max.x {
+        for y in min.y..=max.y {
+            let cell = ivec2(x, y);
+            if open(cell) && in_polygon(cell_center(cell), &before) {
+                came_from.insert(cell, None);
+                queue.push_back(cell);
+            }
+        }
+    }
+
+    let mut goal = None;
+    while let Some(cell) = queue.pop_front() {
+        if in_polygon(cell_center(cell), &after) {
+            goal = Some(cell);
+            break;
+        }
+        for dir in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            let neighbour = cell + dir;
+            if !came_from.contains_key(&neighbour) && open(neighbour) {
+                came_from.insert(neighbour, Some(cell));
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    let goal = goal?;
+
+    // walk back along the way round to where it crosses the line the checkpoint is on
+    let side_of = |cell: IVec2| (b - a).perp_dot(cell_center(cell) - a) > 0.;
+    let mut cur = goal;
+    let mut crossing = goal;
+    let mut visited = HashSet::new();
+    while let Some(Some(prev_cell)) = came_from.get(&cur) {
+        if !visited.insert(cur) {
+            break;
+        }
+        if side_of(*prev_cell) != side_of(cur) {
+            crossing = cur;
+            break;
+        }
+        cur = *prev_cell;
+    }
+    let pos = cell_center(crossing);
+    let t = (pos - a).dot(b - a) / (b - a).length_squared();
+    let (side, end) = if t < 0.5 {
+        (SkipSide::Left, a)
+    } else {
+        (SkipSide::Right, b)
+    };
+    let height = cells.get(&crossing).copied().unwrap_or_default();
+    Some(CheckpointSkip {
+        checkpoint,
+        side,
+        gap: pos.extend(height).xzy(),
+        end: end.extend(height).xzy(),
+    })
+}
+
+// mark each way round a checkpoint with a line from the end of the checkpoint out to where the gap is
+fn draw_checkpoint_skips(mut gizmos: Gizmos, skips: Res<CheckpointSkips>) {
+    for skip in skips.iter() {
+        gizmos.line(skip.end, skip.gap, css::RED);
+        gizmos.sphere(skip.gap, Quat::IDENTITY, CELL_SIZE, css::RED);
+    }
+}
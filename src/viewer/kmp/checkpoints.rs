@@ -1,8 +1,9 @@
 use super::{
     calc_cp_arrow_transform, calc_line_transform,
-    meshes_materials::{CheckpointMaterials, KmpMeshes},
+    meshes_materials::{CheckpointMaterials, KmpMeshes, SectionMaterials},
     ordering::{NextOrderID, OrderId},
     path::{get_kmp_data_and_component_groups, link_entity_groups, EntityGroup, KmpPathNode},
+    sections::KmpEditMode,
     Checkpoint, CheckpointKind, CheckpointMarker, KmpFile, KmpSectionIdEntityMap, KmpSelectablePoint, PathOverallStart,
     RespawnPoint, TransformEditOptions,
 };
@@ -19,12 +20,14 @@ use crate::{
     },
 };
 use bevy::{
+    color::palettes::css,
     ecs::{
         entity::{EntityHashMap, EntityHashSet},
         system::SystemParam,
     },
     math::vec3,
     prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
     transform::TransformSystem,
 };
 use bevy_mod_outline::{OutlineBundle, OutlineVolume};
@@ -32,6 +35,7 @@ use bon::builder;
 
 pub fn checkpoint_plugin(app: &mut App) {
     app.init_resource::<CheckpointHeight>()
+        .add_systems(Startup, spawn_checkpoint_quads)
         .add_systems(
             Update,
             (
@@ -39,6 +43,7 @@ pub fn checkpoint_plugin(app: &mut App) {
                 update_checkpoint_lines_arrows,
                 update_checkpoint_planes,
                 update_checkpoint_colors,
+                update_checkpoint_quads,
             ),
         )
         .add_systems(
@@ -175,14 +180,10 @@ pub fn checkpoint_spawner(
 
     let line_transform = calc_line_transform(left_tr, right_tr);
 
-    let meshes = world.resource::<KmpMeshes>();
-    let (sphere_mesh, cylinder_mesh, cone_mesh, plane_mesh) = (
-        meshes.sphere.clone(),
-        meshes.cylinder.clone(),
-        meshes.cone.clone(),
-        meshes.plane.clone(),
-    );
-    let cp_materials = world.resource::<CheckpointMaterials>();
+    let meshes = KmpMeshes::get(world);
+    let (sphere_mesh, cylinder_mesh, cone_mesh, plane_mesh) =
+        (meshes.sphere, meshes.cylinder, meshes.cone, meshes.plane);
+    let cp_materials = CheckpointMaterials::get(world);
     let (material, material_plane) = match cp.kind {
         CheckpointKind::Normal => (cp_materials.normal.clone(), cp_materials.normal_plane.clone()),
         CheckpointKind::Key(_) => (cp_materials.key.clone(), cp_materials.key_plane.clone()),
@@ -335,7 +336,7 @@ pub fn spawn_checkpoint_section(world: &mut World, kmp: &KmpFile) {
     let mut left_entity_groups: Vec<EntityGroup> = Vec::with_capacity(kmp_groups.len());
     let mut right_entity_groups = left_entity_groups.clone();
     let mut acc = 0;
-    for (i, (data_group, component_group)) in kmp_groups.iter().enumerate() {
+    for (data_group, component_group) in kmp_groups.iter() {
         let mut left_entity_group = EntityGroup {
             entities: Vec::with_capacity(data_group.nodes.len()),
             next_groups: data_group.next_groups.clone(),
@@ -351,7 +352,8 @@ pub fn spawn_checkpoint_section(world: &mut World, kmp: &KmpFile) {
                 .order_id(acc as u32)
                 .world(world)
                 .call();
-            if i == 0 && j == 0 {
+            // the first group may be empty, so the overall start is the first point that actually exists
+            if acc == 0 {
                 world.entity_mut(left).insert(PathOverallStart);
             }
             let maybe_respawn_e = world
@@ -402,8 +404,12 @@ fn update_checkpoint_colors(
     q_cp_left: Query<(Ref<Checkpoint>, &CheckpointLeft, Entity)>,
     mut q_std_mat: Query<&mut Handle<StandardMaterial>>,
     q_children: Query<&Children>,
-    materials: Res<CheckpointMaterials>,
+    materials: Option<Res<CheckpointMaterials>>,
 ) {
+    // there are no checkpoints to update until their materials have been made
+    let Some(materials) = materials else {
+        return;
+    };
     for (cp, cp_left, cp_e) in q_cp_left.iter() {
         if !cp.is_changed() {
             continue;
@@ -469,6 +475,169 @@ fn update_checkpoint_planes(
     }
 }
 
+/// The filled area between each checkpoint and the next, which a player has to drive through to go from one
+/// checkpoint to the next. Gaps between these areas are places where checkpoints could be skipped.
+#[derive(Component)]
+pub struct CheckpointQuads;
+
+fn spawn_checkpoint_quads(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default()));
+    // the colour of each quad comes from its vertex colours
+    let material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        CheckpointQuads,
+    ));
+}
+
+// the game can't tell which side of a checkpoint quad a player is on if the quad isn't convex
+fn is_convex(quad: [Vec2; 4]) -> bool {
+    let turns: Vec<f32> = (0..4)
+        .map(|i| {
+            let (a, b, c) = (quad[i], quad[(i + 1) % 4], quad[(i + 2) % 4]);
+            (b - a).perp_dot(c - b)
+        })
+        .collect();
+    turns.iter().all(|x| *x > 0.) || turns.iter().all(|x| *x < 0.)
+}
+
+/// A checkpoint this many times as wide as the one after it, or the other way round, is flagged, as the quad between
+/// them is stretched into a wedge which is easy to skip part of
+pub const WIDTH_CHANGE_WARNING: f32 = 2.;
+
+/// The sizes of a checkpoint, seen from above, so they don't have to be measured by hand
+pub struct CheckpointStats {
+    /// The distance between the left and right of the checkpoint
+    pub width: f32,
+    /// The area of the quad between this checkpoint and each checkpoint after it, along with the left of that checkpoint
+    pub quads: Vec<(Entity, f32)>,
+}
+
+// the left and right of a checkpoint seen from above, given its left entity
+fn checkpoint_ends(world: &World, left: Entity) -> Option<(Vec2, Vec2)> {
+    let right = world.get::<CheckpointLeft>(left)?.right;
+    Some((
+        world.get::<Transform>(left)?.translation.xz(),
+        world.get::<Transform>(right)?.translation.xz(),
+    ))
+}
+
+// the area of a quad from the positions of its corners in order, which is negative if they go round clockwise
+fn quad_area(quad: [Vec2; 4]) -> f32 {
+    (0..4).map(|i| quad[i].perp_dot(quad[(i + 1) % 4])).sum::<f32>() / 2.
+}
+
+/// Measure a checkpoint from its left entity
+pub fn checkpoint_stats(world: &World, left: Entity) -> Option<CheckpointStats> {
+    let (left_pos, right_pos) = checkpoint_ends(world, left)?;
+    let quads = world
+        .get::<KmpPathNode>(left)?
+        .next_nodes
+        .iter()
+        .filter_map(|next| {
+            let (next_left, next_right) = checkpoint_ends(world, *next)?;
+            Some((*next, quad_area([left_pos, right_pos, next_right, next_left]).abs()))
+        })
+        .collect();
+    Some(CheckpointStats {
+        width: left_pos.distance(right_pos),
+        quads,
+    })
+}
+
+/// How many times wider one checkpoint is than the next, either way round, if that is enough to be flagged
+pub fn abrupt_width_change(world: &World, left: Entity, next_left: Entity) -> Option<f32> {
+    let width = |e| checkpoint_ends(world, e).map(|(l, r)| l.distance(r));
+    let (a, b) = (width(left)?, width(next_left)?);
+    let ratio = a.max(b) / a.min(b).max(f32::EPSILON);
+    (ratio > WIDTH_CHANGE_WARNING).then_some(ratio)
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_checkpoint_quads(
+    settings: Res<AppSettings>,
+    mode: Res<KmpEditMode>,
+    q_changed: Query<
+        (),
+        (
+            Or<(Changed<Transform>, Changed<Checkpoint>, Changed<KmpPathNode>)>,
+            Or<(With<Checkpoint>, With<CheckpointRight>)>,
+        ),
+    >,
+    mut removed: RemovedComponents<Checkpoint>,
+    q_cp: Query<(&Checkpoint, &CheckpointLeft, &Transform, &KmpPathNode)>,
+    q_transform: Query<&Transform>,
+    mut q_quads: Query<(&Handle<Mesh>, &mut Visibility), With<CheckpointQuads>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok((mesh, mut visibility)) = q_quads.get_single_mut() else {
+        return;
+    };
+    let visible = settings.kmp_model.show_checkpoint_quads && *mode == KmpEditMode::Checkpoints;
+    visibility.set_if_neq(if visible {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    });
+    let removed = removed.read().count() > 0;
+    if !visible || (q_changed.is_empty() && !removed && !settings.is_changed() && !mode.is_changed()) {
+        return;
+    }
+
+    let colors = &settings.kmp_model.color.checkpoints;
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut vertex_colors: Vec<[f32; 4]> = Vec::new();
+    for (cp, cp_left, left, node) in q_cp.iter() {
+        let Ok(right) = q_transform.get(cp_left.right) else {
+            continue;
+        };
+        for next_e in node.next_nodes.iter() {
+            let Some((next_left, next_right)) = q_cp.get(*next_e).ok().and_then(|(_, next_cp_left, next_left, _)| {
+                Some((next_left, q_transform.get(next_cp_left.right).ok()?))
+            }) else {
+                continue;
+            };
+            let corners = [left, right, next_right, next_left].map(|x| x.translation);
+            let color = if !is_convex(corners.map(|x| x.xz())) {
+                Color::from(css::RED).with_alpha(0.6)
+            } else {
+                match cp.kind {
+                    CheckpointKind::Normal => colors.normal,
+                    CheckpointKind::Key(_) => colors.key,
+                    CheckpointKind::LapCount => colors.lap_count,
+                }
+                .with_alpha(0.25)
+            };
+            positions.extend([corners[0], corners[1], corners[2], corners[0], corners[2], corners[3]]);
+            vertex_colors.extend([color.to_linear().to_f32_array(); 6]);
+        }
+    }
+
+    let Some(mesh) = meshes.get_mut(mesh) else {
+        return;
+    };
+    *mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vertex_colors);
+    mesh.compute_flat_normals();
+}
+
 #[derive(SystemParam)]
 pub struct GetSelectedCheckpoints<'w, 's> {
     q_cp_left: Query<'w, 's, (&'static mut Checkpoint, Entity, Has<Selected>)>,
@@ -10,10 +10,14 @@ use super::{
 };
 use crate::{
     ui::util::{get_euler_rot, set_euler_rot},
-    util::kmp_file::{Area, Came, Enpt, Gobj, Itpt, Ktpt, Poti, PotiPoint, Stgi},
+    util::kmp_file::{Area, Came, Enpt, ExtendedPresence, Gobj, Itpt, Ktpt, Poti, PotiPoint, Stgi},
     viewer::kmp::KmpSectionEntityIdMap,
 };
-use bevy::{ecs::entity::EntityHashSet, math::vec3, prelude::*};
+use bevy::{
+    ecs::entity::EntityHashSet,
+    math::{vec2, vec3},
+    prelude::*,
+};
 use binrw::{BinRead, BinWrite};
 use bon::builder;
 use derive_new::new;
@@ -37,6 +41,16 @@ pub struct PathStart;
 #[derive(Component, Default)]
 pub struct PathOverallStart;
 
+/// A name given to a point in the editor to tell it apart from the others, which isn't saved in the KMP
+#[derive(Component, Clone, Default, PartialEq)]
+pub struct DisplayName(pub String);
+/// A note left on a point in the editor, e.g. about why it is where it is, which isn't saved in the KMP
+#[derive(Component, Clone, Default, PartialEq)]
+pub struct PointNote(pub String);
+/// A colour given to a point in the editor to group it with others in the outliner, which isn't saved in the KMP
+#[derive(Component, Clone, Copy, Default, PartialEq)]
+pub struct LabelColor(pub [u8; 3]);
+
 // --- TRACK INFO COMPONENTS ---
 #[derive(Resource, Component, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct TrackInfo {
@@ -80,6 +94,10 @@ pub struct EnemyPathPoint {
     pub setting_2: EnemyPathSetting2,
     pub setting_3: u8,
 }
+/// Marks an enemy point in a battle arena as a dispatch point, where CPUs choose which way to go next.
+/// Each dispatch point is saved as its own group.
+#[derive(Component, Default)]
+pub struct BattleDispatchPoint;
 #[derive(
     Display, EnumString, IntoStaticStr, EnumIter, Default, PartialEq, Clone, Copy, Debug, Serialize, Deserialize,
 )]
@@ -160,6 +178,8 @@ pub struct Object {
     pub scale: Vec3,
     pub settings: [u16; 8],
     pub presence: u16,
+    /// LE-CODE extended presence flags or definition object
+    pub extended_presence: ExtendedPresence,
 }
 
 // --- ROUTE COMPONENTS ---
@@ -183,6 +203,11 @@ pub struct RoutePoint {
 }
 
 // --- AREA COMPONENTS ---
+/// The size of an area with a scale of 1 in the KMP. [`AreaPoint::scale`] is the full width, height and depth of the
+/// area, which is centred on its position on X and Z but sits on top of it on Y. So an area with a scale of 1 extends
+/// 5000 units either side of its position and 10000 units up from it, as the game has it.
+pub const AREA_BASE_SIZE: Vec3 = vec3(10000., 10000., 10000.);
+
 #[derive(Component, Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct AreaPoint {
     pub shape: AreaShape,
@@ -197,11 +222,30 @@ impl Default for AreaPoint {
             shape: AreaShape::default(),
             kind: AreaKind::default(),
             priority: 0,
-            scale: vec3(10000., 10000., 10000.),
+            scale: AREA_BASE_SIZE,
             show_area: false,
         }
     }
 }
+impl AreaPoint {
+    /// Whether a position is inside the area, given the area's transform
+    pub fn contains(&self, transform: &Transform, pos: Vec3) -> bool {
+        // position relative to the bottom centre of the area, in the area's own rotation
+        let local = transform.rotation.inverse() * (pos - transform.translation);
+        if local.y < 0. || local.y > self.scale.y {
+            return false;
+        }
+        let half_size = vec2(self.scale.x, self.scale.z) / 2.;
+        if half_size.min_element() <= 0. {
+            return false;
+        }
+        let local = vec2(local.x, local.z) / half_size;
+        match self.shape {
+            AreaShape::Box => local.abs().max_element() <= 1.,
+            AreaShape::Cylinder => local.length_squared() <= 1.,
+        }
+    }
+}
 #[derive(
     Display, EnumString, IntoStaticStr, EnumIter, Default, Clone, Copy, PartialEq, Serialize, Deserialize, Debug,
 )]
@@ -560,12 +604,13 @@ impl KmpComponent for Object {
             scale: data.scale.into(),
             settings: data.settings,
             presence: data.presence_flags,
+            extended_presence: ExtendedPresence::from_raw(data.extended_presence),
         }
     }
     fn to_kmp(&self, transform: Transform, world: &mut World, e: Entity) -> Gobj {
         Gobj {
             object_id: self.object_id,
-            padding: 0,
+            extended_presence: self.extended_presence.to_raw(),
             position: transform.translation.into(),
             rotation: get_euler_rot(&transform).into(),
             scale: self.scale.into(),
@@ -673,7 +718,7 @@ impl KmpComponent for AreaPoint {
                 }
             },
             priority: data.priority,
-            scale: Vec3::from(data.scale) * vec3(5000., 10000., 5000.),
+            scale: Vec3::from(data.scale) * AREA_BASE_SIZE,
             kind: match data.kind {
                 0 => AreaKind::Camera {
                     cam_index: data.came_index,
@@ -785,7 +830,7 @@ impl KmpComponent for AreaPoint {
             rotation: get_euler_rot(&transform).into(),
             shape: self.shape as u8,
             priority: self.priority,
-            scale: (self.scale / vec3(5000., 10000., 5000.)).into(),
+            scale: (self.scale / AREA_BASE_SIZE).into(),
             kind,
             came_index,
             setting_1,
@@ -1032,3 +1077,33 @@ impl MaxConnectedPath for Checkpoint {
 impl MaxConnectedPath for RoutePoint {
     const MAX_CONNECTED: u8 = 1;
 }
+
+#[test]
+fn test_area_extents() {
+    use crate::util::kmp_file::KmpFile;
+    use std::io::Cursor;
+
+    let mut world = World::new();
+    world.init_resource::<KmpErrors>();
+    let kmp_file = std::fs::read("test_files/desert_course/course.kmp").unwrap();
+    let kmp = KmpFile::read(&mut Cursor::new(kmp_file)).unwrap();
+
+    for data in kmp.area.entries.iter() {
+        let area = AreaPoint::from_kmp(data, &mut world);
+        // a scale of 1 is 5000 either side of the position on X and Z, and 10000 up on Y
+        let [x, y, z] = data.scale.map(|s| s * 5000.);
+        let y = y * 2.;
+        let inside = |pos| area.contains(&Transform::IDENTITY, pos);
+        assert!(inside(vec3(0., y * 0.99, 0.)));
+        assert!(!inside(vec3(0., y * 1.01, 0.)));
+        assert!(!inside(vec3(0., -1., 0.)));
+        assert!(inside(vec3(x * 0.99, 1., 0.)) && inside(vec3(-x * 0.99, 1., 0.)));
+        assert!(!inside(vec3(x * 1.01, 1., 0.)) && !inside(vec3(-x * 1.01, 1., 0.)));
+        assert!(inside(vec3(0., 1., z * 0.99)) && inside(vec3(0., 1., -z * 0.99)));
+        assert!(!inside(vec3(0., 1., z * 1.01)) && !inside(vec3(0., 1., -z * 1.01)));
+        // the corners are only inside boxes
+        let corner = vec3(x * 0.9, 1., z * 0.9);
+        assert_eq!(inside(corner), area.shape == AreaShape::Box);
+    }
+    assert!(!kmp.area.entries.is_empty());
+}
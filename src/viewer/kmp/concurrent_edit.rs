@@ -0,0 +1,162 @@
+use super::{documents::OpenDocuments, sidecar::KmpSidecar};
+use crate::{ui::errors::show_errors, viewer::edit::undo::UndoHistory};
+use bevy::{prelude::*, time::common_conditions::on_timer, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub fn concurrent_edit_plugin(app: &mut App) {
+    app.init_resource::<ThisEditor>()
+        .init_resource::<ConcurrentEdits>()
+        .add_systems(
+            Update,
+            refresh_editing.pipe(show_errors).run_if(on_timer(HEARTBEAT_INTERVAL)),
+        )
+        .add_systems(Last, release_on_exit.pipe(show_errors).run_if(on_event::<AppExit>()));
+}
+
+// how often the changed KMPs are marked as still being edited, and how long after that they are assumed to have been
+// left behind by a kmpeek that closed without letting go of them (e.g. if it crashed)
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const STALE_AFTER: Duration = Duration::from_secs(120);
+
+/// A kmpeek which has unsaved changes to a KMP. This is stored in the KMP's sidecar, so that anyone else opening it
+/// can be warned that whichever of them saves last will overwrite the other's changes. Only KMPs which have been
+/// changed are claimed, so just opening a KMP to look at it doesn't write anything next to it.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct EditorSession {
+    /// Different every time kmpeek is run, so that the same user having the file open twice is also caught
+    pub id: String,
+    pub user: String,
+    pub host: String,
+    /// Seconds since the unix epoch that the KMP was last confirmed to still be open
+    pub heartbeat: u64,
+}
+impl EditorSession {
+    pub fn is_stale(&self) -> bool {
+        now_secs().saturating_sub(self.heartbeat) > STALE_AFTER.as_secs()
+    }
+    /// Who is editing, e.g. 'thomas on studio-pc'
+    pub fn who(&self) -> String {
+        format!("{} on {}", self.user, self.host)
+    }
+}
+
+pub(super) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+}
+
+/// This kmpeek, as it is written into the sidecars of the KMPs it is editing
+#[derive(Resource, Deref)]
+pub struct ThisEditor(EditorSession);
+impl Default for ThisEditor {
+    fn default() -> Self {
+        let var = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|x| std::env::var(x).ok())
+                .unwrap_or_else(|| "unknown".into())
+        };
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_nanos())
+            .unwrap_or_default();
+        Self(EditorSession {
+            id: format!("{}-{started}", std::process::id()),
+            user: var(&["USER", "USERNAME"]),
+            host: var(&["HOSTNAME", "COMPUTERNAME"]),
+            heartbeat: 0,
+        })
+    }
+}
+
+/// Another kmpeek that is editing the KMP this sidecar belongs to, if it hasn't gone stale
+pub fn other_editor(world: &World, sidecar: &KmpSidecar) -> Option<EditorSession> {
+    let this = world.resource::<ThisEditor>();
+    sidecar.editor.clone().filter(|x| x.id != this.id && !x.is_stale())
+}
+
+/// Mark the KMP at this path as being edited by this kmpeek
+pub fn claim_kmp(world: &World, path: &Path) -> anyhow::Result<()> {
+    let editor = EditorSession {
+        heartbeat: now_secs(),
+        ..world.resource::<ThisEditor>().0.clone()
+    };
+    KmpSidecar::save_editor(path, Some(editor))
+}
+
+/// Stop marking the KMP at this path as being edited by this kmpeek, unless someone else has started editing it since
+pub fn release_kmp(world: &World, path: &Path) -> anyhow::Result<()> {
+    let this = world.resource::<ThisEditor>();
+    if KmpSidecar::load(path).editor.is_some_and(|x| x.id == this.id) {
+        KmpSidecar::save_editor(path, None)?;
+    }
+    Ok(())
+}
+
+/// Shown when a KMP being opened is already being edited by another kmpeek, asking whether to open it read-only. Set
+/// `read_only` and send the path again to open it.
+#[derive(Resource)]
+pub struct ConcurrentEditPrompt {
+    pub path: PathBuf,
+    pub editor: EditorSession,
+    pub read_only: Option<bool>,
+}
+
+/// The other kmpeeks that have started editing the KMPs this one is editing
+#[derive(Resource, Default)]
+pub struct ConcurrentEdits {
+    pub others: HashMap<PathBuf, EditorSession>,
+    /// The ids of the sessions that have already been warned about
+    pub dismissed: Vec<String>,
+}
+impl ConcurrentEdits {
+    /// The other editors which haven't been warned about yet
+    pub fn undismissed(&self) -> impl Iterator<Item = (&PathBuf, &EditorSession)> {
+        self.others.iter().filter(|x| !self.dismissed.contains(&x.1.id))
+    }
+    pub fn dismiss_all(&mut self) {
+        let ids: Vec<_> = self.others.values().map(|x| x.id.clone()).collect();
+        self.dismissed.extend(ids);
+    }
+}
+
+// the open KMPs which weren't opened read-only, and whether each has unsaved changes
+fn editable_docs(world: &World) -> Vec<(PathBuf, bool)> {
+    let docs = world.resource::<OpenDocuments>();
+    let sidecar = world.get_resource::<KmpSidecar>();
+    let history = world.resource::<UndoHistory>();
+    (0..docs.docs.len())
+        .filter(|i| !docs.is_read_only(*i, sidecar))
+        .map(|i| (docs.docs[i].path.clone(), docs.has_unsaved_changes(i, history)))
+        .collect()
+}
+
+// mark the KMPs with unsaved changes as being edited, unless someone else has started editing them, in which case
+// they are left to them and the user is warned instead. KMPs which have been saved since are let go of again.
+fn refresh_editing(world: &mut World) -> anyhow::Result<()> {
+    let mut others = HashMap::default();
+    for (path, changed) in editable_docs(world) {
+        match other_editor(world, &KmpSidecar::load(&path)) {
+            Some(other) => {
+                others.insert(path, other);
+            }
+            None if changed => claim_kmp(world, &path)?,
+            None => release_kmp(world, &path)?,
+        }
+    }
+    world.resource_mut::<ConcurrentEdits>().others = others;
+    Ok(())
+}
+
+// let go of the KMPs so that nobody is warned about them being edited after kmpeek has closed
+fn release_on_exit(world: &mut World) -> anyhow::Result<()> {
+    for (path, _) in editable_docs(world) {
+        release_kmp(world, &path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use super::{
+    components::{
+        BattleFinishPoint, CannonPoint, EnemyPathPoint, ItemPathPoint, KmpComponent, Object, PathOverallStart,
+        RespawnPoint, RouteSettings, StartPoint,
+    },
+    csv::{
+        export_section_csv, read_csv_points, section_csv_file_name, section_supports_csv, CsvPoint, ToFromCsvFormat,
+    },
+    ordering::OrderId,
+    path::{get_kmp_data_and_component_groups, link_entity_groups, save_path_section, EntityGroup, KmpPathNode},
+    point::save_point_section,
+    routes::RouteLink,
+    sections::KmpEditMode,
+    write_kmp_file, KmpErrors, KmpSectionEntityIdMap,
+};
+use crate::{
+    ui::util::set_euler_rot,
+    util::{
+        kmp_file::{
+            Gobj, KmpFile, KmpGetPathSection, KmpGetSection, KmpPositionPoint, KmpRotationPoint, KmpSectionName,
+            PathGroup, Section,
+        },
+        szs::{read_szs, COURSE_KMP},
+    },
+};
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use std::{ffi::OsStr, fs, path::Path};
+use strum::IntoEnumIterator;
+
+/// Convert a KMP to a folder of CSV files, or a folder of CSV files back to a KMP, without opening the editor. The
+/// points are spawned into a world of their own with only their data, so they are read and written by the same code
+/// as when editing, without anything needing to be rendered.
+pub fn convert(input: &Path, output: &Path, base: Option<&Path>) -> anyhow::Result<()> {
+    if input.is_dir() {
+        let base = base.context("converting CSV files to a KMP needs '--base' for the sections CSV can't store")?;
+        csv_to_kmp(input, base, output)
+    } else {
+        kmp_to_csv(input, output)
+    }
+}
+
+fn read_kmp(path: &Path) -> anyhow::Result<KmpFile> {
+    let data = match path.extension().and_then(OsStr::to_str) {
+        Some("kmp") => fs::read(path).context("could not open kmp file")?,
+        Some("szs") => read_szs(path)?
+            .file(COURSE_KMP)
+            .context("szs file has no course.kmp")?
+            .clone(),
+        _ => bail!("file extension was not .kmp or .szs"),
+    };
+    KmpFile::read_any(&data).context("could not read kmp file")
+}
+
+fn kmp_to_csv(input: &Path, output_dir: &Path) -> anyhow::Result<()> {
+    let kmp = read_kmp(input)?;
+    let mut world = World::new();
+    world.init_resource::<KmpErrors>();
+
+    spawn_points::<StartPoint>(&mut world, &kmp);
+    spawn_paths::<EnemyPathPoint>(&mut world, &kmp);
+    spawn_paths::<ItemPathPoint>(&mut world, &kmp);
+    spawn_points::<RespawnPoint>(&mut world, &kmp);
+    spawn_points::<Object>(&mut world, &kmp);
+    link_object_routes(&mut world, &kmp);
+    spawn_points::<CannonPoint>(&mut world, &kmp);
+    spawn_points::<BattleFinishPoint>(&mut world, &kmp);
+
+    let errors = world.resource::<KmpErrors>().len();
+    if errors > 0 {
+        eprintln!("warning: {errors} invalid values were found and replaced with defaults");
+    }
+
+    fs::create_dir_all(output_dir).context("could not create output folder")?;
+    for mode in KmpEditMode::iter().filter(|x| section_supports_csv(*x)) {
+        let path = output_dir.join(section_csv_file_name(mode));
+        export_section_csv(&mut world, mode, &path).with_context(|| format!("could not export {mode}"))?;
+    }
+    Ok(())
+}
+
+fn csv_to_kmp(input_dir: &Path, base: &Path, output: &Path) -> anyhow::Result<()> {
+    let mut kmp = read_kmp(base)?;
+    let mut world = World::new();
+    let routes = spawn_route_starts(&mut world, &kmp);
+
+    let mut converted = 0;
+    for mode in KmpEditMode::iter().filter(|x| section_supports_csv(*x)) {
+        let path = input_dir.join(section_csv_file_name(mode));
+        // sections without a CSV file are kept as they are in the base KMP
+        if !path.exists() {
+            continue;
+        }
+        let context = || format!("could not import {mode}");
+        match mode {
+            KmpEditMode::StartPoints => {
+                kmp.ktpt = csv_point_section::<StartPoint>(&mut world, &path).with_context(context)?
+            }
+            KmpEditMode::EnemyPaths => {
+                (kmp.enpt, kmp.enph) = csv_path_section::<EnemyPathPoint>(&mut world, &path).with_context(context)?
+            }
+            KmpEditMode::ItemPaths => {
+                (kmp.itpt, kmp.itph) = csv_path_section::<ItemPathPoint>(&mut world, &path).with_context(context)?
+            }
+            KmpEditMode::RespawnPoints => {
+                kmp.jgpt = csv_point_section::<RespawnPoint>(&mut world, &path).with_context(context)?
+            }
+            KmpEditMode::Objects => {
+                kmp.gobj = csv_object_section(&mut world, &path, &routes, &kmp).with_context(context)?
+            }
+            KmpEditMode::CannonPoints => {
+                kmp.cnpt = csv_point_section::<CannonPoint>(&mut world, &path).with_context(context)?
+            }
+            KmpEditMode::BattleFinishPoints => {
+                kmp.mspt = csv_point_section::<BattleFinishPoint>(&mut world, &path).with_context(context)?
+            }
+            _ => continue,
+        }
+        converted += 1;
+    }
+    if converted == 0 {
+        bail!("there are no CSV files in '{}'", input_dir.display());
+    }
+    write_kmp_file(output, &kmp)?;
+    Ok(())
+}
+
+// only the data that is converted to and from the KMP and CSV is spawned, as nothing is shown
+fn spawn_data<T: Component>(world: &mut World, component: T, pos: Vec3, rot: Vec3, order_id: u32) -> Entity {
+    let mut transform = Transform::from_translation(pos);
+    set_euler_rot(rot, &mut transform);
+    world.spawn((component, transform, OrderId(order_id))).id()
+}
+
+fn spawn_points<T: KmpComponent>(world: &mut World, kmp: &KmpFile)
+where
+    T::KmpFormat: KmpGetSection + KmpPositionPoint + KmpRotationPoint,
+{
+    for (i, node) in T::KmpFormat::get_section(kmp).iter().enumerate() {
+        let component = T::from_kmp(node, world);
+        spawn_data(
+            world,
+            component,
+            node.get_position().into(),
+            node.get_rotation().into(),
+            i as u32,
+        );
+    }
+}
+
+fn spawn_paths<T: KmpComponent>(world: &mut World, kmp: &KmpFile)
+where
+    T::KmpFormat: KmpGetSection + KmpGetPathSection + KmpPositionPoint,
+    PathGroup<T::KmpFormat>: KmpSectionName,
+{
+    let kmp_groups = get_kmp_data_and_component_groups::<T>(kmp, world);
+    let mut entity_groups = Vec::with_capacity(kmp_groups.len());
+    let mut order_id = 0;
+    for (data_group, component_group) in kmp_groups {
+        let mut entities = Vec::with_capacity(data_group.nodes.len());
+        for (node, component) in data_group.nodes.iter().zip(component_group) {
+            let e = spawn_data(world, component, node.get_position().into(), Vec3::ZERO, order_id);
+            world.entity_mut(e).insert(KmpPathNode::default());
+            entities.push(e);
+            order_id += 1;
+        }
+        entity_groups.push(EntityGroup {
+            entities,
+            next_groups: data_group.next_groups,
+        });
+    }
+    if let Some(first) = entity_groups.first().and_then(|x| x.entities.first()) {
+        world.entity_mut(*first).insert(PathOverallStart);
+    }
+    link_entity_groups(world, entity_groups);
+}
+
+// routes can't be stored as CSV, so they are kept from the KMP, with just a route start spawned for each one for
+// objects to link to
+fn spawn_route_starts(world: &mut World, kmp: &KmpFile) -> Vec<Entity> {
+    let starts: Vec<_> = (0..kmp.poti.len())
+        .map(|i| world.spawn((RouteSettings::default(), OrderId(i as u32))).id())
+        .collect();
+    world.insert_resource(KmpSectionEntityIdMap::<RouteSettings>::new(
+        starts.iter().enumerate().map(|(i, e)| (*e, i as u8)).collect(),
+    ));
+    starts
+}
+
+fn link_object_routes(world: &mut World, kmp: &KmpFile) {
+    let routes = spawn_route_starts(world, kmp);
+    let objects: Vec<_> = world
+        .query_filtered::<(Entity, &OrderId), With<Object>>()
+        .iter(world)
+        .map(|(e, order_id)| (e, order_id.0))
+        .collect();
+    for (e, i) in objects {
+        let route = kmp.gobj.get(i as usize).map_or(0xffff, |x| x.route);
+        if let Some(route_e) = routes.get(route as usize) {
+            world.entity_mut(e).insert(RouteLink(*route_e));
+        }
+    }
+}
+
+fn csv_object_section(
+    world: &mut World,
+    path: &Path,
+    routes: &[Entity],
+    base: &KmpFile,
+) -> anyhow::Result<Section<Gobj>> {
+    let (entities, points) = spawn_csv_points::<Object>(world, path)?;
+    for (i, (e, point)) in entities.into_iter().zip(points).enumerate() {
+        // a blank route keeps the route the object has in the base KMP
+        let route = match point.route {
+            Some(route) => route,
+            None => base.gobj.get(i).map(|x| x.route as u32).filter(|x| *x != 0xffff),
+        };
+        if let Some(route) = route {
+            let route_e = routes
+                .get(route as usize)
+                .with_context(|| format!("route {route} doesn't exist"))?;
+            world.entity_mut(e).insert(RouteLink(*route_e));
+        }
+    }
+    Ok(save_point_section::<Object>(world).0)
+}
+
+fn spawn_csv_points<T: ToFromCsvFormat>(
+    world: &mut World,
+    path: &Path,
+) -> anyhow::Result<(Vec<Entity>, Vec<CsvPoint<T>>)> {
+    let points = read_csv_points::<T>(path)?;
+    let entities: Vec<_> = points
+        .iter()
+        .enumerate()
+        .map(|(i, point)| spawn_data(world, point.component.clone(), point.pos, point.rot, i as u32))
+        .collect();
+    if T::is_path() {
+        for e in entities.iter() {
+            world.entity_mut(*e).insert(KmpPathNode::default());
+        }
+        for (point, e) in points.iter().zip(entities.iter()) {
+            for next in point.next_points.iter() {
+                KmpPathNode::link_nodes(*e, entities[*next as usize], world);
+            }
+        }
+        if let Some(first) = entities.first() {
+            world.entity_mut(*first).insert(PathOverallStart);
+        }
+    }
+    Ok((entities, points))
+}
+
+fn csv_point_section<T: ToFromCsvFormat + KmpComponent>(
+    world: &mut World,
+    path: &Path,
+) -> anyhow::Result<Section<T::KmpFormat>> {
+    spawn_csv_points::<T>(world, path)?;
+    Ok(save_point_section::<T>(world).0)
+}
+
+fn csv_path_section<T: ToFromCsvFormat + KmpComponent>(
+    world: &mut World,
+    path: &Path,
+) -> anyhow::Result<(Section<T::KmpFormat>, Section<PathGroup<T::KmpFormat>>)>
+where
+    PathGroup<T::KmpFormat>: KmpSectionName,
+{
+    spawn_csv_points::<T>(world, path)?;
+    Ok(save_path_section::<T>(world))
+}
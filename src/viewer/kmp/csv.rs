@@ -1,199 +1,658 @@
-// use std::{arch::aarch64::vaddvq_u8, fmt::Debug};
-
-// use bevy::{math::vec3, prelude::*};
-// use serde::{ser::SerializeMap, Deserialize, Serialize};
-
-// use crate::ui::{
-//     file_dialog::{DialogType, FileDialogResult},
-//     util::{euler_to_quat, quat_to_euler},
-// };
-
-// use super::{
-//     ordering::OrderID,
-//     path::{is_path, KmpPathNode},
-//     point::PointSpawner,
-//     sections::{add_for_all_components, KmpEditMode, ToKmpSection},
-//     AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, EnemyPathSetting1, EnemyPathSetting2,
-//     ItemPathPoint, KmpCamera, Object, RespawnPoint, Spawn, Spawner, StartPoint,
-// };
-
-// pub fn csv_plugin(app: &mut App) {
-//     app.add_systems(
-//         Update,
-//         (csv_export_import::<StartPoint>, csv_export_import::<EnemyPathPoint>),
-//     );
-// }
-
-// pub fn csv_export_import<T: Component + Clone + Debug + ToFromCsvFormat + Spawn>(
-//     mut ev_file_dialog_result: EventReader<FileDialogResult>,
-//     mode: Option<Res<KmpEditMode<T>>>,
-//     q: Query<(Entity, &OrderID, &T, &Transform)>,
-//     mut commands: Commands,
-// ) {
-//     if mode.is_none() {
-//         return;
-//     }
-//     for FileDialogResult { path, dialog_type } in ev_file_dialog_result.read() {
-//         match dialog_type {
-//             DialogType::ExportCsv => {
-//                 let mut items: Vec<_> = q.iter().collect();
-//                 items.sort_by(|x, y| x.1.cmp(y.1));
-//                 let items: Vec<_> = items.iter().map(|x| (x.0, x.2.clone(), *x.3)).collect();
-//                 let path = path.clone();
-
-//                 commands.add(move |world: &mut World| {
-//                     let Ok(mut wtr) = csv::Writer::from_path(path) else {
-//                         // send error
-//                         return;
-//                     };
-//                     for (e, t, transform) in items {
-//                         let csv_format = t.clone().to_csv_format(transform, e, world);
-//                         let res = wtr.serialize(csv_format);
-//                         if res.is_err() {
-//                             dbg!(res);
-//                             // send error
-//                             continue;
-//                         }
-//                     }
-//                     if wtr.flush().is_err() {
-//                         // send error
-//                     }
-//                 });
-//             }
-//             DialogType::ImportCsv => {
-//                 let Ok(mut rdr) = csv::Reader::from_path(path) else {
-//                     // send error
-//                     return;
-//                 };
-//                 for (entity, _, _, _) in q.iter() {
-//                     commands.entity(entity).despawn_recursive();
-//                 }
-//                 commands.add(move |world: &mut World| {
-//                     let mut records = Vec::new();
-//                     for (i, result) in rdr.deserialize::<T::CsvFormat>().enumerate() {
-//                         let Ok(record) = result else {
-//                             // send error
-//                             continue;
-//                         };
-//                         let (component, transform) = T::from_csv_format(record.clone(), world);
-
-//                         let e = Spawner::<T>::new(component)
-//                             .transform(transform)
-//                             .order_id(i as u32)
-//                             .spawn(world);
-
-//                         records.push((record, e));
-//                     }
-//                     if is_path::<T>() {
-//                         for (cur_record, cur_e) in records.iter() {
-//                             for i in T::next_points(cur_record).unwrap() {
-//                                 let next_e = records[i as usize].1;
-//                                 KmpPathNode::link_nodes(*cur_e, next_e, world);
-//                             }
-//                         }
-//                     }
-//                 });
-//             }
-//             _ => {}
-//         }
-//     }
-// }
-
-// pub trait ToFromCsvFormat
-// where
-//     Self: Sized,
-//     Self::CsvFormat: Serialize + Clone,
-//     for<'de> Self::CsvFormat: Deserialize<'de>,
-// {
-//     type CsvFormat;
-//     fn to_csv_format(self, transform: Transform, entity: Entity, world: &mut World) -> Self::CsvFormat;
-//     fn from_csv_format(value: Self::CsvFormat, world: &mut World) -> (Self, Transform);
-//     fn next_points(csv: &Self::CsvFormat) -> Option<Vec<u32>> {
-//         None
-//     }
-// }
-
-// #[derive(Serialize, Deserialize, Clone)]
-// pub struct StartPointCsvRow {
-//     position_x: f32,
-//     position_y: f32,
-//     position_z: f32,
-//     rotation_x: f32,
-//     rotation_y: f32,
-//     rotation_z: f32,
-//     player_index: i16,
-// }
-// impl ToFromCsvFormat for StartPoint {
-//     type CsvFormat = StartPointCsvRow;
-//     fn to_csv_format(self, transform: Transform, _: Entity, _: &mut World) -> Self::CsvFormat {
-//         let rotation = quat_to_euler(&transform);
-//         StartPointCsvRow {
-//             position_x: transform.translation.x,
-//             position_y: transform.translation.y,
-//             position_z: transform.translation.z,
-//             rotation_x: rotation.x,
-//             rotation_y: rotation.y,
-//             rotation_z: rotation.z,
-//             player_index: self.player_index,
-//         }
-//     }
-//     fn from_csv_format(value: Self::CsvFormat, _: &mut World) -> (Self, Transform) {
-//         (
-//             StartPoint {
-//                 player_index: value.player_index,
-//             },
-//             Transform::from_xyz(value.position_x, value.position_y, value.position_z).with_rotation(Quat::from_euler(
-//                 EulerRot::XYZ,
-//                 value.rotation_x,
-//                 value.rotation_y,
-//                 value.rotation_z,
-//             )),
-//         )
-//     }
-// }
-
-// #[derive(Serialize, Deserialize, Clone)]
-// pub struct EnemyPathCsvRow {
-//     position_x: f32,
-//     position_y: f32,
-//     position_z: f32,
-//     leniency: f32,
-//     setting_1: EnemyPathSetting1,
-//     setting_2: EnemyPathSetting2,
-//     setting_3: u8,
-//     next_points: Vec<u32>,
-// }
-// impl ToFromCsvFormat for EnemyPathPoint {
-//     type CsvFormat = EnemyPathCsvRow;
-//     fn to_csv_format(self, transform: Transform, entity: Entity, world: &mut World) -> Self::CsvFormat {
-//         let next_entities = world.entity(entity).get::<KmpPathNode>().unwrap().next_nodes.clone();
-
-//         let mut q = world.query::<&OrderID>();
-//         let next_points = q.iter_many(world, next_entities).map(|x| x.0).collect();
-
-//         EnemyPathCsvRow {
-//             position_x: transform.translation.x,
-//             position_y: transform.translation.y,
-//             position_z: transform.translation.z,
-//             leniency: self.leniency,
-//             setting_1: self.setting_1,
-//             setting_2: self.setting_2,
-//             setting_3: self.setting_3,
-//             next_points,
-//         }
-//     }
-//     fn from_csv_format(value: Self::CsvFormat, _: &mut World) -> (Self, Transform) {
-//         (
-//             EnemyPathPoint {
-//                 leniency: value.leniency,
-//                 setting_1: value.setting_1,
-//                 setting_2: value.setting_2,
-//                 setting_3: value.setting_3,
-//             },
-//             Transform::from_xyz(value.position_x, value.position_y, value.position_z),
-//         )
-//     }
-//     fn next_points(csv: &Self::CsvFormat) -> Option<Vec<u32>> {
-//         Some(csv.next_points.clone())
-//     }
-// }
+use super::{
+    components::{
+        BattleFinishPoint, CannonPoint, CannonShootEffect, EnemyPathPoint, EnemyPathSetting1, EnemyPathSetting2,
+        ItemPathBulletHeight, ItemPathPoint, Object, PathOverallStart, RespawnPoint, RouteSettings, Spawn, Spawner,
+        StartPoint,
+    },
+    ordering::{OrderId, RefreshOrdering},
+    path::{KmpPathNode, RecalcPaths},
+    routes::RouteLink,
+    sections::KmpEditMode,
+};
+use crate::{
+    ui::{
+        errors::show_errors,
+        file_dialog::{DialogType, FileDialogResult},
+        util::{get_euler_rot, set_euler_rot},
+    },
+    util::kmp_file::ExtendedPresence,
+};
+use anyhow::{bail, Context};
+use bevy::{
+    ecs::{entity::EntityHashMap, event::ManualEventReader},
+    math::vec3,
+    prelude::*,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+
+pub fn csv_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        export_import_csv
+            .pipe(show_errors)
+            .run_if(on_event::<FileDialogResult>()),
+    );
+}
+
+/// A point as it is stored in a CSV file, before being converted to/from the row format of its section
+pub struct CsvPoint<T> {
+    pub component: T,
+    pub pos: Vec3,
+    /// Euler rotation in degrees
+    pub rot: Vec3,
+    /// Indices of the rows this point links to, only used for paths
+    pub next_points: Vec<u32>,
+    /// Index of the route this point follows, or `None` to keep the route it already follows, e.g. for CSV files made
+    /// before the route column was added. Only used for objects.
+    pub route: Option<Option<u32>>,
+}
+
+pub trait ToFromCsvFormat
+where
+    Self: Component + Spawn,
+{
+    type CsvFormat: Serialize + DeserializeOwned;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat;
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>>;
+    fn is_path() -> bool {
+        false
+    }
+    fn has_route() -> bool {
+        false
+    }
+}
+
+// the route starts in the order the routes are saved in, so routes can be referred to by index
+fn route_starts(world: &mut World) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, &OrderId), With<RouteSettings>>();
+    let mut starts: Vec<_> = q.iter(world).map(|(e, order_id)| (e, order_id.0)).collect();
+    starts.sort_by_key(|x| x.1);
+    starts.into_iter().map(|x| x.0).collect()
+}
+
+/// Export a section to a CSV file in the order the points are in the KMP
+pub fn export_csv<T: ToFromCsvFormat>(world: &mut World, path: &Path) -> anyhow::Result<()> {
+    let file = File::create(path).context("could not create CSV file")?;
+    write_csv::<T>(world, file)
+}
+
+/// Write a section as CSV in the order the points are in the KMP
+pub fn write_csv<T: ToFromCsvFormat>(world: &mut World, writer: impl Write) -> anyhow::Result<()> {
+    let routes = route_starts(world);
+    let mut q = world.query::<(
+        Entity,
+        &OrderId,
+        &T,
+        &Transform,
+        Option<&KmpPathNode>,
+        Option<&RouteLink>,
+    )>();
+    let mut items: Vec<_> = q.iter(world).collect();
+    items.sort_by_key(|x| x.1);
+
+    let index_map: EntityHashMap<u32> = items.iter().enumerate().map(|(i, x)| (x.0, i as u32)).collect();
+
+    let mut wtr = csv::Writer::from_writer(writer);
+    for (_, _, component, transform, node, route_link) in items {
+        let mut next_points: Vec<u32> = node
+            .map(|node| {
+                node.get_next()
+                    .iter()
+                    .filter_map(|e| index_map.get(e).copied())
+                    .collect()
+            })
+            .unwrap_or_default();
+        next_points.sort();
+        let row = T::to_csv_format(CsvPoint {
+            component: component.clone(),
+            pos: transform.translation,
+            rot: get_euler_rot(transform),
+            next_points,
+            route: Some(
+                route_link
+                    .and_then(|x| routes.iter().position(|e| *e == x.0))
+                    .map(|x| x as u32),
+            ),
+        });
+        wtr.serialize(row).context("could not write CSV row")?;
+    }
+    wtr.flush().context("could not write CSV file")?;
+    Ok(())
+}
+
+/// Read all the points in a CSV file, checking that every point they link to exists
+pub fn read_csv_points<T: ToFromCsvFormat>(path: &Path) -> anyhow::Result<Vec<CsvPoint<T>>> {
+    let file = File::open(path).context("could not open CSV file")?;
+    parse_csv_points(file)
+}
+
+/// Read all the points in some CSV, checking that every point they link to exists
+pub fn parse_csv_points<T: ToFromCsvFormat>(reader: impl Read) -> anyhow::Result<Vec<CsvPoint<T>>> {
+    let mut rdr = csv::Reader::from_reader(reader);
+    let points = rdr
+        .deserialize::<T::CsvFormat>()
+        .enumerate()
+        .map(|(i, row)| T::from_csv_format(row.with_context(|| format!("could not read CSV row {}", i + 1))?))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    for (i, point) in points.iter().enumerate() {
+        if let Some(next) = point.next_points.iter().find(|x| **x as usize >= points.len()) {
+            bail!("row {} links to point {next}, which doesn't exist", i + 1);
+        }
+    }
+    Ok(points)
+}
+
+/// Replace all the points in a section with the points in a CSV file
+pub fn import_csv<T: ToFromCsvFormat>(world: &mut World, path: &Path) -> anyhow::Result<()> {
+    let file = File::open(path).context("could not open CSV file")?;
+    read_csv::<T>(world, file)
+}
+
+/// Replace all the points in a section with the points in some CSV. The existing points are updated in place row by
+/// row, so anything that links to them (like checkpoints to their respawn points) still does, and points are only
+/// spawned or despawned where the number of rows has changed.
+pub fn read_csv<T: ToFromCsvFormat>(world: &mut World, reader: impl Read) -> anyhow::Result<()> {
+    // read the whole file before touching anything, so that a bad file doesn't lose the existing points
+    let points = parse_csv_points::<T>(reader)?;
+    let routes = route_starts(world);
+    if let Some(route) = points
+        .iter()
+        .filter_map(|x| x.route.flatten())
+        .find(|x| *x as usize >= routes.len())
+    {
+        bail!("route {route} doesn't exist");
+    }
+
+    let mut q = world.query_filtered::<(Entity, &OrderId), With<T>>();
+    let mut old: Vec<_> = q.iter(world).map(|(e, order_id)| (e, order_id.0)).collect();
+    old.sort_by_key(|x| x.1);
+    let old: Vec<_> = old.into_iter().map(|x| x.0).collect();
+    for e in old.iter().skip(points.len()) {
+        world.entity_mut(*e).despawn_recursive();
+    }
+
+    let visible = world.resource::<KmpEditMode>().in_mode::<T>();
+    let mut entities = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        let e = match old.get(i) {
+            Some(e) => {
+                let mut entity = world.entity_mut(*e);
+                entity.insert((point.component.clone(), OrderId(i as u32)));
+                if let Some(mut transform) = entity.get_mut::<Transform>() {
+                    transform.translation = point.pos;
+                    set_euler_rot(point.rot, &mut transform);
+                }
+                *e
+            }
+            None => Spawner::builder()
+                .component(point.component.clone())
+                .pos(point.pos)
+                .rot(point.rot)
+                .order_id(i as u32)
+                .visible(visible)
+                .build()
+                .spawn(world),
+        };
+        if let Some(route) = point.route.filter(|_| T::has_route()) {
+            let route = route.map(|x| routes[x as usize]);
+            if world.get::<RouteLink>(e).map(|x| x.0) != route {
+                world.entity_mut(e).remove::<RouteLink>();
+                if let Some(route) = route {
+                    world.entity_mut(e).insert(RouteLink(route));
+                }
+            }
+        }
+        entities.push(e);
+    }
+    if T::is_path() {
+        // the links are all made again from the rows
+        for e in entities.iter() {
+            let next = world.get::<KmpPathNode>(*e).map(|x| x.get_next()).unwrap_or_default();
+            for next_e in next {
+                KmpPathNode::unlink_nodes(*e, next_e, world);
+            }
+            world.entity_mut(*e).remove::<PathOverallStart>();
+        }
+        for (point, e) in points.iter().zip(entities.iter()) {
+            for next in point.next_points.iter() {
+                KmpPathNode::link_nodes(*e, entities[*next as usize], world);
+            }
+        }
+        if let Some(first) = entities.first() {
+            world.entity_mut(*first).insert(PathOverallStart);
+        }
+        world.send_event(RecalcPaths::all());
+    }
+    world.send_event_default::<RefreshOrdering>();
+    Ok(())
+}
+
+fn next_points_to_string(next_points: &[u32]) -> String {
+    next_points.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(" ")
+}
+fn parse_next_points(s: &str) -> anyhow::Result<Vec<u32>> {
+    s.split_whitespace()
+        .map(|x| x.parse().with_context(|| format!("'{x}' is not a valid point index")))
+        .collect()
+}
+
+// a point with no route is written as 'none', so that a blank cell can mean the route is left as it is
+fn route_to_string(route: Option<Option<u32>>) -> String {
+    match route {
+        Some(Some(route)) => route.to_string(),
+        Some(None) => "none".into(),
+        None => String::new(),
+    }
+}
+
+fn parse_route(s: &str) -> anyhow::Result<Option<Option<u32>>> {
+    match s.trim() {
+        "" => Ok(None),
+        "none" => Ok(Some(None)),
+        x => Ok(Some(Some(
+            x.parse().with_context(|| format!("'{x}' is not a valid route index"))?,
+        ))),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StartPointCsvRow {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    rotation_x: f32,
+    rotation_y: f32,
+    rotation_z: f32,
+    player_index: i16,
+}
+impl ToFromCsvFormat for StartPoint {
+    type CsvFormat = StartPointCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        StartPointCsvRow {
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            rotation_x: point.rot.x,
+            rotation_y: point.rot.y,
+            rotation_z: point.rot.z,
+            player_index: point.component.player_index,
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: StartPoint {
+                player_index: row.player_index,
+            },
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: vec3(row.rotation_x, row.rotation_y, row.rotation_z),
+            next_points: Vec::new(),
+            route: None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EnemyPathCsvRow {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    leniency: f32,
+    setting_1: EnemyPathSetting1,
+    setting_2: EnemyPathSetting2,
+    setting_3: u8,
+    next_points: String,
+}
+impl ToFromCsvFormat for EnemyPathPoint {
+    type CsvFormat = EnemyPathCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        EnemyPathCsvRow {
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            leniency: point.component.leniency,
+            setting_1: point.component.setting_1,
+            setting_2: point.component.setting_2,
+            setting_3: point.component.setting_3,
+            next_points: next_points_to_string(&point.next_points),
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: EnemyPathPoint {
+                leniency: row.leniency,
+                setting_1: row.setting_1,
+                setting_2: row.setting_2,
+                setting_3: row.setting_3,
+            },
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: Vec3::ZERO,
+            next_points: parse_next_points(&row.next_points)?,
+            route: None,
+        })
+    }
+    fn is_path() -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ItemPathCsvRow {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    bullet_control: f32,
+    bullet_height: ItemPathBulletHeight,
+    bullet_cant_drop: bool,
+    low_shell_priority: bool,
+    next_points: String,
+}
+impl ToFromCsvFormat for ItemPathPoint {
+    type CsvFormat = ItemPathCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        ItemPathCsvRow {
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            bullet_control: point.component.bullet_control,
+            bullet_height: point.component.bullet_height,
+            bullet_cant_drop: point.component.bullet_cant_drop,
+            low_shell_priority: point.component.low_shell_priority,
+            next_points: next_points_to_string(&point.next_points),
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: ItemPathPoint {
+                bullet_control: row.bullet_control,
+                bullet_height: row.bullet_height,
+                bullet_cant_drop: row.bullet_cant_drop,
+                low_shell_priority: row.low_shell_priority,
+            },
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: Vec3::ZERO,
+            next_points: parse_next_points(&row.next_points)?,
+            route: None,
+        })
+    }
+    fn is_path() -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RespawnPointCsvRow {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    rotation_x: f32,
+    rotation_y: f32,
+    rotation_z: f32,
+    sound_trigger: i8,
+}
+impl ToFromCsvFormat for RespawnPoint {
+    type CsvFormat = RespawnPointCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        RespawnPointCsvRow {
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            rotation_x: point.rot.x,
+            rotation_y: point.rot.y,
+            rotation_z: point.rot.z,
+            sound_trigger: point.component.sound_trigger,
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: RespawnPoint {
+                sound_trigger: row.sound_trigger,
+            },
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: vec3(row.rotation_x, row.rotation_y, row.rotation_z),
+            next_points: Vec::new(),
+            route: None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ObjectCsvRow {
+    object_id: u16,
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    rotation_x: f32,
+    rotation_y: f32,
+    rotation_z: f32,
+    scale_x: f32,
+    scale_y: f32,
+    scale_z: f32,
+    setting_1: u16,
+    setting_2: u16,
+    setting_3: u16,
+    setting_4: u16,
+    setting_5: u16,
+    setting_6: u16,
+    setting_7: u16,
+    setting_8: u16,
+    presence: u16,
+    // older CSV files don't have these columns
+    #[serde(default)]
+    extended_presence: u16,
+    #[serde(default)]
+    route: String,
+}
+impl ToFromCsvFormat for Object {
+    type CsvFormat = ObjectCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        let s = point.component.settings;
+        ObjectCsvRow {
+            object_id: point.component.object_id,
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            rotation_x: point.rot.x,
+            rotation_y: point.rot.y,
+            rotation_z: point.rot.z,
+            scale_x: point.component.scale.x,
+            scale_y: point.component.scale.y,
+            scale_z: point.component.scale.z,
+            setting_1: s[0],
+            setting_2: s[1],
+            setting_3: s[2],
+            setting_4: s[3],
+            setting_5: s[4],
+            setting_6: s[5],
+            setting_7: s[6],
+            setting_8: s[7],
+            presence: point.component.presence,
+            extended_presence: point.component.extended_presence.to_raw(),
+            route: route_to_string(point.route),
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: Object {
+                object_id: row.object_id,
+                scale: vec3(row.scale_x, row.scale_y, row.scale_z),
+                settings: [
+                    row.setting_1,
+                    row.setting_2,
+                    row.setting_3,
+                    row.setting_4,
+                    row.setting_5,
+                    row.setting_6,
+                    row.setting_7,
+                    row.setting_8,
+                ],
+                presence: row.presence,
+                extended_presence: ExtendedPresence::from_raw(row.extended_presence),
+            },
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: vec3(row.rotation_x, row.rotation_y, row.rotation_z),
+            next_points: Vec::new(),
+            route: parse_route(&row.route)?,
+        })
+    }
+    fn has_route() -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CannonPointCsvRow {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    rotation_x: f32,
+    rotation_y: f32,
+    rotation_z: f32,
+    shoot_effect: CannonShootEffect,
+}
+impl ToFromCsvFormat for CannonPoint {
+    type CsvFormat = CannonPointCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        CannonPointCsvRow {
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            rotation_x: point.rot.x,
+            rotation_y: point.rot.y,
+            rotation_z: point.rot.z,
+            shoot_effect: point.component.shoot_effect,
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: CannonPoint {
+                shoot_effect: row.shoot_effect,
+            },
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: vec3(row.rotation_x, row.rotation_y, row.rotation_z),
+            next_points: Vec::new(),
+            route: None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BattleFinishPointCsvRow {
+    position_x: f32,
+    position_y: f32,
+    position_z: f32,
+    rotation_x: f32,
+    rotation_y: f32,
+    rotation_z: f32,
+}
+impl ToFromCsvFormat for BattleFinishPoint {
+    type CsvFormat = BattleFinishPointCsvRow;
+    fn to_csv_format(point: CsvPoint<Self>) -> Self::CsvFormat {
+        BattleFinishPointCsvRow {
+            position_x: point.pos.x,
+            position_y: point.pos.y,
+            position_z: point.pos.z,
+            rotation_x: point.rot.x,
+            rotation_y: point.rot.y,
+            rotation_z: point.rot.z,
+        }
+    }
+    fn from_csv_format(row: Self::CsvFormat) -> anyhow::Result<CsvPoint<Self>> {
+        Ok(CsvPoint {
+            component: BattleFinishPoint,
+            pos: vec3(row.position_x, row.position_y, row.position_z),
+            rot: vec3(row.rotation_x, row.rotation_y, row.rotation_z),
+            next_points: Vec::new(),
+            route: None,
+        })
+    }
+}
+
+/// Export whichever section the mode refers to, if that section can be stored as CSV
+pub fn export_section_csv(world: &mut World, mode: KmpEditMode, path: &Path) -> anyhow::Result<()> {
+    let file = File::create(path).context("could not create CSV file")?;
+    write_section_csv(world, mode, file)
+}
+
+/// Import whichever section the mode refers to, if that section can be stored as CSV
+pub fn import_section_csv(world: &mut World, mode: KmpEditMode, path: &Path) -> anyhow::Result<()> {
+    let file = File::open(path).context("could not open CSV file")?;
+    read_section_csv(world, mode, file)
+}
+
+/// Write whichever section the mode refers to as CSV, if that section can be stored as CSV
+pub fn write_section_csv(world: &mut World, mode: KmpEditMode, writer: impl Write) -> anyhow::Result<()> {
+    match mode {
+        KmpEditMode::StartPoints => write_csv::<StartPoint>(world, writer),
+        KmpEditMode::EnemyPaths => write_csv::<EnemyPathPoint>(world, writer),
+        KmpEditMode::ItemPaths => write_csv::<ItemPathPoint>(world, writer),
+        KmpEditMode::RespawnPoints => write_csv::<RespawnPoint>(world, writer),
+        KmpEditMode::Objects => write_csv::<Object>(world, writer),
+        KmpEditMode::CannonPoints => write_csv::<CannonPoint>(world, writer),
+        KmpEditMode::BattleFinishPoints => write_csv::<BattleFinishPoint>(world, writer),
+        _ => bail!("{mode} can't be stored as CSV"),
+    }
+}
+
+/// Replace whichever section the mode refers to with the points in some CSV, if that section can be stored as CSV
+pub fn read_section_csv(world: &mut World, mode: KmpEditMode, reader: impl Read) -> anyhow::Result<()> {
+    match mode {
+        KmpEditMode::StartPoints => read_csv::<StartPoint>(world, reader),
+        KmpEditMode::EnemyPaths => read_csv::<EnemyPathPoint>(world, reader),
+        KmpEditMode::ItemPaths => read_csv::<ItemPathPoint>(world, reader),
+        KmpEditMode::RespawnPoints => read_csv::<RespawnPoint>(world, reader),
+        KmpEditMode::Objects => read_csv::<Object>(world, reader),
+        KmpEditMode::CannonPoints => read_csv::<CannonPoint>(world, reader),
+        KmpEditMode::BattleFinishPoints => read_csv::<BattleFinishPoint>(world, reader),
+        _ => bail!("{mode} can't be stored as CSV"),
+    }
+}
+
+pub fn section_supports_csv(mode: KmpEditMode) -> bool {
+    matches!(
+        mode,
+        KmpEditMode::StartPoints
+            | KmpEditMode::EnemyPaths
+            | KmpEditMode::ItemPaths
+            | KmpEditMode::RespawnPoints
+            | KmpEditMode::Objects
+            | KmpEditMode::CannonPoints
+            | KmpEditMode::BattleFinishPoints
+    )
+}
+
+/// Name of the file a section is exported to by default, after the section in the KMP
+pub fn section_csv_file_name(mode: KmpEditMode) -> String {
+    let name = match mode {
+        KmpEditMode::StartPoints => "ktpt",
+        KmpEditMode::EnemyPaths => "enpt",
+        KmpEditMode::ItemPaths => "itpt",
+        KmpEditMode::RespawnPoints => "jgpt",
+        KmpEditMode::Objects => "gobj",
+        KmpEditMode::CannonPoints => "cnpt",
+        KmpEditMode::BattleFinishPoints => "mspt",
+        _ => "section",
+    };
+    format!("{name}.csv")
+}
+
+fn export_import_csv(world: &mut World, mut reader: Local<ManualEventReader<FileDialogResult>>) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let results: Vec<_> = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ExportCsv(_) | DialogType::ImportCsv(_)))
+        .map(|x| (x.dialog_type, x.path.clone()))
+        .collect();
+    for (dialog_type, path) in results {
+        match dialog_type {
+            DialogType::ExportCsv(mode) => export_section_csv(world, mode, &path)?,
+            DialogType::ImportCsv(mode) => import_section_csv(world, mode, &path)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use super::{
+    checkpoints::CheckpointRespawnLink,
+    components::{
+        AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
+        KmpSelectablePoint, Object, RespawnPoint, RoutePoint, RouteSettings, StartPoint,
+    },
+    ordering::OrderId,
+    routes::RouteLink,
+    sections::KmpEditMode,
+    validate::{IssueSeverity, KmpIssue},
+};
+use crate::ui::{
+    file_dialog::{DialogType, FileDialogResult},
+    settings::AppSettings,
+};
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub fn custom_rules_plugin(app: &mut App) {
+    app.add_systems(Update, choose_custom_rules_file.run_if(on_event::<FileDialogResult>()));
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CustomRuleSettings {
+    /// A TOML file of extra rules, which the KMP is checked against along with the built in checks. This lets a
+    /// community enforce its own standards for tracks without the editor having to know about them. The path is kept
+    /// absolute, as the settings are shared by every project.
+    pub file: Option<PathBuf>,
+}
+
+/// An example of every kind of rule, for showing to anyone writing a rules file
+pub const EXAMPLE_RULES: &str = r#"[[rule]]
+name = "Too many objects"
+section = "Objects"
+max_count = 150
+
+[[rule]]
+name = "Respawn points need a sound trigger"
+section = "Respawn Points"
+severity = "error"
+field = "sound_trigger"
+op = ">="
+value = 0
+
+[[rule]]
+name = "Objects should be linked to a route"
+section = "Objects"
+require_link = true
+"#;
+
+#[derive(Deserialize)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<CustomRule>,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum RuleSeverity {
+    Error,
+    #[default]
+    Warning,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+enum CompareOp {
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+}
+impl CompareOp {
+    // numbers can be compared in any way, but anything else can only be equal or not
+    fn holds(self, a: &Value, b: &Value) -> bool {
+        if let Some((a, b)) = a.as_f64().zip(b.as_f64()) {
+            return match self {
+                Self::Eq => a == b,
+                Self::Ne => a != b,
+                Self::Lt => a < b,
+                Self::Le => a <= b,
+                Self::Gt => a > b,
+                Self::Ge => a >= b,
+            };
+        }
+        match self {
+            Self::Eq => a == b,
+            Self::Ne => a != b,
+            _ => false,
+        }
+    }
+}
+
+/// A single rule from the rules file. Each rule checks one section in one of three ways: how many points it has,
+/// that a field of every point compares to a value, or that every point is linked to what it should be.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomRule {
+    /// Shown in the issues tab when the rule is broken
+    name: String,
+    /// The section as it is named in the editor, e.g. 'Enemy Paths'
+    section: String,
+    #[serde(default)]
+    severity: RuleSeverity,
+    min_count: Option<usize>,
+    max_count: Option<usize>,
+    /// The field of each point to check, with '.' between the parts of nested fields like 'scale.1'. The position of
+    /// each point is 'position_x', 'position_y' and 'position_z'.
+    field: Option<String>,
+    op: Option<CompareOp>,
+    value: Option<toml::Value>,
+    /// Checkpoints must link to a respawn point, and objects, cameras and areas to a route
+    #[serde(default)]
+    require_link: bool,
+}
+
+// every point in a section in the order they are in the KMP, with its fields in a form they can be looked up by name
+fn section_points<T: Component + Serialize>(world: &mut World) -> Vec<(Entity, Value, Vec3)> {
+    let mut q = world.query_filtered::<(Entity, &OrderId, &T, &Transform), With<KmpSelectablePoint>>();
+    let mut items: Vec<_> = q.iter(world).collect();
+    items.sort_by_key(|x| x.1);
+    items
+        .into_iter()
+        .map(|(e, _, component, transform)| {
+            let value = serde_json::to_value(component).unwrap_or_default();
+            (e, value, transform.translation)
+        })
+        .collect()
+}
+
+fn points_in(world: &mut World, mode: KmpEditMode) -> anyhow::Result<Vec<(Entity, Value, Vec3)>> {
+    Ok(match mode {
+        KmpEditMode::StartPoints => section_points::<StartPoint>(world),
+        KmpEditMode::EnemyPaths => section_points::<EnemyPathPoint>(world),
+        KmpEditMode::ItemPaths => section_points::<ItemPathPoint>(world),
+        KmpEditMode::Checkpoints => section_points::<Checkpoint>(world),
+        KmpEditMode::RespawnPoints => section_points::<RespawnPoint>(world),
+        KmpEditMode::Objects => section_points::<Object>(world),
+        KmpEditMode::Routes => section_points::<RoutePoint>(world),
+        KmpEditMode::Areas => section_points::<AreaPoint>(world),
+        KmpEditMode::Cameras => section_points::<KmpCamera>(world),
+        KmpEditMode::CannonPoints => section_points::<CannonPoint>(world),
+        KmpEditMode::BattleFinishPoints => section_points::<BattleFinishPoint>(world),
+        KmpEditMode::TrackInfo => bail!("{mode} doesn't have points to check"),
+    })
+}
+
+fn field_value(value: &Value, pos: Vec3, field: &str) -> Option<Value> {
+    match field {
+        "position_x" => return Some(pos.x.into()),
+        "position_y" => return Some(pos.y.into()),
+        "position_z" => return Some(pos.z.into()),
+        _ => {}
+    }
+    let mut value = value;
+    for part in field.split('.') {
+        value = match part.parse::<usize>() {
+            Ok(i) => value.get(i)?,
+            Err(_) => value.get(part)?,
+        };
+    }
+    Some(value.clone())
+}
+
+fn is_linked(world: &World, e: Entity, mode: KmpEditMode) -> anyhow::Result<bool> {
+    Ok(match mode {
+        KmpEditMode::Checkpoints => world
+            .get::<CheckpointRespawnLink>(e)
+            .is_some_and(|x| world.get::<RespawnPoint>(x.0).is_some()),
+        KmpEditMode::Objects | KmpEditMode::Cameras | KmpEditMode::Areas => world
+            .get::<RouteLink>(e)
+            .is_some_and(|x| world.get::<RouteSettings>(x.0).is_some()),
+        _ => bail!("{mode} can't be linked to anything"),
+    })
+}
+
+fn check_rule(world: &mut World, rule: &CustomRule) -> anyhow::Result<Option<KmpIssue>> {
+    let mode =
+        KmpEditMode::from_str(&rule.section).with_context(|| format!("there is no section '{}'", rule.section))?;
+    let points = points_in(world, mode)?;
+    let count = points.len();
+    let severity = match rule.severity {
+        RuleSeverity::Error => IssueSeverity::Error,
+        RuleSeverity::Warning => IssueSeverity::Warning,
+    };
+    let issue = |message: String, entities: Vec<Entity>| {
+        Ok(Some(KmpIssue {
+            severity,
+            message,
+            mode,
+            entities,
+        }))
+    };
+
+    if let Some(min) = rule.min_count.filter(|min| count < *min) {
+        return issue(
+            format!("{} (there are {count} points, at least {min} are needed)", rule.name),
+            Vec::new(),
+        );
+    }
+    if let Some(max) = rule.max_count.filter(|max| count > *max) {
+        // the points past the limit are the ones that would have to go
+        let entities = points.iter().skip(max).map(|x| x.0).collect();
+        return issue(
+            format!("{} (there are {count} points, at most {max} are allowed)", rule.name),
+            entities,
+        );
+    }
+    let mut failing = Vec::new();
+    if let Some(field) = &rule.field {
+        let (Some(op), Some(value)) = (rule.op, &rule.value) else {
+            bail!("checking '{field}' needs both 'op' and 'value'");
+        };
+        let value = serde_json::to_value(value)?;
+        for (e, point, pos) in points.iter() {
+            let Some(field_value) = field_value(point, *pos, field) else {
+                bail!("{mode} don't have a field '{field}'");
+            };
+            if !op.holds(&field_value, &value) {
+                failing.push(*e);
+            }
+        }
+    } else if rule.require_link {
+        for (e, ..) in points.iter() {
+            if !is_linked(world, *e, mode)? {
+                failing.push(*e);
+            }
+        }
+    }
+    if failing.is_empty() {
+        return Ok(None);
+    }
+    issue(format!("{} ({} points)", rule.name, failing.len()), failing)
+}
+
+fn read_rules(path: &Path) -> anyhow::Result<Vec<CustomRule>> {
+    let text = fs::read_to_string(path).context("could not read the rules file")?;
+    let file: RulesFile = toml::from_str(&text).context("could not parse the rules file")?;
+    Ok(file.rules)
+}
+
+/// Check the KMP against the rules in the custom rules file, if one has been chosen. Problems with the rules file
+/// itself are shown as errors too, so that broken rules don't go unnoticed.
+pub fn custom_rule_issues(world: &mut World) -> Vec<KmpIssue> {
+    let Some(path) = world.resource::<AppSettings>().custom_rules.file.clone() else {
+        return Vec::new();
+    };
+    let config_error = |message: String| KmpIssue {
+        severity: IssueSeverity::Error,
+        message,
+        mode: KmpEditMode::TrackInfo,
+        entities: Vec::new(),
+    };
+    let rules = match read_rules(&path) {
+        Ok(rules) => rules,
+        Err(err) => return vec![config_error(format!("Custom rules: {err:#}"))],
+    };
+    let mut issues = Vec::new();
+    for rule in rules.iter() {
+        match check_rule(world, rule) {
+            Ok(issue) => issues.extend(issue),
+            Err(err) => issues.push(config_error(format!("Custom rule '{}': {err:#}", rule.name))),
+        }
+    }
+    issues
+}
+
+fn choose_custom_rules_file(mut ev_file_dialog: EventReader<FileDialogResult>, mut settings: ResMut<AppSettings>) {
+    for FileDialogResult { path, dialog_type } in ev_file_dialog.read() {
+        if matches!(dialog_type, DialogType::OpenCustomRules) {
+            settings.custom_rules.file = Some(path.clone());
+        }
+    }
+}
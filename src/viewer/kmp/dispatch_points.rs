@@ -0,0 +1,63 @@
+use super::{
+    components::{BattleDispatchPoint, TrackInfo, TrackType},
+    path::RecalcPaths,
+};
+use crate::{util::world_to_gizmo_cam, viewer::camera::Gizmo2dCam};
+use bevy::{color::palettes::css, prelude::*, transform::TransformSystem};
+use bevy_vector_shapes::{painter::ShapePainter, shapes::DiscPainter};
+
+pub fn dispatch_points_plugin(app: &mut App) {
+    app.add_systems(Update, recalc_paths_on_dispatch_change).add_systems(
+        PostUpdate,
+        draw_dispatch_points
+            .after(TransformSystem::TransformPropagate)
+            .run_if(is_battle_track),
+    );
+}
+
+fn is_battle_track(track_info: Option<Res<TrackInfo>>) -> bool {
+    track_info.is_some_and(|x| x.track_type == TrackType::Battle)
+}
+
+// dispatch points are split into their own groups, so the paths need recalculating whenever one is marked or unmarked
+fn recalc_paths_on_dispatch_change(
+    q_added: Query<(), Added<BattleDispatchPoint>>,
+    mut removed: RemovedComponents<BattleDispatchPoint>,
+    mut ev_recalc_paths: EventWriter<RecalcPaths>,
+) {
+    let removed_any = removed.read().count() > 0;
+    if !q_added.is_empty() || removed_any {
+        ev_recalc_paths.send(RecalcPaths::enemy());
+    }
+}
+
+// draw a double ring around each dispatch point using the 2d gizmo camera so they stand out from the normal points
+fn draw_dispatch_points(
+    q_dispatch: Query<(&GlobalTransform, &ViewVisibility), With<BattleDispatchPoint>>,
+    q_cam: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_gizmo_cam: Query<(&Camera, &GlobalTransform), With<Gizmo2dCam>>,
+    mut painter: ShapePainter,
+) {
+    if q_dispatch.is_empty() {
+        return;
+    }
+    let Some(cam) = q_cam.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let gizmo_cam = q_gizmo_cam.single();
+
+    painter.color = css::GOLD.into();
+    painter.hollow = true;
+    painter.thickness = 2.;
+
+    for (gt, view_visibility) in q_dispatch.iter() {
+        if !view_visibility.get() {
+            continue;
+        }
+        if let Some(pos) = world_to_gizmo_cam(cam, gizmo_cam, gt.translation()) {
+            painter.transform.translation = pos;
+            painter.circle(12.);
+            painter.circle(17.);
+        }
+    }
+}
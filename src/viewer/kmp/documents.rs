@@ -0,0 +1,270 @@
+use super::{
+    branches::KmpBranches,
+    concurrent_edit::{release_kmp, ThisEditor},
+    find_value::{FindValue, FoundValues},
+    ghosts::SavedGhosts,
+    header_values::OpenedHeaderValues,
+    history::record_save,
+    project::companion_kcl,
+    respawn_kmp,
+    sections::KmpEditMode,
+    sidecar::KmpSidecar,
+    validate::{KmpIssues, ValidateKmp},
+    write_kmp, write_kmp_file, KmpState,
+};
+use crate::{
+    ui::{errors::show_errors, ui_state::KmpFilePath, update_ui::KclFileSelected},
+    util::kmp_file::KmpFile,
+    viewer::{edit::undo::UndoHistory, kcl_model::unload_kcl},
+};
+use anyhow::bail;
+use bevy::prelude::*;
+use std::{
+    io::Cursor,
+    mem,
+    path::{Path, PathBuf},
+};
+
+pub fn documents_plugin(app: &mut App) {
+    app.init_resource::<OpenDocuments>()
+        .add_event::<SwitchDocument>()
+        .add_event::<CloseDocument>()
+        .add_systems(
+            Update,
+            (
+                switch_document.run_if(on_event::<SwitchDocument>()),
+                close_document.pipe(show_errors).run_if(on_event::<CloseDocument>()),
+            ),
+        );
+}
+
+/// Every KMP that is open, in the order they were opened. Only the active one is spawned in the world, the others
+/// are kept as they were left (along with their undo history) until they are switched back to.
+#[derive(Resource, Default)]
+pub struct OpenDocuments {
+    pub docs: Vec<KmpDocument>,
+    pub active: Option<usize>,
+}
+
+pub struct KmpDocument {
+    pub path: PathBuf,
+    /// Everything needed to respawn the document, which is `None` while it is the active one
+    stored: Option<StoredDocument>,
+}
+
+struct StoredDocument {
+    state: KmpState,
+    sidecar: Option<KmpSidecar>,
+    history: UndoHistory,
+    ghosts: Option<SavedGhosts>,
+    header_values: Option<OpenedHeaderValues>,
+    edit_mode: KmpEditMode,
+    branches: KmpBranches,
+    /// Whether the KMP had been checked, so it can be checked again when it is respawned, as the issues point to the
+    /// old entities
+    checked: bool,
+    /// The last search for values, which is done again when the KMP is respawned for the same reason
+    searched: Option<FindValue>,
+}
+
+impl KmpDocument {
+    pub fn name(&self) -> String {
+        self.path.file_name().unwrap_or_default().to_string_lossy().to_string()
+    }
+}
+
+impl OpenDocuments {
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        self.docs.iter().position(|x| x.path == path)
+    }
+    /// Whether a document has changed since it was last opened or saved. The active document's history is in the world.
+    pub fn has_unsaved_changes(&self, index: usize, active_history: &UndoHistory) -> bool {
+        match self.docs.get(index).map(|x| &x.stored) {
+            Some(Some(stored)) => stored.history.has_unsaved_changes(),
+            Some(None) => active_history.has_unsaved_changes(),
+            None => false,
+        }
+    }
+    /// Whether a document was opened read-only. The active document's sidecar is in the world.
+    pub fn is_read_only(&self, index: usize, active_sidecar: Option<&KmpSidecar>) -> bool {
+        match self.docs.get(index).map(|x| &x.stored) {
+            Some(Some(stored)) => stored.sidecar.as_ref().is_some_and(|x| x.read_only),
+            Some(None) => active_sidecar.is_some_and(|x| x.read_only),
+            None => false,
+        }
+    }
+    /// Take the active document out of the world, so that another can be spawned in its place. Returns false if it
+    /// can't be taken yet, because it is still being spawned.
+    pub fn stash_active(world: &mut World) -> bool {
+        let Some(active) = world.resource::<OpenDocuments>().active else {
+            return true;
+        };
+        let Some(state) = UndoHistory::record_latest(world) else {
+            return false;
+        };
+        let stored = StoredDocument {
+            state,
+            sidecar: world.remove_resource::<KmpSidecar>(),
+            history: mem::take(&mut *world.resource_mut::<UndoHistory>()),
+            ghosts: world.remove_resource::<SavedGhosts>(),
+            header_values: world.remove_resource::<OpenedHeaderValues>(),
+            edit_mode: *world.resource::<KmpEditMode>(),
+            branches: mem::take(&mut *world.resource_mut::<KmpBranches>()),
+            checked: world.resource::<KmpIssues>().checked,
+            searched: world.resource::<FoundValues>().searched,
+        };
+        let mut docs = world.resource_mut::<OpenDocuments>();
+        docs.docs[active].stored = Some(stored);
+        docs.active = None;
+        true
+    }
+    /// Add a newly opened document, which is about to be spawned into the world, as the active one
+    pub fn push_active(&mut self, path: PathBuf) {
+        self.docs.push(KmpDocument { path, stored: None });
+        self.active = Some(self.docs.len() - 1);
+    }
+}
+
+// spawn a stored document back into the world, as the active one
+fn restore_document(world: &mut World, index: usize) {
+    let mut docs = world.resource_mut::<OpenDocuments>();
+    let doc = &mut docs.docs[index];
+    let Some(stored) = doc.stored.take() else {
+        return;
+    };
+    let path = doc.path.clone();
+    docs.active = Some(index);
+
+    // the history and branches are put back first, so that they aren't cleared by the path changing
+    let mut history = stored.history;
+    history.restored();
+    world.insert_resource(history);
+    world.insert_resource(stored.branches);
+    world.insert_resource(KmpFilePath(path.clone()));
+
+    *world.resource_mut::<KmpEditMode>() = stored.edit_mode;
+    respawn_kmp(world, &stored.state);
+    world.insert_resource(stored.sidecar.unwrap_or_default());
+    if let Some(header_values) = stored.header_values {
+        world.insert_resource(header_values);
+    }
+    match stored.ghosts {
+        Some(ghosts) => world.insert_resource(ghosts),
+        None => SavedGhosts::snapshot(world),
+    }
+    if stored.checked {
+        world.send_event(ValidateKmp);
+    }
+    if let Some(searched) = stored.searched {
+        world.send_event(searched);
+    }
+
+    // show the KCL that goes with the track, if it can be found, and otherwise don't leave the last track's KCL shown
+    unload_kcl(world);
+    let kcl_path = if path.extension().is_some_and(|x| x == "szs") {
+        path
+    } else {
+        companion_kcl(&path)
+    };
+    if kcl_path.exists() {
+        world.send_event(KclFileSelected(kcl_path));
+    }
+}
+
+/// Send this event to make the document at this index the active one
+#[derive(Event)]
+pub struct SwitchDocument(pub usize);
+
+/// Send this event to close the document at this index, saving it first if `save` is set
+#[derive(Event)]
+pub struct CloseDocument {
+    pub index: usize,
+    pub save: bool,
+}
+
+/// The document at this index has unsaved changes, so ask whether to save it before closing it
+#[derive(Resource)]
+pub struct CloseDocumentPrompt(pub usize);
+
+/// Switch to another open document, keeping the current one as it is
+pub fn switch_to_document(world: &mut World, index: usize) {
+    let docs = world.resource::<OpenDocuments>();
+    if index >= docs.docs.len() || docs.active == Some(index) {
+        return;
+    }
+    if !OpenDocuments::stash_active(world) {
+        return;
+    }
+    restore_document(world, index);
+}
+
+fn switch_document(world: &mut World) {
+    let Some(SwitchDocument(index)) = world.resource_mut::<Events<SwitchDocument>>().drain().last() else {
+        return;
+    };
+    switch_to_document(world, index);
+}
+
+fn close_document(world: &mut World) -> anyhow::Result<()> {
+    let Some(CloseDocument { index, save }) = world.resource_mut::<Events<CloseDocument>>().drain().last() else {
+        return Ok(());
+    };
+    let docs = world.resource::<OpenDocuments>();
+    // there always has to be a document open, so the last one can't be closed
+    if index >= docs.docs.len() || docs.docs.len() < 2 {
+        return Ok(());
+    }
+    let active = docs.active;
+    let path = docs.docs[index].path.clone();
+    let read_only = docs.is_read_only(index, world.get_resource::<KmpSidecar>());
+
+    if save && read_only {
+        bail!("the kmp was opened read-only, so can't be saved");
+    }
+    if save {
+        if active == Some(index) {
+            write_kmp(world)?;
+        } else {
+            let by = world.resource::<ThisEditor>().who();
+            let mut docs = world.resource_mut::<OpenDocuments>();
+            let doc = &mut docs.docs[index];
+            if let Some(stored) = doc.stored.as_mut() {
+                let old_kmp = stored
+                    .history
+                    .saved()
+                    .and_then(|x| KmpFile::read(&mut Cursor::new(x)).ok());
+                let bytes = write_kmp_file(&doc.path, &stored.state.kmp)?;
+                let mut sidecar = stored.sidecar.clone().unwrap_or_default();
+                sidecar.shown_areas = stored.state.shown_areas.clone();
+                sidecar.points = stored.state.point_metadata.clone();
+                record_save(&mut sidecar.history, by, old_kmp.as_ref(), &stored.state.kmp);
+                sidecar.save(&doc.path)?;
+                stored.sidecar = Some(sidecar);
+                stored.history.mark_saved(bytes);
+            }
+        }
+    }
+
+    if !read_only {
+        release_kmp(world, &path)?;
+    }
+
+    // closing the active document switches to the one next to it, without keeping the closed one
+    if active == Some(index) {
+        let next = if index + 1 < world.resource::<OpenDocuments>().docs.len() {
+            index + 1
+        } else {
+            index - 1
+        };
+        restore_document(world, next);
+    }
+    let mut docs = world.resource_mut::<OpenDocuments>();
+    docs.docs.remove(index);
+    if let Some(active) = docs.active.as_mut() {
+        if *active > index {
+            *active -= 1;
+        }
+    }
+    world.remove_resource::<CloseDocumentPrompt>();
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use super::{
+    checkpoints::CheckpointRespawnLink,
+    components::{KmpSelectablePoint, RespawnPoint, StartPoint, TrackInfo},
+    ordering::OrderId,
+    point::{spawn_point_section, AddRespawnPointPreview},
+    refresh_spawned_points, KmpComponent, KmpErrors,
+};
+use crate::{
+    ui::{
+        errors::show_errors,
+        file_dialog::{DialogType, FileDialogResult},
+        ui_state::KmpFilePath,
+    },
+    util::kmp_file::KmpFile,
+};
+use anyhow::{bail, Context};
+use bevy::{
+    ecs::{event::ManualEventReader, world::Command},
+    prelude::*,
+};
+use std::{fs, path::PathBuf};
+
+pub fn donor_import_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        import_from_donor_kmp
+            .pipe(show_errors)
+            .run_if(on_event::<FileDialogResult>()),
+    );
+}
+
+/// If a checkpoint's old respawn point is further than this from every respawn point in the donor KMP,
+/// the link is removed rather than pointing at some unrelated respawn point
+const MAX_RESPAWN_MATCH_DISTANCE: f32 = 1000.;
+
+/// The result of the last import from a donor KMP, so it can be shown to the user
+#[derive(Resource)]
+pub struct DonorImportReport {
+    pub donor: PathBuf,
+    pub matched: usize,
+    /// Indexes of checkpoints whose respawn link couldn't be matched to a new respawn point
+    pub unmatched: Vec<u32>,
+}
+
+/// Replace the start points, respawn points and track info with the ones from another KMP, keeping everything else.
+/// Checkpoint respawn links are moved over to whichever new respawn point is closest to the old one.
+fn import_from_donor_kmp(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<FileDialogResult>>,
+) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let Some(path) = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ImportDonorKmp))
+        .map(|x| x.path.clone())
+        .last()
+    else {
+        return Ok(());
+    };
+    if !world.contains_resource::<KmpFilePath>() {
+        bail!("a KMP needs to be open to import into");
+    }
+
+    let data = fs::read(&path).context("could not open donor kmp file")?;
+    let donor = KmpFile::read_any(&data).context("could not read donor kmp file")?;
+    let stgi = donor.stgi.first().context("donor kmp has no track info")?;
+
+    // remember where each checkpoint's respawn point was, as the old respawn points are about to be despawned
+    let cp_links: Vec<(Entity, Entity)> = world
+        .query::<(Entity, &CheckpointRespawnLink)>()
+        .iter(world)
+        .map(|(cp_e, link)| (cp_e, link.0))
+        .collect();
+    let cp_links: Vec<(Entity, Option<Vec3>)> = cp_links
+        .into_iter()
+        .map(|(cp_e, respawn_e)| (cp_e, world.get::<Transform>(respawn_e).map(|x| x.translation)))
+        .collect();
+
+    let old_points: Vec<_> = world
+        .query_filtered::<Entity, (With<KmpSelectablePoint>, Or<(With<StartPoint>, With<RespawnPoint>)>)>()
+        .iter(world)
+        .collect();
+    for e in old_points {
+        world.entity_mut(e).despawn_recursive();
+    }
+
+    world.init_resource::<KmpErrors>();
+
+    let track_info = TrackInfo::from_kmp(stgi, world);
+    world.insert_resource(track_info);
+
+    spawn_point_section::<StartPoint>(world, &donor);
+    let respawn_id_map = spawn_point_section::<RespawnPoint>(world, &donor);
+    let new_respawns: Vec<(Entity, Vec3)> = respawn_id_map
+        .values()
+        .map(|e| (*e, world.get::<Transform>(*e).unwrap().translation))
+        .collect();
+    for (e, _) in new_respawns.iter() {
+        AddRespawnPointPreview(*e).apply(world);
+    }
+
+    world.remove_resource::<KmpErrors>();
+
+    let mut report = DonorImportReport {
+        donor: path,
+        matched: 0,
+        unmatched: Vec::new(),
+    };
+    for (cp_e, old_pos) in cp_links {
+        let nearest = old_pos.and_then(|old_pos| {
+            new_respawns
+                .iter()
+                .map(|(e, pos)| (*e, pos.distance(old_pos)))
+                .filter(|(_, dist)| *dist <= MAX_RESPAWN_MATCH_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+        });
+        if let Some((respawn_e, _)) = nearest {
+            world.entity_mut(cp_e).insert(CheckpointRespawnLink(respawn_e));
+            report.matched += 1;
+        } else {
+            world.entity_mut(cp_e).remove::<CheckpointRespawnLink>();
+            report
+                .unmatched
+                .push(world.get::<OrderId>(cp_e).map(|x| x.0).unwrap_or_default());
+        }
+    }
+    report.unmatched.sort();
+    world.insert_resource(report);
+
+    refresh_spawned_points(world);
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use super::{
+    csv::{export_section_csv, import_section_csv},
+    sections::KmpEditMode,
+};
+use crate::{
+    ui::{errors::show_errors, ui_state::KmpFilePath},
+    util::open_with_default_program,
+};
+use anyhow::Context;
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use std::{fs, path::PathBuf, time::Duration, time::SystemTime};
+
+pub fn external_edit_plugin(app: &mut App) {
+    app.add_event::<EditSectionExternally>().add_systems(
+        Update,
+        (
+            start_external_edit
+                .pipe(show_errors)
+                .run_if(on_event::<EditSectionExternally>()),
+            reimport_external_edit
+                .pipe(show_errors)
+                .run_if(resource_exists::<ExternalEdit>.and_then(on_timer(Duration::from_millis(500)))),
+            stop_external_edit_on_open.run_if(resource_exists_and_changed::<KmpFilePath>),
+        ),
+    );
+}
+
+/// Send this event to export the current section to a CSV file and open it in the default program for CSV files.
+/// Whenever that file is saved, the section will be replaced with its contents.
+#[derive(Event, Default)]
+pub struct EditSectionExternally;
+
+/// The section currently being edited in an external program, remove this resource to stop watching the file
+#[derive(Resource)]
+pub struct ExternalEdit {
+    pub mode: KmpEditMode,
+    pub path: PathBuf,
+    modified: SystemTime,
+}
+
+fn start_external_edit(world: &mut World) -> anyhow::Result<()> {
+    let mode = *world.resource::<KmpEditMode>();
+    let file_name = format!("kmpeek {mode}.csv").replace(' ', "_").to_lowercase();
+    let path = std::env::temp_dir().join(file_name);
+
+    export_section_csv(world, mode, &path)?;
+    let modified = fs::metadata(&path)?.modified()?;
+    open_with_default_program(&path).context("could not open the CSV file")?;
+
+    world.insert_resource(ExternalEdit { mode, path, modified });
+    Ok(())
+}
+
+fn reimport_external_edit(world: &mut World) -> anyhow::Result<()> {
+    let edit = world.resource::<ExternalEdit>();
+    let Ok(modified) = fs::metadata(&edit.path).and_then(|x| x.modified()) else {
+        // the file has been deleted, so there's nothing to watch any more
+        world.remove_resource::<ExternalEdit>();
+        return Ok(());
+    };
+    if modified == edit.modified {
+        return Ok(());
+    }
+    let (mode, path) = (edit.mode, edit.path.clone());
+    // set this first so that we don't keep trying to import a file with errors in it
+    world.resource_mut::<ExternalEdit>().modified = modified;
+    import_section_csv(world, mode, &path)
+}
+
+// the exported file belongs to the previous KMP, so stop watching it when another is opened
+fn stop_external_edit_on_open(mut commands: Commands) {
+    commands.remove_resource::<ExternalEdit>();
+}
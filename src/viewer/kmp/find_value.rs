@@ -0,0 +1,180 @@
+use super::{
+    checkpoints::CheckpointRespawnLink,
+    components::{
+        AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object,
+        RespawnPoint, RoutePoint, RouteSettings, StartPoint, TrackInfo,
+    },
+    ordering::OrderId,
+    routes::RouteLink,
+    sections::KmpEditMode,
+};
+use crate::ui::ui_state::KmpFilePath;
+use bevy::{ecs::entity::EntityHashMap, prelude::*};
+use serde::Serialize;
+use serde_json::Value;
+
+pub fn find_value_plugin(app: &mut App) {
+    app.add_event::<FindValue>().init_resource::<FoundValues>().add_systems(
+        Update,
+        (
+            clear_found_values.run_if(resource_exists_and_changed::<KmpFilePath>),
+            find_value.run_if(on_event::<FindValue>()),
+        )
+            .chain(),
+    );
+}
+
+/// Send this event to search every numeric field of every section for values between `min` and `max` (inclusive)
+#[derive(Event, Clone, Copy)]
+pub struct FindValue {
+    pub min: f64,
+    pub max: f64,
+}
+
+pub struct ValueMatch {
+    pub mode: KmpEditMode,
+    /// The point the value belongs to, which is `None` for the track info
+    pub entity: Option<Entity>,
+    /// The index of the point in its section
+    pub index: u32,
+    pub field: String,
+    pub value: f64,
+}
+
+/// The values found by the last search, in section order
+#[derive(Resource, Default)]
+pub struct FoundValues {
+    /// The range that was searched for, or `None` if nothing has been searched for yet
+    pub searched: Option<FindValue>,
+    pub matches: Vec<ValueMatch>,
+}
+
+// every numeric value inside a serialized component, along with the name of the field it is in
+fn numeric_fields(prefix: String, value: &Value, out: &mut Vec<(String, f64)>) {
+    match value {
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64() {
+                out.push((prefix, n));
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                numeric_fields(format!("{prefix}[{i}]"), value, out);
+            }
+        }
+        // enum variants with data are serialized as an object with the variant name as the only key, which is left out
+        // so that e.g. the camera index of a camera area shows as 'kind.cam_index'
+        Value::Object(fields) => {
+            let is_variant = fields.len() == 1 && fields.keys().all(|x| x.starts_with(char::is_uppercase));
+            for (key, value) in fields.iter() {
+                let name = match (prefix.is_empty(), is_variant) {
+                    (_, true) => prefix.clone(),
+                    (true, false) => key.clone(),
+                    (false, false) => format!("{prefix}.{key}"),
+                };
+                numeric_fields(name, value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn search_component(
+    mode: KmpEditMode,
+    entity: Option<Entity>,
+    index: u32,
+    component: &impl Serialize,
+    search: FindValue,
+    matches: &mut Vec<ValueMatch>,
+) {
+    let Ok(value) = serde_json::to_value(component) else {
+        return;
+    };
+    let mut fields = Vec::new();
+    numeric_fields(String::new(), &value, &mut fields);
+    for (field, value) in fields {
+        if value >= search.min && value <= search.max {
+            matches.push(ValueMatch {
+                mode,
+                entity,
+                index,
+                field,
+                value,
+            });
+        }
+    }
+}
+
+fn search_section<T: Component + Serialize>(
+    world: &mut World,
+    search: FindValue,
+    route_indexes: &EntityHashMap<u32>,
+    matches: &mut Vec<ValueMatch>,
+) {
+    let mode = KmpEditMode::from_type::<T>();
+    let mut q = world.query::<(Entity, &T, &OrderId, Option<&RouteLink>, Option<&CheckpointRespawnLink>)>();
+    let mut items: Vec<_> = q.iter(world).collect();
+    items.sort_by_key(|x| x.2);
+    for (e, component, order_id, route_link, respawn_link) in items {
+        search_component(mode, Some(e), order_id.0, component, search, matches);
+
+        // links to other points are stored as entities, so search the index they are saved as
+        let route = route_link.and_then(|x| route_indexes.get(&x.0)).map(|x| ("route", *x));
+        let respawn = respawn_link
+            .and_then(|x| world.get::<OrderId>(x.0))
+            .map(|x| ("respawn", x.0));
+        for (field, value) in route.into_iter().chain(respawn) {
+            if value as f64 >= search.min && value as f64 <= search.max {
+                matches.push(ValueMatch {
+                    mode,
+                    entity: Some(e),
+                    index: order_id.0,
+                    field: field.into(),
+                    value: value as f64,
+                });
+            }
+        }
+    }
+}
+
+fn find_value(world: &mut World) {
+    let Some(search) = world.resource_mut::<Events<FindValue>>().drain().last() else {
+        return;
+    };
+    let mut matches = Vec::new();
+
+    // routes are indexed in the order of their first points
+    let mut q_route_start = world.query_filtered::<(Entity, &OrderId), With<RouteSettings>>();
+    let mut route_starts: Vec<_> = q_route_start.iter(world).collect();
+    route_starts.sort_by_key(|x| x.1);
+    let route_indexes: EntityHashMap<u32> = route_starts
+        .into_iter()
+        .enumerate()
+        .map(|(i, (e, _))| (e, i as u32))
+        .collect();
+
+    if let Some(track_info) = world.get_resource::<TrackInfo>() {
+        search_component(KmpEditMode::TrackInfo, None, 0, track_info, search, &mut matches);
+    }
+    search_section::<StartPoint>(world, search, &route_indexes, &mut matches);
+    search_section::<EnemyPathPoint>(world, search, &route_indexes, &mut matches);
+    search_section::<ItemPathPoint>(world, search, &route_indexes, &mut matches);
+    search_section::<Checkpoint>(world, search, &route_indexes, &mut matches);
+    search_section::<RespawnPoint>(world, search, &route_indexes, &mut matches);
+    search_section::<Object>(world, search, &route_indexes, &mut matches);
+    search_section::<RoutePoint>(world, search, &route_indexes, &mut matches);
+    search_section::<AreaPoint>(world, search, &route_indexes, &mut matches);
+    search_section::<KmpCamera>(world, search, &route_indexes, &mut matches);
+    search_section::<CannonPoint>(world, search, &route_indexes, &mut matches);
+    search_section::<BattleFinishPoint>(world, search, &route_indexes, &mut matches);
+
+    *world.resource_mut::<FoundValues>() = FoundValues {
+        searched: Some(search),
+        matches,
+    };
+}
+
+// the matches belong to the file they were found in
+fn clear_found_values(mut found: ResMut<FoundValues>) {
+    *found = FoundValues::default();
+}
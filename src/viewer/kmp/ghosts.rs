@@ -0,0 +1,101 @@
+use super::{components::*, sections::KmpEditMode};
+use crate::{
+    ui::{settings::AppSettings, util::Icons},
+    util::world_to_gizmo_cam,
+    viewer::camera::Gizmo2dCam,
+};
+use bevy::{
+    prelude::*,
+    transform::TransformSystem,
+    utils::{HashMap, HashSet},
+};
+use bevy_vector_shapes::{painter::ShapePainter, shapes::DiscPainter};
+
+pub fn ghosts_plugin(app: &mut App) {
+    // drawn after TransformPropagate for the same reason as the area handles, so they don't lag behind the camera
+    app.add_systems(
+        PostUpdate,
+        draw_saved_ghosts
+            .after(TransformSystem::TransformPropagate)
+            .run_if(resource_exists::<SavedGhosts>),
+    );
+}
+
+/// The positions of every point as they were when the KMP was last opened or saved, per section.
+/// These are drawn as faded 'ghosts' so the net effect of the current session is visible before saving.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SavedGhosts(pub HashMap<KmpEditMode, Vec<Vec3>>);
+
+impl SavedGhosts {
+    /// Record the current position of every point in the world as the saved state
+    pub fn snapshot(world: &mut World) {
+        let mut ghosts = Self::default();
+        ghosts.insert_section::<StartPoint>(world);
+        ghosts.insert_section::<EnemyPathPoint>(world);
+        ghosts.insert_section::<ItemPathPoint>(world);
+        // both the left and right checkpoint entities are marked with this, only the left has the Checkpoint component
+        ghosts.insert(KmpEditMode::Checkpoints, section_positions::<CheckpointMarker>(world));
+        ghosts.insert_section::<RespawnPoint>(world);
+        ghosts.insert_section::<Object>(world);
+        ghosts.insert_section::<RoutePoint>(world);
+        ghosts.insert_section::<AreaPoint>(world);
+        ghosts.insert_section::<KmpCamera>(world);
+        ghosts.insert_section::<CannonPoint>(world);
+        ghosts.insert_section::<BattleFinishPoint>(world);
+        world.insert_resource(ghosts);
+    }
+    fn insert_section<T: Component>(&mut self, world: &mut World) {
+        self.insert(KmpEditMode::from_type::<T>(), section_positions::<T>(world));
+    }
+}
+
+fn section_positions<T: Component>(world: &mut World) -> Vec<Vec3> {
+    world
+        .query_filtered::<&Transform, (With<T>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .map(|t| t.translation)
+        .collect()
+}
+
+// ghosts are drawn using the 2d gizmo camera so that they stay the same size on screen like the points themselves
+fn draw_saved_ghosts(
+    ghosts: Res<SavedGhosts>,
+    settings: Res<AppSettings>,
+    mode: Res<KmpEditMode>,
+    q_cam: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_gizmo_cam: Query<(&Camera, &GlobalTransform), With<Gizmo2dCam>>,
+    q_current: Query<&Transform, With<KmpSelectablePoint>>,
+    mut painter: ShapePainter,
+) {
+    const GHOST_RADIUS: f32 = 8.;
+    const GHOST_ALPHA: u8 = 110;
+
+    if !settings.kmp_model.show_saved_ghosts {
+        return;
+    }
+    let Some(positions) = ghosts.get(&*mode) else { return };
+    let Some(cam) = q_cam.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let gizmo_cam = q_gizmo_cam.single();
+
+    let color = Icons::SECTION_COLORS[*mode as usize];
+    painter.color = Color::srgba_u8(color.r(), color.g(), color.b(), GHOST_ALPHA);
+    painter.hollow = true;
+    painter.thickness = 2.;
+
+    // the bits of each coordinate, as floats can't be hashed
+    let bits = |pos: Vec3| pos.to_array().map(f32::to_bits);
+    let current: HashSet<_> = q_current.iter().map(|t| bits(t.translation)).collect();
+
+    for pos in positions.iter() {
+        // don't bother drawing ghosts for points which haven't moved since they would be hidden anyway
+        if current.contains(&bits(*pos)) {
+            continue;
+        }
+        if let Some(pos) = world_to_gizmo_cam(cam, gizmo_cam, *pos) {
+            painter.transform.translation = pos;
+            painter.circle(GHOST_RADIUS * settings.kmp_model.point_scale);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::sections::KmpEditMode;
+use crate::util::kmp_file::KmpFile;
+use bevy::prelude::*;
+
+// the sections whose additional values are worked out from the KMP when it is built
+const POTI: usize = 8;
+const CAME: usize = 10;
+
+/// The additional value of each section header in the KMP file as it was opened or last saved, in file order. Apart
+/// from the ones worked out from the KMP itself, these are written back as they were, so data other tools keep there
+/// isn't lost.
+#[derive(Resource, Clone, Default)]
+pub struct OpenedHeaderValues(pub [u16; 15]);
+impl OpenedHeaderValues {
+    pub fn new(kmp: &KmpFile) -> Self {
+        Self(kmp.section_headers().map(|x| x.additional_value))
+    }
+}
+
+/// Put the additional values the KMP was opened with back into a KMP built from the world. The POTI value is the
+/// number of route points so is left as it was built, and only the second byte of the CAME value is put back, as the
+/// first is the camera the intro starts from.
+pub fn restore_header_values(world: &World, kmp: &mut KmpFile) {
+    let Some(opened) = world.get_resource::<OpenedHeaderValues>() else {
+        return;
+    };
+    for (i, header) in kmp.section_headers_mut().into_iter().enumerate() {
+        match i {
+            POTI => {}
+            CAME => header.additional_value = (header.additional_value & 0xff00) | (opened.0[i] & 0x00ff),
+            _ => header.additional_value = opened.0[i],
+        }
+    }
+}
+
+/// What the additional value of a section's header holds
+pub fn header_value_meaning(index: usize) -> &'static str {
+    match index {
+        POTI => "Number of route points",
+        CAME => "Intro start camera, then unknown",
+        _ => "Padding, unless a tool uses it",
+    }
+}
+
+/// A section header whose additional value is different in the built KMP to how it was opened
+pub struct HeaderValueChange {
+    pub index: usize,
+    pub section: String,
+    pub opened: u16,
+    pub built: u16,
+}
+impl HeaderValueChange {
+    /// The section the header belongs to, to switch to when the change is selected in the issues list
+    pub fn mode(&self) -> KmpEditMode {
+        use KmpEditMode::*;
+        [
+            StartPoints,
+            EnemyPaths,
+            EnemyPaths,
+            ItemPaths,
+            ItemPaths,
+            Checkpoints,
+            Checkpoints,
+            Objects,
+            Routes,
+            Areas,
+            Cameras,
+            RespawnPoints,
+            CannonPoints,
+            BattleFinishPoints,
+            TrackInfo,
+        ][self.index]
+    }
+    pub fn message(&self) -> String {
+        let reason = match self.index {
+            POTI => ", as it holds the number of route points",
+            CAME => ", as its first byte is the camera the intro starts from",
+            _ => "",
+        };
+        format!(
+            "The {} header's additional value will be saved as {:#06x} instead of {:#06x}{reason}",
+            self.section, self.built, self.opened
+        )
+    }
+}
+
+/// The section headers whose additional values will be saved differently to how they were opened
+pub fn header_value_changes(world: &World, kmp: &KmpFile) -> Vec<HeaderValueChange> {
+    let Some(opened) = world.get_resource::<OpenedHeaderValues>() else {
+        return Vec::new();
+    };
+    kmp.section_headers()
+        .into_iter()
+        .enumerate()
+        .filter(|(i, header)| header.additional_value != opened.0[*i])
+        .map(|(i, header)| HeaderValueChange {
+            index: i,
+            section: String::from_utf8_lossy(&header.section_name).into_owned(),
+            opened: opened.0[i],
+            built: header.additional_value,
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+use super::{concurrent_edit::now_secs, section_lengths};
+use crate::util::kmp_file::KmpFile;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// the oldest saves are forgotten after this many, so the sidecar doesn't keep growing
+const MAX_HISTORY: usize = 100;
+
+/// A save of the KMP, kept in its sidecar so that anyone opening it can see when and roughly what was last changed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveRecord {
+    /// Seconds since the unix epoch
+    pub time: u64,
+    /// Who saved it, e.g. 'thomas on studio-pc'
+    pub by: String,
+    /// The number of entries in each section
+    pub counts: Vec<(String, usize)>,
+    /// What changed in each section since the save before, e.g. 'CKPT: 2 added, 5 changed'
+    pub changes: Vec<String>,
+}
+
+/// Shows the save history of the open KMP. The dialog is open while this resource exists.
+#[derive(Resource, Default)]
+pub struct FileProperties {
+    /// Whether the section headers are shown, which needs the KMP to be built each frame
+    pub section_headers: bool,
+}
+
+fn to_values<T: Serialize>(entries: &[T]) -> Vec<Value> {
+    entries.iter().filter_map(|x| serde_json::to_value(x).ok()).collect()
+}
+
+// every entry of every section in a form they can be compared in
+fn section_values(kmp: &KmpFile) -> [Vec<Value>; 15] {
+    [
+        to_values(&kmp.ktpt),
+        to_values(&kmp.enpt),
+        to_values(&kmp.enph),
+        to_values(&kmp.itpt),
+        to_values(&kmp.itph),
+        to_values(&kmp.ckpt),
+        to_values(&kmp.ckph),
+        to_values(&kmp.gobj),
+        to_values(&kmp.poti),
+        to_values(&kmp.area),
+        to_values(&kmp.came),
+        to_values(&kmp.jgpt),
+        to_values(&kmp.cnpt),
+        to_values(&kmp.mspt),
+        to_values(&kmp.stgi),
+    ]
+}
+
+/// What changed in each section between two versions of a KMP. Entries are compared by index, so inserting one
+/// part way through a section shows up as the ones after it changing.
+pub fn summarise_changes(old: &KmpFile, new: &KmpFile) -> Vec<String> {
+    let names = section_lengths(new).map(|x| x.0);
+    let mut changes = Vec::new();
+    for ((name, old), new) in names.into_iter().zip(section_values(old)).zip(section_values(new)) {
+        let changed = old.iter().zip(new.iter()).filter(|(a, b)| a != b).count();
+        let mut parts = Vec::new();
+        if new.len() > old.len() {
+            parts.push(format!("{} added", new.len() - old.len()));
+        }
+        if old.len() > new.len() {
+            parts.push(format!("{} removed", old.len() - new.len()));
+        }
+        if changed > 0 {
+            parts.push(format!("{changed} changed"));
+        }
+        if !parts.is_empty() {
+            changes.push(format!("{name}: {}", parts.join(", ")));
+        }
+    }
+    changes
+}
+
+/// Add a save of `new` by someone to the history, working out what changed since `old` if that is known
+pub fn record_save(history: &mut Vec<SaveRecord>, by: String, old: Option<&KmpFile>, new: &KmpFile) {
+    let changes = match old {
+        Some(old) => summarise_changes(old, new),
+        None => Vec::new(),
+    };
+    history.push(SaveRecord {
+        time: now_secs(),
+        by,
+        counts: section_lengths(new)
+            .map(|(name, count)| (name.to_string(), count))
+            .into(),
+        changes,
+    });
+    if history.len() > MAX_HISTORY {
+        history.drain(..history.len() - MAX_HISTORY);
+    }
+}
+
+/// A time as a UTC date and time, e.g. '2024-06-01 14:05 UTC'
+pub fn format_save_time(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let (hour, minute) = (secs % 86400 / 3600, secs % 3600 / 60);
+    // days since the epoch to a civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{year}-{month:02}-{day:02} {hour:02}:{minute:02} UTC")
+}
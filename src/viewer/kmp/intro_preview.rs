@@ -0,0 +1,247 @@
+use super::{
+    camera_chain::NO_NEXT_CAMERA,
+    components::{KmpCamera, KmpCameraIntroStart, RoutePoint},
+    ordering::OrderId,
+    path::KmpPathNode,
+    routes::{route_positions, RouteLink},
+};
+use crate::{
+    ui::settings::AppSettings,
+    viewer::camera::{CameraMode, CameraModeChanged, FlyCam, UpdateCameraSet},
+};
+use bevy::{color::palettes::css, ecs::entity::EntityHashSet, prelude::*};
+
+pub fn intro_preview_plugin(app: &mut App) {
+    app.add_event::<PlayIntro>().add_event::<StopIntro>().add_systems(
+        Update,
+        (
+            start_intro_preview.run_if(on_event::<PlayIntro>()),
+            play_intro_preview.run_if(resource_exists::<IntroPreview>),
+            draw_intro_path.run_if(resource_exists::<IntroPreview>),
+            stop_intro_preview.run_if(on_event::<StopIntro>()),
+        )
+            .chain()
+            .after(UpdateCameraSet),
+    );
+}
+
+/// Send this event to animate the viewport camera through the opening cameras, starting from the intro start
+#[derive(Event, Default)]
+pub struct PlayIntro;
+
+/// Send this event to stop the intro preview and put the viewport camera back where it was
+#[derive(Event, Default)]
+pub struct StopIntro;
+
+// the game runs at 60fps, and camera times are in frames
+const FRAMES_PER_SECOND: f32 = 60.;
+
+/// The intro being played in the viewport. The preview is playing while this resource exists.
+#[derive(Resource)]
+pub struct IntroPreview {
+    pub shots: Vec<IntroShot>,
+    pub current: usize,
+    /// Seconds since the current shot started
+    pub elapsed: f32,
+    pub paused: bool,
+    // where the viewport camera was before the preview, to put it back afterwards
+    prev_transform: Transform,
+    prev_fov: f32,
+    prev_mode: CameraMode,
+}
+
+/// One opening camera of the intro, with everything needed to animate it
+pub struct IntroShot {
+    /// The index of the camera in the CAME section
+    pub index: u32,
+    /// How long the camera is shown for, in seconds
+    pub duration: f32,
+    /// The positions of the route the camera moves along, or just the camera's position if it doesn't move
+    positions: Vec<Vec3>,
+    view: (Vec3, Vec3),
+    zoom: (f32, f32),
+}
+
+impl IntroShot {
+    fn new(index: u32, camera: &KmpCamera, transform: &Transform, route: Vec<Vec3>) -> Self {
+        // a velocity of 0 means that part of the camera stays where it starts
+        let positions = if route.len() >= 2 && camera.point_velocity > 0 {
+            route
+        } else {
+            vec![route.first().copied().unwrap_or(transform.translation)]
+        };
+        let view_end = if camera.view_velocity > 0 {
+            camera.view_end
+        } else {
+            camera.view_start
+        };
+        let zoom_end = if camera.zoom_velocity > 0 {
+            camera.zoom_end
+        } else {
+            camera.zoom_start
+        };
+        Self {
+            index,
+            duration: camera.time.max(1.) / FRAMES_PER_SECOND,
+            positions,
+            view: (camera.view_start, view_end),
+            zoom: (camera.zoom_start, zoom_end),
+        }
+    }
+    /// The position of the camera, the point it is looking at, and its field of view in degrees, `t` of the way through
+    pub fn at(&self, t: f32) -> (Vec3, Vec3, f32) {
+        let t = t.clamp(0., 1.);
+        (
+            point_along(&self.positions, t),
+            self.view.0.lerp(self.view.1, t),
+            self.zoom.0.lerp(self.zoom.1, t),
+        )
+    }
+}
+
+// the point a fraction of the way along a line through some points
+fn point_along(points: &[Vec3], t: f32) -> Vec3 {
+    let lengths: Vec<_> = points.windows(2).map(|x| x[0].distance(x[1])).collect();
+    let total: f32 = lengths.iter().sum();
+    if total <= 0. {
+        return points.first().copied().unwrap_or_default();
+    }
+    let mut distance = t * total;
+    for (i, length) in lengths.iter().enumerate() {
+        if distance <= *length {
+            return points[i].lerp(points[i + 1], distance / length);
+        }
+        distance -= length;
+    }
+    *points.last().unwrap()
+}
+
+fn start_intro_preview(
+    mut commands: Commands,
+    q_cameras: Query<(Entity, &KmpCamera, &Transform, &OrderId, Option<&RouteLink>)>,
+    q_intro_start: Query<Entity, With<KmpCameraIntroStart>>,
+    q_route_pt: Query<(&Transform, &KmpPathNode), With<RoutePoint>>,
+    q_fly_cam: Query<(&Transform, &Projection), With<FlyCam>>,
+    mut settings: ResMut<AppSettings>,
+    mut ev_camera_mode_changed: EventWriter<CameraModeChanged>,
+    prev_preview: Option<Res<IntroPreview>>,
+) {
+    let Some(start_e) = q_intro_start.iter().next() else {
+        return;
+    };
+    // follow the next indexes from the intro start until the chain ends or loops back on itself
+    let mut shots = Vec::new();
+    let mut visited = EntityHashSet::default();
+    let mut cur_e = Some(start_e);
+    while let Some((e, camera, transform, order_id, route_link)) = cur_e.and_then(|e| q_cameras.get(e).ok()) {
+        if !visited.insert(e) {
+            break;
+        }
+        let route = route_link
+            .map(|x| route_positions(**x, &q_route_pt))
+            .unwrap_or_default();
+        shots.push(IntroShot::new(order_id.0, camera, transform, route));
+        cur_e = if camera.next_index == NO_NEXT_CAMERA {
+            None
+        } else {
+            q_cameras
+                .iter()
+                .find(|x| x.3 .0 == camera.next_index as u32)
+                .map(|x| x.0)
+        };
+    }
+
+    // if the intro is played again while it is already playing, keep the camera position from before the first time
+    let (prev_transform, prev_fov, prev_mode) = match prev_preview {
+        Some(prev) => (prev.prev_transform, prev.prev_fov, prev.prev_mode),
+        None => {
+            let Ok((transform, projection)) = q_fly_cam.get_single() else {
+                return;
+            };
+            let fov = match projection {
+                Projection::Perspective(projection) => projection.fov,
+                _ => PerspectiveProjection::default().fov,
+            };
+            (*transform, fov, settings.camera.mode)
+        }
+    };
+    // the intro is played through the fly camera, as it is the only one which can point anywhere
+    if settings.camera.mode != CameraMode::Fly {
+        settings.camera.mode = CameraMode::Fly;
+        ev_camera_mode_changed.send(CameraModeChanged(CameraMode::Fly));
+    }
+    commands.insert_resource(IntroPreview {
+        shots,
+        current: 0,
+        elapsed: 0.,
+        paused: false,
+        prev_transform,
+        prev_fov,
+        prev_mode,
+    });
+}
+
+fn play_intro_preview(
+    mut preview: ResMut<IntroPreview>,
+    mut q_fly_cam: Query<(&mut Transform, &mut Projection), With<FlyCam>>,
+    time: Res<Time>,
+    mut ev_stop: EventWriter<StopIntro>,
+) {
+    if !preview.paused {
+        preview.elapsed += time.delta_seconds();
+    }
+    while let Some(shot) = preview.shots.get(preview.current) {
+        if preview.elapsed < shot.duration {
+            break;
+        }
+        preview.elapsed -= shot.duration;
+        preview.current += 1;
+    }
+    let Some(shot) = preview.shots.get(preview.current) else {
+        ev_stop.send_default();
+        return;
+    };
+    let Ok((mut transform, mut projection)) = q_fly_cam.get_single_mut() else {
+        return;
+    };
+    let (pos, look_at, fov) = shot.at(preview.elapsed / shot.duration);
+    *transform = Transform::from_translation(pos);
+    if look_at != pos {
+        transform.look_at(look_at, Vec3::Y);
+    }
+    if let Projection::Perspective(projection) = &mut *projection {
+        projection.fov = fov.clamp(1., 179.).to_radians();
+    }
+}
+
+// show the path of the current shot, so it can be seen if the preview is paused and the camera moved away
+fn draw_intro_path(mut gizmos: Gizmos, preview: Res<IntroPreview>) {
+    let Some(shot) = preview.shots.get(preview.current) else {
+        return;
+    };
+    gizmos.linestrip(shot.positions.iter().copied(), css::ORANGE);
+    gizmos.line(shot.view.0, shot.view.1, css::AQUA);
+}
+
+fn stop_intro_preview(
+    mut commands: Commands,
+    preview: Option<Res<IntroPreview>>,
+    mut q_fly_cam: Query<(&mut Transform, &mut Projection), With<FlyCam>>,
+    mut settings: ResMut<AppSettings>,
+    mut ev_camera_mode_changed: EventWriter<CameraModeChanged>,
+) {
+    let Some(preview) = preview else {
+        return;
+    };
+    if let Ok((mut transform, mut projection)) = q_fly_cam.get_single_mut() {
+        *transform = preview.prev_transform;
+        if let Projection::Perspective(projection) = &mut *projection {
+            projection.fov = preview.prev_fov;
+        }
+    }
+    if settings.camera.mode != preview.prev_mode {
+        settings.camera.mode = preview.prev_mode;
+        ev_camera_mode_changed.send(CameraModeChanged(preview.prev_mode));
+    }
+    commands.remove_resource::<IntroPreview>();
+}
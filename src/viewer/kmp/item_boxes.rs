@@ -0,0 +1,239 @@
+use super::{
+    components::{Object, Spawner},
+    ordering::RefreshOrdering,
+    sections::KmpEditMode,
+};
+use crate::{
+    ui::{settings::AppSettings, viewport::ViewportInfo},
+    util::{kmp_file::ExtendedPresence, ui_viewport_to_ndc, RaycastFromCam},
+    viewer::{camera::Gizmo2dCam, edit::select::Selected, kcl_model::KCLModelSection},
+};
+use bevy::{color::palettes::css, prelude::*};
+use bevy_mod_raycast::prelude::*;
+use std::f32::consts::PI;
+use strum_macros::{Display, EnumIter};
+
+pub fn item_boxes_plugin(app: &mut App) {
+    app.add_event::<CreateItemBoxes>().add_systems(
+        Update,
+        (
+            pick_item_box_point.run_if(resource_exists::<ItemBoxGenerator>),
+            draw_item_box_preview.run_if(resource_exists::<ItemBoxGenerator>),
+            create_item_boxes.run_if(on_event::<CreateItemBoxes>()),
+        ),
+    );
+}
+
+/// The object ID of an item box
+pub const ITEM_BOX_ID: u16 = 0x65;
+// size of the preview cubes, before being scaled by the point scale
+const PREVIEW_SIZE: f32 = 300.;
+
+/// Generates a row or arc of evenly spaced item boxes. The generator is open while this resource exists.
+#[derive(Resource)]
+pub struct ItemBoxGenerator {
+    pub shape: ItemBoxShape,
+    pub count: u32,
+    pub spacing: ItemBoxSpacing,
+    /// The distance between each box, for [`ItemBoxSpacing::Fixed`]
+    pub distance: f32,
+    /// How far above the picked positions the boxes are placed
+    pub height: f32,
+    pub start: Vec3,
+    pub end: Vec3,
+    pub center: Vec3,
+    pub radius: f32,
+    /// The angle the arc sweeps through, in degrees
+    pub arc_angle: f32,
+    /// The direction of the middle of the arc from its centre, in degrees around the Y axis
+    pub direction: f32,
+    /// Which position the next click on the KCL sets, if any
+    pub picking: Option<ItemBoxPick>,
+}
+impl ItemBoxGenerator {
+    pub fn new(pos: Vec3) -> Self {
+        Self {
+            shape: ItemBoxShape::default(),
+            count: 4,
+            spacing: ItemBoxSpacing::default(),
+            distance: 700.,
+            height: 150.,
+            start: pos - Vec3::X * 1500.,
+            end: pos + Vec3::X * 1500.,
+            center: pos,
+            radius: 3000.,
+            arc_angle: 90.,
+            direction: 0.,
+            picking: None,
+        }
+    }
+    /// Where each item box will be placed
+    pub fn positions(&self) -> Vec<Vec3> {
+        let n = self.count as usize;
+        if n == 0 {
+            return Vec::new();
+        }
+        // the length of the row or arc, so that a fixed spacing can be turned into a fraction of it
+        let length = match self.shape {
+            ItemBoxShape::Row => self.start.distance(self.end),
+            ItemBoxShape::Arc => self.radius * self.arc_angle.to_radians().abs(),
+        };
+        // how far along the row or arc each box is, from 0 to 1
+        let fractions: Vec<f32> = (0..n)
+            .map(|i| match self.spacing {
+                ItemBoxSpacing::Even if n == 1 => 0.5,
+                ItemBoxSpacing::Even => i as f32 / (n - 1) as f32,
+                ItemBoxSpacing::Inset => (i as f32 + 0.5) / n as f32,
+                ItemBoxSpacing::Fixed if length <= 0. => 0.5,
+                ItemBoxSpacing::Fixed => 0.5 + (i as f32 - (n - 1) as f32 / 2.) * self.distance / length,
+            })
+            .collect();
+        fractions
+            .into_iter()
+            .map(|t| match self.shape {
+                ItemBoxShape::Row => self.start.lerp(self.end, t),
+                ItemBoxShape::Arc => {
+                    let angle = (self.direction + (t - 0.5) * self.arc_angle).to_radians();
+                    self.center + Vec3::new(angle.sin(), 0., angle.cos()) * self.radius
+                }
+            })
+            .map(|pos| pos + Vec3::Y * self.height)
+            .collect()
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum ItemBoxShape {
+    #[default]
+    Row,
+    Arc,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Display, EnumIter)]
+pub enum ItemBoxSpacing {
+    /// The first and last boxes are on the ends, with the rest spread evenly between them
+    #[default]
+    #[strum(serialize = "Evenly Spread")]
+    Even,
+    /// Each box takes up an equal share of the row, with half a gap left at each end
+    #[strum(serialize = "Inset From Ends")]
+    Inset,
+    /// The boxes are a set distance apart, centred on the middle of the row
+    #[strum(serialize = "Fixed Distance")]
+    Fixed,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ItemBoxPick {
+    Start,
+    End,
+    Center,
+}
+
+/// Send this event to spawn the item boxes in the [`ItemBoxGenerator`], and close it
+#[derive(Event, Default)]
+pub struct CreateItemBoxes;
+
+// set the position being picked to wherever on the KCL is clicked
+fn pick_item_box_point(
+    mut generator: ResMut<ItemBoxGenerator>,
+    viewport_info: Res<ViewportInfo>,
+    q_window: Query<&Window>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    q_camera: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    mut raycast: Raycast,
+    q_kcl: Query<(), With<KCLModelSection>>,
+) {
+    let Some(picking) = generator.picking else {
+        return;
+    };
+    if !viewport_info.mouse_in_viewport
+        || viewport_info.mouse_on_overlayed_ui
+        || !mouse_buttons.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let Some(mouse_pos) = q_window.get_single().ok().and_then(|x| x.cursor_position()) else {
+        return;
+    };
+    let Some(cam) = q_camera.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let mouse_pos_ndc = ui_viewport_to_ndc(mouse_pos, viewport_info.viewport_rect);
+    let intersections = RaycastFromCam::new(cam, mouse_pos_ndc, &mut raycast)
+        .filter(&|e| q_kcl.contains(e))
+        .cast();
+    let Some(pos) = intersections.first().map(|x| x.1.position()) else {
+        return;
+    };
+    match picking {
+        ItemBoxPick::Start => generator.start = pos,
+        ItemBoxPick::End => generator.end = pos,
+        ItemBoxPick::Center => generator.center = pos,
+    }
+    generator.picking = None;
+}
+
+fn draw_item_box_preview(mut gizmos: Gizmos, generator: Res<ItemBoxGenerator>, settings: Res<AppSettings>) {
+    let size = PREVIEW_SIZE * settings.kmp_model.point_scale;
+    let color = settings.kmp_model.color.objects.point;
+    for pos in generator.positions() {
+        gizmos.cuboid(Transform::from_translation(pos).with_scale(Vec3::splat(size)), color);
+    }
+    // show the row or arc the boxes are placed along
+    let guide_color = css::GRAY;
+    let up = Vec3::Y * generator.height;
+    match generator.shape {
+        ItemBoxShape::Row => {
+            gizmos.line(generator.start + up, generator.end + up, guide_color);
+        }
+        ItemBoxShape::Arc => {
+            let arc_angle = generator.arc_angle.to_radians();
+            // bevy's arcs start from the +X axis, a quarter turn round from +Z where our angles start
+            let start_angle = generator.direction.to_radians() - arc_angle / 2. - PI / 2.;
+            gizmos
+                .arc_3d(
+                    arc_angle,
+                    generator.radius,
+                    generator.center + up,
+                    Quat::from_rotation_y(start_angle),
+                    guide_color,
+                )
+                .resolution(32);
+        }
+    }
+}
+
+fn create_item_boxes(world: &mut World) {
+    world.resource_mut::<Events<CreateItemBoxes>>().clear();
+    let Some(generator) = world.remove_resource::<ItemBoxGenerator>() else {
+        return;
+    };
+    let created: Vec<_> = generator
+        .positions()
+        .into_iter()
+        .map(|pos| {
+            Spawner::<Object>::builder()
+                .pos(pos)
+                .component(Object {
+                    object_id: ITEM_BOX_ID,
+                    scale: Vec3::ONE,
+                    settings: [0; 8],
+                    presence: 0x3f,
+                    extended_presence: ExtendedPresence::None,
+                })
+                .build()
+                .spawn(world)
+        })
+        .collect();
+    world.send_event(RefreshOrdering);
+
+    let selected: Vec<_> = world.query_filtered::<Entity, With<Selected>>().iter(world).collect();
+    for e in selected {
+        world.entity_mut(e).remove::<Selected>();
+    }
+    for e in created {
+        world.entity_mut(e).insert(Selected);
+    }
+    *world.resource_mut::<KmpEditMode>() = KmpEditMode::Objects;
+}
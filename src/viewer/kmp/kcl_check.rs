@@ -0,0 +1,161 @@
+use super::components::{EnemyPathPoint, ItemPathPoint, KmpSelectablePoint};
+use crate::{
+    ui::settings::AppSettings,
+    util::{kcl_file::KclFlag, world_to_gizmo_cam},
+    viewer::{camera::Gizmo2dCam, kcl_model::KCLModelSection},
+};
+use bevy::{color::palettes::css, prelude::*, transform::TransformSystem};
+use bevy_mod_raycast::prelude::*;
+use bevy_vector_shapes::{painter::ShapePainter, shapes::DiscPainter};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+pub fn kcl_check_plugin(app: &mut App) {
+    app.add_event::<CheckPathsAgainstKcl>()
+        .add_systems(
+            Update,
+            (
+                check_paths_against_kcl.run_if(on_event::<CheckPathsAgainstKcl>()),
+                clear_moved_warnings,
+            ),
+        )
+        .add_systems(PostUpdate, draw_kcl_warnings.after(TransformSystem::TransformPropagate));
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct KclCheckSettings {
+    /// Path points which are further than this above the ground below them are flagged as floating
+    pub max_height_above_ground: f32,
+}
+impl Default for KclCheckSettings {
+    fn default() -> Self {
+        Self {
+            max_height_above_ground: 1000.,
+        }
+    }
+}
+
+/// Send this event to check all enemy and item path points against the KCL
+#[derive(Event, Default)]
+pub struct CheckPathsAgainstKcl;
+
+/// Attached to enemy/item path points which failed the last KCL check.
+/// Removed as soon as the point is moved, since the warning may no longer apply.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub enum PathKclWarning {
+    InsideWall,
+    OverOutOfBounds,
+    Floating(f32),
+    NoGroundBelow,
+}
+impl Display for PathKclWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InsideWall => write!(f, "Point is inside or directly above a wall"),
+            Self::OverOutOfBounds => write!(f, "Point is above a fall boundary or solid fall"),
+            Self::Floating(height) => write!(f, "Point is floating {height:.0} units above the ground"),
+            Self::NoGroundBelow => write!(f, "There is no ground below the point"),
+        }
+    }
+}
+
+pub(crate) const WALL_FLAGS: [KclFlag; 9] = [
+    KclFlag::Wall1,
+    KclFlag::Wall2,
+    KclFlag::WeakWall,
+    KclFlag::InvisibleWall1,
+    KclFlag::InvisibleWall2,
+    KclFlag::SpecialWall,
+    KclFlag::PlayerOnlyWall,
+    KclFlag::ItemWall,
+    KclFlag::HalfPipeInvisibleWall,
+];
+const OUT_OF_BOUNDS_FLAGS: [KclFlag; 2] = [KclFlag::FallBoundary, KclFlag::SolidFall];
+// these don't have any collision, so we ignore them when looking for the ground
+pub(crate) const TRIGGER_FLAGS: [KclFlag; 5] = [
+    KclFlag::SoundTrigger,
+    KclFlag::EffectTrigger,
+    KclFlag::ItemStateModifier,
+    KclFlag::CannonTrigger,
+    KclFlag::ForceRecalculation,
+];
+
+fn check_paths_against_kcl(
+    mut commands: Commands,
+    q_points: Query<
+        (Entity, &Transform),
+        (
+            With<KmpSelectablePoint>,
+            Or<(With<EnemyPathPoint>, With<ItemPathPoint>)>,
+        ),
+    >,
+    q_kcl: Query<&KCLModelSection>,
+    mut raycast: Raycast,
+    settings: Res<AppSettings>,
+) {
+    let is_flag = |flags: &[KclFlag], e: Entity| q_kcl.get(e).is_ok_and(|s| flags.iter().any(|f| *f as usize == s.0));
+    let filter = |e: Entity| q_kcl.contains(e) && !is_flag(&TRIGGER_FLAGS, e);
+    let raycast_settings = RaycastSettings {
+        // hidden parts of the KCL still have collision in game
+        visibility: RaycastVisibility::Ignore,
+        ..default()
+    }
+    .with_filter(&filter);
+
+    // start the ray slightly above the point so points sitting exactly on the road still hit it
+    const RAY_START_OFFSET: f32 = 10.;
+
+    for (e, transform) in q_points.iter() {
+        commands.entity(e).remove::<PathKclWarning>();
+
+        let ray = Ray3d::new(transform.translation + Vec3::Y * RAY_START_OFFSET, Vec3::NEG_Y);
+        let warning = match raycast.cast_ray(ray, &raycast_settings).first() {
+            None => Some(PathKclWarning::NoGroundBelow),
+            Some((hit_e, _)) if is_flag(&WALL_FLAGS, *hit_e) => Some(PathKclWarning::InsideWall),
+            Some((hit_e, _)) if is_flag(&OUT_OF_BOUNDS_FLAGS, *hit_e) => Some(PathKclWarning::OverOutOfBounds),
+            Some((_, hit)) if hit.distance() - RAY_START_OFFSET > settings.kcl_check.max_height_above_ground => {
+                Some(PathKclWarning::Floating(hit.distance() - RAY_START_OFFSET))
+            }
+            _ => None,
+        };
+        if let Some(warning) = warning {
+            commands.entity(e).insert(warning);
+        }
+    }
+}
+
+fn clear_moved_warnings(mut commands: Commands, q: Query<Entity, (With<PathKclWarning>, Changed<Transform>)>) {
+    for e in q.iter() {
+        commands.entity(e).remove::<PathKclWarning>();
+    }
+}
+
+// draw a ring around each point with a warning using the 2d gizmo camera so it's easy to spot
+fn draw_kcl_warnings(
+    q_warnings: Query<(&GlobalTransform, &ViewVisibility), With<PathKclWarning>>,
+    q_cam: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_gizmo_cam: Query<(&Camera, &GlobalTransform), With<Gizmo2dCam>>,
+    mut painter: ShapePainter,
+) {
+    if q_warnings.is_empty() {
+        return;
+    }
+    let Some(cam) = q_cam.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let gizmo_cam = q_gizmo_cam.single();
+
+    painter.color = css::ORANGE_RED.into();
+    painter.hollow = true;
+    painter.thickness = 3.;
+
+    for (gt, view_visibility) in q_warnings.iter() {
+        if !view_visibility.get() {
+            continue;
+        }
+        if let Some(pos) = world_to_gizmo_cam(cam, gizmo_cam, gt.translation()) {
+            painter.transform.translation = pos;
+            painter.circle(16.);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use super::{
+    checkpoints::{checkpoint_spawner, CheckpointHeight, CheckpointLeft},
+    components::{Checkpoint, CheckpointKind, PathOverallStart, StartPoint},
+    kcl_check::WALL_FLAGS,
+    ordering::OrderId,
+    path::{KmpPathNode, RecalcPaths},
+};
+use crate::{ui::errors::show_errors, viewer::kcl_model::KCLModelSection};
+use anyhow::bail;
+use bevy::{ecs::system::SystemState, math::vec2, prelude::*};
+use bevy_mod_raycast::prelude::*;
+
+pub fn lap_count_plugin(app: &mut App) {
+    app.add_event::<PlaceLapCountCheckpoint>().add_systems(
+        Update,
+        place_lap_count_checkpoint
+            .pipe(show_errors)
+            .run_if(on_event::<PlaceLapCountCheckpoint>()),
+    );
+}
+
+/// Send this event to make sure there is exactly one lap count checkpoint, and that it lies across the finish line
+#[derive(Event, Default)]
+pub struct PlaceLapCountCheckpoint;
+
+// how far either side of the start point the checkpoint goes if we can't find any walls
+const DEFAULT_HALF_WIDTH: f32 = 2500.;
+// walls further away than this are probably not the edge of the track at the finish line
+const MAX_HALF_WIDTH: f32 = 10000.;
+// extend the checkpoint a bit past the walls so it can't be driven around
+const WALL_MARGIN: f32 = 300.;
+// cast the rays a bit above the start point so they don't hit the road itself
+const RAY_HEIGHT: f32 = 100.;
+
+fn place_lap_count_checkpoint(world: &mut World) -> anyhow::Result<()> {
+    // the finish line goes through the first start point, perpendicular to the direction it faces
+    let mut q_start = world.query_filtered::<(&Transform, &OrderId), With<StartPoint>>();
+    let Some(start) = q_start.iter(world).min_by_key(|x| x.1 .0).map(|x| *x.0) else {
+        bail!("there is no start point to place the lap count checkpoint at");
+    };
+    let forward = (start.rotation * Vec3::Z).xz().normalize_or_zero();
+    if forward == Vec2::ZERO {
+        bail!("the start point is facing straight up or down");
+    }
+    let left_dir = vec2(forward.y, -forward.x);
+    let start_pos = start.translation.xz();
+
+    let left_width = track_half_width(world, start.translation, left_dir);
+    let right_width = track_half_width(world, start.translation, -left_dir);
+    let left_pos = start_pos + left_dir * left_width;
+    let right_pos = start_pos - left_dir * right_width;
+
+    // there should only be one lap count checkpoint, so turn any extras back into normal checkpoints
+    let mut q_cp = world.query::<(Entity, &Checkpoint, &OrderId)>();
+    let mut lap_counts: Vec<_> = q_cp
+        .iter(world)
+        .filter(|x| x.1.kind == CheckpointKind::LapCount)
+        .map(|x| (x.0, x.2 .0))
+        .collect();
+    lap_counts.sort_by_key(|x| x.1);
+    for (e, _) in lap_counts.iter().skip(1) {
+        world.get_mut::<Checkpoint>(*e).unwrap().kind = CheckpointKind::Normal;
+    }
+
+    let existing = lap_counts.first().map(|x| x.0);
+    let cp_e = insert_lap_count_checkpoint(world, existing, start_pos, forward, (left_pos, right_pos));
+
+    // move the checkpoint onto the finish line
+    let height = **world.resource::<CheckpointHeight>();
+    let right_e = world.get::<CheckpointLeft>(cp_e).unwrap().right;
+    world.get_mut::<Transform>(cp_e).unwrap().translation = left_pos.extend(height).xzy();
+    world.get_mut::<Transform>(right_e).unwrap().translation = right_pos.extend(height).xzy();
+
+    // the lap count checkpoint has to be the first checkpoint
+    let mut q_path_start = world.query_filtered::<Entity, (With<PathOverallStart>, With<Checkpoint>)>();
+    let path_starts: Vec<_> = q_path_start.iter(world).collect();
+    for e in path_starts {
+        world.entity_mut(e).remove::<PathOverallStart>();
+    }
+    world.entity_mut(cp_e).insert(PathOverallStart);
+    world.send_event(RecalcPaths::cp());
+
+    Ok(())
+}
+
+/// Find the distance from the start point to the nearest wall in a direction, so the checkpoint spans the track
+fn track_half_width(world: &mut World, start: Vec3, dir: Vec2) -> f32 {
+    let mut ss = SystemState::<(Raycast, Query<&KCLModelSection>)>::new(world);
+    let (mut raycast, q_kcl) = ss.get_mut(world);
+
+    let filter = |e: Entity| {
+        q_kcl
+            .get(e)
+            .is_ok_and(|s| WALL_FLAGS.iter().any(|f| *f as usize == s.0))
+    };
+    let raycast_settings = RaycastSettings {
+        visibility: RaycastVisibility::Ignore,
+        ..default()
+    }
+    .with_filter(&filter);
+
+    let ray = Ray3d::new(start + Vec3::Y * RAY_HEIGHT, dir.extend(0.).xzy());
+    match raycast.cast_ray(ray, &raycast_settings).first() {
+        Some((_, hit)) if hit.distance() <= MAX_HALF_WIDTH => hit.distance() + WALL_MARGIN,
+        _ => DEFAULT_HALF_WIDTH,
+    }
+}
+
+/// Take a checkpoint out of its place in the path, linking the checkpoints before it to the ones after it instead
+fn detach_checkpoint(world: &mut World, left_e: Entity) {
+    let right_e = world.get::<CheckpointLeft>(left_e).unwrap().right;
+    for e in [left_e, right_e] {
+        let node = world.get::<KmpPathNode>(e).unwrap();
+        let (prevs, nexts) = (node.get_previous(), node.get_next());
+        for prev in prevs.iter() {
+            KmpPathNode::unlink_nodes(*prev, e, world);
+        }
+        for next in nexts.iter() {
+            KmpPathNode::unlink_nodes(e, *next, world);
+        }
+        for prev in prevs.iter() {
+            for next in nexts.iter() {
+                KmpPathNode::link_nodes(*prev, *next, world);
+            }
+        }
+    }
+}
+
+/// Turn the checkpoint nearest the finish line into the lap count checkpoint, or if there isn't one close by,
+/// move the existing lap count checkpoint (or spawn a new one) in between the checkpoints either side of the finish
+/// line
+fn insert_lap_count_checkpoint(
+    world: &mut World,
+    existing: Option<Entity>,
+    start_pos: Vec2,
+    forward: Vec2,
+    pos: (Vec2, Vec2),
+) -> Entity {
+    let mut q_cp = world.query_filtered::<(Entity, &Transform, &CheckpointLeft, &KmpPathNode), With<Checkpoint>>();
+    let mut q_transform = world.query::<&Transform>();
+
+    let mut midpoint = |left: &Transform, right_e: Entity| {
+        let right = q_transform.get(world, right_e).unwrap();
+        left.translation.xz().lerp(right.translation.xz(), 0.5)
+    };
+    // positive if in front of the finish line, negative if behind it
+    let side = |p: Vec2| (p - start_pos).dot(forward);
+
+    let mids: Vec<_> = q_cp
+        .iter(world)
+        .map(|(e, t, cp_left, node)| (e, midpoint(t, cp_left.right), node.get_next()))
+        .collect();
+
+    // if a checkpoint is already practically on the finish line, reuse it
+    let nearest = mids
+        .iter()
+        .map(|(e, mid, _)| (*e, side(*mid).abs(), mid.distance(start_pos)))
+        .filter(|x| x.2 <= pos.0.distance(pos.1))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((e, dist_to_line, _)) = nearest {
+        if dist_to_line <= WALL_MARGIN {
+            if let Some(existing) = existing.filter(|x| *x != e) {
+                world.get_mut::<Checkpoint>(existing).unwrap().kind = CheckpointKind::Normal;
+            }
+            world.get_mut::<Checkpoint>(e).unwrap().kind = CheckpointKind::LapCount;
+            return e;
+        }
+    }
+
+    // the existing lap count checkpoint is moved, so it mustn't be linked to where it was any more
+    if let Some(existing) = existing {
+        detach_checkpoint(world, existing);
+    }
+    let mids: Vec<_> = mids
+        .into_iter()
+        .filter(|x| Some(x.0) != existing)
+        .map(|(e, mid, _)| (e, mid, world.get::<KmpPathNode>(e).unwrap().get_next()))
+        .collect();
+
+    // find the link between two checkpoints which crosses the finish line closest to the start point
+    let crossing = mids
+        .iter()
+        .flat_map(|(prev_e, prev_mid, next)| {
+            next.iter().copied().filter_map(|next_e| {
+                let next_mid = mids.iter().find(|x| x.0 == next_e)?.1;
+                if side(*prev_mid) > 0. || side(next_mid) <= 0. {
+                    return None;
+                }
+                Some((*prev_e, next_e, prev_mid.lerp(next_mid, 0.5).distance(start_pos)))
+            })
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2));
+
+    let (left_e, right_e) = match existing {
+        Some(e) => (e, world.get::<CheckpointLeft>(e).unwrap().right),
+        None => {
+            let height = **world.resource::<CheckpointHeight>();
+            checkpoint_spawner()
+                .cp(Checkpoint {
+                    kind: CheckpointKind::LapCount,
+                })
+                .pos(pos)
+                .height(height)
+                .world(world)
+                .call()
+        }
+    };
+
+    if let Some((prev_left, next_left, _)) = crossing {
+        let prev_right = world.get::<CheckpointLeft>(prev_left).unwrap().right;
+        let next_right = world.get::<CheckpointLeft>(next_left).unwrap().right;
+        for (prev, new, next) in [(prev_left, left_e, next_left), (prev_right, right_e, next_right)] {
+            KmpPathNode::unlink_nodes(prev, next, world);
+            KmpPathNode::link_nodes(prev, new, world);
+            KmpPathNode::link_nodes(new, next, world);
+        }
+    }
+    left_e
+}
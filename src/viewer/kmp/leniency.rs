@@ -0,0 +1,116 @@
+use super::{components::EnemyPathPoint, kcl_check::WALL_FLAGS, path::KmpPathNode};
+use crate::viewer::kcl_model::KCLModelSection;
+use bevy::{ecs::system::SystemState, prelude::*};
+use bevy_mod_raycast::prelude::*;
+
+pub fn leniency_plugin(app: &mut App) {
+    app.init_resource::<LeniencySuggestion>();
+}
+
+// start the rays this far above the point, so they go over kerbs and small bumps in the road instead of hitting them
+const RAY_HEIGHT: f32 = 100.;
+/// Walls further than this from a point aren't looked for, so points in wide open areas don't get huge leniencies
+pub const MAX_HALF_WIDTH: f32 = 10000.;
+
+/// Leniencies worked out from how wide the track is around each enemy point, for the user to look over before they
+/// are set
+#[derive(Resource)]
+pub struct LeniencySuggestion {
+    /// The leniency given for each unit of distance between a point and the nearest wall beside it
+    pub scale: f32,
+    /// The measured points, along with the distance to the nearest wall either side of the path there, if one was found
+    pub widths: Vec<(Entity, Option<f32>)>,
+}
+impl Default for LeniencySuggestion {
+    fn default() -> Self {
+        Self {
+            scale: 0.01,
+            widths: Vec::new(),
+        }
+    }
+}
+impl LeniencySuggestion {
+    /// Whether the measurements are of exactly these points, so they aren't offered for a different selection
+    pub fn is_for(&self, points: &[Entity]) -> bool {
+        self.widths.len() == points.len() && self.widths.iter().all(|(e, _)| points.contains(e))
+    }
+    /// The leniency for each measured point that a wall was found beside
+    pub fn leniencies(&self) -> impl Iterator<Item = (Entity, f32)> + '_ {
+        self.widths
+            .iter()
+            .filter_map(|(e, width)| width.map(|width| (*e, width * self.scale)))
+    }
+}
+
+// the horizontal direction the path goes through a point, averaged between the way it comes in and the way it goes out
+fn path_direction(world: &World, e: Entity) -> Option<Vec3> {
+    let node = world.get::<KmpPathNode>(e)?;
+    let pos = world.get::<Transform>(e)?.translation;
+    let dir_to = |other: &Entity| {
+        world
+            .get::<Transform>(*other)
+            .and_then(|x| (x.translation - pos).with_y(0.).try_normalize())
+            .unwrap_or_default()
+    };
+    let outgoing: Vec3 = node.next_nodes.iter().map(dir_to).sum();
+    let incoming: Vec3 = node.prev_nodes.iter().map(dir_to).sum();
+    (outgoing - incoming).try_normalize()
+}
+
+/// Measure how far it is from each enemy point to the nearest wall either side of the path, by casting rays across
+/// the path against the wall KCL. Points with no wall within [`MAX_HALF_WIDTH`] on either side, or which aren't linked
+/// to any other points, have no width.
+pub fn measure_track_widths(world: &mut World, points: &[Entity]) -> Vec<(Entity, Option<f32>)> {
+    let rays: Vec<_> = points
+        .iter()
+        .map(|e| {
+            let across = world
+                .get::<Transform>(*e)
+                .zip(path_direction(world, *e))
+                .map(|(transform, dir)| (transform.translation + Vec3::Y * RAY_HEIGHT, dir.cross(Vec3::Y)));
+            (*e, across)
+        })
+        .collect();
+
+    let mut ss = SystemState::<(Raycast, Query<&KCLModelSection>)>::new(world);
+    let (mut raycast, q_kcl) = ss.get_mut(world);
+    let filter = |e: Entity| {
+        q_kcl
+            .get(e)
+            .is_ok_and(|s| WALL_FLAGS.iter().any(|f| *f as usize == s.0))
+    };
+    let raycast_settings = RaycastSettings {
+        // hidden parts of the KCL still have collision in game
+        visibility: RaycastVisibility::Ignore,
+        ..default()
+    }
+    .with_filter(&filter);
+
+    rays.into_iter()
+        .map(|(e, across)| {
+            let width = across.and_then(|(origin, side)| {
+                [side, -side]
+                    .into_iter()
+                    .filter_map(|dir| {
+                        raycast
+                            .cast_ray(Ray3d::new(origin, dir), &raycast_settings)
+                            .first()
+                            .map(|(_, hit)| hit.distance())
+                    })
+                    .filter(|x| *x <= MAX_HALF_WIDTH)
+                    .reduce(f32::min)
+            });
+            (e, width)
+        })
+        .collect()
+}
+
+/// Set the leniency of every measured point that a wall was found beside
+pub fn apply_leniency_suggestion(world: &mut World) {
+    let leniencies: Vec<_> = world.resource::<LeniencySuggestion>().leniencies().collect();
+    for (e, leniency) in leniencies {
+        if let Some(mut point) = world.get_mut::<EnemyPathPoint>(e) {
+            point.leniency = leniency;
+        }
+    }
+}
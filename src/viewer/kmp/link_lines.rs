@@ -0,0 +1,161 @@
+use super::{
+    components::{RoutePoint, RouteSettings},
+    path::{despawn_link_meshes, spawn_link_meshes, KmpPathNode, KmpPathNodeLink, KmpPathNodeLinkLine, PathType},
+    routes::{smoothed_route_visible, GetRouteStart},
+};
+use crate::{ui::settings::AppSettings, viewer::edit::select::Selected};
+use bevy::{ecs::entity::EntityHashMap, prelude::*};
+
+pub fn link_lines_plugin(app: &mut App) {
+    app.init_gizmo_group::<PathLinkGizmos>()
+        .init_resource::<RouteStarts>()
+        .add_systems(
+            Update,
+            (
+                update_link_line_config,
+                update_route_starts,
+                (show_link_meshes, draw_link_lines).after(update_route_starts),
+            ),
+        );
+}
+
+// the largest the arrow drawn halfway along each link gets, so that long links don't have huge arrows
+const MAX_ARROW_LENGTH: f32 = 250.;
+
+/// Path links drawn as GPU lines, which are much cheaper than a cylinder mesh for every link on tracks with dense
+/// paths. Only links to selected points have meshes, so that they stand out.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct PathLinkGizmos;
+
+/// The first point of the route each route point is in, worked out whenever the routes change rather than walking
+/// back along the route for every link every frame
+#[derive(Resource, Default, Deref)]
+struct RouteStarts(EntityHashMap<Entity>);
+
+fn update_route_starts(
+    mut route_starts: ResMut<RouteStarts>,
+    q_changed: Query<(), (Changed<KmpPathNode>, With<RoutePoint>)>,
+    mut removed: RemovedComponents<KmpPathNode>,
+    q_route_pt: Query<Entity, With<RoutePoint>>,
+    get_route_start: GetRouteStart,
+) {
+    if q_changed.is_empty() && removed.read().count() == 0 {
+        return;
+    }
+    route_starts.0 = q_route_pt.iter().map(|e| (e, get_route_start.get_entity(e))).collect();
+}
+
+fn update_link_line_config(mut config_store: ResMut<GizmoConfigStore>, settings: Res<AppSettings>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let (config, _) = config_store.config_mut::<PathLinkGizmos>();
+    config.enabled = settings.kmp_model.gpu_path_links;
+    config.line_width = settings.kmp_model.path_link_width;
+}
+
+// a link keeps its meshes if either of the points it links is selected
+fn is_emphasised(link: &KmpPathNodeLink, q_selected: &Query<(), With<Selected>>) -> bool {
+    q_selected.contains(link.prev_node) || q_selected.contains(link.next_node)
+}
+
+// whether the link is part of a route that is drawn as the curve the game follows
+fn is_smoothed_route(
+    link: &KmpPathNodeLink,
+    settings: &AppSettings,
+    q_route_settings: &Query<&RouteSettings>,
+    route_starts: &RouteStarts,
+) -> bool {
+    link.kind == PathType::Route
+        && route_starts
+            .get(&link.prev_node)
+            .and_then(|start| q_route_settings.get(*start).ok())
+            .is_some_and(|route_settings| smoothed_route_visible(settings, route_settings))
+}
+
+// spawn the line and arrow meshes of the links which aren't drawn as GPU lines, and despawn them from the ones which
+// are, so that tracks with dense paths don't have a mesh for every link. The straight lines between route points
+// would just get in the way of the smoothed curve, so those are hidden either way, but the arrows are kept so the
+// direction of the route can still be seen.
+fn show_link_meshes(
+    mut commands: Commands,
+    settings: Res<AppSettings>,
+    q_node_link: Query<(Entity, &KmpPathNodeLink, Option<&Children>)>,
+    mut q_children: Query<(&mut Visibility, Has<KmpPathNodeLinkLine>), Without<KmpPathNodeLink>>,
+    q_selected: Query<(), With<Selected>>,
+    q_route_settings: Query<&RouteSettings>,
+    route_starts: Res<RouteStarts>,
+) {
+    for (link_e, node_link, children) in q_node_link.iter() {
+        let meshes = !settings.kmp_model.gpu_path_links || is_emphasised(node_link, &q_selected);
+        let children = match (meshes, children) {
+            (true, Some(children)) => children,
+            (true, None) => {
+                commands.add(move |world: &mut World| spawn_link_meshes(world, link_e));
+                continue;
+            }
+            (false, Some(_)) => {
+                commands.add(move |world: &mut World| despawn_link_meshes(world, link_e));
+                continue;
+            }
+            (false, None) => continue,
+        };
+        let smoothed = is_smoothed_route(node_link, &settings, &q_route_settings, &route_starts);
+        for child in children.iter() {
+            let Ok((mut visibility, is_line)) = q_children.get_mut(*child) else {
+                continue;
+            };
+            let new_visibility = if is_line && smoothed {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+            visibility.set_if_neq(new_visibility);
+        }
+    }
+}
+
+fn draw_link_lines(
+    mut gizmos: Gizmos<PathLinkGizmos>,
+    settings: Res<AppSettings>,
+    q_node_link: Query<(&KmpPathNodeLink, &Visibility)>,
+    q_transform: Query<&Transform>,
+    q_selected: Query<(), With<Selected>>,
+    q_route_settings: Query<&RouteSettings>,
+    route_starts: Res<RouteStarts>,
+) {
+    if !settings.kmp_model.gpu_path_links {
+        return;
+    }
+    let colors = &settings.kmp_model.color;
+    for (node_link, visibility) in q_node_link.iter() {
+        if visibility == Visibility::Hidden || is_emphasised(node_link, &q_selected) {
+            continue;
+        }
+        let Ok([prev, next]) = q_transform.get_many([node_link.prev_node, node_link.next_node]) else {
+            continue;
+        };
+        let (prev, next) = (prev.translation, next.translation);
+        let (line, arrow) = match node_link.kind {
+            PathType::Enemy => (colors.enemy_paths.line, colors.enemy_paths.arrow),
+            PathType::Item => (colors.item_paths.line, colors.item_paths.arrow),
+            PathType::Checkpoint { .. } => (colors.checkpoints.line, colors.checkpoints.arrow),
+            PathType::Route => (colors.routes.line, colors.routes.arrow),
+        };
+        // route lines are already drawn, either in each route's colour or as the smoothed curve
+        let route_drawn = node_link.kind == PathType::Route
+            && (settings.kmp_model.color_routes
+                || is_smoothed_route(node_link, &settings, &q_route_settings, &route_starts));
+        if !route_drawn {
+            gizmos.line(prev, next, line);
+        }
+        let Some(dir) = (next - prev).try_normalize() else {
+            continue;
+        };
+        let mid = prev.lerp(next, 0.5);
+        let length = (prev.distance(next) / 4.).min(MAX_ARROW_LENGTH);
+        gizmos
+            .arrow(mid - dir * length / 2., mid + dir * length / 2., arrow)
+            .with_tip_length(length);
+    }
+}
@@ -1,15 +1,17 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, mem::size_of};
 
 use super::{
-    settings::{CheckpointColor, PathColor, PointColor},
-    AreaPoint, BattleFinishPoint, CannonPoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object, RespawnPoint,
-    RoutePoint, StartPoint,
+    sections::KmpEditMode,
+    settings::{CheckpointColor, KmpModelColors, PathColor, PointColor},
+    AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera,
+    KmpSelectablePoint, Object, RespawnPoint, RoutePoint, StartPoint,
 };
 use crate::{
     ui::settings::AppSettings,
     util::shapes::{Cone, Cylinder},
 };
 use bevy::prelude::*;
+use strum::IntoEnumIterator;
 
 #[derive(Clone, Resource)]
 pub struct KmpMeshes {
@@ -108,60 +110,204 @@ pub fn unlit_material(materials: &mut Assets<StandardMaterial>, color: Color) ->
     })
 }
 
-pub fn setup_kmp_meshes_materials(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    settings: Res<AppSettings>,
-) {
-    let kmp_meshes = KmpMeshes {
-        sphere: meshes.add(Sphere::new(100.).mesh()),
-        cylinder: meshes.add(Mesh::from(Cylinder {
-            height: 1.,
-            radius_bottom: 50.,
-            radius_top: 50.,
-            radial_segments: 32,
-            height_segments: 32,
-        })),
-        frustrum: meshes.add(Mesh::from(Cylinder {
-            height: 100.,
-            radius_bottom: 100.,
-            radius_top: 50.,
-            radial_segments: 32,
-            height_segments: 32,
-        })),
-        cone: meshes.add(Mesh::from(Cone {
-            height: 200.,
-            radius: 100.,
-            segments: 32,
-        })),
-        plane: meshes.add(Plane3d::default().mesh()),
+impl KmpMeshes {
+    /// Get the meshes shared by all the sections, creating them the first time any section needs them
+    pub fn get(world: &mut World) -> Self {
+        if let Some(meshes) = world.get_resource::<Self>() {
+            return meshes.clone();
+        }
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let kmp_meshes = Self {
+            sphere: meshes.add(Sphere::new(100.).mesh()),
+            cylinder: meshes.add(Mesh::from(Cylinder {
+                height: 1.,
+                radius_bottom: 50.,
+                radius_top: 50.,
+                radial_segments: 32,
+                height_segments: 32,
+            })),
+            frustrum: meshes.add(Mesh::from(Cylinder {
+                height: 100.,
+                radius_bottom: 100.,
+                radius_top: 50.,
+                radial_segments: 32,
+                height_segments: 32,
+            })),
+            cone: meshes.add(Mesh::from(Cone {
+                height: 200.,
+                radius: 100.,
+                segments: 32,
+            })),
+            plane: meshes.add(Plane3d::default().mesh()),
+        };
+        world.insert_resource(kmp_meshes.clone());
+        kmp_meshes
+    }
+}
+
+/// The materials for the points of a section, which are only created once a point in that section is spawned
+pub trait SectionMaterials: Resource + Clone {
+    type Colors;
+    fn colors(colors: &KmpModelColors) -> &Self::Colors;
+    fn create(materials: &mut Assets<StandardMaterial>, colors: &Self::Colors) -> Self;
+    /// How many materials the section uses
+    const COUNT: usize;
+
+    /// Get the materials for the section, creating them the first time they are needed
+    fn get(world: &mut World) -> Self {
+        if let Some(materials) = world.get_resource::<Self>() {
+            return materials.clone();
+        }
+        let section_materials = world.resource_scope(|world, mut materials: Mut<Assets<StandardMaterial>>| {
+            let colors = &world.resource::<AppSettings>().kmp_model.color;
+            Self::create(&mut materials, Self::colors(colors))
+        });
+        world.insert_resource(section_materials.clone());
+        section_materials
+    }
+}
+
+macro_rules! section_materials {
+    ($materials:ident<$ty:ty>, $colors:ty, $field:ident, $count:expr) => {
+        impl SectionMaterials for $materials<$ty> {
+            type Colors = $colors;
+            const COUNT: usize = $count;
+            fn colors(colors: &KmpModelColors) -> &Self::Colors {
+                &colors.$field
+            }
+            fn create(materials: &mut Assets<StandardMaterial>, colors: &Self::Colors) -> Self {
+                Self::from_colors(materials, colors)
+            }
+        }
     };
-    commands.insert_resource(kmp_meshes);
+}
+section_materials!(PointMaterials<StartPoint>, PointColor, start_points, 4);
+section_materials!(PathMaterials<EnemyPathPoint>, PathColor, enemy_paths, 3);
+section_materials!(PathMaterials<ItemPathPoint>, PathColor, item_paths, 3);
+section_materials!(PointMaterials<RespawnPoint>, PointColor, respawn_points, 4);
+section_materials!(PointMaterials<Object>, PointColor, objects, 4);
+section_materials!(PathMaterials<RoutePoint>, PathColor, routes, 3);
+section_materials!(PointMaterials<AreaPoint>, PointColor, areas, 4);
+section_materials!(PointMaterials<KmpCamera>, PointColor, cameras, 4);
+section_materials!(PointMaterials<CannonPoint>, PointColor, cannon_points, 4);
+section_materials!(PointMaterials<BattleFinishPoint>, PointColor, battle_finish_points, 4);
+impl SectionMaterials for CheckpointMaterials {
+    type Colors = CheckpointColor;
+    const COUNT: usize = 8;
+    fn colors(colors: &KmpModelColors) -> &Self::Colors {
+        &colors.checkpoints
+    }
+    fn create(materials: &mut Assets<StandardMaterial>, colors: &Self::Colors) -> Self {
+        Self::from_colors(materials, colors)
+    }
+}
 
-    let colors = &settings.kmp_model.color;
+/// How many materials a section has loaded, which is 0 if none of its points have been spawned yet
+fn loaded_material_count(world: &World, section: KmpEditMode) -> usize {
+    fn count<M: SectionMaterials>(world: &World) -> usize {
+        if world.contains_resource::<M>() {
+            M::COUNT
+        } else {
+            0
+        }
+    }
+    match section {
+        KmpEditMode::StartPoints => count::<PointMaterials<StartPoint>>(world),
+        KmpEditMode::EnemyPaths => count::<PathMaterials<EnemyPathPoint>>(world),
+        KmpEditMode::ItemPaths => count::<PathMaterials<ItemPathPoint>>(world),
+        KmpEditMode::Checkpoints => count::<CheckpointMaterials>(world),
+        KmpEditMode::RespawnPoints => count::<PointMaterials<RespawnPoint>>(world),
+        KmpEditMode::Objects => count::<PointMaterials<Object>>(world),
+        KmpEditMode::Routes => count::<PathMaterials<RoutePoint>>(world),
+        KmpEditMode::Areas => count::<PointMaterials<AreaPoint>>(world),
+        KmpEditMode::Cameras => count::<PointMaterials<KmpCamera>>(world),
+        KmpEditMode::CannonPoints => count::<PointMaterials<CannonPoint>>(world),
+        KmpEditMode::BattleFinishPoints => count::<PointMaterials<BattleFinishPoint>>(world),
+        KmpEditMode::TrackInfo => 0,
+    }
+}
 
-    let start_points = PointMaterials::<StartPoint>::from_colors(&mut materials, &colors.start_points);
-    commands.insert_resource(start_points);
-    let enemy_paths = PathMaterials::<EnemyPathPoint>::from_colors(&mut materials, &colors.enemy_paths);
-    commands.insert_resource(enemy_paths);
-    let item_paths = PathMaterials::<ItemPathPoint>::from_colors(&mut materials, &colors.item_paths);
-    commands.insert_resource(item_paths);
-    let checkpoints = CheckpointMaterials::from_colors(&mut materials, &colors.checkpoints);
-    commands.insert_resource(checkpoints);
-    let respawn_points = PointMaterials::<RespawnPoint>::from_colors(&mut materials, &colors.respawn_points);
-    commands.insert_resource(respawn_points);
-    let objects = PointMaterials::<Object>::from_colors(&mut materials, &colors.objects);
-    commands.insert_resource(objects);
-    let routes = PathMaterials::<RoutePoint>::from_colors(&mut materials, &colors.routes);
-    commands.insert_resource(routes);
-    let areas = PointMaterials::<AreaPoint>::from_colors(&mut materials, &colors.areas);
-    commands.insert_resource(areas);
-    let cameras = PointMaterials::<KmpCamera>::from_colors(&mut materials, &colors.cameras);
-    commands.insert_resource(cameras);
-    let cannon_points = PointMaterials::<CannonPoint>::from_colors(&mut materials, &colors.cannon_points);
-    commands.insert_resource(cannon_points);
-    let battle_finish_points =
-        PointMaterials::<BattleFinishPoint>::from_colors(&mut materials, &colors.battle_finish_points);
-    commands.insert_resource(battle_finish_points);
+/// Get rid of the materials of sections which have no points, such as after opening a KMP with fewer sections
+/// than the last one, so they don't stay in memory. They are made again if a point is added to the section.
+pub fn unload_unused_section_materials(world: &mut World) {
+    fn unload<T: Component, M: SectionMaterials>(world: &mut World) {
+        if world.query_filtered::<(), With<T>>().iter(world).next().is_none() {
+            world.remove_resource::<M>();
+        }
+    }
+    unload::<StartPoint, PointMaterials<StartPoint>>(world);
+    unload::<EnemyPathPoint, PathMaterials<EnemyPathPoint>>(world);
+    unload::<ItemPathPoint, PathMaterials<ItemPathPoint>>(world);
+    unload::<Checkpoint, CheckpointMaterials>(world);
+    unload::<RespawnPoint, PointMaterials<RespawnPoint>>(world);
+    unload::<Object, PointMaterials<Object>>(world);
+    unload::<RoutePoint, PathMaterials<RoutePoint>>(world);
+    unload::<AreaPoint, PointMaterials<AreaPoint>>(world);
+    unload::<KmpCamera, PointMaterials<KmpCamera>>(world);
+    unload::<CannonPoint, PointMaterials<CannonPoint>>(world);
+    unload::<BattleFinishPoint, PointMaterials<BattleFinishPoint>>(world);
+    if world
+        .query_filtered::<(), With<KmpSelectablePoint>>()
+        .iter(world)
+        .next()
+        .is_none()
+    {
+        world.remove_resource::<KmpMeshes>();
+    }
+}
+
+/// Roughly how much memory each section is taking up
+pub struct SectionMemory {
+    pub section: KmpEditMode,
+    /// The number of entities making up the section's points, including their lines, arrows and other parts
+    pub entities: usize,
+    pub materials: usize,
+    /// The size of the components of those entities, plus the materials
+    pub bytes: usize,
+}
+
+/// How much memory each section is using, for sections which have anything loaded
+pub fn section_memory(world: &mut World) -> Vec<SectionMemory> {
+    let points: Vec<_> = world
+        .query_filtered::<Entity, With<KmpSelectablePoint>>()
+        .iter(world)
+        .collect();
+    // the size of an entity's components, and those of all its children
+    fn entity_bytes(world: &World, e: Entity, entities: &mut usize) -> usize {
+        let entity = world.entity(e);
+        *entities += 1;
+        let components = world.components();
+        let own: usize = entity
+            .archetype()
+            .components()
+            .filter_map(|id| components.get_info(id))
+            .map(|info| info.layout().size())
+            .sum();
+        let children: usize = entity
+            .get::<Children>()
+            .map(|children| children.iter().map(|child| entity_bytes(world, *child, entities)).sum())
+            .unwrap_or_default();
+        own + children
+    }
+
+    let mut memory: Vec<_> = KmpEditMode::iter()
+        .map(|section| {
+            let materials = loaded_material_count(world, section);
+            SectionMemory {
+                section,
+                entities: 0,
+                materials,
+                bytes: materials * size_of::<StandardMaterial>(),
+            }
+        })
+        .collect();
+    for e in points {
+        let entity = world.entity(e);
+        let Some(section) = memory.iter_mut().find(|x| x.section.contains_entity(&entity)) else {
+            continue;
+        };
+        section.bytes += entity_bytes(world, e, &mut section.entities);
+    }
+    memory.retain(|x| x.entities > 0 || x.materials > 0);
+    memory
 }
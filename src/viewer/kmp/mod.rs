@@ -1,29 +1,126 @@
+pub mod area_volumes;
+pub mod branches;
+pub mod bullet_path;
+pub mod camera_aim;
+pub mod camera_chain;
+pub mod camera_file;
+pub mod cannons;
+pub mod checkpoint_skips;
 pub mod checkpoints;
 pub mod components;
+pub mod concurrent_edit;
+pub mod convert;
 pub mod csv;
+pub mod custom_rules;
+pub mod dispatch_points;
+pub mod documents;
+pub mod donor_import;
+pub mod external_edit;
+pub mod find_value;
+pub mod ghosts;
+pub mod header_values;
+pub mod history;
+pub mod intro_preview;
+pub mod item_boxes;
+pub mod kcl_check;
+pub mod lap_count;
+pub mod leniency;
+pub mod link_lines;
 pub mod meshes_materials;
+pub mod moving_road;
+pub mod object_density;
+pub mod object_models;
 pub mod ordering;
 pub mod path;
+pub mod path_start;
+pub mod path_tools;
 pub mod point;
+pub mod point_metadata;
+pub mod point_state;
+pub mod project;
+pub mod reference;
+pub mod respawn_links;
+pub mod reverse_path;
 pub mod routes;
+pub mod row_clipboard;
+pub mod schema;
+pub mod section_import;
 pub mod sections;
 pub mod settings;
+pub mod sidecar;
+pub mod sound_triggers;
+pub mod start_points;
+pub mod track_length;
+pub mod track_type;
+pub mod validate;
+pub mod value_colors;
+pub mod workbook;
 
 use self::{
+    area_volumes::area_volumes_plugin,
+    branches::branches_plugin,
+    bullet_path::bullet_path_plugin,
+    camera_aim::camera_aim_plugin,
+    camera_chain::camera_chain_plugin,
+    camera_file::camera_file_plugin,
+    cannons::cannons_plugin,
+    checkpoint_skips::checkpoint_skips_plugin,
     checkpoints::{checkpoint_plugin, spawn_checkpoint_section},
     components::*,
-    meshes_materials::setup_kmp_meshes_materials,
+    concurrent_edit::{concurrent_edit_plugin, other_editor, ConcurrentEditPrompt, ThisEditor},
+    csv::csv_plugin,
+    custom_rules::custom_rules_plugin,
+    dispatch_points::dispatch_points_plugin,
+    documents::{documents_plugin, switch_to_document, OpenDocuments},
+    donor_import::donor_import_plugin,
+    external_edit::external_edit_plugin,
+    find_value::find_value_plugin,
+    ghosts::{ghosts_plugin, SavedGhosts},
+    header_values::{restore_header_values, OpenedHeaderValues},
+    history::record_save,
+    intro_preview::intro_preview_plugin,
+    item_boxes::item_boxes_plugin,
+    kcl_check::kcl_check_plugin,
+    lap_count::lap_count_plugin,
+    leniency::leniency_plugin,
+    link_lines::link_lines_plugin,
+    meshes_materials::unload_unused_section_materials,
+    moving_road::moving_road_plugin,
+    object_density::object_density_plugin,
+    object_models::object_models_plugin,
     path::{spawn_enemy_item_path_section, RecalcPaths},
-    point::{spawn_point_section, AddRespawnPointPreview},
+    path_start::path_start_plugin,
+    path_tools::path_tools_plugin,
+    point::{point_plugin, spawn_point_section, AddRespawnPointPreview},
+    point_metadata::{apply_point_metadata, collect_point_metadata, PointMetadata},
+    point_state::{apply_point_states, collect_point_states, PointState},
+    project::project_plugin,
+    reference::reference_plugin,
+    respawn_links::respawn_links_plugin,
+    reverse_path::reverse_path_plugin,
+    row_clipboard::row_clipboard_plugin,
+    section_import::section_import_plugin,
+    sound_triggers::sound_triggers_plugin,
+    start_points::start_points_plugin,
+    track_length::track_length_plugin,
+    track_type::track_type_plugin,
+    validate::validate_plugin,
+    value_colors::value_colors_plugin,
+    workbook::workbook_plugin,
 };
 use crate::{
     ui::{
+        errors::{show_errors, ErrorMessages},
         file_dialog::{DialogType, FileDialogResult},
-        settings::{AppSettings, SetupAppSettingsSet},
+        settings::AppSettings,
         ui_state::KmpFilePath,
         update_ui::{KclFileSelected, KmpFileSelected},
     },
-    util::kmp_file::*,
+    util::{
+        kmp_file::*,
+        szs::{read_szs, write_szs, COURSE_KMP},
+    },
+    viewer::edit::undo::UndoHistory,
 };
 use anyhow::{bail, Context};
 use bevy::{
@@ -32,12 +129,19 @@ use bevy::{
     utils::HashMap,
 };
 use derive_new::new;
-use ordering::{ordering_plugin, RefreshOrdering};
+use ordering::{ordering_plugin, set_original_indices, IndexChangeWarning, RefreshOrdering};
 use path::{path_plugin, save_path_section, EntityPathGroups};
 use point::save_point_section;
 use routes::{routes_plugin, spawn_route_section};
 use sections::{add_for_all_components, section_plugin, KmpEditMode};
-use std::{ffi::OsStr, fs::File, marker::PhantomData};
+use sidecar::KmpSidecar;
+use std::{
+    ffi::OsStr,
+    fs,
+    io::Cursor,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
 
 pub fn kmp_plugin(app: &mut App) {
     app.add_plugins((
@@ -46,19 +150,58 @@ pub fn kmp_plugin(app: &mut App) {
         ordering_plugin,
         section_plugin,
         routes_plugin,
+        ghosts_plugin,
+        kcl_check_plugin,
+        lap_count_plugin,
+        external_edit_plugin,
+        value_colors_plugin,
+        dispatch_points_plugin,
+        donor_import_plugin,
+        camera_chain_plugin,
+        object_density_plugin,
+        section_import_plugin,
+    ))
+    .add_plugins((
+        branches_plugin,
+        start_points_plugin,
+        path_start_plugin,
+        csv_plugin,
+        moving_road_plugin,
+        item_boxes_plugin,
+        validate_plugin,
+        reference_plugin,
+        intro_preview_plugin,
+        find_value_plugin,
+        camera_aim_plugin,
+        point_plugin,
+        documents_plugin,
+        track_length_plugin,
+        path_tools_plugin,
     ))
+    .add_plugins((
+        object_models_plugin,
+        area_volumes_plugin,
+        checkpoint_skips_plugin,
+        track_type_plugin,
+        concurrent_edit_plugin,
+        link_lines_plugin,
+        sound_triggers_plugin,
+        respawn_links_plugin,
+        workbook_plugin,
+        cannons_plugin,
+        bullet_path_plugin,
+        custom_rules_plugin,
+        row_clipboard_plugin,
+        camera_file_plugin,
+        leniency_plugin,
+    ))
+    .add_plugins((reverse_path_plugin, project_plugin))
     .add_event::<SaveFile>()
-    .add_systems(Startup, setup_kmp_meshes_materials.after(SetupAppSettingsSet))
-    .add_systems(
-        Update,
-        (save_kmp.pipe(handle_save_kmp_errors)).run_if(on_event::<SaveFile>()),
-    )
+    .add_systems(Update, (save_kmp.pipe(show_errors)).run_if(on_event::<SaveFile>()))
     .add_systems(
         Update,
         (
-            open_kmp
-                .pipe(handle_open_kmp_errors)
-                .run_if(on_event::<KmpFileSelected>()),
+            open_kmp.pipe(show_errors).run_if(on_event::<KmpFileSelected>()),
             open_kmp_kcl,
         ),
     );
@@ -89,12 +232,24 @@ pub fn open_kmp_kcl(
                     }
                 } else if file_ext == "kcl" {
                     ev_kcl_file_selected.send(KclFileSelected(path.into()));
+                } else if file_ext == "szs" {
+                    // the KMP and KCL are both read out of the archive
+                    ev_kmp_file_selected.send(KmpFileSelected(path.into()));
+                    ev_kcl_file_selected.send(KclFileSelected(path.into()));
                 }
             }
         }
     }
 }
 
+/// Shown when a KMP being opened is little-endian, asking whether it should be converted to the normal big-endian
+/// format. Set `accepted` and send the path again to open it converted.
+#[derive(Resource, new)]
+pub struct KmpConversionPrompt {
+    pub path: PathBuf,
+    pub accepted: bool,
+}
+
 #[derive(Resource, Deref, DerefMut, Clone, Default)]
 pub struct KmpErrors(pub Vec<KmpError>);
 impl KmpErrors {
@@ -104,9 +259,77 @@ impl KmpErrors {
 }
 #[derive(Clone, new)]
 pub struct KmpError {
-    #[allow(unused)]
-    message: String,
+    pub message: String,
 }
+
+/// What was odd about the last KMP opened, for KMPs that aren't normal courses (e.g. for mission mode) and so have
+/// sections that are empty or don't make sense. Remove this resource to dismiss it.
+#[derive(Resource)]
+pub struct KmpLoadReport {
+    pub path: PathBuf,
+    pub empty_sections: Vec<&'static str>,
+    pub errors: Vec<String>,
+}
+impl KmpLoadReport {
+    /// A report on the KMP if there is anything to report, which is when it couldn't all be loaded, or it is
+    /// missing something every course and battle arena has
+    pub fn new(path: PathBuf, kmp: &KmpFile, errors: KmpErrors) -> Option<Self> {
+        let empty_sections: Vec<_> = section_lengths(kmp)
+            .into_iter()
+            .filter(|x| x.1 == 0)
+            .map(|x| x.0)
+            .collect();
+        let unusual = empty_sections.iter().any(|x| COURSE_SECTIONS.contains(x));
+        if errors.is_empty() && !unusual {
+            return None;
+        }
+        // the same problem is often found on lots of points, so it is only listed once with how many times it was
+        let mut counted: Vec<(String, usize)> = Vec::new();
+        for error in errors.0 {
+            match counted.iter_mut().find(|x| x.0 == error.message) {
+                Some(x) => x.1 += 1,
+                None => counted.push((error.message, 1)),
+            }
+        }
+        let errors = counted
+            .into_iter()
+            .map(|(message, count)| match count {
+                1 => message,
+                _ => format!("{message} ({count} times)"),
+            })
+            .collect();
+        Some(Self {
+            path,
+            empty_sections,
+            errors,
+        })
+    }
+}
+
+// the sections every course and battle arena has, so if any are empty the KMP is probably something else
+const COURSE_SECTIONS: [&str; 3] = ["KTPT", "ENPT", "STGI"];
+
+// the name and number of entries of every section
+fn section_lengths(kmp: &KmpFile) -> [(&'static str, usize); 15] {
+    [
+        ("KTPT", kmp.ktpt.len()),
+        ("ENPT", kmp.enpt.len()),
+        ("ENPH", kmp.enph.len()),
+        ("ITPT", kmp.itpt.len()),
+        ("ITPH", kmp.itph.len()),
+        ("CKPT", kmp.ckpt.len()),
+        ("CKPH", kmp.ckph.len()),
+        ("GOBJ", kmp.gobj.len()),
+        ("POTI", kmp.poti.len()),
+        ("AREA", kmp.area.len()),
+        ("CAME", kmp.came.len()),
+        ("JGPT", kmp.jgpt.len()),
+        ("CNPT", kmp.cnpt.len()),
+        ("MSPT", kmp.mspt.len()),
+        ("STGI", kmp.stgi.len()),
+    ]
+}
+
 #[derive(Resource, Deref, DerefMut, Clone, Default, new)]
 pub struct KmpSectionIdEntityMap<T: Component>(#[deref] pub HashMap<u32, Entity>, PhantomData<T>);
 
@@ -116,17 +339,86 @@ pub fn open_kmp(world: &mut World) -> anyhow::Result<()> {
     let Some(ev) = ev_kmp_file_selected.read().next() else {
         return Ok(());
     };
-    // if the file extension is not 'kmp' return
-    if ev.extension() != Some(OsStr::new("kmp")) {
-        bail!("file extension was not .kmp")
+    // a file that is already open is switched to rather than opened again
+    if let Some(index) = world.resource::<OpenDocuments>().position(&ev.0) {
+        switch_to_document(world, index);
+        return Ok(());
+    }
+    let data = match ev.extension().and_then(OsStr::to_str) {
+        Some("kmp") => fs::read(&ev.0).context("could not open kmp file")?,
+        // the KMP is read out of the archive, and saved back into it
+        Some("szs") => read_szs(&ev.0)?
+            .file(COURSE_KMP)
+            .context("szs file has no course.kmp")?
+            .clone(),
+        _ => bail!("file extension was not .kmp or .szs"),
+    };
+    let path = ev.0.clone();
+
+    let format = KmpFormat::detect(&data);
+    if format == KmpFormat::LittleEndian {
+        // only convert once the user has agreed to it, as the file will be saved back big-endian
+        let accepted = world
+            .get_resource::<KmpConversionPrompt>()
+            .is_some_and(|prompt| prompt.accepted && prompt.path == path);
+        if !accepted {
+            world.insert_resource(KmpConversionPrompt::new(path, false));
+            return Ok(());
+        }
+    }
+    let kmp = KmpFile::read_format(&data, &format).context("could not read kmp file")?;
+
+    // if another kmpeek is already editing the file, whichever saves last would overwrite the other's changes, so
+    // ask whether to open it read-only first
+    let mut sidecar = KmpSidecar::load(&path);
+    let read_only = world
+        .get_resource::<ConcurrentEditPrompt>()
+        .filter(|prompt| prompt.path == path)
+        .and_then(|prompt| prompt.read_only);
+    match (read_only, other_editor(world, &sidecar)) {
+        (Some(read_only), _) => sidecar.read_only = read_only,
+        (None, Some(editor)) => {
+            world.insert_resource(ConcurrentEditPrompt {
+                path,
+                editor,
+                read_only: None,
+            });
+            return Ok(());
+        }
+        (None, None) => {}
     }
+    // the prompts are only done with once the file is actually opened, so that answering one doesn't ask the other again
+    world.remove_resource::<KmpConversionPrompt>();
+    world.remove_resource::<ConcurrentEditPrompt>();
 
-    // open the KMP file and read it
-    let mut kmp_file = File::open(ev.0.clone()).context("could not open kmp file")?;
-    let kmp = KmpFile::read(&mut kmp_file).context("could not read kmp file")?;
+    // keep whatever was open before, so it can be switched back to
+    if !OpenDocuments::stash_active(world) {
+        bail!("the current kmp is still being opened");
+    }
+    world.resource_mut::<OpenDocuments>().push_active(path.clone());
 
-    world.insert_resource(KmpFilePath(ev.0.clone()));
+    world.insert_resource(KmpFilePath(path.clone()));
 
+    let errors = spawn_kmp(world, &kmp, &sidecar.shown_areas);
+    world.insert_resource(OpenedHeaderValues::new(&kmp));
+    apply_point_metadata(world, &sidecar.points);
+    world.insert_resource(sidecar);
+    world.remove_resource::<KmpLoadReport>();
+    if let Some(report) = KmpLoadReport::new(path.clone(), &kmp, errors) {
+        world.insert_resource(report);
+    }
+
+    // the order ids are still the indexes in the file at this point
+    set_original_indices(world);
+    world.send_event(RefreshOrdering);
+
+    SavedGhosts::snapshot(world);
+    Ok(())
+}
+
+/// Replace all the KMP entities in the world with the ones from a KMP file, returning anything in it that couldn't be
+/// loaded properly
+pub fn spawn_kmp(world: &mut World, kmp: &KmpFile, shown_areas: &[u32]) -> KmpErrors {
     // get rid of all kmp points we may currently have in the world
     let entities: Vec<_> = world
         .query_filtered::<Entity, With<KmpSelectablePoint>>()
@@ -141,70 +433,110 @@ pub fn open_kmp(world: &mut World) -> anyhow::Result<()> {
 
     world.init_resource::<KmpErrors>();
 
-    let stgi = kmp.stgi.first().unwrap();
-    let track_info = TrackInfo::from_kmp(stgi, world);
+    let track_info = match kmp.stgi.first() {
+        Some(stgi) => TrackInfo::from_kmp(stgi, world),
+        None => {
+            world
+                .resource_mut::<KmpErrors>()
+                .add("There is no STGI entry, so the default track info was used");
+            TrackInfo::default()
+        }
+    };
     world.insert_resource(track_info);
 
     // --- ROUTES ---
-    let route_id_map = spawn_route_section(world, &kmp);
+    let route_id_map = spawn_route_section(world, kmp);
     world.insert_resource(route_id_map);
 
     // --- RESPAWN POINTS ---
-    let respawn_pts_id_map = spawn_point_section::<RespawnPoint>(world, &kmp);
+    let respawn_pts_id_map = spawn_point_section::<RespawnPoint>(world, kmp);
     respawn_pts_id_map
         .iter()
         .for_each(|(_, e)| AddRespawnPointPreview(*e).apply(world));
     world.insert_resource(respawn_pts_id_map);
 
     // --- START POINTS ---
-    spawn_point_section::<StartPoint>(world, &kmp);
+    spawn_point_section::<StartPoint>(world, kmp);
 
     // --- ENEMY PATHS ---
-    spawn_enemy_item_path_section::<EnemyPathPoint>(world, &kmp);
+    spawn_enemy_item_path_section::<EnemyPathPoint>(world, kmp);
 
     // --- ITEM PATHS ---
-    spawn_enemy_item_path_section::<ItemPathPoint>(world, &kmp);
+    spawn_enemy_item_path_section::<ItemPathPoint>(world, kmp);
 
     // --- CHECKPOINTS ---
-    spawn_checkpoint_section(world, &kmp);
+    spawn_checkpoint_section(world, kmp);
 
     // --- OBJECTS ---
-    spawn_point_section::<Object>(world, &kmp);
+    spawn_point_section::<Object>(world, kmp);
 
     // --- AREAS ---
-    spawn_point_section::<AreaPoint>(world, &kmp);
+    let area_id_map = spawn_point_section::<AreaPoint>(world, kmp);
+    for id in shown_areas.iter() {
+        if let Some(mut area) = area_id_map.get(id).and_then(|e| world.get_mut::<AreaPoint>(*e)) {
+            area.show_area = true;
+        }
+    }
 
     // --- CAMREAS ---
-    let camera_id_map = spawn_point_section::<KmpCamera>(world, &kmp);
+    let camera_id_map = spawn_point_section::<KmpCamera>(world, kmp);
 
     // the intro start index is the first byte of the additional value
     let intro_start = kmp.came.section_header.additional_value >> 8;
-    dbg!(&intro_start);
     if let Some(e) = camera_id_map.get(&(intro_start as u32)) {
         world.entity_mut(*e).insert(KmpCameraIntroStart);
     }
 
     // --- CANNON POINTS ---
-    spawn_point_section::<CannonPoint>(world, &kmp);
+    spawn_point_section::<CannonPoint>(world, kmp);
 
     // --- FINISH POINTS ---
-    spawn_point_section::<BattleFinishPoint>(world, &kmp);
+    spawn_point_section::<BattleFinishPoint>(world, kmp);
+
+    // the last KMP may have had sections that this one doesn't
+    unload_unused_section_materials(world);
 
     world.send_event(RecalcPaths::all());
 
-    world.remove_resource::<KmpErrors>();
     world.remove_resource::<KmpSectionIdEntityMap<RoutePoint>>();
     world.remove_resource::<KmpSectionIdEntityMap<RespawnPoint>>();
+    world.remove_resource::<KmpErrors>().unwrap_or_default()
+}
 
-    world.send_event(RefreshOrdering);
+/// Everything needed to respawn the KMP as it is now, including the editor state of its points which isn't in the file
+#[derive(Clone)]
+pub struct KmpState {
+    pub kmp: KmpFile,
+    pub shown_areas: Vec<u32>,
+    pub point_metadata: Vec<PointMetadata>,
+    pub point_states: Vec<PointState>,
+}
+impl KmpState {
+    pub fn take(world: &mut World) -> Self {
+        let (kmp, shown_areas) = build_kmp(world);
+        Self {
+            kmp,
+            shown_areas,
+            point_metadata: collect_point_metadata(world),
+            point_states: collect_point_states(world),
+        }
+    }
+}
 
-    Ok(())
+/// Replace all the KMP entities in the world with a state that was taken earlier (e.g. by undo, or switching branch or
+/// document), putting back the names, selection, pins and original indices of its points
+pub fn respawn_kmp(world: &mut World, state: &KmpState) {
+    spawn_kmp(world, &state.kmp, &state.shown_areas);
+    apply_point_metadata(world, &state.point_metadata);
+    apply_point_states(world, &state.point_states);
+    refresh_spawned_points(world);
 }
 
-fn handle_open_kmp_errors(In(result): In<anyhow::Result<()>>) {
-    if let Err(err) = result {
-        dbg!(err);
-    }
+/// Put points which have just been spawned in order, and show them if they are in the current section
+pub fn refresh_spawned_points(world: &mut World) {
+    world.send_event(RefreshOrdering);
+    // the new points are spawned hidden, so refresh the visibilities for the current section
+    world.resource_mut::<KmpEditMode>().set_changed();
 }
 
 #[derive(Resource, Deref, DerefMut, Clone, Default, new)]
@@ -214,6 +546,70 @@ pub struct KmpSectionEntityIdMap<T: Component>(#[deref] pub EntityHashMap<u8>, P
 pub struct SaveFile;
 
 pub fn save_kmp(world: &mut World) -> anyhow::Result<()> {
+    // if any indexes would change, ask for confirmation first
+    if let Some(warning) = IndexChangeWarning::check(world) {
+        world.insert_resource(warning);
+        return Ok(());
+    }
+    write_kmp(world)
+}
+
+impl IndexChangeWarning {
+    pub fn save_anyway(world: &mut World) {
+        world.remove_resource::<IndexChangeWarning>();
+        let result = write_kmp(world);
+        ErrorMessages::report(world, result);
+    }
+}
+
+fn write_kmp(world: &mut World) -> anyhow::Result<()> {
+    if world.get_resource::<KmpSidecar>().is_some_and(|x| x.read_only) {
+        bail!("the kmp was opened read-only, so can't be saved");
+    }
+    let (kmp, shown_areas) = build_kmp(world);
+    // the file as it was last saved, to see what has changed since
+    let old_kmp = world
+        .resource::<UndoHistory>()
+        .saved()
+        .and_then(|x| KmpFile::read(&mut Cursor::new(x)).ok());
+
+    let kmp_file_path = world.resource::<KmpFilePath>().clone().0;
+    let kmp_data = write_kmp_file(&kmp_file_path, &kmp)?;
+    world.resource_mut::<UndoHistory>().mark_saved(kmp_data);
+    world.insert_resource(OpenedHeaderValues::new(&kmp));
+
+    let mut sidecar = world.get_resource::<KmpSidecar>().cloned().unwrap_or_default();
+    sidecar.shown_areas = shown_areas;
+    sidecar.points = collect_point_metadata(world);
+    let by = world.resource::<ThisEditor>().who();
+    record_save(&mut sidecar.history, by, old_kmp.as_ref(), &kmp);
+    sidecar.save(&kmp_file_path)?;
+    world.insert_resource(sidecar);
+
+    SavedGhosts::snapshot(world);
+    set_original_indices(world);
+
+    Ok(())
+}
+
+/// Write a KMP to a path, re-packing it into the archive there if the path is an SZS, returning the written KMP
+pub fn write_kmp_file(path: &Path, kmp: &KmpFile) -> anyhow::Result<Vec<u8>> {
+    let mut kmp_data = Cursor::new(Vec::new());
+    kmp.clone().write(&mut kmp_data).context("could not write kmp file")?;
+    let kmp_data = kmp_data.into_inner();
+    if path.extension() == Some(OsStr::new("szs")) {
+        // re-pack the KMP into the archive it was opened from, leaving everything else in it as it was
+        let mut archive = read_szs(path)?;
+        *archive.file_mut(COURSE_KMP).context("szs file has no course.kmp")? = kmp_data.clone();
+        write_szs(path, &archive)?;
+    } else {
+        fs::write(path, &kmp_data).context("could not write kmp file")?;
+    }
+    Ok(kmp_data)
+}
+
+/// Convert all the KMP entities in the world into a KMP file, along with the indexes of the areas which are always shown
+pub fn build_kmp(world: &mut World) -> (KmpFile, Vec<u32>) {
     let mut kmp = KmpFile::default();
     let (mut poti, route_id_map) = save_point_section::<RouteSettings>(world);
     // additional value of poti section header must be set to the total number of points in all routes
@@ -237,8 +633,14 @@ pub fn save_kmp(world: &mut World) -> anyhow::Result<()> {
     kmp.ckph = ckph;
     let (gobj, _) = save_point_section::<Object>(world);
     kmp.gobj = gobj;
-    let (area, _) = save_point_section::<AreaPoint>(world);
+    let (area, area_id_map) = save_point_section::<AreaPoint>(world);
     kmp.area = area;
+    let shown_areas = world
+        .query::<(Entity, &AreaPoint)>()
+        .iter(world)
+        .filter(|(_, area)| area.show_area)
+        .filter_map(|(e, _)| area_id_map.get(&e).map(|id| *id as u32))
+        .collect();
     let (mut came, camera_id_map) = save_point_section::<KmpCamera>(world);
     // additional value of came section is the intro cam start
     let intro_start_e = world
@@ -262,19 +664,10 @@ pub fn save_kmp(world: &mut World) -> anyhow::Result<()> {
         world,
         Entity::PLACEHOLDER,
     )]);
+    // keep whatever was in the other section headers' additional values, as some tools store data there
+    restore_header_values(world, &mut kmp);
 
-    let kmp_file_path = world.resource::<KmpFilePath>().clone().0;
-    let mut kmp_file = File::create(kmp_file_path)?;
-
-    kmp.write(&mut kmp_file).context("could not write kmp file")?;
-
-    Ok(())
-}
-
-fn handle_save_kmp_errors(In(result): In<anyhow::Result<()>>) {
-    if let Err(err) = result {
-        dbg!(err);
-    }
+    (kmp, shown_areas)
 }
 
 #[derive(Event, Deref, new)]
@@ -0,0 +1,202 @@
+use super::{
+    components::{AreaKind, AreaPoint, KmpSelectablePoint},
+    ordering::OrderId,
+    path::{EntityPathGroups, KmpPathNode},
+    routes::{route_color, route_positions, RouteLink},
+    RoutePoint,
+};
+use crate::util::kcl_file::{kcl_variant, Kcl, KclFlag};
+use bevy::{color::palettes::css, prelude::*, utils::HashMap};
+
+pub fn moving_road_plugin(app: &mut App) {
+    app.add_event::<CheckMovingRoad>().add_systems(
+        Update,
+        (
+            check_moving_road.run_if(on_event::<CheckMovingRoad>()),
+            draw_moving_road_preview.run_if(resource_exists::<MovingRoadCheck>),
+        )
+            .chain(),
+    );
+}
+
+/// Send this event to check that a moving road area, the route it follows, and the Moving Road KCL inside it all
+/// agree with each other
+#[derive(Event)]
+pub struct CheckMovingRoad(pub Entity);
+
+// how long the preview takes to carry the road along its whole route
+const PREVIEW_DURATION: f32 = 4.;
+// outlining every triangle of a big moving road would be slow, so only this many are drawn
+const MAX_PREVIEW_TRIANGLES: usize = 2000;
+
+/// The result of the last moving road check. Remove this resource to dismiss it.
+#[derive(Resource)]
+pub struct MovingRoadCheck {
+    pub area: Entity,
+    /// The index of the route the area follows, if it is linked to one
+    pub route: Option<usize>,
+    /// Each variant of Moving Road KCL inside the area, with how many triangles use it
+    pub variants: Vec<(u16, usize)>,
+    pub warnings: Vec<String>,
+    /// Whether to animate the Moving Road KCL inside the area along the route
+    pub preview: bool,
+    triangles: Vec<[Vec3; 3]>,
+    route_positions: Vec<Vec3>,
+}
+
+// the Moving Road KCL triangles with their centre inside an area, along with the variant of each
+fn moving_road_triangles<'a>(
+    kcl: &'a Kcl,
+    area: &'a AreaPoint,
+    transform: &'a Transform,
+) -> impl Iterator<Item = ([Vec3; 3], u16)> + 'a {
+    let group = &kcl.vertex_groups[KclFlag::MovingRoad as usize];
+    group
+        .vertices
+        .chunks_exact(3)
+        .zip(group.flags.iter())
+        .map(|(v, flag)| ([v[0], v[1], v[2]], kcl_variant(*flag)))
+        .filter(move |(v, _)| area.contains(transform, (v[0] + v[1] + v[2]) / 3.))
+}
+
+fn route_name(route: Option<usize>) -> String {
+    match route {
+        Some(i) => format!("Route {i}"),
+        None => "no route".into(),
+    }
+}
+
+fn check_moving_road(
+    mut commands: Commands,
+    mut ev_check: EventReader<CheckMovingRoad>,
+    q_areas: Query<(Entity, &Transform, &AreaPoint, Option<&RouteLink>, Option<&OrderId>), With<KmpSelectablePoint>>,
+    q_route_pt: Query<(&Transform, &KmpPathNode), With<RoutePoint>>,
+    route_groups: Option<Res<EntityPathGroups<RoutePoint>>>,
+    kcl: Option<Res<Kcl>>,
+    prev_check: Option<Res<MovingRoadCheck>>,
+) {
+    let Some(area_e) = ev_check.read().last().map(|x| x.0) else {
+        return;
+    };
+    let Ok((_, transform, area, route_link, _)) = q_areas.get(area_e) else {
+        return;
+    };
+    let route_index = |route_link: Option<&RouteLink>| {
+        let start_e = **route_link?;
+        route_groups
+            .as_ref()?
+            .iter()
+            .position(|x| x.path.first() == Some(&start_e))
+    };
+    let mut warnings = Vec::new();
+
+    if !matches!(area.kind, AreaKind::MovingRoad) {
+        warnings.push("The area isn't a Moving Road area, so nothing inside it will be moved".to_string());
+    }
+
+    let route = route_index(route_link);
+    let route_positions = route_link
+        .map(|x| route_positions(**x, &q_route_pt))
+        .unwrap_or_default();
+    if route.is_none() {
+        warnings.push("The area isn't linked to a route, so there is nothing for the road to follow".into());
+    } else if route_positions.len() < 2 {
+        warnings.push("The route needs at least 2 points for the road to move along it".into());
+    }
+
+    let mut variants: HashMap<u16, usize> = HashMap::default();
+    let mut triangles = Vec::new();
+    if let Some(ref kcl) = kcl {
+        for (triangle, variant) in moving_road_triangles(kcl, area, transform) {
+            *variants.entry(variant).or_default() += 1;
+            if triangles.len() < MAX_PREVIEW_TRIANGLES {
+                triangles.push(triangle);
+            }
+        }
+    }
+    let mut variants: Vec<_> = variants.into_iter().collect();
+    variants.sort();
+
+    match kcl {
+        None => warnings.push("No KCL is loaded, so the Moving Road KCL inside the area can't be checked".into()),
+        Some(_) if variants.is_empty() => {
+            warnings.push("There is no Moving Road KCL inside the area, so nothing will be moved".into())
+        }
+        Some(_) if variants.len() > 1 => {
+            let list: Vec<_> = variants.iter().map(|x| x.0.to_string()).collect();
+            warnings.push(format!(
+                "The Moving Road KCL inside the area uses more than one variant ({}), but the area only follows one route",
+                list.join(", ")
+            ));
+        }
+        _ => {}
+    }
+
+    // the same variant of Moving Road KCL inside another moving road area which follows a different route
+    if let Some(ref kcl) = kcl {
+        for (other_e, other_transform, other_area, other_route_link, other_id) in q_areas.iter() {
+            if other_e == area_e || !matches!(other_area.kind, AreaKind::MovingRoad) {
+                continue;
+            }
+            let other_route = route_index(other_route_link);
+            if other_route == route {
+                continue;
+            }
+            let mut shared: Vec<_> = moving_road_triangles(kcl, other_area, other_transform)
+                .map(|x| x.1)
+                .filter(|x| variants.iter().any(|(v, _)| v == x))
+                .collect();
+            shared.sort();
+            shared.dedup();
+            for variant in shared {
+                let other_name = match other_id {
+                    Some(id) => format!("Area {}", id.0),
+                    None => "another moving road area".into(),
+                };
+                warnings.push(format!(
+                    "Variant {variant} is also used by the Moving Road KCL inside {other_name}, which follows {} instead of {}",
+                    route_name(other_route),
+                    route_name(route),
+                ));
+            }
+        }
+    }
+
+    commands.insert_resource(MovingRoadCheck {
+        area: area_e,
+        route,
+        variants,
+        warnings,
+        preview: prev_check.map(|x| x.area == area_e && x.preview).unwrap_or(true),
+        triangles,
+        route_positions,
+    });
+}
+
+// move an outline of the Moving Road KCL along the route, looping, to show which way things on it will be carried
+fn draw_moving_road_preview(mut gizmos: Gizmos, check: Res<MovingRoadCheck>, time: Res<Time>) {
+    if !check.preview || check.route_positions.len() < 2 {
+        return;
+    }
+    let segment_lengths: Vec<_> = check.route_positions.windows(2).map(|x| x[0].distance(x[1])).collect();
+    let total_length: f32 = segment_lengths.iter().sum();
+    if total_length <= 0. {
+        return;
+    }
+    let mut distance = (time.elapsed_seconds() % PREVIEW_DURATION) / PREVIEW_DURATION * total_length;
+    let mut pos = *check.route_positions.last().unwrap();
+    for (i, length) in segment_lengths.iter().enumerate() {
+        if distance <= *length {
+            pos = check.route_positions[i].lerp(check.route_positions[i + 1], distance / length);
+            break;
+        }
+        distance -= length;
+    }
+    let offset = pos - check.route_positions[0];
+    let color = check.route.map(route_color).unwrap_or(css::WHITE.into());
+
+    for [a, b, c] in check.triangles.iter() {
+        gizmos.linestrip([*a, *b, *c, *a].map(|x| x + offset), color);
+    }
+    gizmos.sphere(pos, Quat::IDENTITY, 150., color);
+}
@@ -0,0 +1,93 @@
+use super::components::{KmpSelectablePoint, Object};
+use bevy::{prelude::*, utils::HashMap};
+
+pub fn object_density_plugin(app: &mut App) {
+    app.add_event::<AuditObjectDensity>()
+        .add_systems(Update, audit_object_density.run_if(on_event::<AuditObjectDensity>()));
+}
+
+/// Send this event to count how many objects are within draw distance of each other, to find areas likely to
+/// cause slowdown in game
+#[derive(Event, Default)]
+pub struct AuditObjectDensity;
+
+/// Roughly how far away the game draws and processes objects
+pub const DENSITY_RADIUS: f32 = 10000.;
+/// More objects than this within [`DENSITY_RADIUS`] of each other is likely to cause slowdown. This is only a rough
+/// guide, as some objects are much more expensive than others.
+pub const HOTSPOT_OBJECT_LIMIT: usize = 50;
+// how many of the densest areas to list
+const MAX_HOTSPOTS: usize = 5;
+
+/// The result of the last object density audit. Remove this resource to dismiss it.
+#[derive(Resource)]
+pub struct ObjectDensityAudit {
+    pub total: usize,
+    /// Number of objects of each ID in the whole track, most common first
+    pub counts_by_id: Vec<(u16, usize)>,
+    /// The densest areas of the track, densest first, none of which overlap each other
+    pub hotspots: Vec<ObjectHotspot>,
+}
+
+pub struct ObjectHotspot {
+    pub center: Vec3,
+    pub objects: Vec<Entity>,
+    pub counts_by_id: Vec<(u16, usize)>,
+}
+impl ObjectHotspot {
+    pub fn is_over_limit(&self) -> bool {
+        self.objects.len() > HOTSPOT_OBJECT_LIMIT
+    }
+}
+
+fn count_by_id<'a>(objects: impl IntoIterator<Item = &'a Object>) -> Vec<(u16, usize)> {
+    let mut counts: HashMap<u16, usize> = HashMap::default();
+    for object in objects {
+        *counts.entry(object.object_id).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+}
+
+fn audit_object_density(
+    mut commands: Commands,
+    q_objects: Query<(Entity, &Transform, &Object), With<KmpSelectablePoint>>,
+) {
+    let objects: Vec<_> = q_objects.iter().map(|(e, t, o)| (e, t.translation, o)).collect();
+
+    // use each object as the centre of a sphere, and count everything in draw distance of it
+    let mut spheres: Vec<(Vec3, Vec<usize>)> = objects
+        .iter()
+        .map(|(_, center, _)| {
+            let inside = (0..objects.len())
+                .filter(|i| objects[*i].1.distance(*center) <= DENSITY_RADIUS)
+                .collect();
+            (*center, inside)
+        })
+        .collect();
+    spheres.sort_by_key(|x| std::cmp::Reverse(x.1.len()));
+
+    // take the densest spheres, skipping any which overlap one we've already taken
+    let mut hotspots: Vec<ObjectHotspot> = Vec::new();
+    for (center, inside) in spheres {
+        if hotspots.len() >= MAX_HOTSPOTS {
+            break;
+        }
+        if hotspots.iter().any(|x| x.center.distance(center) < DENSITY_RADIUS) {
+            continue;
+        }
+        let entities: Vec<_> = inside.iter().map(|i| objects[*i].0).collect();
+        hotspots.push(ObjectHotspot {
+            center,
+            counts_by_id: count_by_id(inside.iter().map(|i| objects[*i].2)),
+            objects: entities,
+        });
+    }
+
+    commands.insert_resource(ObjectDensityAudit {
+        total: objects.len(),
+        counts_by_id: count_by_id(objects.iter().map(|x| x.2)),
+        hotspots,
+    });
+}
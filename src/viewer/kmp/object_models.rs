@@ -0,0 +1,184 @@
+use super::{components::Object, meshes_materials::unlit_material};
+use crate::{
+    ui::{errors::ErrorMessages, settings::AppSettings},
+    util::shapes::{Cone, Cylinder},
+};
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+pub fn object_models_plugin(app: &mut App) {
+    app.add_systems(Startup, setup_object_models)
+        .add_systems(Update, update_object_models.run_if(resource_exists::<ObjectModels>));
+}
+
+// low-poly placeholder models for objects which are common enough to be worth recognising, each made of a few
+// simple shapes. Objects whose ID isn't in the library are just shown as points.
+const MODEL_LIBRARY: &str = include_str!("../../../assets/objects/models.json");
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    name: String,
+    ids: Vec<u16>,
+    parts: Vec<ModelPartEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelPartEntry {
+    shape: PartShape,
+    /// The size of the shape's bounding box in world units, at an object scale of 1
+    size: Vec3,
+    /// Where the centre of the shape is relative to the object's position
+    #[serde(default)]
+    offset: Vec3,
+    color: [u8; 3],
+    #[serde(default = "opaque")]
+    alpha: f32,
+}
+fn opaque() -> f32 {
+    1.
+}
+
+#[derive(Deserialize, Clone, Copy)]
+enum PartShape {
+    Cube,
+    Cylinder,
+    Cone,
+    Sphere,
+}
+
+/// The placeholder models for objects, keyed by object ID
+#[derive(Resource)]
+pub struct ObjectModels(HashMap<u16, ObjectModel>);
+impl ObjectModels {
+    pub fn get(&self, object_id: u16) -> Option<&ObjectModel> {
+        self.0.get(&object_id)
+    }
+}
+
+#[derive(Clone)]
+pub struct ObjectModel {
+    pub name: String,
+    parts: Vec<ObjectModelPart>,
+}
+
+#[derive(Clone)]
+struct ObjectModelPart {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    size: Vec3,
+    offset: Vec3,
+}
+impl ObjectModelPart {
+    fn transform(&self, object_scale: Vec3) -> Transform {
+        Transform::from_translation(self.offset * object_scale).with_scale(self.size * object_scale)
+    }
+}
+
+/// Marks the shapes making up an object's model, which are children of the object
+#[derive(Component)]
+pub struct ObjectModelShape;
+
+fn setup_object_models(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut errors: ResMut<ErrorMessages>,
+) {
+    let entries: Vec<ModelEntry> = match serde_json::from_str(MODEL_LIBRARY) {
+        Ok(entries) => entries,
+        Err(err) => {
+            errors.push(anyhow::Error::new(err).context("couldn't read the object model library"));
+            return;
+        }
+    };
+    // every shape is 1 unit across, so that it can be scaled to the size of the part
+    let cube = meshes.add(Cuboid::from_size(Vec3::ONE));
+    let cylinder = meshes.add(Mesh::from(Cylinder {
+        height: 1.,
+        radius_bottom: 0.5,
+        radius_top: 0.5,
+        radial_segments: 12,
+        height_segments: 1,
+    }));
+    let cone = meshes.add(Mesh::from(Cone {
+        height: 1.,
+        radius: 0.5,
+        segments: 12,
+    }));
+    let sphere = meshes.add(Sphere::new(0.5).mesh().uv(12, 8));
+
+    let mut models = HashMap::new();
+    for entry in entries {
+        let parts: Vec<_> = entry
+            .parts
+            .iter()
+            .map(|part| {
+                let [r, g, b] = part.color;
+                ObjectModelPart {
+                    mesh: match part.shape {
+                        PartShape::Cube => cube.clone(),
+                        PartShape::Cylinder => cylinder.clone(),
+                        PartShape::Cone => cone.clone(),
+                        PartShape::Sphere => sphere.clone(),
+                    },
+                    material: unlit_material(&mut materials, Color::srgb_u8(r, g, b).with_alpha(part.alpha)),
+                    size: part.size,
+                    offset: part.offset,
+                }
+            })
+            .collect();
+        let model = ObjectModel {
+            name: entry.name,
+            parts,
+        };
+        for id in entry.ids.iter() {
+            models.insert(*id, model.clone());
+        }
+    }
+    commands.insert_resource(ObjectModels(models));
+}
+
+// give each object the model for its ID, rebuilding it whenever the ID or scale changes
+fn update_object_models(
+    mut commands: Commands,
+    q_objects: Query<(Entity, Ref<Object>, Option<&Children>)>,
+    q_shapes: Query<(), With<ObjectModelShape>>,
+    models: Res<ObjectModels>,
+    settings: Res<AppSettings>,
+) {
+    let show = settings.kmp_model.show_object_models;
+    for (e, object, children) in q_objects.iter() {
+        if !object.is_changed() && !settings.is_changed() {
+            continue;
+        }
+        for child in children
+            .iter()
+            .flat_map(|x| x.iter())
+            .filter(|x| q_shapes.contains(**x))
+        {
+            commands.entity(*child).despawn_recursive();
+        }
+        let Some(model) = models.get(object.object_id).filter(|_| show) else {
+            continue;
+        };
+        // objects which haven't had a scale set yet are shown at their normal size
+        let scale = if object.scale == Vec3::ZERO {
+            Vec3::ONE
+        } else {
+            object.scale
+        };
+        commands.entity(e).with_children(|parent| {
+            for part in model.parts.iter() {
+                parent.spawn((
+                    PbrBundle {
+                        mesh: part.mesh.clone(),
+                        material: part.material.clone(),
+                        transform: part.transform(scale),
+                        ..default()
+                    },
+                    ObjectModelShape,
+                ));
+            }
+        });
+    }
+}
@@ -1,6 +1,7 @@
 use super::{
-    sections::add_for_all_components, AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint,
-    ItemPathPoint, KmpCamera, Object, RespawnPoint, RoutePoint, StartPoint,
+    sections::{add_for_all_components, KmpEditMode},
+    AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object,
+    RespawnPoint, RoutePoint, StartPoint,
 };
 use bevy::prelude::*;
 use std::{
@@ -54,3 +55,82 @@ pub fn refresh_order<T: Component>(mut q: Query<&mut OrderId, With<T>>, next_id:
     }
     next_id.set(id);
 }
+
+/// Move a point to where another is in its section's order, shifting the points in between along by one
+pub fn move_in_order<T: Component>(world: &mut World, moved: Entity, target: Entity) {
+    let mut q = world.query_filtered::<(Entity, &OrderId), With<T>>();
+    let mut order: Vec<_> = q.iter(world).map(|(e, order_id)| (e, order_id.0)).collect();
+    order.sort_by_key(|x| x.1);
+    let mut order: Vec<_> = order.into_iter().map(|x| x.0).collect();
+    let (Some(from), Some(to)) = (
+        order.iter().position(|e| *e == moved),
+        order.iter().position(|e| *e == target),
+    ) else {
+        return;
+    };
+    order.remove(from);
+    order.insert(to, moved);
+    for (i, e) in order.into_iter().enumerate() {
+        if let Some(mut order_id) = world.get_mut::<OrderId>(e) {
+            order_id.0 = i as u32;
+        }
+    }
+    world.send_event(RefreshOrdering);
+}
+
+/// Index of the entity in the KMP file as it was when last opened or saved, which unlike [`OrderId`] doesn't change
+/// when points are added, removed or reordered. New entities don't have one until the file is saved.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct OriginalIndex(pub u32);
+
+/// Set the original index of every entity to its current index, after the file has been opened or saved
+pub fn set_original_indices(world: &mut World) {
+    let order_ids: Vec<_> = world
+        .query::<(Entity, &OrderId)>()
+        .iter(world)
+        .map(|(e, order_id)| (e, OriginalIndex(order_id.0)))
+        .collect();
+    for (e, original_index) in order_ids {
+        world.entity_mut(e).insert(original_index);
+    }
+}
+
+pub struct IndexChange {
+    pub section: KmpEditMode,
+    pub old: u32,
+    pub new: u32,
+}
+
+/// Entities that will be written to a different index than the one they had in the file, shown before saving
+/// so that it's clear if anything referencing fixed indices (like external patches) will need updating
+#[derive(Resource)]
+pub struct IndexChangeWarning(pub Vec<IndexChange>);
+impl IndexChangeWarning {
+    pub fn check(world: &mut World) -> Option<Self> {
+        let mut changes = Vec::new();
+        index_changes::<StartPoint>(world, &mut changes);
+        index_changes::<EnemyPathPoint>(world, &mut changes);
+        index_changes::<ItemPathPoint>(world, &mut changes);
+        index_changes::<Checkpoint>(world, &mut changes);
+        index_changes::<RespawnPoint>(world, &mut changes);
+        index_changes::<Object>(world, &mut changes);
+        index_changes::<AreaPoint>(world, &mut changes);
+        index_changes::<KmpCamera>(world, &mut changes);
+        index_changes::<CannonPoint>(world, &mut changes);
+        index_changes::<BattleFinishPoint>(world, &mut changes);
+        (!changes.is_empty()).then_some(Self(changes))
+    }
+}
+
+fn index_changes<T: Component>(world: &mut World, changes: &mut Vec<IndexChange>) {
+    let mut q = world.query_filtered::<(&OrderId, &OriginalIndex), With<T>>();
+    for (order_id, original) in q.iter(world).sort::<&OrderId>() {
+        if order_id.0 != original.0 {
+            changes.push(IndexChange {
+                section: KmpEditMode::from_type::<T>(),
+                old: original.0,
+                new: order_id.0,
+            });
+        }
+    }
+}
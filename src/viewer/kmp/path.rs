@@ -1,8 +1,9 @@
 use super::{
     checkpoints::CheckpointRight,
-    meshes_materials::{CheckpointMaterials, KmpMeshes, PathMaterials},
+    components::{BattleDispatchPoint, TrackInfo, TrackType},
+    meshes_materials::{CheckpointMaterials, KmpMeshes, PathMaterials, SectionMaterials},
     ordering::{NextOrderID, OrderId},
-    Checkpoint, EnemyPathPoint, ItemPathPoint, KmpComponent, KmpSectionName, KmpSelectablePoint, PathGroup,
+    Checkpoint, EnemyPathPoint, ItemPathPoint, KmpComponent, KmpErrors, KmpSectionName, KmpSelectablePoint, PathGroup,
     PathOverallStart, RoutePoint, Section, Spawn, Spawner, TransformEditOptions,
 };
 use crate::{
@@ -269,18 +270,15 @@ pub struct KmpDataGroup<T> {
     pub next_groups: Vec<u8>,
 }
 
-// pub fn is_enemy_point<T: 'static>() -> bool {
-//     TypeId::of::<T>() == TypeId::of::<EnemyPathPoint>()
-// }
+pub fn is_enemy_point<T: 'static>() -> bool {
+    TypeId::of::<T>() == TypeId::of::<EnemyPathPoint>()
+}
 // pub fn is_item_point<T: 'static>() -> bool {
 //     TypeId::of::<T>() == TypeId::of::<ItemPathPoint>()
 // }
 pub fn is_checkpoint<T: 'static>() -> bool {
     TypeId::of::<T>() == TypeId::of::<Checkpoint>()
 }
-pub fn is_checkpoint_right<T: 'static>() -> bool {
-    TypeId::of::<T>() == TypeId::of::<CheckpointRight>()
-}
 pub fn is_route_pt<T: 'static>() -> bool {
     TypeId::of::<T>() == TypeId::of::<RoutePoint>()
 }
@@ -294,10 +292,12 @@ where
     PathGroup<T::KmpFormat>: KmpSectionName,
 {
     let kmp_groups = get_kmp_data_and_component_groups::<T>(kmp, world);
+    // battle arenas store each dispatch point as a group on its own
+    let mark_dispatch_points = is_enemy_point::<T>() && is_battle_track(world);
 
     let mut entity_groups: Vec<EntityGroup> = Vec::with_capacity(kmp_groups.len());
     let mut acc = 0;
-    for (i, (data_group, component_group)) in kmp_groups.iter().enumerate() {
+    for (data_group, component_group) in kmp_groups.iter() {
         let mut entity_group = EntityGroup {
             entities: Vec::with_capacity(data_group.nodes.len()),
             next_groups: data_group.next_groups.clone(),
@@ -313,9 +313,13 @@ where
                 .build()
                 .spawn(world);
 
-            if i == 0 && j == 0 {
+            // the first group may be empty, so the overall start is the first point that actually exists
+            if acc == 0 {
                 world.entity_mut(spawned_entity).insert(PathOverallStart);
             }
+            if mark_dispatch_points && data_group.nodes.len() == 1 {
+                world.entity_mut(spawned_entity).insert(BattleDispatchPoint);
+            }
             entity_group.entities.push(spawned_entity);
             acc += 1;
         }
@@ -324,9 +328,18 @@ where
     link_entity_groups(world, entity_groups);
 }
 
-pub fn spawn_path<T: Spawn + Component + Clone>(spawner: Spawner<T>, world: &mut World) -> Entity {
-    let mesh = world.resource::<KmpMeshes>().sphere.clone();
-    let material = world.resource::<PathMaterials<T>>().point.clone();
+pub fn is_battle_track(world: &World) -> bool {
+    world
+        .get_resource::<TrackInfo>()
+        .is_some_and(|x| x.track_type == TrackType::Battle)
+}
+
+pub fn spawn_path<T: Spawn + Component + Clone>(spawner: Spawner<T>, world: &mut World) -> Entity
+where
+    PathMaterials<T>: SectionMaterials,
+{
+    let mesh = KmpMeshes::get(world).sphere;
+    let material = PathMaterials::<T>::get(world).point;
     let outline = world.get_resource::<AppSettings>().unwrap().kmp_model.outline;
 
     // either gets the order id, or gets it from the NextOrderID (which will increment it for next time)
@@ -384,7 +397,7 @@ where
 
     let mut result: Vec<(KmpDataGroup<T::KmpFormat>, Vec<T>)> = Vec::with_capacity(pathgroup_entries.len());
 
-    for group in pathgroup_entries.iter() {
+    for (group_i, group) in pathgroup_entries.iter().enumerate() {
         let mut next_groups = Vec::new();
         let mut kmp_component_group = Vec::new();
         let mut nodes = Vec::with_capacity(group.group_length.into());
@@ -395,8 +408,16 @@ where
             }
         }
 
-        for i in group.start..(group.start + group.group_length) {
-            let node = &node_entries[i as usize];
+        let start = group.start as usize;
+        for i in start..(start + group.group_length as usize) {
+            // some special KMPs (e.g. for mission mode) have groups which don't match up with their points
+            let Some(node) = node_entries.get(i) else {
+                let name = String::from_utf8_lossy(&PathGroup::<T::KmpFormat>::SECTION_NAME).into_owned();
+                world.resource_mut::<KmpErrors>().add(format!(
+                    "{name} group {group_i} refers to points past the end of the section, so they were skipped"
+                ));
+                break;
+            };
             nodes.push(node.clone());
             let kmp_component = T::from_kmp(node, world);
             kmp_component_group.push(kmp_component);
@@ -421,8 +442,13 @@ pub fn link_entity_groups(world: &mut World, entity_groups: Vec<EntityGroup>) {
         let Some(entity) = prev_entity else { continue };
         // for each next group linked to the current group
         for next_group_index in group.next_groups.iter() {
-            // get the first entity in the next group
-            let next_entity = entity_groups[*next_group_index as usize].entities[0];
+            // get the first entity in the next group, if the group exists and has any points
+            let Some(next_entity) = entity_groups
+                .get(*next_group_index as usize)
+                .and_then(|x| x.entities.first().copied())
+            else {
+                continue;
+            };
             // link the last entity in the current group with the first entity in the next group
             KmpPathNode::link_nodes(entity, next_entity, world);
         }
@@ -435,26 +461,15 @@ fn spawn_node_link<T: Component + Clone + ToPathType>(
     next_node: Entity,
     visible: bool,
 ) -> Entity {
-    let meshes = world.resource::<KmpMeshes>().clone();
-    let (line, arrow) = if is_checkpoint::<T>() || is_checkpoint_right::<T>() {
-        let materials = world.resource::<CheckpointMaterials>().clone();
-        (materials.line, materials.arrow)
-    } else {
-        let materials = world.resource::<PathMaterials<T>>().clone();
-        (materials.line, materials.arrow)
-    };
-
     let prev_pos = world.get::<Transform>(prev_node).unwrap().translation;
     let next_pos = world.get::<Transform>(next_node).unwrap().translation;
 
     let mut parent_transform = Transform::from_translation(prev_pos.lerp(next_pos, 0.5)).looking_at(next_pos, Vec3::Y);
     parent_transform.rotate_local_x(f32::to_radians(-90.));
 
-    let mut line_transform = Transform::default();
-    line_transform.scale.y = prev_pos.distance(next_pos);
-
-    // spawn a parent component which contains a transform, and stores the entities of the nodes the node links
-    let e = world
+    // spawn a parent component which contains a transform, and stores the entities of the nodes the node links. The
+    // line and arrow meshes are only spawned as its children when they are shown
+    world
         .spawn((
             SpatialBundle {
                 transform: parent_transform,
@@ -471,31 +486,84 @@ fn spawn_node_link<T: Component + Clone + ToPathType>(
                 kind: T::to_path_type(),
             },
         ))
-        // spawn the line and arrow as children of this parent component, which will inherit its transform & visibility
-        .with_children(|parent| {
-            parent.spawn((
-                PbrBundle {
-                    mesh: meshes.cylinder,
-                    material: line,
-                    transform: line_transform,
-                    ..default()
-                },
-                // KmpSection,
-                Normalize::new(200., 30., BVec3::new(true, false, true)),
-                KmpPathNodeLinkLine,
-            ));
-            parent.spawn((
-                PbrBundle {
-                    mesh: meshes.frustrum,
-                    material: arrow,
-                    ..default()
-                },
-                // KmpSection,
-                Normalize::new(200., 30., BVec3::TRUE),
-            ));
-        })
-        .id();
-    e
+        .id()
+}
+
+/// Spawn the line and arrow meshes of a link as its children, which inherit its transform & visibility
+pub fn spawn_link_meshes(world: &mut World, link_e: Entity) {
+    let Some(entity) = world.get_entity(link_e) else {
+        return;
+    };
+    if entity.contains::<Children>() {
+        return;
+    }
+    let Some(&KmpPathNodeLink {
+        prev_node,
+        next_node,
+        kind,
+    }) = entity.get::<KmpPathNodeLink>()
+    else {
+        return;
+    };
+    let Some(prev_pos) = world.get::<Transform>(prev_node).map(|x| x.translation) else {
+        return;
+    };
+    let Some(next_pos) = world.get::<Transform>(next_node).map(|x| x.translation) else {
+        return;
+    };
+
+    let meshes = KmpMeshes::get(world);
+    let (line, arrow) = match kind {
+        PathType::Enemy => {
+            let materials = PathMaterials::<EnemyPathPoint>::get(world);
+            (materials.line, materials.arrow)
+        }
+        PathType::Item => {
+            let materials = PathMaterials::<ItemPathPoint>::get(world);
+            (materials.line, materials.arrow)
+        }
+        PathType::Checkpoint { .. } => {
+            let materials = CheckpointMaterials::get(world);
+            (materials.line, materials.arrow)
+        }
+        PathType::Route => {
+            let materials = PathMaterials::<RoutePoint>::get(world);
+            (materials.line, materials.arrow)
+        }
+    };
+
+    let mut line_transform = Transform::default();
+    line_transform.scale.y = prev_pos.distance(next_pos);
+
+    world.entity_mut(link_e).with_children(|parent| {
+        parent.spawn((
+            PbrBundle {
+                mesh: meshes.cylinder,
+                material: line,
+                transform: line_transform,
+                ..default()
+            },
+            // KmpSection,
+            Normalize::new(200., 30., BVec3::new(true, false, true)),
+            KmpPathNodeLinkLine,
+        ));
+        parent.spawn((
+            PbrBundle {
+                mesh: meshes.frustrum,
+                material: arrow,
+                ..default()
+            },
+            // KmpSection,
+            Normalize::new(200., 30., BVec3::TRUE),
+        ));
+    });
+}
+
+/// Despawn the line and arrow meshes of a link, when it is drawn as a GPU line instead
+pub fn despawn_link_meshes(world: &mut World, link_e: Entity) {
+    if let Some(mut entity) = world.get_entity_mut(link_e) {
+        entity.despawn_descendants();
+    }
 }
 
 // TODO: make this more efficient by attaching link lines to the kmp points themselves
@@ -503,7 +571,7 @@ pub fn update_node_links<T: Component + Clone + ToPathType>(
     // mode: Option<Res<KmpEditMode<T>>>,
     // cp_mode: Option<Res<KmpEditMode<Checkpoint>>>,
     q_visibility: Query<&Visibility, Without<KmpPathNodeLink>>,
-    mut q_kmp_node_link: Query<(Entity, &KmpPathNodeLink, &Children, &mut Visibility)>,
+    mut q_kmp_node_link: Query<(Entity, &KmpPathNodeLink, Option<&Children>, &mut Visibility)>,
     q_kmp_node: Query<(Entity, &KmpPathNode), With<T>>,
     mut q_transform: Query<&mut Transform>,
     q_line: Query<&KmpPathNodeLinkLine>,
@@ -575,7 +643,7 @@ pub fn update_node_links<T: Component + Clone + ToPathType>(
         *parent_transform = new_parent_transform;
 
         // find the child of the kmp node link that has KmpNodeLinkLine, and set its transform
-        if let Some(child) = children.iter().find(|x| q_line.get(**x).is_ok()) {
+        if let Some(child) = children.and_then(|x| x.iter().find(|x| q_line.get(**x).is_ok())) {
             let mut line_transform = q_transform.get_mut(*child).unwrap();
             *line_transform = new_line_transform;
         }
@@ -661,15 +729,19 @@ pub fn traverse_paths(
 pub struct TraversePath<'w, 's, T: Component> {
     q_start: Query<'w, 's, Entity, (With<PathOverallStart>, With<T>, With<KmpPathNode>)>,
     q: Query<'w, 's, (Entity, &'static KmpPathNode), With<T>>,
+    q_dispatch: Query<'w, 's, (), (With<BattleDispatchPoint>, With<T>)>,
+    track_info: Option<Res<'w, TrackInfo>>,
 }
 impl<'w, 's, T: Component> TraversePath<'w, 's, T> {
     fn traverse(self) -> EntityPathGroups<T> {
         let mut paths: Vec<EntityPathGroup> = Vec::new();
         let mut node_to_path_index: HashMap<Entity, usize> = HashMap::default();
-        let battle_mode = false;
+        let battle_mode = self
+            .track_info
+            .as_ref()
+            .is_some_and(|x| x.track_type == TrackType::Battle);
 
-        let is_battle_dispatcher =
-            |node: &KmpPathNode| battle_mode && (node.next_nodes.len() + node.next_nodes.len() > 2);
+        let is_battle_dispatcher = |e: Entity| battle_mode && self.q_dispatch.contains(e);
 
         let mut nodes_to_handle: EntityHashMap<&KmpPathNode> = self.q.iter().collect();
         if nodes_to_handle.is_empty() {
@@ -692,7 +764,7 @@ impl<'w, 's, T: Component> TraversePath<'w, 's, T> {
             let mut path: Vec<Entity> = Vec::new();
             let path_index = paths.len();
 
-            if is_battle_dispatcher(node) {
+            if is_battle_dispatcher(node_e) {
                 path.push(node_e);
                 paths.push(EntityPathGroup { path, ..default() });
                 nodes_to_handle.remove(&node_e);
@@ -709,7 +781,7 @@ impl<'w, 's, T: Component> TraversePath<'w, 's, T> {
                     .then(|| self.q.get(*start_node.prev_nodes.iter().next().unwrap()).ok())
                     .flatten()
                 {
-                    if prev_node.next_nodes.len() != 1 || is_battle_dispatcher(prev_node) {
+                    if prev_node.next_nodes.len() != 1 || is_battle_dispatcher(prev_node_e) {
                         break;
                     }
                     if node_to_path_index.contains_key(&prev_node_e) {
@@ -734,7 +806,7 @@ impl<'w, 's, T: Component> TraversePath<'w, 's, T> {
                 .flatten()
                 .filter(|x| nodes_to_handle.contains_key(&x.0))
             {
-                if next_node.prev_nodes.len() != 1 || is_battle_dispatcher(next_node) {
+                if next_node.prev_nodes.len() != 1 || is_battle_dispatcher(next_node_e) {
                     break;
                 }
                 (path_node_e, path_node) = (next_node_e, next_node);
@@ -780,14 +852,12 @@ where
     PathGroup<T::KmpFormat>: KmpSectionName,
 {
     let mut ss = SystemState::<TraversePath<T>>::new(world);
-    let traverse_path = ss.get_mut(world);
-    traverse_path.traverse();
-    ss.apply(world);
+    let entity_paths = ss.get_mut(world).traverse();
+    world.insert_resource(entity_paths.clone());
 
     let mut points = Vec::new();
     let mut paths = Vec::new();
 
-    let entity_paths = world.resource::<EntityPathGroups<T>>().clone();
     for entity_path in entity_paths.iter() {
         let start = points.len() as u8;
         let group_length = entity_path.path.len() as u8;
@@ -0,0 +1,90 @@
+use super::{
+    checkpoints::CheckpointRight,
+    components::{Checkpoint, EnemyPathPoint, ItemPathPoint, KmpSelectablePoint, PathOverallStart},
+    path::RecalcPaths,
+};
+use crate::ui::settings::AppSettings;
+use bevy::prelude::*;
+
+pub fn path_start_plugin(app: &mut App) {
+    app.add_systems(Update, draw_path_start_markers);
+}
+
+// size of the flag, before being scaled by the point scale
+const POLE_HEIGHT: f32 = 500.;
+const FLAG_SIZE: Vec2 = Vec2::new(250., 150.);
+
+// draw a flag on the point each enemy, item and checkpoint path starts from, as it decides the order the whole
+// section is saved in
+fn draw_path_start_markers(
+    mut gizmos: Gizmos,
+    q_path_start: Query<
+        (&Transform, &Visibility, Has<EnemyPathPoint>, Has<ItemPathPoint>),
+        (With<PathOverallStart>, With<KmpSelectablePoint>),
+    >,
+    settings: Res<AppSettings>,
+) {
+    let colors = &settings.kmp_model.color;
+    let scale = settings.kmp_model.point_scale;
+    for (transform, visibility, is_enemy, is_item) in q_path_start.iter() {
+        if visibility == Visibility::Hidden {
+            continue;
+        }
+        let color = if is_enemy {
+            colors.enemy_paths.line
+        } else if is_item {
+            colors.item_paths.line
+        } else {
+            colors.checkpoints.line
+        };
+        let bottom = transform.translation;
+        let top = bottom + Vec3::Y * POLE_HEIGHT * scale;
+        let flag_size = FLAG_SIZE * scale;
+        // the flag points the same way as the point
+        let forward = (transform.forward().as_vec3() * Vec3::new(1., 0., 1.)).normalize_or(Vec3::Z);
+        gizmos.line(bottom, top, color);
+        gizmos.linestrip(
+            [
+                top,
+                top - Vec3::Y * flag_size.y / 2. + forward * flag_size.x,
+                top - Vec3::Y * flag_size.y,
+            ],
+            color,
+        );
+    }
+}
+
+/// Make the point its section's paths start from, which decides the order the whole section is saved in. Returns
+/// false if the point isn't an enemy, item or checkpoint path point.
+pub fn set_path_start(world: &mut World, mut e: Entity) -> bool {
+    // the path start of a checkpoint is stored on its left point
+    if let Some(cp_right) = world.get::<CheckpointRight>(e) {
+        e = cp_right.left;
+    }
+    let Some(entity) = world.get_entity(e) else {
+        return false;
+    };
+    let path_pt = (
+        entity.contains::<EnemyPathPoint>(),
+        entity.contains::<ItemPathPoint>(),
+        entity.contains::<Checkpoint>(),
+    );
+    let (ev, is_same_section): (_, fn(bool, bool, bool) -> bool) = match path_pt {
+        (true, _, _) => (RecalcPaths::enemy(), |enemy, _, _| enemy),
+        (_, true, _) => (RecalcPaths::item(), |_, item, _| item),
+        (_, _, true) => (RecalcPaths::cp(), |_, _, cp| cp),
+        _ => return false,
+    };
+    let old: Vec<_> = world
+        .query_filtered::<(Entity, Has<EnemyPathPoint>, Has<ItemPathPoint>, Has<Checkpoint>), With<PathOverallStart>>()
+        .iter(world)
+        .filter(|(_, is_enemy, is_item, is_cp)| is_same_section(*is_enemy, *is_item, *is_cp))
+        .map(|x| x.0)
+        .collect();
+    for start_e in old {
+        world.entity_mut(start_e).remove::<PathOverallStart>();
+    }
+    world.entity_mut(e).insert(PathOverallStart);
+    world.send_event(ev);
+    true
+}
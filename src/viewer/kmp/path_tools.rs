@@ -0,0 +1,403 @@
+use super::{
+    checkpoints::{
+        checkpoint_spawner, get_both_cp_nodes, CheckpointHeight, CheckpointLeft, CheckpointRespawnLink, CheckpointRight,
+    },
+    components::{
+        Checkpoint, CheckpointKind, EnemyPathPoint, ItemPathPoint, MaxConnectedPath, RoutePoint, Spawn, Spawner,
+    },
+    ordering::RefreshOrdering,
+    path::{KmpPathNode, KmpPathNodeLink, RecalcPaths},
+};
+use crate::viewer::edit::{context_menu::select_only, select::Selected};
+use bevy::{prelude::*, utils::HashSet};
+
+pub fn path_tools_plugin(app: &mut App) {
+    app.init_resource::<PathResampleSpacing>()
+        .add_event::<ResamplePath>()
+        .add_event::<InsertPointOnLink>()
+        .add_systems(
+            Update,
+            (
+                resample_path.run_if(on_event::<ResamplePath>()),
+                insert_points_on_links.run_if(on_event::<InsertPointOnLink>()),
+            ),
+        );
+}
+
+// how many straight pieces each spline segment is split into when measuring its length
+const SUBDIVISIONS: usize = 32;
+// a path group can't hold more points than this
+const MAX_POINTS: usize = 255;
+/// The closest together resampled points can be placed
+pub const MIN_SPACING: f32 = 50.;
+
+/// The distance between points that the resample tool in the edit tab aims for
+#[derive(Resource, Deref, DerefMut)]
+pub struct PathResampleSpacing(pub f32);
+impl Default for PathResampleSpacing {
+    fn default() -> Self {
+        Self(1500.)
+    }
+}
+
+/// Send this event to replace the selected chain of enemy or item points with points evenly spaced along a smooth
+/// curve through them. The first and last points stay where they are.
+#[derive(Event, Default)]
+pub struct ResamplePath;
+
+/// Send this event to split a link between two path points with a new point halfway along it, which is then selected
+#[derive(Event)]
+pub struct InsertPointOnLink(pub Entity);
+
+/// Path points whose settings can be blended between two neighbouring points
+pub trait Resample: Component + Spawn + Clone + Default {
+    /// The settings of a point `t` of the way from `self` to `other`. Numbers are blended, and anything else is taken
+    /// from whichever point is nearer.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+impl Resample for EnemyPathPoint {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let nearest = if t < 0.5 { self } else { other };
+        Self {
+            leniency: self.leniency + (other.leniency - self.leniency) * t,
+            ..*nearest
+        }
+    }
+}
+impl Resample for ItemPathPoint {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let nearest = if t < 0.5 { self } else { other };
+        Self {
+            bullet_control: self.bullet_control + (other.bullet_control - self.bullet_control) * t,
+            ..nearest.clone()
+        }
+    }
+}
+impl Resample for RoutePoint {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let nearest = if t < 0.5 { self } else { other };
+        // the settings are usually a speed, but the additional settings are often flags so can't be blended
+        let settings = self.settings as f32 + (other.settings as f32 - self.settings as f32) * t;
+        Self {
+            settings: settings.round() as u16,
+            ..nearest.clone()
+        }
+    }
+}
+
+/// The selected points of this type in the order they are linked, if they make up a single unbranching chain of at
+/// least 2 points
+pub fn selected_chain<T: Component>(world: &mut World) -> Option<Vec<Entity>> {
+    let selected: Vec<_> = world
+        .query_filtered::<(Entity, &KmpPathNode), (With<T>, With<Selected>)>()
+        .iter(world)
+        .map(|(e, node)| (e, node.clone()))
+        .collect();
+    if selected.len() < 2 {
+        return None;
+    }
+    let is_selected = |e: &Entity| selected.iter().any(|x| x.0 == *e);
+    // each point can only lead to one other selected point, or the chain would branch
+    let selected_next = |node: &KmpPathNode| {
+        let mut next = node.next_nodes.iter().filter(|e| is_selected(*e));
+        match (next.next(), next.next()) {
+            (next, None) => Ok(next.copied()),
+            _ => Err(()),
+        }
+    };
+    let mut starts = selected
+        .iter()
+        .filter(|(_, node)| !node.prev_nodes.iter().any(is_selected));
+    let (start, None) = (starts.next()?, starts.next()) else {
+        return None;
+    };
+
+    let mut chain = vec![start.0];
+    let mut node = &start.1;
+    while let Some(next) = selected_next(node).ok()? {
+        chain.push(next);
+        node = &selected.iter().find(|x| x.0 == next)?.1;
+        if chain.len() > selected.len() {
+            return None;
+        }
+    }
+    (chain.len() == selected.len()).then_some(chain)
+}
+
+fn insert_between<T: Resample + MaxConnectedPath>(world: &mut World, prev: Entity, next: Entity) -> Option<Entity> {
+    if !world.get::<KmpPathNode>(prev)?.next_nodes.contains(&next) {
+        return None;
+    }
+    let pos = world.get::<Transform>(prev)?.translation;
+    let pos = pos.lerp(world.get::<Transform>(next)?.translation, 0.5);
+    let component = world.get::<T>(prev)?.interpolate(world.get::<T>(next)?, 0.5);
+    KmpPathNode::unlink_nodes(prev, next, world);
+    let e = Spawner::<T>::builder()
+        .pos(pos)
+        .component(component)
+        .prev_nodes([prev].into_iter().collect())
+        .max(T::MAX_CONNECTED)
+        .build()
+        .spawn(world);
+    KmpPathNode::link_nodes(e, next, world);
+    Some(e)
+}
+
+// both sides of a checkpoint are linked to both sides of the next one, so both links are split together. The new
+// checkpoint is always a normal one, as there can only be one lap count checkpoint and key checkpoints go in order.
+fn insert_checkpoint_between(world: &mut World, prev: Entity, next: Entity) -> Option<Entity> {
+    if !world.get::<KmpPathNode>(prev)?.next_nodes.contains(&next) {
+        return None;
+    }
+    let (prev_right, next_right) = (
+        world.get::<CheckpointLeft>(prev)?.right,
+        world.get::<CheckpointLeft>(next)?.right,
+    );
+    let pos = |world: &World, a: Entity, b: Entity| -> Option<Vec2> {
+        let a = world.get::<Transform>(a)?.translation.xz();
+        Some(a.lerp(world.get::<Transform>(b)?.translation.xz(), 0.5))
+    };
+    let left_pos = pos(world, prev, next)?;
+    let right_pos = pos(world, prev_right, next_right)?;
+    let respawn_link = world.get::<CheckpointRespawnLink>(prev).copied();
+    let height = world.resource::<CheckpointHeight>().0;
+
+    KmpPathNode::unlink_nodes(prev, next, world);
+    KmpPathNode::unlink_nodes(prev_right, next_right, world);
+    let (left, right) = checkpoint_spawner()
+        .cp(Checkpoint {
+            kind: CheckpointKind::Normal,
+        })
+        .pos((left_pos, right_pos))
+        .height(height)
+        .world(world)
+        .call();
+    KmpPathNode::link_nodes(prev, left, world);
+    KmpPathNode::link_nodes(left, next, world);
+    KmpPathNode::link_nodes(prev_right, right, world);
+    KmpPathNode::link_nodes(right, next_right, world);
+    if let Some(respawn_link) = respawn_link {
+        world.entity_mut(left).insert(respawn_link);
+    }
+    Some(left)
+}
+
+fn insert_either_way<T: Resample + MaxConnectedPath>(world: &mut World, a: Entity, b: Entity) -> Option<Entity> {
+    insert_between::<T>(world, a, b).or_else(|| insert_between::<T>(world, b, a))
+}
+
+/// Add a point halfway between two points which link straight on to each other, either way round, linked in between
+/// them with its settings blended from both. This works for enemy, item and route points, and for checkpoints,
+/// where either side of each checkpoint can be given and the new checkpoint goes halfway between both sides. Returns
+/// the new point, or the left of the new checkpoint, if the points were linked.
+pub fn insert_point_between(world: &mut World, a: Entity, b: Entity) -> Option<Entity> {
+    // which of the kinds of path a point is on
+    let kind = |e| {
+        let entity = world.get_entity(e)?;
+        [
+            entity.contains::<EnemyPathPoint>(),
+            entity.contains::<ItemPathPoint>(),
+            entity.contains::<RoutePoint>(),
+            entity.contains::<CheckpointLeft>() || entity.contains::<CheckpointRight>(),
+        ]
+        .iter()
+        .position(|x| *x)
+    };
+    let (kind_a, kind_b) = (kind(a)?, kind(b)?);
+    if kind_a != kind_b {
+        return None;
+    }
+    let e = match kind_a {
+        0 => insert_either_way::<EnemyPathPoint>(world, a, b),
+        1 => insert_either_way::<ItemPathPoint>(world, a, b),
+        2 => insert_either_way::<RoutePoint>(world, a, b),
+        _ => {
+            let (a, b) = (get_both_cp_nodes(world, a).0, get_both_cp_nodes(world, b).0);
+            insert_checkpoint_between(world, a, b).or_else(|| insert_checkpoint_between(world, b, a))
+        }
+    }?;
+    world.send_event(RecalcPaths::all());
+    world.send_event(RefreshOrdering);
+    Some(e)
+}
+
+/// Split a link with a new point halfway along it, as [`insert_point_between`] does for the points it links
+pub fn insert_point_on_link(world: &mut World, link: Entity) -> Option<Entity> {
+    let link = world.get::<KmpPathNodeLink>(link)?;
+    let (prev, next) = (link.prev_node, link.next_node);
+    insert_point_between(world, prev, next)
+}
+
+fn insert_points_on_links(world: &mut World) {
+    let links: Vec<_> = world
+        .resource_mut::<Events<InsertPointOnLink>>()
+        .drain()
+        .map(|x| x.0)
+        .collect();
+    let inserted: Vec<_> = links
+        .into_iter()
+        .filter_map(|link| insert_point_on_link(world, link))
+        .collect();
+    if inserted.is_empty() {
+        return;
+    }
+    select_only(world, &inserted);
+}
+
+// centripetal catmull-rom, which doesn't overshoot or loop when points are unevenly spaced
+fn catmull_rom(p: [Vec3; 4], t: f32) -> Vec3 {
+    let knot = |a: Vec3, b: Vec3| a.distance(b).sqrt().max(1e-3);
+    let t0 = 0.;
+    let t1 = t0 + knot(p[0], p[1]);
+    let t2 = t1 + knot(p[1], p[2]);
+    let t3 = t2 + knot(p[2], p[3]);
+    let u = t1 + (t2 - t1) * t;
+
+    let blend = |a: Vec3, b: Vec3, ta: f32, tb: f32| a * ((tb - u) / (tb - ta)) + b * ((u - ta) / (tb - ta));
+    let a1 = blend(p[0], p[1], t0, t1);
+    let a2 = blend(p[1], p[2], t1, t2);
+    let a3 = blend(p[2], p[3], t2, t3);
+    let b1 = blend(a1, a2, t0, t2);
+    let b2 = blend(a2, a3, t1, t3);
+    blend(b1, b2, t1, t2)
+}
+
+/// A point along the curve, as the segment it is on and how far along that segment it is
+#[derive(Clone, Copy)]
+struct CurvePoint {
+    segment: usize,
+    t: f32,
+    pos: Vec3,
+}
+
+/// Points evenly spaced along a curve through `points`, as close to `spacing` apart as fits exactly between the
+/// first and last. `before` and `after` are the points either side of the chain, which keep the curve flowing
+/// into the rest of the path.
+fn resample_curve(points: &[Vec3], before: Option<Vec3>, after: Option<Vec3>, spacing: f32) -> Vec<CurvePoint> {
+    let n = points.len();
+    // without a neighbour, continue the curve straight on from the end
+    let first = before.unwrap_or(2. * points[0] - points[1]);
+    let last = after.unwrap_or(2. * points[n - 1] - points[n - 2]);
+    let get = |i: isize| match i {
+        -1 => first,
+        i if i as usize == n => last,
+        i => points[i as usize],
+    };
+
+    // walk along the curve in small steps, adding up the distance
+    let mut steps = vec![(
+        0.,
+        CurvePoint {
+            segment: 0,
+            t: 0.,
+            pos: points[0],
+        },
+    )];
+    let mut length = 0.;
+    for segment in 0..n - 1 {
+        let i = segment as isize;
+        let controls = [get(i - 1), get(i), get(i + 1), get(i + 2)];
+        for j in 1..=SUBDIVISIONS {
+            let t = j as f32 / SUBDIVISIONS as f32;
+            let pos = catmull_rom(controls, t);
+            length += steps.last().unwrap().1.pos.distance(pos);
+            steps.push((length, CurvePoint { segment, t, pos }));
+        }
+    }
+
+    let intervals = ((length / spacing.max(MIN_SPACING)).round() as usize).clamp(1, MAX_POINTS - 1);
+    let mut result = Vec::with_capacity(intervals + 1);
+    let mut step = 0;
+    for k in 0..=intervals {
+        let target = length * k as f32 / intervals as f32;
+        while step + 2 < steps.len() && steps[step + 1].0 < target {
+            step += 1;
+        }
+        let (start_len, a) = steps[step];
+        let (end_len, b) = steps[(step + 1).min(steps.len() - 1)];
+        let f = if end_len > start_len {
+            ((target - start_len) / (end_len - start_len)).clamp(0., 1.)
+        } else {
+            0.
+        };
+        // the end of one segment is the start of the next
+        let (segment, a_t) = if b.segment != a.segment {
+            (b.segment, 0.)
+        } else {
+            (a.segment, a.t)
+        };
+        result.push(CurvePoint {
+            segment,
+            t: a_t + (b.t - a_t) * f,
+            pos: a.pos.lerp(b.pos, f),
+        });
+    }
+    // make sure the ends land exactly on the original end points
+    result[0].pos = points[0];
+    let end = result.last_mut().unwrap();
+    *end = CurvePoint {
+        segment: n - 2,
+        t: 1.,
+        pos: points[n - 1],
+    };
+    result
+}
+
+fn resample_chain<T: Resample>(world: &mut World, chain: Vec<Entity>, spacing: f32) -> Vec<Entity> {
+    let points: Vec<_> = chain
+        .iter()
+        .map(|e| world.get::<Transform>(*e).unwrap().translation)
+        .collect();
+    let components: Vec<_> = chain.iter().map(|e| world.get::<T>(*e).unwrap().clone()).collect();
+    // only carry on into the rest of the path if it doesn't branch there
+    let only = |nodes: &HashSet<Entity>| match nodes.len() {
+        1 => nodes.iter().next().copied(),
+        _ => None,
+    };
+    let neighbour_pos = |e: Option<Entity>| e.and_then(|e| world.get::<Transform>(e)).map(|x| x.translation);
+    let first_node = world.get::<KmpPathNode>(chain[0]).unwrap();
+    let last_node = world.get::<KmpPathNode>(*chain.last().unwrap()).unwrap();
+    let before = neighbour_pos(only(&first_node.prev_nodes));
+    let after = neighbour_pos(only(&last_node.next_nodes));
+
+    let curve = resample_curve(&points, before, after, spacing);
+
+    let (first, last) = (chain[0], *chain.last().unwrap());
+    KmpPathNode::unlink_nodes(first, chain[1], world);
+    for e in chain[1..chain.len() - 1].iter() {
+        world.entity_mut(*e).despawn_recursive();
+    }
+
+    let mut result = vec![first];
+    let mut prev = first;
+    for point in curve[1..curve.len() - 1].iter() {
+        let component = components[point.segment].interpolate(&components[point.segment + 1], point.t);
+        prev = Spawner::<T>::builder()
+            .pos(point.pos)
+            .component(component)
+            .prev_nodes([prev].into_iter().collect())
+            .build()
+            .spawn(world);
+        result.push(prev);
+    }
+    KmpPathNode::link_nodes(prev, last, world);
+    result.push(last);
+    result
+}
+
+fn resample_path(world: &mut World) {
+    world.resource_mut::<Events<ResamplePath>>().clear();
+    let spacing = **world.resource::<PathResampleSpacing>();
+
+    let resampled = if let Some(chain) = selected_chain::<EnemyPathPoint>(world) {
+        world.send_event(RecalcPaths::enemy());
+        resample_chain::<EnemyPathPoint>(world, chain, spacing)
+    } else if let Some(chain) = selected_chain::<ItemPathPoint>(world) {
+        world.send_event(RecalcPaths::item());
+        resample_chain::<ItemPathPoint>(world, chain, spacing)
+    } else {
+        return;
+    };
+    world.send_event(RefreshOrdering);
+    select_only(world, &resampled);
+}
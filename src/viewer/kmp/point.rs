@@ -1,5 +1,5 @@
 use super::{
-    meshes_materials::{KmpMeshes, PointMaterials},
+    meshes_materials::{KmpMeshes, PointMaterials, SectionMaterials},
     ordering::{NextOrderID, OrderId},
     routes::RouteLink,
     KmpComponent, KmpSectionEntityIdMap, KmpSectionIdEntityMap, KmpSelectablePoint, MaybeRouteId, RespawnPoint,
@@ -19,6 +19,19 @@ use crate::{
 use bevy::{ecs::world::Command, math::vec3, prelude::*};
 use bevy_mod_outline::{OutlineBundle, OutlineVolume};
 
+pub fn point_plugin(app: &mut App) {
+    app.add_systems(Update, draw_respawn_previews);
+}
+
+// how high above a respawn point lakitu drops players from
+const RESPAWN_DROP_HEIGHT: f32 = 700.;
+// the spacing and extent of the grid of positions that players are dropped at, behind and either side of the point
+const RESPAWN_GRID_SPACING: f32 = 300.;
+const RESPAWN_GRID_HALF_WIDTH: f32 = 450.;
+const RESPAWN_GRID_DEPTH: f32 = 600.;
+// roughly the width, height and length of a kart
+const KART_SIZE: Vec3 = vec3(200., 150., 350.);
+
 pub fn spawn_point_section<T: KmpComponent + Spawn>(world: &mut World, kmp: &KmpFile) -> KmpSectionIdEntityMap<T>
 where
     T::KmpFormat: KmpGetSection + KmpPositionPoint + KmpRotationPoint + MaybeRouteId,
@@ -47,9 +60,12 @@ where
     id_entity_map
 }
 
-pub fn spawn_point<T: Spawn + Component + Clone>(spawner: Spawner<T>, world: &mut World) -> Entity {
-    let meshes = world.resource::<KmpMeshes>().clone();
-    let materials = world.resource::<PointMaterials<T>>().clone();
+pub fn spawn_point<T: Spawn + Component + Clone>(spawner: Spawner<T>, world: &mut World) -> Entity
+where
+    PointMaterials<T>: SectionMaterials,
+{
+    let meshes = KmpMeshes::get(world);
+    let materials = PointMaterials::<T>::get(world);
     let outline = world.get_resource::<AppSettings>().unwrap().kmp_model.outline;
 
     // either gets the order id, or gets it from the NextOrderID (which will increment it for next time)
@@ -137,16 +153,16 @@ pub fn spawn_point<T: Spawn + Component + Clone>(spawner: Spawner<T>, world: &mu
 pub struct AddRespawnPointPreview(pub Entity);
 impl Command for AddRespawnPointPreview {
     fn apply(self, world: &mut World) {
-        let mesh = world.resource::<KmpMeshes>().sphere.clone();
-        let material = world.resource::<PointMaterials<RespawnPoint>>().line.clone();
+        let mesh = KmpMeshes::get(world).sphere;
+        let material = PointMaterials::<RespawnPoint>::get(world).line;
 
         world.entity_mut(self.0).with_children(|parent| {
             // spawn respawn position previews
-            let y = 700.;
-            let mut z = -600.;
+            let y = RESPAWN_DROP_HEIGHT;
+            let mut z = -RESPAWN_GRID_DEPTH;
             while z <= 0. {
-                let mut x = -450.;
-                while x <= 450. {
+                let mut x = -RESPAWN_GRID_HALF_WIDTH;
+                while x <= RESPAWN_GRID_HALF_WIDTH {
                     parent.spawn({
                         PbrBundle {
                             mesh: mesh.clone(),
@@ -155,14 +171,53 @@ impl Command for AddRespawnPointPreview {
                             ..default()
                         }
                     });
-                    x += 300.;
+                    x += RESPAWN_GRID_SPACING;
                 }
-                z += 300.;
+                z += RESPAWN_GRID_SPACING;
             }
         });
     }
 }
 
+// show which way players face when they are dropped back onto the track, and the area they can be dropped into
+fn draw_respawn_previews(
+    mut gizmos: Gizmos,
+    q_respawn: Query<(&Transform, &Visibility), With<RespawnPoint>>,
+    settings: Res<AppSettings>,
+) {
+    let colors = &settings.kmp_model.color.respawn_points;
+    for (transform, visibility) in q_respawn.iter() {
+        if visibility == Visibility::Hidden {
+            continue;
+        }
+        let rotation = transform.rotation;
+        let drop_pos = transform.translation + Vec3::Y * RESPAWN_DROP_HEIGHT;
+        let forward = rotation * Vec3::Z;
+
+        gizmos.line(transform.translation, drop_pos, colors.line);
+        gizmos.cuboid(
+            Transform::from_translation(drop_pos)
+                .with_rotation(rotation)
+                .with_scale(KART_SIZE),
+            colors.point,
+        );
+        let front = drop_pos + forward * KART_SIZE.z / 2.;
+        gizmos.arrow(front, front + forward * KART_SIZE.z * 2., colors.arrow);
+
+        // the outline of the grid, with half a space around the outside positions
+        let margin = RESPAWN_GRID_SPACING / 2.;
+        let half_width = RESPAWN_GRID_HALF_WIDTH + margin;
+        let corners = [
+            vec3(-half_width, 0., margin),
+            vec3(half_width, 0., margin),
+            vec3(half_width, 0., -RESPAWN_GRID_DEPTH - margin),
+            vec3(-half_width, 0., -RESPAWN_GRID_DEPTH - margin),
+        ]
+        .map(|x| drop_pos + rotation * x);
+        gizmos.linestrip(corners.into_iter().chain([corners[0]]), colors.line);
+    }
+}
+
 pub fn save_point_section<T: KmpComponent>(world: &mut World) -> (Section<T::KmpFormat>, KmpSectionEntityIdMap<T>) {
     let mut q = world.query::<(&T, &Transform, Entity, &OrderId)>();
     let components: Vec<_> = q
@@ -0,0 +1,116 @@
+use super::{
+    components::{
+        AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, DisplayName, EnemyPathPoint, ItemPathPoint, KmpCamera,
+        LabelColor, Object, PointNote, RespawnPoint, StartPoint,
+    },
+    ordering::OrderId,
+    path::EntityPathGroups,
+    sections::KmpEditMode,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Editor-only information about a point, which is kept in the sidecar by the point's section and index in the KMP
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PointMetadata {
+    /// The section as it is named in the editor, e.g. 'Enemy Paths'
+    pub section: String,
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[u8; 3]>,
+}
+
+// the points of a section in the order they were last written to the KMP, which for paths is the order of their
+// path groups, as worked out when the KMP was built
+pub(super) fn written_order<T: Component>(world: &mut World) -> Vec<Entity> {
+    if let Some(groups) = world.get_resource::<EntityPathGroups<T>>() {
+        return groups.iter().flat_map(|x| x.path.iter().copied()).collect();
+    }
+    spawned_order::<T>(world)
+}
+
+// the points of a section in order of their order ids, which are their indices in the KMP just after it is spawned
+pub(super) fn spawned_order<T: Component>(world: &mut World) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, &OrderId), With<T>>();
+    let mut points: Vec<_> = q.iter(world).map(|(e, order_id)| (e, order_id.0)).collect();
+    points.sort_by_key(|x| x.1);
+    points.into_iter().map(|x| x.0).collect()
+}
+
+fn collect_section<T: Component>(world: &mut World, metadata: &mut Vec<PointMetadata>) {
+    let section = KmpEditMode::from_type::<T>().to_string();
+    for (i, e) in written_order::<T>(world).into_iter().enumerate() {
+        // paths which were worked out before points were deleted can still have them in
+        let Some(entity) = world.get_entity(e) else {
+            continue;
+        };
+        let name = entity.get::<DisplayName>().map(|x| x.0.clone());
+        let note = entity.get::<PointNote>().map(|x| x.0.clone());
+        let color = entity.get::<LabelColor>().map(|x| x.0);
+        if name.is_none() && note.is_none() && color.is_none() {
+            continue;
+        }
+        metadata.push(PointMetadata {
+            section: section.clone(),
+            index: i as u32,
+            name,
+            note,
+            color,
+        });
+    }
+}
+
+fn apply_section<T: Component>(world: &mut World, metadata: &[PointMetadata]) {
+    let section = KmpEditMode::from_type::<T>().to_string();
+    let points = spawned_order::<T>(world);
+    for m in metadata.iter().filter(|x| x.section == section) {
+        let Some(e) = points.get(m.index as usize) else {
+            continue;
+        };
+        let mut entity = world.entity_mut(*e);
+        if let Some(name) = &m.name {
+            entity.insert(DisplayName(name.clone()));
+        }
+        if let Some(note) = &m.note {
+            entity.insert(PointNote(note.clone()));
+        }
+        if let Some(color) = m.color {
+            entity.insert(LabelColor(color));
+        }
+    }
+}
+
+/// The names, notes and colours of all the points, by their indices in the KMP as it was last built
+pub fn collect_point_metadata(world: &mut World) -> Vec<PointMetadata> {
+    let mut metadata = Vec::new();
+    collect_section::<StartPoint>(world, &mut metadata);
+    collect_section::<EnemyPathPoint>(world, &mut metadata);
+    collect_section::<ItemPathPoint>(world, &mut metadata);
+    collect_section::<Checkpoint>(world, &mut metadata);
+    collect_section::<RespawnPoint>(world, &mut metadata);
+    collect_section::<Object>(world, &mut metadata);
+    collect_section::<AreaPoint>(world, &mut metadata);
+    collect_section::<KmpCamera>(world, &mut metadata);
+    collect_section::<CannonPoint>(world, &mut metadata);
+    collect_section::<BattleFinishPoint>(world, &mut metadata);
+    metadata
+}
+
+/// Give the points of a KMP which has just been spawned their names, notes and colours. This has to be done before the
+/// ordering is refreshed, while the order ids are still the indices in the KMP.
+pub fn apply_point_metadata(world: &mut World, metadata: &[PointMetadata]) {
+    apply_section::<StartPoint>(world, metadata);
+    apply_section::<EnemyPathPoint>(world, metadata);
+    apply_section::<ItemPathPoint>(world, metadata);
+    apply_section::<Checkpoint>(world, metadata);
+    apply_section::<RespawnPoint>(world, metadata);
+    apply_section::<Object>(world, metadata);
+    apply_section::<AreaPoint>(world, metadata);
+    apply_section::<KmpCamera>(world, metadata);
+    apply_section::<CannonPoint>(world, metadata);
+    apply_section::<BattleFinishPoint>(world, metadata);
+}
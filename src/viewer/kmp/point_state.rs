@@ -0,0 +1,146 @@
+use super::{
+    checkpoints::CheckpointLeft,
+    components::{
+        AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object,
+        RespawnPoint, RoutePoint, RouteSettings, StartPoint,
+    },
+    ordering::{OrderId, OriginalIndex},
+    path::KmpPathNode,
+    point_metadata::{spawned_order, written_order},
+    sections::KmpEditMode,
+};
+use crate::viewer::edit::{pin::Pinned, select::Selected, transform_history::TransformHistory};
+use bevy::{ecs::entity::EntityHashSet, prelude::*};
+
+/// Editor state of a point which only lasts for the session, kept by the point's section and index so that it can be
+/// put back when the KMP is respawned, e.g. by undo
+#[derive(Clone)]
+pub struct PointState {
+    section: KmpEditMode,
+    index: u32,
+    selected: bool,
+    /// Whether the right side of a checkpoint is selected, as the left side holds the state of both
+    right_selected: bool,
+    pinned: Option<Pinned>,
+    transform_history: Option<TransformHistory>,
+    original_index: Option<OriginalIndex>,
+}
+
+// route points in the order of their routes, then along each route
+fn route_order(world: &mut World) -> Vec<Entity> {
+    let mut q = world.query_filtered::<(Entity, &OrderId), With<RouteSettings>>();
+    let mut starts: Vec<_> = q.iter(world).map(|(e, order_id)| (e, order_id.0)).collect();
+    starts.sort_by_key(|x| x.1);
+    let mut seen = EntityHashSet::default();
+    let mut points = Vec::new();
+    for (start, _) in starts {
+        let mut next = Some(start);
+        while let Some(e) = next.filter(|e| seen.insert(*e)) {
+            points.push(e);
+            next = world
+                .get::<KmpPathNode>(e)
+                .and_then(|x| x.next_nodes.iter().next().copied());
+        }
+    }
+    points
+}
+
+fn collect_section(world: &World, section: KmpEditMode, order: Vec<Entity>, states: &mut Vec<PointState>) {
+    for (i, e) in order.into_iter().enumerate() {
+        // paths which were worked out before points were deleted can still have them in
+        let Some(entity) = world.get_entity(e) else {
+            continue;
+        };
+        let right_selected = entity
+            .get::<CheckpointLeft>()
+            .is_some_and(|x| world.get::<Selected>(x.right).is_some());
+        let state = PointState {
+            section,
+            index: i as u32,
+            selected: entity.contains::<Selected>(),
+            right_selected,
+            pinned: entity.get::<Pinned>().copied(),
+            transform_history: entity.get::<TransformHistory>().cloned(),
+            original_index: entity.get::<OriginalIndex>().copied(),
+        };
+        if state.selected
+            || state.right_selected
+            || state.pinned.is_some()
+            || state.transform_history.is_some()
+            || state.original_index.is_some()
+        {
+            states.push(state);
+        }
+    }
+}
+
+fn apply_section(world: &mut World, section: KmpEditMode, order: Vec<Entity>, states: &[PointState]) {
+    for state in states.iter().filter(|x| x.section == section) {
+        let Some(e) = order.get(state.index as usize).copied() else {
+            continue;
+        };
+        let right = world.get::<CheckpointLeft>(e).map(|x| x.right);
+        let mut entity = world.entity_mut(e);
+        if state.selected {
+            entity.insert(Selected);
+        }
+        if let Some(pinned) = state.pinned {
+            entity.insert(pinned);
+        }
+        if let Some(transform_history) = &state.transform_history {
+            entity.insert(transform_history.clone());
+        }
+        if let Some(original_index) = state.original_index {
+            entity.insert(original_index);
+        }
+        if let Some(right) = right.filter(|_| state.right_selected) {
+            world.entity_mut(right).insert(Selected);
+        }
+    }
+}
+
+fn collect_type<T: Component>(world: &mut World, states: &mut Vec<PointState>) {
+    let order = written_order::<T>(world);
+    collect_section(world, KmpEditMode::from_type::<T>(), order, states);
+}
+
+fn apply_type<T: Component>(world: &mut World, states: &[PointState]) {
+    let order = spawned_order::<T>(world);
+    apply_section(world, KmpEditMode::from_type::<T>(), order, states);
+}
+
+/// The selection, pins, transform histories and original indices of all the points, by their indices in the KMP as
+/// it was last built
+pub fn collect_point_states(world: &mut World) -> Vec<PointState> {
+    let mut states = Vec::new();
+    collect_type::<StartPoint>(world, &mut states);
+    collect_type::<EnemyPathPoint>(world, &mut states);
+    collect_type::<ItemPathPoint>(world, &mut states);
+    collect_type::<Checkpoint>(world, &mut states);
+    collect_type::<RespawnPoint>(world, &mut states);
+    collect_type::<Object>(world, &mut states);
+    collect_type::<AreaPoint>(world, &mut states);
+    collect_type::<KmpCamera>(world, &mut states);
+    collect_type::<CannonPoint>(world, &mut states);
+    collect_type::<BattleFinishPoint>(world, &mut states);
+    let order = route_order(world);
+    collect_section(world, KmpEditMode::from_type::<RoutePoint>(), order, &mut states);
+    states
+}
+
+/// Put back the state of the points of a KMP which has just been spawned. Like the point metadata, this has to be done
+/// before the ordering is refreshed, while the order ids are still the indices in the KMP.
+pub fn apply_point_states(world: &mut World, states: &[PointState]) {
+    apply_type::<StartPoint>(world, states);
+    apply_type::<EnemyPathPoint>(world, states);
+    apply_type::<ItemPathPoint>(world, states);
+    apply_type::<Checkpoint>(world, states);
+    apply_type::<RespawnPoint>(world, states);
+    apply_type::<Object>(world, states);
+    apply_type::<AreaPoint>(world, states);
+    apply_type::<KmpCamera>(world, states);
+    apply_type::<CannonPoint>(world, states);
+    apply_type::<BattleFinishPoint>(world, states);
+    let order = route_order(world);
+    apply_section(world, KmpEditMode::from_type::<RoutePoint>(), order, states);
+}
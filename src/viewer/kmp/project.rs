@@ -0,0 +1,207 @@
+use crate::ui::{
+    errors::{show_errors, ErrorMessages},
+    file_dialog::{DialogType, FileDialogResult},
+    ui_state::KmpFilePath,
+    update_ui::KclFileSelected,
+};
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub fn project_plugin(app: &mut App) {
+    app.init_resource::<ProjectRoot>().add_systems(
+        Update,
+        (
+            find_project_root,
+            (
+                choose_project_root.pipe(show_errors),
+                choose_project_paths.pipe(show_errors),
+            )
+                .run_if(on_event::<FileDialogResult>()),
+        ),
+    );
+}
+
+/// The file which marks a folder as the root of a track project
+pub const PROJECT_FILE_NAME: &str = "kmpeek_project.json";
+
+/// Settings of a track project, stored in the project file. Paths are relative to the project root, so the project
+/// folder can be moved or renamed without breaking them.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ProjectFile {
+    /// The KCL that goes with the KMPs in the project, if it isn't the 'course.kcl' next to each of them
+    pub kcl: Option<PathBuf>,
+    /// The folder files are exported to by default, if it isn't the project root
+    pub exports: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    pub root: PathBuf,
+    pub file: ProjectFile,
+}
+
+impl Project {
+    /// Find the project a file is in, by looking for the project file in its folder and each folder above it
+    pub fn find(path: &Path) -> Option<Self> {
+        let root = path.ancestors().skip(1).find(|x| x.join(PROJECT_FILE_NAME).is_file())?;
+        let file = fs::read_to_string(root.join(PROJECT_FILE_NAME))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Some(Self {
+            root: root.into(),
+            file,
+        })
+    }
+    /// The folder files are exported to by default
+    pub fn exports_dir(&self) -> PathBuf {
+        match &self.file.exports {
+            Some(exports) => self.root.join(exports),
+            None => self.root.clone(),
+        }
+    }
+    /// The KCL set in the project, if there is one
+    pub fn kcl(&self) -> Option<PathBuf> {
+        Some(self.root.join(self.file.kcl.as_ref()?))
+    }
+    /// Make a path relative to the project root if it is inside the project, otherwise leave it as it is
+    pub fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.root).map_or_else(|_| path.into(), Into::into)
+    }
+    /// Write the project settings back to the project file
+    pub fn save(&self) -> anyhow::Result<()> {
+        let project_string =
+            serde_json::to_string_pretty(&self.file).context("could not convert project file to json")?;
+        fs::write(self.root.join(PROJECT_FILE_NAME), project_string).context("could not write project file")?;
+        Ok(())
+    }
+}
+
+/// The track project the open KMP is in, if it is in one. Paths set in the project are stored relative to its root,
+/// so the project folder can be moved or renamed without breaking them.
+#[derive(Resource, Default, Deref, DerefMut, Clone, Debug)]
+pub struct ProjectRoot(pub Option<Project>);
+
+impl ProjectRoot {
+    /// The folder file dialogs start in, if a project is open
+    pub fn dialog_dir(&self) -> Option<PathBuf> {
+        self.as_ref().map(|x| x.root.clone())
+    }
+    /// The folder export dialogs start in, if a project is open
+    pub fn export_dir(&self) -> Option<PathBuf> {
+        self.as_ref().map(|x| x.exports_dir())
+    }
+}
+
+/// The KCL that goes with a KMP: the one set in its project if there is one, otherwise the 'course.kcl' next to it
+pub fn companion_kcl(kmp_path: &Path) -> PathBuf {
+    Project::find(kmp_path)
+        .and_then(|project| project.kcl())
+        .unwrap_or_else(|| kmp_path.with_file_name("course.kcl"))
+}
+
+/// Make a folder the root of a track project, by creating the project file in it if there isn't one already
+pub fn create_project(root: &Path) -> anyhow::Result<()> {
+    let file_path = root.join(PROJECT_FILE_NAME);
+    if file_path.exists() {
+        return Ok(());
+    }
+    let project_string =
+        serde_json::to_string_pretty(&ProjectFile::default()).context("could not convert project file to json")?;
+    fs::write(file_path, project_string).context("could not create project file")?;
+    Ok(())
+}
+
+/// Change the project's KCL and exports folder, writing them to the project file
+pub fn set_project_paths(world: &mut World, kcl: Option<PathBuf>, exports: Option<PathBuf>) {
+    let mut project_root = world.resource_mut::<ProjectRoot>();
+    let Some(project) = &mut project_root.0 else {
+        return;
+    };
+    project.file.kcl = kcl;
+    project.file.exports = exports;
+    let result = project.save();
+    ErrorMessages::report(world, result);
+}
+
+fn find_project_root(kmp_file_path: Option<Res<KmpFilePath>>, mut project: ResMut<ProjectRoot>) {
+    match kmp_file_path {
+        Some(path) if path.is_changed() => **project = Project::find(&path),
+        None if project.is_some() => **project = None,
+        _ => {}
+    }
+}
+
+fn choose_project_root(
+    mut ev_file_dialog: EventReader<FileDialogResult>,
+    kmp_file_path: Option<Res<KmpFilePath>>,
+    mut project: ResMut<ProjectRoot>,
+) -> anyhow::Result<()> {
+    for FileDialogResult { path, dialog_type } in ev_file_dialog.read() {
+        if !matches!(dialog_type, DialogType::ChooseProjectRoot) {
+            continue;
+        }
+        let Some(kmp_file_path) = &kmp_file_path else {
+            continue;
+        };
+        if !kmp_file_path.starts_with(path) {
+            bail!("the KMP isn't inside {}", path.display());
+        }
+        create_project(path)?;
+        **project = Project::find(kmp_file_path);
+    }
+    Ok(())
+}
+
+fn choose_project_paths(
+    mut ev_file_dialog: EventReader<FileDialogResult>,
+    mut project_root: ResMut<ProjectRoot>,
+    mut ev_kcl_file_selected: EventWriter<KclFileSelected>,
+) -> anyhow::Result<()> {
+    for FileDialogResult { path, dialog_type } in ev_file_dialog.read() {
+        let Some(project) = &mut project_root.0 else {
+            continue;
+        };
+        match dialog_type {
+            DialogType::ChooseProjectKcl => {
+                project.file.kcl = Some(project.relative(path));
+                ev_kcl_file_selected.send(KclFileSelected(path.clone()));
+            }
+            DialogType::ChooseProjectExports => project.file.exports = Some(project.relative(path)),
+            _ => continue,
+        }
+        project.save()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_moving_project_keeps_paths() {
+    let temp = std::env::temp_dir().join(format!("kmpeek_project_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp);
+    let (old_root, new_root) = (temp.join("old"), temp.join("new"));
+    fs::create_dir_all(old_root.join("course")).unwrap();
+    create_project(&old_root).unwrap();
+    fs::write(old_root.join("course/course.kmp"), []).unwrap();
+
+    let mut project = Project::find(&old_root.join("course/course.kmp")).unwrap();
+    project.file.kcl = Some(project.relative(&old_root.join("shared.kcl")));
+    project.file.exports = Some(project.relative(&old_root.join("exports")));
+    project.save().unwrap();
+    assert_eq!(project.file.kcl, Some("shared.kcl".into()));
+
+    fs::rename(&old_root, &new_root).unwrap();
+    let kmp_path = new_root.join("course/course.kmp");
+    let project = Project::find(&kmp_path).unwrap();
+    assert_eq!(project.root, new_root);
+    assert_eq!(companion_kcl(&kmp_path), new_root.join("shared.kcl"));
+    assert_eq!(project.exports_dir(), new_root.join("exports"));
+
+    fs::remove_dir_all(&temp).unwrap();
+}
@@ -0,0 +1,250 @@
+use super::{checkpoints::CheckpointHeight, sections::KmpEditMode};
+use crate::{
+    ui::{
+        errors::show_errors,
+        file_dialog::{DialogType, FileDialogResult},
+        settings::AppSettings,
+        util::Icons,
+    },
+    util::{
+        kmp_file::{KmpFile, PathGroup},
+        world_to_gizmo_cam,
+    },
+    viewer::camera::Gizmo2dCam,
+};
+use anyhow::Context;
+use bevy::{
+    color::palettes::css, ecs::event::ManualEventReader, prelude::*, transform::TransformSystem, utils::HashMap,
+};
+use bevy_vector_shapes::{painter::ShapePainter, shapes::DiscPainter};
+use std::{fs, path::PathBuf};
+
+pub fn reference_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        open_reference_kmp
+            .pipe(show_errors)
+            .run_if(on_event::<FileDialogResult>()),
+    )
+    // drawn after TransformPropagate like the saved ghosts, so the points don't lag behind the camera
+    .add_systems(
+        PostUpdate,
+        draw_reference_kmp
+            .after(TransformSystem::TransformPropagate)
+            .run_if(resource_exists::<ReferenceKmp>),
+    );
+}
+
+// how far each section colour is faded towards grey, so the reference can't be mistaken for the KMP being edited
+const REFERENCE_FADE: f32 = 0.6;
+const REFERENCE_ALPHA: f32 = 0.6;
+const REFERENCE_RADIUS: f32 = 6.;
+
+/// Another KMP (usually the original Nintendo track) drawn underneath the one being edited, to line a port or remake
+/// up against. It is only ever drawn, so its points can't be selected, edited or saved. Remove this resource to
+/// close it.
+#[derive(Resource)]
+pub struct ReferenceKmp {
+    pub path: PathBuf,
+    pub show: bool,
+    /// Draw the reference points of every section, rather than just the section being edited
+    pub all_sections: bool,
+    points: HashMap<KmpEditMode, Vec<Vec3>>,
+    links: HashMap<KmpEditMode, Vec<(Vec3, Vec3)>>,
+    /// The left and right of each checkpoint, which are placed at the checkpoint height when drawn
+    checkpoints: Vec<(Vec2, Vec2)>,
+    checkpoint_links: Vec<(usize, usize)>,
+}
+
+impl ReferenceKmp {
+    fn new(path: PathBuf, kmp: &KmpFile) -> Self {
+        let mut points = HashMap::new();
+        let mut links = HashMap::new();
+        let pos = |x: [f32; 3]| Vec3::from(x);
+
+        points.insert(
+            KmpEditMode::StartPoints,
+            kmp.ktpt.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+
+        let enemy: Vec<_> = kmp.enpt.entries.iter().map(|x| pos(x.position)).collect();
+        links.insert(
+            KmpEditMode::EnemyPaths,
+            index_links(&enemy, group_links(&kmp.enph.entries)),
+        );
+        points.insert(KmpEditMode::EnemyPaths, enemy);
+
+        let item: Vec<_> = kmp.itpt.entries.iter().map(|x| pos(x.position)).collect();
+        links.insert(
+            KmpEditMode::ItemPaths,
+            index_links(&item, group_links(&kmp.itph.entries)),
+        );
+        points.insert(KmpEditMode::ItemPaths, item);
+
+        points.insert(
+            KmpEditMode::RespawnPoints,
+            kmp.jgpt.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+        points.insert(
+            KmpEditMode::Objects,
+            kmp.gobj.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+
+        let mut route_points = Vec::new();
+        let mut route_links = Vec::new();
+        for route in kmp.poti.entries.iter() {
+            let route: Vec<_> = route.points.iter().map(|x| pos(x.position)).collect();
+            route_links.extend(route.windows(2).map(|x| (x[0], x[1])));
+            route_points.extend(route);
+        }
+        points.insert(KmpEditMode::Routes, route_points);
+        links.insert(KmpEditMode::Routes, route_links);
+
+        points.insert(
+            KmpEditMode::Areas,
+            kmp.area.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+        points.insert(
+            KmpEditMode::Cameras,
+            kmp.came.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+        points.insert(
+            KmpEditMode::CannonPoints,
+            kmp.cnpt.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+        points.insert(
+            KmpEditMode::BattleFinishPoints,
+            kmp.mspt.entries.iter().map(|x| pos(x.position)).collect(),
+        );
+
+        let checkpoints: Vec<_> = kmp
+            .ckpt
+            .entries
+            .iter()
+            .map(|x| (Vec2::from(x.cp_left), Vec2::from(x.cp_right)))
+            .collect();
+        let checkpoint_links = group_links(&kmp.ckph.entries)
+            .into_iter()
+            .filter(|(a, b)| *a < checkpoints.len() && *b < checkpoints.len())
+            .collect();
+
+        Self {
+            path,
+            show: true,
+            all_sections: false,
+            points,
+            links,
+            checkpoints,
+            checkpoint_links,
+        }
+    }
+}
+
+// the indexes of each pair of points linked together by the path groups
+fn group_links<T: Default>(groups: &[PathGroup<T>]) -> Vec<(usize, usize)> {
+    let mut links = Vec::new();
+    for group in groups.iter() {
+        let start = group.start as usize;
+        let end = start + group.group_length as usize;
+        links.extend((start..end.saturating_sub(1)).map(|i| (i, i + 1)));
+        if group.group_length == 0 {
+            continue;
+        }
+        for next_group in group.next_group.iter().filter(|x| **x != 0xff) {
+            if let Some(next) = groups.get(*next_group as usize) {
+                links.push((end - 1, next.start as usize));
+            }
+        }
+    }
+    links
+}
+
+fn index_links(points: &[Vec3], links: Vec<(usize, usize)>) -> Vec<(Vec3, Vec3)> {
+    links
+        .into_iter()
+        .filter_map(|(a, b)| Some((*points.get(a)?, *points.get(b)?)))
+        .collect()
+}
+
+fn open_reference_kmp(world: &mut World, mut reader: Local<ManualEventReader<FileDialogResult>>) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let Some(path) = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::OpenReferenceKmp))
+        .map(|x| x.path.clone())
+        .last()
+    else {
+        return Ok(());
+    };
+    let data = fs::read(&path).context("could not open reference kmp file")?;
+    let kmp = KmpFile::read_any(&data).context("could not read reference kmp file")?;
+    world.insert_resource(ReferenceKmp::new(path, &kmp));
+    Ok(())
+}
+
+fn reference_color(mode: KmpEditMode) -> Color {
+    let color = Icons::SECTION_COLORS[mode as usize];
+    Srgba::rgb_u8(color.r(), color.g(), color.b())
+        .mix(&css::GRAY, REFERENCE_FADE)
+        .with_alpha(REFERENCE_ALPHA)
+        .into()
+}
+
+// the points are drawn with the 2d gizmo camera so that they stay the same size on screen like the points themselves,
+// and the links between them are drawn in 3d
+fn draw_reference_kmp(
+    reference: Res<ReferenceKmp>,
+    settings: Res<AppSettings>,
+    mode: Res<KmpEditMode>,
+    cp_height: Res<CheckpointHeight>,
+    q_cam: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_gizmo_cam: Query<(&Camera, &GlobalTransform), With<Gizmo2dCam>>,
+    mut painter: ShapePainter,
+    mut gizmos: Gizmos,
+) {
+    if !reference.show {
+        return;
+    }
+    let Some(cam) = q_cam.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let gizmo_cam = q_gizmo_cam.single();
+    let cp_pos = |pos: Vec2| pos.extend(cp_height.0).xzy();
+
+    let modes: Vec<_> = if reference.all_sections {
+        reference
+            .points
+            .keys()
+            .copied()
+            .chain([KmpEditMode::Checkpoints])
+            .collect()
+    } else {
+        vec![*mode]
+    };
+    painter.hollow = false;
+    for mode in modes {
+        let color = reference_color(mode);
+        painter.color = color;
+        let mut points = reference.points.get(&mode).cloned().unwrap_or_default();
+        if mode == KmpEditMode::Checkpoints {
+            for (left, right) in reference.checkpoints.iter() {
+                gizmos.line(cp_pos(*left), cp_pos(*right), color);
+                points.extend([cp_pos(*left), cp_pos(*right)]);
+            }
+            for (a, b) in reference.checkpoint_links.iter() {
+                let (a, b) = (reference.checkpoints[*a], reference.checkpoints[*b]);
+                gizmos.line(cp_pos(a.0), cp_pos(b.0), color);
+                gizmos.line(cp_pos(a.1), cp_pos(b.1), color);
+            }
+        }
+        for (a, b) in reference.links.get(&mode).into_iter().flatten() {
+            gizmos.line(*a, *b, color);
+        }
+        for pos in points {
+            if let Some(pos) = world_to_gizmo_cam(cam, gizmo_cam, pos) {
+                painter.transform.translation = pos;
+                painter.circle(REFERENCE_RADIUS * settings.kmp_model.point_scale);
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::{
+    checkpoints::{CheckpointLeft, CheckpointRespawnLink},
+    components::{Checkpoint, KmpSelectablePoint, RespawnPoint},
+    ordering::OrderId,
+};
+use bevy::prelude::*;
+use strum_macros::{Display, EnumIter};
+
+pub fn respawn_links_plugin(app: &mut App) {
+    app.init_resource::<RespawnLinkMode>()
+        .add_event::<LinkNearestRespawns>()
+        .add_systems(Update, link_nearest_respawns.run_if(on_event::<LinkNearestRespawns>()));
+}
+
+/// How the respawn point for each checkpoint is picked when they are linked automatically
+#[derive(Resource, Default, Display, EnumIter, PartialEq, Clone, Copy)]
+pub enum RespawnLinkMode {
+    /// The last respawn point at or before the checkpoint, going along the checkpoints, so that players aren't put
+    /// back further on than they got
+    #[default]
+    #[strum(serialize = "Behind Along Path")]
+    PathProgress,
+    /// The closest respawn point as the crow flies
+    #[strum(serialize = "Nearest")]
+    Distance,
+}
+
+/// Send this event to link each of these checkpoints (the left entities) to a respawn point, picked using the
+/// [`RespawnLinkMode`]
+#[derive(Event)]
+pub struct LinkNearestRespawns(pub Vec<Entity>);
+
+// the middle of the checkpoint's line, where it is measured from
+fn checkpoint_centre(world: &World, left: Entity) -> Option<Vec3> {
+    let right = world.get::<CheckpointLeft>(left)?.right;
+    let [left, right] = [left, right].map(|e| world.get::<Transform>(e).map(|x| x.translation));
+    Some(left?.lerp(right?, 0.5))
+}
+
+fn link_nearest_respawns(world: &mut World) {
+    let checkpoints: Vec<_> = world
+        .resource_mut::<Events<LinkNearestRespawns>>()
+        .drain()
+        .flat_map(|x| x.0)
+        .collect();
+    let mode = *world.resource::<RespawnLinkMode>();
+
+    // every checkpoint's centre along with how far along the checkpoints it is
+    let cp_lefts: Vec<_> = world
+        .query_filtered::<(Entity, &OrderId), With<Checkpoint>>()
+        .iter(world)
+        .map(|(e, order_id)| (e, order_id.0))
+        .collect();
+    let all_cps: Vec<_> = cp_lefts
+        .into_iter()
+        .filter_map(|(e, index)| Some((e, index, checkpoint_centre(world, e)?)))
+        .collect();
+    // how far along the checkpoints each respawn point is, going by the checkpoint it is closest to
+    let respawns: Vec<_> = world
+        .query_filtered::<(Entity, &Transform), (With<RespawnPoint>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .map(|(e, transform)| {
+            let pos = transform.translation;
+            let progress = all_cps
+                .iter()
+                .min_by(|a, b| a.2.distance_squared(pos).total_cmp(&b.2.distance_squared(pos)))
+                .map(|x| x.1);
+            (e, pos, progress)
+        })
+        .collect();
+
+    for cp in checkpoints {
+        let Some(&(_, cp_index, cp_pos)) = all_cps.iter().find(|x| x.0 == cp) else {
+            continue;
+        };
+        let distance = |x: &&(Entity, Vec3, Option<u32>)| x.1.distance_squared(cp_pos);
+        let nearest = |candidates: Vec<&(Entity, Vec3, Option<u32>)>| {
+            candidates
+                .into_iter()
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                .map(|x| x.0)
+        };
+        let respawn = match mode {
+            RespawnLinkMode::Distance => nearest(respawns.iter().collect()),
+            RespawnLinkMode::PathProgress => {
+                // if there isn't a respawn point before the checkpoint, the lap loops round to the last one
+                let behind = respawns
+                    .iter()
+                    .filter_map(|x| x.2)
+                    .filter(|x| *x <= cp_index)
+                    .max()
+                    .or_else(|| respawns.iter().filter_map(|x| x.2).max());
+                nearest(respawns.iter().filter(|x| x.2.is_some() && x.2 == behind).collect())
+            }
+        };
+        if let Some(respawn) = respawn {
+            world.entity_mut(cp).insert(CheckpointRespawnLink(respawn));
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use super::{
+    checkpoints::{CheckpointLeft, CheckpointRight},
+    components::{Checkpoint, EnemyPathPoint, ItemPathPoint, PathOverallStart, RoutePoint, StartPoint},
+    ordering::{OrderId, RefreshOrdering},
+    path::{KmpPathNode, RecalcPaths},
+};
+use crate::viewer::edit::select::Selected;
+use bevy::{ecs::entity::EntityHashSet, prelude::*};
+use std::{f32::consts::PI, mem};
+
+pub fn reverse_path_plugin(app: &mut App) {
+    app.add_event::<ReversePaths>()
+        .add_systems(Update, reverse_paths.run_if(on_event::<ReversePaths>()));
+}
+
+/// Send this event to turn round every path that a selected point is on, so it is driven the other way, along with
+/// any selected start points. Routes are reversed by moving their points, so the objects and cameras that follow
+/// them still do.
+#[derive(Event, Default)]
+pub struct ReversePaths {
+    /// Reverse every enemy, item and checkpoint path and turn round every start point instead, for making a reversed
+    /// version of a track. Routes are left alone, as they belong to objects and cameras rather than the track.
+    pub whole_track: bool,
+}
+
+// every point of this type linked to the given points, however far along the path
+fn linked_points<T: Component>(world: &World, from: impl IntoIterator<Item = Entity>) -> Vec<Entity> {
+    let mut found = EntityHashSet::default();
+    let mut to_visit: Vec<_> = from.into_iter().collect();
+    while let Some(e) = to_visit.pop() {
+        if world.get::<T>(e).is_none() || !found.insert(e) {
+            continue;
+        }
+        if let Some(node) = world.get::<KmpPathNode>(e) {
+            to_visit.extend(node.prev_nodes.iter().chain(node.next_nodes.iter()));
+        }
+    }
+    found.into_iter().collect()
+}
+
+fn swap_links(world: &mut World, e: Entity) {
+    if let Some(mut node) = world.get_mut::<KmpPathNode>(e) {
+        let node = &mut *node;
+        mem::swap(&mut node.prev_nodes, &mut node.next_nodes);
+    }
+}
+
+// a path that doesn't loop has to start from the point at its new beginning. The order of the points is reversed
+// too, keeping the path start first, so they are listed the way the path now goes.
+fn reverse_order(world: &mut World, points: &[Entity]) {
+    let has_prev = |world: &World, e: Entity| world.get::<KmpPathNode>(e).is_some_and(|x| !x.prev_nodes.is_empty());
+    let old_start = points
+        .iter()
+        .copied()
+        .find(|e| world.get::<PathOverallStart>(*e).is_some());
+    let new_start = points.iter().copied().find(|e| !has_prev(world, *e));
+    if let (Some(old_start), Some(new_start)) = (old_start, new_start) {
+        if has_prev(world, old_start) {
+            world.entity_mut(old_start).remove::<PathOverallStart>();
+            world.entity_mut(new_start).insert(PathOverallStart);
+        }
+    }
+
+    let mut order: Vec<_> = points
+        .iter()
+        .filter_map(|e| world.get::<OrderId>(*e).map(|x| (*e, x.0)))
+        .collect();
+    order.sort_by_key(|x| x.1);
+    let ids: Vec<_> = order.iter().map(|x| x.1).collect();
+    let mut reversed: Vec<_> = order.into_iter().rev().map(|x| x.0).collect();
+    if let Some(i) = reversed
+        .iter()
+        .position(|e| world.get::<PathOverallStart>(*e).is_some())
+    {
+        reversed.rotate_left(i);
+    }
+    for (e, id) in reversed.into_iter().zip(ids) {
+        if let Some(mut order_id) = world.get_mut::<OrderId>(e) {
+            order_id.0 = id;
+        }
+    }
+}
+
+fn reverse_path(world: &mut World, points: &[Entity]) {
+    for e in points {
+        swap_links(world, *e);
+    }
+    reverse_order(world, points);
+}
+
+// both sides of each checkpoint are linked separately, and the sides swap over so the checkpoints face the other way
+fn reverse_checkpoints(world: &mut World, lefts: &[Entity]) {
+    for left in lefts {
+        let Some(right) = world.get::<CheckpointLeft>(*left).map(|x| x.right) else {
+            continue;
+        };
+        swap_links(world, *left);
+        swap_links(world, right);
+        let (Some(left_pos), Some(right_pos)) = (
+            world.get::<Transform>(*left).map(|x| x.translation),
+            world.get::<Transform>(right).map(|x| x.translation),
+        ) else {
+            continue;
+        };
+        world.get_mut::<Transform>(*left).unwrap().translation = right_pos;
+        world.get_mut::<Transform>(right).unwrap().translation = left_pos;
+    }
+    reverse_order(world, lefts);
+}
+
+// the route start holds the route's settings and what follows it, so rather than relinking the points, their
+// positions and settings are put in the opposite order along the route
+fn reverse_route(world: &mut World, points: &[Entity]) {
+    let Some(start) = points
+        .iter()
+        .copied()
+        .find(|e| world.get::<KmpPathNode>(*e).is_some_and(|x| x.prev_nodes.is_empty()))
+    else {
+        return;
+    };
+    let mut chain = vec![start];
+    while let Some(next) = world
+        .get::<KmpPathNode>(*chain.last().unwrap())
+        .and_then(|x| x.next_nodes.iter().next().copied())
+    {
+        if chain.contains(&next) {
+            break;
+        }
+        chain.push(next);
+    }
+    let data: Vec<_> = chain
+        .iter()
+        .filter_map(|e| {
+            Some((
+                world.get::<Transform>(*e)?.translation,
+                world.get::<RoutePoint>(*e)?.clone(),
+            ))
+        })
+        .collect();
+    if data.len() != chain.len() {
+        return;
+    }
+    for (e, (pos, route_pt)) in chain.into_iter().zip(data.into_iter().rev()) {
+        world.get_mut::<Transform>(e).unwrap().translation = pos;
+        *world.get_mut::<RoutePoint>(e).unwrap() = route_pt;
+    }
+}
+
+// split the points into the separate paths they make up
+fn paths<T: Component>(world: &World, points: &[Entity]) -> Vec<Vec<Entity>> {
+    let mut result: Vec<Vec<Entity>> = Vec::new();
+    for e in points.iter().filter(|e| world.get::<T>(**e).is_some()) {
+        if result.iter().any(|x| x.contains(e)) {
+            continue;
+        }
+        result.push(linked_points::<T>(world, [*e]));
+    }
+    result
+}
+
+fn reverse_paths(world: &mut World) {
+    let Some(whole_track) = world
+        .resource_mut::<Events<ReversePaths>>()
+        .drain()
+        .map(|x| x.whole_track)
+        .last()
+    else {
+        return;
+    };
+
+    let mut points: Vec<Entity> = if whole_track {
+        world
+            .query_filtered::<Entity, Or<(With<EnemyPathPoint>, With<ItemPathPoint>, With<Checkpoint>)>>()
+            .iter(world)
+            .collect()
+    } else {
+        world.query_filtered::<Entity, With<Selected>>().iter(world).collect()
+    };
+    // checkpoints are reversed from their left side
+    for e in points.iter_mut() {
+        if let Some(cp_right) = world.get::<CheckpointRight>(*e) {
+            *e = cp_right.left;
+        }
+    }
+
+    for path in paths::<EnemyPathPoint>(world, &points) {
+        reverse_path(world, &path);
+    }
+    for path in paths::<ItemPathPoint>(world, &points) {
+        reverse_path(world, &path);
+    }
+    for path in paths::<Checkpoint>(world, &points) {
+        reverse_checkpoints(world, &path);
+    }
+    if !whole_track {
+        for path in paths::<RoutePoint>(world, &points) {
+            reverse_route(world, &path);
+        }
+    }
+
+    let start_points: Vec<_> = if whole_track {
+        world.query_filtered::<Entity, With<StartPoint>>().iter(world).collect()
+    } else {
+        points
+            .into_iter()
+            .filter(|e| world.get::<StartPoint>(*e).is_some())
+            .collect()
+    };
+    for e in start_points {
+        if let Some(mut transform) = world.get_mut::<Transform>(e) {
+            transform.rotate_y(PI);
+        }
+    }
+
+    world.send_event(RecalcPaths::all());
+    world.send_event(RefreshOrdering);
+}
@@ -1,8 +1,10 @@
-use crate::viewer::edit::select::Selected;
+use crate::{ui::settings::AppSettings, viewer::edit::select::Selected};
 
 use super::{
+    ordering::OrderId,
     path::{KmpPathNode, RecalcPaths},
-    KmpComponent, KmpFile, KmpSectionIdEntityMap, RoutePoint, RouteSettings, Spawner,
+    value_colors::category_color,
+    KmpComponent, KmpFile, KmpSectionIdEntityMap, RouteLoopStyle, RoutePoint, RouteSettings, Spawner,
 };
 use bevy::{
     ecs::{entity::EntityHashSet, system::SystemParam},
@@ -13,7 +15,7 @@ use bevy::{
 use serde::{Deserialize, Serialize};
 
 pub fn routes_plugin(app: &mut App) {
-    app.add_systems(Update, update_routes)
+    app.add_systems(Update, (update_routes, draw_smoothed_routes, draw_route_colors))
         .observe(on_add_route_linked_entities)
         .observe(on_remove_route_linked_entities)
         .observe(on_add_route_link)
@@ -39,8 +41,8 @@ impl RouteLinkedEntities {
 
 #[derive(Bundle, Default)]
 pub struct RouteStartBundle {
-    route_linked_entities: RouteLinkedEntities,
-    route_settings: RouteSettings,
+    pub route_linked_entities: RouteLinkedEntities,
+    pub route_settings: RouteSettings,
 }
 
 fn on_add_route_linked_entities(
@@ -232,3 +234,116 @@ impl GetRouteStart<'_, '_> {
         start_es
     }
 }
+
+/// Number of line segments drawn between each pair of route points when showing the smoothed curve
+const SMOOTH_ROUTE_SUBDIVISIONS: usize = 16;
+
+/// Colour of a route when routes are coloured by their index, which stays the same as long as the index does
+pub fn route_color(index: usize) -> Color {
+    category_color(index as f32)
+}
+
+// positions of the points of a route in order, from its first point
+pub(super) fn route_positions(
+    start_e: Entity,
+    q_route_pt: &Query<(&Transform, &KmpPathNode), With<RoutePoint>>,
+) -> Vec<Vec3> {
+    let mut points = Vec::new();
+    let mut cur_e = Some(start_e);
+    while let Some((transform, node)) = cur_e.and_then(|e| q_route_pt.get(e).ok()) {
+        points.push(transform.translation);
+        cur_e = node.next_nodes.iter().next().copied().filter(|e| *e != start_e);
+    }
+    points
+}
+
+pub(super) fn smoothed_route_visible(settings: &AppSettings, route_settings: &RouteSettings) -> bool {
+    settings.kmp_model.smooth_routes && route_settings.smooth_motion
+}
+
+// draw the curve that the game interpolates through the points of routes with smooth motion enabled
+fn draw_smoothed_routes(
+    mut gizmos: Gizmos,
+    settings: Res<AppSettings>,
+    q_route_start: Query<(Entity, &RouteSettings, &Visibility, &OrderId)>,
+    q_route_pt: Query<(&Transform, &KmpPathNode), With<RoutePoint>>,
+) {
+    for (i, (start_e, route_settings, visibility, _)) in q_route_start.iter().sort::<&OrderId>().enumerate() {
+        if visibility == Visibility::Hidden || !smoothed_route_visible(&settings, route_settings) {
+            continue;
+        }
+        let points = route_positions(start_e, &q_route_pt);
+        if points.len() < 2 {
+            continue;
+        }
+        let curve = smooth_route_curve(&points, route_settings.loop_style == RouteLoopStyle::Cyclic);
+        let color = if settings.kmp_model.color_routes {
+            route_color(i)
+        } else {
+            settings.kmp_model.color.routes.line
+        };
+        gizmos.linestrip(curve, color);
+    }
+}
+
+// outline the points and lines of each route in its own colour, so that routes which cross over each other can be
+// told apart
+fn draw_route_colors(
+    mut gizmos: Gizmos,
+    settings: Res<AppSettings>,
+    q_route_start: Query<(Entity, &RouteSettings, &Visibility, &OrderId)>,
+    q_route_pt: Query<(&Transform, &KmpPathNode), With<RoutePoint>>,
+) {
+    if !settings.kmp_model.color_routes {
+        return;
+    }
+    let radius = 120. * settings.kmp_model.point_scale;
+    for (i, (start_e, route_settings, visibility, _)) in q_route_start.iter().sort::<&OrderId>().enumerate() {
+        if visibility == Visibility::Hidden {
+            continue;
+        }
+        let color = route_color(i);
+        let mut points = route_positions(start_e, &q_route_pt);
+        for point in points.iter() {
+            gizmos.sphere(*point, Quat::IDENTITY, radius, color);
+        }
+        // the smoothed curve is already drawn in the route's colour
+        if smoothed_route_visible(&settings, route_settings) {
+            continue;
+        }
+        if route_settings.loop_style == RouteLoopStyle::Cyclic && points.len() > 2 {
+            points.push(points[0]);
+        }
+        gizmos.linestrip(points, color);
+    }
+}
+
+/// Catmull-Rom spline through all the points. Cyclic routes go from the last point back to the first,
+/// while mirrored routes turn around at either end, so the curve is clamped there instead.
+fn smooth_route_curve(points: &[Vec3], cyclic: bool) -> Vec<Vec3> {
+    let len = points.len() as isize;
+    let get = |i: isize| {
+        if cyclic {
+            points[i.rem_euclid(len) as usize]
+        } else {
+            points[i.clamp(0, len - 1) as usize]
+        }
+    };
+    let segments = if cyclic { len } else { len - 1 };
+    let mut curve = Vec::with_capacity(segments as usize * SMOOTH_ROUTE_SUBDIVISIONS + 1);
+    for i in 0..segments {
+        let (p0, p1, p2, p3) = (get(i - 1), get(i), get(i + 1), get(i + 2));
+        for step in 0..SMOOTH_ROUTE_SUBDIVISIONS {
+            let t = step as f32 / SMOOTH_ROUTE_SUBDIVISIONS as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            curve.push(
+                0.5 * (2. * p1
+                    + (p2 - p0) * t
+                    + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+                    + (3. * p1 - p0 - 3. * p2 + p3) * t3),
+            );
+        }
+    }
+    curve.push(get(segments));
+    curve
+}
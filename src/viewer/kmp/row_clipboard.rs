@@ -0,0 +1,177 @@
+use super::{
+    components::{BattleFinishPoint, CannonPoint, EnemyPathPoint, ItemPathPoint, Object, RespawnPoint, StartPoint},
+    csv::{CsvPoint, ToFromCsvFormat},
+    ordering::OrderId,
+    sections::KmpEditMode,
+};
+use crate::{
+    ui::util::{get_euler_rot, set_euler_rot},
+    viewer::edit::select::Selected,
+};
+use anyhow::{bail, Context};
+use bevy::prelude::*;
+use csv::StringRecord;
+
+pub fn row_clipboard_plugin(app: &mut App) {
+    app.init_resource::<RowClipboard>();
+}
+
+/// Rows copied from the table, so they can be pasted again without going through the system clipboard
+#[derive(Resource, Default)]
+pub struct RowClipboard(pub Option<String>);
+
+// columns which are never pasted, as pasting onto existing points shouldn't change what they link to
+const SKIPPED_COLUMNS: &[&str] = &["next_points", "route"];
+// a headerless row with this many values is taken to be a position, which is what most coordinate dumps are
+const POSITION_COLUMNS: [&str; 3] = ["position_x", "position_y", "position_z"];
+
+// the selected points of a section in the order they are in the KMP
+fn selected_points<T: ToFromCsvFormat>(world: &mut World) -> Vec<(Entity, CsvPoint<T>)> {
+    let mut q = world.query_filtered::<(Entity, &OrderId, &T, &Transform), With<Selected>>();
+    let mut items: Vec<_> = q.iter(world).collect();
+    items.sort_by_key(|x| x.1);
+    items
+        .into_iter()
+        .map(|(e, _, component, transform)| {
+            let point = CsvPoint {
+                component: component.clone(),
+                pos: transform.translation,
+                rot: get_euler_rot(transform),
+                next_points: Vec::new(),
+                route: None,
+            };
+            (e, point)
+        })
+        .collect()
+}
+
+// a point as a row of its section's CSV format, along with the names of the columns
+fn to_record<T: ToFromCsvFormat>(point: CsvPoint<T>) -> anyhow::Result<(StringRecord, StringRecord)> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.serialize(T::to_csv_format(point))?;
+    let data = wtr.into_inner()?;
+    let mut rdr = csv::Reader::from_reader(&data[..]);
+    let header = rdr.headers()?.clone();
+    let record = rdr.records().next().context("point has no row")??;
+    Ok((header, record))
+}
+
+/// The selected rows of a section as tab separated values with a header, in the same columns as its CSV format
+pub fn copy_rows<T: ToFromCsvFormat>(world: &mut World) -> anyhow::Result<String> {
+    let points = selected_points::<T>(world);
+    if points.is_empty() {
+        bail!("no rows are selected");
+    }
+    let mut wtr = csv::WriterBuilder::new().delimiter(b'\t').from_writer(Vec::new());
+    for (_, point) in points {
+        wtr.serialize(T::to_csv_format(point)).context("could not write row")?;
+    }
+    let data = wtr.into_inner().context("could not write rows")?;
+    Ok(String::from_utf8(data)?)
+}
+
+/// Paste tab separated rows onto the selected rows of a section, in order. A single row is pasted onto every selected
+/// row, so settings can be copied from one point to many. Only the columns in the pasted rows are changed: rows with
+/// a header can have any of the section's columns in any order, rows without one are taken to be in the section's own
+/// column order, or to be just a position if they have three values. Returns how many rows were changed.
+pub fn paste_rows<T: ToFromCsvFormat>(world: &mut World, text: &str) -> anyhow::Result<usize> {
+    let targets = selected_points::<T>(world);
+    if targets.is_empty() {
+        bail!("select the rows to paste onto first");
+    }
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let mut rows = rdr
+        .records()
+        .filter(|x| !x.as_ref().is_ok_and(|x| x.iter().all(|x| x.is_empty())))
+        .collect::<Result<Vec<_>, _>>()
+        .context("could not read pasted rows")?;
+
+    // work out the new value of every target before changing any, so a bad row doesn't leave a paste half done
+    let mut changes = Vec::with_capacity(targets.len());
+    let mut columns = None;
+    for (i, (e, point)) in targets.into_iter().enumerate() {
+        let (section_header, record) = to_record(point)?;
+        let columns = columns.get_or_insert_with(|| {
+            let first = rows.first().cloned().unwrap_or_default();
+            if !first.is_empty() && first.iter().all(|x| section_header.iter().any(|y| y == x)) {
+                rows.remove(0);
+                first
+            } else if first.len() == POSITION_COLUMNS.len() {
+                StringRecord::from(POSITION_COLUMNS.to_vec())
+            } else {
+                section_header.clone()
+            }
+        });
+        let row = match rows.len() {
+            0 => bail!("there are no rows to paste"),
+            1 => &rows[0],
+            _ => match rows.get(i) {
+                Some(row) => row,
+                None => break,
+            },
+        };
+        let mut fields: Vec<_> = record.iter().map(String::from).collect();
+        for (name, value) in columns.iter().zip(row.iter()) {
+            if SKIPPED_COLUMNS.contains(&name) {
+                continue;
+            }
+            if let Some(ix) = section_header.iter().position(|x| x == name) {
+                fields[ix] = value.to_string();
+            }
+        }
+        let row = StringRecord::from(fields)
+            .deserialize::<T::CsvFormat>(Some(&section_header))
+            .with_context(|| format!("could not read pasted row {}", i.min(rows.len() - 1) + 1))?;
+        changes.push((e, T::from_csv_format(row)?));
+    }
+    let columns = columns.unwrap_or_default();
+    let has_pos = columns.iter().any(|x| x.starts_with("position_"));
+    let has_rot = columns.iter().any(|x| x.starts_with("rotation_"));
+
+    let count = changes.len();
+    for (e, point) in changes {
+        let mut entity = world.entity_mut(e);
+        *entity.get_mut::<T>().unwrap() = point.component;
+        let mut transform = entity.get_mut::<Transform>().unwrap();
+        if has_pos {
+            transform.translation = point.pos;
+        }
+        if has_rot {
+            set_euler_rot(point.rot, &mut transform);
+        }
+    }
+    Ok(count)
+}
+
+/// Copy the selected rows of whichever section the mode refers to, if its rows can be copied
+pub fn copy_section_rows(world: &mut World, mode: KmpEditMode) -> anyhow::Result<String> {
+    match mode {
+        KmpEditMode::StartPoints => copy_rows::<StartPoint>(world),
+        KmpEditMode::EnemyPaths => copy_rows::<EnemyPathPoint>(world),
+        KmpEditMode::ItemPaths => copy_rows::<ItemPathPoint>(world),
+        KmpEditMode::RespawnPoints => copy_rows::<RespawnPoint>(world),
+        KmpEditMode::Objects => copy_rows::<Object>(world),
+        KmpEditMode::CannonPoints => copy_rows::<CannonPoint>(world),
+        KmpEditMode::BattleFinishPoints => copy_rows::<BattleFinishPoint>(world),
+        _ => bail!("rows of {mode} can't be copied"),
+    }
+}
+
+/// Paste onto the selected rows of whichever section the mode refers to, if rows can be pasted onto it
+pub fn paste_section_rows(world: &mut World, mode: KmpEditMode, text: &str) -> anyhow::Result<usize> {
+    match mode {
+        KmpEditMode::StartPoints => paste_rows::<StartPoint>(world, text),
+        KmpEditMode::EnemyPaths => paste_rows::<EnemyPathPoint>(world, text),
+        KmpEditMode::ItemPaths => paste_rows::<ItemPathPoint>(world, text),
+        KmpEditMode::RespawnPoints => paste_rows::<RespawnPoint>(world, text),
+        KmpEditMode::Objects => paste_rows::<Object>(world, text),
+        KmpEditMode::CannonPoints => paste_rows::<CannonPoint>(world, text),
+        KmpEditMode::BattleFinishPoints => paste_rows::<BattleFinishPoint>(world, text),
+        _ => bail!("rows can't be pasted onto {mode}"),
+    }
+}
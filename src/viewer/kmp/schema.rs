@@ -0,0 +1,227 @@
+use super::components::{
+    AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object,
+    RespawnPoint, RoutePoint, RouteSettings, StartPoint, TrackInfo,
+};
+
+/// Documentation for a single field of a KMP component, shown as a tooltip wherever the field is edited
+pub struct FieldSchema {
+    /// The label the field is shown with in the edit tab
+    pub name: &'static str,
+    /// Other labels the same field is shown with, e.g. table column headers
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    /// The smallest and largest values the game accepts
+    pub range: Option<(f64, f64)>,
+    /// What the field does in game, where it is known
+    pub in_game: Option<&'static str>,
+}
+
+impl FieldSchema {
+    pub fn tooltip(&self) -> String {
+        let mut tooltip = self.description.to_string();
+        if let Some(in_game) = self.in_game {
+            tooltip += &format!("\n\nIn game: {in_game}");
+        }
+        if let Some((min, max)) = self.range {
+            tooltip += &format!("\n\nValid range: {min} to {max}");
+        }
+        tooltip
+    }
+    /// Whether the field is shown with this label
+    pub fn matches(&self, label: &str) -> bool {
+        // vec3 rows are labelled with the axis of the first row
+        let label = label.strip_suffix(" X").unwrap_or(label);
+        self.name == label || self.aliases.contains(&label)
+    }
+}
+
+/// The documentation of each field of a KMP component, in the order they are edited in
+pub trait KmpSchema {
+    const FIELDS: &'static [FieldSchema];
+    /// Find a field by the label it is shown with
+    fn field(label: &str) -> Option<&'static FieldSchema> {
+        Self::FIELDS.iter().find(|x| x.matches(label))
+    }
+}
+
+macro_rules! kmp_schema {
+    ($ty:ty { $($name:literal $(| $alias:literal)* => $desc:literal $(, range: $min:literal to $max:literal)? $(, in_game: $in_game:literal)?;)* }) => {
+        impl KmpSchema for $ty {
+            const FIELDS: &'static [FieldSchema] = &[$(FieldSchema {
+                name: $name,
+                aliases: &[$($alias),*],
+                description: $desc,
+                range: kmp_schema!(@opt $(($min as f64, $max as f64))?),
+                in_game: kmp_schema!(@opt $($in_game)?),
+            }),*];
+        }
+    };
+    (@opt) => { None };
+    (@opt $x:expr) => { Some($x) };
+}
+
+kmp_schema!(TrackInfo {
+    "Track Type" => "Whether the KMP is for a race track or a battle arena, which use different sections",
+        in_game: "Battle arenas don't use checkpoints, respawn points or item paths in the same way as race tracks";
+    "Lap Count" => "The number of laps in a race", range: 1 to 9;
+    "Speed Mod" => "Multiplies the top speed of every kart on the track, where 1 is normal speed",
+        in_game: "Stored in the track info as a truncated float, so very precise values are rounded";
+    "Lens Flare Colour" => "The colour of the lens flare when looking towards the sun";
+    "Lens Flare Flashing" => "Whether the lens flare flashes";
+    "First Player Pos" => "Which side of the start line the player in first place starts on";
+    "Narrow Player Spacing" => "Start the players closer together, for narrow start lines";
+});
+
+kmp_schema!(StartPoint {
+    "Player" => "Which player starts at this point, or every player",
+        range: -1 to 11,
+        in_game: "Race tracks have one start point for every player (-1), and the starting grid is placed behind it. Battle arenas have one for each player";
+});
+
+kmp_schema!(EnemyPathPoint {
+    "Leniency" => "How far the CPUs can stray from the point and still be following the path",
+        in_game: "Larger values let CPUs take wider lines through the point";
+    "Setting 1" => "Actions CPUs take at this point, like using mushrooms or doing wheelies";
+    "Setting 2" => "How CPUs drift at this point";
+    "Setting 3" => "Extra data used by a few Nintendo tracks, whose effect isn't fully known", range: 0 to 255;
+    "Dispatch Point" => "Marks a point in a battle arena where CPUs choose which way to go next",
+        in_game: "Each dispatch point is saved as a group of its own";
+});
+
+kmp_schema!(ItemPathPoint {
+    "Bullet Control" => "How far the Bullet Bill can stray from the point",
+        in_game: "Larger values let the Bullet Bill take wider lines through the point";
+    "Bullet Height" => "How the height of the Bullet Bill is worked out around the point";
+    "Bullet Can't Drop" => "Stops the Bullet Bill from running out at this point",
+        in_game: "Used over gaps and off road, so players aren't dropped somewhere they can't recover from";
+    "Low Shell Priority" => "Red and blue shells prefer other paths over this one where the path splits";
+});
+
+kmp_schema!(Checkpoint {
+    "Type" => "Normal checkpoints track the progress of players. Key checkpoints have to be crossed for a lap to count, and the lap count checkpoint is the finish line";
+    "Key Checkpoint ID" => "The order the key checkpoint has to be crossed in", range: 0 to 254,
+        in_game: "Players who skip a key checkpoint don't complete the lap";
+    "Respawn" => "The respawn point used when a player falls off the track after crossing this checkpoint";
+});
+
+kmp_schema!(RespawnPoint {
+    "Sound Trigger" => "The variant of Sound Trigger KCL the sound is set from when respawning here, or -1 for none. See Tools > Respawn Sound Triggers to check them",
+        range: -128 to 127;
+});
+
+kmp_schema!(Object {
+    "Scale" => "How much the model and collision of the object are stretched along each axis";
+    "ID" | "Object ID" => "Which object this is, e.g. 101 (0x65) for an item box",
+        range: 0 to 65535;
+    "Setting 1" => "A setting whose meaning depends on the object ID";
+    "Setting 2" => "A setting whose meaning depends on the object ID";
+    "Setting 3" => "A setting whose meaning depends on the object ID";
+    "Setting 4" => "A setting whose meaning depends on the object ID";
+    "Setting 5" => "A setting whose meaning depends on the object ID";
+    "Setting 6" => "A setting whose meaning depends on the object ID";
+    "Setting 7" => "A setting whose meaning depends on the object ID";
+    "Setting 8" => "A setting whose meaning depends on the object ID";
+    "Route" => "The route the object moves along, for objects which move";
+    "Presence" => "Which player counts the object appears in",
+        in_game: "Bit 0 is single player, bit 1 is two players, and bit 2 is three and four players";
+    "Extended Presence" => "Whether LE-CODE decides when the object appears from flags, from a definition object, or not at all. Leave this at None for objects which don't use the extension",
+        in_game: "Ignored by the original game, but used by LE-CODE distributions";
+    "Definition Object" => "The index of the definition object whose conditions decide when this object appears",
+        range: 0 to 4095;
+});
+
+kmp_schema!(RouteSettings {
+    "Smooth Motion" => "Move along a smooth curve through the points, rather than in straight lines between them";
+    "Loop Style" => "What happens at the end of the route, whether it loops back to the start or goes back the way it came";
+});
+
+kmp_schema!(RoutePoint {
+    "Settings" => "For objects, the speed they move to this point at. For cameras, the speed the camera moves at",
+        range: 0 to 65535;
+    "Additional Settings" => "Extra data whose meaning depends on the object, like how long to wait at the point",
+        range: 0 to 65535;
+});
+
+kmp_schema!(AreaPoint {
+    "Scale" => "The size of the area along each axis";
+    "Shape" => "Whether the area is a box or a cylinder";
+    "Priority" => "Where areas of the same type overlap, the one with the highest priority is used", range: 0 to 255;
+    "Type" => "What the area does to players or objects inside it";
+    "Camera Index" => "The camera used while a player is inside the area", range: 0 to 255,
+        in_game: "Used for replays and the view after finishing";
+    "Env Effect Object" => "The environment effect shown inside the area";
+    "BFG Entry" => "The entry of the fog file (posteffect.bfg) used inside the area", range: 0 to 65535;
+    "Setting 1" => "A setting whose meaning depends on the area type";
+    "Setting 2" => "A setting whose meaning depends on the area type";
+    "Route" => "The route a moving road carries things along";
+    "BBLM File" => "Which bloom file (posteffect.bblm) is used inside the area", range: 0 to 65535;
+    "Fade Time" => "How long the change to the bloom takes, in frames", range: 0 to 65535;
+    "Group ID" => "The group of objects shown or unloaded by the area", range: 0 to 65535;
+    "Always Show Area" => "Keep the outline of the area visible when it isn't selected. This is stored next to the KMP rather than in it";
+});
+
+kmp_schema!(KmpCamera {
+    "Intro Start" => "The camera the intro starts with, before the race";
+    "Type" => "How the camera moves and what it looks at";
+    "Next Index" => "The camera shown after this one in the intro", range: 0 to 255,
+        in_game: "255 means there is no next camera";
+    "Route" | "Route Index" => "The route the camera moves along";
+    "Time" => "How long the camera is shown for, in frames";
+    "Point Speed" => "How fast the camera moves along its route", range: 0 to 65535;
+    "Zoom Speed" => "How fast the field of view changes from the zoom start to the zoom end", range: 0 to 65535;
+    "View Speed" => "How fast the point the camera looks at moves from the view start to the view end", range: 0 to 65535;
+    "Zoom Start" => "The field of view the camera starts with, in degrees";
+    "Zoom End" => "The field of view the camera ends with, in degrees";
+    "View Start" => "The point the camera looks at to start with";
+    "View End" => "The point the camera looks at in the end";
+    "Shake (?)" | "Shake" => "Not fully understood, thought to make the camera shake", range: 0 to 255;
+    "Start (?)" | "Start" => "Not fully understood", range: 0 to 255;
+    "Movie (?)" | "Movie" => "Not fully understood, thought to be used for the opening movie", range: 0 to 255;
+});
+
+kmp_schema!(CannonPoint {
+    "Shoot Effect" => "How the player is launched towards the cannon point";
+});
+
+kmp_schema!(BattleFinishPoint {});
+
+// the name and field documentation of every KMP component
+fn all_field_schemas() -> [(&'static str, &'static [FieldSchema]); 13] {
+    [
+        ("Track Info", TrackInfo::FIELDS),
+        ("Start Point", StartPoint::FIELDS),
+        ("Enemy Point", EnemyPathPoint::FIELDS),
+        ("Item Point", ItemPathPoint::FIELDS),
+        ("Checkpoint", Checkpoint::FIELDS),
+        ("Respawn Point", RespawnPoint::FIELDS),
+        ("Object", Object::FIELDS),
+        ("Route Settings", RouteSettings::FIELDS),
+        ("Route Point", RoutePoint::FIELDS),
+        ("Area", AreaPoint::FIELDS),
+        ("Camera", KmpCamera::FIELDS),
+        ("Cannon Point", CannonPoint::FIELDS),
+        ("Battle Finish Point", BattleFinishPoint::FIELDS),
+    ]
+}
+
+/// Generate markdown documentation of every field of every KMP component
+pub fn field_docs_markdown() -> String {
+    let mut docs = String::from("# KMP Fields\n");
+    for (component, fields) in all_field_schemas() {
+        if fields.is_empty() {
+            continue;
+        }
+        docs += &format!("\n## {component}\n\n| Field | Description | In Game | Valid Range |\n|---|---|---|---|\n");
+        for field in fields {
+            let range = field.range.map(|(min, max)| format!("{min} to {max}"));
+            docs += &format!(
+                "| {} | {} | {} | {} |\n",
+                field.name,
+                field.description,
+                field.in_game.unwrap_or_default(),
+                range.unwrap_or_default()
+            );
+        }
+    }
+    docs
+}
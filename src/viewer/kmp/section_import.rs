@@ -0,0 +1,248 @@
+use super::{
+    checkpoints::spawn_checkpoint_section,
+    components::{
+        AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object,
+        PathOverallStart, RespawnPoint, RoutePoint, RouteSettings, StartPoint,
+    },
+    ordering::OrderId,
+    path::{spawn_enemy_item_path_section, RecalcPaths},
+    point::{spawn_point_section, AddRespawnPointPreview},
+    refresh_spawned_points,
+    routes::spawn_route_section,
+    sections::KmpEditMode,
+    KmpErrors, KmpSectionIdEntityMap,
+};
+use crate::{
+    ui::{
+        errors::show_errors,
+        file_dialog::{DialogType, FileDialogResult},
+        ui_state::KmpFilePath,
+    },
+    util::kmp_file::KmpFile,
+};
+use anyhow::{bail, Context};
+use bevy::{
+    ecs::{entity::EntityHashSet, event::ManualEventReader, world::Command},
+    prelude::*,
+    utils::HashMap,
+};
+use std::{fs, path::PathBuf};
+
+pub fn section_import_plugin(app: &mut App) {
+    app.add_event::<ApplySectionImport>().add_systems(
+        Update,
+        (
+            open_section_import
+                .pipe(show_errors)
+                .run_if(on_event::<FileDialogResult>()),
+            apply_section_import.run_if(on_event::<ApplySectionImport>()),
+        ),
+    );
+}
+
+/// Another KMP whose sections can be picked to be merged into the current one. The import is open while this
+/// resource exists.
+#[derive(Resource)]
+pub struct SectionImport {
+    pub path: PathBuf,
+    pub kmp: KmpFile,
+    /// The sections that will be imported
+    pub chosen: Vec<KmpEditMode>,
+}
+impl SectionImport {
+    /// How many entries the section has in the KMP being imported from
+    pub fn count(&self, section: KmpEditMode) -> usize {
+        let kmp = &self.kmp;
+        match section {
+            KmpEditMode::StartPoints => kmp.ktpt.len(),
+            KmpEditMode::EnemyPaths => kmp.enpt.len(),
+            KmpEditMode::ItemPaths => kmp.itpt.len(),
+            KmpEditMode::Checkpoints => kmp.ckpt.len(),
+            KmpEditMode::RespawnPoints => kmp.jgpt.len(),
+            KmpEditMode::Objects => kmp.gobj.len(),
+            KmpEditMode::Routes => kmp.poti.len(),
+            KmpEditMode::Areas => kmp.area.len(),
+            KmpEditMode::Cameras => kmp.came.len(),
+            KmpEditMode::CannonPoints => kmp.cnpt.len(),
+            KmpEditMode::BattleFinishPoints => kmp.mspt.len(),
+            KmpEditMode::TrackInfo => 0,
+        }
+    }
+    pub fn is_chosen(&self, section: KmpEditMode) -> bool {
+        self.chosen.contains(&section)
+    }
+    pub fn set_chosen(&mut self, section: KmpEditMode, chosen: bool) {
+        self.chosen.retain(|x| *x != section);
+        if chosen {
+            self.chosen.push(section);
+        }
+    }
+}
+
+/// Send this event to merge the chosen sections of the [`SectionImport`] into the current KMP, and close it
+#[derive(Event, Default)]
+pub struct ApplySectionImport;
+
+fn open_section_import(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<FileDialogResult>>,
+) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let Some(path) = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ImportSectionsKmp))
+        .map(|x| x.path.clone())
+        .last()
+    else {
+        return Ok(());
+    };
+    if !world.contains_resource::<KmpFilePath>() {
+        bail!("a KMP needs to be open to import into");
+    }
+    let data = fs::read(&path).context("could not open kmp file to import from")?;
+    let kmp = KmpFile::read_any(&data).context("could not read kmp file to import from")?;
+    world.insert_resource(SectionImport {
+        path,
+        kmp,
+        chosen: Vec::new(),
+    });
+    Ok(())
+}
+
+// the points of a section that are already in the world, in order, so indexes in the imported KMP can be pointed
+// at them when their section isn't being imported
+fn existing_id_map<T: Component, M: Component>(world: &mut World) -> KmpSectionIdEntityMap<M> {
+    let mut q = world.query_filtered::<(Entity, &OrderId), With<T>>();
+    let entities: HashMap<u32, Entity> = q
+        .iter(world)
+        .sort::<&OrderId>()
+        .enumerate()
+        .map(|(i, (e, _))| (i as u32, e))
+        .collect();
+    KmpSectionIdEntityMap::new(entities)
+}
+
+// spawn an imported section after the points already in it, returning the new points
+pub(super) fn import_section<T: Component>(world: &mut World, spawn: impl FnOnce(&mut World)) -> Vec<Entity> {
+    let existing: EntityHashSet = world.query_filtered::<Entity, With<T>>().iter(world).collect();
+    let next_id = world
+        .query_filtered::<&OrderId, With<T>>()
+        .iter(world)
+        .map(|x| x.0 + 1)
+        .max()
+        .unwrap_or_default();
+    let has_start = world
+        .query_filtered::<(), (With<T>, With<PathOverallStart>)>()
+        .iter(world)
+        .next()
+        .is_some();
+
+    spawn(world);
+
+    let spawned: Vec<_> = world
+        .query_filtered::<Entity, With<T>>()
+        .iter(world)
+        .filter(|e| !existing.contains(e))
+        .collect();
+    for e in spawned.iter() {
+        if let Some(mut order_id) = world.get_mut::<OrderId>(*e) {
+            order_id.0 += next_id;
+        }
+        // the path already starts somewhere, so the imported points just join on as extra groups
+        if has_start {
+            world.entity_mut(*e).remove::<PathOverallStart>();
+        }
+    }
+    spawned
+}
+
+fn apply_section_import(world: &mut World) {
+    world.resource_mut::<Events<ApplySectionImport>>().clear();
+    let Some(import) = world.remove_resource::<SectionImport>() else {
+        return;
+    };
+    let kmp = &import.kmp;
+    world.init_resource::<KmpErrors>();
+
+    // imported points which follow a route or respawn at a respawn point use the imported ones if those are being
+    // imported too, and otherwise whichever of the current ones has the same index
+    let route_id_map = if import.is_chosen(KmpEditMode::Routes) {
+        let mut route_id_map = None;
+        import_section::<RoutePoint>(world, |world| route_id_map = Some(spawn_route_section(world, kmp)));
+        route_id_map.unwrap()
+    } else {
+        existing_id_map::<RouteSettings, RoutePoint>(world)
+    };
+    world.insert_resource(route_id_map);
+
+    let respawn_id_map = if import.is_chosen(KmpEditMode::RespawnPoints) {
+        let mut respawn_id_map = None;
+        let spawned = import_section::<RespawnPoint>(world, |world| {
+            respawn_id_map = Some(spawn_point_section::<RespawnPoint>(world, kmp))
+        });
+        for e in spawned {
+            AddRespawnPointPreview(e).apply(world);
+        }
+        respawn_id_map.unwrap()
+    } else {
+        existing_id_map::<RespawnPoint, RespawnPoint>(world)
+    };
+    world.insert_resource(respawn_id_map);
+
+    for section in import.chosen.iter() {
+        match section {
+            KmpEditMode::StartPoints => {
+                import_section::<StartPoint>(world, |world| {
+                    spawn_point_section::<StartPoint>(world, kmp);
+                });
+            }
+            KmpEditMode::EnemyPaths => {
+                import_section::<EnemyPathPoint>(world, |world| {
+                    spawn_enemy_item_path_section::<EnemyPathPoint>(world, kmp)
+                });
+            }
+            KmpEditMode::ItemPaths => {
+                import_section::<ItemPathPoint>(world, |world| {
+                    spawn_enemy_item_path_section::<ItemPathPoint>(world, kmp)
+                });
+            }
+            KmpEditMode::Checkpoints => {
+                import_section::<Checkpoint>(world, |world| spawn_checkpoint_section(world, kmp));
+            }
+            KmpEditMode::Objects => {
+                import_section::<Object>(world, |world| {
+                    spawn_point_section::<Object>(world, kmp);
+                });
+            }
+            KmpEditMode::Areas => {
+                import_section::<AreaPoint>(world, |world| {
+                    spawn_point_section::<AreaPoint>(world, kmp);
+                });
+            }
+            KmpEditMode::Cameras => {
+                import_section::<KmpCamera>(world, |world| {
+                    spawn_point_section::<KmpCamera>(world, kmp);
+                });
+            }
+            KmpEditMode::CannonPoints => {
+                import_section::<CannonPoint>(world, |world| {
+                    spawn_point_section::<CannonPoint>(world, kmp);
+                });
+            }
+            KmpEditMode::BattleFinishPoints => {
+                import_section::<BattleFinishPoint>(world, |world| {
+                    spawn_point_section::<BattleFinishPoint>(world, kmp);
+                });
+            }
+            // already imported above, so that the other sections can link to them
+            KmpEditMode::Routes | KmpEditMode::RespawnPoints | KmpEditMode::TrackInfo => {}
+        }
+    }
+
+    world.remove_resource::<KmpErrors>();
+    world.remove_resource::<KmpSectionIdEntityMap<RoutePoint>>();
+    world.remove_resource::<KmpSectionIdEntityMap<RespawnPoint>>();
+
+    world.send_event(RecalcPaths::all());
+    refresh_spawned_points(world);
+}
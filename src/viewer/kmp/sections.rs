@@ -4,8 +4,8 @@ use bevy::{ecs::system::Resource, prelude::*};
 use strum_macros::{Display, EnumIter, EnumString, IntoStaticStr};
 
 use super::{
-    AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint, KmpCamera, Object,
-    RespawnPoint, RoutePoint, StartPoint, TrackInfo,
+    checkpoints::CheckpointRight, AreaPoint, BattleFinishPoint, CannonPoint, Checkpoint, EnemyPathPoint, ItemPathPoint,
+    KmpCamera, Object, RespawnPoint, RoutePoint, StartPoint, TrackInfo, TrackType,
 };
 
 pub fn section_plugin(app: &mut App) {
@@ -89,6 +89,20 @@ impl KmpEditMode {
     pub fn set_mode<T: 'static>(&mut self) {
         *self = Self::from_type::<T>();
     }
+    /// If the game ignores this section for the given type of track, a note saying so
+    pub fn unused_note(self, track_type: &TrackType) -> Option<&'static str> {
+        match (self, track_type) {
+            (Self::Checkpoints, TrackType::Battle) => Some("Checkpoints are not used in battle arenas"),
+            (Self::BattleFinishPoints, TrackType::Race) => Some("Battle finish points are only used in battle arenas"),
+            _ => None,
+        }
+    }
+    /// Whether the entity is a point in this section
+    pub fn contains_entity(self, entity: &EntityRef) -> bool {
+        // only the left point of a checkpoint has the checkpoint component
+        entity.contains_type_id(self.to_type_id())
+            || (self == Self::Checkpoints && entity.contains::<CheckpointRight>())
+    }
 }
 
 macro_rules! add_for_all_components {
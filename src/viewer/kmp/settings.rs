@@ -1,3 +1,4 @@
+use super::value_colors::ColorByValueSettings;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +9,30 @@ pub struct KmpModelSettings {
     pub color: KmpModelColors,
     pub outline: OutlineSettings,
     pub checkpoint_height: f32,
+    /// Whether to show faded ghosts of where points were when the KMP was last saved
+    pub show_saved_ghosts: bool,
+    /// Colour points by the value of one of their fields instead of by section
+    pub color_by_value: ColorByValueSettings,
+    /// Draw routes with smooth motion enabled as the curve the game follows, instead of straight lines
+    pub smooth_routes: bool,
+    /// Give the points and lines of each route a different colour, and label each route with its index
+    pub color_routes: bool,
+    /// Fill in the area between each checkpoint and the next, to find gaps where checkpoints could be skipped
+    pub show_checkpoint_quads: bool,
+    /// Show the space a Bullet Bill travels through along the item paths, while editing item paths
+    pub show_bullet_corridor: bool,
+    /// Show the type, time and index of each camera next to it in the viewport
+    pub show_camera_labels: bool,
+    /// Show a simple model for objects whose ID is recognised, at the object's scale
+    pub show_object_models: bool,
+    /// Show every area as a translucent volume, coloured by its kind
+    pub show_area_volumes: bool,
+    /// Highlight where areas of the same kind but different priorities overlap
+    pub show_area_overlaps: bool,
+    /// Draw path links as GPU lines rather than meshes, apart from links to selected points
+    pub gpu_path_links: bool,
+    /// Width of the path links drawn as GPU lines, in pixels
+    pub path_link_width: f32,
 }
 impl Default for KmpModelSettings {
     fn default() -> Self {
@@ -17,6 +42,18 @@ impl Default for KmpModelSettings {
             color: KmpModelColors::default(),
             outline: OutlineSettings::default(),
             checkpoint_height: 10000.,
+            show_saved_ghosts: false,
+            color_by_value: ColorByValueSettings::default(),
+            smooth_routes: true,
+            color_routes: true,
+            show_checkpoint_quads: false,
+            show_bullet_corridor: false,
+            show_camera_labels: true,
+            show_object_models: true,
+            show_area_volumes: false,
+            show_area_overlaps: false,
+            gpu_path_links: true,
+            path_link_width: 3.,
         }
     }
 }
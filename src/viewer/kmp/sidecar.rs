@@ -0,0 +1,93 @@
+use super::{concurrent_edit::EditorSession, history::SaveRecord, point_metadata::PointMetadata};
+use crate::viewer::{
+    camera::{CameraBookmark, HomeCamera, BOOKMARK_SLOTS},
+    edit::guides::Guide,
+};
+use anyhow::Context;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Editor-only data which has no place in the KMP itself, so is stored in a separate file alongside it.
+/// Missing fields fall back to their defaults so that older sidecar files can still be read.
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct KmpSidecar {
+    /// Indices of the areas which have 'Always Show Area' enabled
+    pub shown_areas: Vec<u32>,
+    /// Names, notes and colours given to points in the editor
+    pub points: Vec<PointMetadata>,
+    /// The camera position to go to when the file is opened or the Home key is pressed
+    pub home_camera: Option<HomeCamera>,
+    /// Named camera positions which can be gone to with the number keys
+    pub camera_bookmarks: [Option<CameraBookmark>; BOOKMARK_SLOTS],
+    /// Guide lines placed in the top down camera
+    pub guides: Vec<Guide>,
+    /// When the KMP was saved, by who, and roughly what changed each time, oldest first
+    pub history: Vec<SaveRecord>,
+    /// The kmpeek which currently has the KMP open for editing, if any
+    pub editor: Option<EditorSession>,
+    /// Whether the KMP was opened without being able to save it, which isn't stored
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+impl KmpSidecar {
+    /// The sidecar for 'course.kmp' is stored as 'course.kmp.kmpeek' in the same directory, so that 'course.szs'
+    /// next to it gets its own sidecar
+    pub fn path(kmp_path: &Path) -> PathBuf {
+        let mut file_name = kmp_path.file_name().unwrap_or_default().to_owned();
+        file_name.push(".kmpeek");
+        kmp_path.with_file_name(file_name)
+    }
+    // where sidecars used to be stored, replacing the extension of the KMP
+    fn old_path(kmp_path: &Path) -> PathBuf {
+        kmp_path.with_extension("kmpeek")
+    }
+    /// Load the sidecar for a KMP file, falling back to the default if it doesn't exist or can't be read. A sidecar
+    /// stored under the old name is read if there isn't one under the new name, and is saved under the new name.
+    pub fn load(kmp_path: &Path) -> Self {
+        read_to_string(Self::path(kmp_path))
+            .or_else(|_| read_to_string(Self::old_path(kmp_path)))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+    /// Save the sidecar for a KMP file. Which kmpeek is editing the KMP is kept as it is in the file, as that is
+    /// changed separately with [`KmpSidecar::save_editor`].
+    pub fn save(&self, kmp_path: &Path) -> anyhow::Result<()> {
+        Self {
+            editor: Self::load(kmp_path).editor,
+            ..self.clone()
+        }
+        .write(kmp_path)
+    }
+    /// Change which kmpeek is editing a KMP file, leaving the rest of its sidecar as it is in the file
+    pub fn save_editor(kmp_path: &Path, editor: Option<EditorSession>) -> anyhow::Result<()> {
+        Self {
+            editor,
+            ..Self::load(kmp_path)
+        }
+        .write(kmp_path)
+    }
+    fn write(&self, kmp_path: &Path) -> anyhow::Result<()> {
+        let sidecar_string = serde_json::to_string_pretty(self).context("could not convert sidecar to json")?;
+        let mut file = File::create(Self::path(kmp_path)).context("could not create sidecar file")?;
+        file.write_all(sidecar_string.as_bytes())
+            .context("could not write to sidecar file")?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sidecar_path() {
+    let kmp = Path::new("tracks/course.kmp");
+    let szs = Path::new("tracks/course.szs");
+    assert_eq!(KmpSidecar::path(kmp), Path::new("tracks/course.kmp.kmpeek"));
+    assert_eq!(KmpSidecar::path(szs), Path::new("tracks/course.szs.kmpeek"));
+    assert_ne!(KmpSidecar::path(kmp), KmpSidecar::path(szs));
+}
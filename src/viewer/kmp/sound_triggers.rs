@@ -0,0 +1,111 @@
+use super::{
+    components::{KmpSelectablePoint, RespawnPoint},
+    ordering::OrderId,
+};
+use crate::util::kcl_file::{kcl_variant, Kcl, KclFlag};
+use bevy::{prelude::*, utils::HashMap};
+use std::collections::BTreeSet;
+
+pub fn sound_triggers_plugin(app: &mut App) {
+    app.add_event::<AssignSoundTriggers>()
+        .add_systems(Update, assign_sound_triggers.run_if(on_event::<AssignSoundTriggers>()));
+}
+
+/// The highest sound trigger ID a respawn point can have. The KMP stores it as (ID + 1) * 100, which has to fit in
+/// the respawn's extra data.
+pub const MAX_SOUND_TRIGGER: i8 = i8::MAX;
+/// The sound trigger of a respawn point which doesn't have one
+pub const NO_SOUND_TRIGGER: i8 = -1;
+
+/// Lists the sound trigger of every respawn point, so they can be edited without dealing with how they are stored in
+/// the KMP. The window is open while this resource exists.
+#[derive(Resource, Default)]
+pub struct SoundTriggerEditor;
+
+/// Send this event to give each of these respawn points a sound trigger ID that no other respawn point uses
+#[derive(Event)]
+pub struct AssignSoundTriggers(pub Vec<Entity>);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SoundTriggerConflict {
+    /// Another respawn point, with this index, already uses the same ID
+    Duplicate(u32),
+    /// The KCL has no Sound Trigger collision of this variant for the ID to refer to
+    NotInKcl,
+}
+
+pub struct RespawnSoundTrigger {
+    pub e: Entity,
+    pub index: u32,
+    pub id: Option<i8>,
+    pub conflict: Option<SoundTriggerConflict>,
+}
+
+/// The variants of Sound Trigger KCL, which the sound trigger IDs of respawn points refer to
+pub fn kcl_sound_triggers(kcl: &Kcl) -> BTreeSet<i8> {
+    kcl.vertex_groups[KclFlag::SoundTrigger as usize]
+        .flags
+        .iter()
+        .map(|flag| kcl_variant(*flag) as i8)
+        .collect()
+}
+
+/// Every respawn point in order along with its sound trigger, and what is wrong with it if anything
+pub fn respawn_sound_triggers(world: &mut World) -> Vec<RespawnSoundTrigger> {
+    let mut respawns: Vec<_> = world
+        .query_filtered::<(Entity, &RespawnPoint, &OrderId), With<KmpSelectablePoint>>()
+        .iter(world)
+        .map(|(e, respawn, order_id)| (e, respawn.sound_trigger, order_id.0))
+        .collect();
+    respawns.sort_by_key(|x| x.2);
+    let kcl_ids = world.get_resource::<Kcl>().map(kcl_sound_triggers);
+
+    let mut first_with_id = HashMap::new();
+    respawns
+        .into_iter()
+        .map(|(e, sound_trigger, index)| {
+            let id = (sound_trigger >= 0).then_some(sound_trigger);
+            let conflict = id.and_then(|id| {
+                if let Some(first) = first_with_id.get(&id) {
+                    return Some(SoundTriggerConflict::Duplicate(*first));
+                }
+                first_with_id.insert(id, index);
+                // without a KCL loaded there's nothing to check the ID against
+                let in_kcl = kcl_ids.as_ref().map_or(true, |x| x.contains(&id));
+                (!in_kcl).then_some(SoundTriggerConflict::NotInKcl)
+            });
+            RespawnSoundTrigger { e, index, id, conflict }
+        })
+        .collect()
+}
+
+// the IDs no other respawn point uses, with the ones the KCL has Sound Trigger collision for first, so that they
+// actually do something in game
+fn free_sound_triggers(used: &BTreeSet<i8>, kcl_ids: &BTreeSet<i8>) -> Vec<i8> {
+    let (in_kcl, rest): (Vec<_>, Vec<_>) = (0..=MAX_SOUND_TRIGGER)
+        .filter(|id| !used.contains(id))
+        .partition(|id| kcl_ids.contains(id));
+    in_kcl.into_iter().chain(rest).collect()
+}
+
+fn assign_sound_triggers(world: &mut World) {
+    let entities: Vec<_> = world
+        .resource_mut::<Events<AssignSoundTriggers>>()
+        .drain()
+        .flat_map(|x| x.0)
+        .collect();
+    let kcl_ids = world.get_resource::<Kcl>().map(kcl_sound_triggers).unwrap_or_default();
+    // the respawns being given new IDs let go of their old ones
+    let used: BTreeSet<_> = respawn_sound_triggers(world)
+        .iter()
+        .filter(|x| !entities.contains(&x.e))
+        .filter_map(|x| x.id)
+        .collect();
+    let free = free_sound_triggers(&used, &kcl_ids);
+
+    for (e, id) in entities.into_iter().zip(free) {
+        if let Some(mut respawn) = world.get_mut::<RespawnPoint>(e) {
+            respawn.sound_trigger = id;
+        }
+    }
+}
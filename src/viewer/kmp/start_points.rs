@@ -0,0 +1,147 @@
+use super::{
+    components::{FirstPlayerPos, KmpSelectablePoint, StartPoint, TrackInfo, TrackType},
+    ordering::OrderId,
+    sections::KmpEditMode,
+};
+use crate::ui::settings::AppSettings;
+use bevy::{math::vec3, prelude::*};
+use std::{collections::BTreeMap, f32::consts::FRAC_PI_2};
+
+pub fn start_points_plugin(app: &mut App) {
+    app.add_systems(Update, draw_start_slots.run_if(in_start_points_mode));
+}
+
+fn in_start_points_mode(mode: Res<KmpEditMode>) -> bool {
+    *mode == KmpEditMode::StartPoints
+}
+
+/// Value of player_index for a start point which every player starts from
+pub const ALL_PLAYERS: i16 = -1;
+/// Battle arenas have a start point for each player, with player indexes from 0 up to this
+pub const NUM_PLAYERS: i16 = 12;
+
+pub fn player_index_name(index: i16) -> String {
+    match index {
+        ALL_PLAYERS => "All Players".into(),
+        0..NUM_PLAYERS => format!("Player {}", index + 1),
+        _ => format!("Unknown ({index})"),
+    }
+}
+
+/// Every player index with a meaning in game
+pub fn player_index_options() -> impl Iterator<Item = i16> {
+    std::iter::once(ALL_PLAYERS).chain(0..NUM_PLAYERS)
+}
+
+/// Problems with the player indexes of the start points for the current track type
+pub fn start_point_warnings(world: &mut World) -> Vec<String> {
+    let Some(track_type) = world.get_resource::<TrackInfo>().map(|x| x.track_type.clone()) else {
+        return Vec::new();
+    };
+    let mut counts: BTreeMap<i16, usize> = BTreeMap::new();
+    for start_point in world.query::<&StartPoint>().iter(world) {
+        *counts.entry(start_point.player_index).or_default() += 1;
+    }
+    let all_players = counts.get(&ALL_PLAYERS).copied().unwrap_or_default();
+
+    let mut warnings = Vec::new();
+    let unknown: Vec<_> = counts
+        .keys()
+        .filter(|x| !(ALL_PLAYERS..NUM_PLAYERS).contains(x))
+        .collect();
+    if !unknown.is_empty() {
+        warnings.push(format!("Unknown player indexes: {unknown:?}"));
+    }
+    match track_type {
+        TrackType::Race => {
+            if all_players == 0 {
+                warnings.push("Race tracks need a start point for 'All Players'".into());
+            } else if all_players > 1 {
+                warnings.push("Only the first start point for 'All Players' is used".into());
+            }
+            if counts.keys().any(|x| (0..NUM_PLAYERS).contains(x)) {
+                warnings.push("Start points for single players are only used in battle arenas".into());
+            }
+        }
+        TrackType::Battle => {
+            if all_players > 0 {
+                warnings.push("Battle arenas need a start point for each player rather than 'All Players'".into());
+            }
+            let missing: Vec<_> = (0..NUM_PLAYERS)
+                .filter(|x| !counts.contains_key(x))
+                .map(player_index_name)
+                .collect();
+            if !missing.is_empty() {
+                warnings.push(format!("No start point for {}", missing.join(", ")));
+            }
+            let duplicated: Vec<_> = (0..NUM_PLAYERS)
+                .filter(|x| counts.get(x).is_some_and(|count| *count > 1))
+                .map(player_index_name)
+                .collect();
+            if !duplicated.is_empty() {
+                warnings.push(format!("More than one start point for {}", duplicated.join(", ")));
+            }
+        }
+    }
+    warnings
+}
+
+// roughly how far apart the game puts players on the starting grid, in the start point's local space
+// where +Z is the direction the players face
+const GRID_SIDE_SPACING: f32 = 400.;
+const GRID_NARROW_SIDE_SPACING: f32 = 250.;
+const GRID_BACK_SPACING: f32 = 300.;
+
+/// Roughly where each player starts from a start point, as (player number from 0, position).
+/// A start point for all players lays out a staggered grid behind it, otherwise the player starts on the point.
+pub fn start_slots(start_point: &StartPoint, transform: &Transform, track_info: &TrackInfo) -> Vec<(i16, Vec3)> {
+    if start_point.player_index != ALL_PLAYERS {
+        return vec![(start_point.player_index, transform.translation)];
+    }
+    let side_spacing = if track_info.narrow_player_spacing {
+        GRID_NARROW_SIDE_SPACING
+    } else {
+        GRID_SIDE_SPACING
+    };
+    let first_side = match track_info.first_player_pos {
+        FirstPlayerPos::Left => -1.,
+        FirstPlayerPos::Right => 1.,
+    };
+    (0..NUM_PLAYERS)
+        .map(|i| {
+            let side = if i % 2 == 0 { first_side } else { -first_side };
+            let local = vec3(side * side_spacing / 2., 0., -(i as f32) * GRID_BACK_SPACING);
+            (i, transform.transform_point(local))
+        })
+        .collect()
+}
+
+// outline where each player starts, so it's clear which slots each start point controls
+fn draw_start_slots(
+    mut gizmos: Gizmos,
+    q_start_points: Query<(&StartPoint, &Transform, &OrderId), With<KmpSelectablePoint>>,
+    track_info: Option<Res<TrackInfo>>,
+    settings: Res<AppSettings>,
+) {
+    let Some(track_info) = track_info else {
+        return;
+    };
+    let color = settings.kmp_model.color.start_points.line;
+    // only the first start point for all players is used
+    let first_all_players = q_start_points
+        .iter()
+        .filter(|x| x.0.player_index == ALL_PLAYERS)
+        .min_by_key(|x| x.2 .0)
+        .map(|x| x.2 .0);
+
+    for (start_point, transform, order_id) in q_start_points.iter() {
+        if start_point.player_index == ALL_PLAYERS && Some(order_id.0) != first_all_players {
+            continue;
+        }
+        // lay the rectangles flat, facing up
+        let rotation = transform.rotation * Quat::from_rotation_x(FRAC_PI_2);
+        for (_, pos) in start_slots(start_point, transform, &track_info) {
+            gizmos.rect(pos, rotation, Vec2::new(150., 250.), color);
+        }
+    }
+}
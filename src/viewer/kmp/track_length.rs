@@ -0,0 +1,119 @@
+use super::{
+    checkpoints::CheckpointLeft,
+    components::{Checkpoint, TrackInfo},
+    path::EntityPathGroups,
+};
+use bevy::{color::palettes::css, ecs::entity::EntityHashSet, prelude::*};
+
+pub fn track_length_plugin(app: &mut App) {
+    app.add_event::<MeasureTrackLength>().add_systems(
+        Update,
+        (
+            measure_track_length.run_if(on_event::<MeasureTrackLength>()),
+            draw_track_midline.run_if(resource_exists::<TrackStats>),
+        ),
+    );
+}
+
+/// Send this event to measure a lap along the middle of the checkpoints, and estimate how long it takes to drive
+#[derive(Event, Default)]
+pub struct MeasureTrackLength;
+
+/// Roughly how fast a kart goes around a lap on average at 150cc in units per second, taking into account corners,
+/// items and mini-turbos, which is the game's top speed of around 84 units per frame brought down a little
+pub const DEFAULT_KART_SPEED: f32 = 4800.;
+/// Most tracks have laps between these lengths in seconds at 150cc. Anything outside them is likely to feel too short
+/// or drag on, unless it's intentional (like a single lap track).
+pub const NORMAL_LAP_TIME: (f32, f32) = (25., 50.);
+
+/// The result of the last track length measurement. Remove this resource to dismiss it.
+#[derive(Resource)]
+pub struct TrackStats {
+    /// The midpoints of the checkpoints driven through in one lap, in order
+    pub midline: Vec<Vec3>,
+    /// The length of the lap in units
+    pub lap_length: f32,
+    /// Whether the checkpoints loop back round to the start. If not, the length is only up to the last checkpoint.
+    pub closed: bool,
+    /// Whether the checkpoints split anywhere, in which case only the first way at each split is measured
+    pub branches: bool,
+    pub lap_count: u8,
+    pub speed_mod: f32,
+    /// The average speed assumed for the estimate, in units per second
+    pub kart_speed: f32,
+}
+impl TrackStats {
+    /// The estimated time of one lap in seconds
+    pub fn lap_time(&self) -> f32 {
+        // a speed modifier of 0 means the speed is left as normal
+        let speed_mod = if self.speed_mod > 0. { self.speed_mod } else { 1. };
+        self.lap_length / (self.kart_speed * speed_mod).max(1.)
+    }
+    /// The estimated time of the whole race in seconds
+    pub fn race_time(&self) -> f32 {
+        self.lap_time() * self.lap_count.max(1) as f32
+    }
+    pub fn is_lap_time_normal(&self) -> bool {
+        (NORMAL_LAP_TIME.0..=NORMAL_LAP_TIME.1).contains(&self.lap_time())
+    }
+}
+
+fn measure_track_length(
+    mut commands: Commands,
+    cp_groups: Option<Res<EntityPathGroups<Checkpoint>>>,
+    q_left: Query<(&Transform, &CheckpointLeft)>,
+    q_transform: Query<&Transform>,
+    track_info: Option<Res<TrackInfo>>,
+    prev_stats: Option<Res<TrackStats>>,
+) {
+    let midpoint = |e: Entity| {
+        let (left, cp) = q_left.get(e).ok()?;
+        let right = q_transform.get(cp.right).ok()?;
+        Some(left.translation.lerp(right.translation, 0.5))
+    };
+
+    // follow the checkpoint groups from the start, taking the first way at each split, until they get back to a group
+    // that has already been driven through
+    let mut midline = Vec::new();
+    let mut closed = false;
+    let mut branches = false;
+    if let Some(groups) = cp_groups.as_ref().filter(|x| !x.is_empty()) {
+        let mut visited = EntityHashSet::default();
+        let mut i = 0;
+        loop {
+            let group = &groups[i];
+            if group.path.first().is_some_and(|e| !visited.insert(*e)) {
+                closed = i == 0;
+                break;
+            }
+            midline.extend(group.path.iter().filter_map(|e| midpoint(*e)));
+            branches |= group.next_paths.len() > 1;
+            let Some(next) = group.next_paths.first() else {
+                break;
+            };
+            i = *next;
+        }
+    }
+    let mut lap_length: f32 = midline.windows(2).map(|x| x[0].distance(x[1])).sum();
+    if closed && midline.len() > 1 {
+        lap_length += midline.last().unwrap().distance(midline[0]);
+    }
+
+    commands.insert_resource(TrackStats {
+        midline,
+        lap_length,
+        closed,
+        branches,
+        lap_count: track_info.as_ref().map(|x| x.lap_count).unwrap_or(3),
+        speed_mod: track_info.as_ref().map(|x| x.speed_mod).unwrap_or(0.),
+        // keep the speed from the last time, in case it was changed
+        kart_speed: prev_stats.map(|x| x.kart_speed).unwrap_or(DEFAULT_KART_SPEED),
+    });
+}
+
+fn draw_track_midline(mut gizmos: Gizmos, stats: Res<TrackStats>) {
+    gizmos.linestrip(stats.midline.iter().copied(), css::LIME);
+    if stats.closed && stats.midline.len() > 1 {
+        gizmos.line(*stats.midline.last().unwrap(), stats.midline[0], css::LIME);
+    }
+}
@@ -0,0 +1,35 @@
+use super::{
+    components::{TrackInfo, TrackType},
+    path::RecalcPaths,
+    validate::{KmpIssues, ValidateKmp},
+};
+use bevy::prelude::*;
+
+pub fn track_type_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        on_track_type_change.run_if(resource_exists_and_changed::<TrackInfo>),
+    );
+}
+
+// enemy paths are split up at dispatch points in battle arenas, and which sections are checked depends on the track
+// type, so both need redoing when it is changed
+fn on_track_type_change(
+    track_info: Res<TrackInfo>,
+    mut last_track_type: Local<Option<TrackType>>,
+    issues: Res<KmpIssues>,
+    mut ev_recalc_paths: EventWriter<RecalcPaths>,
+    mut ev_validate: EventWriter<ValidateKmp>,
+) {
+    let track_type = track_info.track_type.clone();
+    let Some(last) = last_track_type.replace(track_type.clone()) else {
+        return;
+    };
+    if last == track_type {
+        return;
+    }
+    ev_recalc_paths.send(RecalcPaths::enemy());
+    if issues.checked {
+        ev_validate.send_default();
+    }
+}
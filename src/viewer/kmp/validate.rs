@@ -0,0 +1,376 @@
+use super::{
+    build_kmp,
+    checkpoints::CheckpointRespawnLink,
+    components::{
+        AreaKind, AreaPoint, BattleFinishPoint, Checkpoint, CheckpointKind, EnemyPathPoint, ItemPathPoint, KmpCamera,
+        KmpSelectablePoint, Object, RespawnPoint, RouteSettings, StartPoint, TrackInfo, TrackType,
+    },
+    custom_rules::custom_rule_issues,
+    header_values::header_value_changes,
+    path::EntityPathGroups,
+    routes::RouteLink,
+    sections::KmpEditMode,
+    start_points::start_point_warnings,
+    SaveFile,
+};
+use crate::ui::ui_state::KmpFilePath;
+use bevy::prelude::*;
+use strum_macros::Display;
+
+pub fn validate_plugin(app: &mut App) {
+    app.add_event::<ValidateKmp>().init_resource::<KmpIssues>().add_systems(
+        Update,
+        (
+            clear_issues.run_if(resource_exists_and_changed::<KmpFilePath>),
+            validate_kmp.run_if(on_event::<ValidateKmp>().or_else(on_event::<SaveFile>())),
+        )
+            .chain(),
+    );
+}
+
+/// Send this event to check the KMP for common mistakes. This is also done whenever the KMP is saved.
+#[derive(Event, Default)]
+pub struct ValidateKmp;
+
+// the KMP stores point indexes and group lengths in a single byte
+pub const MAX_POINT_INDEX: usize = 255;
+
+#[derive(Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+pub struct KmpIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+    /// The section the problem is in, to switch to when selecting the entities
+    pub mode: KmpEditMode,
+    /// The points which are causing the problem
+    pub entities: Vec<Entity>,
+}
+
+/// The problems found the last time the KMP was checked, errors first
+#[derive(Resource, Default)]
+pub struct KmpIssues {
+    /// Whether the KMP has been checked since it was opened
+    pub checked: bool,
+    pub issues: Vec<KmpIssue>,
+}
+
+struct IssueList(Vec<KmpIssue>);
+impl IssueList {
+    fn add(&mut self, severity: IssueSeverity, mode: KmpEditMode, message: impl Into<String>, entities: Vec<Entity>) {
+        self.0.push(KmpIssue {
+            severity,
+            message: message.into(),
+            mode,
+            entities,
+        });
+    }
+    fn error(&mut self, mode: KmpEditMode, message: impl Into<String>, entities: Vec<Entity>) {
+        self.add(IssueSeverity::Error, mode, message, entities);
+    }
+    fn warning(&mut self, mode: KmpEditMode, message: impl Into<String>, entities: Vec<Entity>) {
+        self.add(IssueSeverity::Warning, mode, message, entities);
+    }
+}
+
+fn points<T: Component>(world: &mut World) -> Vec<Entity> {
+    world
+        .query_filtered::<Entity, (With<T>, With<KmpSelectablePoint>)>()
+        .iter(world)
+        .collect()
+}
+
+// points in a section the game ignores for this type of track are harmless, but probably not what was intended
+fn check_unused_section<T: Component>(world: &mut World, issues: &mut IssueList, track_type: &TrackType) {
+    let mode = KmpEditMode::from_type::<T>();
+    let Some(note) = mode.unused_note(track_type) else {
+        return;
+    };
+    let entities = points::<T>(world);
+    if !entities.is_empty() {
+        issues.warning(mode, note, entities);
+    }
+}
+
+// paths can't have more points than can be indexed, and each group stores its length in a byte
+fn check_path_groups<T: Component>(world: &mut World, issues: &mut IssueList, name: &str) {
+    let mode = KmpEditMode::from_type::<T>();
+    let Some(groups) = world.get_resource::<EntityPathGroups<T>>() else {
+        return;
+    };
+    let mut total = 0;
+    for (i, group) in groups.iter().enumerate() {
+        total += group.path.len();
+        if group.path.len() > MAX_POINT_INDEX {
+            issues.error(
+                mode,
+                format!(
+                    "{name} group {i} has {} points, but groups can have at most {MAX_POINT_INDEX}",
+                    group.path.len()
+                ),
+                group.path.clone(),
+            );
+        }
+    }
+    if total > MAX_POINT_INDEX + 1 {
+        issues.error(
+            mode,
+            format!(
+                "There are {total} {name} points, but points after index {MAX_POINT_INDEX} can't be reached by groups"
+            ),
+            Vec::new(),
+        );
+    }
+}
+
+fn check_checkpoints(world: &mut World, issues: &mut IssueList) {
+    let mode = KmpEditMode::Checkpoints;
+    let checkpoints: Vec<_> = world
+        .query::<(Entity, &Checkpoint, Option<&CheckpointRespawnLink>)>()
+        .iter(world)
+        .map(|(e, cp, link)| (e, cp.kind.clone(), link.map(|x| x.0)))
+        .collect();
+    if checkpoints.is_empty() {
+        issues.error(mode, "There are no checkpoints", Vec::new());
+        return;
+    }
+
+    let lap_counts: Vec<_> = checkpoints
+        .iter()
+        .filter(|x| x.1 == CheckpointKind::LapCount)
+        .map(|x| x.0)
+        .collect();
+    match lap_counts.len() {
+        0 => issues.error(mode, "There is no lap count checkpoint", Vec::new()),
+        1 => {}
+        n => issues.warning(
+            mode,
+            format!("There are {n} lap count checkpoints, only one is used"),
+            lap_counts,
+        ),
+    }
+
+    let unlinked: Vec<_> = checkpoints
+        .iter()
+        .filter(|x| {
+            x.2.is_none_or(|respawn_e| world.get::<RespawnPoint>(respawn_e).is_none())
+        })
+        .map(|x| x.0)
+        .collect();
+    if !unlinked.is_empty() {
+        issues.error(
+            mode,
+            format!("{} checkpoints aren't linked to a respawn point", unlinked.len()),
+            unlinked,
+        );
+    }
+}
+
+fn check_route_links(world: &mut World, issues: &mut IssueList) {
+    let mut q_linked = world.query::<(Entity, &RouteLink, Has<Object>, Has<KmpCamera>, Has<AreaPoint>)>();
+    let linked: Vec<_> = q_linked
+        .iter(world)
+        .map(|(e, link, is_obj, is_cam, is_area)| (e, link.0, is_obj, is_cam, is_area))
+        .collect();
+    for (e, route_e, is_obj, is_cam, is_area) in linked {
+        if world.get::<RouteSettings>(route_e).is_some() {
+            continue;
+        }
+        let mode = match (is_obj, is_cam, is_area) {
+            (true, _, _) => KmpEditMode::Objects,
+            (_, true, _) => KmpEditMode::Cameras,
+            (_, _, true) => KmpEditMode::Areas,
+            _ => continue,
+        };
+        issues.error(mode, "Linked to a route which doesn't exist", vec![e]);
+    }
+
+    // moving roads need a route to move along
+    let mut q_areas = world.query::<(Entity, &AreaPoint, Has<RouteLink>)>();
+    let no_route: Vec<_> = q_areas
+        .iter(world)
+        .filter(|(_, area, has_route)| matches!(area.kind, AreaKind::MovingRoad) && !has_route)
+        .map(|x| x.0)
+        .collect();
+    if !no_route.is_empty() {
+        issues.warning(
+            KmpEditMode::Areas,
+            format!("{} moving road areas aren't linked to a route", no_route.len()),
+            no_route,
+        );
+    }
+}
+
+fn check_area_indexes(world: &mut World, issues: &mut IssueList) {
+    let num_cameras = points::<KmpCamera>(world).len();
+    let num_enemy_groups = world
+        .get_resource::<EntityPathGroups<EnemyPathPoint>>()
+        .map(|x| x.len())
+        .unwrap_or_default();
+    let mut q_areas = world.query::<(Entity, &AreaPoint)>();
+    for (e, area) in q_areas.iter(world) {
+        match area.kind {
+            AreaKind::Camera { cam_index } if cam_index as usize >= num_cameras => issues.error(
+                KmpEditMode::Areas,
+                format!("Camera area uses camera {cam_index}, but there are only {num_cameras} cameras"),
+                vec![e],
+            ),
+            AreaKind::ForceRecalc { enemy_path_id } if enemy_path_id as usize >= num_enemy_groups => issues.error(
+                KmpEditMode::Areas,
+                format!(
+                    "Force recalc area uses enemy path group {enemy_path_id}, but there are only {num_enemy_groups} groups"
+                ),
+                vec![e],
+            ),
+            _ => {}
+        }
+    }
+}
+
+// other tools can store data in the section headers, so warn about any of it that won't be saved as it was
+fn check_header_values(world: &mut World, issues: &mut IssueList) {
+    let (kmp, _) = build_kmp(world);
+    for change in header_value_changes(world, &kmp) {
+        issues.warning(change.mode(), change.message(), Vec::new());
+    }
+}
+
+fn validate_kmp(world: &mut World) {
+    world.resource_mut::<Events<ValidateKmp>>().clear();
+    let Some(track_type) = world.get_resource::<TrackInfo>().map(|x| x.track_type.clone()) else {
+        return;
+    };
+    let mut issues = IssueList(Vec::new());
+
+    for warning in start_point_warnings(world) {
+        issues.warning(KmpEditMode::StartPoints, warning, points::<StartPoint>(world));
+    }
+    if track_type == TrackType::Race {
+        check_checkpoints(world, &mut issues);
+        if points::<RespawnPoint>(world).is_empty() {
+            issues.error(KmpEditMode::RespawnPoints, "There are no respawn points", Vec::new());
+        }
+        if points::<EnemyPathPoint>(world).is_empty() {
+            issues.warning(KmpEditMode::EnemyPaths, "There are no enemy paths", Vec::new());
+        }
+        if points::<ItemPathPoint>(world).is_empty() {
+            issues.warning(KmpEditMode::ItemPaths, "There are no item paths", Vec::new());
+        }
+    } else if points::<BattleFinishPoint>(world).is_empty() {
+        issues.warning(
+            KmpEditMode::BattleFinishPoints,
+            "There are no battle finish points",
+            Vec::new(),
+        );
+    }
+    check_unused_section::<Checkpoint>(world, &mut issues, &track_type);
+    check_unused_section::<BattleFinishPoint>(world, &mut issues, &track_type);
+    check_path_groups::<EnemyPathPoint>(world, &mut issues, "Enemy path");
+    check_path_groups::<ItemPathPoint>(world, &mut issues, "Item path");
+    check_path_groups::<Checkpoint>(world, &mut issues, "Checkpoint");
+    check_route_links(world, &mut issues);
+    check_area_indexes(world, &mut issues);
+    check_header_values(world, &mut issues);
+    issues.0.extend(custom_rule_issues(world));
+
+    let mut issues = issues.0;
+    issues.sort_by_key(|x| x.severity);
+    *world.resource_mut::<KmpIssues>() = KmpIssues { checked: true, issues };
+}
+
+// the issues belong to the file they were found in
+fn clear_issues(mut issues: ResMut<KmpIssues>) {
+    *issues = KmpIssues::default();
+}
+
+#[test]
+fn test_path_group_limits() {
+    use super::path::EntityPathGroup;
+
+    let group = |len| EntityPathGroup {
+        path: vec![Entity::PLACEHOLDER; len],
+        ..default()
+    };
+    let mut world = World::new();
+    let mut check = |groups: Vec<EntityPathGroup>| {
+        world.insert_resource(EntityPathGroups::<EnemyPathPoint>::new(groups));
+        let mut issues = IssueList(Vec::new());
+        check_path_groups::<EnemyPathPoint>(&mut world, &mut issues, "Enemy path");
+        issues.0
+    };
+
+    // as many points as can be indexed, with no group too long
+    assert!(check(vec![group(MAX_POINT_INDEX), group(1)]).is_empty());
+    // a group too long, and one point too many to index
+    let issues = check(vec![group(MAX_POINT_INDEX + 1), group(1)]);
+    assert_eq!(issues.len(), 2);
+    assert!(issues.iter().all(|x| x.severity == IssueSeverity::Error));
+    assert_eq!(issues[0].entities.len(), MAX_POINT_INDEX + 1);
+}
+
+#[test]
+fn test_lap_count_checkpoint_count() {
+    let mut world = World::new();
+    let respawn = world.spawn(RespawnPoint::default()).id();
+    let spawn_checkpoint =
+        |world: &mut World, kind| world.spawn((Checkpoint { kind }, CheckpointRespawnLink(respawn))).id();
+    let check = |world: &mut World| {
+        let mut issues = IssueList(Vec::new());
+        check_checkpoints(world, &mut issues);
+        issues.0
+    };
+
+    spawn_checkpoint(&mut world, CheckpointKind::Normal);
+    let issues = check(&mut world);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].severity, IssueSeverity::Error);
+
+    let first = spawn_checkpoint(&mut world, CheckpointKind::LapCount);
+    assert!(check(&mut world).is_empty());
+
+    let second = spawn_checkpoint(&mut world, CheckpointKind::LapCount);
+    let issues = check(&mut world);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].severity, IssueSeverity::Warning);
+    assert!(issues[0].entities.contains(&first) && issues[0].entities.contains(&second));
+}
+
+#[test]
+fn test_area_indexes() {
+    use super::path::EntityPathGroup;
+
+    let mut world = World::new();
+    for _ in 0..2 {
+        world.spawn((KmpCamera::default(), KmpSelectablePoint));
+    }
+    world.insert_resource(EntityPathGroups::<EnemyPathPoint>::new(
+        vec![EntityPathGroup::default()],
+    ));
+    let mut spawn_area = |kind| world.spawn(AreaPoint { kind, ..default() }).id();
+    spawn_area(AreaKind::Camera { cam_index: 1 });
+    let bad_camera = spawn_area(AreaKind::Camera { cam_index: 2 });
+    spawn_area(AreaKind::ForceRecalc { enemy_path_id: 0 });
+    let bad_recalc = spawn_area(AreaKind::ForceRecalc { enemy_path_id: 1 });
+
+    let mut issues = IssueList(Vec::new());
+    check_area_indexes(&mut world, &mut issues);
+    let mut flagged: Vec<_> = issues.0.iter().flat_map(|x| x.entities.clone()).collect();
+    flagged.sort();
+    let mut expected = vec![bad_camera, bad_recalc];
+    expected.sort();
+    assert_eq!(flagged, expected);
+    assert!(issues.0.iter().all(|x| x.severity == IssueSeverity::Error));
+}
+
+#[test]
+fn test_errors_sorted_first() {
+    let mut issues = IssueList(Vec::new());
+    issues.warning(KmpEditMode::Areas, "warning", Vec::new());
+    issues.error(KmpEditMode::Areas, "error", Vec::new());
+    let mut issues = issues.0;
+    issues.sort_by_key(|x| x.severity);
+    assert_eq!(issues[0].severity, IssueSeverity::Error);
+}
@@ -0,0 +1,143 @@
+use super::components::{EnemyPathPoint, KmpCamera, KmpSelectablePoint, Object, RespawnPoint};
+use crate::{ui::settings::AppSettings, util::world_to_gizmo_cam, viewer::camera::Gizmo2dCam};
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_vector_shapes::{painter::ShapePainter, shapes::DiscPainter};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+pub fn value_colors_plugin(app: &mut App) {
+    app.init_resource::<ValueColorLegend>()
+        .add_systems(PostUpdate, draw_value_colors.after(TransformSystem::TransformPropagate));
+}
+
+/// A field which points can be coloured by, so that outliers stand out in the viewport
+#[derive(Serialize, Deserialize, Display, EnumIter, Default, Clone, Copy, PartialEq, Debug)]
+pub enum ColorByField {
+    #[default]
+    Off,
+    #[strum(serialize = "Enemy Path Leniency")]
+    Leniency,
+    #[strum(serialize = "Object ID")]
+    ObjectId,
+    #[strum(serialize = "Respawn Sound Trigger")]
+    SoundTrigger,
+    #[strum(serialize = "Camera Time")]
+    CameraTime,
+}
+impl ColorByField {
+    /// Object IDs aren't really a scale, so each ID just gets its own colour instead of being mapped between min and max
+    pub fn is_categorical(self) -> bool {
+        self == Self::ObjectId
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColorByValueSettings {
+    pub field: ColorByField,
+    /// If enabled, the lowest and highest values of the field are used as the ends of the colour scale
+    pub auto_range: bool,
+    pub min: f32,
+    pub max: f32,
+}
+impl Default for ColorByValueSettings {
+    fn default() -> Self {
+        Self {
+            field: ColorByField::Off,
+            auto_range: true,
+            min: 0.,
+            max: 1.,
+        }
+    }
+}
+
+/// The range currently used for colouring the points, so that the viewport can show a legend
+#[derive(Resource, Default)]
+pub struct ValueColorLegend {
+    pub field: ColorByField,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Blue for the lowest values through to red for the highest
+pub fn value_color(value: f32, min: f32, max: f32) -> Color {
+    let t = if max > min {
+        ((value - min) / (max - min)).clamp(0., 1.)
+    } else {
+        0.5
+    };
+    Color::hsl((1. - t) * 240., 1., 0.5)
+}
+
+pub fn category_color(value: f32) -> Color {
+    // golden angle, so that consecutive IDs get very different colours
+    Color::hsl((value * 137.508) % 360., 0.9, 0.55)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_value_colors(
+    settings: Res<AppSettings>,
+    mut legend: ResMut<ValueColorLegend>,
+    q_enemy: Query<(&GlobalTransform, &ViewVisibility, &EnemyPathPoint), With<KmpSelectablePoint>>,
+    q_object: Query<(&GlobalTransform, &ViewVisibility, &Object), With<KmpSelectablePoint>>,
+    q_respawn: Query<(&GlobalTransform, &ViewVisibility, &RespawnPoint), With<KmpSelectablePoint>>,
+    q_camera: Query<(&GlobalTransform, &ViewVisibility, &KmpCamera), With<KmpSelectablePoint>>,
+    q_cam: Query<(&Camera, &GlobalTransform), Without<Gizmo2dCam>>,
+    q_gizmo_cam: Query<(&Camera, &GlobalTransform), With<Gizmo2dCam>>,
+    mut painter: ShapePainter,
+) {
+    let color_by = &settings.kmp_model.color_by_value;
+    let points: Vec<(Vec3, bool, f32)> = match color_by.field {
+        ColorByField::Off => Vec::new(),
+        ColorByField::Leniency => q_enemy
+            .iter()
+            .map(|(gt, v, x)| (gt.translation(), v.get(), x.leniency))
+            .collect(),
+        ColorByField::ObjectId => q_object
+            .iter()
+            .map(|(gt, v, x)| (gt.translation(), v.get(), x.object_id as f32))
+            .collect(),
+        ColorByField::SoundTrigger => q_respawn
+            .iter()
+            .map(|(gt, v, x)| (gt.translation(), v.get(), x.sound_trigger as f32))
+            .collect(),
+        ColorByField::CameraTime => q_camera
+            .iter()
+            .map(|(gt, v, x)| (gt.translation(), v.get(), x.time))
+            .collect(),
+    };
+
+    // the range is taken from every point in the section, not just the visible ones, so it doesn't change with the camera
+    let (min, max) = if color_by.auto_range {
+        points
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), x| (min.min(x.2), max.max(x.2)))
+    } else {
+        (color_by.min, color_by.max)
+    };
+    legend.field = color_by.field;
+    (legend.min, legend.max) = if points.is_empty() { (0., 0.) } else { (min, max) };
+
+    if points.is_empty() {
+        return;
+    }
+    let Some(cam) = q_cam.iter().find(|cam| cam.0.is_active) else {
+        return;
+    };
+    let gizmo_cam = q_gizmo_cam.single();
+
+    for (pos, visible, value) in points {
+        if !visible {
+            continue;
+        }
+        let Some(pos) = world_to_gizmo_cam(cam, gizmo_cam, pos) else {
+            continue;
+        };
+        painter.color = if color_by.field.is_categorical() {
+            category_color(value)
+        } else {
+            value_color(value, min, max)
+        };
+        painter.transform.translation = pos;
+        painter.circle(7. * settings.kmp_model.point_scale);
+    }
+}
@@ -0,0 +1,123 @@
+use super::{
+    csv::{read_section_csv, section_supports_csv, write_section_csv},
+    sections::KmpEditMode,
+};
+use crate::ui::{
+    errors::show_errors,
+    file_dialog::{DialogType, FileDialogResult},
+};
+use anyhow::{bail, Context};
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use calamine::{open_workbook_auto, Reader};
+use rust_xlsxwriter::{Format, Workbook};
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+pub fn workbook_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        export_import_workbook
+            .pipe(show_errors)
+            .run_if(on_event::<FileDialogResult>()),
+    );
+}
+
+/// Name of the file all the sections are exported to by default
+pub const WORKBOOK_FILE_NAME: &str = "kmp.xlsx";
+
+// each sheet is named after its section, which is also how sheets are matched back up to sections when importing
+fn sheet_name(mode: KmpEditMode) -> String {
+    mode.to_string()
+}
+
+/// Export every section that can be stored as CSV into one .xlsx workbook, with a sheet per section. The sheets have
+/// the same columns as the CSV files, so the two can be used interchangeably.
+pub fn export_workbook(world: &mut World, path: &Path) -> anyhow::Result<()> {
+    let mut workbook = Workbook::new();
+    let header = Format::new().set_bold();
+    for mode in KmpEditMode::iter().filter(|x| section_supports_csv(*x)) {
+        let mut data = Vec::new();
+        write_section_csv(world, mode, &mut data).with_context(|| format!("could not export {mode}"))?;
+
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(sheet_name(mode))?;
+        sheet.set_freeze_panes(1, 0)?;
+        let mut rdr = csv::ReaderBuilder::new().has_headers(false).from_reader(&data[..]);
+        for (row, record) in rdr.records().enumerate() {
+            let record = record.context("could not read exported CSV")?;
+            for (col, field) in record.iter().enumerate() {
+                let (row, col) = (row as u32, col as u16);
+                // values are written as numbers and booleans where they can be, so that they can be sorted and
+                // calculated with in the spreadsheet
+                if row == 0 {
+                    sheet.write_string_with_format(row, col, field, &header)?;
+                } else if let Ok(x) = field.parse::<f64>() {
+                    sheet.write_number(row, col, x)?;
+                } else if let Ok(x) = field.parse::<bool>() {
+                    sheet.write_boolean(row, col, x)?;
+                } else {
+                    sheet.write_string(row, col, field)?;
+                }
+            }
+        }
+    }
+    workbook.save(path).context("could not write workbook")?;
+    Ok(())
+}
+
+/// Replace each section which has a sheet in the workbook with the rows of that sheet. Sections without a sheet are
+/// left as they are. Reads .xlsx as well as .ods, so the workbook can be edited in whichever spreadsheet program.
+pub fn import_workbook(world: &mut World, path: &Path) -> anyhow::Result<()> {
+    let mut workbook = open_workbook_auto(path).context("could not open workbook")?;
+    let sheet_names = workbook.sheet_names();
+
+    // every sheet is read before anything is replaced, so that a workbook that can't be read doesn't lose anything
+    let mut sheets = Vec::new();
+    for mode in KmpEditMode::iter().filter(|x| section_supports_csv(*x)) {
+        let name = sheet_name(mode);
+        if !sheet_names.contains(&name) {
+            continue;
+        }
+        let range = workbook
+            .worksheet_range(&name)
+            .with_context(|| format!("could not read the {name} sheet"))?;
+        let mut wtr = csv::Writer::from_writer(Vec::new());
+        for row in range.rows() {
+            let fields: Vec<_> = row.iter().map(|cell| cell.to_string()).collect();
+            // rows which have been cleared out rather than deleted are skipped
+            if fields.iter().all(|x| x.is_empty()) {
+                continue;
+            }
+            wtr.write_record(&fields).context("could not convert sheet")?;
+        }
+        let data = wtr.into_inner().context("could not convert sheet")?;
+        sheets.push((mode, data));
+    }
+    if sheets.is_empty() {
+        bail!("the workbook has no sheets named after a section");
+    }
+    for (mode, data) in sheets {
+        read_section_csv(world, mode, &data[..]).with_context(|| format!("could not import {mode}"))?;
+    }
+    Ok(())
+}
+
+fn export_import_workbook(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<FileDialogResult>>,
+) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let results: Vec<_> = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ExportWorkbook | DialogType::ImportWorkbook))
+        .map(|x| (x.dialog_type, x.path.clone()))
+        .collect();
+    for (dialog_type, path) in results {
+        match dialog_type {
+            DialogType::ExportWorkbook => export_workbook(world, &path)?,
+            DialogType::ImportWorkbook => import_workbook(world, &path)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -1,24 +1,31 @@
 use bevy::app::App;
 
 use self::{
-    camera::camera_plugin, edit::edit_plugin, grid::grid_plugin, kcl_model::kcl_plugin, kmp::kmp_plugin,
-    normalize::normalize_plugin,
+    camera::camera_plugin, edit::edit_plugin, grid::grid_plugin, kcl_heatmap::kcl_heatmap_plugin,
+    kcl_model::kcl_plugin, kcl_reachability::kcl_reachability_plugin, kmp::kmp_plugin, normalize::normalize_plugin,
+    snapshot::snapshot_plugin,
 };
 
 pub mod camera;
 pub mod edit;
 mod grid;
+pub mod kcl_heatmap;
 pub mod kcl_model;
+pub mod kcl_reachability;
 pub mod kmp;
 mod normalize;
+pub mod snapshot;
 
 pub fn viewer_plugin(app: &mut App) {
     app.add_plugins((
         camera_plugin,
         kmp_plugin,
         kcl_plugin,
+        kcl_heatmap_plugin,
+        kcl_reachability_plugin,
         normalize_plugin,
         grid_plugin,
         edit_plugin,
+        snapshot_plugin,
     ));
 }
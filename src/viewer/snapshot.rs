@@ -0,0 +1,290 @@
+use super::camera::TopDownCam;
+use crate::ui::{
+    errors::show_errors,
+    file_dialog::{DialogType, FileDialogResult},
+};
+use anyhow::{bail, Context};
+use bevy::{
+    ecs::event::ManualEventReader,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        graph::CameraDriverLabel,
+        render_asset::RenderAssets,
+        render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+            ImageDataLayout, Maintain, MapMode, TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::GpuImage,
+        Extract, Render, RenderApp, RenderSet,
+    },
+};
+use std::{
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+};
+
+pub fn snapshot_plugin(app: &mut App) {
+    let (sender, receiver) = mpsc::channel();
+    app.insert_resource(SnapshotReceiver(Mutex::new(receiver))).add_systems(
+        Update,
+        (start_snapshot.pipe(show_errors), finish_snapshot.pipe(show_errors)).chain(),
+    );
+
+    let render_app = app.sub_app_mut(RenderApp);
+    let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+    graph.add_node(SnapshotCopyLabel, SnapshotCopyNode);
+    graph.add_node_edge(CameraDriverLabel, SnapshotCopyLabel);
+    render_app
+        .insert_resource(SnapshotSender(sender))
+        .init_resource::<SnapshotCopiers>()
+        .add_systems(ExtractSchedule, extract_snapshot_copiers)
+        .add_systems(
+            Render,
+            read_snapshot_buffers
+                .after(RenderSet::Render)
+                .before(RenderSet::Cleanup),
+        );
+}
+
+// how many frames the snapshot camera renders for before the image is saved, so that everything it sees has had
+// time to be prepared for rendering
+const SNAPSHOT_WAIT_FRAMES: u32 = 4;
+pub const MAX_SNAPSHOT_SIZE: u32 = 8192;
+
+/// Shows the window for exporting a snapshot of the top down view to a PNG, for minimap mock-ups and documentation.
+/// The snapshot is centred on the top down camera and covers the same height of the track as the viewport does, with
+/// the KCL and whichever KMP sections are currently shown.
+#[derive(Resource)]
+pub struct TopDownSnapshot {
+    pub width: u32,
+    pub height: u32,
+}
+impl Default for TopDownSnapshot {
+    fn default() -> Self {
+        Self {
+            width: 2048,
+            height: 2048,
+        }
+    }
+}
+
+// a snapshot which is being rendered, and will be saved once it is ready
+#[derive(Resource)]
+struct PendingSnapshot {
+    path: PathBuf,
+    camera: Entity,
+    width: u32,
+    height: u32,
+    frames: u32,
+}
+
+/// The camera that renders a snapshot offscreen, separately from the viewport
+#[derive(Component)]
+pub struct SnapshotCam;
+
+fn start_snapshot(world: &mut World, mut reader: Local<ManualEventReader<FileDialogResult>>) -> anyhow::Result<()> {
+    let events = world.resource::<Events<FileDialogResult>>();
+    let Some(path) = reader
+        .read(events)
+        .filter(|x| matches!(x.dialog_type, DialogType::ExportSnapshot))
+        .map(|x| x.path.clone())
+        .last()
+    else {
+        return Ok(());
+    };
+    if world.contains_resource::<PendingSnapshot>() {
+        bail!("a snapshot is already being exported");
+    }
+    let (width, height) = {
+        let snapshot = world
+            .get_resource::<TopDownSnapshot>()
+            .context("the snapshot window was closed")?;
+        (
+            snapshot.width.clamp(1, MAX_SNAPSHOT_SIZE),
+            snapshot.height.clamp(1, MAX_SNAPSHOT_SIZE),
+        )
+    };
+    let mut q_topdown = world.query_filtered::<(&Transform, &Projection), With<TopDownCam>>();
+    let (transform, projection) = q_topdown
+        .get_single(world)
+        .context("could not find the top down camera")?;
+    let (transform, projection) = (*transform, projection.clone());
+    let Projection::Orthographic(mut projection) = projection else {
+        bail!("the top down camera is not orthographic");
+    };
+    // cover the same height of the track as the viewport, with the width following the snapshot's aspect ratio
+    projection.scaling_mode = ScalingMode::FixedVertical(projection.area.height());
+    projection.scale = 1.;
+
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        default(),
+    );
+    image.texture_descriptor.usage |=
+        TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+    let handle = world.resource_mut::<Assets<Image>>().add(image);
+    let copier = SnapshotCopier::new(handle.clone(), size, world.resource::<RenderDevice>());
+
+    let camera = world
+        .spawn((
+            Camera3dBundle {
+                camera: Camera {
+                    target: RenderTarget::Image(handle),
+                    ..default()
+                },
+                projection: Projection::Orthographic(projection),
+                transform,
+                ..default()
+            },
+            SnapshotCam,
+            copier,
+        ))
+        .id();
+    world.insert_resource(PendingSnapshot {
+        path,
+        camera,
+        width,
+        height,
+        frames: 0,
+    });
+    Ok(())
+}
+
+fn finish_snapshot(world: &mut World) -> anyhow::Result<()> {
+    let Some(mut pending) = world.get_resource_mut::<PendingSnapshot>() else {
+        return Ok(());
+    };
+    pending.frames += 1;
+    let ready = pending.frames > SNAPSHOT_WAIT_FRAMES;
+    // only the latest image is wanted, earlier ones may not be fully rendered
+    let data = {
+        let receiver = world.resource::<SnapshotReceiver>().0.lock().unwrap();
+        receiver.try_iter().last()
+    };
+    let Some(data) = data.filter(|_| ready) else {
+        return Ok(());
+    };
+    let pending = world.remove_resource::<PendingSnapshot>().unwrap();
+    world.entity_mut(pending.camera).despawn_recursive();
+
+    // rows are padded out to the alignment the GPU copies with, which needs taking off again
+    let row_len = pending.width as usize * 4;
+    let padded_row_len = RenderDevice::align_copy_bytes_per_row(row_len);
+    let pixels: Vec<u8> = data
+        .chunks(padded_row_len)
+        .take(pending.height as usize)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect();
+    let image =
+        image::RgbaImage::from_raw(pending.width, pending.height, pixels).context("the snapshot was the wrong size")?;
+    image.save(&pending.path).context("could not save the snapshot")?;
+    Ok(())
+}
+
+#[derive(Resource)]
+struct SnapshotReceiver(Mutex<Receiver<Vec<u8>>>);
+
+#[derive(Resource)]
+struct SnapshotSender(Sender<Vec<u8>>);
+
+// copies the snapshot camera's image from the GPU into a buffer that can be read back
+#[derive(Component, Clone)]
+struct SnapshotCopier {
+    buffer: Buffer,
+    image: Handle<Image>,
+}
+impl SnapshotCopier {
+    fn new(image: Handle<Image>, size: Extent3d, render_device: &RenderDevice) -> Self {
+        let padded_row_len = RenderDevice::align_copy_bytes_per_row(size.width as usize * 4);
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("snapshot_buffer"),
+            size: padded_row_len as u64 * size.height as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self { buffer, image }
+    }
+}
+
+#[derive(Resource, Default, Deref)]
+struct SnapshotCopiers(Vec<SnapshotCopier>);
+
+fn extract_snapshot_copiers(mut commands: Commands, q_copiers: Extract<Query<&SnapshotCopier>>) {
+    commands.insert_resource(SnapshotCopiers(q_copiers.iter().cloned().collect()));
+}
+
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct SnapshotCopyLabel;
+
+#[derive(Default)]
+struct SnapshotCopyNode;
+impl render_graph::Node for SnapshotCopyNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let copiers = world.resource::<SnapshotCopiers>();
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+        for copier in copiers.iter() {
+            let Some(image) = gpu_images.get(&copier.image) else {
+                continue;
+            };
+            let mut encoder = render_context
+                .render_device()
+                .create_command_encoder(&CommandEncoderDescriptor::default());
+            let padded_row_len = RenderDevice::align_copy_bytes_per_row(image.size.x as usize * 4);
+            encoder.copy_texture_to_buffer(
+                image.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &copier.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_row_len as u32),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: image.size.x,
+                    height: image.size.y,
+                    depth_or_array_layers: 1,
+                },
+            );
+            world
+                .resource::<RenderQueue>()
+                .submit(std::iter::once(encoder.finish()));
+        }
+        Ok(())
+    }
+}
+
+fn read_snapshot_buffers(copiers: Res<SnapshotCopiers>, render_device: Res<RenderDevice>, sender: Res<SnapshotSender>) {
+    for copier in copiers.iter() {
+        let slice = copier.buffer.slice(..);
+        let (mapped_sender, mapped_receiver) = mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = mapped_sender.send(result);
+        });
+        render_device.poll(Maintain::wait()).panic_on_timeout();
+        if let Ok(Ok(())) = mapped_receiver.recv() {
+            let _ = sender.0.send(slice.get_mapped_range().to_vec());
+        }
+        copier.buffer.unmap();
+    }
+}